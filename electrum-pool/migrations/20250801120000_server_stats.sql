@@ -0,0 +1,8 @@
+-- How every Electrum server handled our requests, summed up over all runs
+CREATE TABLE IF NOT EXISTS electrum_servers (
+    url TEXT PRIMARY KEY NOT NULL,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    avg_latency_ms REAL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
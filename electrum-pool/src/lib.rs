@@ -1,9 +1,11 @@
 use backoff::{Error as BackoffError, ExponentialBackoff};
-use bdk_electrum::electrum_client::{Client, ConfigBuilder, ElectrumApi, Error};
+use bdk_electrum::electrum_client::{Client, ConfigBuilder, ElectrumApi, Error, Socks5Config};
 use bdk_electrum::BdkElectrumClient;
 use bitcoin::Transaction;
 use futures::future::join_all;
 use once_cell::sync::OnceCell;
+use stats::{StatsDatabase, StatsTracker};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -11,6 +13,13 @@ use std::time::Instant;
 use tokio::task::spawn_blocking;
 use tracing::{debug, error, instrument, trace, warn};
 
+mod stats;
+
+pub use stats::ServerStats;
+
+/// How often the server stats are written to the database, if they are persisted.
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Round-robin load balancer for Electrum connections.
 ///
 /// The balancer will try each Electrum node until the provided
@@ -28,6 +37,8 @@ where
     next: AtomicUsize,
     config: ElectrumBalancerConfig,
     factory: Arc<dyn ElectrumClientFactory<C> + Send + Sync>,
+    stats: Arc<StatsTracker>,
+    stats_db: Option<StatsDatabase>,
 }
 
 impl<C> ElectrumBalancer<C>
@@ -105,8 +116,11 @@ where
 
     /// Create a new balancer from a list of Electrum URLs with custom configuration.
     /// Clients are initialized lazily on first use.
+    ///
+    /// If the config has a stats database, the URLs are tried in the order of how reliable
+    /// the servers were in previous runs.
     pub async fn new_with_config_and_factory(
-        urls: Vec<String>,
+        mut urls: Vec<String>,
         config: ElectrumBalancerConfig,
         factory: Arc<dyn ElectrumClientFactory<C> + Send + Sync>,
     ) -> Result<Self, Error> {
@@ -114,11 +128,54 @@ where
             return Err(Error::Protocol("No Electrum URLs provided".into()));
         }
 
+        let mut last_invalid_url_error = None;
+        urls.retain(
+            |url| match validate_url(url, config.socks5_proxy.as_deref()) {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!(server_url = url, error = %err, "Ignoring Electrum server");
+                    last_invalid_url_error = Some(err);
+                    false
+                }
+            },
+        );
+        if urls.is_empty() {
+            return Err(last_invalid_url_error
+                .unwrap_or_else(|| Error::Protocol("No Electrum URLs provided".into())));
+        }
+
+        // The stats only help choosing a server, we can do without them
+        let (stats, stats_db) = match &config.stats_db_path {
+            Some(path) => match Self::load_stats(path).await {
+                Ok((stats, stats_db)) => (stats, Some(stats_db)),
+                Err(err) => {
+                    warn!(path = %path.display(), error = ?err, "Failed to load Electrum server stats");
+                    (Vec::new(), None)
+                }
+            },
+            None => (Vec::new(), None),
+        };
+        let stats = Arc::new(StatsTracker::new(stats));
+
+        if stats_db.is_some() {
+            let server_stats = stats.get(&urls);
+            let score = |url: &String| {
+                server_stats
+                    .iter()
+                    .find(|stats| &stats.url == url)
+                    .map(ServerStats::reliability_score)
+                    .unwrap_or_default()
+            };
+            // Stable, so servers without stats keep the configured order
+            urls.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+
         debug!(
             servers = ?urls,
             server_count = urls.len(),
             timeout_seconds = config.request_timeout,
             min_retries = config.min_retries,
+            socks5_proxy = ?config.socks5_proxy,
             "Initializing Electrum load balancer"
         );
 
@@ -126,15 +183,75 @@ where
         let clients: Vec<Arc<OnceCell<Arc<C>>>> =
             urls.iter().map(|_| Arc::new(OnceCell::new())).collect();
 
+        if let Some(stats_db) = stats_db.clone() {
+            Self::spawn_stats_flush(Arc::downgrade(&stats), stats_db);
+        }
+
         Ok(Self {
             urls,
             clients: Arc::new(RwLock::new(clients)),
             next: AtomicUsize::new(0),
             config,
             factory,
+            stats,
+            stats_db,
         })
     }
 
+    async fn load_stats(
+        path: &std::path::Path,
+    ) -> Result<(Vec<ServerStats>, StatsDatabase), sqlx::Error> {
+        let stats_db = StatsDatabase::open(path).await?;
+        let stats = stats_db.load().await?;
+
+        Ok((stats, stats_db))
+    }
+
+    /// Periodically write the stats to the database until the last balancer is dropped.
+    fn spawn_stats_flush(stats: std::sync::Weak<StatsTracker>, stats_db: StatsDatabase) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STATS_FLUSH_INTERVAL);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                let Some(stats) = stats.upgrade() else {
+                    break;
+                };
+
+                if let Err(err) = stats_db.save(&stats.all()).await {
+                    warn!(error = ?err, "Failed to save Electrum server stats");
+                }
+            }
+        });
+    }
+
+    /// How the servers of this balancer handled our requests, in the order they are tried in.
+    pub fn server_stats(&self) -> Vec<ServerStats> {
+        self.stats.get(&self.urls)
+    }
+
+    /// Write the server stats to the database right away instead of on the next periodic
+    /// flush. Does nothing if the stats aren't persisted.
+    pub async fn flush_stats(&self) -> Result<(), sqlx::Error> {
+        match &self.stats_db {
+            Some(stats_db) => stats_db.save(&self.stats.all()).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Remember how the server at `idx` handled a request.
+    fn record_outcome<T>(&self, idx: usize, result: &Result<T, Error>, latency: Duration) {
+        let url = &self.urls[idx];
+        match result {
+            Ok(_) => self.stats.record_success(url, latency),
+            // The server answered, it just didn't like the request
+            Err(Error::Protocol(_)) => {}
+            Err(_) => self.stats.record_failure(url),
+        }
+    }
+
     /// Get the number of URLs (potential clients)
     pub fn client_count(&self) -> usize {
         self.urls.len()
@@ -267,6 +384,7 @@ where
                     "Client initialization failed, switching to next client"
                 );
 
+                self.stats.record_failure(&self.urls[idx]);
                 errors.push(err);
 
                 BackoffError::transient(())
@@ -274,7 +392,10 @@ where
 
             // Execute the request synchronously
             let start = Instant::now();
-            match f(&client) {
+            let result = f(&client);
+            self.record_outcome(idx, &result, start.elapsed());
+
+            match result {
                 Ok(res) => {
                     trace!(
                         server_url = self.urls[idx],
@@ -369,15 +490,24 @@ where
 
                     tokio::spawn(async move {
                         match balancer.get_or_init_client_async(idx).await {
-                            Ok(client) => tokio::task::spawn_blocking(move || f(&client))
-                                .await
-                                .map_err(|e| {
-                                    Error::IOError(std::io::Error::new(
-                                        std::io::ErrorKind::Other,
-                                        e.to_string(),
-                                    ))
-                                })?,
-                            Err(e) => Err(e),
+                            Ok(client) => {
+                                let start = Instant::now();
+                                let result = tokio::task::spawn_blocking(move || f(&client))
+                                    .await
+                                    .unwrap_or_else(|e| {
+                                        Err(Error::IOError(std::io::Error::new(
+                                            std::io::ErrorKind::Other,
+                                            e.to_string(),
+                                        )))
+                                    });
+                                balancer.record_outcome(idx, &result, start.elapsed());
+
+                                result
+                            }
+                            Err(e) => {
+                                balancer.stats.record_failure(&balancer.urls[idx]);
+                                Err(e)
+                            }
                         }
                     })
                 })
@@ -446,7 +576,8 @@ where
     ///
     /// The method returns a list of results in the same order as the
     /// configured nodes. Errors for individual nodes do not abort the
-    /// others.
+    /// others. The broadcast counts as successful if any node accepted the
+    /// transaction, the nodes that rejected it anyway are logged.
     #[instrument(level = "debug", skip(self, tx), fields(txid = %tx.compute_txid(), total_clients = self.client_count()))]
     pub async fn broadcast_all(
         &self,
@@ -477,6 +608,19 @@ where
                 duration_ms = start_time.elapsed().as_millis(),
                 "Transaction broadcast completed successfully"
             );
+
+            // A node rejecting what others accept is out of sync or has a stricter mempool
+            // policy, either way it won't relay the transaction for us
+            for (url, result) in self.urls.iter().zip(&results) {
+                if let Err(error) = result {
+                    warn!(
+                        txid = %txid,
+                        url = %url,
+                        error = %error,
+                        "Electrum server rejected a transaction other servers accepted"
+                    );
+                }
+            }
         } else {
             error!(
                 txid = %txid,
@@ -535,6 +679,8 @@ where
             next: AtomicUsize::new(self.next.load(Ordering::SeqCst)),
             config: self.config.clone(),
             factory: self.factory.clone(),
+            stats: self.stats.clone(),
+            stats_db: self.stats_db.clone(),
         }
     }
 }
@@ -567,6 +713,12 @@ pub struct ElectrumBalancerConfig {
     pub request_timeout: u8,
     /// Minimum number of retry attempts across all nodes
     pub min_retries: usize,
+    /// Sqlite database to keep the server stats in between runs. Without it the stats are only
+    /// kept in memory.
+    pub stats_db_path: Option<PathBuf>,
+    /// Connect to the servers through this SOCKS5 proxy, e.g. Tor at `127.0.0.1:9050`.
+    /// Required for `.onion` servers.
+    pub socks5_proxy: Option<String>,
 }
 
 impl Default for ElectrumBalancerConfig {
@@ -574,6 +726,8 @@ impl Default for ElectrumBalancerConfig {
         Self {
             request_timeout: 15,
             min_retries: 15,
+            stats_db_path: None,
+            socks5_proxy: None,
         }
     }
 }
@@ -595,6 +749,7 @@ impl ElectrumClientFactory<BdkElectrumClient<Client>> for BdkElectrumClientFacto
         let client_config = ConfigBuilder::new()
             .timeout(Some(config.request_timeout))
             .retry(0)
+            .socks5(config.socks5_proxy.as_ref().map(Socks5Config::new))
             .build();
 
         let client = Client::from_config(url, client_config).map_err(|e| {
@@ -647,6 +802,41 @@ impl ElectrumBalancer<BdkElectrumClient<Client>> {
 /// Type alias for the default Electrum balancer using BdkElectrumClient
 pub type DefaultElectrumBalancer = ElectrumBalancer<BdkElectrumClient<Client>>;
 
+/// Check that an Electrum URL can be reached with the given proxy. `.onion` servers can only
+/// be reached through a SOCKS5 proxy like Tor and have to be v3 onion addresses.
+pub fn validate_url(url: &str, socks5_proxy: Option<&str>) -> Result<(), Error> {
+    let invalid =
+        |reason: &str| Error::Protocol(format!("Invalid Electrum URL {}: {}", url, reason).into());
+
+    // Like the Electrum client, accept the URL with or without a scheme
+    let address = url
+        .strip_prefix("tcp://")
+        .or_else(|| url.strip_prefix("ssl://"))
+        .unwrap_or(url);
+    let host = address
+        .rsplit_once(':')
+        .map_or(address, |(host, _port)| host);
+
+    if let Some(onion_service) = host.strip_suffix(".onion") {
+        if socks5_proxy.is_none() {
+            return Err(invalid(
+                ".onion servers can only be reached through a SOCKS5 proxy",
+            ));
+        }
+
+        // v3 onion addresses are the base32 encoded public key, checksum and version
+        let is_v3 = onion_service.len() == 56
+            && onion_service
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c));
+        if !is_v3 {
+            return Err(invalid("not a v3 onion address"));
+        }
+    }
+
+    Ok(())
+}
+
 /// Error type that contains multiple Electrum errors from different nodes.
 ///
 /// This allows the caller to inspect all individual failures while still
@@ -973,6 +1163,8 @@ mod tests {
         let config = ElectrumBalancerConfig {
             request_timeout: 5,
             min_retries: 0,
+            stats_db_path: None,
+            socks5_proxy: None,
         };
 
         let balancer = ElectrumBalancer::new_with_config_and_factory(urls, config, factory.clone())
@@ -1046,6 +1238,8 @@ mod tests {
         let config = ElectrumBalancerConfig {
             request_timeout: 5,
             min_retries: 1,
+            stats_db_path: None,
+            socks5_proxy: None,
         };
 
         let balancer = ElectrumBalancer::new_with_config_and_factory(urls, config, factory.clone())
@@ -1182,12 +1376,115 @@ mod tests {
         assert_eq!(factory.get_client(1).unwrap().call_count(), 1);
     }
 
+    #[tokio::test]
+    async fn test_broadcast_all_with_divergent_rejection() {
+        let urls = vec![
+            "tcp://localhost:50001".to_string(),
+            "tcp://localhost:50002".to_string(),
+        ];
+
+        let factory = Arc::new(MockElectrumClientFactory::new());
+        factory.add_client(
+            MockElectrumClient::new(urls[0].clone()).with_failure(MockErrorType::NonRetryable),
+        );
+        factory.add_client(MockElectrumClient::new(urls[1].clone()));
+
+        let balancer = ElectrumBalancer::new_with_factory(urls, factory.clone())
+            .await
+            .unwrap();
+
+        let results = balancer
+            .broadcast_all(create_dummy_transaction())
+            .await
+            .unwrap();
+
+        // One server accepting the transaction is enough
+        assert!(results[0].is_err());
+        assert!(results[1].is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_persisted_stats_reorder_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let urls = vec![
+            "tcp://localhost:50001".to_string(),
+            "tcp://localhost:50002".to_string(),
+        ];
+        let config = ElectrumBalancerConfig {
+            request_timeout: 5,
+            min_retries: 0,
+            stats_db_path: Some(dir.path().join("electrum.sqlite")),
+            socks5_proxy: None,
+        };
+
+        let factory = Arc::new(MockElectrumClientFactory::new());
+        factory.add_client(
+            MockElectrumClient::new(urls[0].clone()).with_failure(MockErrorType::IOError),
+        );
+        factory.add_client(MockElectrumClient::new(urls[1].clone()));
+
+        let balancer = ElectrumBalancer::new_with_config_and_factory(
+            urls.clone(),
+            config.clone(),
+            factory.clone(),
+        )
+        .await
+        .unwrap();
+        balancer
+            .call("test", |client| {
+                client.transaction_broadcast(&create_dummy_transaction())
+            })
+            .await
+            .unwrap();
+
+        let stats = balancer.server_stats();
+        assert_eq!(stats[0].failure_count, 1);
+        assert_eq!(stats[1].success_count, 1);
+        balancer.flush_stats().await.unwrap();
+
+        // The working server is tried first after a restart
+        let balancer = ElectrumBalancer::new_with_config_and_factory(urls.clone(), config, factory)
+            .await
+            .unwrap();
+        assert_eq!(balancer.urls(), &vec![urls[1].clone(), urls[0].clone()]);
+        assert_eq!(balancer.server_stats()[0].success_count, 1);
+    }
+
+    #[test]
+    fn test_validate_url() {
+        let onion = "tcp://explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion:110";
+        let proxy = Some("127.0.0.1:9050");
+
+        assert!(validate_url("ssl://blockstream.info:700", None).is_ok());
+        assert!(validate_url(onion, proxy).is_ok());
+        assert!(validate_url(onion, None).is_err());
+        assert!(validate_url("tcp://shortonion.onion:50001", proxy).is_err());
+        assert!(validate_url("electrum.blockstream.info:50001", None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_onion_urls_are_ignored_without_proxy() {
+        let onion = "tcp://explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion:110";
+        let urls = vec![onion.to_string(), "tcp://localhost:50001".to_string()];
+        let factory = Arc::new(MockElectrumClientFactory::new());
+
+        let balancer = ElectrumBalancer::new_with_factory(urls, factory.clone())
+            .await
+            .unwrap();
+        assert_eq!(balancer.urls(), &vec!["tcp://localhost:50001".to_string()]);
+
+        let result = ElectrumBalancer::new_with_factory(vec![onion.to_string()], factory).await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn test_config_and_urls_accessors() {
         let urls = vec!["tcp://localhost:50001".to_string()];
         let config = ElectrumBalancerConfig {
             request_timeout: 15,
             min_retries: 7,
+            stats_db_path: None,
+            socks5_proxy: None,
         };
 
         let factory = Arc::new(MockElectrumClientFactory::new());
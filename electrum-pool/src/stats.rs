@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use sqlx::SqlitePool;
+
+/// How an Electrum server handled our requests.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerStats {
+    pub url: String,
+    pub success_count: u64,
+    pub failure_count: u64,
+    /// Average latency of the successful requests.
+    pub avg_latency_ms: Option<f64>,
+}
+
+impl ServerStats {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            success_count: 0,
+            failure_count: 0,
+            avg_latency_ms: None,
+        }
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        let total = self.success_count + self.failure_count;
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.success_count as f64 / total as f64
+    }
+
+    /// Scores the server the same way the Monero RPC pool scores nodes: 80% success rate,
+    /// weighted by the number of requests, and 20% latency.
+    pub fn reliability_score(&self) -> f64 {
+        let total_requests = self.success_count + self.failure_count;
+        let request_weight = (total_requests as f64).min(200.0) / 200.0;
+        let mut score = self.success_rate() * request_weight;
+
+        if let Some(avg_latency) = self.avg_latency_ms {
+            let latency_factor = 1.0 - (avg_latency.min(2000.0) / 2000.0);
+            score = score * 0.8 + latency_factor * 0.2;
+        }
+
+        score
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        let avg_latency_ms = match self.avg_latency_ms {
+            Some(avg) => {
+                (avg * self.success_count as f64 + latency_ms) / (self.success_count + 1) as f64
+            }
+            None => latency_ms,
+        };
+
+        self.success_count += 1;
+        self.avg_latency_ms = Some(avg_latency_ms);
+    }
+
+    fn record_failure(&mut self) {
+        self.failure_count += 1;
+    }
+}
+
+/// The stats of every server the balancer sent requests to, kept in memory.
+#[derive(Debug, Default)]
+pub(crate) struct StatsTracker {
+    servers: Mutex<HashMap<String, ServerStats>>,
+}
+
+impl StatsTracker {
+    pub(crate) fn new(stats: Vec<ServerStats>) -> Self {
+        Self {
+            servers: Mutex::new(
+                stats
+                    .into_iter()
+                    .map(|stats| (stats.url.clone(), stats))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub(crate) fn record_success(&self, url: &str, latency: Duration) {
+        self.update(url, |stats| stats.record_success(latency));
+    }
+
+    pub(crate) fn record_failure(&self, url: &str) {
+        self.update(url, ServerStats::record_failure);
+    }
+
+    fn update(&self, url: &str, update: impl FnOnce(&mut ServerStats)) {
+        let mut servers = self.servers.lock().expect("lock not to be poisoned");
+        let stats = servers
+            .entry(url.to_string())
+            .or_insert_with(|| ServerStats::new(url));
+        update(stats);
+    }
+
+    /// The stats of the given servers, in the same order. Servers we never sent a request to
+    /// have empty stats.
+    pub(crate) fn get(&self, urls: &[String]) -> Vec<ServerStats> {
+        let servers = self.servers.lock().expect("lock not to be poisoned");
+        urls.iter()
+            .map(|url| {
+                servers
+                    .get(url)
+                    .cloned()
+                    .unwrap_or_else(|| ServerStats::new(url))
+            })
+            .collect()
+    }
+
+    pub(crate) fn all(&self) -> Vec<ServerStats> {
+        let servers = self.servers.lock().expect("lock not to be poisoned");
+        servers.values().cloned().collect()
+    }
+}
+
+/// Keeps the server stats between restarts, so the balancer starts with the servers that
+/// worked best last time.
+#[derive(Clone)]
+pub(crate) struct StatsDatabase {
+    pool: SqlitePool,
+}
+
+impl StatsDatabase {
+    pub(crate) async fn open(path: &Path) -> Result<Self, sqlx::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let database_url = format!("sqlite:{}?mode=rwc", path.display());
+        let options =
+            SqliteConnectOptions::from_str(&database_url)?.journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePool::connect_with(options).await?;
+
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    pub(crate) async fn load(&self) -> Result<Vec<ServerStats>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT url, success_count, failure_count, avg_latency_ms
+            FROM electrum_servers
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ServerStats {
+                url: row.url,
+                success_count: row.success_count.try_into().unwrap_or_default(),
+                failure_count: row.failure_count.try_into().unwrap_or_default(),
+                avg_latency_ms: row.avg_latency_ms,
+            })
+            .collect())
+    }
+
+    /// Overwrite the stored stats of the given servers.
+    pub(crate) async fn save(&self, stats: &[ServerStats]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for server in stats {
+            let success_count = i64::try_from(server.success_count).unwrap_or(i64::MAX);
+            let failure_count = i64::try_from(server.failure_count).unwrap_or(i64::MAX);
+            sqlx::query!(
+                r#"
+                INSERT INTO electrum_servers (url, success_count, failure_count, avg_latency_ms, updated_at)
+                VALUES (?, ?, ?, ?, datetime('now'))
+                ON CONFLICT(url) DO UPDATE SET
+                    success_count = excluded.success_count,
+                    failure_count = excluded.failure_count,
+                    avg_latency_ms = excluded.avg_latency_ms,
+                    updated_at = excluded.updated_at
+                "#,
+                server.url,
+                success_count,
+                failure_count,
+                server.avg_latency_ms
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_the_latency_of_successful_requests() {
+        let tracker = StatsTracker::default();
+
+        tracker.record_success("tcp://a:50001", Duration::from_millis(100));
+        tracker.record_success("tcp://a:50001", Duration::from_millis(300));
+        tracker.record_failure("tcp://a:50001");
+
+        let stats = tracker.get(&["tcp://a:50001".to_string(), "tcp://b:50001".to_string()]);
+        assert_eq!(stats[0].success_count, 2);
+        assert_eq!(stats[0].failure_count, 1);
+        assert_eq!(stats[0].avg_latency_ms, Some(200.0));
+        assert_eq!(stats[1], ServerStats::new("tcp://b:50001"));
+    }
+
+    #[test]
+    fn reliable_servers_score_higher() {
+        let mut reliable = ServerStats::new("tcp://a:50001");
+        let mut flaky = ServerStats::new("tcp://b:50001");
+        for _ in 0..10 {
+            reliable.record_success(Duration::from_millis(100));
+            reliable.record_success(Duration::from_millis(100));
+            flaky.record_success(Duration::from_millis(100));
+            flaky.record_failure();
+        }
+
+        assert!(reliable.reliability_score() > flaky.reliability_score());
+        assert!(flaky.reliability_score() > ServerStats::new("tcp://c:50001").reliability_score());
+    }
+}
@@ -30,7 +30,7 @@ use tokio::time;
 use monero::{Address, Amount};
 use monero_rpc::monerod::MonerodRpc as _;
 use monero_rpc::monerod::{self, GenerateBlocks};
-use monero_sys::{no_listener, Daemon, SyncProgress, TxReceipt, WalletHandle};
+use monero_sys::{no_listener, Daemon, SeedLanguage, SyncProgress, TxReceipt, WalletHandle};
 
 use crate::image::{MONEROD_DAEMON_CONTAINER_NAME, MONEROD_DEFAULT_NETWORK, RPC_PORT};
 
@@ -77,6 +77,7 @@ impl<'c> Monero {
             Daemon {
                 address: monerod_url,
                 ssl: false,
+                ..Default::default()
             }
         };
 
@@ -412,9 +413,11 @@ impl MoneroWallet {
         // and this avoids address-parsing errors when calling daemon RPCs.
         let wallet = WalletHandle::open_or_create(
             wallet_path.display().to_string(),
-            daemon,
+            vec![daemon],
             monero::Network::Mainnet,
+            SeedLanguage::English,
             true,
+            None,
         )
         .await
         .context("Failed to create or open wallet")?;
@@ -0,0 +1,16 @@
+-- Only accept the networks known to `types::network_from_str`.
+-- SQLite can't add a CHECK constraint to an existing table, so enforce it with triggers.
+
+CREATE TRIGGER IF NOT EXISTS check_node_network_insert
+BEFORE INSERT ON monero_nodes
+WHEN NEW.network NOT IN ('mainnet', 'stagenet', 'testnet')
+BEGIN
+    SELECT RAISE(ABORT, 'invalid network');
+END;
+
+CREATE TRIGGER IF NOT EXISTS check_node_network_update
+BEFORE UPDATE OF network ON monero_nodes
+WHEN NEW.network NOT IN ('mainnet', 'stagenet', 'testnet')
+BEGIN
+    SELECT RAISE(ABORT, 'invalid network');
+END;
@@ -0,0 +1,2 @@
+-- Nodes banned by an operator are kept (so they aren't re-added) but never used
+ALTER TABLE monero_nodes ADD COLUMN banned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+-- What each node last reported about itself in `get_info`. NULL until the first health check.
+ALTER TABLE monero_nodes ADD COLUMN rpc_nettype TEXT;
+ALTER TABLE monero_nodes ADD COLUMN rpc_synchronized BOOLEAN;
+ALTER TABLE monero_nodes ADD COLUMN rpc_untrusted BOOLEAN;
+ALTER TABLE monero_nodes ADD COLUMN rpc_restricted BOOLEAN;
+ALTER TABLE monero_nodes ADD COLUMN rpc_checked_at TEXT;
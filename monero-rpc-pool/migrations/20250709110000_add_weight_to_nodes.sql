@@ -0,0 +1,18 @@
+-- Operators can make the pool pick a node more often (weight) or before all others
+-- while it answers (preferred), e.g. their own node
+ALTER TABLE monero_nodes ADD COLUMN weight INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE monero_nodes ADD COLUMN preferred BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TRIGGER IF NOT EXISTS check_node_weight_insert
+BEFORE INSERT ON monero_nodes
+WHEN NEW.weight < 1
+BEGIN
+    SELECT RAISE(ABORT, 'weight must be at least 1');
+END;
+
+CREATE TRIGGER IF NOT EXISTS check_node_weight_update
+BEFORE UPDATE OF weight ON monero_nodes
+WHEN NEW.weight < 1
+BEGIN
+    SELECT RAISE(ABORT, 'weight must be at least 1');
+END;
@@ -0,0 +1,63 @@
+-- The same node used to be stored several times if its address was spelled differently
+-- (case, a trailing dot, IPv6 without brackets), splitting its health history. Normalize
+-- the addresses like `NodeAddress::new` does and merge the duplicates into the oldest record.
+-- IPv6 addresses that aren't in their canonical form can't be fixed in SQL and are only
+-- bracketed.
+
+CREATE TEMP TABLE node_identity AS
+SELECT id,
+       lower(scheme) AS scheme,
+       CASE
+           WHEN instr(host, ':') > 0 AND substr(host, 1, 1) != '[' THEN '[' || host || ']'
+           ELSE host
+       END AS host,
+       port
+FROM (SELECT id, scheme, lower(rtrim(host, '.')) AS host, port FROM monero_nodes);
+
+CREATE TEMP TABLE node_merges AS
+SELECT duplicate.id AS old_id,
+       (SELECT min(original.id)
+        FROM node_identity original
+        WHERE original.scheme = duplicate.scheme
+          AND original.host = duplicate.host
+          AND original.port = duplicate.port) AS new_id
+FROM node_identity duplicate;
+
+-- The merged node keeps the strongest operator settings of its duplicates
+UPDATE monero_nodes
+SET banned = (SELECT max(other.banned) FROM monero_nodes other
+              JOIN node_merges ON node_merges.old_id = other.id
+              WHERE node_merges.new_id = monero_nodes.id),
+    preferred = (SELECT max(other.preferred) FROM monero_nodes other
+                 JOIN node_merges ON node_merges.old_id = other.id
+                 WHERE node_merges.new_id = monero_nodes.id),
+    weight = (SELECT max(other.weight) FROM monero_nodes other
+              JOIN node_merges ON node_merges.old_id = other.id
+              WHERE node_merges.new_id = monero_nodes.id),
+    first_seen_at = (SELECT min(other.first_seen_at) FROM monero_nodes other
+                     JOIN node_merges ON node_merges.old_id = other.id
+                     WHERE node_merges.new_id = monero_nodes.id)
+WHERE id IN (SELECT new_id FROM node_merges WHERE old_id != new_id);
+
+UPDATE health_checks
+SET node_id = (SELECT new_id FROM node_merges WHERE old_id = health_checks.node_id)
+WHERE node_id IN (SELECT old_id FROM node_merges WHERE old_id != new_id);
+
+DELETE FROM monero_nodes
+WHERE id IN (SELECT old_id FROM node_merges WHERE old_id != new_id);
+
+UPDATE monero_nodes
+SET scheme = (SELECT scheme FROM node_identity WHERE node_identity.id = monero_nodes.id),
+    host = (SELECT host FROM node_identity WHERE node_identity.id = monero_nodes.id)
+WHERE id IN (
+    SELECT node_identity.id
+    FROM node_identity
+    JOIN monero_nodes node ON node.id = node_identity.id
+    WHERE node.scheme != node_identity.scheme OR node.host != node_identity.host
+);
+
+DROP TABLE node_merges;
+DROP TABLE node_identity;
+
+-- Addresses that only differ in case are the same node
+CREATE UNIQUE INDEX IF NOT EXISTS idx_nodes_identity ON monero_nodes(lower(scheme), lower(host), port);
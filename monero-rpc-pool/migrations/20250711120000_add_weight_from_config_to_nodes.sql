@@ -0,0 +1,3 @@
+-- Weights from the config are reset on startup once the node is no longer in the config,
+-- weights set through the API are kept
+ALTER TABLE monero_nodes ADD COLUMN weight_from_config BOOLEAN NOT NULL DEFAULT FALSE;
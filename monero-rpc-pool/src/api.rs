@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::types::{NodeAddress, NodeList, NodeWeight};
+use crate::AppState;
+
+/// Endpoints for operators (and the GUI) to manage the nodes of the pool.
+///
+/// Every request has to carry `Authorization: Bearer <api_token>`.
+pub fn router(api_token: String) -> Router<AppState> {
+    Router::new()
+        .route("/api/nodes", get(list_nodes_handler).post(add_node_handler))
+        .route("/api/nodes/export", get(export_nodes_handler))
+        .route("/api/nodes/import", post(import_nodes_handler))
+        .route("/api/nodes/:id", delete(delete_node_handler))
+        .route("/api/nodes/:id/ban", post(ban_node_handler))
+        .route("/api/nodes/:id/weight", post(set_node_weight_handler))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::<str>::from(api_token),
+            require_api_token,
+        ))
+}
+
+async fn require_api_token(
+    State(api_token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), api_token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "Missing or invalid API token" }),
+        ),
+    }
+}
+
+/// Compare without returning early so the response time doesn't leak the token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+fn internal_error(context: &str, e: anyhow::Error) -> Response {
+    error!("{}: {:#}", context, e);
+    json_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "error": context }),
+    )
+}
+
+fn node_not_found(id: i64) -> Response {
+    json_response(
+        StatusCode::NOT_FOUND,
+        json!({ "error": format!("No node with id {}", id) }),
+    )
+}
+
+fn is_unique_violation(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .is_some_and(|e| e.is_unique_violation())
+}
+
+async fn list_nodes_handler(State(state): State<AppState>) -> Response {
+    match state.node_pool.list_nodes().await {
+        Ok(nodes) => json_response(StatusCode::OK, json!({ "nodes": nodes })),
+        Err(e) => internal_error("Failed to list nodes", e),
+    }
+}
+
+async fn add_node_handler(
+    State(state): State<AppState>,
+    Json(address): Json<NodeAddress>,
+) -> Response {
+    if !matches!(address.scheme.as_str(), "http" | "https") || address.host.is_empty() {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            json!({ "error": "Node needs an http or https scheme and a host" }),
+        );
+    }
+
+    match state.node_pool.add_node(&address).await {
+        Ok(id) => json_response(StatusCode::CREATED, json!({ "id": id })),
+        Err(e) if is_unique_violation(&e) => json_response(
+            StatusCode::CONFLICT,
+            json!({ "error": format!("Node {} already exists", address) }),
+        ),
+        Err(e) => internal_error("Failed to add node", e),
+    }
+}
+
+async fn export_nodes_handler(State(state): State<AppState>) -> Response {
+    match state.node_pool.export_nodes().await {
+        Ok(list) => json_response(StatusCode::OK, json!(list)),
+        Err(e) => internal_error("Failed to export nodes", e),
+    }
+}
+
+async fn import_nodes_handler(
+    State(state): State<AppState>,
+    Json(list): Json<NodeList>,
+) -> Response {
+    let nodes = match list.addresses(state.node_pool.network()) {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            return json_response(
+                StatusCode::BAD_REQUEST,
+                json!({ "error": format!("{:#}", e) }),
+            )
+        }
+    };
+
+    match state.node_pool.add_nodes_if_new(&nodes).await {
+        Ok(added) => json_response(
+            StatusCode::OK,
+            json!({ "added": added, "already_known": nodes.len() - added }),
+        ),
+        Err(e) => internal_error("Failed to import nodes", e),
+    }
+}
+
+async fn delete_node_handler(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match state.node_pool.remove_node(id).await {
+        Ok(true) => json_response(StatusCode::OK, json!({ "id": id })),
+        Ok(false) => node_not_found(id),
+        Err(e) => internal_error("Failed to delete node", e),
+    }
+}
+
+async fn ban_node_handler(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match state.node_pool.ban_node(id).await {
+        Ok(true) => json_response(StatusCode::OK, json!({ "id": id, "banned": true })),
+        Ok(false) => node_not_found(id),
+        Err(e) => internal_error("Failed to ban node", e),
+    }
+}
+
+async fn set_node_weight_handler(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(weight): Json<NodeWeight>,
+) -> Response {
+    if weight.weight == 0 {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            json!({ "error": "Weight must be at least 1" }),
+        );
+    }
+
+    match state.node_pool.set_node_weight(id, weight).await {
+        Ok(true) => json_response(
+            StatusCode::OK,
+            json!({ "id": id, "weight": weight.weight, "preferred": weight.preferred }),
+        ),
+        Ok(false) => node_not_found(id),
+        Err(e) => internal_error("Failed to set node weight", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::pool::NodePool;
+    use crate::proxy::UpstreamClients;
+    use crate::recent::{RecentRequests, RECENT_REQUESTS_CAPACITY};
+    use monero::Network;
+    use tower::ServiceExt;
+
+    const API_TOKEN: &str = "secret";
+
+    async fn app_state() -> AppState {
+        let data_dir = std::env::temp_dir().join(format!("rpc-pool-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(data_dir).await.unwrap();
+        let (node_pool, _) = NodePool::new(db, Network::Mainnet);
+
+        AppState {
+            node_pool: Arc::new(node_pool),
+            upstream_clients: UpstreamClients::new().unwrap(),
+            hedge_requests: false,
+            sticky_sessions: false,
+            allowed_methods: None,
+            node_limiter: None,
+            response_cache: None,
+            recent_requests: Arc::new(RecentRequests::new(RECENT_REQUESTS_CAPACITY)),
+        }
+    }
+
+    #[tokio::test]
+    async fn added_nodes_are_normalized() {
+        let state = app_state().await;
+        let app = router(API_TOKEN.to_string()).with_state(state.clone());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/nodes")
+            .header(header::AUTHORIZATION, format!("Bearer {}", API_TOKEN))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                r#"{"scheme": "HTTPS", "host": "Node.Example.COM.", "port": 18089}"#,
+            ))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let nodes = state.node_pool.list_nodes().await.unwrap();
+        assert!(nodes
+            .iter()
+            .any(|node| node.address.full_url() == "https://node.example.com:18089"));
+    }
+}
@@ -0,0 +1,224 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+
+/// `get_transactions` also reports the number of confirmations, which grows with every block.
+/// Those responses are only reused for about a block.
+const TRANSACTIONS_TTL: Duration = Duration::from_secs(120);
+
+/// Which requests return data that can't change anymore, and how to tell if a response is usable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cacheable {
+    /// `get_block` by hash (by height could be reorged).
+    BlockByHash,
+    /// `/get_transactions` for transactions that are already mined.
+    Transactions,
+    /// `/get_o_indexes.bin` of a mined transaction.
+    OutputIndexes,
+}
+
+impl Cacheable {
+    /// Check whether the request only asks for immutable data.
+    pub fn from_request(path: &str, body: Option<&[u8]>) -> Option<Self> {
+        match path {
+            "/json_rpc" => {
+                let request: serde_json::Value = serde_json::from_slice(body?).ok()?;
+                let by_hash = request.pointer("/params/hash").is_some();
+
+                (request.get("method")?.as_str()? == "get_block" && by_hash)
+                    .then_some(Cacheable::BlockByHash)
+            }
+            "/get_transactions" | "/gettransactions" => Some(Cacheable::Transactions),
+            "/get_o_indexes.bin" => Some(Cacheable::OutputIndexes),
+            _ => None,
+        }
+    }
+
+    /// Failures and answers about transactions still in the mempool must not be cached.
+    fn accepts(self, body: &[u8]) -> bool {
+        match self {
+            Cacheable::BlockByHash => serde_json::from_slice::<serde_json::Value>(body)
+                .ok()
+                .and_then(|response| {
+                    response
+                        .pointer("/result/status")
+                        .map(|status| status == "OK")
+                })
+                .unwrap_or(false),
+            Cacheable::Transactions => serde_json::from_slice::<serde_json::Value>(body)
+                .ok()
+                .map(|response| {
+                    let ok = response.get("status").is_some_and(|status| status == "OK");
+                    let missed = response
+                        .get("missed_tx")
+                        .and_then(|missed| missed.as_array())
+                        .is_some_and(|missed| !missed.is_empty());
+                    let in_pool = response
+                        .get("txs")
+                        .and_then(|txs| txs.as_array())
+                        .is_some_and(|txs| {
+                            txs.iter()
+                                .any(|tx| tx.get("in_pool") != Some(&false.into()))
+                        });
+
+                    ok && !missed && !in_pool
+                })
+                .unwrap_or(false),
+            Cacheable::OutputIndexes => epee_status_is_ok(body),
+        }
+    }
+
+    fn ttl(self) -> Option<Duration> {
+        match self {
+            Cacheable::Transactions => Some(TRANSACTIONS_TTL),
+            Cacheable::BlockByHash | Cacheable::OutputIndexes => None,
+        }
+    }
+}
+
+/// Look for `status: "OK"` in an epee portable storage response: the key length, the key,
+/// the string type tag (0x0a) and the varint encoded length of "OK".
+fn epee_status_is_ok(body: &[u8]) -> bool {
+    const STATUS_OK: &[u8] = b"\x06status\x0a\x08OK";
+
+    body.windows(STATUS_OK.len())
+        .any(|window| window == STATUS_OK)
+}
+
+/// Identifies a request by a hash of its path and body.
+pub type CacheKey = [u8; 32];
+
+pub fn cache_key(path: &str, body: Option<&[u8]>) -> CacheKey {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    hasher.update(body.unwrap_or_default());
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    expires_at: Option<Instant>,
+}
+
+/// LRU cache of the responses to requests for immutable data.
+pub struct ResponseCache {
+    entries: Mutex<LruCache<CacheKey, CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Response> {
+        let mut entries = self.entries.lock().expect("lock not to be poisoned");
+
+        let cached = entries.get(key)?;
+        if cached
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            entries.pop(key);
+            return None;
+        }
+
+        let mut response = Response::new(Body::from(cached.body.clone()));
+        *response.status_mut() = cached.status;
+        *response.headers_mut() = cached.headers.clone();
+
+        Some(response)
+    }
+
+    /// Store the response if it is a successful answer. Returns whether it was stored.
+    pub fn insert(
+        &self,
+        key: CacheKey,
+        cacheable: Cacheable,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &Bytes,
+    ) -> bool {
+        if !status.is_success() || !cacheable.accepts(body) {
+            return false;
+        }
+
+        let cached = CachedResponse {
+            status,
+            headers: headers.clone(),
+            body: body.clone(),
+            expires_at: cacheable.ttl().map(|ttl| Instant::now() + ttl),
+        };
+        self.entries
+            .lock()
+            .expect("lock not to be poisoned")
+            .put(key, cached);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_get_block_by_hash_is_cacheable() {
+        let by_hash = br#"{"jsonrpc":"2.0","id":"0","method":"get_block","params":{"hash":"ab"}}"#;
+        let by_height = br#"{"jsonrpc":"2.0","id":"0","method":"get_block","params":{"height":1}}"#;
+        let get_info = br#"{"jsonrpc":"2.0","id":"0","method":"get_info"}"#;
+
+        assert_eq!(
+            Cacheable::from_request("/json_rpc", Some(by_hash)),
+            Some(Cacheable::BlockByHash)
+        );
+        assert_eq!(Cacheable::from_request("/json_rpc", Some(by_height)), None);
+        assert_eq!(Cacheable::from_request("/json_rpc", Some(get_info)), None);
+        assert_eq!(Cacheable::from_request("/get_info", None), None);
+    }
+
+    #[test]
+    fn does_not_cache_transactions_in_pool() {
+        let cache = ResponseCache::new(NonZeroUsize::new(2).unwrap());
+        let key = cache_key("/get_transactions", Some(b"{}"));
+        let in_pool = Bytes::from_static(br#"{"status":"OK","txs":[{"in_pool":true}]}"#);
+        let mined = Bytes::from_static(br#"{"status":"OK","txs":[{"in_pool":false}]}"#);
+
+        assert!(!cache.insert(
+            key,
+            Cacheable::Transactions,
+            StatusCode::OK,
+            &HeaderMap::new(),
+            &in_pool
+        ));
+        assert!(cache.get(&key).is_none());
+
+        assert!(cache.insert(
+            key,
+            Cacheable::Transactions,
+            StatusCode::OK,
+            &HeaderMap::new(),
+            &mined
+        ));
+        assert!(cache.get(&key).is_some());
+    }
+
+    #[test]
+    fn recognizes_epee_status() {
+        assert!(epee_status_is_ok(b"\x01\x11\x01\x01\x06status\x0a\x08OK"));
+        assert!(!epee_status_is_ok(b"\x06status\x0a\x18Failed"));
+    }
+}
@@ -1,11 +1,229 @@
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::health::HealthTier;
+use crate::types::{NodeAddress, NodeWeight};
+
+pub const DEFAULT_RESPONSE_CACHE_SIZE: usize = 256;
+pub const DEFAULT_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The JSON-RPC methods and other endpoints a wallet needs to sync and to look up
+/// transactions. Allowing only these (`--restricted`) keeps the pool from relaying
+/// transactions or forwarding admin and mining requests.
+pub const READ_ONLY_METHODS: &[&str] = &[
+    // JSON-RPC methods
+    "get_version",
+    "get_block_count",
+    "getblockcount",
+    "on_get_block_hash",
+    "on_getblockhash",
+    "get_last_block_header",
+    "getlastblockheader",
+    "get_block_header_by_hash",
+    "getblockheaderbyhash",
+    "get_block_header_by_height",
+    "getblockheaderbyheight",
+    "get_block_headers_range",
+    "getblockheadersrange",
+    "get_block",
+    "getblock",
+    "hard_fork_info",
+    "get_fee_estimate",
+    "get_output_distribution",
+    "get_output_histogram",
+    "get_txpool_backlog",
+    // Other endpoints, JSON and binary
+    "get_info",
+    "getinfo",
+    "get_height",
+    "getheight",
+    "get_blocks.bin",
+    "getblocks.bin",
+    "get_blocks_by_height.bin",
+    "getblocks_by_height.bin",
+    "get_hashes.bin",
+    "gethashes.bin",
+    "get_o_indexes.bin",
+    "get_outs.bin",
+    "get_outs",
+    "get_output_distribution.bin",
+    "get_transactions",
+    "gettransactions",
+    "get_transaction_pool",
+    "get_transaction_pool_hashes",
+    "get_transaction_pool_hashes.bin",
+    "is_key_image_spent",
+];
+
+/// Settings for finding more nodes by asking the known ones for their peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Discovery {
+    /// How often to crawl.
+    pub interval: Duration,
+    /// How many of the top nodes to ask for their peers on each crawl.
+    pub nodes_to_query: usize,
+    /// Pause between two requests of the same crawl.
+    pub request_delay: Duration,
+    /// At most this many new nodes are added per crawl.
+    pub max_new_nodes: usize,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60 * 60),
+            nodes_to_query: 5,
+            request_delay: Duration::from_secs(2),
+            max_new_nodes: 20,
+        }
+    }
+}
+
+/// How much health check history to keep, so the database doesn't grow forever.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Retention {
+    /// How often to prune (and then vacuum) the database.
+    pub interval: Duration,
+    /// Health checks older than this are deleted.
+    pub max_age: Duration,
+    /// Only the latest this many health checks of every node are kept.
+    pub max_checks_per_node: u32,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60 * 60),
+            max_age: Duration::from_secs(7 * 24 * 60 * 60),
+            max_checks_per_node: 1000,
+        }
+    }
+}
+
+/// How often to call `get_info` on a node, depending on how it has been doing. Nodes we
+/// rely on are checked often, nodes that keep failing less and less.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckSchedule {
+    /// Between checks of nodes that passed their last check and most earlier ones.
+    pub reliable_interval: Duration,
+    /// Between checks of nodes that passed their last check but failed many earlier ones.
+    pub unknown_interval: Duration,
+    /// Wait after the first failed check in a row, doubled with every further failure.
+    pub dead_initial_backoff: Duration,
+    /// Nodes that keep failing are still checked at least this often.
+    pub dead_max_backoff: Duration,
+}
+
+impl Default for HealthCheckSchedule {
+    fn default() -> Self {
+        Self {
+            reliable_interval: Duration::from_secs(30),
+            unknown_interval: Duration::from_secs(10 * 60),
+            dead_initial_backoff: Duration::from_secs(60),
+            dead_max_backoff: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+impl HealthCheckSchedule {
+    /// How long to wait before checking a node of the given tier again.
+    pub fn interval(&self, tier: HealthTier) -> Duration {
+        match tier {
+            HealthTier::Reliable => self.reliable_interval,
+            HealthTier::Unknown => self.unknown_interval,
+            HealthTier::Dead {
+                consecutive_failures,
+            } => {
+                let doublings = consecutive_failures.saturating_sub(1).min(31);
+                self.dead_initial_backoff
+                    .saturating_mul(1 << doublings)
+                    .min(self.dead_max_backoff)
+            }
+        }
+    }
+}
+
+/// Limits on the requests sent to a single node. Requests over the limit go to the next node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// How many requests a node may be answering at the same time.
+    pub max_concurrent_requests: usize,
+    /// How many requests a node gets per second on average.
+    pub requests_per_second: u32,
+    /// How many requests a node may get in a burst after being idle.
+    pub burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 4,
+            requests_per_second: 10,
+            burst: 20,
+        }
+    }
+}
+
+/// Settings for periodically cross-checking the chain tip reported by several nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusCheck {
+    /// How often to compare the nodes.
+    pub interval: Duration,
+    /// How many of the top nodes to query on each check.
+    pub sample_size: usize,
+    /// Nodes whose height differs from the median by more than this are quarantined.
+    pub max_height_divergence: u64,
+}
+
+impl Default for ConsensusCheck {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5 * 60),
+            sample_size: 5,
+            max_height_divergence: 10,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub host: String,
     pub port: u16,
     pub data_dir: PathBuf,
+    /// Race idempotent reads against the two best nodes and return whichever answers first.
+    pub hedge_requests: bool,
+    /// Pin every client to one node until that node fails, so a syncing wallet doesn't see
+    /// different chain heights. Clients are told apart by the `X-RPC-Pool-Session` header or,
+    /// without it, by their connection.
+    pub sticky_sessions: bool,
+    /// JSON-RPC methods (e.g. `get_info`) and other endpoints (e.g. `get_blocks.bin`) the pool
+    /// forwards, the others are refused. Everything is forwarded if `None`.
+    pub allowed_methods: Option<Vec<String>>,
+    /// Limit the requests per node. Unlimited if `None`.
+    pub rate_limit: Option<RateLimit>,
+    /// Quarantine nodes that disagree with the others about the chain tip. Disabled if `None`.
+    pub consensus_check: Option<ConsensusCheck>,
+    /// Bearer token for the `/api/nodes` management endpoints. They are disabled if `None`.
+    pub api_token: Option<String>,
+    /// How many responses to immutable requests (blocks by hash, mined transactions, output
+    /// indexes) to keep in memory. Caching is disabled if `0`.
+    pub response_cache_size: usize,
+    /// How often to call `get_info` on the nodes to check they are usable. Disabled if `None`.
+    pub health_checks: Option<HealthCheckSchedule>,
+    /// Crawl the peer lists of known nodes for more nodes. Disabled if `None`.
+    pub discovery: Option<Discovery>,
+    /// Nodes added to the database on startup, in addition to the default ones.
+    pub custom_nodes: Vec<NodeAddress>,
+    /// Weights to apply to these nodes on startup, adding the nodes if they are new.
+    pub node_weights: Vec<(NodeAddress, NodeWeight)>,
+    /// Prune old health checks. History is kept forever if `None`.
+    pub retention: Option<Retention>,
+    /// How often to ask the nodes for new blocks to announce on `/notify/new_block`.
+    /// Disabled if `None`.
+    pub block_poll_interval: Option<Duration>,
 }
 
 impl Config {
@@ -14,6 +232,19 @@ impl Config {
             host,
             port,
             data_dir,
+            hedge_requests: true,
+            sticky_sessions: false,
+            allowed_methods: None,
+            rate_limit: None,
+            consensus_check: None,
+            api_token: None,
+            response_cache_size: DEFAULT_RESPONSE_CACHE_SIZE,
+            health_checks: Some(HealthCheckSchedule::default()),
+            discovery: None,
+            custom_nodes: Vec::new(),
+            node_weights: Vec::new(),
+            retention: Some(Retention::default()),
+            block_poll_interval: None,
         }
     }
 
@@ -22,6 +253,264 @@ impl Config {
             host,
             port: 0,
             data_dir,
+            hedge_requests: true,
+            sticky_sessions: false,
+            allowed_methods: None,
+            rate_limit: None,
+            consensus_check: None,
+            api_token: None,
+            response_cache_size: DEFAULT_RESPONSE_CACHE_SIZE,
+            health_checks: Some(HealthCheckSchedule::default()),
+            discovery: None,
+            custom_nodes: Vec::new(),
+            node_weights: Vec::new(),
+            retention: Some(Retention::default()),
+            block_poll_interval: None,
+        }
+    }
+}
+
+/// The contents of a `--config` TOML file. Everything is optional, command line flags and
+/// environment variables take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// "mainnet", "stagenet" or "testnet".
+    pub network: Option<String>,
+    /// Where the node database is kept.
+    pub data_dir: Option<PathBuf>,
+    /// URLs of additional nodes, e.g. `https://node.example.com:18089`.
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    /// Seconds between health checks of reliable nodes, `0` disables health checks.
+    pub health_check_interval_secs: Option<u64>,
+    pub discovery: Option<bool>,
+    /// Announce new blocks on `/notify/new_block`.
+    pub block_notifications: Option<bool>,
+    pub consensus_check: Option<bool>,
+    pub hedge_requests: Option<bool>,
+    pub sticky_sessions: Option<bool>,
+    /// Only forward the methods in [`READ_ONLY_METHODS`] (plus `allowed_methods`).
+    pub restricted: Option<bool>,
+    /// JSON-RPC methods and endpoints to forward, everything is forwarded if unset.
+    pub allowed_methods: Option<Vec<String>>,
+    /// `true` enables the per node limits.
+    pub rate_limit: Option<bool>,
+    pub max_concurrent_requests_per_node: Option<usize>,
+    pub requests_per_second_per_node: Option<u32>,
+    pub response_cache_size: Option<usize>,
+    pub api_token: Option<String>,
+    /// URLs of nodes to use before all others while they are up, e.g. your own node.
+    #[serde(default)]
+    pub preferred_nodes: Vec<String>,
+    /// Node URLs with how many times more likely they should be picked.
+    #[serde(default)]
+    pub node_weights: BTreeMap<String, u32>,
+    /// Days of health check history to keep.
+    pub health_check_retention_days: Option<u64>,
+    /// Number of health checks to keep per node.
+    pub max_health_checks_per_node: Option<u32>,
+}
+
+impl ConfigFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+}
+
+/// Parse a node URL like `https://node.example.com:18089`. The port is required because
+/// nodes rarely listen on the default HTTP(S) ports.
+pub fn parse_node_url(node_url: &str) -> Result<NodeAddress> {
+    let url =
+        url::Url::parse(node_url).with_context(|| format!("Invalid node URL `{}`", node_url))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Node URL `{}` must use http or https", node_url);
+    }
+    let host = url
+        .host_str()
+        .with_context(|| format!("Node URL `{}` has no host", node_url))?;
+    let port = url
+        .port()
+        .with_context(|| format!("Node URL `{}` has no port", node_url))?;
+
+    Ok(NodeAddress::new(
+        url.scheme().to_string(),
+        host.to_string(),
+        port,
+    ))
+}
+
+/// Combine the weights and preferred nodes of the config into the weight of every node.
+pub fn parse_node_weights(
+    weights: &BTreeMap<String, u32>,
+    preferred_nodes: &[String],
+) -> Result<Vec<(NodeAddress, NodeWeight)>> {
+    let mut node_weights = Vec::new();
+
+    for (node_url, &weight) in weights {
+        if weight == 0 {
+            bail!("Weight of node `{}` must be at least 1", node_url);
+        }
+
+        node_weights.push((
+            parse_node_url(node_url)?,
+            NodeWeight {
+                weight,
+                preferred: false,
+            },
+        ));
+    }
+
+    for node_url in preferred_nodes {
+        let node = parse_node_url(node_url)?;
+        match node_weights
+            .iter_mut()
+            .find(|(address, _)| *address == node)
+        {
+            Some((_, weight)) => weight.preferred = true,
+            None => node_weights.push((
+                node,
+                NodeWeight {
+                    preferred: true,
+                    ..NodeWeight::default()
+                },
+            )),
         }
     }
+
+    Ok(node_weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_from_dead_nodes() {
+        let schedule = HealthCheckSchedule::default();
+        let dead = |consecutive_failures| HealthTier::Dead {
+            consecutive_failures,
+        };
+
+        assert_eq!(
+            schedule.interval(HealthTier::Reliable),
+            Duration::from_secs(30)
+        );
+        assert_eq!(schedule.interval(dead(1)), Duration::from_secs(60));
+        assert_eq!(schedule.interval(dead(3)), Duration::from_secs(4 * 60));
+        assert_eq!(schedule.interval(dead(100)), schedule.dead_max_backoff);
+    }
+
+    #[test]
+    fn parses_config_file() {
+        let config: ConfigFile = toml::from_str(
+            r#"
+            port = 18089
+            network = "stagenet"
+            data_dir = "/var/lib/monero-rpc-pool"
+            nodes = ["https://node.example.com:38089"]
+            health_check_interval_secs = 60
+            discovery = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            ConfigFile {
+                port: Some(18089),
+                network: Some("stagenet".into()),
+                data_dir: Some("/var/lib/monero-rpc-pool".into()),
+                nodes: vec!["https://node.example.com:38089".into()],
+                health_check_interval_secs: Some(60),
+                discovery: Some(true),
+                ..Default::default()
+            }
+        );
+        assert!(toml::from_str::<ConfigFile>("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn parses_node_urls() {
+        assert_eq!(
+            parse_node_url("https://node.example.com:18089").unwrap(),
+            NodeAddress::new("https".into(), "node.example.com".into(), 18089)
+        );
+        assert!(parse_node_url("https://node.example.com").is_err());
+        assert!(parse_node_url("ftp://node.example.com:18089").is_err());
+    }
+
+    #[test]
+    fn normalizes_node_addresses() {
+        let node = |url| parse_node_url(url).unwrap().to_string();
+
+        assert_eq!(
+            node("HTTPS://Node.Example.COM.:18089"),
+            "https://node.example.com:18089"
+        );
+        assert_eq!(
+            node("http://[2001:DB8:0::1]:18081"),
+            "http://[2001:db8::1]:18081"
+        );
+        assert_eq!(
+            NodeAddress::new("http".into(), "2001:db8::1".into(), 18081).to_string(),
+            "http://[2001:db8::1]:18081"
+        );
+    }
+
+    #[test]
+    fn combines_weights_and_preferred_nodes() {
+        let weights = BTreeMap::from([
+            ("https://a.example.com:18089".to_string(), 3),
+            ("https://b.example.com:18089".to_string(), 2),
+        ]);
+        let preferred = ["https://b.example.com:18089".to_string()];
+
+        let node_weights = parse_node_weights(&weights, &preferred).unwrap();
+
+        assert_eq!(
+            node_weights,
+            vec![
+                (
+                    NodeAddress::new("https".into(), "a.example.com".into(), 18089),
+                    NodeWeight {
+                        weight: 3,
+                        preferred: false
+                    }
+                ),
+                (
+                    NodeAddress::new("https".into(), "b.example.com".into(), 18089),
+                    NodeWeight {
+                        weight: 2,
+                        preferred: true
+                    }
+                ),
+            ]
+        );
+        assert!(parse_node_weights(
+            &BTreeMap::from([("https://a.example.com:18089".to_string(), 0)]),
+            &[]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn node_weights_default_missing_fields() {
+        let weight: NodeWeight = serde_json::from_str(r#"{"preferred": true}"#).unwrap();
+
+        assert_eq!(
+            weight,
+            NodeWeight {
+                weight: 1,
+                preferred: true
+            }
+        );
+    }
 }
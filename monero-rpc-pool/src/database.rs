@@ -1,13 +1,42 @@
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::types::{NodeAddress, NodeHealthStats, NodeMetadata, NodeRecord};
+use crate::health::NodeInfo;
+use crate::types::{
+    network_from_str, network_to_str, LatencyPercentiles, NodeAddress, NodeHealthStats,
+    NodeMetadata, NodeRecord, NodeWeight,
+};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use monero::Network;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
 use sqlx::SqlitePool;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Health checks are written in batches. Once this many are waiting they are written right
+/// away instead of on the next flush.
+const MAX_PENDING_HEALTH_CHECKS: usize = 1000;
+
+/// How long a connection waits for another one to finish writing before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Clone)]
 pub struct Database {
     pub pool: SqlitePool,
+    pending_health_checks: Arc<Mutex<Vec<PendingHealthCheck>>>,
+}
+
+/// A health check that hasn't been written to the database yet.
+#[derive(Debug, Clone)]
+struct PendingHealthCheck {
+    scheme: String,
+    host: String,
+    port: i64,
+    timestamp: DateTime<Utc>,
+    was_successful: bool,
+    latency_ms: Option<f64>,
 }
 
 impl Database {
@@ -22,9 +51,17 @@ impl Database {
         info!("Using database at {}", db_path.display());
 
         let database_url = format!("sqlite:{}?mode=rwc", db_path.display());
-        let pool = SqlitePool::connect(&database_url).await?;
+        // WAL lets the proxy read node stats while health checks are being written
+        let options = SqliteConnectOptions::from_str(&database_url)?
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT);
+        let pool = SqlitePool::connect_with(options).await?;
 
-        let db = Self { pool };
+        let db = Self {
+            pool,
+            pending_health_checks: Arc::new(Mutex::new(Vec::new())),
+        };
         db.migrate().await?;
 
         Ok(db)
@@ -38,7 +75,7 @@ impl Database {
         Ok(())
     }
 
-    /// Record a health check event
+    /// Record a health check event. It is written by the next [`Database::flush_health_checks`].
     pub async fn record_health_check(
         &self,
         scheme: &str,
@@ -47,34 +84,109 @@ impl Database {
         was_successful: bool,
         latency_ms: Option<f64>,
     ) -> Result<()> {
-        let result = sqlx::query!(
+        let pending = {
+            let mut pending = self
+                .pending_health_checks
+                .lock()
+                .expect("lock not to be poisoned");
+            pending.push(PendingHealthCheck {
+                scheme: scheme.to_string(),
+                host: host.to_string(),
+                port,
+                timestamp: Utc::now(),
+                was_successful,
+                latency_ms,
+            });
+            pending.len()
+        };
+
+        if pending >= MAX_PENDING_HEALTH_CHECKS {
+            self.flush_health_checks().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the recorded health checks in a single transaction, so many nodes being checked
+    /// at once don't all compete for the database lock.
+    pub async fn flush_health_checks(&self) -> Result<()> {
+        let checks = std::mem::take(
+            &mut *self
+                .pending_health_checks
+                .lock()
+                .expect("lock not to be poisoned"),
+        );
+
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for check in &checks {
+            let timestamp = check.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+            let result = sqlx::query!(
+                r#"
+                INSERT INTO health_checks (node_id, timestamp, was_successful, latency_ms)
+                SELECT id, ?, ?, ?
+                FROM monero_nodes
+                WHERE scheme = ? AND host = ? AND port = ?
+                "#,
+                timestamp,
+                check.was_successful,
+                check.latency_ms,
+                check.scheme,
+                check.host,
+                check.port
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                warn!(
+                    "Cannot record health check for unknown node: {}://{}:{}",
+                    check.scheme, check.host, check.port
+                );
+            }
+        }
+        tx.commit().await?;
+
+        debug!("Wrote {} health checks", checks.len());
+
+        Ok(())
+    }
+
+    /// Store what a node reported about itself in `get_info`. Nodes on another network,
+    /// still syncing or relying on an untrusted bootstrap daemon are no longer selected.
+    pub async fn record_node_info(&self, address: &NodeAddress, info: &NodeInfo) -> Result<()> {
+        let port = address.port as i64;
+        sqlx::query!(
             r#"
-            INSERT INTO health_checks (node_id, timestamp, was_successful, latency_ms)
-            SELECT id, datetime('now'), ?, ?
-            FROM monero_nodes 
+            UPDATE monero_nodes
+            SET rpc_nettype = ?,
+                rpc_synchronized = ?,
+                rpc_untrusted = ?,
+                rpc_restricted = ?,
+                rpc_checked_at = datetime('now'),
+                updated_at = datetime('now')
             WHERE scheme = ? AND host = ? AND port = ?
             "#,
-            was_successful,
-            latency_ms,
-            scheme,
-            host,
+            info.nettype,
+            info.synchronized,
+            info.untrusted,
+            info.restricted,
+            address.scheme,
+            address.host,
             port
         )
         .execute(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            warn!(
-                "Cannot record health check for unknown node: {}://{}:{}",
-                scheme, host, port
-            );
-        }
-
         Ok(())
     }
 
     /// Get reliable nodes (top 4 by reliability score)
-    pub async fn get_reliable_nodes(&self, network: &str) -> Result<Vec<NodeRecord>> {
+    pub async fn get_reliable_nodes(&self, network: Network) -> Result<Vec<NodeRecord>> {
+        let network = network_to_str(network);
         let rows = sqlx::query!(
             r#"
             SELECT 
@@ -84,6 +196,8 @@ impl Database {
                 n.port,
                 n.network,
                 n.first_seen_at,
+                n.weight as "weight!: u32",
+                n.preferred as "preferred!: bool",
                 CAST(COALESCE(stats.success_count, 0) AS INTEGER) as "success_count!: i64",
                 CAST(COALESCE(stats.failure_count, 0) AS INTEGER) as "failure_count!: i64",
                 stats.last_success as "last_success?: String",
@@ -110,7 +224,10 @@ impl Database {
                 FROM health_checks 
                 GROUP BY node_id
             ) stats ON n.id = stats.node_id
-            WHERE n.network = ? AND (COALESCE(stats.success_count, 0) + COALESCE(stats.failure_count, 0)) > 0
+            WHERE n.network = ? AND NOT n.banned
+                AND (n.rpc_nettype IS NULL OR n.rpc_nettype = n.network)
+                AND COALESCE(n.rpc_synchronized, TRUE) AND NOT COALESCE(n.rpc_untrusted, FALSE)
+                AND (COALESCE(stats.success_count, 0) + COALESCE(stats.failure_count, 0)) > 0
             ORDER BY 
                 (CAST(COALESCE(stats.success_count, 0) AS REAL) / CAST(COALESCE(stats.success_count, 0) + COALESCE(stats.failure_count, 0) AS REAL)) * 
                 (MIN(COALESCE(stats.success_count, 0) + COALESCE(stats.failure_count, 0), 200) / 200.0) * 0.8 +
@@ -125,7 +242,7 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let nodes: Vec<NodeRecord> = rows
+        let nodes = rows
             .into_iter()
             .map(|row| {
                 let address = NodeAddress::new(row.scheme, row.host, row.port as u16);
@@ -134,7 +251,16 @@ impl Database {
                     .parse()
                     .unwrap_or_else(|_| chrono::Utc::now());
 
-                let metadata = NodeMetadata::new(row.id, row.network, first_seen_at);
+                let metadata = NodeMetadata::new(
+                    row.id,
+                    network_from_str(&row.network)?,
+                    first_seen_at,
+                    false,
+                    NodeWeight {
+                        weight: row.weight,
+                        preferred: row.preferred,
+                    },
+                );
                 let health = NodeHealthStats {
                     success_count: row.success_count,
                     failure_count: row.failure_count,
@@ -146,15 +272,16 @@ impl Database {
                     max_latency_ms: row.max_latency_ms,
                     last_latency_ms: row.last_latency_ms,
                 };
-                NodeRecord::new(address, metadata, health)
+                Ok(NodeRecord::new(address, metadata, health))
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
         Ok(nodes)
     }
 
     /// Get node statistics for a network
-    pub async fn get_node_stats(&self, network: &str) -> Result<(i64, i64, i64)> {
+    pub async fn get_node_stats(&self, network: Network) -> Result<(i64, i64, i64)> {
+        let network = network_to_str(network);
         let row = sqlx::query!(
             r#"
             SELECT 
@@ -181,7 +308,8 @@ impl Database {
     }
 
     /// Get health check statistics for a network
-    pub async fn get_health_check_stats(&self, network: &str) -> Result<(u64, u64)> {
+    pub async fn get_health_check_stats(&self, network: Network) -> Result<(u64, u64)> {
+        let network = network_to_str(network);
         let row = sqlx::query!(
             r#"
             SELECT 
@@ -210,9 +338,10 @@ impl Database {
     /// Get top nodes based on success rate
     pub async fn get_top_nodes_by_recent_success(
         &self,
-        network: &str,
+        network: Network,
         limit: i64,
     ) -> Result<Vec<NodeAddress>> {
+        let network = network_to_str(network);
         let rows = sqlx::query!(
             r#"
             SELECT 
@@ -233,7 +362,9 @@ impl Database {
                 ) recent_checks
                 GROUP BY node_id
             ) stats ON n.id = stats.node_id
-            WHERE n.network = ?
+            WHERE n.network = ? AND NOT n.banned
+                AND (n.rpc_nettype IS NULL OR n.rpc_nettype = n.network)
+                AND COALESCE(n.rpc_synchronized, TRUE) AND NOT COALESCE(n.rpc_untrusted, FALSE)
             ORDER BY 
                 CASE 
                     WHEN (COALESCE(stats.success_count, 0) + COALESCE(stats.failure_count, 0)) > 0 
@@ -255,4 +386,410 @@ impl Database {
 
         Ok(addresses)
     }
+
+    /// Get all nodes of a network together with their health, including banned ones
+    pub async fn get_all_nodes(&self, network: Network) -> Result<Vec<NodeRecord>> {
+        let network = network_to_str(network);
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                n.id as "id!: i64",
+                n.scheme,
+                n.host,
+                n.port,
+                n.network,
+                n.first_seen_at,
+                n.banned as "banned!: bool",
+                n.weight as "weight!: u32",
+                n.preferred as "preferred!: bool",
+                CAST(COALESCE(stats.success_count, 0) AS INTEGER) as "success_count!: i64",
+                CAST(COALESCE(stats.failure_count, 0) AS INTEGER) as "failure_count!: i64",
+                stats.last_success as "last_success?: String",
+                stats.last_failure as "last_failure?: String",
+                stats.last_checked as "last_checked?: String",
+                stats.avg_latency_ms as "avg_latency_ms?: f64",
+                stats.min_latency_ms as "min_latency_ms?: f64",
+                stats.max_latency_ms as "max_latency_ms?: f64",
+                stats.last_latency_ms as "last_latency_ms?: f64"
+            FROM monero_nodes n
+            LEFT JOIN (
+                SELECT 
+                    node_id,
+                    SUM(CASE WHEN was_successful THEN 1 ELSE 0 END) as success_count,
+                    SUM(CASE WHEN NOT was_successful THEN 1 ELSE 0 END) as failure_count,
+                    MAX(CASE WHEN was_successful THEN timestamp END) as last_success,
+                    MAX(CASE WHEN NOT was_successful THEN timestamp END) as last_failure,
+                    MAX(timestamp) as last_checked,
+                    AVG(CASE WHEN was_successful AND latency_ms IS NOT NULL THEN latency_ms END) as avg_latency_ms,
+                    MIN(CASE WHEN was_successful AND latency_ms IS NOT NULL THEN latency_ms END) as min_latency_ms,
+                    MAX(CASE WHEN was_successful AND latency_ms IS NOT NULL THEN latency_ms END) as max_latency_ms,
+                    (SELECT latency_ms FROM health_checks hc2 WHERE hc2.node_id = health_checks.node_id ORDER BY timestamp DESC LIMIT 1) as last_latency_ms
+                FROM health_checks 
+                GROUP BY node_id
+            ) stats ON n.id = stats.node_id
+            WHERE n.network = ?
+            ORDER BY n.id
+            "#,
+            network
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let address = NodeAddress::new(row.scheme, row.host, row.port as u16);
+                let first_seen_at = row
+                    .first_seen_at
+                    .parse()
+                    .unwrap_or_else(|_| chrono::Utc::now());
+
+                let metadata = NodeMetadata::new(
+                    row.id,
+                    network_from_str(&row.network)?,
+                    first_seen_at,
+                    row.banned,
+                    NodeWeight {
+                        weight: row.weight,
+                        preferred: row.preferred,
+                    },
+                );
+                let health = NodeHealthStats {
+                    success_count: row.success_count,
+                    failure_count: row.failure_count,
+                    last_success: row.last_success.and_then(|s| s.parse().ok()),
+                    last_failure: row.last_failure.and_then(|s| s.parse().ok()),
+                    last_checked: row.last_checked.and_then(|s| s.parse().ok()),
+                    avg_latency_ms: row.avg_latency_ms,
+                    min_latency_ms: row.min_latency_ms,
+                    max_latency_ms: row.max_latency_ms,
+                    last_latency_ms: row.last_latency_ms,
+                };
+                Ok(NodeRecord::new(address, metadata, health))
+            })
+            .collect()
+    }
+
+    /// Add a node, returning its id. Fails if the node is already known.
+    pub async fn add_node(&self, address: &NodeAddress, network: Network) -> Result<i64> {
+        let network = network_to_str(network);
+        let port = address.port as i64;
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO monero_nodes (scheme, host, port, network, first_seen_at)
+            VALUES (?, ?, ?, ?, datetime('now'))
+            "#,
+            address.scheme,
+            address.host,
+            port,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Add a node unless it is already known. Returns whether it was added.
+    pub async fn insert_node_if_new(
+        &self,
+        address: &NodeAddress,
+        network: Network,
+    ) -> Result<bool> {
+        let network = network_to_str(network);
+        let port = address.port as i64;
+        let result = sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO monero_nodes (scheme, host, port, network, first_seen_at)
+            VALUES (?, ?, ?, ?, datetime('now'))
+            "#,
+            address.scheme,
+            address.host,
+            port,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a node of the given network, its health checks are removed by the foreign key.
+    /// Returns whether the node existed.
+    pub async fn delete_node(&self, id: i64, network: Network) -> Result<bool> {
+        let network = network_to_str(network);
+        let result = sqlx::query!(
+            "DELETE FROM monero_nodes WHERE id = ? AND network = ?",
+            id,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Ban a node of the given network so it is never selected again.
+    /// Returns whether the node existed.
+    pub async fn ban_node(&self, id: i64, network: Network) -> Result<bool> {
+        let network = network_to_str(network);
+        let result = sqlx::query!(
+            r#"
+            UPDATE monero_nodes
+            SET banned = TRUE, updated_at = datetime('now')
+            WHERE id = ? AND network = ?
+            "#,
+            id,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set the weight of a node of the given network. Returns whether the node exists.
+    pub async fn set_node_weight(
+        &self,
+        id: i64,
+        network: Network,
+        weight: NodeWeight,
+    ) -> Result<bool> {
+        let network = network_to_str(network);
+        let result = sqlx::query!(
+            r#"
+            UPDATE monero_nodes
+            SET weight = ?, preferred = ?, weight_from_config = FALSE, updated_at = datetime('now')
+            WHERE id = ? AND network = ?
+            "#,
+            weight.weight,
+            weight.preferred,
+            id,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Like [`Database::set_node_weight`], but for a node identified by its address, whose
+    /// weight comes from the config.
+    pub async fn set_node_weight_by_address(
+        &self,
+        address: &NodeAddress,
+        network: Network,
+        weight: NodeWeight,
+    ) -> Result<bool> {
+        let network = network_to_str(network);
+        let port = address.port as i64;
+        let result = sqlx::query!(
+            r#"
+            UPDATE monero_nodes
+            SET weight = ?, preferred = ?, weight_from_config = TRUE, updated_at = datetime('now')
+            WHERE scheme = ? AND host = ? AND port = ? AND network = ?
+            "#,
+            weight.weight,
+            weight.preferred,
+            address.scheme,
+            address.host,
+            port,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Reset the weights that came from the config to the default. Returns the number of
+    /// nodes that had one.
+    pub async fn reset_config_node_weights(&self, network: Network) -> Result<u64> {
+        let network = network_to_str(network);
+        let result = sqlx::query!(
+            r#"
+            UPDATE monero_nodes
+            SET weight = 1, preferred = FALSE, weight_from_config = FALSE, updated_at = datetime('now')
+            WHERE weight_from_config AND network = ?
+            "#,
+            network
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get the usable nodes whose weight was changed by an operator. Preferred nodes are
+    /// only returned as preferred if their last request succeeded (or they weren't used yet).
+    pub async fn get_weighted_nodes(
+        &self,
+        network: Network,
+    ) -> Result<Vec<(NodeAddress, NodeWeight)>> {
+        let network = network_to_str(network);
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                n.scheme,
+                n.host,
+                n.port,
+                n.weight as "weight!: u32",
+                n.preferred AND COALESCE((
+                    SELECT hc.was_successful
+                    FROM health_checks hc
+                    WHERE hc.node_id = n.id
+                    ORDER BY hc.timestamp DESC, hc.id DESC
+                    LIMIT 1
+                ), TRUE) as "preferred!: bool"
+            FROM monero_nodes n
+            WHERE n.network = ? AND NOT n.banned
+                AND (n.rpc_nettype IS NULL OR n.rpc_nettype = n.network)
+                AND COALESCE(n.rpc_synchronized, TRUE) AND NOT COALESCE(n.rpc_untrusted, FALSE)
+                AND (n.weight != 1 OR n.preferred)
+            ORDER BY n.weight DESC, n.id
+            "#,
+            network
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let address = NodeAddress::new(row.scheme, row.host, row.port as u16);
+                let weight = NodeWeight {
+                    weight: row.weight,
+                    preferred: row.preferred,
+                };
+                (address, weight)
+            })
+            .collect())
+    }
+
+    /// Delete health checks older than `max_age` and all but the latest `max_checks_per_node`
+    /// of every node. Returns the number of deleted health checks.
+    pub async fn prune_health_checks(
+        &self,
+        max_age: Duration,
+        max_checks_per_node: u32,
+    ) -> Result<u64> {
+        let cutoff = format!("-{} seconds", max_age.as_secs());
+        let expired = sqlx::query!(
+            "DELETE FROM health_checks WHERE timestamp < datetime('now', ?)",
+            cutoff
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let excess = sqlx::query!(
+            r#"
+            DELETE FROM health_checks
+            WHERE id IN (
+                SELECT id FROM (
+                    SELECT 
+                        id,
+                        ROW_NUMBER() OVER (PARTITION BY node_id ORDER BY timestamp DESC, id DESC) as position
+                    FROM health_checks
+                )
+                WHERE position > ?
+            )
+            "#,
+            max_checks_per_node
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(expired.rows_affected() + excess.rows_affected())
+    }
+
+    /// Give the space of deleted rows back to the file system
+    pub async fn vacuum(&self) -> Result<()> {
+        sqlx::query!("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Get the p50/p95/p99 latency of every node's successful requests in the last day
+    pub async fn get_latency_percentiles(
+        &self,
+        network: Network,
+    ) -> Result<Vec<(NodeAddress, LatencyPercentiles)>> {
+        let network = network_to_str(network);
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                n.scheme,
+                n.host,
+                n.port,
+                MIN(CASE WHEN ranked.position >= 0.50 * ranked.total THEN ranked.latency_ms END) as "p50?: f64",
+                MIN(CASE WHEN ranked.position >= 0.95 * ranked.total THEN ranked.latency_ms END) as "p95?: f64",
+                MIN(CASE WHEN ranked.position >= 0.99 * ranked.total THEN ranked.latency_ms END) as "p99?: f64"
+            FROM (
+                SELECT 
+                    node_id,
+                    latency_ms,
+                    ROW_NUMBER() OVER (PARTITION BY node_id ORDER BY latency_ms) as position,
+                    COUNT(*) OVER (PARTITION BY node_id) as total
+                FROM health_checks
+                WHERE was_successful AND latency_ms IS NOT NULL AND timestamp > datetime('now', '-1 day')
+            ) ranked
+            JOIN monero_nodes n ON ranked.node_id = n.id
+            WHERE n.network = ?
+            GROUP BY n.id
+            "#,
+            network
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let address = NodeAddress::new(row.scheme, row.host, row.port as u16);
+                let percentiles = LatencyPercentiles {
+                    p50_ms: row.p50,
+                    p95_ms: row.p95,
+                    p99_ms: row.p99,
+                };
+                (address, percentiles)
+            })
+            .collect())
+    }
+
+    /// Count the (successful) health checks of every node in the last hour, in buckets of
+    /// `bucket_minutes`. Returns the node, how many buckets ago, the number of checks and
+    /// the number of successful checks.
+    pub async fn get_success_history(
+        &self,
+        network: Network,
+        bucket_minutes: u32,
+    ) -> Result<Vec<(NodeAddress, u32, i64, i64)>> {
+        let network = network_to_str(network);
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                n.scheme,
+                n.host,
+                n.port,
+                CAST((julianday('now') - julianday(hc.timestamp)) * 24 * 60 / ? AS INTEGER) as "bucket!: i64",
+                COUNT(*) as "checks!: i64",
+                CAST(SUM(CASE WHEN hc.was_successful THEN 1 ELSE 0 END) AS INTEGER) as "successful!: i64"
+            FROM health_checks hc
+            JOIN monero_nodes n ON hc.node_id = n.id
+            WHERE n.network = ? AND hc.timestamp > datetime('now', '-1 hour')
+            GROUP BY n.id, 4
+            ORDER BY n.id, 4
+            "#,
+            bucket_minutes,
+            network
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let address = NodeAddress::new(row.scheme, row.host, row.port as u16);
+                (address, row.bucket as u32, row.checks, row.successful)
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::types::NodeAddress;
+
+/// Ask the given nodes which of their peers serve RPC.
+///
+/// The nodes are queried one after the other with `request_delay` in between, so we don't
+/// hammer the nodes we rely on. The returned addresses are deduplicated.
+pub async fn crawl_peer_lists(
+    client: &reqwest::Client,
+    nodes: &[NodeAddress],
+    request_delay: Duration,
+) -> Vec<NodeAddress> {
+    let mut seen = HashSet::new();
+    let mut discovered = Vec::new();
+
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(request_delay).await;
+        }
+
+        match fetch_rpc_peers(client, node).await {
+            Ok(peers) => {
+                debug!("Node {} knows {} peers serving RPC", node, peers.len());
+
+                for peer in peers {
+                    if seen.insert(peer.clone()) {
+                        discovered.push(peer);
+                    }
+                }
+            }
+            Err(e) => debug!("Failed to get peers of {}: {:#}", node, e),
+        }
+    }
+
+    discovered
+}
+
+/// Get the peers of a node that advertise an RPC port. Public nodes usually only allow
+/// `get_peer_list`, `sync_info` is tried if that doesn't return anything.
+async fn fetch_rpc_peers(client: &reqwest::Client, node: &NodeAddress) -> Result<Vec<NodeAddress>> {
+    let peer_list: Value = client
+        .get(format!("{}/get_peer_list", node.full_url()))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse get_peer_list response")?;
+
+    let peers = rpc_peers_from_peer_list(&peer_list);
+    if !peers.is_empty() {
+        return Ok(peers);
+    }
+
+    let sync_info: Value = client
+        .post(format!("{}/json_rpc", node.full_url()))
+        .json(&json!({ "jsonrpc": "2.0", "id": "0", "method": "sync_info" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse sync_info response")?;
+
+    Ok(rpc_peers_from_sync_info(&sync_info))
+}
+
+fn rpc_peers_from_peer_list(response: &Value) -> Vec<NodeAddress> {
+    ["white_list", "gray_list"]
+        .iter()
+        .filter_map(|list| response.get(*list)?.as_array())
+        .flatten()
+        .filter_map(|peer| {
+            let host = match peer.get("host").and_then(Value::as_str) {
+                Some(host) => host.to_string(),
+                // Older nodes only send the IPv4 address as a little endian integer
+                None => {
+                    let ip = peer.get("ip")?.as_u64()? as u32;
+                    Ipv4Addr::from(ip.swap_bytes()).to_string()
+                }
+            };
+
+            peer_address(&host, peer.get("rpc_port")?.as_u64()?)
+        })
+        .collect()
+}
+
+fn rpc_peers_from_sync_info(response: &Value) -> Vec<NodeAddress> {
+    response
+        .pointer("/result/peers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|peer| {
+            let info = peer.get("info")?;
+            let host = info.get("host").and_then(Value::as_str).or_else(|| {
+                let address = info.get("address")?.as_str()?;
+                Some(address.rsplit_once(':').map_or(address, |(host, _)| host))
+            })?;
+
+            peer_address(host, info.get("rpc_port")?.as_u64()?)
+        })
+        .collect()
+}
+
+/// Peers without an RPC port, on addresses we can't reach (Tor, I2P) or on private networks
+/// are skipped. Peers don't tell whether they support TLS, so plain HTTP is assumed.
+fn peer_address(host: &str, rpc_port: u64) -> Option<NodeAddress> {
+    let rpc_port = u16::try_from(rpc_port).ok().filter(|port| *port != 0)?;
+
+    if host.is_empty() || host.ends_with(".onion") || host.ends_with(".i2p") {
+        return None;
+    }
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        let local = match ip {
+            IpAddr::V4(ip) => {
+                ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+            }
+            IpAddr::V6(ip) => ip.is_loopback() || ip.is_unspecified(),
+        };
+
+        if local {
+            return None;
+        }
+    }
+
+    Some(NodeAddress::new(
+        "http".to_string(),
+        host.to_string(),
+        rpc_port,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_rpc_peers_from_peer_list() {
+        let response = json!({
+            "status": "OK",
+            "white_list": [
+                { "host": "1.2.3.4", "port": 18080, "rpc_port": 18089 },
+                { "host": "5.6.7.8", "port": 18080, "rpc_port": 0 },
+                { "host": "192.168.1.2", "port": 18080, "rpc_port": 18081 }
+            ],
+            "gray_list": [
+                { "ip": 0x0403_0201u32, "port": 18080, "rpc_port": 18081 },
+                { "host": "abc.onion", "port": 18083, "rpc_port": 18089 }
+            ]
+        });
+
+        assert_eq!(
+            rpc_peers_from_peer_list(&response),
+            vec![
+                NodeAddress::new("http".into(), "1.2.3.4".into(), 18089),
+                NodeAddress::new("http".into(), "1.2.3.4".into(), 18081),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_rpc_peers_from_sync_info() {
+        let response = json!({
+            "result": {
+                "peers": [
+                    { "info": { "address": "9.9.9.9:18080", "rpc_port": 18081 } },
+                    { "info": { "address": "8.8.8.8:18080", "rpc_port": 0 } }
+                ]
+            }
+        });
+
+        assert_eq!(
+            rpc_peers_from_sync_info(&response),
+            vec![NodeAddress::new("http".into(), "9.9.9.9".into(), 18081)]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use monero::Network;
+use monero_sys::Daemon;
+use tokio::sync::broadcast;
+use tracing::info;
+
+use crate::config::Config;
+use crate::pool::PoolStatus;
+use crate::{start_server_with_random_port, PoolHandle, ServerInfo, Shutdown};
+
+/// A pool started by [`spawn_for_wallet`].
+pub struct EmbeddedPool {
+    /// Pass this to the wallet to have it talk to the pool.
+    pub daemon: Daemon,
+    pub server_info: ServerInfo,
+    pub status_receiver: broadcast::Receiver<PoolStatus>,
+    /// The background tasks stop once this is dropped, keep it for as long as the wallet runs.
+    pub handle: PoolHandle,
+    pub shutdown: Shutdown,
+}
+
+impl ServerInfo {
+    /// The daemon a wallet should use to connect to the pool.
+    pub fn daemon(&self) -> Daemon {
+        Daemon {
+            // The pool only serves plain HTTP on the loopback interface
+            address: format!("http://{}:{}", self.host, self.port),
+            ssl: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// Start the pool on a random port of 127.0.0.1, keeping its database in `data_dir`.
+pub async fn spawn_for_wallet(data_dir: PathBuf, network: Network) -> Result<EmbeddedPool> {
+    let (server_info, status_receiver, handle, shutdown) = start_server_with_random_port(
+        Config::new_random_port("127.0.0.1".to_string(), data_dir),
+        network,
+    )
+    .await
+    .context("Failed to start Monero RPC pool")?;
+
+    info!(
+        "Monero RPC pool started on {}:{}",
+        server_info.host, server_info.port
+    );
+
+    Ok(EmbeddedPool {
+        daemon: server_info.daemon(),
+        server_info,
+        status_receiver,
+        handle,
+        shutdown,
+    })
+}
@@ -0,0 +1,117 @@
+use anyhow::{bail, Context, Result};
+use monero::Network;
+use serde::Deserialize;
+
+use crate::types::{network_to_str, NodeAddress};
+
+/// The parts of a node's `get_info` response that decide whether wallets can use it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NodeInfo {
+    pub height: u64,
+    pub top_block_hash: String,
+    /// "mainnet", "stagenet", "testnet" or "fakechain".
+    pub nettype: String,
+    /// Whether the node has caught up with the network.
+    pub synchronized: bool,
+    /// Set if the node forwards the request to a bootstrap daemon it doesn't trust.
+    #[serde(default)]
+    pub untrusted: bool,
+    /// Whether the node only serves the restricted (public) RPC.
+    #[serde(default)]
+    pub restricted: bool,
+}
+
+impl NodeInfo {
+    /// Why wallets of the given network shouldn't use this node, if there is a reason.
+    pub fn problem(&self, network: Network) -> Option<String> {
+        let expected = network_to_str(network);
+
+        if self.nettype != expected {
+            Some(format!("is on {} instead of {}", self.nettype, expected))
+        } else if !self.synchronized {
+            Some(format!("is still syncing (at height {})", self.height))
+        } else if self.untrusted {
+            Some("answers from an untrusted bootstrap daemon".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Nodes that passed their last health check and at least this share of all requests are
+/// checked as reliable.
+const RELIABLE_SUCCESS_RATE: f64 = 0.8;
+
+/// How a node has been doing, which decides how often it is health checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthTier {
+    Reliable,
+    /// Passed the last check but doesn't have a good enough track record yet.
+    Unknown,
+    /// Failed the last `consecutive_failures` checks.
+    Dead {
+        consecutive_failures: u32,
+    },
+}
+
+impl HealthTier {
+    pub fn classify(consecutive_failures: u32, success_rate: f64) -> Self {
+        if consecutive_failures > 0 {
+            HealthTier::Dead {
+                consecutive_failures,
+            }
+        } else if success_rate >= RELIABLE_SUCCESS_RATE {
+            HealthTier::Reliable
+        } else {
+            HealthTier::Unknown
+        }
+    }
+}
+
+/// Call `get_info` on the node.
+pub async fn fetch_node_info(client: &reqwest::Client, node: &NodeAddress) -> Result<NodeInfo> {
+    let response: serde_json::Value = client
+        .get(format!("{}/get_info", node.full_url()))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let status = response.get("status").and_then(|status| status.as_str());
+    if status != Some("OK") {
+        bail!("get_info returned status {:?}", status);
+    }
+
+    serde_json::from_value(response).context("Failed to parse get_info response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(nettype: &str, synchronized: bool, untrusted: bool) -> NodeInfo {
+        NodeInfo {
+            height: 3_000_000,
+            top_block_hash: "ab".into(),
+            nettype: nettype.into(),
+            synchronized,
+            untrusted,
+            restricted: true,
+        }
+    }
+
+    #[test]
+    fn rejects_nodes_wallets_cannot_use() {
+        assert_eq!(info("mainnet", true, false).problem(Network::Mainnet), None);
+        assert!(info("mainnet", true, false)
+            .problem(Network::Stagenet)
+            .is_some());
+        assert!(info("mainnet", false, false)
+            .problem(Network::Mainnet)
+            .is_some());
+        assert!(info("mainnet", true, true)
+            .problem(Network::Mainnet)
+            .is_some());
+    }
+}
@@ -1,54 +1,131 @@
+use std::collections::HashSet;
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::{
     routing::{any, get},
     Router,
 };
 use monero::Network;
 
-use tokio::task::JoinHandle;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tokio::task::{AbortHandle, JoinHandle};
 use tower_http::cors::CorsLayer;
-use tracing::{error, info};
-
-pub trait ToNetworkString {
-    fn to_network_string(&self) -> String;
-}
-
-impl ToNetworkString for Network {
-    fn to_network_string(&self) -> String {
-        match self {
-            Network::Mainnet => "mainnet".to_string(),
-            Network::Stagenet => "stagenet".to_string(),
-            Network::Testnet => "testnet".to_string(),
-        }
-    }
-}
+use tracing::{error, info, warn};
 
+pub mod api;
+pub mod cache;
 pub mod config;
 pub mod database;
+pub mod discovery;
+#[cfg(feature = "embedded")]
+pub mod embedded;
+pub mod health;
+pub mod limiter;
+pub mod notify;
 pub mod pool;
 pub mod proxy;
+pub mod recent;
 pub mod types;
 
+use cache::ResponseCache;
 use config::Config;
 use database::Database;
+use limiter::NodeLimiter;
+use notify::BlockNotifier;
 use pool::{NodePool, PoolStatus};
-use proxy::{proxy_handler, stats_handler};
+use proxy::{proxy_handler, recent_requests_handler, stats_handler, UpstreamClients};
+use recent::{RecentRequests, RECENT_REQUESTS_CAPACITY};
 
 #[derive(Clone)]
 pub struct AppState {
     pub node_pool: Arc<NodePool>,
+    pub upstream_clients: UpstreamClients,
+    pub hedge_requests: bool,
+    pub sticky_sessions: bool,
+    pub allowed_methods: Option<Arc<HashSet<String>>>,
+    pub node_limiter: Option<Arc<NodeLimiter>>,
+    pub response_cache: Option<Arc<ResponseCache>>,
+    pub recent_requests: Arc<RecentRequests>,
 }
 
 /// Manages background tasks for the RPC pool
 pub struct PoolHandle {
     pub status_update_handle: JoinHandle<()>,
+    pub health_check_flush_handle: JoinHandle<()>,
+    pub consensus_check_handle: Option<JoinHandle<()>>,
+    pub health_check_handle: Option<JoinHandle<()>>,
+    pub discovery_handle: Option<JoinHandle<()>>,
+    pub retention_handle: Option<JoinHandle<()>>,
+    pub block_poll_handle: Option<JoinHandle<()>>,
+    /// Subscribe to get notified about new blocks, if block notifications are enabled.
+    pub block_notifier: Option<Arc<BlockNotifier>>,
 }
 
 impl Drop for PoolHandle {
     fn drop(&mut self) {
         self.status_update_handle.abort();
+        self.health_check_flush_handle.abort();
+        for handle in [
+            &self.consensus_check_handle,
+            &self.health_check_handle,
+            &self.discovery_handle,
+            &self.retention_handle,
+            &self.block_poll_handle,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            handle.abort();
+        }
+    }
+}
+
+impl PoolHandle {
+    fn abort_handles(&self) -> Vec<AbortHandle> {
+        [
+            Some(&self.status_update_handle),
+            Some(&self.health_check_flush_handle),
+            self.consensus_check_handle.as_ref(),
+            self.health_check_handle.as_ref(),
+            self.discovery_handle.as_ref(),
+            self.retention_handle.as_ref(),
+            self.block_poll_handle.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(JoinHandle::abort_handle)
+        .collect()
+    }
+}
+
+/// How long the requests in flight get to finish once the server shuts down.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to look for nodes that are due for a health check.
+const HEALTH_CHECK_TICK: Duration = Duration::from_secs(10);
+
+/// How often the recorded health checks are written to the database.
+const HEALTH_CHECK_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Stops a server started by [`start_server_with_random_port`]. Dropping it leaves the
+/// server running.
+pub struct Shutdown {
+    signal: Arc<Notify>,
+    server: JoinHandle<()>,
+}
+
+impl Shutdown {
+    /// Stop accepting connections and wait for the requests in flight (at most
+    /// [`DRAIN_TIMEOUT`]). Then the background tasks are stopped and the database is closed.
+    pub async fn shutdown(self) -> Result<()> {
+        self.signal.notify_one();
+        self.server.await.context("Server task failed")
     }
 }
 
@@ -66,15 +143,24 @@ async fn create_app_with_receiver(
     Router,
     tokio::sync::broadcast::Receiver<PoolStatus>,
     PoolHandle,
+    Database,
 )> {
     // Initialize database
     let db = Database::new(config.data_dir.clone()).await?;
 
     // Initialize node pool with network
-    let network_str = network.to_network_string();
-    let (node_pool, status_receiver) = NodePool::new(db.clone(), network_str.clone());
+    let (node_pool, status_receiver) = NodePool::new(db.clone(), network);
     let node_pool = Arc::new(node_pool);
 
+    node_pool
+        .add_nodes_if_new(&config.custom_nodes)
+        .await
+        .context("Failed to add custom nodes")?;
+    node_pool
+        .apply_node_weights(&config.node_weights)
+        .await
+        .context("Failed to apply node weights")?;
+
     // Publish initial status immediately to ensure first event is sent
     if let Err(e) = node_pool.publish_status_update().await {
         error!("Failed to publish initial status update: {}", e);
@@ -93,24 +179,203 @@ async fn create_app_with_receiver(
         }
     });
 
+    // Check that the nodes are on our network and synced, each node as often as its tier asks for
+    let health_check_handle = config.health_checks.clone().map(|schedule| {
+        let node_pool = node_pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_TICK);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = node_pool.check_node_health(&schedule).await {
+                    error!("Failed to check node health: {:#}", e);
+                }
+            }
+        })
+    });
+
+    // Write the recorded health checks in batches instead of one by one
+    let health_check_flush_handle = {
+        let db = db.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = db.flush_health_checks().await {
+                    error!("Failed to write health checks: {:#}", e);
+                }
+            }
+        })
+    };
+
+    // Periodically drop old health checks so the database doesn't grow forever
+    let retention_handle = config.retention.clone().map(|retention| {
+        let node_pool = node_pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(retention.interval);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = node_pool.prune_history(&retention).await {
+                    error!("Failed to prune health check history: {:#}", e);
+                }
+            }
+        })
+    });
+
+    // Periodically look for more nodes in the peer lists of the known ones
+    let discovery_handle = config.discovery.clone().map(|discovery| {
+        let node_pool = node_pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(discovery.interval);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = node_pool.discover_nodes(&discovery).await {
+                    error!("Failed to discover nodes: {:#}", e);
+                }
+            }
+        })
+    });
+
+    // Periodically cross-check the chain tip reported by the top nodes
+    let consensus_check_handle = config.consensus_check.clone().map(|check| {
+        let node_pool = node_pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check.interval);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = node_pool.check_consensus(&check).await {
+                    error!("Failed to check consensus between nodes: {:#}", e);
+                }
+            }
+        })
+    });
+
+    // Poll the nodes for new blocks and announce them to the clients waiting for one
+    let block_notifier = config
+        .block_poll_interval
+        .map(|_| Arc::new(BlockNotifier::new()));
+    let block_poll_handle =
+        config
+            .block_poll_interval
+            .zip(block_notifier.clone())
+            .map(|(period, notifier)| {
+                let node_pool = node_pool.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(period);
+                    loop {
+                        interval.tick().await;
+
+                        if let Err(e) = node_pool.poll_new_block(&notifier).await {
+                            error!("Failed to poll for new blocks: {:#}", e);
+                        }
+                    }
+                })
+            });
+
     let pool_handle = PoolHandle {
         status_update_handle,
+        health_check_flush_handle,
+        consensus_check_handle,
+        health_check_handle,
+        discovery_handle,
+        retention_handle,
+        block_poll_handle,
+        block_notifier: block_notifier.clone(),
     };
 
-    let app_state = AppState { node_pool };
+    let app_state = AppState {
+        node_pool,
+        upstream_clients: UpstreamClients::new().context("Failed to build HTTP clients")?,
+        hedge_requests: config.hedge_requests,
+        sticky_sessions: config.sticky_sessions,
+        allowed_methods: config
+            .allowed_methods
+            .clone()
+            .map(|methods| Arc::new(methods.into_iter().collect())),
+        node_limiter: config
+            .rate_limit
+            .clone()
+            .map(|limit| Arc::new(NodeLimiter::new(limit))),
+        response_cache: NonZeroUsize::new(config.response_cache_size)
+            .map(|capacity| Arc::new(ResponseCache::new(capacity))),
+        recent_requests: Arc::new(RecentRequests::new(RECENT_REQUESTS_CAPACITY)),
+    };
 
     // Build the app
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/stats", get(stats_handler))
+        .route("/stats/requests", get(recent_requests_handler));
+    if let Some(api_token) = config.api_token.clone() {
+        app = app.merge(api::router(api_token));
+    }
+    if let Some(notifier) = block_notifier {
+        app = app.merge(notify::router(notifier));
+    }
+    let app = app
         .route("/*path", any(proxy_handler))
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
-    Ok((app, status_receiver, pool_handle))
+    Ok((app, status_receiver, pool_handle, db))
+}
+
+/// Serve the app until `signal` resolves, then stop accepting connections and give the
+/// requests in flight [`DRAIN_TIMEOUT`] to finish before closing their connections.
+/// Afterwards the background tasks are aborted, the pending health checks are written and the
+/// database is closed.
+async fn serve_until(
+    listener: TcpListener,
+    app: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+    background_tasks: Vec<AbortHandle>,
+    db: Database,
+) -> Result<()> {
+    let draining = Arc::new(Notify::new());
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let draining = draining.clone();
+        async move {
+            signal.await;
+            info!("Shutting down, waiting for requests in flight to finish");
+            draining.notify_one();
+        }
+    });
+
+    let result = tokio::select! {
+        result = server.into_future() => result.context("Server error"),
+        _ = async {
+            draining.notified().await;
+            tokio::time::sleep(DRAIN_TIMEOUT).await;
+        } => {
+            warn!(
+                "Requests still in flight after {:?}, closing their connections",
+                DRAIN_TIMEOUT
+            );
+            Ok(())
+        }
+    };
+
+    for task in background_tasks {
+        task.abort();
+    }
+    if let Err(e) = db.flush_health_checks().await {
+        error!("Failed to write health checks: {:#}", e);
+    }
+    db.pool.close().await;
+
+    info!("Server stopped");
+    result
 }
 
 pub async fn create_app(config: Config, network: Network) -> Result<Router> {
-    let (app, _, _pool_handle) = create_app_with_receiver(config, network).await?;
+    let (app, _, _pool_handle, _) = create_app_with_receiver(config, network).await?;
     // Note: pool_handle is dropped here, so tasks will be aborted when this function returns
     // This is intentional for the simple create_app use case
     Ok(app)
@@ -122,21 +387,31 @@ pub async fn create_app_with_data_dir(
     network: Network,
     data_dir: std::path::PathBuf,
 ) -> Result<Router> {
-    let config_with_data_dir = Config::new_with_port(config.host, config.port, data_dir);
+    let config_with_data_dir = Config { data_dir, ..config };
     create_app(config_with_data_dir, network).await
 }
 
+/// Run the server until ctrl-c is pressed
 pub async fn run_server(config: Config, network: Network) -> Result<()> {
-    let app = create_app(config.clone(), network).await?;
+    let (app, _, pool_handle, db) = create_app_with_receiver(config.clone(), network).await?;
 
     let bind_address = format!("{}:{}", config.host, config.port);
     info!("Starting server on {}", bind_address);
 
-    let listener = tokio::net::TcpListener::bind(&bind_address).await?;
+    let listener = TcpListener::bind(&bind_address).await?;
     info!("Server listening on {}", bind_address);
 
-    axum::serve(listener, app).await?;
-    Ok(())
+    let ctrl_c = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => info!("Received ctrl-c"),
+            Err(e) => {
+                error!("Failed to listen for ctrl-c: {}", e);
+                std::future::pending::<()>().await
+            }
+        }
+    };
+
+    serve_until(listener, app, ctrl_c, pool_handle.abort_handles(), db).await
 }
 
 /// Run a server with a custom data directory
@@ -145,12 +420,13 @@ pub async fn run_server_with_data_dir(
     network: Network,
     data_dir: std::path::PathBuf,
 ) -> Result<()> {
-    let config_with_data_dir = Config::new_with_port(config.host, config.port, data_dir);
+    let config_with_data_dir = Config { data_dir, ..config };
     run_server(config_with_data_dir, network).await
 }
 
 /// Start a server with a random port for library usage
-/// Returns the server info with the actual port used, a receiver for pool status updates, and pool handle
+/// Returns the server info with the actual port used, a receiver for pool status updates, the
+/// pool handle and a handle to shut the server down
 pub async fn start_server_with_random_port(
     config: Config,
     network: Network,
@@ -158,18 +434,19 @@ pub async fn start_server_with_random_port(
     ServerInfo,
     tokio::sync::broadcast::Receiver<PoolStatus>,
     PoolHandle,
+    Shutdown,
 )> {
     // Clone the host before moving config
     let host = config.host.clone();
 
     // If port is 0, the system will assign a random available port
-    let config_with_random_port = Config::new_random_port(config.host, config.data_dir);
+    let config_with_random_port = Config { port: 0, ..config };
 
-    let (app, status_receiver, pool_handle) =
+    let (app, status_receiver, pool_handle, db) =
         create_app_with_receiver(config_with_random_port, network).await?;
 
     // Bind to port 0 to get a random available port
-    let listener = tokio::net::TcpListener::bind(format!("{}:0", host)).await?;
+    let listener = TcpListener::bind(format!("{}:0", host)).await?;
     let actual_addr = listener.local_addr()?;
 
     let server_info = ServerInfo {
@@ -183,17 +460,31 @@ pub async fn start_server_with_random_port(
     );
 
     // Start the server in a background task
-    tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
-            error!("Server error: {}", e);
+    let signal = Arc::new(Notify::new());
+    let server = tokio::spawn({
+        let signal = signal.clone();
+        let background_tasks = pool_handle.abort_handles();
+        async move {
+            let shutdown_requested = async move { signal.notified().await };
+            if let Err(e) =
+                serve_until(listener, app, shutdown_requested, background_tasks, db).await
+            {
+                error!("Server error: {:#}", e);
+            }
         }
     });
 
-    Ok((server_info, status_receiver, pool_handle))
+    Ok((
+        server_info,
+        status_receiver,
+        pool_handle,
+        Shutdown { signal, server },
+    ))
 }
 
 /// Start a server with a random port and custom data directory for library usage
-/// Returns the server info with the actual port used, a receiver for pool status updates, and pool handle
+/// Returns the server info with the actual port used, a receiver for pool status updates, the
+/// pool handle and a handle to shut the server down
 pub async fn start_server_with_random_port_and_data_dir(
     config: Config,
     network: Network,
@@ -202,7 +493,12 @@ pub async fn start_server_with_random_port_and_data_dir(
     ServerInfo,
     tokio::sync::broadcast::Receiver<PoolStatus>,
     PoolHandle,
+    Shutdown,
 )> {
-    let config_with_data_dir = Config::new_random_port(config.host, data_dir);
+    let config_with_data_dir = Config {
+        port: 0,
+        data_dir,
+        ..config
+    };
     start_server_with_random_port(config_with_data_dir, network).await
 }
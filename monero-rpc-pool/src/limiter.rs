@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::RateLimit;
+use crate::types::NodeAddress;
+
+/// Caps the requests we send to every node, so a burst of wallet requests doesn't get our IP
+/// banned by a public node.
+pub struct NodeLimiter {
+    limit: RateLimit,
+    nodes: Mutex<HashMap<NodeAddress, NodeBudget>>,
+}
+
+/// What a single node still allows: a slot for a concurrent request and a token of its
+/// token bucket.
+struct NodeBudget {
+    slots: Arc<Semaphore>,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Keeps the concurrent request slot of a node taken until it is dropped.
+#[derive(Debug)]
+pub struct NodePermit {
+    _slot: OwnedSemaphorePermit,
+}
+
+impl NodeLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            nodes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a slot and a token of the node if it has both left. `None` means the request
+    /// should go to another node.
+    pub fn try_acquire(&self, node: &NodeAddress) -> Option<NodePermit> {
+        let mut nodes = self.nodes.lock().expect("lock not to be poisoned");
+        let budget = nodes.entry(node.clone()).or_insert_with(|| NodeBudget {
+            slots: Arc::new(Semaphore::new(self.limit.max_concurrent_requests)),
+            tokens: self.limit.burst as f64,
+            refilled_at: Instant::now(),
+        });
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(budget.refilled_at).as_secs_f64();
+        budget.tokens = (budget.tokens + elapsed * self.limit.requests_per_second as f64)
+            .min(self.limit.burst as f64);
+        budget.refilled_at = now;
+
+        if budget.tokens < 1.0 {
+            return None;
+        }
+
+        let slot = budget.slots.clone().try_acquire_owned().ok()?;
+        budget.tokens -= 1.0;
+
+        Some(NodePermit { _slot: slot })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(port: u16) -> NodeAddress {
+        NodeAddress::new("http".into(), "node".into(), port)
+    }
+
+    #[test]
+    fn limits_concurrent_requests_per_node() {
+        let limiter = NodeLimiter::new(RateLimit {
+            max_concurrent_requests: 1,
+            requests_per_second: 100,
+            burst: 100,
+        });
+
+        let permit = limiter.try_acquire(&node(1)).unwrap();
+        assert!(limiter.try_acquire(&node(1)).is_none());
+        assert!(limiter.try_acquire(&node(2)).is_some());
+
+        drop(permit);
+        assert!(limiter.try_acquire(&node(1)).is_some());
+    }
+
+    #[test]
+    fn limits_requests_to_the_burst() {
+        let limiter = NodeLimiter::new(RateLimit {
+            max_concurrent_requests: 10,
+            requests_per_second: 1,
+            burst: 2,
+        });
+
+        assert!(limiter.try_acquire(&node(1)).is_some());
+        assert!(limiter.try_acquire(&node(1)).is_some());
+        assert!(limiter.try_acquire(&node(1)).is_none());
+    }
+}
@@ -1,29 +1,31 @@
-use clap::Parser;
-use monero_rpc_pool::{config::Config, run_server};
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use monero_rpc_pool::{
+    config::{
+        parse_node_url, parse_node_weights, Config, ConfigFile, ConsensusCheck, Discovery,
+        HealthCheckSchedule, RateLimit, Retention, DEFAULT_BLOCK_POLL_INTERVAL,
+        DEFAULT_RESPONSE_CACHE_SIZE, READ_ONLY_METHODS,
+    },
+    database::Database,
+    pool::NodePool,
+    run_server,
+    types::{network_from_str, network_to_str, NodeList},
+};
 use tracing::info;
 use tracing_subscriber::{self, EnvFilter};
 
 use monero::Network;
 
 fn parse_network(s: &str) -> Result<Network, String> {
-    match s.to_lowercase().as_str() {
-        "mainnet" => Ok(Network::Mainnet),
-        "stagenet" => Ok(Network::Stagenet),
-        "testnet" => Ok(Network::Testnet),
-        _ => Err(format!(
+    network_from_str(&s.to_lowercase()).map_err(|_| {
+        format!(
             "Invalid network: {}. Must be mainnet, stagenet, or testnet",
             s
-        )),
-    }
-}
-
-// TODO: Replace with Display impl for Network
-fn network_to_string(network: &Network) -> String {
-    match network {
-        Network::Mainnet => "mainnet".to_string(),
-        Network::Stagenet => "stagenet".to_string(),
-        Network::Testnet => "testnet".to_string(),
-    }
+        )
+    })
 }
 
 #[derive(Parser)]
@@ -31,22 +33,165 @@ fn network_to_string(network: &Network) -> String {
 #[command(about = "A load-balancing HTTP proxy for Monero RPC nodes")]
 #[command(version)]
 struct Args {
-    #[arg(long, default_value = "127.0.0.1")]
-    #[arg(help = "Host address to bind the server to")]
-    host: String,
+    #[arg(short, long, env = "MONERO_RPC_POOL_CONFIG")]
+    #[arg(help = "TOML config file, command line flags take precedence over its values")]
+    config: Option<PathBuf>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_HOST")]
+    #[arg(help = "Host address to bind the server to [default: 127.0.0.1]")]
+    host: Option<String>,
 
-    #[arg(short, long, default_value = "18081")]
-    #[arg(help = "Port to bind the server to")]
-    port: u16,
+    #[arg(short, long, env = "MONERO_RPC_POOL_PORT")]
+    #[arg(help = "Port to bind the server to [default: 18081]")]
+    port: Option<u16>,
 
-    #[arg(short, long, default_value = "mainnet")]
-    #[arg(help = "Network to use for automatic node discovery")]
+    #[arg(short, long, env = "MONERO_RPC_POOL_NETWORK")]
+    #[arg(help = "Network to use for automatic node discovery [default: mainnet]")]
     #[arg(value_parser = parse_network)]
-    network: Network,
+    network: Option<Network>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_DATA_DIR")]
+    #[arg(help = "Directory of the node database [default: a directory in the temp dir]")]
+    data_dir: Option<PathBuf>,
+
+    #[arg(long = "node", env = "MONERO_RPC_POOL_NODES", value_delimiter = ',')]
+    #[arg(help = "URL of an additional node, e.g. https://node.example.com:18089 (repeatable)")]
+    nodes: Vec<String>,
+
+    #[arg(long = "preferred-node", env = "MONERO_RPC_POOL_PREFERRED_NODES")]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "URL of a node to use before all others while it is up (repeatable)")]
+    preferred_nodes: Vec<String>,
 
     #[arg(short, long)]
     #[arg(help = "Enable verbose logging")]
     verbose: bool,
+
+    #[arg(long)]
+    #[arg(help = "Send idempotent reads to a single node at a time instead of racing two nodes")]
+    no_hedging: bool,
+
+    #[arg(long)]
+    #[arg(help = "Only forward the requests wallets need to sync, e.g. no transaction relaying")]
+    restricted: bool,
+
+    #[arg(long = "allow-method", env = "MONERO_RPC_POOL_ALLOWED_METHODS")]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Only forward this JSON-RPC method or endpoint, e.g. get_info (repeatable)")]
+    allowed_methods: Vec<String>,
+
+    #[arg(long)]
+    #[arg(help = "Keep sending the requests of a client to the same node until that node fails")]
+    sticky_sessions: bool,
+
+    #[arg(long, env = "MONERO_RPC_POOL_MAX_CONCURRENT_REQUESTS_PER_NODE")]
+    #[arg(
+        help = "Number of requests a single node may be answering at once, with --rate-limit \
+                  [default: 4]"
+    )]
+    max_concurrent_requests_per_node: Option<usize>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_REQUESTS_PER_SECOND_PER_NODE")]
+    #[arg(
+        help = "Number of requests per second a single node gets on average, with \
+                  --rate-limit [default: 10]"
+    )]
+    requests_per_second_per_node: Option<u32>,
+
+    #[arg(long)]
+    #[arg(help = "Limit the requests sent to a single node, so public nodes don't ban the pool")]
+    rate_limit: bool,
+
+    #[arg(long)]
+    #[arg(
+        help = "Periodically compare the chain tip of the nodes and quarantine the ones that diverge"
+    )]
+    consensus_check: bool,
+
+    #[arg(long, env = "MONERO_RPC_POOL_API_TOKEN", hide_env_values = true)]
+    #[arg(help = "Enable the /api/nodes management endpoints, protected by this bearer token")]
+    api_token: Option<String>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_RESPONSE_CACHE_SIZE")]
+    #[arg(help = "Number of responses to immutable requests to cache, 0 disables the cache")]
+    response_cache_size: Option<usize>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_HEALTH_CHECK_INTERVAL")]
+    #[arg(
+        help = "Seconds between checks that reliable nodes are on the right network and synced \
+                  [default: 30]"
+    )]
+    health_check_interval: Option<u64>,
+
+    #[arg(long)]
+    #[arg(help = "Don't periodically check that the nodes are on the right network and synced")]
+    no_health_checks: bool,
+
+    #[arg(long, env = "MONERO_RPC_POOL_HEALTH_CHECK_RETENTION_DAYS")]
+    #[arg(help = "Days of health check history to keep [default: 7]")]
+    health_check_retention_days: Option<u64>,
+
+    #[arg(long, env = "MONERO_RPC_POOL_MAX_HEALTH_CHECKS_PER_NODE")]
+    #[arg(help = "Number of health checks to keep per node [default: 1000]")]
+    max_health_checks_per_node: Option<u32>,
+
+    #[arg(long)]
+    #[arg(help = "Find more nodes by crawling the peer lists of the known nodes")]
+    discovery: bool,
+
+    #[arg(long)]
+    #[arg(help = "Announce new blocks to clients long-polling /notify/new_block")]
+    block_notifications: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Write the known nodes, except banned ones, to a JSON file")]
+    ExportNodes {
+        #[arg(help = "File to write the node list to")]
+        file: PathBuf,
+    },
+    #[command(about = "Add the nodes of a file written by export-nodes to the database")]
+    ImportNodes {
+        #[arg(help = "File to read the node list from")]
+        file: PathBuf,
+    },
+}
+
+/// Export or import the node list of the database without starting the server.
+async fn run_command(command: Command, data_dir: PathBuf, network: Network) -> anyhow::Result<()> {
+    let db = Database::new(data_dir).await?;
+    let (node_pool, _) = NodePool::new(db, network);
+
+    match command {
+        Command::ExportNodes { file } => {
+            let list = node_pool.export_nodes().await?;
+            std::fs::write(&file, serde_json::to_string_pretty(&list)?)
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+
+            info!("Exported {} nodes to {}", list.nodes.len(), file.display());
+        }
+        Command::ImportNodes { file } => {
+            let contents = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let list: NodeList = serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse node list {}", file.display()))?;
+            let nodes = list.addresses(network)?;
+            let added = node_pool.add_nodes_if_new(&nodes).await?;
+
+            info!(
+                "Imported {} new nodes from {} ({} were already known)",
+                added,
+                file.display(),
+                nodes.len() - added
+            );
+        }
+    }
+
+    Ok(())
 }
 
 #[tokio::main]
@@ -60,20 +205,124 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_line_number(true)
         .init();
 
-    let config = Config::new_with_port(
-        args.host,
-        args.port,
-        std::env::temp_dir().join("monero-rpc-pool"),
+    let file = match &args.config {
+        Some(path) => ConfigFile::read(path)?,
+        None => ConfigFile::default(),
+    };
+
+    let network = match args.network {
+        Some(network) => network,
+        None => file
+            .network
+            .as_deref()
+            .map(parse_network)
+            .transpose()?
+            .unwrap_or(Network::Mainnet),
+    };
+
+    let mut config = Config::new_with_port(
+        args.host
+            .or(file.host)
+            .unwrap_or_else(|| "127.0.0.1".to_string()),
+        args.port.or(file.port).unwrap_or(18081),
+        args.data_dir
+            .or(file.data_dir)
+            .unwrap_or_else(|| std::env::temp_dir().join("monero-rpc-pool")),
     );
+    config.hedge_requests = !args.no_hedging && file.hedge_requests.unwrap_or(true);
+    let mut allowed_methods = if args.allowed_methods.is_empty() {
+        file.allowed_methods
+    } else {
+        Some(args.allowed_methods)
+    };
+    if args.restricted || file.restricted.unwrap_or(false) {
+        allowed_methods
+            .get_or_insert_with(Vec::new)
+            .extend(READ_ONLY_METHODS.iter().map(|method| method.to_string()));
+    }
+    config.allowed_methods = allowed_methods;
+    config.sticky_sessions = args.sticky_sessions || file.sticky_sessions.unwrap_or(false);
+    let default_rate_limit = RateLimit::default();
+    config.rate_limit = (args.rate_limit || file.rate_limit.unwrap_or(false)).then(|| RateLimit {
+        max_concurrent_requests: args
+            .max_concurrent_requests_per_node
+            .or(file.max_concurrent_requests_per_node)
+            .unwrap_or(default_rate_limit.max_concurrent_requests),
+        requests_per_second: args
+            .requests_per_second_per_node
+            .or(file.requests_per_second_per_node)
+            .unwrap_or(default_rate_limit.requests_per_second),
+        ..default_rate_limit
+    });
+    config.consensus_check = (args.consensus_check || file.consensus_check.unwrap_or(false))
+        .then(ConsensusCheck::default);
+    config.api_token = args.api_token.or(file.api_token);
+    config.response_cache_size = args
+        .response_cache_size
+        .or(file.response_cache_size)
+        .unwrap_or(DEFAULT_RESPONSE_CACHE_SIZE);
+    config.block_poll_interval = (args.block_notifications
+        || file.block_notifications.unwrap_or(false))
+    .then_some(DEFAULT_BLOCK_POLL_INTERVAL);
+    config.discovery = (args.discovery || file.discovery.unwrap_or(false)).then(Discovery::default);
+    config.health_checks = match args
+        .health_check_interval
+        .or(file.health_check_interval_secs)
+    {
+        _ if args.no_health_checks => None,
+        Some(0) => None,
+        Some(secs) => Some(HealthCheckSchedule {
+            reliable_interval: Duration::from_secs(secs),
+            ..HealthCheckSchedule::default()
+        }),
+        None => Some(HealthCheckSchedule::default()),
+    };
+
+    let default_retention = Retention::default();
+    config.retention = Some(Retention {
+        max_age: args
+            .health_check_retention_days
+            .or(file.health_check_retention_days)
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+            .unwrap_or(default_retention.max_age),
+        max_checks_per_node: args
+            .max_health_checks_per_node
+            .or(file.max_health_checks_per_node)
+            .unwrap_or(default_retention.max_checks_per_node),
+        ..default_retention
+    });
+
+    let nodes = if args.nodes.is_empty() {
+        file.nodes
+    } else {
+        args.nodes
+    };
+    config.custom_nodes = nodes
+        .iter()
+        .map(|node| parse_node_url(node))
+        .collect::<anyhow::Result<_>>()?;
+
+    let preferred_nodes = if args.preferred_nodes.is_empty() {
+        file.preferred_nodes
+    } else {
+        args.preferred_nodes
+    };
+    config.node_weights = parse_node_weights(&file.node_weights, &preferred_nodes)?;
+
+    if let Some(command) = args.command {
+        run_command(command, config.data_dir, network).await?;
+        return Ok(());
+    }
 
     info!(
         host = config.host,
         port = config.port,
-        network = network_to_string(&args.network),
+        network = network_to_str(network),
+        data_dir = %config.data_dir.display(),
         "Starting Monero RPC Pool"
     );
 
-    if let Err(e) = run_server(config, args.network).await {
+    if let Err(e) = run_server(config, network).await {
         eprintln!("Server error: {}", e);
         std::process::exit(1);
     }
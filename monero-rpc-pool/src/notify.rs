@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::StatusCode,
+    response::Response,
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::watch;
+
+/// How long `/notify/new_block` waits for a new block before answering with the current tip.
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The top block of the chain, as announced to clients waiting for new blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewBlock {
+    /// The chain height as reported by `get_info`, i.e. the number of blocks.
+    pub height: u64,
+    pub hash: String,
+}
+
+/// Rebroadcasts the blocks seen by the pool to local clients, so wallets can refresh when a
+/// block arrives instead of polling on a fixed interval.
+pub struct BlockNotifier {
+    sender: watch::Sender<Option<NewBlock>>,
+}
+
+impl Default for BlockNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockNotifier {
+    pub fn new() -> Self {
+        Self {
+            sender: watch::Sender::new(None),
+        }
+    }
+
+    /// The latest block, updated whenever a new block is announced.
+    pub fn subscribe(&self) -> watch::Receiver<Option<NewBlock>> {
+        self.sender.subscribe()
+    }
+
+    /// Announce the block unless it is already known or the chain is already longer.
+    /// A different block at the same height (a reorg) is announced too. Returns whether
+    /// the block was announced.
+    pub fn announce(&self, block: NewBlock) -> bool {
+        self.sender.send_if_modified(|tip| {
+            let is_new = match tip {
+                Some(tip) => {
+                    block.height > tip.height
+                        || (block.height == tip.height && block.hash != tip.hash)
+                }
+                None => true,
+            };
+
+            if is_new {
+                *tip = Some(block);
+            }
+            is_new
+        })
+    }
+}
+
+/// The `/notify/new_block` long-poll endpoint.
+pub fn router<S>(notifier: Arc<BlockNotifier>) -> Router<S> {
+    Router::new()
+        .route("/notify/new_block", get(new_block_handler))
+        .with_state(notifier)
+}
+
+#[derive(Debug, Deserialize)]
+struct NewBlockQuery {
+    /// The height the client already knows about. Without it the current tip is returned
+    /// right away.
+    height: Option<u64>,
+}
+
+/// Wait until the chain is longer than the height the client knows about and return the new
+/// tip. After [`LONG_POLL_TIMEOUT`] the current tip is returned anyway, clients compare the
+/// height and poll again.
+async fn new_block_handler(
+    State(notifier): State<Arc<BlockNotifier>>,
+    Query(query): Query<NewBlockQuery>,
+) -> Response {
+    let mut receiver = notifier.subscribe();
+
+    if let Some(known) = query.height {
+        let is_new = |tip: &Option<NewBlock>| tip.as_ref().is_some_and(|tip| tip.height > known);
+        // Timing out is fine, the client gets the current tip
+        let _ = tokio::time::timeout(LONG_POLL_TIMEOUT, receiver.wait_for(is_new)).await;
+    }
+
+    let tip = receiver.borrow().clone();
+    let (status, body) = match tip {
+        Some(tip) => (StatusCode::OK, json!(tip)),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            json!({ "error": "No block seen yet" }),
+        ),
+    };
+
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: u64, hash: &str) -> NewBlock {
+        NewBlock {
+            height,
+            hash: hash.into(),
+        }
+    }
+
+    #[test]
+    fn only_announces_new_blocks() {
+        let notifier = BlockNotifier::new();
+
+        assert!(notifier.announce(block(100, "a")));
+        assert!(!notifier.announce(block(100, "a")));
+        assert!(!notifier.announce(block(99, "b")));
+        assert!(notifier.announce(block(100, "c")));
+        assert!(notifier.announce(block(101, "d")));
+        assert_eq!(*notifier.subscribe().borrow(), Some(block(101, "d")));
+    }
+}
@@ -1,10 +1,36 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use monero::Network;
 use tokio::sync::broadcast;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 use typeshare::typeshare;
 
+use crate::config::{ConsensusCheck, Discovery, HealthCheckSchedule, Retention};
 use crate::database::Database;
-use crate::types::NodeAddress;
+use crate::discovery::crawl_peer_lists;
+use crate::health::{fetch_node_info, HealthTier};
+use crate::notify::{BlockNotifier, NewBlock};
+use crate::types::{network_to_str, NodeAddress, NodeList, NodeRecord, NodeWeight};
+
+/// How many nodes are health checked at the same time.
+const HEALTH_CHECK_CONCURRENCY: usize = 8;
+
+/// How long a node stays quarantined unless a later consensus check clears it earlier.
+const QUARANTINE_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// How many of the top nodes are asked for the chain tip when polling for new blocks.
+const BLOCK_POLL_NODES: usize = 2;
+
+/// Sticky sessions that haven't sent a request for this long are forgotten.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Width of the buckets of the success rate history, which covers the last hour.
+const SUCCESS_HISTORY_BUCKET_MINUTES: u32 = 5;
 
 #[derive(Debug, Clone, serde::Serialize)]
 #[typeshare]
@@ -24,21 +50,65 @@ pub struct ReliableNodeInfo {
     pub url: String,
     pub success_rate: f64,
     pub avg_latency_ms: Option<f64>,
+    /// Latency percentiles of the successful requests in the last day.
+    pub p50_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+    pub p99_latency_ms: Option<f64>,
+    /// Success rate over the last hour, most recent bucket first. Buckets without any
+    /// requests are left out.
+    pub success_rate_history: Vec<SuccessRateSample>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[typeshare]
+pub struct SuccessRateSample {
+    /// Start of the bucket, in minutes before now.
+    pub minutes_ago: u32,
+    pub requests: u32,
+    pub success_rate: f64,
 }
 
 pub struct NodePool {
     db: Database,
-    network: String,
+    network: Network,
     status_sender: broadcast::Sender<PoolStatus>,
+    /// Nodes that disagreed with the others about the chain tip, and when that was noticed.
+    quarantined: Mutex<HashMap<NodeAddress, Instant>>,
+    /// The node each sticky session is pinned to.
+    sessions: Mutex<HashMap<String, PinnedNode>>,
+    /// When each node is due for its next health check.
+    health_checks: Mutex<HashMap<NodeAddress, ScheduledCheck>>,
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledCheck {
+    due: Instant,
+    consecutive_failures: u32,
+}
+
+#[derive(Debug, Clone)]
+struct PinnedNode {
+    node: NodeAddress,
+    last_used: Instant,
+}
+
+/// The chain tip as reported by a node's `get_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChainTip {
+    height: u64,
+    top_block_hash: String,
 }
 
 impl NodePool {
-    pub fn new(db: Database, network: String) -> (Self, broadcast::Receiver<PoolStatus>) {
+    pub fn new(db: Database, network: Network) -> (Self, broadcast::Receiver<PoolStatus>) {
         let (status_sender, status_receiver) = broadcast::channel(100);
         let pool = Self {
             db,
             network,
             status_sender,
+            quarantined: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            health_checks: Mutex::new(HashMap::new()),
         };
         (pool, status_receiver)
     }
@@ -76,18 +146,53 @@ impl NodePool {
     }
 
     pub async fn get_current_status(&self) -> Result<PoolStatus> {
-        let (total, reachable, _reliable) = self.db.get_node_stats(&self.network).await?;
-        let reliable_nodes = self.db.get_reliable_nodes(&self.network).await?;
+        let (total, reachable, _reliable) = self.db.get_node_stats(self.network).await?;
+        let reliable_nodes = self.db.get_reliable_nodes(self.network).await?;
         let (successful_checks, unsuccessful_checks) =
-            self.db.get_health_check_stats(&self.network).await?;
+            self.db.get_health_check_stats(self.network).await?;
+
+        let mut latency_percentiles: HashMap<_, _> = self
+            .db
+            .get_latency_percentiles(self.network)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut success_rate_history: HashMap<NodeAddress, Vec<SuccessRateSample>> = HashMap::new();
+        for (address, bucket, requests, successful) in self
+            .db
+            .get_success_history(self.network, SUCCESS_HISTORY_BUCKET_MINUTES)
+            .await?
+        {
+            success_rate_history
+                .entry(address)
+                .or_default()
+                .push(SuccessRateSample {
+                    minutes_ago: bucket * SUCCESS_HISTORY_BUCKET_MINUTES,
+                    requests: requests as u32,
+                    success_rate: successful as f64 / requests as f64,
+                });
+        }
 
         let top_reliable_nodes = reliable_nodes
             .into_iter()
             .take(5)
-            .map(|node| ReliableNodeInfo {
-                url: node.full_url(),
-                success_rate: node.success_rate(),
-                avg_latency_ms: node.health.avg_latency_ms,
+            .map(|node| {
+                let percentiles = latency_percentiles
+                    .remove(&node.address)
+                    .unwrap_or_default();
+
+                ReliableNodeInfo {
+                    url: node.full_url(),
+                    success_rate: node.success_rate(),
+                    avg_latency_ms: node.health.avg_latency_ms,
+                    p50_latency_ms: percentiles.p50_ms,
+                    p95_latency_ms: percentiles.p95_ms,
+                    p99_latency_ms: percentiles.p99_ms,
+                    success_rate_history: success_rate_history
+                        .remove(&node.address)
+                        .unwrap_or_default(),
+                }
             })
             .collect();
 
@@ -102,20 +207,46 @@ impl NodePool {
 
     /// Get nodes to use, with weighted selection favoring top performers
     /// The list has some randomness, but the top nodes are still more likely to be chosen
+    /// Preferred nodes that are up come first, and operator weights multiply the chances
     pub async fn get_top_reliable_nodes(&self, limit: usize) -> Result<Vec<NodeAddress>> {
         use rand::seq::SliceRandom;
 
         debug!(
             "Getting top reliable nodes for network {} (target: {})",
-            self.network, limit
+            network_to_str(self.network),
+            limit
         );
 
-        let available_nodes = self
+        // Ask for extra nodes to make up for the ones we are going to skip
+        let quarantined = self.quarantined_count();
+        let available_nodes: Vec<NodeAddress> = self
             .db
-            .get_top_nodes_by_recent_success(&self.network, limit as i64)
+            .get_top_nodes_by_recent_success(self.network, (limit + quarantined) as i64)
             .await
-            .context("Failed to get top nodes by recent success")?;
+            .context("Failed to get top nodes by recent success")?
+            .into_iter()
+            .filter(|node| !self.is_quarantined(node))
+            .take(limit)
+            .collect();
 
+        let weighted_nodes = self
+            .db
+            .get_weighted_nodes(self.network)
+            .await
+            .context("Failed to get weighted nodes")?;
+
+        // Preferred nodes go first, even if they don't have much history yet
+        let preferred_nodes: Vec<NodeAddress> = weighted_nodes
+            .iter()
+            .filter(|(node, weight)| weight.preferred && !self.is_quarantined(node))
+            .map(|(node, _)| node.clone())
+            .take(limit)
+            .collect();
+
+        let available_nodes: Vec<NodeAddress> = available_nodes
+            .into_iter()
+            .filter(|node| !preferred_nodes.contains(node))
+            .collect();
         let total_candidates = available_nodes.len();
 
         let weighted: Vec<(NodeAddress, f64)> = available_nodes
@@ -123,15 +254,19 @@ impl NodePool {
             .enumerate()
             .map(|(idx, node)| {
                 // Higher-ranked (smaller idx) ⇒ larger weight
-                let weight = 1.5_f64.powi((total_candidates - idx) as i32);
-                (node, weight)
+                let rank_weight = 1.5_f64.powi((total_candidates - idx) as i32);
+                let operator_weight = weighted_nodes
+                    .iter()
+                    .find(|(weighted_node, _)| *weighted_node == node)
+                    .map_or(1, |(_, weight)| weight.weight);
+                (node, rank_weight * operator_weight as f64)
             })
             .collect();
 
         let mut rng = rand::thread_rng();
 
         let mut candidates = weighted;
-        let mut selected_nodes = Vec::with_capacity(limit);
+        let mut selected_nodes = preferred_nodes;
 
         while selected_nodes.len() < limit && !candidates.is_empty() {
             // Choose one node based on its weight using `choose_weighted`
@@ -152,16 +287,421 @@ impl NodePool {
         debug!(
             "Pool size: {} nodes for network {} (target: {})",
             selected_nodes.len(),
-            self.network,
+            network_to_str(self.network),
             limit
         );
 
         Ok(selected_nodes)
     }
 
+    /// All nodes of the pool's network with their health, including banned ones.
+    pub async fn list_nodes(&self) -> Result<Vec<NodeRecord>> {
+        self.db.get_all_nodes(self.network).await
+    }
+
+    /// Add a custom node to the pool, returning its id.
+    pub async fn add_node(&self, address: &NodeAddress) -> Result<i64> {
+        let id = self.db.add_node(address, self.network).await?;
+        info!("Added node {} with id {}", address, id);
+        Ok(id)
+    }
+
+    /// Add the nodes that aren't known yet, e.g. the ones from the config. Returns how many
+    /// were added.
+    pub async fn add_nodes_if_new(&self, nodes: &[NodeAddress]) -> Result<usize> {
+        let mut added = 0;
+        for node in nodes {
+            if self.db.insert_node_if_new(node, self.network).await? {
+                info!("Added node {}", node);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Change how strongly the selection favors a node. Returns whether the node exists.
+    pub async fn set_node_weight(&self, id: i64, weight: NodeWeight) -> Result<bool> {
+        let updated = self.db.set_node_weight(id, self.network, weight).await?;
+        if updated {
+            info!(
+                "Set weight of node with id {} to {} (preferred: {})",
+                id, weight.weight, weight.preferred
+            );
+        }
+        Ok(updated)
+    }
+
+    /// Apply the weights from the config, adding the nodes that aren't known yet. Nodes that
+    /// were weighted by an earlier config but aren't anymore get the default weight again.
+    pub async fn apply_node_weights(&self, weights: &[(NodeAddress, NodeWeight)]) -> Result<()> {
+        self.db.reset_config_node_weights(self.network).await?;
+
+        for (node, weight) in weights {
+            self.db.insert_node_if_new(node, self.network).await?;
+            self.db
+                .set_node_weight_by_address(node, self.network, *weight)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// All nodes of the pool except the banned ones, to be imported elsewhere.
+    pub async fn export_nodes(&self) -> Result<NodeList> {
+        let nodes = self
+            .list_nodes()
+            .await?
+            .into_iter()
+            .filter(|node| !node.metadata.banned)
+            .map(|node| node.full_url())
+            .collect();
+
+        Ok(NodeList {
+            network: self.network,
+            nodes,
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Remove a node from the pool. Returns whether the node existed.
+    pub async fn remove_node(&self, id: i64) -> Result<bool> {
+        let removed = self.db.delete_node(id, self.network).await?;
+        if removed {
+            info!("Removed node with id {}", id);
+        }
+        Ok(removed)
+    }
+
+    /// Ban a node so it is never selected again. Returns whether the node existed.
+    pub async fn ban_node(&self, id: i64) -> Result<bool> {
+        let banned = self.db.ban_node(id, self.network).await?;
+        if banned {
+            info!("Banned node with id {}", id);
+        }
+        Ok(banned)
+    }
+
+    /// Ask the top nodes for their peers and add the ones serving RPC that we don't know yet.
+    /// They are only used once the health check confirmed they are on our network.
+    pub async fn discover_nodes(&self, discovery: &Discovery) -> Result<()> {
+        let nodes = self
+            .db
+            .get_top_nodes_by_recent_success(self.network, discovery.nodes_to_query as i64)
+            .await
+            .context("Failed to get nodes to query for peers")?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let peers = crawl_peer_lists(&client, &nodes, discovery.request_delay).await;
+
+        let mut added = 0;
+        for peer in peers {
+            if added >= discovery.max_new_nodes {
+                break;
+            }
+
+            if self.db.insert_node_if_new(&peer, self.network).await? {
+                debug!("Discovered node {}", peer);
+                added += 1;
+            }
+        }
+
+        info!(
+            "Discovered {} new nodes by asking {} nodes for their peers",
+            added,
+            nodes.len()
+        );
+
+        Ok(())
+    }
+
+    /// Delete the health checks that fall outside the retention policy and shrink the database.
+    pub async fn prune_history(&self, retention: &Retention) -> Result<()> {
+        let deleted = self
+            .db
+            .prune_health_checks(retention.max_age, retention.max_checks_per_node)
+            .await
+            .context("Failed to prune health checks")?;
+
+        if deleted > 0 {
+            info!("Pruned {} old health checks", deleted);
+            self.db
+                .vacuum()
+                .await
+                .context("Failed to vacuum database")?;
+        }
+
+        Ok(())
+    }
+
+    /// Call `get_info` on the nodes that are due for a health check and record what they
+    /// report, so nodes that are on the wrong network, still syncing or untrusted are no longer
+    /// selected. Nodes that weren't checked since startup are due right away.
+    pub async fn check_node_health(&self, schedule: &HealthCheckSchedule) -> Result<()> {
+        let nodes = self
+            .db
+            .get_all_nodes(self.network)
+            .await
+            .context("Failed to get nodes for health check")?;
+
+        let now = Instant::now();
+        let due: Vec<NodeRecord> = {
+            let mut scheduled = self.health_checks.lock().expect("lock not to be poisoned");
+            let known: HashSet<_> = nodes.iter().map(|node| &node.address).collect();
+            scheduled.retain(|node, _| known.contains(node));
+
+            nodes
+                .iter()
+                .filter(|node| !node.metadata.banned)
+                .filter(|node| {
+                    scheduled
+                        .get(&node.address)
+                        .is_none_or(|check| check.due <= now)
+                })
+                .cloned()
+                .collect()
+        };
+
+        if due.is_empty() {
+            return Ok(());
+        }
+        debug!("Health checking {} nodes", due.len());
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let results: Vec<Result<()>> = stream::iter(due)
+            .map(|node| {
+                let client = client.clone();
+                async move {
+                    let passed = self.check_single_node(&client, &node.address).await?;
+                    self.schedule_health_check(&node, passed, schedule);
+                    Ok(())
+                }
+            })
+            .buffer_unordered(HEALTH_CHECK_CONCURRENCY)
+            .collect()
+            .await;
+
+        for result in results {
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn schedule_health_check(
+        &self,
+        node: &NodeRecord,
+        passed: bool,
+        schedule: &HealthCheckSchedule,
+    ) {
+        let mut scheduled = self.health_checks.lock().expect("lock not to be poisoned");
+        let consecutive_failures = match passed {
+            true => 0,
+            false => {
+                scheduled
+                    .get(&node.address)
+                    .map_or(0, |check| check.consecutive_failures)
+                    + 1
+            }
+        };
+
+        let tier = HealthTier::classify(consecutive_failures, node.success_rate());
+        scheduled.insert(
+            node.address.clone(),
+            ScheduledCheck {
+                due: Instant::now() + schedule.interval(tier),
+                consecutive_failures,
+            },
+        );
+    }
+
+    /// Returns whether the node is usable.
+    async fn check_single_node(
+        &self,
+        client: &reqwest::Client,
+        node: &NodeAddress,
+    ) -> Result<bool> {
+        let port = node.port as i64;
+        let start = Instant::now();
+
+        let info = match fetch_node_info(client, node).await {
+            Ok(info) => info,
+            Err(e) => {
+                debug!("Health check of {} failed: {:#}", node, e);
+                self.record_failure(&node.scheme, &node.host, port).await?;
+                return Ok(false);
+            }
+        };
+        let latency_ms = start.elapsed().as_millis() as f64;
+
+        self.db.record_node_info(node, &info).await?;
+
+        match info.problem(self.network) {
+            None => {
+                self.record_success(&node.scheme, &node.host, port, latency_ms)
+                    .await?;
+                Ok(true)
+            }
+            Some(problem) => {
+                debug!("Node {} {}, not using it", node, problem);
+                self.record_failure(&node.scheme, &node.host, port).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Ask the top nodes for their chain tip and announce it if it is a new block. The highest
+    /// tip wins, so a single lagging node doesn't delay the announcement.
+    pub async fn poll_new_block(&self, notifier: &BlockNotifier) -> Result<()> {
+        let nodes = self.get_top_reliable_nodes(BLOCK_POLL_NODES).await?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let tips = join_all(nodes.iter().map(|node| {
+            let client = client.clone();
+            async move {
+                fetch_chain_tip(&client, node)
+                    .await
+                    .inspect_err(|e| debug!("Failed to get chain tip from {}: {:#}", node, e))
+                    .ok()
+            }
+        }))
+        .await;
+
+        if let Some(tip) = tips.into_iter().flatten().max_by_key(|tip| tip.height) {
+            let height = tip.height;
+            if notifier.announce(NewBlock {
+                height,
+                hash: tip.top_block_hash,
+            }) {
+                debug!("Announcing new block, chain height is now {}", height);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compare the chain tip of the top nodes and quarantine the ones that diverge from the
+    /// majority, so wallets don't sync against a malicious or forked node. Nodes that agree
+    /// again are released from quarantine.
+    pub async fn check_consensus(&self, check: &ConsensusCheck) -> Result<()> {
+        let nodes = self
+            .db
+            .get_top_nodes_by_recent_success(self.network, check.sample_size as i64)
+            .await
+            .context("Failed to get nodes for consensus check")?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let tips = join_all(nodes.into_iter().map(|node| {
+            let client = client.clone();
+            async move {
+                let tip = fetch_chain_tip(&client, &node).await;
+                (node, tip)
+            }
+        }))
+        .await;
+
+        let tips: Vec<(NodeAddress, ChainTip)> = tips
+            .into_iter()
+            .filter_map(|(node, tip)| match tip {
+                Ok(tip) => Some((node, tip)),
+                Err(e) => {
+                    debug!("Failed to get chain tip from {}: {:#}", node, e);
+                    None
+                }
+            })
+            .collect();
+
+        let divergent = find_divergent_nodes(&tips, check.max_height_divergence);
+
+        let mut quarantined = self.quarantined.lock().expect("lock not to be poisoned");
+        for (node, tip) in &tips {
+            if divergent.contains(node) {
+                if quarantined.insert(node.clone(), Instant::now()).is_none() {
+                    warn!(
+                        "Quarantining node {} at height {} ({}) which disagrees with the other nodes",
+                        node, tip.height, tip.top_block_hash
+                    );
+                }
+            } else if quarantined.remove(node).is_some() {
+                info!(
+                    "Node {} agrees with the other nodes again, releasing it from quarantine",
+                    node
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_quarantined(&self, node: &NodeAddress) -> bool {
+        self.quarantined
+            .lock()
+            .expect("lock not to be poisoned")
+            .get(node)
+            .is_some_and(|since| since.elapsed() < QUARANTINE_DURATION)
+    }
+
+    /// The node the session is pinned to. Pins of idle sessions and to quarantined nodes are
+    /// dropped.
+    pub fn pinned_node(&self, session: &str) -> Option<NodeAddress> {
+        let mut sessions = self.sessions.lock().expect("lock not to be poisoned");
+        let pinned = sessions.get_mut(session)?;
+
+        if pinned.last_used.elapsed() >= SESSION_IDLE_TIMEOUT || self.is_quarantined(&pinned.node) {
+            sessions.remove(session);
+            return None;
+        }
+
+        pinned.last_used = Instant::now();
+        Some(pinned.node.clone())
+    }
+
+    /// Send the following requests of the session to this node.
+    pub fn pin_node(&self, session: &str, node: NodeAddress) {
+        let mut sessions = self.sessions.lock().expect("lock not to be poisoned");
+        sessions.retain(|_, pinned| pinned.last_used.elapsed() < SESSION_IDLE_TIMEOUT);
+        sessions.insert(
+            session.to_string(),
+            PinnedNode {
+                node,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    pub fn unpin_node(&self, session: &str) {
+        self.sessions
+            .lock()
+            .expect("lock not to be poisoned")
+            .remove(session);
+    }
+
+    fn quarantined_count(&self) -> usize {
+        let mut quarantined = self.quarantined.lock().expect("lock not to be poisoned");
+        quarantined.retain(|_, since| since.elapsed() < QUARANTINE_DURATION);
+        quarantined.len()
+    }
+
     pub async fn get_pool_stats(&self) -> Result<PoolStats> {
-        let (total, reachable, reliable) = self.db.get_node_stats(&self.network).await?;
-        let reliable_nodes = self.db.get_reliable_nodes(&self.network).await?;
+        let (total, reachable, reliable) = self.db.get_node_stats(self.network).await?;
+        let reliable_nodes = self.db.get_reliable_nodes(self.network).await?;
 
         let avg_reliable_latency = if reliable_nodes.is_empty() {
             None
@@ -191,6 +731,51 @@ impl NodePool {
     }
 }
 
+async fn fetch_chain_tip(client: &reqwest::Client, node: &NodeAddress) -> Result<ChainTip> {
+    let info = fetch_node_info(client, node).await?;
+
+    Ok(ChainTip {
+        height: info.height,
+        top_block_hash: info.top_block_hash,
+    })
+}
+
+/// Find the nodes whose height is more than `max_height_divergence` blocks away from the median,
+/// or that report a different top block than the majority of the nodes at the median height.
+///
+/// At least three answers are needed to tell which side is in the minority.
+fn find_divergent_nodes(
+    tips: &[(NodeAddress, ChainTip)],
+    max_height_divergence: u64,
+) -> Vec<NodeAddress> {
+    if tips.len() < 3 {
+        return Vec::new();
+    }
+
+    let mut heights: Vec<u64> = tips.iter().map(|(_, tip)| tip.height).collect();
+    heights.sort_unstable();
+    let median_height = heights[heights.len() / 2];
+
+    let mut hash_counts: HashMap<&str, usize> = HashMap::new();
+    for (_, tip) in tips.iter().filter(|(_, tip)| tip.height == median_height) {
+        *hash_counts.entry(&tip.top_block_hash).or_default() += 1;
+    }
+    let at_median_height: usize = hash_counts.values().sum();
+    let majority_hash = hash_counts
+        .into_iter()
+        .find(|(_, count)| count * 2 > at_median_height)
+        .map(|(hash, _)| hash);
+
+    tips.iter()
+        .filter(|(_, tip)| {
+            tip.height.abs_diff(median_height) > max_height_divergence
+                || (tip.height == median_height
+                    && majority_hash.is_some_and(|hash| hash != tip.top_block_hash))
+        })
+        .map(|(node, _)| node.clone())
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct PoolStats {
     pub total_nodes: i64,
@@ -208,3 +793,77 @@ impl PoolStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tip(port: u16, height: u64, top_block_hash: &str) -> (NodeAddress, ChainTip) {
+        (
+            NodeAddress::new("http".into(), "node".into(), port),
+            ChainTip {
+                height,
+                top_block_hash: top_block_hash.into(),
+            },
+        )
+    }
+
+    #[test]
+    fn finds_nodes_behind_or_ahead_of_the_majority() {
+        let tips = [
+            tip(1, 3_000_000, "a"),
+            tip(2, 3_000_001, "b"),
+            tip(3, 2_999_000, "c"),
+            tip(4, 3_000_000, "a"),
+        ];
+
+        let divergent = find_divergent_nodes(&tips, 10);
+
+        assert_eq!(divergent, vec![tips[2].0.clone()]);
+    }
+
+    #[test]
+    fn finds_nodes_on_a_different_fork() {
+        let tips = [
+            tip(1, 3_000_000, "a"),
+            tip(2, 3_000_000, "a"),
+            tip(3, 3_000_000, "fork"),
+        ];
+
+        let divergent = find_divergent_nodes(&tips, 10);
+
+        assert_eq!(divergent, vec![tips[2].0.clone()]);
+    }
+
+    #[test]
+    fn needs_at_least_three_nodes() {
+        let tips = [tip(1, 3_000_000, "a"), tip(2, 1, "b")];
+
+        assert!(find_divergent_nodes(&tips, 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn resets_weights_that_are_no_longer_configured() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(data_dir.path().to_path_buf()).await.unwrap();
+        let (pool, _) = NodePool::new(db.clone(), Network::Mainnet);
+
+        let node = |port| NodeAddress::new("http".into(), "node".into(), port);
+        let heavy = NodeWeight {
+            weight: 5,
+            preferred: false,
+        };
+
+        pool.apply_node_weights(&[(node(1), heavy), (node(2), heavy)])
+            .await
+            .unwrap();
+        let id = pool.add_node(&node(3)).await.unwrap();
+        pool.set_node_weight(id, heavy).await.unwrap();
+
+        // Node 2 was removed from the config, the weight set through the API stays
+        pool.apply_node_weights(&[(node(1), heavy)]).await.unwrap();
+
+        let weighted = db.get_weighted_nodes(Network::Mainnet).await.unwrap();
+        assert_eq!(weighted, vec![(node(1), heavy), (node(3), heavy)]);
+    }
+}
@@ -1,16 +1,31 @@
 use axum::{
     body::Body,
-    extract::State,
-    http::{HeaderMap, Method, StatusCode},
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
     response::Response,
 };
+use chrono::Utc;
+use futures::stream::{FuturesUnordered, StreamExt};
 use serde_json::json;
-use std::time::Instant;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info_span, Instrument};
 use uuid::Uuid;
 
+use crate::cache::{cache_key, Cacheable};
+use crate::limiter::NodePermit;
+use crate::recent::RequestRecord;
+use crate::types::NodeAddress;
 use crate::AppState;
 
+/// Clients that send this header are pinned to the same node for as long as they send the
+/// same value. Otherwise their connection identifies the sticky session.
+pub const SESSION_HEADER: &str = "x-rpc-pool-session";
+
+/// Added to every proxied response, names the node that served it or `cache`.
+pub const NODE_HEADER: &str = "x-monero-pool-node";
+
 #[derive(Debug, Clone)]
 enum HandlerError {
     NoNodes,
@@ -59,17 +74,92 @@ fn extract_jsonrpc_method(body: &[u8]) -> Option<String> {
     None
 }
 
+/// Whether the pool forwards the request: JSON-RPC requests are allowed by their method, the
+/// others by their path without the leading slash.
+fn is_method_allowed(
+    allowed_methods: Option<&HashSet<String>>,
+    path: &str,
+    jsonrpc_method: Option<&str>,
+) -> bool {
+    let Some(allowed_methods) = allowed_methods else {
+        return true;
+    };
+
+    let method = match path {
+        "/json_rpc" => jsonrpc_method,
+        _ => Some(path.trim_start_matches('/')),
+    };
+    method.is_some_and(|method| allowed_methods.contains(method))
+}
+
+/// Number of nodes a hedged request is sent to at the same time.
+const HEDGE_NODES: usize = 2;
+
+/// Whether the request only reads data, so sending it to several nodes at once is harmless.
+fn is_hedgeable(path: &str, jsonrpc_method: Option<&str>) -> bool {
+    match path {
+        "/json_rpc" => matches!(jsonrpc_method, Some("get_info" | "get_block")),
+        "/get_info" | "/getinfo" | "/get_transactions" | "/gettransactions" => true,
+        _ => false,
+    }
+}
+
+/// Whether the response is passed through to the client as it arrives instead of being
+/// buffered. These binary endpoints return large batches of blocks during wallet sync.
+fn is_streamed(path: &str) -> bool {
+    matches!(
+        path,
+        "/get_blocks.bin" | "/getblocks.bin" | "/get_hashes.bin" | "/gethashes.bin"
+    )
+}
+
+/// How long an unused connection to a node is kept open.
+const IDLE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// HTTP clients for the requests to the nodes. They keep the connections to every node open
+/// between requests (and use HTTP/2 with nodes that offer it), instead of paying for a new
+/// TCP and TLS handshake on every request.
+#[derive(Debug, Clone)]
+pub struct UpstreamClients {
+    buffered: reqwest::Client,
+    streamed: reqwest::Client,
+}
+
+impl UpstreamClients {
+    pub fn new() -> reqwest::Result<Self> {
+        let builder = || {
+            reqwest::Client::builder()
+                .pool_idle_timeout(IDLE_CONNECTION_TIMEOUT)
+                .tcp_keepalive(Duration::from_secs(60))
+        };
+
+        Ok(Self {
+            buffered: builder().timeout(Duration::from_secs(30)).build()?,
+            // Streamed bodies can take longer than the timeout to arrive in full, so only give
+            // up on them when the node stops sending data.
+            streamed: builder().read_timeout(Duration::from_secs(30)).build()?,
+        })
+    }
+
+    fn for_path(&self, path: &str) -> &reqwest::Client {
+        if is_streamed(path) {
+            &self.streamed
+        } else {
+            &self.buffered
+        }
+    }
+}
+
 async fn raw_http_request(
+    state: &AppState,
     node_url: (String, String, i64),
     path: &str,
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
+    permit: Option<NodePermit>,
 ) -> Result<Response, HandlerError> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| HandlerError::RequestError(format!("{:#?}", e)))?;
+    let client = state.upstream_clients.for_path(path);
 
     let (scheme, host, port) = &node_url;
     let url = format!("{}://{}:{}{}", scheme, host, port, path);
@@ -104,19 +194,23 @@ async fn raw_http_request(
                 | "trailers"
         );
 
+        // The session only concerns the pool, nodes don't need a way to tell our clients apart.
+        let is_session_header = header_name_lc == SESSION_HEADER;
+
         // If we are not forwarding a body (e.g. GET request) then forwarding `content-length` or
         // `content-type` with an absent body makes many Monero nodes hang waiting for bytes and
         // eventually close the connection.  This manifests as the time-outs we have observed.
         let is_body_header_without_body =
             body.is_none() && matches!(header_name_lc.as_str(), "content-length" | "content-type");
 
-        if !is_hop_by_hop && !is_body_header_without_body {
+        if !is_hop_by_hop && !is_session_header && !is_body_header_without_body {
             if let Ok(header_value) = std::str::from_utf8(value.as_bytes()) {
                 request_builder = request_builder.header(header_name, header_value);
             }
         }
     }
 
+    let start_time = Instant::now();
     let response = request_builder
         .send()
         .await
@@ -126,11 +220,18 @@ async fn raw_http_request(
     let status = response.status();
     let response_headers = response.headers().clone();
 
-    let body_bytes = response.bytes().await.map_err(|e| {
-        HandlerError::RequestError(format!("Failed to read response body: {:#?}", e))
-    })?;
+    // A failure while streaming can't be retried with another node anymore, the client
+    // sees a truncated body instead.
+    let response_body = if is_streamed(path) {
+        stream_body(state, node_url.clone(), response, permit, start_time)
+    } else {
+        let body_bytes = response.bytes().await.map_err(|e| {
+            HandlerError::RequestError(format!("Failed to read response body: {:#?}", e))
+        })?;
+        Body::from(body_bytes)
+    };
 
-    let mut axum_response = Response::new(Body::from(body_bytes));
+    let mut axum_response = Response::new(response_body);
     *axum_response.status_mut() =
         StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
 
@@ -146,9 +247,51 @@ async fn raw_http_request(
         }
     }
 
+    if let Ok(node) = HeaderValue::from_str(&format!("{}://{}:{}", scheme, host, port)) {
+        axum_response.headers_mut().insert(NODE_HEADER, node);
+    }
+
     Ok(axum_response)
 }
 
+/// Stream the body of the response to the client. The node's permit is held until the body
+/// is done, and only then the request is recorded as a success with the time the whole body
+/// took, like the buffered responses are.
+fn stream_body(
+    state: &AppState,
+    node_url: (String, String, i64),
+    response: reqwest::Response,
+    permit: Option<NodePermit>,
+    start_time: Instant,
+) -> Body {
+    let state = state.clone();
+    let chunks = Box::pin(response.bytes_stream());
+
+    let stream = futures::stream::unfold(Some((chunks, permit)), move |streaming| {
+        let state = state.clone();
+        let (scheme, host, port) = node_url.clone();
+
+        async move {
+            let (mut chunks, permit) = streaming?;
+
+            match chunks.next().await {
+                Some(Ok(chunk)) => Some((Ok(chunk), Some((chunks, permit)))),
+                Some(Err(e)) => {
+                    record_failure(&state, &scheme, &host, port).await;
+                    Some((Err(e), None))
+                }
+                None => {
+                    let latency_ms = start_time.elapsed().as_millis() as f64;
+                    record_success(&state, &scheme, &host, port, latency_ms).await;
+                    None
+                }
+            }
+        }
+    });
+
+    Body::from_stream(stream)
+}
+
 async fn record_success(state: &AppState, scheme: &str, host: &str, port: i64, latency_ms: f64) {
     if let Err(e) = state
         .node_pool
@@ -171,16 +314,37 @@ async fn record_failure(state: &AppState, scheme: &str, host: &str, port: i64) {
     }
 }
 
+/// Take a share of the node's rate limit, if there is one. Fails if the node has used up its
+/// share, the request should go to another node then.
+fn acquire_node(
+    state: &AppState,
+    node: &(String, String, i64),
+) -> Result<Option<NodePermit>, HandlerError> {
+    let Some(limiter) = &state.node_limiter else {
+        return Ok(None);
+    };
+
+    let address = NodeAddress::new(node.0.clone(), node.1.clone(), node.2 as u16);
+    limiter
+        .try_acquire(&address)
+        .map(Some)
+        .ok_or_else(|| HandlerError::RequestError("Node is at its rate limit".to_string()))
+}
+
+/// Send the request to one node. The permit of the node is released once the response body
+/// is done, which for streamed responses is after this returns.
 async fn single_raw_request(
+    state: &AppState,
     node_url: (String, String, i64),
     path: &str,
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
+    permit: Option<NodePermit>,
 ) -> Result<(Response, (String, String, i64), f64), HandlerError> {
     let start_time = Instant::now();
 
-    match raw_http_request(node_url.clone(), path, method, headers, body).await {
+    match raw_http_request(state, node_url.clone(), path, method, headers, body, permit).await {
         Ok(response) => {
             let elapsed = start_time.elapsed();
             let latency_ms = elapsed.as_millis() as f64;
@@ -217,13 +381,15 @@ async fn single_raw_request(
     }
 }
 
+/// Try the nodes one by one until one of them answers, except for the `tried` nodes.
 async fn sequential_requests(
     state: &AppState,
     path: &str,
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
-) -> Result<Response, HandlerError> {
+    tried: &[(String, String, i64)],
+) -> Result<(Response, (String, String, i64)), HandlerError> {
     const POOL_SIZE: usize = 20;
 
     // Extract JSON-RPC method for better logging
@@ -251,6 +417,7 @@ async fn sequential_requests(
         let pool: Vec<(String, String, i64)> = nodes
             .into_iter()
             .map(|node| (node.scheme, node.host, node.port as i64))
+            .filter(|node| !tried.contains(node))
             .collect();
 
         pool
@@ -262,9 +429,20 @@ async fn sequential_requests(
 
     // Try nodes one by one sequentially
     for node in available_pool.iter().take(POOL_SIZE) {
-        tried_nodes += 1;
         let node_display = format!("{}://{}:{}", node.0, node.1, node.2);
 
+        // Spill over to the next node instead of getting banned by this one
+        let permit = match acquire_node(state, node) {
+            Ok(permit) => permit,
+            Err(e) => {
+                debug!("Skipping node {}: {}", node_display, e);
+                collected_errors.push((node_display, e.to_string()));
+                continue;
+            }
+        };
+
+        tried_nodes += 1;
+
         match &jsonrpc_method {
             Some(rpc_method) => debug!(
                 "Trying {} request to {} (JSON-RPC: {}) - attempt {} of {}",
@@ -283,7 +461,7 @@ async fn sequential_requests(
             ),
         }
 
-        match single_raw_request(node.clone(), path, method, headers, body).await {
+        match single_raw_request(state, node.clone(), path, method, headers, body, permit).await {
             Ok((response, winning_node, latency_ms)) => {
                 let (scheme, host, port) = &winning_node;
                 let winning_node_display = format!("{}://{}:{}", scheme, host, port);
@@ -299,9 +477,12 @@ async fn sequential_requests(
                     ),
                 }
 
-                record_success(state, &node.0, &node.1, node.2, latency_ms).await;
+                // Streamed responses are recorded once their body is done
+                if !is_streamed(path) {
+                    record_success(state, &node.0, &node.1, node.2, latency_ms).await;
+                }
 
-                return Ok(response);
+                return Ok((response, winning_node));
             }
             Err(e) => {
                 collected_errors.push((node_display.clone(), e.to_string()));
@@ -343,17 +524,240 @@ async fn sequential_requests(
     Err(HandlerError::AllRequestsFailed(collected_errors))
 }
 
+/// Send the request to the top [`HEDGE_NODES`] nodes concurrently and return the first
+/// successful response. The requests still in flight are dropped once one succeeds.
+///
+/// The nodes the request was sent to are added to `tried`.
+async fn hedged_requests(
+    state: &AppState,
+    path: &str,
+    method: &str,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+    tried: &mut Vec<(String, String, i64)>,
+) -> Result<Response, HandlerError> {
+    let nodes = state
+        .node_pool
+        .get_top_reliable_nodes(HEDGE_NODES)
+        .await
+        .map_err(|e| HandlerError::PoolError(e.to_string()))?;
+
+    if nodes.is_empty() {
+        return Err(HandlerError::NoNodes);
+    }
+
+    let mut collected_errors: Vec<(String, String)> = Vec::new();
+
+    let mut in_flight = FuturesUnordered::new();
+    for node in nodes {
+        let node_url = (node.scheme, node.host, node.port as i64);
+
+        let permit = match acquire_node(state, &node_url) {
+            Ok(permit) => permit,
+            Err(e) => {
+                let node_display = format!("{}://{}:{}", node_url.0, node_url.1, node_url.2);
+                collected_errors.push((node_display, e.to_string()));
+                continue;
+            }
+        };
+
+        tried.push(node_url.clone());
+        in_flight.push(async move {
+            let result =
+                single_raw_request(state, node_url.clone(), path, method, headers, body, permit)
+                    .await;
+            (node_url, result)
+        });
+    }
+
+    while let Some((node, result)) = in_flight.next().await {
+        let node_display = format!("{}://{}:{}", node.0, node.1, node.2);
+
+        match result {
+            Ok((response, _, latency_ms)) => {
+                debug!(
+                    "{} response from {} ({}ms) - SUCCESS in hedged request",
+                    method, node_display, latency_ms
+                );
+
+                if !is_streamed(path) {
+                    record_success(state, &node.0, &node.1, node.2, latency_ms).await;
+                }
+
+                return Ok(response);
+            }
+            Err(e) => {
+                debug!(
+                    "Hedged request failed with node {} with error {}",
+                    node_display, e
+                );
+
+                record_failure(state, &node.0, &node.1, node.2).await;
+
+                collected_errors.push((node_display, e.to_string()));
+            }
+        }
+    }
+
+    Err(HandlerError::AllRequestsFailed(collected_errors))
+}
+
+/// Send the request to the node the session is pinned to. If that fails, or the session isn't
+/// pinned yet, the nodes are tried one by one and the session is pinned to the one that
+/// answers.
+async fn session_request(
+    state: &AppState,
+    session: &str,
+    path: &str,
+    method: &str,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+) -> Result<Response, HandlerError> {
+    if let Some(node) = state.node_pool.pinned_node(session) {
+        let node_url = (node.scheme.clone(), node.host.clone(), node.port as i64);
+
+        // Only this request goes elsewhere, the session stays pinned
+        let Ok(permit) = acquire_node(state, &node_url) else {
+            debug!(
+                "Pinned node {} is at its rate limit - using another node for this request",
+                node
+            );
+            return sequential_requests(state, path, method, headers, body, &[])
+                .await
+                .map(|(response, _)| response);
+        };
+
+        match single_raw_request(state, node_url.clone(), path, method, headers, body, permit).await
+        {
+            Ok((response, _, latency_ms)) => {
+                debug!(
+                    "{} response from pinned node {} ({}ms)",
+                    method, node, latency_ms
+                );
+
+                if !is_streamed(path) {
+                    record_success(state, &node_url.0, &node_url.1, node_url.2, latency_ms).await;
+                }
+
+                return Ok(response);
+            }
+            Err(e) => {
+                debug!(
+                    "Pinned node {} failed with error {} - pinning another node",
+                    node, e
+                );
+
+                record_failure(state, &node_url.0, &node_url.1, node_url.2).await;
+                state.node_pool.unpin_node(session);
+            }
+        }
+    }
+
+    let (response, (scheme, host, port)) =
+        sequential_requests(state, path, method, headers, body, &[]).await?;
+    state
+        .node_pool
+        .pin_node(session, NodeAddress::new(scheme, host, port as u16));
+
+    Ok(response)
+}
+
+/// Hedge the request if it is safe to do so, falling back to trying the nodes one by one.
+/// Requests of sticky sessions always go to the pinned node instead.
+async fn forward_request(
+    state: &AppState,
+    session: Option<&str>,
+    path: &str,
+    method: &str,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+) -> Result<Response, HandlerError> {
+    if let Some(session) = session {
+        return session_request(state, session, path, method, headers, body).await;
+    }
+
+    let jsonrpc_method = match (path, body) {
+        ("/json_rpc", Some(body_data)) => extract_jsonrpc_method(body_data),
+        _ => None,
+    };
+
+    let mut tried = Vec::new();
+    let mut hedge_error = None;
+
+    if state.hedge_requests && is_hedgeable(path, jsonrpc_method.as_deref()) {
+        match hedged_requests(state, path, method, headers, body, &mut tried).await {
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                debug!(
+                    "Hedged request failed ({}) - falling back to sequential requests",
+                    e
+                );
+                hedge_error = Some(e);
+            }
+        }
+    }
+
+    // The failures of the hedged nodes are already recorded, so we don't ask them again
+    let result = sequential_requests(state, path, method, headers, body, &tried)
+        .await
+        .map(|(response, _)| response);
+
+    match (result, hedge_error) {
+        (Err(HandlerError::NoNodes), Some(hedge_error)) => Err(hedge_error),
+        (result, _) => result,
+    }
+}
+
+/// Serve requests for immutable data from the cache if possible, otherwise forward them and
+/// remember the response.
+async fn cached_request(
+    state: &AppState,
+    session: Option<&str>,
+    path: &str,
+    method: &str,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+) -> Result<Response, HandlerError> {
+    let (cache, cacheable) = match (&state.response_cache, Cacheable::from_request(path, body)) {
+        (Some(cache), Some(cacheable)) => (cache, cacheable),
+        _ => return forward_request(state, session, path, method, headers, body).await,
+    };
+
+    let key = cache_key(path, body);
+    if let Some(mut response) = cache.get(&key) {
+        debug!("Serving {} request to {} from cache", method, path);
+        response
+            .headers_mut()
+            .insert(NODE_HEADER, HeaderValue::from_static("cache"));
+        return Ok(response);
+    }
+
+    let response = forward_request(state, session, path, method, headers, body).await?;
+
+    let (parts, body_stream) = response.into_parts();
+    let body_bytes = axum::body::to_bytes(body_stream, usize::MAX)
+        .await
+        .map_err(|e| HandlerError::RequestError(format!("{:#?}", e)))?;
+
+    if cache.insert(key, cacheable, parts.status, &parts.headers, &body_bytes) {
+        debug!("Cached response to {} request to {}", method, path);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body_bytes)))
+}
+
 /// Forward a request to the node pool, returning either a successful response or a simple
 /// `500` with text "All nodes failed".  Keeps the error handling logic in one place so the
 /// public handlers stay readable.
 async fn proxy_request(
     state: &AppState,
+    session: Option<&str>,
     path: &str,
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
 ) -> Response {
-    match sequential_requests(state, path, method, headers, body).await {
+    match cached_request(state, session, path, method, headers, body).await {
         Ok(res) => res,
         Err(handler_error) => {
             let error_response = match &handler_error {
@@ -411,15 +815,32 @@ async fn proxy_request(
     }
 }
 
+/// Identify the sticky session of a request by its [`SESSION_HEADER`], falling back to the
+/// client's address and port (i.e. its connection).
+fn session_id(headers: &HeaderMap, connection: Option<SocketAddr>) -> Option<String> {
+    headers
+        .get(SESSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(|value| format!("header:{}", value))
+        .or_else(|| connection.map(|addr| format!("connection:{}", addr)))
+}
+
 #[axum::debug_handler]
 pub async fn proxy_handler(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     method: Method,
     uri: axum::http::Uri,
     headers: HeaderMap,
     body: axum::body::Bytes,
 ) -> Response {
+    let session = state
+        .sticky_sessions
+        .then(|| session_id(&headers, connect_info.map(|ConnectInfo(addr)| addr)))
+        .flatten();
     let body_size = body.len();
+    let start = Instant::now();
     let request_id = Uuid::new_v4();
     let path = uri.path().to_string();
     let method_str = method.to_string();
@@ -447,7 +868,52 @@ pub async fn proxy_handler(
             None => debug!("Proxying {} {} ({} bytes)", method, path, body_size),
         }
 
-        proxy_request(&state, &path, method.as_str(), &headers, body_option).await
+        let response = if !is_method_allowed(
+            state.allowed_methods.as_deref(),
+            &path,
+            jsonrpc_method.as_deref(),
+        ) {
+            debug!("Refusing request for a method that isn't allowed");
+
+            let error_response = json!({
+                "error": "Method not allowed",
+                "details": {
+                    "type": "MethodNotAllowed",
+                    "message": "The pool is configured not to forward this request"
+                }
+            });
+            Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .header("content-type", "application/json")
+                .body(Body::from(error_response.to_string()))
+                .unwrap_or_else(|_| Response::new(Body::empty()))
+        } else {
+            proxy_request(
+                &state,
+                session.as_deref(),
+                &path,
+                method.as_str(),
+                &headers,
+                body_option,
+            )
+            .await
+        };
+
+        state.recent_requests.record(RequestRecord {
+            timestamp: Utc::now(),
+            method: method.to_string(),
+            path,
+            jsonrpc_method,
+            node: response
+                .headers()
+                .get(NODE_HEADER)
+                .and_then(|node| node.to_str().ok())
+                .map(str::to_string),
+            status: response.status().as_u16(),
+            latency_ms: start.elapsed().as_millis() as f64,
+        });
+
+        response
     }
     .instrument(info_span!("proxy_request",
         request_id = %request_id,
@@ -493,3 +959,47 @@ pub async fn stats_handler(State(state): State<AppState>) -> Response {
     .instrument(info_span!("stats_request"))
     .await
 }
+
+/// The latest proxied requests, newest first, with the node that served each of them.
+#[axum::debug_handler]
+pub async fn recent_requests_handler(State(state): State<AppState>) -> Response {
+    let requests = json!({ "requests": state.recent_requests.latest() });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "application/json")
+        .body(Body::from(requests.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_forwards_allowed_methods() {
+        let allowed: HashSet<String> = ["get_info", "get_blocks.bin"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert!(is_method_allowed(None, "/send_raw_transaction", None));
+        assert!(is_method_allowed(
+            Some(&allowed),
+            "/json_rpc",
+            Some("get_info")
+        ));
+        assert!(is_method_allowed(Some(&allowed), "/get_blocks.bin", None));
+        assert!(!is_method_allowed(
+            Some(&allowed),
+            "/json_rpc",
+            Some("relay_tx")
+        ));
+        assert!(!is_method_allowed(Some(&allowed), "/json_rpc", None));
+        assert!(!is_method_allowed(
+            Some(&allowed),
+            "/send_raw_transaction",
+            None
+        ));
+    }
+}
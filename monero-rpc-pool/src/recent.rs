@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// How many proxied requests `/stats/requests` remembers.
+pub const RECENT_REQUESTS_CAPACITY: usize = 200;
+
+/// What happened to a single proxied request.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestRecord {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub jsonrpc_method: Option<String>,
+    /// The node that answered, `cache` if the response came from the cache and `None` if no
+    /// node answered.
+    pub node: Option<String>,
+    /// The status code returned to the client.
+    pub status: u16,
+    /// Time until the response headers were ready, including retries with other nodes.
+    pub latency_ms: f64,
+}
+
+/// The latest proxied requests, so reports of failing wallet requests can be traced back to
+/// the node that served them.
+pub struct RecentRequests {
+    capacity: usize,
+    requests: Mutex<VecDeque<RequestRecord>>,
+}
+
+impl RecentRequests {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            requests: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Remember the request, forgetting the oldest one once the buffer is full.
+    pub fn record(&self, request: RequestRecord) {
+        let mut requests = self.requests.lock().expect("lock not to be poisoned");
+        if requests.len() == self.capacity {
+            requests.pop_front();
+        }
+        requests.push_back(request);
+    }
+
+    /// The remembered requests, newest first.
+    pub fn latest(&self) -> Vec<RequestRecord> {
+        let requests = self.requests.lock().expect("lock not to be poisoned");
+        requests.iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> RequestRecord {
+        RequestRecord {
+            timestamp: Utc::now(),
+            method: "POST".into(),
+            path: path.into(),
+            jsonrpc_method: None,
+            node: Some("http://node:18081".into()),
+            status: 200,
+            latency_ms: 12.0,
+        }
+    }
+
+    #[test]
+    fn keeps_the_latest_requests() {
+        let recent = RecentRequests::new(2);
+
+        recent.record(request("/get_info"));
+        recent.record(request("/get_height"));
+        recent.record(request("/get_blocks.bin"));
+
+        let paths: Vec<_> = recent
+            .latest()
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(paths, vec!["/get_blocks.bin", "/get_height"]);
+    }
+}
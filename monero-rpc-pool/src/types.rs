@@ -1,17 +1,74 @@
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use monero::Network;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::net::IpAddr;
+
+/// The name under which a network is stored in the `monero_nodes.network` column.
+pub fn network_to_str(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "mainnet",
+        Network::Stagenet => "stagenet",
+        Network::Testnet => "testnet",
+    }
+}
+
+/// Inverse of [`network_to_str`].
+pub fn network_from_str(network: &str) -> Result<Network> {
+    Ok(match network {
+        "mainnet" => Network::Mainnet,
+        "stagenet" => Network::Stagenet,
+        "testnet" => Network::Testnet,
+        _ => bail!("Unknown network `{}`", network),
+    })
+}
+
+/// (De)serializes a [`Network`] the same way it is stored in the database.
+mod network_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(network: &Network, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(network_to_str(*network))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Network, D::Error> {
+        let network = String::deserialize(deserializer)?;
+        network_from_str(&network).map_err(serde::de::Error::custom)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "UnnormalizedNodeAddress")]
 pub struct NodeAddress {
     pub scheme: String, // "http" or "https"
     pub host: String,
     pub port: u16,
 }
 
+/// A [`NodeAddress`] as sent to the API, normalized by [`NodeAddress::new`] when deserialized.
+#[derive(Deserialize)]
+struct UnnormalizedNodeAddress {
+    scheme: String,
+    host: String,
+    port: u16,
+}
+
+impl From<UnnormalizedNodeAddress> for NodeAddress {
+    fn from(address: UnnormalizedNodeAddress) -> Self {
+        NodeAddress::new(address.scheme, address.host, address.port)
+    }
+}
+
 impl NodeAddress {
+    /// Different spellings of the same node (case, a trailing dot, IPv6 with or without
+    /// brackets) result in the same address, so a node doesn't end up in the database twice.
     pub fn new(scheme: String, host: String, port: u16) -> Self {
-        Self { scheme, host, port }
+        Self {
+            scheme: scheme.to_ascii_lowercase(),
+            host: normalize_host(&host),
+            port,
+        }
     }
 
     pub fn full_url(&self) -> String {
@@ -19,6 +76,22 @@ impl NodeAddress {
     }
 }
 
+/// Lowercase the host and drop the trailing dot of a fully qualified name. IP addresses are
+/// written in their canonical form, IPv6 addresses in brackets so they can be put into a URL.
+fn normalize_host(host: &str) -> String {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let unbracketed = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(&host);
+
+    match unbracketed.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.to_string(),
+        Ok(IpAddr::V6(ip)) => format!("[{}]", ip),
+        Err(_) => host,
+    }
+}
+
 impl fmt::Display for NodeAddress {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}://{}:{}", self.scheme, self.host, self.port)
@@ -28,16 +101,48 @@ impl fmt::Display for NodeAddress {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeMetadata {
     pub id: i64,
-    pub network: String, // "mainnet", "stagenet", or "testnet"
+    #[serde(with = "network_serde")]
+    pub network: Network,
     pub first_seen_at: DateTime<Utc>,
+    /// Banned nodes are never selected by the pool.
+    pub banned: bool,
+    #[serde(flatten)]
+    pub weight: NodeWeight,
 }
 
 impl NodeMetadata {
-    pub fn new(id: i64, network: String, first_seen_at: DateTime<Utc>) -> Self {
+    pub fn new(
+        id: i64,
+        network: Network,
+        first_seen_at: DateTime<Utc>,
+        banned: bool,
+        weight: NodeWeight,
+    ) -> Self {
         Self {
             id,
             network,
             first_seen_at,
+            banned,
+            weight,
+        }
+    }
+}
+
+/// How strongly the pool favors a node, as set by an operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeWeight {
+    /// Multiplies the chance of the node being picked. At least 1.
+    pub weight: u32,
+    /// Preferred nodes are used before all others as long as their last request succeeded.
+    pub preferred: bool,
+}
+
+impl Default for NodeWeight {
+    fn default() -> Self {
+        Self {
+            weight: 1,
+            preferred: false,
         }
     }
 }
@@ -85,6 +190,41 @@ impl NodeHealthStats {
     }
 }
 
+/// Nearest-rank latency percentiles of a node's successful requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+/// A list of nodes to share between installs, see `GET /api/nodes/export`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeList {
+    #[serde(with = "network_serde")]
+    pub network: Network,
+    /// Node URLs like `https://node.example.com:18089`.
+    pub nodes: Vec<String>,
+}
+
+impl NodeList {
+    /// Parse the node URLs, making sure the list is meant for the given network.
+    pub fn addresses(&self, network: Network) -> Result<Vec<NodeAddress>> {
+        if self.network != network {
+            bail!(
+                "The nodes are for {}, not {}",
+                network_to_str(self.network),
+                network_to_str(network)
+            );
+        }
+
+        self.nodes
+            .iter()
+            .map(|node| crate::config::parse_node_url(node))
+            .collect()
+    }
+}
+
 /// A complete node record combining address, metadata, and health stats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeRecord {
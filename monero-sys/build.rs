@@ -21,15 +21,36 @@ macro_rules! embedded_patch {
 }
 
 /// Embedded patches applied at compile time
-const EMBEDDED_PATCHES: &[EmbeddedPatch] = &[embedded_patch!(
-    "wallet2_api_allow_subtract_from_fee",
-    "Adds subtract_fee_from_outputs parameter to wallet2_api transaction creation methods",
-    "patches/wallet2_api_allow_subtract_from_fee.patch"
-)];
+const EMBEDDED_PATCHES: &[EmbeddedPatch] = &[
+    embedded_patch!(
+        "wallet2_api_allow_subtract_from_fee",
+        "Adds subtract_fee_from_outputs parameter to wallet2_api transaction creation methods",
+        "patches/wallet2_api_allow_subtract_from_fee.patch"
+    ),
+    embedded_patch!(
+        "wallet2_api_sweep_below_and_single",
+        "Adds sweep-below-threshold and sweep-single-output transaction creation to wallet2_api",
+        "patches/wallet2_api_sweep_below_and_single.patch"
+    ),
+    embedded_patch!(
+        "wallet2_api_pending_transaction_details",
+        "Exposes the weight and destinations of pending transactions through wallet2_api",
+        "patches/wallet2_api_pending_transaction_details.patch"
+    ),
+    embedded_patch!(
+        "wallet2_api_fee_per_byte",
+        "Exposes the fee per byte at a given priority through wallet2_api",
+        "patches/wallet2_api_fee_per_byte.patch"
+    ),
+];
 
 fn main() {
     let is_github_actions: bool = std::env::var("GITHUB_ACTIONS").is_ok();
     let is_docker_build: bool = std::env::var("DOCKER_BUILD").is_ok();
+    // Hardware wallet support pulls in hidapi and libusb, so it's opt-in.
+    let hardware_wallets: bool = std::env::var("CARGO_FEATURE_HARDWARE_WALLETS").is_ok();
+    let device_support = if hardware_wallets { "ON" } else { "OFF" };
+    let disable_device_packages = if hardware_wallets { "OFF" } else { "ON" };
 
     // Eerun this when the bridge.rs or static_bridge.h file changes.
     println!("cargo:rerun-if-changed=src/bridge.rs");
@@ -58,20 +79,20 @@ fn main() {
         .define("BUILD_TESTS", "OFF")
         .define("Boost_USE_STATIC_LIBS", "ON")
         .define("Boost_USE_STATIC_RUNTIME", "ON")
-        //// Disable support for ALL hardware wallets
-        // Disable Trezor support completely
-        .define("USE_DEVICE_TREZOR", "OFF")
+        //// Hardware wallets are only supported with the `hardware-wallets` feature
+        // Trezor support
+        .define("USE_DEVICE_TREZOR", device_support)
         .define("USE_DEVICE_TREZOR_MANDATORY", "OFF")
         .define("USE_DEVICE_TREZOR_PROTOBUF_TEST", "OFF")
-        .define("USE_DEVICE_TREZOR_LIBUSB", "OFF")
+        .define("USE_DEVICE_TREZOR_LIBUSB", device_support)
         .define("USE_DEVICE_TREZOR_UDP_RELEASE", "OFF")
         .define("USE_DEVICE_TREZOR_DEBUG", "OFF")
         .define("TREZOR_DEBUG", "OFF")
         // Prevent CMake from finding dependencies that could enable Trezor
-        .define("CMAKE_DISABLE_FIND_PACKAGE_LibUSB", "ON")
-        // Disable Ledger support
-        .define("USE_DEVICE_LEDGER", "OFF")
-        .define("CMAKE_DISABLE_FIND_PACKAGE_HIDAPI", "ON")
+        .define("CMAKE_DISABLE_FIND_PACKAGE_LibUSB", disable_device_packages)
+        // Ledger support
+        .define("USE_DEVICE_LEDGER", device_support)
+        .define("CMAKE_DISABLE_FIND_PACKAGE_HIDAPI", disable_device_packages)
         .define("GTEST_HAS_ABSL", "OFF")
         // Use lightweight crypto library
         .define("MONERO_WALLET_CRYPTO_LIBRARY", "cn")
@@ -266,6 +287,18 @@ fn main() {
     // Link protobuf statically
     println!("cargo:rustc-link-lib=static=protobuf");
 
+    // Hardware wallets talk to the device via hidapi (Ledger) and libusb (Trezor).
+    // The build script runs on the host, so we ask cargo for the OS we compile for.
+    if hardware_wallets {
+        let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+        if target_os == "macos" {
+            println!("cargo:rustc-link-lib=hidapi");
+        } else {
+            println!("cargo:rustc-link-lib=hidapi-libusb");
+        }
+        println!("cargo:rustc-link-lib=usb-1.0");
+    }
+
     #[cfg(target_os = "macos")]
     println!("cargo:rustc-link-arg=-mmacosx-version-min=11.0");
 
@@ -32,6 +32,7 @@
 namespace Monero
 {
     using ConnectionStatus = Wallet::ConnectionStatus;
+    using Device = Wallet::Device;
 
     /**
      * CXX doesn't support static methods as yet, so we define free functions here that simply
@@ -68,6 +69,14 @@ namespace Monero
         return std::make_unique<std::string>(err);
     }
 
+    /**
+     * Find all wallets (by their keys files) in a directory, searching recursively.
+     */
+    inline std::unique_ptr<std::vector<std::string>> walletManagerFindWallets(WalletManager &manager, const std::string &path)
+    {
+        return std::make_unique<std::vector<std::string>>(manager.findWallets(path));
+    }
+
     /**
      * Get the error string of a pending transaction.
      */
@@ -132,6 +141,30 @@ namespace Monero
         return wallet.createTransaction(dest_address, "", Monero::optional<uint64_t>(), 0, PendingTransaction::Priority_Default);
     }
 
+    /**
+     * Create a transaction that spends all unlocked outputs below `below` (in atomic units)
+     * to a single destination. Used to consolidate dust.
+     */
+    inline PendingTransaction *createSweepBelowTransaction(
+        Wallet &wallet,
+        const std::string &dest_address,
+        uint64_t below)
+    {
+        return wallet.createSweepBelowTransaction(dest_address, below, PendingTransaction::Priority_Default);
+    }
+
+    /**
+     * Create a transaction that spends exactly the output with the given key image
+     * to a single destination.
+     */
+    inline PendingTransaction *createSweepSingleTransaction(
+        Wallet &wallet,
+        const std::string &key_image,
+        const std::string &dest_address)
+    {
+        return wallet.createSweepSingleTransaction(key_image, dest_address, PendingTransaction::Priority_Default);
+    }
+
     /**
      * Creates a transaction that spends the unlocked balance to multiple destinations with given ratios.
      * Ratiosn must sum to 1.
@@ -178,11 +211,53 @@ namespace Monero
             subtract_fee_indices); // Subtract fee from all outputs
     }
 
+    /**
+     * Estimate the fee of a typical transaction (one destination plus change)
+     * at the default priority, using the dynamic fee reported by the daemon.
+     */
+    inline uint64_t estimateTypicalTransactionFee(const Wallet &wallet)
+    {
+        // Only the number of destinations matters for the estimate, not the address or amount
+        const std::vector<std::pair<std::string, uint64_t>> destinations{{"", 0}};
+
+        return wallet.estimateTransactionFee(destinations, PendingTransaction::Priority_Default);
+    }
+
+    /**
+     * Get the fee per byte for the given priority (a PendingTransaction::Priority).
+     */
+    inline uint64_t estimateFeePerByte(Wallet &wallet, uint32_t priority)
+    {
+        return wallet.estimateFeePerByte(static_cast<PendingTransaction::Priority>(priority));
+    }
+
     inline bool setWalletDaemon(Wallet &wallet, const std::string &daemon_address)
     {
         return wallet.setDaemon(daemon_address);
     }
 
+    /**
+     * Set the daemon address and only accept TLS certificates signed by the given CA
+     * or matching one of the given (hex encoded SHA-256) fingerprints.
+     */
+    inline bool setWalletDaemonPinned(
+        Wallet &wallet,
+        const std::string &daemon_address,
+        const std::string &ssl_ca_file,
+        const std::vector<std::string> &ssl_allowed_fingerprints)
+    {
+        return wallet.setDaemon(
+            daemon_address,
+            "",      // No username
+            "",      // No password
+            true,    // Trusted daemon, same default as setWalletDaemon
+            "enabled",
+            "",      // No client private key
+            "",      // No client certificate
+            ssl_ca_file,
+            ssl_allowed_fingerprints);
+    }
+
     inline std::unique_ptr<std::string> pendingTransactionTxId(const PendingTransaction &tx)
     {
         const auto ids = tx.txid();
@@ -200,6 +275,58 @@ namespace Monero
         return std::make_unique<std::string>(key);
     }
 
+    /**
+     * Get a proof that a transaction paid a given address.
+     */
+    inline std::unique_ptr<std::string> walletGetTxProof(
+        const Wallet &wallet,
+        const std::string &txid,
+        const std::string &address,
+        const std::string &message)
+    {
+        auto proof = wallet.getTxProof(txid, address, message);
+        return std::make_unique<std::string>(proof);
+    }
+
+    /**
+     * Get a proof that we are the sender of a transaction.
+     */
+    inline std::unique_ptr<std::string> walletGetSpendProof(
+        const Wallet &wallet,
+        const std::string &txid,
+        const std::string &message)
+    {
+        auto proof = wallet.getSpendProof(txid, message);
+        return std::make_unique<std::string>(proof);
+    }
+
+    /**
+     * Get a proof that the wallet holds (at least) the given amount of unspent funds.
+     */
+    inline std::unique_ptr<std::string> walletGetReserveProof(
+        const Wallet &wallet,
+        bool all,
+        uint32_t account_index,
+        uint64_t amount,
+        const std::string &message)
+    {
+        auto proof = wallet.getReserveProof(all, account_index, amount, message);
+        return std::make_unique<std::string>(proof);
+    }
+
+    /**
+     * Sign an arbitrary message with the spend key of the given address.
+     * An empty address signs with the main address.
+     */
+    inline std::unique_ptr<std::string> walletSignMessage(
+        Wallet &wallet,
+        const std::string &message,
+        const std::string &address)
+    {
+        auto signature = wallet.signMessage(message, address);
+        return std::make_unique<std::string>(signature);
+    }
+
     /**
      * Get the seed of the wallet.
      */
@@ -209,6 +336,142 @@ namespace Monero
         return std::make_unique<std::string>(seed);
     }
 
+    /**
+     * Get the language of the wallet's seed.
+     */
+    inline std::unique_ptr<std::string> walletSeedLanguage(const Wallet &wallet)
+    {
+        return std::make_unique<std::string>(wallet.getSeedLanguage());
+    }
+
+    /**
+     * Get the note attached to a transaction (empty if there is none).
+     */
+    inline std::unique_ptr<std::string> walletUserNote(const Wallet &wallet, const std::string &txid)
+    {
+        return std::make_unique<std::string>(wallet.getUserNote(txid));
+    }
+
+    /**
+     * Get an attribute stored in the wallet cache (empty if it isn't set).
+     */
+    inline std::unique_ptr<std::string> walletCacheAttribute(const Wallet &wallet, const std::string &key)
+    {
+        return std::make_unique<std::string>(wallet.getCacheAttribute(key));
+    }
+
+    /**
+     * Enable or disable background sync.
+     * CXX doesn't support Monero::optional, so an empty cache password means
+     * the wallet password is reused for the background cache.
+     */
+    inline bool setupBackgroundSync(
+        Wallet &wallet,
+        bool enabled,
+        const std::string &wallet_password,
+        const std::string &background_cache_password)
+    {
+        if (!enabled)
+        {
+            return wallet.setupBackgroundSync(Wallet::BackgroundSync_Off, wallet_password, optional<std::string>());
+        }
+
+        if (background_cache_password.empty())
+        {
+            return wallet.setupBackgroundSync(Wallet::BackgroundSync_ReusePassword, wallet_password, optional<std::string>());
+        }
+
+        return wallet.setupBackgroundSync(
+            Wallet::BackgroundSync_CustomPassword,
+            wallet_password,
+            optional<std::string>(background_cache_password));
+    }
+
+    /**
+     * Whether background sync has been set up for the wallet.
+     */
+    inline bool backgroundSyncEnabled(const Wallet &wallet)
+    {
+        return wallet.getBackgroundSyncType() != Wallet::BackgroundSync_Off;
+    }
+
+    /**
+     * Load an unsigned transaction file created by a view-only wallet.
+     * The caller takes ownership of the returned transaction.
+     */
+    inline std::unique_ptr<UnsignedTransaction> walletLoadUnsignedTx(Wallet &wallet, const std::string &unsigned_filename)
+    {
+        return std::unique_ptr<UnsignedTransaction>(wallet.loadUnsignedTx(unsigned_filename));
+    }
+
+    /**
+     * Get the error string of an unsigned transaction.
+     */
+    inline std::unique_ptr<std::string> unsignedTransactionErrorString(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::string>(tx.errorString());
+    }
+
+    /**
+     * Get the amounts sent by each transaction of an unsigned transaction set.
+     */
+    inline std::unique_ptr<std::vector<uint64_t>> unsignedTransactionAmounts(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<uint64_t>>(tx.amount());
+    }
+
+    /**
+     * Get the fees paid by each transaction of an unsigned transaction set.
+     */
+    inline std::unique_ptr<std::vector<uint64_t>> unsignedTransactionFees(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<uint64_t>>(tx.fee());
+    }
+
+    /**
+     * Get the recipient addresses of an unsigned transaction set.
+     */
+    inline std::unique_ptr<std::vector<std::string>> unsignedTransactionRecipients(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<std::string>>(tx.recipientAddress());
+    }
+
+    /**
+     * Add an entry to the wallet's address book.
+     */
+    inline bool addressBookAdd(
+        Wallet &wallet,
+        const std::string &address,
+        const std::string &payment_id,
+        const std::string &description)
+    {
+        return wallet.addressBook()->addRow(address, payment_id, description);
+    }
+
+    /**
+     * Delete an entry from the wallet's address book.
+     */
+    inline bool addressBookDelete(Wallet &wallet, size_t row_id)
+    {
+        return wallet.addressBook()->deleteRow(row_id);
+    }
+
+    /**
+     * Change the description of an entry in the wallet's address book.
+     */
+    inline bool addressBookSetDescription(Wallet &wallet, size_t row_id, const std::string &description)
+    {
+        return wallet.addressBook()->setDescription(row_id, description);
+    }
+
+    /**
+     * Get the error string of the last failed address book operation.
+     */
+    inline std::unique_ptr<std::string> addressBookErrorString(Wallet &wallet)
+    {
+        return std::make_unique<std::string>(wallet.addressBook()->errorString());
+    }
+
     inline std::unique_ptr<std::vector<std::string>> pendingTransactionTxIds(const PendingTransaction &tx)
     {
         return std::make_unique<std::vector<std::string>>(tx.txid());
@@ -231,6 +494,55 @@ namespace Monero
 #include "bridge.h"
 #include "monero-sys/src/bridge.rs.h"
 
+/**
+ * Glue code which needs the shared types generated by CXX (see bridge.rs),
+ * and can therefore only be defined after including the generated header.
+ */
+namespace Monero
+{
+    /**
+     * Get all entries of the wallet's address book.
+     */
+    inline rust::Vec<AddressBookEntry> addressBookGetAll(Wallet &wallet)
+    {
+        rust::Vec<AddressBookEntry> entries;
+        AddressBook *address_book = wallet.addressBook();
+
+        // The rows are cached, make sure they reflect the wallet file
+        address_book->refresh();
+
+        for (auto *row : address_book->getAll())
+        {
+            AddressBookEntry entry;
+            entry.row_id = row->getRowId();
+            entry.address = rust::String(row->getAddress());
+            entry.payment_id = rust::String(row->getPaymentId());
+            entry.description = rust::String(row->getDescription());
+            entries.push_back(entry);
+        }
+
+        return entries;
+    }
+
+    /**
+     * Get the destinations (address and amount) of all transactions of a pending transaction.
+     */
+    inline rust::Vec<TxDestination> pendingTransactionDestinations(const Wallet &wallet, const PendingTransaction &tx)
+    {
+        rust::Vec<TxDestination> destinations;
+
+        for (const auto &[address, amount] : wallet.pendingTransactionDestinations(tx))
+        {
+            TxDestination destination;
+            destination.address = rust::String(address);
+            destination.amount = amount;
+            destinations.push_back(destination);
+        }
+
+        return destinations;
+    }
+}
+
 /**
  * This section is us capturing the log messages from easylogging++
  * and forwarding it to rust's tracing.
@@ -35,6 +35,31 @@ pub mod ffi {
         ConnectionStatus_WrongVersion = 2,
     }
 
+    /// The kind of device holding the wallet's spend key.
+    #[repr(u32)]
+    enum Device {
+        #[rust_name = "Software"]
+        Device_Software = 0,
+        #[rust_name = "Ledger"]
+        Device_Ledger = 1,
+        #[rust_name = "Trezor"]
+        Device_Trezor = 2,
+    }
+
+    /// An entry of the wallet's address book.
+    struct AddressBookEntry {
+        row_id: usize,
+        address: String,
+        payment_id: String,
+        description: String,
+    }
+
+    /// A destination of a pending transaction.
+    struct TxDestination {
+        address: String,
+        amount: u64,
+    }
+
     unsafe extern "C++" {
         include!("wallet/api/wallet2_api.h");
         include!("bridge.h");
@@ -51,9 +76,15 @@ pub mod ffi {
         /// The status of the connection to the daemon.
         type ConnectionStatus;
 
+        /// The kind of device holding the wallet's spend key.
+        type Device;
+
         /// A pending transaction.
         type PendingTransaction;
 
+        /// An unsigned transaction, loaded from a file created by a view-only wallet.
+        type UnsignedTransaction;
+
         /// A wallet listener.
         ///
         /// Can be attached to a wallet and will get notified upon specific events.
@@ -100,6 +131,21 @@ pub mod ffi {
             seed_offset: &CxxString,
         ) -> Result<*mut Wallet>;
 
+        /// Create a new wallet whose keys are stored on a hardware device.
+        #[cfg(feature = "hardware-wallets")]
+        #[allow(clippy::too_many_arguments)]
+        unsafe fn createWalletFromDevice(
+            self: Pin<&mut WalletManager>,
+            path: &CxxString,
+            password: &CxxString,
+            network_type: NetworkType,
+            device_name: &CxxString,
+            restore_height: u64,
+            subaddress_lookahead: &CxxString,
+            kdf_rounds: u64,
+            listener: *mut WalletListener,
+        ) -> Result<*mut Wallet>;
+
         ///virtual Wallet * openWallet(const std::string &path, const std::string &password, NetworkType nettype, uint64_t kdf_rounds = 1, WalletListener * listener = nullptr) = 0;
         unsafe fn openWallet(
             self: Pin<&mut WalletManager>,
@@ -120,6 +166,21 @@ pub mod ffi {
         /// Check whether a wallet exists at the given path.
         fn walletExists(self: Pin<&mut WalletManager>, path: &CxxString) -> Result<bool>;
 
+        /// Find all wallets in a directory (recursively).
+        fn walletManagerFindWallets(
+            manager: Pin<&mut WalletManager>,
+            path: &CxxString,
+        ) -> Result<UniquePtr<CxxVector<CxxString>>>;
+
+        /// Check whether the password unlocks the wallet with the given keys file.
+        fn verifyWalletPassword(
+            self: &WalletManager,
+            keys_file_name: &CxxString,
+            password: &CxxString,
+            no_spend_key: bool,
+            kdf_rounds: u64,
+        ) -> Result<bool>;
+
         /// Set the address of the remote node ("daemon").
         fn setDaemonAddress(self: Pin<&mut WalletManager>, address: &CxxString) -> Result<()>;
 
@@ -160,12 +221,37 @@ pub mod ffi {
         /// Get the seed of the wallet.
         fn walletSeed(wallet: &Wallet, seed_offset: &CxxString) -> Result<UniquePtr<CxxString>>;
 
+        /// Get the language of the wallet's seed.
+        fn walletSeedLanguage(wallet: &Wallet) -> Result<UniquePtr<CxxString>>;
+
+        /// Attach a note to a transaction.
+        fn setUserNote(self: Pin<&mut Wallet>, txid: &CxxString, note: &CxxString) -> Result<bool>;
+
+        /// Get the note attached to a transaction.
+        fn walletUserNote(wallet: &Wallet, txid: &CxxString) -> Result<UniquePtr<CxxString>>;
+
+        /// Store an arbitrary attribute in the wallet cache.
+        fn setCacheAttribute(
+            self: Pin<&mut Wallet>,
+            key: &CxxString,
+            value: &CxxString,
+        ) -> Result<bool>;
+
+        /// Get an attribute stored in the wallet cache.
+        fn walletCacheAttribute(wallet: &Wallet, key: &CxxString) -> Result<UniquePtr<CxxString>>;
+
         /// Get the wallet creation height.
         fn getRefreshFromBlockHeight(self: &Wallet) -> Result<u64>;
 
         /// Check whether the wallet is connected to the daemon.
         fn connected(self: &Wallet) -> Result<ConnectionStatus>;
 
+        /// Get the kind of device the wallet's keys are stored on.
+        fn getDeviceType(self: &Wallet) -> Result<Device>;
+
+        /// Reconnect to the hardware device. Returns false if the device is unavailable.
+        fn reconnectDevice(self: Pin<&mut Wallet>) -> Result<bool>;
+
         /// Start the background refresh thread (refreshes every 10 seconds).
         fn startRefresh(self: Pin<&mut Wallet>) -> Result<()>;
 
@@ -175,15 +261,41 @@ pub mod ffi {
         /// Set the daemon address.
         fn setWalletDaemon(wallet: Pin<&mut Wallet>, daemon_address: &CxxString) -> Result<bool>;
 
+        /// Set the daemon address and pin its TLS certificate.
+        fn setWalletDaemonPinned(
+            wallet: Pin<&mut Wallet>,
+            daemon_address: &CxxString,
+            ssl_ca_file: &CxxString,
+            ssl_allowed_fingerprints: &CxxVector<CxxString>,
+        ) -> Result<bool>;
+
         /// Set whether the daemon is trusted.
         fn setTrustedDaemon(self: Pin<&mut Wallet>, trusted: bool) -> Result<()>;
 
+        /// Whether the daemon is trusted.
+        fn trustedDaemon(self: &Wallet) -> Result<bool>;
+
         /// Get the current blockchain height.
         fn blockChainHeight(self: &Wallet) -> Result<u64>;
 
         /// Get the daemon's blockchain height.
         fn daemonBlockChainTargetHeight(self: &Wallet) -> Result<u64>;
 
+        /// Get the height the daemon has synced to.
+        fn daemonBlockChainHeight(self: &Wallet) -> Result<u64>;
+
+        /// Check whether the daemon is synced with the network.
+        fn daemonSynced(self: &Wallet) -> Result<bool>;
+
+        /// Get the current hard fork version and the height it activated at.
+        fn hardForkInfo(self: &Wallet, version: &mut u8, earliest_height: &mut u64) -> Result<()>;
+
+        /// Estimate the fee of a transaction with one destination.
+        fn estimateTypicalTransactionFee(wallet: &Wallet) -> Result<u64>;
+
+        /// Get the fee per byte at the given priority. Returns 0 on error.
+        fn estimateFeePerByte(wallet: Pin<&mut Wallet>, priority: u32) -> Result<u64>;
+
         /// Check if wallet was ever synchronized.
         fn synchronized(self: &Wallet) -> Result<bool>;
 
@@ -199,6 +311,35 @@ pub mod ffi {
         /// Force a specific restore height.
         fn setRefreshFromBlockHeight(self: Pin<&mut Wallet>, height: u64) -> Result<()>;
 
+        /// Store the wallet to disk. Pass an empty path to store it to its current file.
+        fn store(self: Pin<&mut Wallet>, path: &CxxString) -> Result<bool>;
+
+        /// Discard the cached blockchain and rescan it from the restore height.
+        fn rescanBlockchain(self: Pin<&mut Wallet>) -> Result<bool>;
+
+        /// Ask the daemon which of our outputs have been spent.
+        fn rescanSpent(self: Pin<&mut Wallet>) -> Result<bool>;
+
+        /// Enable or disable background sync. An empty cache password reuses the wallet password.
+        fn setupBackgroundSync(
+            wallet: Pin<&mut Wallet>,
+            enabled: bool,
+            wallet_password: &CxxString,
+            background_cache_password: &CxxString,
+        ) -> Result<bool>;
+
+        /// Whether background sync has been set up.
+        fn backgroundSyncEnabled(wallet: &Wallet) -> Result<bool>;
+
+        /// Wipe the spend key from memory and keep syncing with the view key only.
+        fn startBackgroundSync(self: Pin<&mut Wallet>) -> Result<bool>;
+
+        /// Restore the spend key and process what was synced in the background.
+        fn stopBackgroundSync(self: Pin<&mut Wallet>, wallet_password: &CxxString) -> Result<bool>;
+
+        /// Whether the wallet is currently syncing in the background.
+        fn isBackgroundSyncing(self: &Wallet) -> Result<bool>;
+
         /// Set whether to allow mismatched daemon versions.
         fn setAllowMismatchedDaemonVersion(
             self: Pin<&mut Wallet>,
@@ -232,6 +373,20 @@ pub mod ffi {
             dest_address: &CxxString,
         ) -> Result<*mut PendingTransaction>;
 
+        /// Create a sweep transaction spending only outputs below a threshold.
+        fn createSweepBelowTransaction(
+            wallet: Pin<&mut Wallet>,
+            dest_address: &CxxString,
+            below: u64,
+        ) -> Result<*mut PendingTransaction>;
+
+        /// Create a sweep transaction spending a single output, identified by its key image.
+        fn createSweepSingleTransaction(
+            wallet: Pin<&mut Wallet>,
+            key_image: &CxxString,
+            dest_address: &CxxString,
+        ) -> Result<*mut PendingTransaction>;
+
         /// Create a multi-sweep transaction.
         fn createTransactionMultiDest(
             wallet: Pin<&mut Wallet>,
@@ -255,9 +410,170 @@ pub mod ffi {
             tx: &PendingTransaction,
         ) -> Result<UniquePtr<CxxVector<CxxString>>>;
 
+        /// Get the total fee of a pending transaction.
+        fn fee(self: &PendingTransaction) -> Result<u64>;
+
+        /// Get the total amount sent by a pending transaction (excluding change).
+        fn amount(self: &PendingTransaction) -> Result<u64>;
+
+        /// Get the number of transactions a pending transaction was split into.
+        fn txCount(self: &PendingTransaction) -> Result<u64>;
+
+        /// Get the combined weight of all transactions of a pending transaction.
+        fn pendingTransactionWeight(self: &Wallet, tx: &PendingTransaction) -> Result<u64>;
+
+        /// Get the destinations of all transactions of a pending transaction.
+        fn pendingTransactionDestinations(
+            wallet: &Wallet,
+            tx: &PendingTransaction,
+        ) -> Result<Vec<TxDestination>>;
+
         /// Get the transaction key (r) for a given txid.
         fn walletGetTxKey(wallet: &Wallet, txid: &CxxString) -> Result<UniquePtr<CxxString>>;
 
+        /// Get a proof that the transaction paid the given address.
+        fn walletGetTxProof(
+            wallet: &Wallet,
+            txid: &CxxString,
+            address: &CxxString,
+            message: &CxxString,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Check a transaction proof.
+        #[allow(clippy::too_many_arguments)]
+        fn checkTxProof(
+            self: Pin<&mut Wallet>,
+            txid: &CxxString,
+            address: &CxxString,
+            message: &CxxString,
+            signature: &CxxString,
+            good: &mut bool,
+            received: &mut u64,
+            in_pool: &mut bool,
+            confirmations: &mut u64,
+        ) -> Result<bool>;
+
+        /// Get a proof that we are the sender of the transaction.
+        fn walletGetSpendProof(
+            wallet: &Wallet,
+            txid: &CxxString,
+            message: &CxxString,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Check a spend proof.
+        fn checkSpendProof(
+            self: &Wallet,
+            txid: &CxxString,
+            message: &CxxString,
+            signature: &CxxString,
+            good: &mut bool,
+        ) -> Result<bool>;
+
+        /// Get a proof of the unspent funds of an account.
+        /// If `all` is set, the amount is ignored and all funds are included.
+        fn walletGetReserveProof(
+            wallet: &Wallet,
+            all: bool,
+            account_index: u32,
+            amount: u64,
+            message: &CxxString,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Check a reserve proof.
+        fn checkReserveProof(
+            self: &Wallet,
+            address: &CxxString,
+            message: &CxxString,
+            signature: &CxxString,
+            good: &mut bool,
+            total: &mut u64,
+            spent: &mut u64,
+        ) -> Result<bool>;
+
+        /// Sign a message with the spend key of the given address (empty for the main address).
+        fn walletSignMessage(
+            wallet: Pin<&mut Wallet>,
+            message: &CxxString,
+            address: &CxxString,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Verify a message signature created by the owner of the given address.
+        fn verifySignedMessage(
+            self: &Wallet,
+            message: &CxxString,
+            address: &CxxString,
+            signature: &CxxString,
+        ) -> Result<bool>;
+
+        /// Export the outputs of the wallet to a file (needed by the cold wallet to sign).
+        fn exportOutputs(self: Pin<&mut Wallet>, filename: &CxxString, all: bool) -> Result<bool>;
+
+        /// Import outputs exported by a view-only wallet.
+        fn importOutputs(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Export signed key images to a file (needed by the view-only wallet to detect spends).
+        fn exportKeyImages(self: Pin<&mut Wallet>, filename: &CxxString, all: bool)
+            -> Result<bool>;
+
+        /// Import signed key images exported by a cold wallet.
+        fn importKeyImages(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Load an unsigned transaction from a file.
+        fn walletLoadUnsignedTx(
+            wallet: Pin<&mut Wallet>,
+            unsigned_filename: &CxxString,
+        ) -> Result<UniquePtr<UnsignedTransaction>>;
+
+        /// Submit a signed transaction from a file to the daemon.
+        fn submitTransaction(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Get the status of an unsigned transaction.
+        fn status(self: &UnsignedTransaction) -> Result<i32>;
+
+        /// Get the error string of an unsigned transaction.
+        fn unsignedTransactionErrorString(tx: &UnsignedTransaction)
+            -> Result<UniquePtr<CxxString>>;
+
+        /// Get the amounts of an unsigned transaction set.
+        fn unsignedTransactionAmounts(
+            tx: &UnsignedTransaction,
+        ) -> Result<UniquePtr<CxxVector<u64>>>;
+
+        /// Get the fees of an unsigned transaction set.
+        fn unsignedTransactionFees(tx: &UnsignedTransaction) -> Result<UniquePtr<CxxVector<u64>>>;
+
+        /// Get the recipient addresses of an unsigned transaction set.
+        fn unsignedTransactionRecipients(
+            tx: &UnsignedTransaction,
+        ) -> Result<UniquePtr<CxxVector<CxxString>>>;
+
+        /// Sign an unsigned transaction and write the result to a file.
+        fn sign(self: Pin<&mut UnsignedTransaction>, signed_filename: &CxxString) -> Result<bool>;
+
+        /// Get all entries of the address book.
+        fn addressBookGetAll(wallet: Pin<&mut Wallet>) -> Result<Vec<AddressBookEntry>>;
+
+        /// Add an entry to the address book.
+        fn addressBookAdd(
+            wallet: Pin<&mut Wallet>,
+            address: &CxxString,
+            payment_id: &CxxString,
+            description: &CxxString,
+        ) -> Result<bool>;
+
+        /// Delete an entry from the address book.
+        fn addressBookDelete(wallet: Pin<&mut Wallet>, row_id: usize) -> Result<bool>;
+
+        /// Change the description of an address book entry.
+        fn addressBookSetDescription(
+            wallet: Pin<&mut Wallet>,
+            row_id: usize,
+            description: &CxxString,
+        ) -> Result<bool>;
+
+        /// Get the error string of the last failed address book operation.
+        fn addressBookErrorString(wallet: Pin<&mut Wallet>) -> Result<UniquePtr<CxxString>>;
+
         /// Commit a pending transaction to the blockchain.
         fn commit(
             self: Pin<&mut PendingTransaction>,
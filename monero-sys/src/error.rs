@@ -0,0 +1,212 @@
+//! Classification of the errors reported by wallet2.
+//!
+//! wallet2 only reports a status (ok, error or critical) together with a
+//! human readable message. We map the messages of the failures callers care about
+//! onto [`WalletError`] so they can be matched on instead of the string.
+
+/// An error reported by the wallet (or one of its pending transactions).
+///
+/// Errors returned by this crate are [`anyhow::Error`]s wrapping this type, use
+/// [`WalletError::from_anyhow`] (or [`anyhow::Error::downcast_ref`]) to get it back.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WalletError {
+    /// The wallet could not reach its daemon.
+    #[error("No connection to the daemon: `{message}`")]
+    NotConnected { message: String },
+    /// The daemon is reachable but too busy to answer (e.g. while syncing).
+    #[error("The daemon is busy: `{message}`")]
+    DaemonBusy { message: String },
+    /// The password doesn't match the wallet's keys file.
+    #[error("Wrong wallet password: `{message}`")]
+    WrongPassword { message: String },
+    /// The (unlocked) balance doesn't cover the amount plus fee.
+    #[error("Not enough money: `{message}`")]
+    NotEnoughMoney { message: String },
+    /// The daemon refused to relay the transaction.
+    #[error("Transaction rejected by the daemon: `{reason}`")]
+    TxRejected { reason: String },
+    /// Any other error. `critical` is set if wallet2 flagged it as critical.
+    #[error("Experienced wallet error ({}): `{message}`", severity(.critical))]
+    Other { critical: bool, message: String },
+}
+
+/// Why a call to the wallet thread didn't return a result.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CallError {
+    /// The call panicked. The wallet thread closes the wallet afterwards,
+    /// so it has to be reopened before it can be used again.
+    #[error("Wallet thread panicked: {message}")]
+    WalletThreadPanicked { message: String },
+    /// The wallet thread isn't running anymore, e.g. because an earlier call panicked.
+    #[error("Wallet thread exited")]
+    WalletThreadExited,
+}
+
+/// Status codes of `Monero::Wallet::Status` and `Monero::PendingTransaction::Status`.
+const STATUS_OK: i32 = 0;
+const STATUS_CRITICAL: i32 = 2;
+
+fn severity(critical: &bool) -> &'static str {
+    if *critical {
+        "critical"
+    } else {
+        "error"
+    }
+}
+
+impl WalletError {
+    /// Classify a wallet2 status code and error message.
+    /// Returns `None` if the status is ok.
+    pub(crate) fn from_status(status: i32, message: String) -> Option<Self> {
+        if status == STATUS_OK {
+            return None;
+        }
+
+        let message = if message.is_empty() {
+            "unknown error, error not set".to_string()
+        } else {
+            message
+        };
+        let lowercase = message.to_lowercase();
+
+        // The messages are the ones set by `wallet/api/wallet.cpp` when catching
+        // the corresponding `tools::error` exceptions.
+        let error = if lowercase.contains("rejected by daemon") {
+            let reason = match message.split_once("Reason: ") {
+                Some((_, reason)) => reason.trim().to_string(),
+                None => message,
+            };
+
+            WalletError::TxRejected { reason }
+        } else if lowercase.contains("no connection to daemon")
+            || lowercase.contains("failed to connect to daemon")
+        {
+            WalletError::NotConnected { message }
+        } else if lowercase.contains("daemon is busy") {
+            WalletError::DaemonBusy { message }
+        } else if lowercase.contains("invalid password") || lowercase.contains("wrong password") {
+            WalletError::WrongPassword { message }
+        } else if lowercase.contains("not enough money")
+            || lowercase.contains("not enough unlocked money")
+        {
+            WalletError::NotEnoughMoney { message }
+        } else {
+            WalletError::Other {
+                critical: status == STATUS_CRITICAL,
+                message,
+            }
+        };
+
+        Some(error)
+    }
+
+    /// Find the wallet error anywhere in the context chain of an error returned by this crate.
+    pub fn from_anyhow(error: &anyhow::Error) -> Option<&Self> {
+        error.downcast_ref::<Self>()
+    }
+
+    /// Whether retrying the same call later might succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            WalletError::NotConnected { .. } | WalletError::DaemonBusy { .. }
+        )
+    }
+}
+
+impl CallError {
+    /// Get the message of a panic caught with [`std::panic::catch_unwind`].
+    pub(crate) fn from_panic(panic: Box<dyn std::any::Any + Send>) -> Self {
+        let message = match panic.downcast::<String>() {
+            Ok(message) => *message,
+            Err(panic) => match panic.downcast::<&'static str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "unknown panic payload".to_string(),
+            },
+        };
+
+        CallError::WalletThreadPanicked { message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ok_status_is_not_an_error() {
+        assert_eq!(WalletError::from_status(0, "whatever".into()), None);
+    }
+
+    #[test]
+    fn classifies_wallet2_messages() {
+        assert!(matches!(
+            WalletError::from_status(
+                1,
+                "no connection to daemon. Please make sure daemon is running.".into()
+            ),
+            Some(WalletError::NotConnected { .. })
+        ));
+        assert!(matches!(
+            WalletError::from_status(1, "daemon is busy. Please try again later.".into()),
+            Some(WalletError::DaemonBusy { .. })
+        ));
+        assert!(matches!(
+            WalletError::from_status(1, "failed to open wallet: invalid password".into()),
+            Some(WalletError::WrongPassword { .. })
+        ));
+        assert!(matches!(
+            WalletError::from_status(
+                1,
+                "not enough money to transfer, available only 0.1, sent amount 1".into()
+            ),
+            Some(WalletError::NotEnoughMoney { .. })
+        ));
+        assert_eq!(
+            WalletError::from_status(2, "something broke".into()),
+            Some(WalletError::Other {
+                critical: true,
+                message: "something broke".into()
+            })
+        );
+    }
+
+    #[test]
+    fn extracts_rejection_reason() {
+        let error = WalletError::from_status(
+            1,
+            "transaction abc was rejected by daemon with status: Failed. Reason: double spend"
+                .into(),
+        );
+
+        assert_eq!(
+            error,
+            Some(WalletError::TxRejected {
+                reason: "double spend".into()
+            })
+        );
+    }
+
+    #[test]
+    fn extracts_panic_message() {
+        let panic = std::panic::catch_unwind(|| panic!("wallet {} broke", "main")).unwrap_err();
+
+        assert_eq!(
+            CallError::from_panic(panic),
+            CallError::WalletThreadPanicked {
+                message: "wallet main broke".into()
+            }
+        );
+    }
+
+    #[test]
+    fn survives_anyhow_context() {
+        let error = anyhow::Error::new(WalletError::DaemonBusy {
+            message: "daemon is busy".into(),
+        })
+        .context("Failed to refresh")
+        .context("Failed to sync");
+
+        assert!(WalletError::from_anyhow(&error).unwrap().is_transient());
+    }
+}
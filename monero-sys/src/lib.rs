@@ -9,12 +9,25 @@
 //!
 //! The wallet thread will be running in the background, and the [`WalletHandle`] will
 //! internally communicate with the wallet thread.
+//!
+//! To host many wallets on a single thread instead, use a [`WalletManagerHandle`].
 
 mod bridge;
+mod error;
+pub mod uri;
 
 use std::{
-    any::Any, cmp::Ordering, fmt::Display, ops::Deref, path::PathBuf, pin::Pin, str::FromStr,
-    time::Duration,
+    any::Any,
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    ops::Deref,
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+    sync::atomic::{self, AtomicU64},
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -22,15 +35,21 @@ use backoff::{future::retry_notify, retry_notify as blocking_retry_notify};
 use cxx::{let_cxx_string, CxxString, CxxVector, UniquePtr};
 use monero::Amount;
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    mpsc::{channel, error::TrySendError, Receiver, Sender},
     oneshot,
 };
+use tokio_util::sync::CancellationToken;
 
 use bridge::ffi;
+pub use error::{CallError, WalletError};
 
 /// A handle which can communicate with the wallet thread via channels.
 pub struct WalletHandle {
-    call_sender: UnboundedSender<Call>,
+    call_sender: Sender<Call>,
+    /// How often a call had to wait because the call queue was full.
+    times_queue_full: AtomicU64,
+    /// The wallet thread, returns the result of closing the wallet when it exits.
+    thread: std::thread::JoinHandle<anyhow::Result<()>>,
 }
 
 impl std::fmt::Display for WalletHandle {
@@ -51,13 +70,39 @@ impl std::fmt::Display for WalletHandle {
 pub struct Wallet {
     wallet: FfiWallet,
     manager: WalletManager,
-    call_receiver: UnboundedReceiver<Call>,
+    call_receiver: Receiver<Call>,
+}
+
+/// A handle to a single thread which hosts a wallet manager and any number of wallets.
+///
+/// Unlike [`WalletHandle`], which spawns a thread (and wallet manager) per wallet,
+/// all wallets opened through this handle share one thread. They are identified by their path.
+/// Calls to different wallets are executed one after another, so this is best suited
+/// for many wallets which are used rarely, like the per-swap wallets of the ASB.
+pub struct WalletManagerHandle {
+    call_sender: Sender<ManagerCall>,
+}
+
+/// The wallet manager and the wallets it hosts.
+/// Lives on the thread of the [`WalletManagerHandle`].
+struct HostedWallets {
+    manager: WalletManager,
+    /// The open wallets, by path.
+    wallets: HashMap<String, FfiWallet>,
+    daemon: Daemon,
+    network: monero::Network,
+}
+
+/// A function call to be executed on the wallet manager thread and a channel to send the result back.
+struct ManagerCall {
+    function: Box<dyn FnOnce(&mut HostedWallets) -> AnyBox + Send>,
+    sender: oneshot::Sender<Result<AnyBox, CallError>>,
 }
 
 /// A function call to be executed on the wallet and a channel to send the result back.
 struct Call {
     function: Box<dyn FnOnce(&mut FfiWallet) -> AnyBox + Send>,
-    sender: oneshot::Sender<AnyBox>,
+    sender: oneshot::Sender<Result<AnyBox, CallError>>,
 }
 
 type AnyBox = Box<dyn Any + Send>;
@@ -66,6 +111,9 @@ type AnyBox = Box<dyn Any + Send>;
 struct WalletManager {
     /// A wrapper around the raw C++ wallet manager pointer.
     inner: RawWalletManager,
+    /// The number of key derivation rounds used to encrypt the wallet files.
+    /// Must be the same when opening a wallet as when it was created.
+    kdf_rounds: u64,
 }
 
 /// This is our own wrapper around a raw C++ wallet manager pointer.
@@ -76,6 +124,21 @@ struct RawWalletManager {
 /// A single Monero wallet.
 pub struct FfiWallet {
     inner: RawWallet,
+    /// How often the wallet thread stores the wallet to disk, if at all.
+    auto_store_interval: Option<Duration>,
+    /// When the wallet was last stored to disk (or opened).
+    last_stored: Instant,
+    /// The daemons the wallet can fail over to, the one in use is at `daemon_index`.
+    daemons: Vec<Daemon>,
+    daemon_index: usize,
+    /// How long the daemon may be unreachable before we switch to the next one.
+    daemon_failover_timeout: Duration,
+    /// Since when the daemon in use has been unreachable, if it is.
+    disconnected_since: Option<Instant>,
+    /// When we last checked whether the daemon in use is reachable.
+    last_daemon_check: Instant,
+    /// Notified whenever the wallet switches to another daemon.
+    daemon_switch_listener: Option<Box<dyn Fn(DaemonSwitch) + Send>>,
 }
 
 /// This is our own wrapper around a raw C++ wallet pointer.
@@ -95,6 +158,53 @@ pub struct SyncProgress {
     pub current_block: u64,
     /// The target block height of the wallet.
     pub target_block: u64,
+    /// How many blocks per second the wallet synced recently, if known.
+    /// Only measured by [`WalletHandle::wait_until_synced`].
+    pub blocks_per_second: Option<f64>,
+}
+
+/// Estimates the sync speed as a rolling average over the last [`SyncRate::WINDOW`].
+struct SyncRate {
+    /// When the wallet was at which height, oldest first.
+    samples: VecDeque<(Instant, u64)>,
+}
+
+/// The wallet's balances and sync state, read in a single call to the wallet thread.
+#[derive(Debug, Clone, Copy)]
+pub struct WalletStatus {
+    pub total_balance: monero::Amount,
+    pub unlocked_balance: monero::Amount,
+    /// The height the wallet has synced to.
+    pub blockchain_height: u64,
+    pub sync_progress: SyncProgress,
+    /// Whether the wallet is connected to its daemon.
+    pub connected: bool,
+}
+
+/// The priority of a transaction. Higher priorities pay a higher fee to get mined sooner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TxPriority {
+    /// Let wallet2 decide, currently the same as [`TxPriority::Low`] unless the mempool is backlogged.
+    #[default]
+    Default,
+    Low,
+    Medium,
+    High,
+}
+
+/// Information about the daemon (remote node) the wallet is connected to.
+#[derive(Debug, Clone, Copy)]
+pub struct DaemonInfo {
+    /// The height the daemon has synced to.
+    pub height: u64,
+    /// The height of the network, as far as the daemon knows.
+    pub target_height: u64,
+    /// Whether the daemon is synced with the network.
+    pub synced: bool,
+    /// The estimated fee of a transaction with a single destination, at default priority.
+    pub fee_estimate: monero::Amount,
+    /// The hard fork version the daemon is on.
+    pub hard_fork_version: u8,
 }
 
 /// The status of a transaction.
@@ -107,6 +217,43 @@ pub struct TxStatus {
     pub confirmations: u64,
 }
 
+/// The funds covered by a valid reserve proof.
+pub struct ReserveProofStatus {
+    /// The total amount of the outputs included in the proof.
+    pub total: monero::Amount,
+    /// The amount of those outputs which has already been spent.
+    pub spent: monero::Amount,
+}
+
+/// A summary of an unsigned transaction set, returned after signing it
+/// so the contents can be shown to the user.
+#[derive(Debug, Clone)]
+pub struct UnsignedTxSummary {
+    /// The recipients of the transactions.
+    pub recipients: Vec<String>,
+    /// The amount sent by each transaction.
+    pub amounts: Vec<monero::Amount>,
+    /// The fee paid by each transaction.
+    pub fees: Vec<monero::Amount>,
+}
+
+/// A saved contact in the wallet's address book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressBookEntry {
+    /// The index of the entry, used to remove or edit it.
+    pub row_id: usize,
+    pub address: monero::Address,
+    pub description: String,
+    /// The (legacy) payment id to attach to transfers, if any.
+    pub payment_id: Option<String>,
+}
+
+/// Access to the address book of a wallet.
+/// The entries are stored in the wallet file.
+pub struct AddressBook<'a> {
+    wallet: &'a WalletHandle,
+}
+
 /// A receipt returned after successfully publishing a transaction.
 /// Contains basic information needed for later verification.
 pub struct TxReceipt {
@@ -114,6 +261,24 @@ pub struct TxReceipt {
     pub tx_key: String,
     /// The blockchain height at the time of publication.
     pub height: u64,
+    /// Details of the transfer this transaction is part of.
+    pub details: TxDetails,
+}
+
+/// Details of a transfer, which may have been split into multiple transactions.
+/// All values are summed over those transactions.
+#[derive(Debug, Clone)]
+pub struct TxDetails {
+    /// The fee paid.
+    pub fee: monero::Amount,
+    /// The amount sent to the destinations (excluding change).
+    pub amount: monero::Amount,
+    /// The destinations and the amount each of them receives.
+    pub destinations: Vec<(monero::Address, monero::Amount)>,
+    /// The weight of the transactions in bytes, which the fee is based on.
+    pub weight: u64,
+    /// The number of transactions the transfer was split into.
+    pub tx_count: u64,
 }
 
 /// A remote node to connect to.
@@ -121,20 +286,127 @@ pub struct TxReceipt {
 pub struct Daemon {
     pub address: String,
     pub ssl: bool,
+    /// Path to a CA certificate (PEM). If set, the certificate of the daemon
+    /// must be signed by this CA instead of one of the system's CAs.
+    pub ssl_ca_file: Option<String>,
+    /// SHA-256 fingerprints (hex, colons allowed) of the certificates the daemon may use.
+    /// If set, any other certificate is rejected, no matter who signed it.
+    pub ssl_allowed_fingerprints: Vec<String>,
+}
+
+/// A hardware wallet which can hold the spend key of a wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareDevice {
+    Ledger,
+    Trezor,
+}
+
+/// The language of a wallet's mnemonic seed.
+///
+/// Only needed when creating a new wallet, when recovering a wallet
+/// the language is detected from the mnemonic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeedLanguage {
+    #[default]
+    English,
+    German,
+    Spanish,
+    French,
+    Italian,
+    Dutch,
+    Portuguese,
+    Russian,
+    Japanese,
+    ChineseSimplified,
+    Esperanto,
+    Lojban,
+}
+
+/// A change in the connection status of a hardware device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// The device is connected and the wallet can talk to it.
+    Connected,
+    /// The device was unplugged, locked or is otherwise unavailable.
+    Disconnected,
+}
+
+/// The wallet switched to another daemon because the one it used was unreachable.
+#[derive(Debug, Clone)]
+pub struct DaemonSwitch {
+    /// The daemon which became unreachable.
+    pub from: Daemon,
+    /// The daemon the wallet uses now.
+    pub to: Daemon,
+}
+
+/// A snapshot of the queue of calls waiting for the wallet thread.
+#[derive(Debug, Clone, Copy)]
+pub struct CallQueueMetrics {
+    /// The number of calls waiting to be executed.
+    pub pending: usize,
+    /// The maximum number of queued calls before callers have to wait.
+    pub capacity: usize,
+    /// How often a caller had to wait because the queue was full.
+    pub times_full: u64,
 }
 
 /// A wrapper around a pending transaction.
-pub struct PendingTransaction(*mut ffi::PendingTransaction);
+///
+/// The transaction is disposed of by the wallet that created it when this is dropped.
+/// Since it holds raw pointers it isn't `Send`, meaning it can't leave the wallet thread
+/// and is therefore always disposed of there.
+pub struct PendingTransaction {
+    inner: *mut ffi::PendingTransaction,
+    /// The wallet which created the transaction and has to dispose of it.
+    wallet: *mut ffi::Wallet,
+}
 
 impl WalletHandle {
+    /// How many calls can be queued for the wallet thread before callers
+    /// have to wait for it to catch up.
+    const CALL_QUEUE_CAPACITY: usize = 64;
+
+    /// The wallet connects to the first daemon and fails over to the others
+    /// (in order) while the one in use is unreachable.
+    fn primary_daemon(daemons: &[Daemon]) -> anyhow::Result<Daemon> {
+        daemons
+            .first()
+            .cloned()
+            .context("At least one daemon is required to open a wallet")
+    }
+
+    /// The key derivation rounds used to encrypt the wallet file, the default
+    /// of the wallet manager unless others are given.
+    fn kdf_rounds(kdf_rounds: Option<u64>) -> anyhow::Result<u64> {
+        let kdf_rounds = kdf_rounds.unwrap_or(WalletManager::DEFAULT_KDF_ROUNDS);
+
+        if kdf_rounds == 0 {
+            bail!("KDF rounds must be at least 1");
+        }
+
+        Ok(kdf_rounds)
+    }
+
     /// Open an existing wallet or create a new one, with a random seed.
+    /// The seed of a new wallet uses the given language.
+    ///
+    /// The wallet connects to the first of `daemons` and switches to the next one
+    /// whenever the daemon in use stays unreachable, see [`WalletHandle::set_daemon_failover_timeout`].
+    ///
+    /// The wallet file is encrypted with `kdf_rounds` key derivation rounds,
+    /// or the default of the wallet manager if `None`.
     pub async fn open_or_create(
         path: String,
-        daemon: Daemon,
+        daemons: Vec<Daemon>,
         network: monero::Network,
+        language: SeedLanguage,
         background_sync: bool,
+        kdf_rounds: Option<u64>,
     ) -> anyhow::Result<Self> {
-        let (call_sender, call_receiver) = unbounded_channel();
+        let daemon = Self::primary_daemon(&daemons)?;
+        let kdf_rounds = Self::kdf_rounds(kdf_rounds)?;
+        let (call_sender, call_receiver) = channel(Self::CALL_QUEUE_CAPACITY);
 
         let wallet_name = path
             .split('/')
@@ -147,7 +419,7 @@ impl WalletHandle {
         // Capture current dispatcher before spawning
         let current_dispatcher = tracing::dispatcher::get_default(|d| d.clone());
 
-        std::thread::Builder::new()
+        let thread = std::thread::Builder::new()
             .name(thread_name)
             .spawn(move || {
                 // Set the dispatcher for this thread
@@ -155,18 +427,34 @@ impl WalletHandle {
 
                 let mut manager = WalletManager::new(daemon.clone(), &wallet_name)
                     .expect("wallet manager to be created");
-                let wallet = manager
-                    .open_or_create_wallet(&path, None, network, background_sync, daemon.clone())
+                manager
+                    .set_kdf_rounds(kdf_rounds)
+                    .expect("kdf rounds to be valid");
+                let mut wallet = manager
+                    .open_or_create_wallet(
+                        &path,
+                        None,
+                        network,
+                        language,
+                        background_sync,
+                        daemon.clone(),
+                    )
                     .expect("wallet to be created");
 
+                wallet.set_daemons(daemons);
+
                 let mut wrapped_wallet = Wallet::new(wallet, manager, call_receiver);
 
-                wrapped_wallet.run();
+                wrapped_wallet.run()
             })
             .context("Couldn't start wallet thread")?;
 
         // Ensure the wallet was created successfully by performing a dummy call
-        let wallet = WalletHandle { call_sender };
+        let wallet = WalletHandle {
+            call_sender,
+            times_queue_full: AtomicU64::new(0),
+            thread,
+        };
         wallet
             .check_wallet()
             .await
@@ -184,9 +472,12 @@ impl WalletHandle {
         network: monero::Network,
         restore_height: u64,
         background_sync: bool,
-        daemon: Daemon,
+        daemons: Vec<Daemon>,
+        kdf_rounds: Option<u64>,
     ) -> anyhow::Result<Self> {
-        let (call_sender, call_receiver) = unbounded_channel();
+        let daemon = Self::primary_daemon(&daemons)?;
+        let kdf_rounds = Self::kdf_rounds(kdf_rounds)?;
+        let (call_sender, call_receiver) = channel(Self::CALL_QUEUE_CAPACITY);
 
         let wallet_name = path
             .split('/')
@@ -201,7 +492,7 @@ impl WalletHandle {
 
         // Spawn the wallet thread – all interactions with the wallet must
         // happen on the same OS thread.
-        std::thread::Builder::new()
+        let thread = std::thread::Builder::new()
             .name(thread_name)
             .spawn(move || {
                 // Set the dispatcher for this thread
@@ -210,19 +501,16 @@ impl WalletHandle {
                 // Create the wallet manager in this thread first.
                 let mut manager = WalletManager::new(daemon.clone(), &wallet_name)
                     .expect("wallet manager to be created");
+                manager
+                    .set_kdf_rounds(kdf_rounds)
+                    .expect("kdf rounds to be valid");
 
                 // Decide whether we have to open an existing wallet or recover it
                 // from the mnemonic.
-                let wallet = if manager.wallet_exists(&path) {
+                let mut wallet = if manager.wallet_exists(&path) {
                     // Existing wallet – open it.
                     manager
-                        .open_or_create_wallet(
-                            &path,
-                            None,
-                            network,
-                            background_sync,
-                            daemon.clone(),
-                        )
+                        .open_wallet(&path, None, network, background_sync, daemon.clone())
                         .expect("wallet to be opened")
                 } else {
                     // Wallet does not exist – recover it from the seed.
@@ -239,13 +527,19 @@ impl WalletHandle {
                         .expect("wallet to be recovered from seed")
                 };
 
+                wallet.set_daemons(daemons);
+
                 let mut wrapped_wallet = Wallet::new(wallet, manager, call_receiver);
 
-                wrapped_wallet.run();
+                wrapped_wallet.run()
             })
             .context("Couldn't start wallet thread")?;
 
-        let wallet = WalletHandle { call_sender };
+        let wallet = WalletHandle {
+            call_sender,
+            times_queue_full: AtomicU64::new(0),
+            thread,
+        };
         // Make a test call to ensure that the wallet is created.
         wallet
             .check_wallet()
@@ -268,9 +562,12 @@ impl WalletHandle {
         spend_key: monero::PrivateKey,
         restore_height: u64,
         background_sync: bool,
-        daemon: Daemon,
+        daemons: Vec<Daemon>,
+        kdf_rounds: Option<u64>,
     ) -> anyhow::Result<Self> {
-        let (call_sender, call_receiver) = unbounded_channel();
+        let daemon = Self::primary_daemon(&daemons)?;
+        let kdf_rounds = Self::kdf_rounds(kdf_rounds)?;
+        let (call_sender, call_receiver) = channel(Self::CALL_QUEUE_CAPACITY);
 
         let wallet_name = path
             .split('/')
@@ -283,7 +580,7 @@ impl WalletHandle {
         // Capture current dispatcher before spawning
         let current_dispatcher = tracing::dispatcher::get_default(|d| d.clone());
 
-        std::thread::Builder::new()
+        let thread = std::thread::Builder::new()
             .name(thread_name)
             .spawn(move || {
                 // Set the dispatcher for this thread
@@ -297,8 +594,11 @@ impl WalletHandle {
 
                 let mut manager = WalletManager::new(daemon.clone(), &wallet_name)
                     .expect("wallet manager to be created");
+                manager
+                    .set_kdf_rounds(kdf_rounds)
+                    .expect("kdf rounds to be valid");
 
-                let wallet = manager
+                let mut wallet = manager
                     .open_or_create_wallet_from_keys(
                         &path,
                         password.as_deref(),
@@ -312,13 +612,19 @@ impl WalletHandle {
                     )
                     .expect("wallet to be opened or created from keys");
 
+                wallet.set_daemons(daemons);
+
                 let mut wrapped_wallet = Wallet::new(wallet, manager, call_receiver);
 
-                wrapped_wallet.run();
+                wrapped_wallet.run()
             })
             .context("Couldn't start wallet thread")?;
 
-        let wallet = WalletHandle { call_sender };
+        let wallet = WalletHandle {
+            call_sender,
+            times_queue_full: AtomicU64::new(0),
+            thread,
+        };
         // Make a test call to ensure that the wallet is created.
         wallet
             .check_wallet()
@@ -328,10 +634,124 @@ impl WalletHandle {
         Ok(wallet)
     }
 
+    /// Open an existing wallet or create a new one whose keys are stored on a
+    /// hardware device. The spend key never leaves the device.
+    ///
+    /// Requires the `hardware-wallets` feature.
+    #[cfg(feature = "hardware-wallets")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open_or_create_from_device(
+        path: String,
+        password: Option<String>,
+        network: monero::Network,
+        device: HardwareDevice,
+        restore_height: u64,
+        background_sync: bool,
+        daemons: Vec<Daemon>,
+        kdf_rounds: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        let daemon = Self::primary_daemon(&daemons)?;
+        let kdf_rounds = Self::kdf_rounds(kdf_rounds)?;
+        let (call_sender, call_receiver) = channel(Self::CALL_QUEUE_CAPACITY);
+
+        let wallet_name = path
+            .split('/')
+            .last()
+            .map(ToString::to_string)
+            .unwrap_or(path.clone());
+
+        let thread_name = format!("wallet-{}", wallet_name);
+
+        // Capture current dispatcher before spawning
+        let current_dispatcher = tracing::dispatcher::get_default(|d| d.clone());
+
+        let thread = std::thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || {
+                // Set the dispatcher for this thread
+                let _guard = tracing::dispatcher::set_default(&current_dispatcher);
+
+                let mut manager = WalletManager::new(daemon.clone(), &wallet_name)
+                    .expect("wallet manager to be created");
+                manager
+                    .set_kdf_rounds(kdf_rounds)
+                    .expect("kdf rounds to be valid");
+
+                let mut wallet = if manager.wallet_exists(&path) {
+                    manager
+                        .open_wallet(
+                            &path,
+                            password.as_deref(),
+                            network,
+                            background_sync,
+                            daemon.clone(),
+                        )
+                        .expect("wallet to be opened")
+                } else {
+                    manager
+                        .create_wallet_from_device(
+                            &path,
+                            password.as_deref(),
+                            network,
+                            device,
+                            restore_height,
+                            background_sync,
+                            daemon.clone(),
+                        )
+                        .expect("wallet to be created from device")
+                };
+
+                wallet.set_daemons(daemons);
+
+                let mut wrapped_wallet = Wallet::new(wallet, manager, call_receiver);
+
+                wrapped_wallet.run()
+            })
+            .context("Couldn't start wallet thread")?;
+
+        let wallet = WalletHandle {
+            call_sender,
+            times_queue_full: AtomicU64::new(0),
+            thread,
+        };
+        // Make a test call to ensure that the wallet is created.
+        wallet
+            .check_wallet()
+            .await
+            .context("Failed to create wallet from device")?;
+
+        Ok(wallet)
+    }
+
     /// Execute a function on the wallet thread and return the result.
     /// Necessary because every interaction with the wallet must run on a single thread.
-    /// Panics if the channel is closed unexpectedly.
+    /// Panics if the call panics or the wallet thread isn't running anymore,
+    /// use [`WalletHandle::try_call`] to handle that instead.
+    ///
+    /// To read several values at once, return them together so they only
+    /// cost a single round trip to the wallet thread:
+    ///
+    /// ```ignore
+    /// let (total, unlocked) = wallet
+    ///     .call(|wallet| (wallet.total_balance(), wallet.unlocked_balance()))
+    ///     .await;
+    /// ```
     pub async fn call<F, R>(&self, function: F) -> R
+    where
+        F: FnOnce(&mut FfiWallet) -> R + Send + 'static,
+        R: Sized + Send + 'static,
+    {
+        self.try_call(function)
+            .await
+            .unwrap_or_else(|e| panic!("Wallet call failed: {e}"))
+    }
+
+    /// Like [`WalletHandle::call`], but return an error instead of panicking if the call
+    /// panics or the wallet thread isn't running anymore.
+    ///
+    /// After a panic the wallet is closed and every further call fails with
+    /// [`CallError::WalletThreadExited`], so the wallet has to be reopened.
+    pub async fn try_call<F, R>(&self, function: F) -> Result<R, CallError>
     where
         F: FnOnce(&mut FfiWallet) -> R + Send + 'static,
         R: Sized + Send + 'static,
@@ -339,21 +759,117 @@ impl WalletHandle {
         // Create a oneshot channel for the result
         let (sender, receiver) = oneshot::channel();
 
-        // Send the function call to the wallet thread (wrapped in a Box)
-        self.call_sender
-            .send(Call {
-                function: Box::new(move |wallet| Box::new(function(wallet)) as Box<dyn Any + Send>),
-                sender,
-            })
-            .inspect_err(|e| tracing::error!(error=%e, "failed to send call"))
-            .expect("channel to be open");
+        let call = Call {
+            function: Box::new(move |wallet| Box::new(function(wallet)) as Box<dyn Any + Send>),
+            sender,
+        };
+
+        // Send the function call to the wallet thread (wrapped in a Box).
+        // If the queue is full we wait until there is room again.
+        match self.call_sender.try_send(call) {
+            Ok(()) => {}
+            Err(TrySendError::Full(call)) => {
+                let times_full = self
+                    .times_queue_full
+                    .fetch_add(1, atomic::Ordering::Relaxed)
+                    + 1;
+                tracing::warn!(
+                    capacity = Self::CALL_QUEUE_CAPACITY,
+                    times_full,
+                    "Wallet call queue is full, waiting for the wallet thread to catch up"
+                );
+
+                self.call_sender
+                    .send(call)
+                    .await
+                    .inspect_err(|e| tracing::error!(error=%e, "failed to send call"))
+                    .map_err(|_| CallError::WalletThreadExited)?;
+            }
+            Err(e @ TrySendError::Closed(_)) => {
+                tracing::error!(error=%e, "failed to send call");
+                return Err(CallError::WalletThreadExited);
+            }
+        }
 
         // Wait for the result and cast back to the expected type
-        *receiver
+        let result = receiver
             .await
-            .expect("channel to be open")
+            .map_err(|_| CallError::WalletThreadExited)??;
+
+        Ok(*result
             .downcast::<R>() // We know that F returns R
-            .expect("return type to be consistent")
+            .expect("return type to be consistent"))
+    }
+
+    /// Get the current state of the queue of calls to the wallet thread.
+    /// A queue that is constantly full means the wallet can't keep up with the calls.
+    pub fn call_queue_metrics(&self) -> CallQueueMetrics {
+        CallQueueMetrics {
+            pending: self.call_sender.max_capacity() - self.call_sender.capacity(),
+            capacity: self.call_sender.max_capacity(),
+            times_full: self.times_queue_full.load(atomic::Ordering::Relaxed),
+        }
+    }
+
+    /// Like [`WalletHandle::call`], but give up waiting for the result after `timeout`.
+    ///
+    /// If the call hasn't started by then it is skipped by the wallet thread.
+    /// A call that already started can't be interrupted: it keeps running
+    /// in the background (blocking later calls) and its result is discarded.
+    pub async fn call_with_timeout<F, R>(&self, timeout: Duration, function: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut FfiWallet) -> R + Send + 'static,
+        R: Sized + Send + 'static,
+    {
+        let result = tokio::time::timeout(timeout, self.try_call(function))
+            .await
+            .with_context(|| format!("Wallet call timed out after {} secs", timeout.as_secs()))?;
+
+        Ok(result?)
+    }
+
+    /// Like [`WalletHandle::call`], but stop waiting for the result once `token` is cancelled.
+    ///
+    /// Same as with [`WalletHandle::call_with_timeout`], only calls which haven't
+    /// started yet are actually aborted.
+    pub async fn call_cancellable<F, R>(
+        &self,
+        token: &CancellationToken,
+        function: F,
+    ) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut FfiWallet) -> R + Send + 'static,
+        R: Sized + Send + 'static,
+    {
+        tokio::select! {
+            result = self.try_call(function) => Ok(result?),
+            _ = token.cancelled() => bail!("Wallet call was cancelled"),
+        }
+    }
+
+    /// Store and close the wallet, then wait for the wallet thread to exit.
+    ///
+    /// Dropping the handle closes the wallet as well, but in the background and
+    /// without reporting errors. Once this returns `Ok`, the wallet file has been written.
+    pub async fn close(self) -> anyhow::Result<()> {
+        let store_result = self.store().await;
+
+        let WalletHandle {
+            call_sender,
+            thread,
+            ..
+        } = self;
+
+        // Closing the channel makes the wallet thread close the wallet and exit
+        drop(call_sender);
+
+        let close_result = tokio::task::spawn_blocking(move || thread.join())
+            .await
+            .context("Failed to wait for wallet thread")?
+            .map_err(|_| anyhow!("Wallet thread panicked"))?;
+
+        store_result.context("Failed to store wallet before closing it")?;
+        close_result.context("Failed to close wallet")
     }
 
     /// Get the file system path to the wallet.
@@ -425,60 +941,342 @@ impl WalletHandle {
         .map_err(|e| anyhow!("Failed to sweep funds after multiple attempts: {e}"))
     }
 
+    /// Sweep all unlocked outputs below `threshold` to an address.
+    /// Used to consolidate dust without touching larger outputs.
+    pub async fn sweep_below(
+        &self,
+        address: &monero::Address,
+        threshold: monero::Amount,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        let address = *address;
+
+        retry_notify(backoff(None, None), || async {
+            self.call(move |wallet| wallet.sweep_below(&address, threshold))
+                .await
+                .map_err(backoff::Error::transient)
+        }, |error, duration: Duration| {
+            tracing::error!(error=%error, "Failed to sweep outputs below threshold, retrying in {} secs", duration.as_secs());
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to sweep outputs below threshold after multiple attempts: {e}"))
+    }
+
+    /// Sweep a single output, identified by its (hex encoded) key image, to an address.
+    pub async fn sweep_output(
+        &self,
+        key_image: String,
+        address: &monero::Address,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        let address = *address;
+
+        retry_notify(backoff(None, None), || async {
+            let key_image = key_image.clone();
+
+            self.call(move |wallet| wallet.sweep_output(&key_image, &address))
+                .await
+                .map_err(backoff::Error::transient)
+        }, |error, duration: Duration| {
+            tracing::error!(error=%error, "Failed to sweep output, retrying in {} secs", duration.as_secs());
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to sweep output after multiple attempts: {e}"))
+    }
+
     /// Get the seed of the wallet.
     pub async fn seed(&self) -> String {
         self.call(move |wallet| wallet.seed()).await
     }
 
-    /// Get the creation height of the wallet.
-    pub async fn creation_height(&self) -> u64 {
-        self.call(move |wallet| wallet.creation_height()).await
+    /// Get the language of the wallet's seed.
+    /// Returns `None` if the wallet has no seed (e.g. hardware wallets).
+    pub async fn seed_language(&self) -> Option<SeedLanguage> {
+        self.call(move |wallet| wallet.seed_language()).await
     }
 
-    /// Sweep all funds to a set of addresses.
-    pub async fn sweep_multi(
-        &self,
-        addresses: &[monero::Address],
-        percentages: &[f64],
-    ) -> anyhow::Result<Vec<TxReceipt>> {
-        let addresses = addresses.to_vec();
-        let percentages = percentages.to_vec();
+    /// Attach a note to one of the wallet's transactions, e.g. the swap it belongs to.
+    /// The note is persisted the next time the wallet is stored.
+    pub async fn set_tx_note(&self, txid: String, note: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.set_tx_note(&txid, &note))
+            .await
+    }
 
-        tracing::debug!(addresses=?addresses, percentages=?percentages, "Sweeping multi");
+    /// Get the note attached to a transaction, if any.
+    pub async fn tx_note(&self, txid: String) -> Option<String> {
+        self.call(move |wallet| wallet.tx_note(&txid)).await
+    }
 
-        self.call(move |wallet| wallet.sweep_multi(&addresses, &percentages))
+    /// Store an arbitrary key/value attribute in the wallet cache.
+    /// The attribute is persisted the next time the wallet is stored.
+    pub async fn set_attribute(&self, key: String, value: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.set_attribute(&key, &value))
             .await
     }
 
-    /// Get the unlocked balance of the wallet.
-    pub async fn unlocked_balance(&self) -> monero::Amount {
-        self.call(move |wallet| wallet.unlocked_balance()).await
+    /// Get an attribute stored with [`WalletHandle::set_attribute`], if it is set.
+    pub async fn attribute(&self, key: String) -> Option<String> {
+        self.call(move |wallet| wallet.attribute(&key)).await
     }
 
-    /// Get the total balance of the wallet.
-    pub async fn total_balance(&self) -> monero::Amount {
-        self.call(move |wallet| wallet.total_balance()).await
+    /// Get the creation height of the wallet.
+    pub async fn creation_height(&self) -> u64 {
+        self.call(move |wallet| wallet.creation_height()).await
     }
 
-    /// Check if the wallet is synchronized.
-    async fn synchronized(&self) -> bool {
-        self.call(move |wallet| wallet.synchronized()).await
+    /// Store the wallet (including the sync progress) to disk.
+    /// Fails instead of panicking if the wallet thread isn't running anymore.
+    pub async fn store(&self) -> anyhow::Result<()> {
+        self.try_call(move |wallet| wallet.store()).await?
     }
 
-    /// Get the sync progress of the wallet.
-    async fn sync_progress(&self) -> SyncProgress {
-        self.call(move |wallet| wallet.sync_progress()).await
+    /// Set how often the wallet thread stores the wallet to disk.
+    /// `None` disables storing periodically, the wallet is then only stored
+    /// when it is closed or [`WalletHandle::store`] is called.
+    ///
+    /// Defaults to every 10 minutes.
+    pub async fn set_auto_store_interval(&self, interval: Option<Duration>) {
+        self.call(move |wallet| wallet.set_auto_store_interval(interval))
+            .await
     }
 
-    /// Check if the wallet is connected to a daemon.
-    pub async fn connected(&self) -> bool {
-        self.call(move |wallet| wallet.connected()).await
+    /// Get the height from which the wallet scans the blockchain (the restore height).
+    pub async fn get_refresh_from_block_height(&self) -> u64 {
+        self.call(move |wallet| wallet.creation_height()).await
     }
 
-    /// Check that the wallet is created and ready to use.
-    /// Call this after creating a wallet to make sure the wallet thread responds correctly.
-    async fn check_wallet(&self) -> anyhow::Result<()> {
-        let (sender, receiver) = oneshot::channel();
+    /// Change the restore height of the wallet.
+    ///
+    /// Only takes effect on the next rescan, see [`WalletHandle::rescan_blockchain`].
+    /// The new height is persisted the next time the wallet is stored.
+    pub async fn set_refresh_from_block_height(&self, height: u64) {
+        self.call(move |wallet| wallet.set_refresh_from_block_height(height))
+            .await
+    }
+
+    /// Discard the cached blockchain data and rescan from the restore height.
+    ///
+    /// Useful when the wallet was restored with a restore height that is too high
+    /// and is missing funds. Blocks the wallet thread until the rescan is done,
+    /// which can take a long time.
+    pub async fn rescan_blockchain(&self) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.rescan_blockchain()).await
+    }
+
+    /// Ask the daemon which of the wallet's outputs have been spent.
+    ///
+    /// Fixes balances that include outputs spent by another instance of this wallet.
+    /// Requires a trusted daemon.
+    pub async fn rescan_spent(&self) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.rescan_spent()).await
+    }
+
+    /// Get the size of the wallet's cache file (the file without `.keys`) in bytes.
+    pub async fn cache_size(&self) -> anyhow::Result<u64> {
+        self.call(move |wallet| wallet.cache_size()).await
+    }
+
+    /// Shrink the wallet cache by rebuilding it from scratch.
+    ///
+    /// Rescans the blockchain from the restore height (the daemon only sends pruned
+    /// blocks) and stores the result, which drops everything that accumulated in the cache
+    /// over time. Like [`WalletHandle::rescan_blockchain`] this blocks the wallet thread
+    /// until the rescan is done. Returns the new size of the cache file in bytes.
+    pub async fn compact_cache(&self) -> anyhow::Result<u64> {
+        self.call(move |wallet| wallet.compact_cache()).await
+    }
+
+    /// Whether the wallet trusts its daemon.
+    pub async fn trusted_daemon(&self) -> bool {
+        self.call(move |wallet| wallet.trusted_daemon()).await
+    }
+
+    /// Set whether the wallet trusts its daemon.
+    ///
+    /// A trusted daemon is asked which outputs have been spent (see
+    /// [`WalletHandle::rescan_spent`]) and is allowed to serve the wallet without the
+    /// privacy precautions taken against untrusted remote nodes.
+    /// Only trust a daemon you run yourself.
+    pub async fn set_trusted_daemon(&self, trusted: bool) {
+        self.call(move |wallet| wallet.set_trusted_daemon(trusted))
+            .await
+    }
+
+    /// Allow the wallet to keep syncing while it is locked (see
+    /// [`WalletHandle::start_background_sync`]).
+    ///
+    /// The data synced while locked is stored in a separate background cache,
+    /// encrypted with `background_cache_password` or, if `None`, the wallet password.
+    pub async fn setup_background_sync(
+        &self,
+        wallet_password: String,
+        background_cache_password: Option<String>,
+    ) -> anyhow::Result<()> {
+        self.call(move |wallet| {
+            wallet.setup_background_sync(
+                true,
+                &wallet_password,
+                background_cache_password.as_deref(),
+            )
+        })
+        .await
+    }
+
+    /// Disable background sync and delete the background cache.
+    pub async fn disable_background_sync(&self, wallet_password: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.setup_background_sync(false, &wallet_password, None))
+            .await
+    }
+
+    /// Lock the wallet: wipe the spend key from memory and keep syncing with the view key only.
+    ///
+    /// Requires [`WalletHandle::setup_background_sync`]. Anything that needs the spend key
+    /// (e.g. transfers) fails until [`WalletHandle::stop_background_sync`] is called.
+    pub async fn start_background_sync(&self) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.start_background_sync())
+            .await
+    }
+
+    /// Unlock the wallet again. Decrypts the spend key with `wallet_password` and
+    /// processes the transactions found while syncing in the background.
+    pub async fn stop_background_sync(&self, wallet_password: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.stop_background_sync(&wallet_password))
+            .await
+    }
+
+    /// Whether the wallet is locked and syncing in the background.
+    pub async fn is_background_syncing(&self) -> bool {
+        self.call(move |wallet| wallet.is_background_syncing())
+            .await
+    }
+
+    /// Whether background sync has been set up for this wallet.
+    pub async fn background_sync_enabled(&self) -> bool {
+        self.call(move |wallet| wallet.background_sync_enabled())
+            .await
+    }
+
+    /// Sweep all funds to a set of addresses.
+    pub async fn sweep_multi(
+        &self,
+        addresses: &[monero::Address],
+        percentages: &[f64],
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        let addresses = addresses.to_vec();
+        let percentages = percentages.to_vec();
+
+        tracing::debug!(addresses=?addresses, percentages=?percentages, "Sweeping multi");
+
+        self.call(move |wallet| wallet.sweep_multi(&addresses, &percentages))
+            .await
+    }
+
+    /// Get the unlocked balance of the wallet.
+    pub async fn unlocked_balance(&self) -> monero::Amount {
+        self.call(move |wallet| wallet.unlocked_balance()).await
+    }
+
+    /// Get the total balance of the wallet.
+    pub async fn total_balance(&self) -> monero::Amount {
+        self.call(move |wallet| wallet.total_balance()).await
+    }
+
+    /// Get the balances, height, sync progress and connection status of the wallet
+    /// in a single call to the wallet thread.
+    pub async fn status(&self) -> WalletStatus {
+        self.call(move |wallet| WalletStatus {
+            total_balance: wallet.total_balance(),
+            unlocked_balance: wallet.unlocked_balance(),
+            blockchain_height: wallet.blockchain_height(),
+            sync_progress: wallet.sync_progress(),
+            connected: wallet.connected(),
+        })
+        .await
+    }
+
+    /// Get the sync progress of the wallet.
+    async fn sync_progress(&self) -> SyncProgress {
+        self.call(move |wallet| wallet.sync_progress()).await
+    }
+
+    /// Check if the wallet is connected to a daemon.
+    pub async fn connected(&self) -> bool {
+        self.call(move |wallet| wallet.connected()).await
+    }
+
+    /// Get the daemon the wallet currently uses.
+    pub async fn current_daemon(&self) -> Daemon {
+        self.call(move |wallet| wallet.current_daemon()).await
+    }
+
+    /// Set how long the daemon in use may be unreachable before the wallet
+    /// switches to the next one. Defaults to 30 seconds.
+    pub async fn set_daemon_failover_timeout(&self, timeout: Duration) {
+        self.call(move |wallet| wallet.daemon_failover_timeout = timeout)
+            .await
+    }
+
+    /// Get notified whenever the wallet switches to another daemon.
+    /// Replaces the previous listener. Called on the wallet thread, so it must not block.
+    pub async fn set_daemon_switch_listener(
+        &self,
+        listener: impl Fn(DaemonSwitch) + Send + 'static,
+    ) {
+        self.call(move |wallet| wallet.daemon_switch_listener = Some(Box::new(listener)))
+            .await
+    }
+
+    /// Get the fee per byte the daemon currently asks for at the given priority.
+    ///
+    /// Multiply by the expected weight of a transaction to get a rough estimate
+    /// of its fee before creating it.
+    pub async fn estimate_fee_per_byte(
+        &self,
+        priority: TxPriority,
+    ) -> anyhow::Result<monero::Amount> {
+        self.call(move |wallet| wallet.estimate_fee_per_byte(priority))
+            .await
+    }
+
+    /// Get information about the daemon the wallet is connected to, like its height and fees.
+    /// Fails if the wallet isn't connected to a daemon.
+    pub async fn daemon_info(&self) -> anyhow::Result<DaemonInfo> {
+        self.call(move |wallet| wallet.daemon_info()).await
+    }
+
+    /// Get the hardware device the wallet's keys are stored on, if any.
+    pub async fn hardware_device(&self) -> Option<HardwareDevice> {
+        self.call(move |wallet| wallet.hardware_device()).await
+    }
+
+    /// Poll the hardware device and notify the listener whenever its connection
+    /// status changes. The current status is always reported first.
+    ///
+    /// Runs until the returned future is dropped, so spawn it as a background task.
+    pub async fn watch_device(&self, listener: impl Fn(DeviceEvent) + Send + 'static) {
+        // Talking to the device is slow, so we don't poll too often.
+        const POLL_INTERVAL_SECS: u64 = 5;
+
+        let mut last_event = None;
+
+        loop {
+            let event = match self.call(move |wallet| wallet.reconnect_device()).await {
+                true => DeviceEvent::Connected,
+                false => DeviceEvent::Disconnected,
+            };
+
+            if last_event != Some(event) {
+                tracing::debug!(?event, "Hardware device status changed");
+                listener(event);
+                last_event = Some(event);
+            }
+
+            tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// Check that the wallet is created and ready to use.
+    /// Call this after creating a wallet to make sure the wallet thread responds correctly.
+    async fn check_wallet(&self) -> anyhow::Result<()> {
+        let (sender, receiver) = oneshot::channel();
 
         self.call_sender
             .send(Call {
@@ -487,11 +1285,12 @@ impl WalletHandle {
                 }),
                 sender,
             })
+            .await
             .map_err(|_| anyhow::anyhow!("failed to send check_wallet call"))?;
 
         receiver
             .await
-            .context("wallet channel closed unexpectedly")?;
+            .context("wallet channel closed unexpectedly")??;
 
         Ok(())
     }
@@ -552,12 +1351,18 @@ impl WalletHandle {
         // Keep track of the sync progress to avoid calling
         // the listener twice with the same progress
         let mut current_progress = self.sync_progress().await;
+        let mut sync_rate = SyncRate::new();
 
         // Continue polling until the sync is complete
         loop {
             // Get the current sync status
-            let (synced, sync_progress) =
-                { (self.synchronized().await, self.sync_progress().await) };
+            let (synced, sync_progress) = self
+                .call(|wallet| (wallet.synchronized(), wallet.sync_progress()))
+                .await;
+            let sync_progress = SyncProgress {
+                blocks_per_second: sync_rate.update(Instant::now(), sync_progress.current_block),
+                ..sync_progress
+            };
 
             // Notify the listener (if it exists)
             if sync_progress > current_progress {
@@ -607,6 +1412,154 @@ impl WalletHandle {
         self.call(move |wallet| wallet.scan_transaction(txid)).await
     }
 
+    /// Get a proof that the transaction `txid` paid `address`.
+    /// The proof can be checked by anyone with [`WalletHandle::check_tx_proof`].
+    pub async fn get_tx_proof(
+        &self,
+        txid: String,
+        address: &monero::Address,
+        message: String,
+    ) -> anyhow::Result<String> {
+        let address = *address;
+        self.call(move |wallet| wallet.get_tx_proof(&txid, &address, &message))
+            .await
+    }
+
+    /// Check a transaction proof.
+    /// Returns `None` if the signature is invalid, otherwise the status of the transaction.
+    pub async fn check_tx_proof(
+        &self,
+        txid: String,
+        address: &monero::Address,
+        message: String,
+        signature: String,
+    ) -> anyhow::Result<Option<TxStatus>> {
+        let address = *address;
+        self.call(move |wallet| wallet.check_tx_proof(&txid, &address, &message, &signature))
+            .await
+    }
+
+    /// Get a proof that this wallet sent the transaction `txid`.
+    pub async fn get_spend_proof(&self, txid: String, message: String) -> anyhow::Result<String> {
+        self.call(move |wallet| wallet.get_spend_proof(&txid, &message))
+            .await
+    }
+
+    /// Check a spend proof. Returns whether the signature is valid.
+    pub async fn check_spend_proof(
+        &self,
+        txid: String,
+        message: String,
+        signature: String,
+    ) -> anyhow::Result<bool> {
+        self.call(move |wallet| wallet.check_spend_proof(&txid, &message, &signature))
+            .await
+    }
+
+    /// Get a proof that the wallet holds at least `amount` of unspent funds.
+    /// Pass `None` to prove the entire balance.
+    pub async fn get_reserve_proof(
+        &self,
+        amount: Option<monero::Amount>,
+        message: String,
+    ) -> anyhow::Result<String> {
+        self.call(move |wallet| wallet.get_reserve_proof(amount, &message))
+            .await
+    }
+
+    /// Check a reserve proof made by the owner of `address`.
+    /// Returns `None` if the signature is invalid.
+    pub async fn check_reserve_proof(
+        &self,
+        address: &monero::Address,
+        message: String,
+        signature: String,
+    ) -> anyhow::Result<Option<ReserveProofStatus>> {
+        let address = *address;
+        self.call(move |wallet| wallet.check_reserve_proof(&address, &message, &signature))
+            .await
+    }
+
+    /// Sign an arbitrary message with the spend key of the main address.
+    /// Can be used to prove ownership of the address.
+    pub async fn sign_message(&self, message: String) -> anyhow::Result<String> {
+        self.call(move |wallet| wallet.sign_message(&message)).await
+    }
+
+    /// Verify that `signature` is a valid signature of `message` by the owner of `address`.
+    pub async fn verify_message(
+        &self,
+        message: String,
+        address: &monero::Address,
+        signature: String,
+    ) -> anyhow::Result<bool> {
+        let address = *address;
+        self.call(move |wallet| wallet.verify_message(&message, &address, &signature))
+            .await
+    }
+
+    /// Export the wallet's outputs to a file.
+    /// Used by a view-only wallet so an offline (cold) wallet can sign transactions.
+    /// If `all` is false, only outputs that haven't been exported before are included.
+    pub async fn export_outputs(&self, path: String, all: bool) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.export_outputs(&path, all))
+            .await
+    }
+
+    /// Import outputs exported by a view-only wallet into the cold wallet.
+    pub async fn import_outputs(&self, path: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.import_outputs(&path)).await
+    }
+
+    /// Export signed key images to a file.
+    /// Used by the cold wallet so the view-only wallet can detect spent outputs.
+    pub async fn export_key_images(&self, path: String, all: bool) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.export_key_images(&path, all))
+            .await
+    }
+
+    /// Import signed key images exported by the cold wallet into the view-only wallet.
+    pub async fn import_key_images(&self, path: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.import_key_images(&path))
+            .await
+    }
+
+    /// Create a transfer on a view-only wallet and write the unsigned transaction to `path`
+    /// instead of publishing it. Sign it with [`WalletHandle::sign_unsigned_transaction`]
+    /// on the cold wallet.
+    pub async fn create_unsigned_transfer(
+        &self,
+        address: &monero::Address,
+        amount: monero::Amount,
+        path: String,
+    ) -> anyhow::Result<()> {
+        let address = *address;
+        self.call(move |wallet| wallet.create_unsigned_transfer(&address, amount, &path))
+            .await
+    }
+
+    /// Sign the unsigned transaction at `unsigned_path` and write the signed
+    /// transaction to `signed_path`.
+    pub async fn sign_unsigned_transaction(
+        &self,
+        unsigned_path: String,
+        signed_path: String,
+    ) -> anyhow::Result<UnsignedTxSummary> {
+        self.call(move |wallet| wallet.sign_unsigned_transaction(&unsigned_path, &signed_path))
+            .await
+    }
+
+    /// Publish a transaction signed by the cold wallet.
+    pub async fn submit_signed_transaction(&self, path: String) -> anyhow::Result<()> {
+        self.call(move |wallet| wallet.submit_signed_transaction(&path))
+            .await
+    }
+
+    /// Get access to the address book of the wallet.
+    pub fn address_book(&self) -> AddressBook<'_> {
+        AddressBook { wallet: self }
+    }
+
     /// Wait until a transaction is confirmed.
     pub async fn wait_until_confirmed(
         &self,
@@ -676,11 +1629,7 @@ impl WalletHandle {
 }
 
 impl Wallet {
-    fn new(
-        wallet: FfiWallet,
-        manager: WalletManager,
-        call_receiver: UnboundedReceiver<Call>,
-    ) -> Self {
+    fn new(wallet: FfiWallet, manager: WalletManager, call_receiver: Receiver<Call>) -> Self {
         Self {
             wallet,
             manager,
@@ -688,22 +1637,84 @@ impl Wallet {
         }
     }
 
-    fn run(&mut self) {
-        while let Some(call) = self.call_receiver.blocking_recv() {
-            let result = (call.function)(&mut self.wallet);
-            call.sender
-                .send(result)
-                .expect("failed to send result back to caller");
+    /// Execute calls until the [`WalletHandle`] is dropped, then close the wallet.
+    /// Returns the result of closing the wallet.
+    fn run(&mut self) -> anyhow::Result<()> {
+        // A minimal runtime which only lets us wait for calls with a timeout, so the
+        // wallet can be stored and its daemon checked periodically while no calls come in.
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("runtime to be created");
+
+        loop {
+            if self.wallet.time_until_auto_store() == Some(Duration::ZERO) {
+                self.wallet.auto_store();
+            }
+
+            if self.wallet.time_until_daemon_check() == Some(Duration::ZERO) {
+                self.wallet.check_daemon();
+            }
+
+            let next_task = [
+                self.wallet.time_until_auto_store(),
+                self.wallet.time_until_daemon_check(),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+
+            let call = match next_task {
+                Some(remaining) => {
+                    match runtime
+                        .block_on(tokio::time::timeout(remaining, self.call_receiver.recv()))
+                    {
+                        Ok(call) => call,
+                        // No call came in before the next task is due
+                        Err(_) => continue,
+                    }
+                }
+                None => self.call_receiver.blocking_recv(),
+            };
+
+            let Some(call) = call else {
+                break;
+            };
+
+            // Nobody is waiting for the result anymore (the call timed out
+            // or was cancelled while queued), so don't execute it at all
+            if call.sender.is_closed() {
+                tracing::debug!("Skipping wallet call which was cancelled before it started");
+                continue;
+            }
+
+            let function = call.function;
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| function(&mut self.wallet)))
+                .map_err(CallError::from_panic);
+            let panicked = result.is_err();
+
+            // The caller may have given up while the call was running
+            if call.sender.send(result).is_err() {
+                tracing::debug!("Discarding result of wallet call, the caller stopped waiting");
+            }
+
+            // We can't tell what state the panic left the wallet in, so we stop
+            // using it. Callers get an error and can reopen the wallet.
+            if panicked {
+                tracing::error!("Wallet call panicked, closing wallet");
+                self.call_receiver.close();
+                break;
+            }
         }
 
         tracing::info!(
             wallet=%self.wallet.path(),
-            "Wallet handle dropped, closing wallet and exiting thread",
+            "Closing wallet and exiting thread",
         );
 
         let result = self.manager.close_wallet(&mut self.wallet);
 
-        if let Err(e) = result {
+        if let Err(e) = &result {
             tracing::error!("Failed to close wallet: {}", e);
             // If we fail to close the wallet, we can't do anything about it.
             // This results in it being leaked.
@@ -717,55 +1728,335 @@ impl Wallet {
         bridge::log::uninstall_log_callback()
             .context("Failed to uninstall log callback: FFI call failed with exception")
             .expect("Shouldn't panic");
+
+        result
     }
 }
 
-impl WalletManager {
-    /// For now we don't support custom difficulty
-    const DEFAULT_KDF_ROUNDS: u64 = 1;
-
-    /// Get the wallet manager instance.
-    /// You can optionally pass a daemon with which the wallet manager and
-    /// all wallets opened by the manager will connect.
-    pub fn new(daemon: Daemon, span_name: &str) -> anyhow::Result<Self> {
-        // Install the log callback to route c++ logs to tracing.
-        let_cxx_string!(span_name = span_name);
-        bridge::log::install_log_callback(&span_name)
-            .context("Failed to install log callback: FFI call failed with exception")?;
+impl WalletManagerHandle {
+    /// Spawn the thread hosting the wallet manager.
+    /// All wallets opened through this handle connect to `daemon`.
+    pub async fn new(name: &str, daemon: Daemon, network: monero::Network) -> anyhow::Result<Self> {
+        let (call_sender, mut call_receiver) = channel(WalletHandle::CALL_QUEUE_CAPACITY);
+        let (init_sender, init_receiver) = oneshot::channel();
 
-        let manager = ffi::getWalletManager()
-            .context("Couldn't get wallet manager: FFi call failed with exception")?;
+        let span_name = name.to_string();
+        let thread_name = format!("wallet-manager-{}", name);
 
-        let mut manager = Self {
-            inner: RawWalletManager::new(manager),
-        };
+        // Capture current dispatcher before spawning
+        let current_dispatcher = tracing::dispatcher::get_default(|d| d.clone());
 
-        manager.set_daemon_address(&daemon.address);
+        std::thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || {
+                // Set the dispatcher for this thread
+                let _guard = tracing::dispatcher::set_default(&current_dispatcher);
 
-        Ok(manager)
-    }
+                let manager = match WalletManager::new(daemon.clone(), &span_name) {
+                    Ok(manager) => manager,
+                    Err(e) => {
+                        let _ = init_sender.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = init_sender.send(Ok(()));
 
-    /// Create a new wallet, or open if it already exists.
-    pub fn open_or_create_wallet(
-        &mut self,
-        path: &str,
-        password: Option<&str>,
-        network: monero::Network,
-        background_sync: bool,
-        daemon: Daemon,
-    ) -> anyhow::Result<FfiWallet> {
-        tracing::debug!(%path, "Opening or creating wallet");
+                let mut hosted = HostedWallets {
+                    manager,
+                    wallets: HashMap::new(),
+                    daemon,
+                    network,
+                };
 
-        // If we haven't loaded the wallet, but it already exists, open it.
-        if self.wallet_exists(path) {
-            tracing::debug!(wallet=%path, "Wallet already exists, opening it");
+                hosted.run(&mut call_receiver);
+            })
+            .context("Couldn't start wallet manager thread")?;
 
-            return self
-                .open_wallet(path, password, network, background_sync, daemon)
-                .context(format!("Failed to open wallet `{}`", &path));
-        }
+        init_receiver
+            .await
+            .context("Wallet manager thread exited unexpectedly")?
+            .context("Failed to create wallet manager")?;
 
-        tracing::debug!(%path, "Wallet doesn't exist, creating it");
+        Ok(Self { call_sender })
+    }
+
+    /// Open an existing wallet or create a new one with a random seed.
+    /// Does nothing if the wallet is already open.
+    pub async fn open_or_create(
+        &self,
+        path: String,
+        password: Option<String>,
+        language: SeedLanguage,
+        background_sync: bool,
+    ) -> anyhow::Result<()> {
+        self.execute(move |hosted| {
+            hosted.open(path.clone(), |manager, daemon, network| {
+                manager.open_or_create_wallet(
+                    &path,
+                    password.as_deref(),
+                    network,
+                    language,
+                    background_sync,
+                    daemon,
+                )
+            })
+        })
+        .await?
+    }
+
+    /// Open an existing wallet or create a new one from spend/view keys.
+    /// Does nothing if the wallet is already open.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open_or_create_from_keys(
+        &self,
+        path: String,
+        password: Option<String>,
+        address: monero::Address,
+        view_key: monero::PrivateKey,
+        spend_key: monero::PrivateKey,
+        restore_height: u64,
+        background_sync: bool,
+    ) -> anyhow::Result<()> {
+        self.execute(move |hosted| {
+            hosted.open(path.clone(), |manager, daemon, network| {
+                manager.open_or_create_wallet_from_keys(
+                    &path,
+                    password.as_deref(),
+                    network,
+                    &address,
+                    view_key,
+                    spend_key,
+                    restore_height,
+                    background_sync,
+                    daemon,
+                )
+            })
+        })
+        .await?
+    }
+
+    /// Store and close the wallet at `path`.
+    pub async fn close(&self, path: &str) -> anyhow::Result<()> {
+        let path = path.to_string();
+        self.execute(move |hosted| hosted.close(&path)).await?
+    }
+
+    /// Get the paths of all open wallets.
+    pub async fn list(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .execute(move |hosted| hosted.wallets.keys().cloned().collect())
+            .await?)
+    }
+
+    /// Set the number of key derivation rounds used to encrypt the files of
+    /// wallets which are created or opened afterwards (default: 1).
+    ///
+    /// Increasing it hardens password protected wallets against brute force.
+    /// A wallet can only be opened with the same number of rounds it was created with.
+    pub async fn set_kdf_rounds(&self, kdf_rounds: u64) -> anyhow::Result<()> {
+        self.execute(move |hosted| hosted.manager.set_kdf_rounds(kdf_rounds))
+            .await?
+    }
+
+    /// Find all wallets in `dir` (and its subdirectories).
+    /// Returns the paths of the wallets, which can be passed to the open functions.
+    pub async fn list_wallets(&self, dir: String) -> anyhow::Result<Vec<String>> {
+        self.execute(move |hosted| hosted.manager.list_wallets(&dir))
+            .await?
+    }
+
+    /// Check whether `password` unlocks the wallet at `path`, without opening it.
+    pub async fn verify_wallet_password(
+        &self,
+        path: String,
+        password: String,
+    ) -> anyhow::Result<bool> {
+        self.execute(move |hosted| hosted.manager.verify_wallet_password(&path, &password))
+            .await?
+    }
+
+    /// Delete the files of the wallet at `path`.
+    /// Fails if the wallet is currently open.
+    ///
+    /// **Warning**: the funds are lost unless the seed (or keys) were backed up.
+    pub async fn delete_wallet(&self, path: String) -> anyhow::Result<()> {
+        self.execute(move |hosted| {
+            if hosted.wallets.contains_key(&path) {
+                bail!("Cannot delete wallet `{}` while it is open", path);
+            }
+
+            hosted.manager.delete_wallet(&path)
+        })
+        .await?
+    }
+
+    /// Execute a function on the wallet at `path` and return the result.
+    /// Fails if the wallet isn't open.
+    pub async fn call<F, R>(&self, path: &str, function: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut FfiWallet) -> R + Send + 'static,
+        R: Sized + Send + 'static,
+    {
+        let path = path.to_string();
+        self.execute(move |hosted| {
+            let wallet = hosted
+                .wallets
+                .get_mut(&path)
+                .with_context(|| format!("Wallet `{}` is not open", path))?;
+
+            Ok(function(wallet))
+        })
+        .await?
+    }
+
+    /// Execute a function on the wallet manager thread and return the result.
+    /// Fails if the function panics or the thread isn't running anymore.
+    async fn execute<F, R>(&self, function: F) -> Result<R, CallError>
+    where
+        F: FnOnce(&mut HostedWallets) -> R + Send + 'static,
+        R: Sized + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        self.call_sender
+            .send(ManagerCall {
+                function: Box::new(move |hosted| Box::new(function(hosted)) as Box<dyn Any + Send>),
+                sender,
+            })
+            .await
+            .inspect_err(|e| tracing::error!(error=%e, "failed to send call"))
+            .map_err(|_| CallError::WalletThreadExited)?;
+
+        let result = receiver
+            .await
+            .map_err(|_| CallError::WalletThreadExited)??;
+
+        Ok(*result
+            .downcast::<R>() // We know that F returns R
+            .expect("return type to be consistent"))
+    }
+}
+
+impl HostedWallets {
+    /// Execute calls until the [`WalletManagerHandle`] is dropped, then close all wallets.
+    fn run(&mut self, call_receiver: &mut Receiver<ManagerCall>) {
+        while let Some(call) = call_receiver.blocking_recv() {
+            // Nobody is waiting for the result anymore, don't bother
+            if call.sender.is_closed() {
+                continue;
+            }
+
+            let function = call.function;
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| function(self)))
+                .map_err(CallError::from_panic);
+            let panicked = result.is_err();
+
+            let _ = call.sender.send(result);
+
+            // Same as in [`Wallet::run`], we don't know what state the panic left
+            // the wallets in. Close them and let the callers open a new handle.
+            if panicked {
+                tracing::error!("Wallet manager call panicked, closing wallets");
+                call_receiver.close();
+                break;
+            }
+        }
+
+        tracing::info!(
+            wallets = self.wallets.len(),
+            "Wallet manager handle dropped, closing wallets and exiting thread"
+        );
+
+        let paths: Vec<String> = self.wallets.keys().cloned().collect();
+        for path in paths {
+            if let Err(e) = self.close(&path) {
+                tracing::error!(wallet=%path, error=?e, "Failed to close wallet");
+            }
+        }
+
+        // Uninstall the log callback, see [`Wallet::run`].
+        bridge::log::uninstall_log_callback()
+            .context("Failed to uninstall log callback: FFI call failed with exception")
+            .expect("Shouldn't panic");
+    }
+
+    /// Open a wallet using `open`, unless it is already open.
+    fn open(
+        &mut self,
+        path: String,
+        open: impl FnOnce(&mut WalletManager, Daemon, monero::Network) -> anyhow::Result<FfiWallet>,
+    ) -> anyhow::Result<()> {
+        if self.wallets.contains_key(&path) {
+            tracing::debug!(wallet=%path, "Wallet is already open");
+            return Ok(());
+        }
+
+        let wallet = open(&mut self.manager, self.daemon.clone(), self.network)?;
+        self.wallets.insert(path, wallet);
+
+        Ok(())
+    }
+
+    /// Close the wallet at `path`.
+    fn close(&mut self, path: &str) -> anyhow::Result<()> {
+        let mut wallet = self
+            .wallets
+            .remove(path)
+            .with_context(|| format!("Wallet `{}` is not open", path))?;
+
+        self.manager.close_wallet(&mut wallet)
+    }
+}
+
+impl WalletManager {
+    /// The default number of key derivation rounds, same as monero-wallet-cli.
+    const DEFAULT_KDF_ROUNDS: u64 = 1;
+
+    /// Get the wallet manager instance.
+    /// You can optionally pass a daemon with which the wallet manager and
+    /// all wallets opened by the manager will connect.
+    pub fn new(daemon: Daemon, span_name: &str) -> anyhow::Result<Self> {
+        // Install the log callback to route c++ logs to tracing.
+        let_cxx_string!(span_name = span_name);
+        bridge::log::install_log_callback(&span_name)
+            .context("Failed to install log callback: FFI call failed with exception")?;
+
+        let manager = ffi::getWalletManager()
+            .context("Couldn't get wallet manager: FFi call failed with exception")?;
+
+        let mut manager = Self {
+            inner: RawWalletManager::new(manager),
+            kdf_rounds: Self::DEFAULT_KDF_ROUNDS,
+        };
+
+        manager.set_daemon_address(&daemon.address);
+
+        Ok(manager)
+    }
+
+    /// Create a new wallet, or open if it already exists.
+    /// `language` is only used for the seed of a new wallet.
+    pub fn open_or_create_wallet(
+        &mut self,
+        path: &str,
+        password: Option<&str>,
+        network: monero::Network,
+        language: SeedLanguage,
+        background_sync: bool,
+        daemon: Daemon,
+    ) -> anyhow::Result<FfiWallet> {
+        tracing::debug!(%path, "Opening or creating wallet");
+
+        // If we haven't loaded the wallet, but it already exists, open it.
+        if self.wallet_exists(path) {
+            tracing::debug!(wallet=%path, "Wallet already exists, opening it");
+
+            return self
+                .open_wallet(path, password, network, background_sync, daemon)
+                .context(format!("Failed to open wallet `{}`", &path));
+        }
+
+        tracing::debug!(%path, "Wallet doesn't exist, creating it");
 
         // Ensure the parent directory exists so the Monero library can write the wallet files
         if let Some(dir) = std::path::Path::new(path).parent() {
@@ -775,10 +2066,10 @@ impl WalletManager {
         }
 
         // Otherwise, create (and open) a new wallet.
-        let kdf_rounds = Self::DEFAULT_KDF_ROUNDS;
+        let kdf_rounds = self.kdf_rounds;
         let_cxx_string!(path = path);
         let_cxx_string!(password = password.unwrap_or(""));
-        let_cxx_string!(language = "English");
+        let_cxx_string!(language = language.name());
         let network_type = network.into();
 
         let wallet_pointer = self
@@ -845,7 +2136,7 @@ impl WalletManager {
         let_cxx_string!(address = address.to_string());
         let_cxx_string!(view_key = view_key.to_string());
         let_cxx_string!(spend_key = spend_key.to_string());
-        let kdf_rounds = Self::DEFAULT_KDF_ROUNDS;
+        let kdf_rounds = self.kdf_rounds;
 
         let wallet_pointer = self
             .inner
@@ -887,6 +2178,7 @@ impl WalletManager {
         background_sync: bool,
         daemon: Daemon,
     ) -> anyhow::Result<FfiWallet> {
+        // The seed language is detected from the words of the mnemonic
         tracing::debug!(%path, "Recovering wallet from seed");
 
         let_cxx_string!(path = path);
@@ -904,7 +2196,7 @@ impl WalletManager {
                 &mnemonic,
                 network_type,
                 restore_height,
-                Self::DEFAULT_KDF_ROUNDS,
+                self.kdf_rounds,
                 &seed_offset,
             )
             .context("Failed to recover wallet from seed: FFI call failed with exception")?;
@@ -916,6 +2208,62 @@ impl WalletManager {
         Ok(wallet)
     }
 
+    /// Create a new wallet whose keys are stored on a hardware device.
+    ///
+    /// The user may have to confirm the creation on the device.
+    #[cfg(feature = "hardware-wallets")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_wallet_from_device(
+        &mut self,
+        path: &str,
+        password: Option<&str>,
+        network: monero::Network,
+        device: HardwareDevice,
+        restore_height: u64,
+        background_sync: bool,
+        daemon: Daemon,
+    ) -> anyhow::Result<FfiWallet> {
+        tracing::debug!(%path, ?device, restore_height, "Creating wallet from hardware device");
+
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!("failed to create wallet directory `{}`", dir.display())
+            })?;
+        }
+
+        let_cxx_string!(path = path);
+        let_cxx_string!(password = password.unwrap_or(""));
+        let_cxx_string!(device_name = device.device_name());
+        let_cxx_string!(subaddress_lookahead = "");
+        let network_type = network.into();
+
+        let wallet_pointer = unsafe {
+            self.inner.pinned().createWalletFromDevice(
+                &path,
+                &password,
+                network_type,
+                &device_name,
+                restore_height,
+                &subaddress_lookahead,
+                self.kdf_rounds,
+                std::ptr::null_mut(),
+            )
+        }
+        .context("Failed to create wallet from device: FFI call failed with exception")?;
+
+        if wallet_pointer.is_null() {
+            anyhow::bail!("Failed to create wallet from device, got null pointer");
+        }
+
+        let raw_wallet = RawWallet::new(wallet_pointer);
+        let wallet = FfiWallet::new(raw_wallet, background_sync, daemon).context(format!(
+            "Failed to initialize wallet `{}` from device",
+            &path
+        ))?;
+
+        Ok(wallet)
+    }
+
     /// Close a wallet, storing the wallet state.
     fn close_wallet(&mut self, wallet: &mut FfiWallet) -> anyhow::Result<()> {
         tracing::info!(wallet=%wallet.filename(), "Closing wallet");
@@ -931,7 +2279,8 @@ impl WalletManager {
         Ok(())
     }
 
-    /// Open a wallet. Only used internally. Use [`WalletManager::open_or_create_wallet`] instead.
+    /// Open an existing wallet. Only used internally.
+    /// Use [`WalletManager::open_or_create_wallet`] unless the wallet is known to exist.
     ///
     /// Todo: add listener support?
     fn open_wallet(
@@ -947,7 +2296,7 @@ impl WalletManager {
         let_cxx_string!(path = path);
         let_cxx_string!(password = password.unwrap_or(""));
         let network_type = network_type.into();
-        let kdf_rounds = Self::DEFAULT_KDF_ROUNDS;
+        let kdf_rounds = self.kdf_rounds;
 
         let wallet_pointer = unsafe {
             self.inner.pinned().openWallet(
@@ -985,6 +2334,83 @@ impl WalletManager {
             .expect("Shouldn't panic");
     }
 
+    /// Set the number of key derivation rounds used to encrypt wallets which
+    /// are created or opened afterwards. More rounds make brute forcing the
+    /// password harder, but also make opening the wallet slower.
+    pub fn set_kdf_rounds(&mut self, kdf_rounds: u64) -> anyhow::Result<()> {
+        if kdf_rounds == 0 {
+            bail!("KDF rounds must be at least 1");
+        }
+
+        self.kdf_rounds = kdf_rounds;
+
+        Ok(())
+    }
+
+    /// Find all wallets in a directory (recursively) and return their paths.
+    pub fn list_wallets(&mut self, dir: &str) -> anyhow::Result<Vec<String>> {
+        let_cxx_string!(dir = dir);
+
+        let keys_files = ffi::walletManagerFindWallets(self.inner.pinned(), &dir)
+            .context("Failed to find wallets: FFI call failed with exception")?;
+
+        // The paths point to the keys files, but we identify wallets by the path without the extension
+        Ok(keys_files
+            .iter()
+            .map(|keys_file| {
+                let keys_file = keys_file.to_string();
+                keys_file
+                    .strip_suffix(".keys")
+                    .map(ToString::to_string)
+                    .unwrap_or(keys_file)
+            })
+            .collect())
+    }
+
+    /// Check whether the password unlocks the wallet at `path`.
+    pub fn verify_wallet_password(&self, path: &str, password: &str) -> anyhow::Result<bool> {
+        let keys_file_name = format!("{}.keys", path);
+
+        if !std::path::Path::new(&keys_file_name).exists() {
+            bail!("Wallet `{}` does not exist", path);
+        }
+
+        let_cxx_string!(keys_file_name = keys_file_name);
+        let_cxx_string!(password = password);
+
+        // Decrypting the view key is enough to verify the password, and works for view-only wallets too
+        self.inner
+            .verifyWalletPassword(&keys_file_name, &password, true, self.kdf_rounds)
+            .context("Failed to verify wallet password: FFI call failed with exception")
+    }
+
+    /// Delete the files of the wallet at `path`. The wallet must not be open.
+    pub fn delete_wallet(&mut self, path: &str) -> anyhow::Result<()> {
+        if !self.wallet_exists(path) {
+            bail!("Wallet `{}` does not exist", path);
+        }
+
+        tracing::warn!(wallet=%path, "Deleting wallet");
+
+        // The cache, the keys and the address file. Only the keys file is guaranteed to exist.
+        for file in [
+            path.to_string(),
+            format!("{}.keys", path),
+            format!("{}.address.txt", path),
+        ] {
+            match std::fs::remove_file(&file) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to delete wallet file `{}`", file))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Check if a wallet exists at the given path.
     pub fn wallet_exists(&mut self, path: &str) -> bool {
         tracing::debug!(%path, "Checking if wallet exists");
@@ -1018,8 +2444,50 @@ impl RawWalletManager {
     }
 }
 
+impl AddressBook<'_> {
+    /// Get all entries of the address book.
+    pub async fn list(&self) -> anyhow::Result<Vec<AddressBookEntry>> {
+        self.wallet
+            .call(move |wallet| wallet.address_book_entries())
+            .await
+    }
+
+    /// Add an entry to the address book.
+    pub async fn add(
+        &self,
+        address: &monero::Address,
+        description: String,
+        payment_id: Option<String>,
+    ) -> anyhow::Result<()> {
+        let address = *address;
+        self.wallet
+            .call(move |wallet| {
+                wallet.add_address_book_entry(&address, &description, payment_id.as_deref())
+            })
+            .await
+    }
+
+    /// Remove the entry with the given row id from the address book.
+    pub async fn remove(&self, row_id: usize) -> anyhow::Result<()> {
+        self.wallet
+            .call(move |wallet| wallet.remove_address_book_entry(row_id))
+            .await
+    }
+
+    /// Change the description of an entry in the address book.
+    pub async fn set_description(&self, row_id: usize, description: String) -> anyhow::Result<()> {
+        self.wallet
+            .call(move |wallet| wallet.set_address_book_description(row_id, &description))
+            .await
+    }
+}
+
 impl FfiWallet {
     const MAIN_ACCOUNT_INDEX: u32 = 0;
+    const DEFAULT_AUTO_STORE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+    const DEFAULT_DAEMON_FAILOVER_TIMEOUT: Duration = Duration::from_secs(30);
+    /// How often we check whether the daemon is reachable, if there is another one to switch to.
+    const DAEMON_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
     /// Create and initialize new wallet from a raw C++ wallet pointer.
     fn new(inner: RawWallet, background_sync: bool, daemon: Daemon) -> anyhow::Result<Self> {
@@ -1027,7 +2495,17 @@ impl FfiWallet {
             anyhow::bail!("Failed to create wallet: got null pointer");
         }
 
-        let mut wallet = Self { inner };
+        let mut wallet = Self {
+            inner,
+            auto_store_interval: Some(Self::DEFAULT_AUTO_STORE_INTERVAL),
+            last_stored: Instant::now(),
+            daemons: vec![daemon.clone()],
+            daemon_index: 0,
+            daemon_failover_timeout: Self::DEFAULT_DAEMON_FAILOVER_TIMEOUT,
+            disconnected_since: None,
+            last_daemon_check: Instant::now(),
+            daemon_switch_listener: None,
+        };
         wallet
             .check_error()
             .context("Something went wrong while creating the wallet (not null pointer, though)")?;
@@ -1047,7 +2525,7 @@ impl FfiWallet {
         .map_err(|e| anyhow!("Failed to initialize wallet: {e}"))?;
         tracing::debug!("Initialized wallet, setting daemon address");
 
-        wallet.set_daemon_address(&daemon.address)?;
+        wallet.set_daemon(&daemon)?;
 
         if background_sync {
             tracing::debug!("Background sync enabled, starting refresh thread");
@@ -1089,31 +2567,127 @@ impl FfiWallet {
         monero::Address::from_str(&address.to_string()).expect("wallet's own address to be valid")
     }
 
-    pub fn set_daemon_address(&mut self, address: &str) -> anyhow::Result<()> {
-        tracing::debug!(%address, "Setting daemon address");
-
-        let_cxx_string!(address = address);
-        let raw_wallet = &mut self.inner;
-
-        let success = ffi::setWalletDaemon(raw_wallet.pinned(), &address)
-            .context("Failed to set daemon address: FFI call failed with exception")?;
-
-        if !success {
-            self.check_error().context("Failed to set daemon address")?;
-            anyhow::bail!("Failed to set daemon address");
+    /// Connect to the given daemon, with its certificate pinned if configured.
+    fn set_daemon(&mut self, daemon: &Daemon) -> anyhow::Result<()> {
+        if daemon.is_pinned() {
+            self.set_pinned_daemon(daemon)
+        } else {
+            self.set_daemon_address(&daemon.address)
         }
+    }
 
-        Ok(())
+    /// Set the daemons to fail over to. The first one has to be the daemon in use.
+    fn set_daemons(&mut self, daemons: Vec<Daemon>) {
+        self.daemons = daemons;
+        self.daemon_index = 0;
+        self.disconnected_since = None;
     }
 
-    /// Get the main address of the walllet (account 0, address 0).
-    pub fn main_address(&self) -> monero::Address {
-        self.address(Self::MAIN_ACCOUNT_INDEX, 0)
+    /// The daemon the wallet currently uses.
+    fn current_daemon(&self) -> Daemon {
+        self.daemons[self.daemon_index].clone()
     }
 
-    /// Initialize the wallet and download initial values from the remote node.
-    /// Does not actuallyt sync the wallet, use any of the refresh methods to do that.
-    fn init(&mut self, daemon_address: &str, ssl: bool) -> anyhow::Result<()> {
+    /// The time left until we should check whether the daemon is reachable.
+    /// `None` if there is no other daemon to switch to anyway.
+    fn time_until_daemon_check(&self) -> Option<Duration> {
+        (self.daemons.len() > 1)
+            .then(|| Self::DAEMON_CHECK_INTERVAL.saturating_sub(self.last_daemon_check.elapsed()))
+    }
+
+    /// Check whether the daemon is reachable and switch to the next one
+    /// if it has been unreachable for longer than the failover timeout.
+    fn check_daemon(&mut self) {
+        self.last_daemon_check = Instant::now();
+
+        if self.connected() {
+            self.disconnected_since = None;
+            return;
+        }
+
+        let disconnected_since = *self.disconnected_since.get_or_insert_with(Instant::now);
+        if disconnected_since.elapsed() < self.daemon_failover_timeout {
+            return;
+        }
+
+        let from = self.current_daemon();
+        self.daemon_index = (self.daemon_index + 1) % self.daemons.len();
+        let to = self.current_daemon();
+
+        tracing::warn!(
+            from=%from.address,
+            to=%to.address,
+            unreachable_for_secs=disconnected_since.elapsed().as_secs(),
+            "Daemon is unreachable, switching to the next one"
+        );
+
+        if let Err(error) = self.set_daemon(&to) {
+            tracing::error!(?error, address=%to.address, "Failed to switch daemon");
+        }
+
+        // Give the new daemon the full timeout before switching again
+        self.disconnected_since = Some(Instant::now());
+
+        if let Some(listener) = &self.daemon_switch_listener {
+            listener(DaemonSwitch { from, to });
+        }
+    }
+
+    /// Set the daemon address and only accept the TLS certificates allowed by
+    /// the daemon's CA file and fingerprints.
+    fn set_pinned_daemon(&mut self, daemon: &Daemon) -> anyhow::Result<()> {
+        tracing::debug!(address=%daemon.address, ca_file=?daemon.ssl_ca_file, fingerprints=?daemon.ssl_allowed_fingerprints, "Setting daemon address with pinned certificate");
+
+        if !daemon.ssl {
+            bail!("Certificate pinning requires an SSL connection to the daemon");
+        }
+
+        let_cxx_string!(address = &daemon.address);
+        let_cxx_string!(ca_file = daemon.ssl_ca_file.as_deref().unwrap_or(""));
+
+        let mut fingerprints = CxxVector::<CxxString>::new();
+        for fingerprint in &daemon.ssl_allowed_fingerprints {
+            let_cxx_string!(fingerprint = normalize_fingerprint(fingerprint)?);
+            ffi::vector_string_push_back(fingerprints.pin_mut(), &fingerprint);
+        }
+
+        let success =
+            ffi::setWalletDaemonPinned(self.inner.pinned(), &address, &ca_file, &fingerprints)
+                .context("Failed to set pinned daemon: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to set pinned daemon")?;
+            bail!("Failed to set pinned daemon (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    pub fn set_daemon_address(&mut self, address: &str) -> anyhow::Result<()> {
+        tracing::debug!(%address, "Setting daemon address");
+
+        let_cxx_string!(address = address);
+        let raw_wallet = &mut self.inner;
+
+        let success = ffi::setWalletDaemon(raw_wallet.pinned(), &address)
+            .context("Failed to set daemon address: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to set daemon address")?;
+            anyhow::bail!("Failed to set daemon address");
+        }
+
+        Ok(())
+    }
+
+    /// Get the main address of the walllet (account 0, address 0).
+    pub fn main_address(&self) -> monero::Address {
+        self.address(Self::MAIN_ACCOUNT_INDEX, 0)
+    }
+
+    /// Initialize the wallet and download initial values from the remote node.
+    /// Does not actuallyt sync the wallet, use any of the refresh methods to do that.
+    fn init(&mut self, daemon_address: &str, ssl: bool) -> anyhow::Result<()> {
         tracing::debug!(%daemon_address, %ssl, "Initializing wallet");
 
         let_cxx_string!(daemon_address = daemon_address);
@@ -1147,7 +2721,7 @@ impl FfiWallet {
     /// Get the sync progress of the wallet as a percentage.
     ///
     /// Returns a zeroed sync progress if the daemon is not connected.
-    fn sync_progress(&self) -> SyncProgress {
+    pub fn sync_progress(&self) -> SyncProgress {
         let current_block = self
             .inner
             .blockChainHeight()
@@ -1166,7 +2740,8 @@ impl FfiWallet {
         progress
     }
 
-    fn connected(&self) -> bool {
+    /// Check if the wallet is connected to its daemon.
+    pub fn connected(&self) -> bool {
         match self
             .inner
             .connected()
@@ -1197,11 +2772,9 @@ impl FfiWallet {
     }
 
     /// Set whether the daemon is trusted.
-    ///
-    /// This is needed for regnet compatibility.
-    ///
-    /// _Do not use for anything besides testing._
     fn set_trusted_daemon(&mut self, trusted: bool) {
+        tracing::debug!(trusted, "Setting whether the daemon is trusted");
+
         self.inner
             .pinned()
             .setTrustedDaemon(trusted)
@@ -1209,6 +2782,14 @@ impl FfiWallet {
             .expect("Shouldn't panic");
     }
 
+    /// Whether the daemon is trusted.
+    fn trusted_daemon(&self) -> bool {
+        self.inner
+            .trustedDaemon()
+            .context("Failed to get whether the daemon is trusted: FFI call failed with exception")
+            .expect("Shouldn't panic")
+    }
+
     /// Force a full sync of the wallet.
     /// Use only for regtest environments, utterly slow otherwise.
     fn force_full_sync(&mut self) {
@@ -1219,6 +2800,34 @@ impl FfiWallet {
             .expect("Shouldn't panic");
     }
 
+    /// Get the hardware device the wallet's keys are stored on.
+    /// Returns `None` for regular (software) wallets.
+    fn hardware_device(&self) -> Option<HardwareDevice> {
+        match self
+            .inner
+            .getDeviceType()
+            .context("Failed to get device type: FFI call failed with exception")
+            .expect("Shouldn't panic")
+        {
+            ffi::Device::Ledger => Some(HardwareDevice::Ledger),
+            ffi::Device::Trezor => Some(HardwareDevice::Trezor),
+            // Software wallets, and anything C++ might come up with in the future.
+            _ => None,
+        }
+    }
+
+    /// Reconnect to the hardware device.
+    /// Returns whether the device is connected afterwards.
+    fn reconnect_device(&mut self) -> bool {
+        match self.inner.pinned().reconnectDevice() {
+            Ok(connected) => connected,
+            Err(e) => {
+                tracing::debug!(error=%e, "Failed to reconnect to hardware device");
+                false
+            }
+        }
+    }
+
     /// Start the background refresh thread (refreshes every 10 seconds).
     fn start_refresh_thread(&mut self) {
         self.inner
@@ -1266,8 +2875,211 @@ impl FfiWallet {
             .expect("Shouldn't panic")
     }
 
+    /// Store the wallet to its file.
+    fn store(&mut self) -> anyhow::Result<()> {
+        tracing::debug!(wallet=%self.filename(), "Storing wallet");
+
+        // An empty path means the wallet's current file
+        let_cxx_string!(path = "");
+
+        let success = self
+            .inner
+            .pinned()
+            .store(&path)
+            .context("Failed to store wallet: FFI call failed with exception")?;
+
+        self.last_stored = Instant::now();
+
+        if !success {
+            self.check_error().context("Failed to store wallet")?;
+            bail!("Failed to store wallet (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Store the wallet because the auto store interval has elapsed.
+    /// Errors are only logged, we'll try again after the next interval.
+    fn auto_store(&mut self) {
+        if let Err(e) = self.store() {
+            tracing::warn!(error=?e, "Failed to store wallet periodically");
+        }
+
+        // Also wait for the next interval if storing failed before reaching the FFI call
+        self.last_stored = Instant::now();
+    }
+
+    /// Set how often the wallet is stored, `None` to disable.
+    fn set_auto_store_interval(&mut self, interval: Option<Duration>) {
+        self.auto_store_interval = interval;
+    }
+
+    /// The time left until the wallet should be stored again.
+    /// `None` if the wallet isn't stored periodically.
+    fn time_until_auto_store(&self) -> Option<Duration> {
+        self.auto_store_interval
+            .map(|interval| interval.saturating_sub(self.last_stored.elapsed()))
+    }
+
+    /// Set the height from which the wallet scans the blockchain.
+    fn set_refresh_from_block_height(&mut self, height: u64) {
+        self.inner
+            .pinned()
+            .setRefreshFromBlockHeight(height)
+            .context("Failed to set refresh from block height: FFI call failed with exception")
+            .expect("Shouldn't panic");
+    }
+
+    /// Enable (or disable) background sync.
+    /// Reuses the wallet password for the background cache if none is given.
+    fn setup_background_sync(
+        &mut self,
+        enabled: bool,
+        wallet_password: &str,
+        background_cache_password: Option<&str>,
+    ) -> anyhow::Result<()> {
+        tracing::debug!(enabled, "Setting up background sync");
+
+        let_cxx_string!(wallet_password = wallet_password);
+        let_cxx_string!(background_cache_password = background_cache_password.unwrap_or(""));
+
+        let success = ffi::setupBackgroundSync(
+            self.inner.pinned(),
+            enabled,
+            &wallet_password,
+            &background_cache_password,
+        )
+        .context("Failed to set up background sync: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to set up background sync")?;
+            bail!("Failed to set up background sync (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Whether background sync has been set up.
+    fn background_sync_enabled(&self) -> bool {
+        ffi::backgroundSyncEnabled(&self.inner)
+            .context("Failed to get background sync type: FFI call failed with exception")
+            .expect("Shouldn't panic")
+    }
+
+    /// Wipe the spend key and continue syncing with the view key only.
+    fn start_background_sync(&mut self) -> anyhow::Result<()> {
+        tracing::debug!("Starting background sync");
+
+        let success = self
+            .inner
+            .pinned()
+            .startBackgroundSync()
+            .context("Failed to start background sync: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to start background sync")?;
+            bail!("Failed to start background sync (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Restore the spend key and process the background synced transactions.
+    fn stop_background_sync(&mut self, wallet_password: &str) -> anyhow::Result<()> {
+        tracing::debug!("Stopping background sync");
+
+        let_cxx_string!(wallet_password = wallet_password);
+
+        let success = self
+            .inner
+            .pinned()
+            .stopBackgroundSync(&wallet_password)
+            .context("Failed to stop background sync: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to stop background sync")?;
+            bail!("Failed to stop background sync (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Whether the wallet is currently syncing in the background.
+    fn is_background_syncing(&self) -> bool {
+        self.inner
+            .isBackgroundSyncing()
+            .context("Failed to check background sync: FFI call failed with exception")
+            .expect("Shouldn't panic")
+    }
+
+    /// Get the size of the cache file in bytes.
+    fn cache_size(&self) -> anyhow::Result<u64> {
+        let path = self.path();
+
+        let metadata = std::fs::metadata(&path)
+            .with_context(|| format!("Failed to read metadata of wallet cache `{}`", path))?;
+
+        Ok(metadata.len())
+    }
+
+    /// Rebuild the cache by rescanning the blockchain and store it.
+    fn compact_cache(&mut self) -> anyhow::Result<u64> {
+        // The cache file doesn't exist until the wallet is stored for the first time
+        let size_before = self.cache_size().ok();
+
+        self.rescan_blockchain()
+            .context("Failed to compact wallet cache")?;
+        self.store().context("Failed to compact wallet cache")?;
+
+        let size_after = self.cache_size()?;
+        tracing::info!(?size_before, size_after, "Compacted wallet cache");
+
+        Ok(size_after)
+    }
+
+    /// Rescan the blockchain from the restore height, discarding cached data.
+    fn rescan_blockchain(&mut self) -> anyhow::Result<()> {
+        tracing::info!(
+            restore_height = self.creation_height(),
+            "Rescanning blockchain"
+        );
+
+        let success = self
+            .inner
+            .pinned()
+            .rescanBlockchain()
+            .context("Failed to rescan blockchain: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to rescan blockchain")?;
+            bail!("Failed to rescan blockchain (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Update the spent status of the wallet's outputs from the daemon.
+    fn rescan_spent(&mut self) -> anyhow::Result<()> {
+        let success = self
+            .inner
+            .pinned()
+            .rescanSpent()
+            .context("Failed to rescan spent outputs: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to rescan spent outputs")?;
+            bail!("Failed to rescan spent outputs (no reason given)");
+        }
+
+        Ok(())
+    }
+
     /// Get the current blockchain height.
-    fn blockchain_height(&self) -> u64 {
+    pub fn blockchain_height(&self) -> u64 {
         self.inner
             .blockChainHeight()
             .context("Failed to get blockchain height: FFI call failed with exception")
@@ -1296,8 +3108,62 @@ impl FfiWallet {
         }
     }
 
+    /// Get the fee per byte for the given priority, from the daemon's dynamic fee.
+    fn estimate_fee_per_byte(&mut self, priority: TxPriority) -> anyhow::Result<monero::Amount> {
+        let fee_per_byte = ffi::estimateFeePerByte(self.inner.pinned(), priority.as_raw())
+            .context("Failed to estimate fee per byte: FFI call failed with exception")?;
+
+        if fee_per_byte == 0 {
+            self.check_error()
+                .context("Failed to estimate fee per byte")?;
+            bail!("Failed to estimate fee per byte (no reason given)");
+        }
+
+        Ok(monero::Amount::from_pico(fee_per_byte))
+    }
+
+    /// Get information about the connected daemon.
+    fn daemon_info(&self) -> anyhow::Result<DaemonInfo> {
+        if !self.connected() {
+            bail!("Failed to get daemon info: not connected to a daemon");
+        }
+
+        let height = self
+            .inner
+            .daemonBlockChainHeight()
+            .context("Failed to get daemon blockchain height: FFI call failed with exception")?;
+        let target_height = self.daemon_blockchain_height().unwrap_or(height);
+        let synced = self
+            .inner
+            .daemonSynced()
+            .context("Failed to check whether daemon is synced: FFI call failed with exception")?;
+
+        let mut hard_fork_version = 0;
+        let mut hard_fork_height = 0;
+        self.inner
+            .hardForkInfo(&mut hard_fork_version, &mut hard_fork_height)
+            .context("Failed to get hard fork info: FFI call failed with exception")?;
+
+        let fee_estimate = ffi::estimateTypicalTransactionFee(&self.inner)
+            .context("Failed to estimate transaction fee: FFI call failed with exception")?;
+
+        // All of the above return 0 if the daemon couldn't be reached
+        if height == 0 || hard_fork_version == 0 {
+            self.check_error().context("Failed to get daemon info")?;
+            bail!("Failed to get daemon info (no reason given)");
+        }
+
+        Ok(DaemonInfo {
+            height,
+            target_height,
+            synced,
+            fee_estimate: monero::Amount::from_pico(fee_estimate),
+            hard_fork_version,
+        })
+    }
+
     /// Get the total balance across all accounts.
-    fn total_balance(&mut self) -> monero::Amount {
+    pub fn total_balance(&mut self) -> monero::Amount {
         let balance = self
             .inner
             .balanceAll()
@@ -1307,7 +3173,7 @@ impl FfiWallet {
     }
 
     /// Get the total unlocked balance across all accounts in atomic units.
-    fn unlocked_balance(&mut self) -> monero::Amount {
+    pub fn unlocked_balance(&mut self) -> monero::Amount {
         let balance = self
             .inner
             .unlockedBalanceAll()
@@ -1317,7 +3183,7 @@ impl FfiWallet {
     }
 
     /// Check if the wallet is synced with the daemon.
-    fn synchronized(&self) -> bool {
+    pub fn synchronized(&self) -> bool {
         self.inner
             .synchronized()
             .context("Failed to check if wallet is synchronized: FFI call failed with exception")
@@ -1368,31 +3234,506 @@ impl FfiWallet {
         .context("Failed to check tx key: FFI call failed with exception")?;
 
         if !success {
-            self.check_error().context("Failed to check tx key")?;
-            anyhow::bail!("Failed to check tx key");
+            self.check_error().context("Failed to check tx key")?;
+            anyhow::bail!("Failed to check tx key");
+        }
+
+        Ok(TxStatus {
+            received: monero::Amount::from_pico(received),
+            in_pool,
+            confirmations,
+        })
+    }
+
+    /// Scan for a specified transaction.
+    /// We use this to import the Monero tx_lock without having to do a
+    /// full sync.
+    /// This is much faster than a full sync.
+    fn scan_transaction(&mut self, tx_id: String) -> anyhow::Result<()> {
+        let_cxx_string!(tx_id = tx_id);
+
+        let raw_wallet = &mut self.inner;
+        let success = ffi::scanTransaction(raw_wallet.pinned(), &tx_id)
+            .context("Failed to scan transaction: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to scan transaction")?;
+            anyhow::bail!("Failed to scan transaction (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Get a proof that the transaction paid the given address.
+    fn get_tx_proof(
+        &self,
+        txid: &str,
+        address: &monero::Address,
+        message: &str,
+    ) -> anyhow::Result<String> {
+        let_cxx_string!(txid = txid);
+        let_cxx_string!(address = address.to_string());
+        let_cxx_string!(message = message);
+
+        let proof = ffi::walletGetTxProof(&self.inner, &txid, &address, &message)
+            .context("Failed to get tx proof: FFI call failed with exception")?
+            .to_string();
+
+        if proof.is_empty() {
+            self.check_error().context("Failed to get tx proof")?;
+            bail!("Failed to get tx proof (no reason given)");
+        }
+
+        Ok(proof)
+    }
+
+    /// Check a transaction proof.
+    /// Returns `None` if the signature is invalid.
+    fn check_tx_proof(
+        &mut self,
+        txid: &str,
+        address: &monero::Address,
+        message: &str,
+        signature: &str,
+    ) -> anyhow::Result<Option<TxStatus>> {
+        let_cxx_string!(txid = txid);
+        let_cxx_string!(address = address.to_string());
+        let_cxx_string!(message = message);
+        let_cxx_string!(signature = signature);
+
+        let mut good = false;
+        let mut received = 0;
+        let mut in_pool = false;
+        let mut confirmations = 0;
+
+        let success = self
+            .inner
+            .pinned()
+            .checkTxProof(
+                &txid,
+                &address,
+                &message,
+                &signature,
+                &mut good,
+                &mut received,
+                &mut in_pool,
+                &mut confirmations,
+            )
+            .context("Failed to check tx proof: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to check tx proof")?;
+            bail!("Failed to check tx proof (no reason given)");
+        }
+
+        if !good {
+            return Ok(None);
+        }
+
+        Ok(Some(TxStatus {
+            received: monero::Amount::from_pico(received),
+            in_pool,
+            confirmations,
+        }))
+    }
+
+    /// Get a proof that we are the sender of the transaction.
+    fn get_spend_proof(&self, txid: &str, message: &str) -> anyhow::Result<String> {
+        let_cxx_string!(txid = txid);
+        let_cxx_string!(message = message);
+
+        let proof = ffi::walletGetSpendProof(&self.inner, &txid, &message)
+            .context("Failed to get spend proof: FFI call failed with exception")?
+            .to_string();
+
+        if proof.is_empty() {
+            self.check_error().context("Failed to get spend proof")?;
+            bail!("Failed to get spend proof (no reason given)");
+        }
+
+        Ok(proof)
+    }
+
+    /// Check a spend proof. Returns whether the signature is valid.
+    fn check_spend_proof(
+        &self,
+        txid: &str,
+        message: &str,
+        signature: &str,
+    ) -> anyhow::Result<bool> {
+        let_cxx_string!(txid = txid);
+        let_cxx_string!(message = message);
+        let_cxx_string!(signature = signature);
+
+        let mut good = false;
+
+        let success = self
+            .inner
+            .checkSpendProof(&txid, &message, &signature, &mut good)
+            .context("Failed to check spend proof: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to check spend proof")?;
+            bail!("Failed to check spend proof (no reason given)");
+        }
+
+        Ok(good)
+    }
+
+    /// Get a proof of the unspent funds in the main account.
+    /// If `amount` is `None`, all funds are included in the proof.
+    fn get_reserve_proof(
+        &self,
+        amount: Option<monero::Amount>,
+        message: &str,
+    ) -> anyhow::Result<String> {
+        let_cxx_string!(message = message);
+
+        let all = amount.is_none();
+        let amount = amount.map(|amount| amount.as_pico()).unwrap_or(0);
+
+        let proof = ffi::walletGetReserveProof(
+            &self.inner,
+            all,
+            Self::MAIN_ACCOUNT_INDEX,
+            amount,
+            &message,
+        )
+        .context("Failed to get reserve proof: FFI call failed with exception")?
+        .to_string();
+
+        if proof.is_empty() {
+            self.check_error().context("Failed to get reserve proof")?;
+            bail!("Failed to get reserve proof (no reason given)");
+        }
+
+        Ok(proof)
+    }
+
+    /// Check a reserve proof.
+    /// Returns `None` if the signature is invalid.
+    fn check_reserve_proof(
+        &self,
+        address: &monero::Address,
+        message: &str,
+        signature: &str,
+    ) -> anyhow::Result<Option<ReserveProofStatus>> {
+        let_cxx_string!(address = address.to_string());
+        let_cxx_string!(message = message);
+        let_cxx_string!(signature = signature);
+
+        let mut good = false;
+        let mut total = 0;
+        let mut spent = 0;
+
+        let success = self
+            .inner
+            .checkReserveProof(
+                &address, &message, &signature, &mut good, &mut total, &mut spent,
+            )
+            .context("Failed to check reserve proof: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to check reserve proof")?;
+            bail!("Failed to check reserve proof (no reason given)");
+        }
+
+        if !good {
+            return Ok(None);
+        }
+
+        Ok(Some(ReserveProofStatus {
+            total: monero::Amount::from_pico(total),
+            spent: monero::Amount::from_pico(spent),
+        }))
+    }
+
+    /// Sign a message with the spend key of the main address.
+    fn sign_message(&mut self, message: &str) -> anyhow::Result<String> {
+        let_cxx_string!(message = message);
+        let_cxx_string!(address = self.main_address().to_string());
+
+        let signature = ffi::walletSignMessage(self.inner.pinned(), &message, &address)
+            .context("Failed to sign message: FFI call failed with exception")?
+            .to_string();
+
+        if signature.is_empty() {
+            self.check_error().context("Failed to sign message")?;
+            bail!("Failed to sign message (no reason given)");
+        }
+
+        Ok(signature)
+    }
+
+    /// Verify a message signature created by the owner of `address`.
+    fn verify_message(
+        &self,
+        message: &str,
+        address: &monero::Address,
+        signature: &str,
+    ) -> anyhow::Result<bool> {
+        let_cxx_string!(message = message);
+        let_cxx_string!(address = address.to_string());
+        let_cxx_string!(signature = signature);
+
+        self.inner
+            .verifySignedMessage(&message, &address, &signature)
+            .context("Failed to verify signed message: FFI call failed with exception")
+    }
+
+    /// Export the outputs of the wallet to a file.
+    fn export_outputs(&mut self, path: &str, all: bool) -> anyhow::Result<()> {
+        let_cxx_string!(path = path);
+
+        let success = self
+            .inner
+            .pinned()
+            .exportOutputs(&path, all)
+            .context("Failed to export outputs: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to export outputs")?;
+            bail!("Failed to export outputs (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Import outputs from a file.
+    fn import_outputs(&mut self, path: &str) -> anyhow::Result<()> {
+        let_cxx_string!(path = path);
+
+        let success = self
+            .inner
+            .pinned()
+            .importOutputs(&path)
+            .context("Failed to import outputs: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to import outputs")?;
+            bail!("Failed to import outputs (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Export signed key images to a file.
+    fn export_key_images(&mut self, path: &str, all: bool) -> anyhow::Result<()> {
+        let_cxx_string!(path = path);
+
+        let success = self
+            .inner
+            .pinned()
+            .exportKeyImages(&path, all)
+            .context("Failed to export key images: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to export key images")?;
+            bail!("Failed to export key images (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Import signed key images from a file.
+    fn import_key_images(&mut self, path: &str) -> anyhow::Result<()> {
+        let_cxx_string!(path = path);
+
+        let success = self
+            .inner
+            .pinned()
+            .importKeyImages(&path)
+            .context("Failed to import key images: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to import key images")?;
+            bail!("Failed to import key images (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Create a transfer and write it to a file as an unsigned transaction.
+    /// Only works for view-only wallets.
+    fn create_unsigned_transfer(
+        &mut self,
+        address: &monero::Address,
+        amount: monero::Amount,
+        path: &str,
+    ) -> anyhow::Result<()> {
+        let_cxx_string!(address = address.to_string());
+
+        let raw_tx = ffi::createTransaction(self.inner.pinned(), &address, amount.as_pico())
+            .context("Failed to create transaction: FFI call failed with exception")?;
+        let mut pending_tx = self.pending_transaction(raw_tx);
+
+        pending_tx
+            .write_to_file(path)
+            .context("Failed to write unsigned transaction")
+    }
+
+    /// Sign the unsigned transaction at `unsigned_path` and write it to `signed_path`.
+    fn sign_unsigned_transaction(
+        &mut self,
+        unsigned_path: &str,
+        signed_path: &str,
+    ) -> anyhow::Result<UnsignedTxSummary> {
+        let_cxx_string!(unsigned_path = unsigned_path);
+        let_cxx_string!(signed_path = signed_path);
+
+        let mut unsigned_tx = ffi::walletLoadUnsignedTx(self.inner.pinned(), &unsigned_path)
+            .context("Failed to load unsigned transaction: FFI call failed with exception")?;
+
+        let status = unsigned_tx
+            .status()
+            .context("Failed to get unsigned transaction status: FFI call failed with exception")?;
+
+        if status != 0 {
+            let error_string = ffi::unsignedTransactionErrorString(&unsigned_tx)
+                .context("Failed to get unsigned transaction error string: FFI call failed with exception")?
+                .to_string();
+            bail!("Failed to load unsigned transaction: {}", error_string);
+        }
+
+        let summary = UnsignedTxSummary {
+            recipients: ffi::unsignedTransactionRecipients(&unsigned_tx)
+                .context("Failed to get recipients of unsigned transaction: FFI call failed with exception")?
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect(),
+            amounts: ffi::unsignedTransactionAmounts(&unsigned_tx)
+                .context("Failed to get amounts of unsigned transaction: FFI call failed with exception")?
+                .iter()
+                .map(|amount| monero::Amount::from_pico(*amount))
+                .collect(),
+            fees: ffi::unsignedTransactionFees(&unsigned_tx)
+                .context("Failed to get fees of unsigned transaction: FFI call failed with exception")?
+                .iter()
+                .map(|fee| monero::Amount::from_pico(*fee))
+                .collect(),
+        };
+
+        let success = unsigned_tx
+            .pin_mut()
+            .sign(&signed_path)
+            .context("Failed to sign transaction: FFI call failed with exception")?;
+
+        if !success {
+            let error_string = ffi::unsignedTransactionErrorString(&unsigned_tx)
+                .context("Failed to get unsigned transaction error string: FFI call failed with exception")?
+                .to_string();
+            bail!("Failed to sign transaction: {}", error_string);
+        }
+
+        Ok(summary)
+    }
+
+    /// Publish a signed transaction from a file.
+    fn submit_signed_transaction(&mut self, path: &str) -> anyhow::Result<()> {
+        let_cxx_string!(path = path);
+
+        let success = self
+            .inner
+            .pinned()
+            .submitTransaction(&path)
+            .context("Failed to submit transaction: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to submit transaction")?;
+            bail!("Failed to submit transaction (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Get all entries of the address book.
+    fn address_book_entries(&mut self) -> anyhow::Result<Vec<AddressBookEntry>> {
+        let entries = ffi::addressBookGetAll(self.inner.pinned())
+            .context("Failed to get address book entries: FFI call failed with exception")?;
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                let address = monero::Address::from_str(&entry.address)
+                    .context("Failed to parse address of address book entry")?;
+
+                Ok(AddressBookEntry {
+                    row_id: entry.row_id,
+                    address,
+                    description: entry.description,
+                    payment_id: Some(entry.payment_id).filter(|id| !id.is_empty()),
+                })
+            })
+            .collect()
+    }
+
+    /// Add an entry to the address book.
+    fn add_address_book_entry(
+        &mut self,
+        address: &monero::Address,
+        description: &str,
+        payment_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let_cxx_string!(address = address.to_string());
+        let_cxx_string!(payment_id = payment_id.unwrap_or_default());
+        let_cxx_string!(description = description);
+
+        let success = ffi::addressBookAdd(self.inner.pinned(), &address, &payment_id, &description)
+            .context("Failed to add address book entry: FFI call failed with exception")?;
+
+        if !success {
+            self.check_address_book_error()
+                .context("Failed to add address book entry")?;
+            bail!("Failed to add address book entry (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Remove an entry from the address book.
+    fn remove_address_book_entry(&mut self, row_id: usize) -> anyhow::Result<()> {
+        let success = ffi::addressBookDelete(self.inner.pinned(), row_id)
+            .context("Failed to remove address book entry: FFI call failed with exception")?;
+
+        if !success {
+            self.check_address_book_error()
+                .context("Failed to remove address book entry")?;
+            bail!("Failed to remove address book entry (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Change the description of an address book entry.
+    fn set_address_book_description(
+        &mut self,
+        row_id: usize,
+        description: &str,
+    ) -> anyhow::Result<()> {
+        let_cxx_string!(description = description);
+
+        let success = ffi::addressBookSetDescription(self.inner.pinned(), row_id, &description)
+            .context("Failed to set address book description: FFI call failed with exception")?;
+
+        if !success {
+            self.check_address_book_error()
+                .context("Failed to set address book description")?;
+            bail!("Failed to set address book description (no reason given)");
         }
 
-        Ok(TxStatus {
-            received: monero::Amount::from_pico(received),
-            in_pool,
-            confirmations,
-        })
+        Ok(())
     }
 
-    /// Scan for a specified transaction.
-    /// We use this to import the Monero tx_lock without having to do a
-    /// full sync.
-    /// This is much faster than a full sync.
-    fn scan_transaction(&mut self, tx_id: String) -> anyhow::Result<()> {
-        let_cxx_string!(tx_id = tx_id);
-
-        let raw_wallet = &mut self.inner;
-        let success = ffi::scanTransaction(raw_wallet.pinned(), &tx_id)
-            .context("Failed to scan transaction: FFI call failed with exception")?;
+    /// Return the error of the last failed address book operation, if any.
+    /// The address book keeps its own error string, separate from the wallet's status.
+    fn check_address_book_error(&mut self) -> anyhow::Result<()> {
+        let error = ffi::addressBookErrorString(self.inner.pinned())
+            .context("Failed to get address book error: FFI call failed with exception")?
+            .to_string();
 
-        if !success {
-            self.check_error().context("Failed to scan transaction")?;
-            anyhow::bail!("Failed to scan transaction (no reason given)");
+        if !error.is_empty() {
+            bail!(error);
         }
 
         Ok(())
@@ -1410,27 +3751,23 @@ impl FfiWallet {
         let amount = amount.as_pico();
 
         // First we need to create a pending transaction.
-        let mut pending_tx = PendingTransaction(
-            ffi::createTransaction(self.inner.pinned(), &address, amount)
-                .context("Failed to create transaction: FFI call failed with exception")?,
-        );
+        let raw_tx = ffi::createTransaction(self.inner.pinned(), &address, amount)
+            .context("Failed to create transaction: FFI call failed with exception")?;
+        let mut pending_tx = self.pending_transaction(raw_tx);
 
-        // Get the txid from the pending transaction before we publish,
-        // otherwise it might be null.
+        // Get the txid and details from the pending transaction before we publish,
+        // otherwise they might be null.
         let txid = ffi::pendingTransactionTxId(&pending_tx)
             .context("Failed to get txid from pending transaction: FFI call failed with exception")?
             .to_string();
+        let details = self.pending_transaction_details(&pending_tx)?;
+
+        tracing::debug!(%txid, fee=%details.fee, weight=details.weight, "Publishing transaction");
 
         // Publish the transaction
-        let result = pending_tx
+        pending_tx
             .publish()
-            .context("Failed to publish transaction");
-
-        // Check for errors (make sure to dispose the transaction)
-        if result.is_err() {
-            self.dispose_transaction(pending_tx);
-            return Err(result.expect_err("result is an error as per the check above"));
-        }
+            .context("Failed to publish transaction")?;
 
         // Fetch the tx key from the wallet.
         let_cxx_string!(txid_cxx = txid.clone());
@@ -1441,13 +3778,11 @@ impl FfiWallet {
         // Get current blockchain height (wallet height).
         let height = self.blockchain_height();
 
-        // Dispose the pending transaction object to avoid memory leak.
-        self.dispose_transaction(pending_tx);
-
         Ok(TxReceipt {
             txid,
             tx_key,
             height,
+            details,
         })
     }
 
@@ -1461,11 +3796,65 @@ impl FfiWallet {
         let_cxx_string!(address = address.to_string());
 
         // Create the sweep transaction
-        let mut pending_tx = PendingTransaction(
-            ffi::createSweepTransaction(self.inner.pinned(), &address)
-                .context("Failed to create sweep transaction: FFI call failed with exception")?,
-        );
+        let raw_tx = ffi::createSweepTransaction(self.inner.pinned(), &address)
+            .context("Failed to create sweep transaction: FFI call failed with exception")?;
+        let pending_tx = self.pending_transaction(raw_tx);
 
+        self.publish_sweep(pending_tx)
+    }
+
+    /// Sweep all unlocked outputs with an amount below `threshold` to a specified address.
+    /// Useful to consolidate dust into a single output.
+    /// Returns a list of transaction ids of the created transactions.
+    fn sweep_below(
+        &mut self,
+        address: &monero::Address,
+        threshold: monero::Amount,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        tracing::info!(%threshold, "Sweeping outputs below threshold to {}, refreshing wallet first", address);
+
+        self.refresh_blocking()?;
+
+        let_cxx_string!(address = address.to_string());
+
+        let raw_tx =
+            ffi::createSweepBelowTransaction(self.inner.pinned(), &address, threshold.as_pico())
+                .context(
+                    "Failed to create sweep below transaction: FFI call failed with exception",
+                )?;
+        let pending_tx = self.pending_transaction(raw_tx);
+
+        self.publish_sweep(pending_tx)
+    }
+
+    /// Sweep the single output identified by `key_image` (hex encoded) to a specified address.
+    /// Returns a list of transaction ids of the created transactions.
+    fn sweep_output(
+        &mut self,
+        key_image: &str,
+        address: &monero::Address,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        tracing::info!(%key_image, "Sweeping single output to {}, refreshing wallet first", address);
+
+        self.refresh_blocking()?;
+
+        let_cxx_string!(key_image = key_image);
+        let_cxx_string!(address = address.to_string());
+
+        let raw_tx =
+            ffi::createSweepSingleTransaction(self.inner.pinned(), &key_image, &address)
+                .context("Failed to create sweep transaction: FFI call failed with exception")?;
+        let pending_tx = self.pending_transaction(raw_tx);
+
+        self.publish_sweep(pending_tx)
+    }
+
+    /// Publish a (possibly split) sweep transaction and
+    /// return a receipt for every transaction it contained.
+    fn publish_sweep(
+        &mut self,
+        mut pending_tx: PendingTransaction,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
         // Get the txids from the pending transaction before we publish,
         // otherwise it might be null.
         let txids: Vec<String> = ffi::pendingTransactionTxIds(&pending_tx)
@@ -1473,17 +3862,14 @@ impl FfiWallet {
             .into_iter()
             .map(|s| s.to_string())
             .collect();
+        let details = self.pending_transaction_details(&pending_tx)?;
+
+        tracing::debug!(?txids, fee=%details.fee, weight=details.weight, "Publishing sweep transaction");
 
         // Publish the transaction
-        let result = pending_tx
+        pending_tx
             .publish()
-            .context("Failed to publish transaction");
-
-        // Dispose of the transaction to avoid leaking memory.
-        self.dispose_transaction(pending_tx);
-
-        // Check for errors only after cleaning up the memory.
-        result.context("Failed to publish transaction")?;
+            .context("Failed to publish transaction")?;
 
         // Get the receipts for the transactions.
         let mut receipts = Vec::new();
@@ -1501,6 +3887,7 @@ impl FfiWallet {
                 txid: txid.clone(),
                 tx_key,
                 height,
+                details: details.clone(),
             });
         }
 
@@ -1564,47 +3951,9 @@ impl FfiWallet {
             anyhow::bail!("Failed to create multi-sweep transaction");
         }
 
-        let mut pending_tx = PendingTransaction(raw_tx);
-
-        // Get the txids from the pending transaction before we publish,
-        // otherwise it might be null.
-        let txids: Vec<String> = ffi::pendingTransactionTxIds(&pending_tx)
-            .context("Failed to get txids of pending transaction: FFI call failed with exception")?
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect();
-
-        // Publish the transaction
-        let result = pending_tx
-            .publish()
-            .context("Failed to publish transaction");
-
-        // Dispose of the transaction to avoid leaking memory.
-        self.dispose_transaction(pending_tx);
-
-        // Check for errors only after cleaning up the memory.
-        result.context("Failed to publish transaction")?;
-
-        // Get the receipts for the transactions.
-        let mut receipts = Vec::new();
-
-        for txid in txids {
-            let_cxx_string!(txid_cxx = &txid);
-
-            let tx_key = ffi::walletGetTxKey(&self.inner, &txid_cxx)
-                .context("Failed to get tx key from wallet: FFI call failed with exception")?
-                .to_string();
-
-            let height = self.blockchain_height();
+        let pending_tx = self.pending_transaction(raw_tx);
 
-            receipts.push(TxReceipt {
-                txid: txid.clone(),
-                tx_key,
-                height,
-            });
-        }
-
-        Ok(receipts)
+        self.publish_sweep(pending_tx)
     }
 
     /// Distribute the funds in the wallet to a set of addresses with a set of percentages,
@@ -1668,16 +4017,56 @@ impl FfiWallet {
         Ok(amounts)
     }
 
-    /// Dispose (deallocate) a pending transaction object.
-    /// Always call this before dropping a pending transaction object,
-    /// otherwise we leak memory.
-    fn dispose_transaction(&mut self, tx: PendingTransaction) {
-        unsafe {
-            self.inner
-                .pinned()
-                .disposeTransaction(tx.0)
-                .context("Failed to dispose transaction: FFI call failed with exception")
-                .expect("Shouldn't panic");
+    /// Get the fee, amount, destinations etc. of a pending transaction.
+    fn pending_transaction_details(
+        &self,
+        pending_tx: &PendingTransaction,
+    ) -> anyhow::Result<TxDetails> {
+        pending_tx
+            .check_error()
+            .context("Failed to create transaction")?;
+
+        let fee = pending_tx
+            .fee()
+            .context("Failed to get fee of pending transaction: FFI call failed with exception")?;
+        let amount = pending_tx.amount().context(
+            "Failed to get amount of pending transaction: FFI call failed with exception",
+        )?;
+        let tx_count = pending_tx.txCount().context(
+            "Failed to get transaction count of pending transaction: FFI call failed with exception",
+        )?;
+        let weight = self.inner.pendingTransactionWeight(pending_tx).context(
+            "Failed to get weight of pending transaction: FFI call failed with exception",
+        )?;
+
+        let destinations = ffi::pendingTransactionDestinations(&self.inner, pending_tx)
+            .context(
+                "Failed to get destinations of pending transaction: FFI call failed with exception",
+            )?
+            .into_iter()
+            .map(|destination| {
+                let address = monero::Address::from_str(&destination.address)
+                    .context("Failed to parse destination address of pending transaction")?;
+
+                Ok((address, monero::Amount::from_pico(destination.amount)))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(TxDetails {
+            fee: monero::Amount::from_pico(fee),
+            amount: monero::Amount::from_pico(amount),
+            destinations,
+            weight,
+            tx_count,
+        })
+    }
+
+    /// Take ownership of a pending transaction created by this wallet.
+    /// It's disposed of when dropped.
+    fn pending_transaction(&self, inner: *mut ffi::PendingTransaction) -> PendingTransaction {
+        PendingTransaction {
+            inner,
+            wallet: self.inner.inner,
         }
     }
 
@@ -1689,32 +4078,93 @@ impl FfiWallet {
     /// release the mutex in between an operation and the check.
     fn check_error(&self) -> anyhow::Result<()> {
         let mut status = 0;
-        let mut error_string = String::new();
-        let_cxx_string!(error_string_ref = &mut error_string);
+        let_cxx_string!(error_string = "");
 
         self.inner
-            .statusWithErrorString(&mut status, error_string_ref)
+            .statusWithErrorString(&mut status, error_string.as_mut())
             .context("Failed to get wallet status: FFI call failed with exception")?;
 
-        // If the status is ok, we return None
-        if status == 0 {
-            return Ok(());
+        match WalletError::from_status(status, error_string.to_string()) {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
         }
+    }
 
-        let error_string = if error_string.is_empty() {
-            "unknown error, error not set".to_string()
-        } else {
-            error_string
-        };
+    /// Attach a note to a transaction.
+    fn set_tx_note(&mut self, txid: &str, note: &str) -> anyhow::Result<()> {
+        let_cxx_string!(txid_ref = txid);
+        let_cxx_string!(note = note);
+
+        let success = self
+            .inner
+            .pinned()
+            .setUserNote(&txid_ref, &note)
+            .context("Failed to set transaction note: FFI call failed with exception")?;
 
-        let error_type = if status == 2 { "critical" } else { "error" };
+        if !success {
+            self.check_error()
+                .context("Failed to set transaction note")?;
+            bail!(
+                "Failed to set transaction note, `{}` is not a valid txid",
+                txid
+            );
+        }
 
-        // Otherwise we return the error
-        bail!(format!(
-            "Experienced wallet error ({}): `{}`",
-            error_type,
-            error_string.to_string()
-        ))
+        Ok(())
+    }
+
+    /// Get the note attached to a transaction, `None` if there is none.
+    fn tx_note(&self, txid: &str) -> Option<String> {
+        let_cxx_string!(txid = txid);
+
+        let note = ffi::walletUserNote(&self.inner, &txid)
+            .context("Failed to get transaction note: FFI call failed with exception")
+            .expect("Shouldn't panic")
+            .to_string();
+
+        (!note.is_empty()).then_some(note)
+    }
+
+    /// Store an attribute in the wallet cache.
+    fn set_attribute(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let_cxx_string!(key = key);
+        let_cxx_string!(value = value);
+
+        let success = self
+            .inner
+            .pinned()
+            .setCacheAttribute(&key, &value)
+            .context("Failed to set wallet attribute: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error()
+                .context("Failed to set wallet attribute")?;
+            bail!("Failed to set wallet attribute (no reason given)");
+        }
+
+        Ok(())
+    }
+
+    /// Get an attribute from the wallet cache, `None` if it isn't set.
+    fn attribute(&self, key: &str) -> Option<String> {
+        let_cxx_string!(key = key);
+
+        let value = ffi::walletCacheAttribute(&self.inner, &key)
+            .context("Failed to get wallet attribute: FFI call failed with exception")
+            .expect("Shouldn't panic")
+            .to_string();
+
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// Get the language of the seed of the wallet.
+    fn seed_language(&self) -> Option<SeedLanguage> {
+        let name = ffi::walletSeedLanguage(&self.inner)
+            .context("Failed to get seed language: FFI call failed with exception")
+            .expect("Shouldn't panic")
+            .to_string();
+
+        SeedLanguage::from_name(&name)
     }
 
     /// Get the seed of the wallet.
@@ -1744,21 +4194,14 @@ impl PendingTransaction {
             )?
             .to_string();
 
-        if status == 0 {
-            return Ok(());
+        match WalletError::from_status(status, error_string) {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
         }
-
-        let error_type = if status == 2 { "critical" } else { "error" };
-
-        bail!(format!(
-            "Experienced pending transaction error ({}): {}",
-            error_type, error_string
-        ))
     }
 
     /// Publish this transaction to the blockchain or return an error.
     ///
-    /// **Important**: you still have to dispose the transaction.
     fn publish(&mut self) -> anyhow::Result<()> {
         self.check_error().context("Failed to create transaction")?;
 
@@ -1781,6 +4224,113 @@ impl PendingTransaction {
                 )))
         }
     }
+
+    /// Write this transaction to a file instead of publishing it.
+    /// For view-only wallets this creates an unsigned transaction file.
+    fn write_to_file(&mut self, path: &str) -> anyhow::Result<()> {
+        self.check_error().context("Failed to create transaction")?;
+
+        let_cxx_string!(filename = path);
+        let success = self
+            .pinned()
+            .commit(&filename, true)
+            .context("Failed to write transaction to file: FFI call failed with exception")?;
+
+        if success {
+            Ok(())
+        } else {
+            Err(self
+                .check_error()
+                .context("Failed to write transaction to file")
+                .err()
+                .unwrap_or(anyhow::anyhow!("Failed to write transaction to file")))
+        }
+    }
+}
+
+impl SeedLanguage {
+    /// All languages supported by Monero.
+    pub const ALL: [SeedLanguage; 12] = [
+        SeedLanguage::English,
+        SeedLanguage::German,
+        SeedLanguage::Spanish,
+        SeedLanguage::French,
+        SeedLanguage::Italian,
+        SeedLanguage::Dutch,
+        SeedLanguage::Portuguese,
+        SeedLanguage::Russian,
+        SeedLanguage::Japanese,
+        SeedLanguage::ChineseSimplified,
+        SeedLanguage::Esperanto,
+        SeedLanguage::Lojban,
+    ];
+
+    /// The (native) name wallet2 uses for the language.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SeedLanguage::English => "English",
+            SeedLanguage::German => "Deutsch",
+            SeedLanguage::Spanish => "Español",
+            SeedLanguage::French => "Français",
+            SeedLanguage::Italian => "Italiano",
+            SeedLanguage::Dutch => "Nederlands",
+            SeedLanguage::Portuguese => "Português",
+            SeedLanguage::Russian => "русский язык",
+            SeedLanguage::Japanese => "日本語",
+            SeedLanguage::ChineseSimplified => "简体中文 (中国)",
+            SeedLanguage::Esperanto => "Esperanto",
+            SeedLanguage::Lojban => "Lojban",
+        }
+    }
+
+    /// Look up a language by the name wallet2 uses for it.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.name() == name)
+    }
+}
+
+impl TxPriority {
+    /// The value of the matching `Monero::PendingTransaction::Priority`.
+    fn as_raw(self) -> u32 {
+        match self {
+            TxPriority::Default => 0,
+            TxPriority::Low => 1,
+            TxPriority::Medium => 2,
+            TxPriority::High => 3,
+        }
+    }
+}
+
+impl Daemon {
+    /// Whether the TLS certificate of the daemon is pinned to a CA or to fingerprints.
+    fn is_pinned(&self) -> bool {
+        self.ssl_ca_file.is_some() || !self.ssl_allowed_fingerprints.is_empty()
+    }
+}
+
+/// Bring a SHA-256 certificate fingerprint into the format wallet2 expects:
+/// lowercase hex without separators (e.g. as printed by `openssl x509 -fingerprint -sha256`).
+fn normalize_fingerprint(fingerprint: &str) -> anyhow::Result<String> {
+    let hex = fingerprint.replace(':', "").trim().to_lowercase();
+
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid SHA-256 certificate fingerprint `{}`", fingerprint);
+    }
+
+    Ok(hex)
+}
+
+#[cfg(feature = "hardware-wallets")]
+impl HardwareDevice {
+    /// The name wallet2 uses to look up the device.
+    fn device_name(&self) -> &'static str {
+        match self {
+            HardwareDevice::Ledger => "Ledger",
+            HardwareDevice::Trezor => "Trezor",
+        }
+    }
 }
 
 impl SyncProgress {
@@ -1789,6 +4339,7 @@ impl SyncProgress {
         Self {
             current_block,
             target_block,
+            blocks_per_second: None,
         }
     }
 
@@ -1797,9 +4348,25 @@ impl SyncProgress {
         Self {
             current_block: 0,
             target_block: 1,
+            blocks_per_second: None,
         }
     }
 
+    /// The number of blocks the wallet still has to sync.
+    pub fn blocks_remaining(&self) -> u64 {
+        self.target_block.saturating_sub(self.current_block)
+    }
+
+    /// Estimate how long the rest of the sync takes at the recent speed.
+    /// `None` if the speed isn't known (yet) or the wallet isn't making progress.
+    pub fn eta(&self) -> Option<Duration> {
+        let blocks_per_second = self.blocks_per_second.filter(|rate| *rate > 0.0)?;
+
+        Some(Duration::from_secs_f64(
+            self.blocks_remaining() as f64 / blocks_per_second,
+        ))
+    }
+
     /// Get the sync progress as a fraction.
     pub fn fraction(&self) -> f32 {
         if self.target_block == 0 {
@@ -1820,6 +4387,35 @@ impl SyncProgress {
     }
 }
 
+impl SyncRate {
+    const WINDOW: Duration = Duration::from_secs(30);
+
+    fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record the height of the wallet and return the blocks per second over the window.
+    /// `None` until there are two samples to compare.
+    fn update(&mut self, now: Instant, height: u64) -> Option<f64> {
+        self.samples.push_back((now, height));
+
+        while self.samples.len() > 2 && now.duration_since(self.samples[0].0) > Self::WINDOW {
+            self.samples.pop_front();
+        }
+
+        let (oldest_time, oldest_height) = *self.samples.front()?;
+        let elapsed = now.duration_since(oldest_time).as_secs_f64();
+
+        if elapsed == 0.0 {
+            return None;
+        }
+
+        Some(height.saturating_sub(oldest_height) as f64 / elapsed)
+    }
+}
+
 impl Display for SyncProgress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}%", self.percentage())
@@ -1852,6 +4448,20 @@ impl RawWallet {
     }
 }
 
+// We implement Deref for RawWalletManager such that we can use the
+// const c++ methods directly on the RawWalletManager struct.
+impl Deref for RawWalletManager {
+    type Target = ffi::WalletManager;
+
+    fn deref(&self) -> &ffi::WalletManager {
+        unsafe {
+            self.inner
+                .as_ref()
+                .expect("wallet manager pointer not to be null")
+        }
+    }
+}
+
 // We implement Deref for RawWallet such that we can use the
 // const c++ methods directly on the RawWallet struct.
 impl Deref for RawWallet {
@@ -1866,7 +4476,7 @@ impl PendingTransaction {
     fn pinned(&mut self) -> Pin<&mut ffi::PendingTransaction> {
         unsafe {
             Pin::new_unchecked(
-                self.0
+                self.inner
                     .as_mut()
                     .expect("pending transaction pointer not to be null"),
             )
@@ -1879,13 +4489,32 @@ impl Deref for PendingTransaction {
 
     fn deref(&self) -> &ffi::PendingTransaction {
         unsafe {
-            self.0
+            self.inner
                 .as_ref()
                 .expect("pending transaction pointer not to be null")
         }
     }
 }
 
+impl Drop for PendingTransaction {
+    fn drop(&mut self) {
+        if self.inner.is_null() {
+            return;
+        }
+
+        // Safety: the wallet outlives its pending transactions, since both only
+        // ever exist on the wallet thread and the wallet is only closed after
+        // the last call returned.
+        let wallet = unsafe {
+            Pin::new_unchecked(self.wallet.as_mut().expect("wallet pointer not to be null"))
+        };
+
+        if let Err(e) = unsafe { wallet.disposeTransaction(self.inner) } {
+            tracing::error!(error=%e, "Failed to dispose pending transaction, leaking it");
+        }
+    }
+}
+
 /// Create a backoff strategy for retrying a function.
 /// Default max elapsed time is 5 minutes, default max interval is 30 seconds.
 fn backoff(
@@ -2066,6 +4695,49 @@ mod tests {
         assert_eq!(amounts[1].as_pico(), 1);
     }
 
+    #[test]
+    fn test_sync_rate() {
+        let start = Instant::now();
+        let mut rate = SyncRate::new();
+
+        assert_eq!(rate.update(start, 100), None);
+        assert_eq!(
+            rate.update(start + Duration::from_secs(10), 200),
+            Some(10.0)
+        );
+
+        // Samples older than the window no longer count
+        rate.update(start + Duration::from_secs(35), 300);
+        assert_eq!(
+            rate.update(start + Duration::from_secs(40), 500),
+            Some(300.0 / 30.0)
+        );
+    }
+
+    #[test]
+    fn test_sync_eta() {
+        let progress = SyncProgress {
+            blocks_per_second: Some(50.0),
+            ..SyncProgress::new(1_000, 2_000)
+        };
+
+        assert_eq!(progress.blocks_remaining(), 1_000);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(20)));
+        assert_eq!(SyncProgress::new(1_000, 2_000).eta(), None);
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        let fingerprint = "AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89";
+
+        assert_eq!(
+            normalize_fingerprint(fingerprint).unwrap(),
+            "abcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456789"
+        );
+        assert!(normalize_fingerprint("abcdef").is_err());
+        assert!(normalize_fingerprint(&"z".repeat(64)).is_err());
+    }
+
     #[test]
     fn test_distribute_percentages_not_sum_to_1() {
         let balance = monero::Amount::from_pico(1000);
@@ -2074,4 +4746,14 @@ mod tests {
         let amounts = FfiWallet::distribute(balance, &percentages);
         assert!(amounts.is_err());
     }
+
+    #[test]
+    fn handles_use_default_kdf_rounds_unless_given() {
+        assert_eq!(
+            WalletHandle::kdf_rounds(None).unwrap(),
+            WalletManager::DEFAULT_KDF_ROUNDS
+        );
+        assert_eq!(WalletHandle::kdf_rounds(Some(64)).unwrap(), 64);
+        assert!(WalletHandle::kdf_rounds(Some(0)).is_err());
+    }
 }
@@ -0,0 +1,280 @@
+//! Parsing and building of Monero payment URIs (`monero:<address>?tx_amount=…`).
+//!
+//! Follows the format understood by wallet2's `parse_uri`/`make_uri`:
+//! parameter values are percent encoded and amounts are given in XMR.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+
+const SCHEME: &str = "monero:";
+const PICONERO_PER_XMR: u64 = 1_000_000_000_000;
+const DECIMALS: usize = 12;
+
+/// The contents of a Monero payment URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentUri {
+    pub address: monero::Address,
+    pub amount: Option<monero::Amount>,
+    pub description: Option<String>,
+    pub recipient_name: Option<String>,
+    /// Legacy 16 or 64 character hex payment id.
+    pub payment_id: Option<String>,
+}
+
+impl PaymentUri {
+    /// A URI for the given address without any parameters.
+    pub fn new(address: monero::Address) -> Self {
+        Self {
+            address,
+            amount: None,
+            description: None,
+            recipient_name: None,
+            payment_id: None,
+        }
+    }
+}
+
+/// Parse a `monero:` URI.
+///
+/// Unknown parameters are ignored, as wallet2 does.
+pub fn parse(uri: &str) -> Result<PaymentUri> {
+    let uri = uri.trim();
+
+    let rest = match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => bail!("URI `{}` doesn't start with `{}`", uri, SCHEME),
+    };
+    let (address, query) = match rest.split_once('?') {
+        Some((address, query)) => (address, Some(query)),
+        None => (rest, None),
+    };
+
+    let address = monero::Address::from_str(address)
+        .with_context(|| format!("Invalid address `{}` in Monero URI", address))?;
+    let mut payment_uri = PaymentUri::new(address);
+
+    for parameter in query.into_iter().flat_map(|query| query.split('&')) {
+        if parameter.is_empty() {
+            continue;
+        }
+
+        let (key, value) = parameter
+            .split_once('=')
+            .with_context(|| format!("Parameter `{}` of Monero URI has no value", parameter))?;
+        let value = percent_decode(value)
+            .with_context(|| format!("Failed to decode parameter `{}` of Monero URI", key))?;
+
+        let field = match key {
+            "tx_amount" => {
+                let amount = parse_amount(&value)?;
+                set_once(&mut payment_uri.amount, amount, key)?;
+                continue;
+            }
+            "tx_description" => &mut payment_uri.description,
+            "tx_recipient_name" => &mut payment_uri.recipient_name,
+            "tx_payment_id" => {
+                if !matches!(value.len(), 16 | 64) || !value.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    bail!("Invalid payment id `{}` in Monero URI", value);
+                }
+                &mut payment_uri.payment_id
+            }
+            _ => {
+                tracing::debug!(%key, "Ignoring unknown Monero URI parameter");
+                continue;
+            }
+        };
+
+        set_once(field, value, key)?;
+    }
+
+    Ok(payment_uri)
+}
+
+/// Build a `monero:` URI, e.g. to show it as a QR code.
+pub fn build(payment_uri: &PaymentUri) -> String {
+    let mut parameters = Vec::new();
+
+    if let Some(payment_id) = &payment_uri.payment_id {
+        parameters.push(format!("tx_payment_id={}", percent_encode(payment_id)));
+    }
+    if let Some(amount) = payment_uri.amount {
+        parameters.push(format!("tx_amount={}", format_amount(amount)));
+    }
+    if let Some(recipient_name) = &payment_uri.recipient_name {
+        parameters.push(format!(
+            "tx_recipient_name={}",
+            percent_encode(recipient_name)
+        ));
+    }
+    if let Some(description) = &payment_uri.description {
+        parameters.push(format!("tx_description={}", percent_encode(description)));
+    }
+
+    let mut uri = format!("{}{}", SCHEME, payment_uri.address);
+    if !parameters.is_empty() {
+        uri.push('?');
+        uri.push_str(&parameters.join("&"));
+    }
+
+    uri
+}
+
+fn set_once<T>(field: &mut Option<T>, value: T, key: &str) -> Result<()> {
+    if field.is_some() {
+        bail!("Parameter `{}` appears more than once in Monero URI", key);
+    }
+
+    *field = Some(value);
+    Ok(())
+}
+
+/// Parse a decimal XMR amount without going through floating point.
+fn parse_amount(amount: &str) -> Result<monero::Amount> {
+    let invalid = || format!("Invalid amount `{}` in Monero URI", amount);
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > DECIMALS
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        bail!(invalid());
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().with_context(invalid)?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = DECIMALS)
+            .parse()
+            .with_context(invalid)?
+    };
+
+    let piconero = whole
+        .checked_mul(PICONERO_PER_XMR)
+        .and_then(|whole| whole.checked_add(fraction))
+        .with_context(invalid)?;
+
+    Ok(monero::Amount::from_pico(piconero))
+}
+
+/// Format an amount in XMR with as few decimals as needed.
+fn format_amount(amount: monero::Amount) -> String {
+    let piconero = amount.as_pico();
+    let whole = piconero / PICONERO_PER_XMR;
+    let fraction = piconero % PICONERO_PER_XMR;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0>width$}", fraction, width = DECIMALS);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+                .context("Invalid percent encoding")?;
+            decoded.push(u8::from_str_radix(hex, 16).expect("two hex digits to fit into a byte"));
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).context("Percent encoded value is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "44Ato7HveWidJYUAVw5QffEcEtSH1DwzSP3FPPkHxNAS4LX9CqgucphTisH978FLHE34YNEx7FcbBfQLQUU8m3NUC4VqsRa";
+
+    #[test]
+    fn parses_all_parameters() {
+        let uri = format!(
+            "monero:{}?tx_amount=1.5&tx_description=Swap%20refund&tx_recipient_name=eigenwallet&tx_payment_id=0123456789abcdef",
+            ADDRESS
+        );
+
+        let payment_uri = parse(&uri).unwrap();
+
+        assert_eq!(payment_uri.address.to_string(), ADDRESS);
+        assert_eq!(
+            payment_uri.amount,
+            Some(monero::Amount::from_pico(1_500_000_000_000))
+        );
+        assert_eq!(payment_uri.description.as_deref(), Some("Swap refund"));
+        assert_eq!(payment_uri.recipient_name.as_deref(), Some("eigenwallet"));
+        assert_eq!(payment_uri.payment_id.as_deref(), Some("0123456789abcdef"));
+    }
+
+    #[test]
+    fn build_roundtrips() {
+        let mut payment_uri = PaymentUri::new(monero::Address::from_str(ADDRESS).unwrap());
+        payment_uri.amount = Some(monero::Amount::from_pico(1));
+        payment_uri.description = Some("Über & more".to_string());
+
+        let uri = build(&payment_uri);
+
+        assert_eq!(
+            uri,
+            format!(
+                "monero:{}?tx_amount=0.000000000001&tx_description=%C3%9Cber%20%26%20more",
+                ADDRESS
+            )
+        );
+        assert_eq!(parse(&uri).unwrap(), payment_uri);
+    }
+
+    #[test]
+    fn address_only() {
+        let payment_uri = parse(&format!("monero:{}", ADDRESS)).unwrap();
+
+        assert_eq!(build(&payment_uri), format!("monero:{}", ADDRESS));
+        assert_eq!(payment_uri.amount, None);
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        assert!(parse(ADDRESS).is_err());
+        assert!(parse("monero:not-an-address").is_err());
+        assert!(parse(&format!("monero:{}?tx_amount=1.0000000000001", ADDRESS)).is_err());
+        assert!(parse(&format!("monero:{}?tx_amount=-1", ADDRESS)).is_err());
+        assert!(parse(&format!("monero:{}?tx_amount=1&tx_amount=2", ADDRESS)).is_err());
+        assert!(parse(&format!("monero:{}?tx_payment_id=xyz", ADDRESS)).is_err());
+    }
+}
@@ -18,6 +18,7 @@ async fn main() {
     let daemon = Daemon {
         address: STAGENET_REMOTE_NODE.into(),
         ssl: true,
+        ..Default::default()
     };
 
     let wallet_name = "recovered_wallet";
@@ -30,7 +31,8 @@ async fn main() {
         monero::Network::Stagenet,
         STAGENET_WALLET_RESTORE_HEIGHT,
         true,
-        daemon,
+        vec![daemon],
+        None,
     )
     .await
     .expect("Failed to recover wallet");
@@ -1,5 +1,5 @@
 use monero::Network;
-use monero_sys::{Daemon, WalletHandle};
+use monero_sys::{Daemon, SeedLanguage, WalletHandle};
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -16,6 +16,7 @@ async fn test_wallet_with_special_paths() {
     let daemon = Daemon {
         address: "https://moneronode.org:18081".into(),
         ssl: true,
+        ..Default::default()
     };
 
     let futures = special_paths
@@ -27,9 +28,11 @@ async fn test_wallet_with_special_paths() {
             tokio::spawn(async move {
                 let result = WalletHandle::open_or_create(
                     path.display().to_string(),
-                    daemon,
+                    vec![daemon],
                     Network::Mainnet,
+                    SeedLanguage::English,
                     true,
+                    None,
                 )
                 .await;
 
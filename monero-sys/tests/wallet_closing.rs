@@ -1,4 +1,4 @@
-use monero_sys::{Daemon, WalletHandle};
+use monero_sys::{CallError, Daemon, SeedLanguage, WalletHandle};
 
 const STAGENET_REMOTE_NODE: &str = "node.sethforprivacy.com:38089";
 
@@ -13,14 +13,17 @@ async fn main() {
     let daemon = Daemon {
         address: STAGENET_REMOTE_NODE.into(),
         ssl: true,
+        ..Default::default()
     };
 
     {
         let wallet = WalletHandle::open_or_create(
             temp_dir.path().join("test_wallet").display().to_string(),
-            daemon.clone(),
+            vec![daemon.clone()],
             monero::Network::Stagenet,
+            SeedLanguage::English,
             true,
+            None,
         )
         .await
         .expect("Failed to create wallet");
@@ -38,9 +41,11 @@ async fn main() {
     {
         let wallet = WalletHandle::open_or_create(
             temp_dir.path().join("test_wallet").display().to_string(),
-            daemon.clone(),
+            vec![daemon.clone()],
             monero::Network::Stagenet,
+            SeedLanguage::English,
             true,
+            None,
         )
         .await
         .expect("Failed to create wallet");
@@ -51,4 +56,28 @@ async fn main() {
 
     tracing::info!("Sleeping for 2 seconds");
     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    {
+        let wallet = WalletHandle::open_or_create(
+            temp_dir.path().join("test_wallet").display().to_string(),
+            vec![daemon.clone()],
+            monero::Network::Stagenet,
+            SeedLanguage::English,
+            true,
+            None,
+        )
+        .await
+        .expect("Failed to create wallet");
+
+        let result = wallet
+            .try_call::<_, ()>(|_| panic!("call panicked on purpose"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(CallError::WalletThreadPanicked { .. })
+        ));
+
+        tracing::info!("Closing wallet after its thread panicked");
+        assert!(wallet.close().await.is_err());
+    }
 }
@@ -20,7 +20,7 @@ import {
 import {
   checkContextAvailability,
   getSwapInfo,
-  initializeContext,
+  startContext,
   listSellersAtRendezvousPoint,
   updateAllNodeStatuses,
 } from "./rpc";
@@ -72,14 +72,14 @@ export async function setupBackgroundTasks(): Promise<void> {
     );
   } else {
     // Warning: If we reload the page while the Context is being initialized, this function will throw an error
-    initializeContext().catch((e) => {
+    startContext().catch((e) => {
       logger.error(
         e,
         "Failed to initialize context on page load. This might be because we reloaded the page while the context was being initialized",
       );
       // Wait a short time before retrying
       setTimeout(() => {
-        initializeContext().catch((e) => {
+        startContext().catch((e) => {
           logger.error(e, "Failed to initialize context even after retry");
         });
       }, 2000);
@@ -20,6 +20,7 @@ import { setupBackgroundTasks } from "renderer/background";
 import "@fontsource/roboto";
 import FeedbackPage from "./pages/feedback/FeedbackPage";
 import IntroductionModal from "./modal/introduction/IntroductionModal";
+import SeedPasswordDialog from "./modal/SeedPasswordDialog";
 
 declare module "@mui/material/styles" {
   interface Theme {
@@ -46,6 +47,7 @@ export default function App() {
         <CssBaseline />
         <GlobalSnackbarProvider>
           <IntroductionModal />
+          <SeedPasswordDialog />
           <Router>
             <Navigation />
             <InnerContent />
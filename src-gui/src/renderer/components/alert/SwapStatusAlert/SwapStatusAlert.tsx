@@ -12,6 +12,7 @@ import { ReactNode } from "react";
 import { exhaustiveGuard } from "utils/typescriptUtils";
 import HumanizedBitcoinBlockDuration from "../../other/HumanizedBitcoinBlockDuration";
 import TruncatedText from "../../other/TruncatedText";
+import { SwapCancelRefundButton } from "../../pages/history/table/HistoryRowActions";
 import { SwapMoneroRecoveryButton } from "../../pages/history/table/SwapMoneroRecoveryButton";
 import { TimelockTimeline } from "./TimelockTimeline";
 
@@ -125,20 +126,30 @@ function BitcoinPossiblyCancelledAlert({
   timelock: TimelockCancel;
 }) {
   return (
-    <MessageList
-      messages={[
-        "The swap is being cancelled because it was not completed in time",
-        "To refund your Bitcoin, resume the swap",
-        <>
-          If we haven't refunded in{" "}
-          <HumanizedBitcoinBlockDuration
-            blocks={timelock.content.blocks_left}
-          />
-          , cooperation from the other party will be required to recover the
-          funds
-        </>,
-      ]}
-    />
+    <Box
+      sx={{
+        display: "flex",
+        flexDirection: "column",
+        gap: 1,
+      }}
+    >
+      <MessageList
+        messages={[
+          "The swap is being cancelled because it was not completed in time",
+          "To refund your Bitcoin, resume the swap",
+          <>
+            If we haven't refunded in{" "}
+            <HumanizedBitcoinBlockDuration
+              blocks={timelock.content.blocks_left}
+            />
+            , cooperation from the other party will be required to recover the
+            funds
+          </>,
+          "If resuming fails, you can also try to cancel and refund manually",
+        ]}
+      />
+      <SwapCancelRefundButton swap={swap} size="small" variant="contained" />
+    </Box>
   );
 }
 
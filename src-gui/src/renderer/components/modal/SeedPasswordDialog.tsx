@@ -0,0 +1,64 @@
+import {
+  Button,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogContentText,
+  DialogTitle,
+  TextField,
+} from "@mui/material";
+import { ChangeEvent, useState } from "react";
+import { initializeContext } from "renderer/rpc";
+import { seedPasswordRequiredChanged } from "store/features/rpcSlice";
+import { useAppDispatch, useAppSelector } from "store/hooks";
+import PromiseInvokeButton from "../PromiseInvokeButton";
+
+export default function SeedPasswordDialog() {
+  const open = useAppSelector((s) => s.rpc.seedPasswordRequired);
+  const dispatch = useAppDispatch();
+  const [password, setPassword] = useState("");
+
+  function handleClose() {
+    setPassword("");
+    dispatch(seedPasswordRequiredChanged(false));
+  }
+
+  function handlePasswordChange(event: ChangeEvent<HTMLInputElement>) {
+    setPassword(event.target.value);
+  }
+
+  return (
+    <Dialog open={open} onClose={handleClose}>
+      <DialogTitle>Unlock wallet</DialogTitle>
+      <DialogContent>
+        <DialogContentText>
+          The seed of your wallet is protected by a password. Enter it to start
+          the daemon.
+        </DialogContentText>
+        <TextField
+          autoFocus
+          fullWidth
+          margin="dense"
+          type="password"
+          label="Password"
+          value={password}
+          onChange={handlePasswordChange}
+        />
+      </DialogContent>
+      <DialogActions>
+        <Button onClick={handleClose}>Cancel</Button>
+        <PromiseInvokeButton
+          color="primary"
+          variant="contained"
+          disabled={password.length === 0}
+          onInvoke={() => initializeContext(password)}
+          onSuccess={handleClose}
+          requiresContext={false}
+          displayErrorSnackbar
+        >
+          Unlock
+        </PromiseInvokeButton>
+      </DialogActions>
+    </Dialog>
+  );
+}
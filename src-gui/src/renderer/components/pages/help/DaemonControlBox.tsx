@@ -5,7 +5,7 @@ import PromiseInvokeButton from "renderer/components/PromiseInvokeButton";
 import { useAppSelector } from "store/hooks";
 import InfoBox from "../../modal/swap/InfoBox";
 import CliLogsBox from "../../other/RenderedCliLog";
-import { getDataDir, initializeContext } from "renderer/rpc";
+import { getDataDir, startContext } from "renderer/rpc";
 import { relaunch } from "@tauri-apps/plugin-process";
 import RotateLeftIcon from "@mui/icons-material/RotateLeft";
 import { revealItemInDir } from "@tauri-apps/plugin-opener";
@@ -39,7 +39,7 @@ export default function DaemonControlBox() {
           <PromiseInvokeButton
             variant="contained"
             endIcon={<PlayArrowIcon />}
-            onInvoke={initializeContext}
+            onInvoke={startContext}
             requiresContext={false}
             disabled={!canContextBeManuallyStarted}
             isLoadingOverride={isContextInitializing}
@@ -38,6 +38,7 @@ import {
   setFiatCurrency,
   setTheme,
   setTorEnabled,
+  setAutoResumeSwaps,
   setUseMoneroRpcPool,
   setDonateToDevelopment,
 } from "store/features/settingsSlice";
@@ -58,7 +59,7 @@ import {
 import { getNetwork } from "store/config";
 import { currencySymbol } from "utils/formatUtils";
 import InfoBox from "renderer/components/modal/swap/InfoBox";
-import { isValidMultiAddressWithPeerId } from "utils/parseUtils";
+import { isValidRendezvousPoint } from "utils/parseUtils";
 import { getNodeStatus } from "renderer/rpc";
 import { setStatus } from "store/features/nodesSlice";
 
@@ -91,6 +92,7 @@ export default function SettingsBox() {
             <Table>
               <TableBody>
                 <TorSettings />
+                <AutoResumeSwapsSetting />
                 <DonationTipSetting />
                 <ElectrumRpcUrlSetting />
                 <MoneroRpcPoolSetting />
@@ -715,6 +717,32 @@ export function TorSettings() {
   );
 }
 
+function AutoResumeSwapsSetting() {
+  const dispatch = useAppDispatch();
+  const autoResumeSwaps = useSettings((settings) => settings.autoResumeSwaps);
+  const handleChange = (event: React.ChangeEvent<HTMLInputElement>) =>
+    dispatch(setAutoResumeSwaps(event.target.checked));
+
+  return (
+    <TableRow>
+      <TableCell>
+        <SettingLabel
+          label="Resume swaps on startup"
+          tooltip="Resume all unfinished swaps whose Bitcoin is already locked when the app starts, so that you don't miss a refund."
+        />
+      </TableCell>
+
+      <TableCell>
+        <Switch
+          checked={autoResumeSwaps}
+          onChange={handleChange}
+          color="primary"
+        />
+      </TableCell>
+    </TableRow>
+  );
+}
+
 /**
  * A setting that allows you to manage rendezvous points for maker discovery
  */
@@ -738,7 +766,7 @@ function RendezvousPointsSetting() {
       <TableCell>
         <SettingLabel
           label="Rendezvous Points"
-          tooltip="These are the points where makers can be discovered. Add custom rendezvous points here to expand your maker discovery options."
+          tooltip="These are the points where makers can be discovered. Add custom rendezvous points or DNS seeds (/dnsaddr/<domain>) here to expand your maker discovery options. If none of them answer, we fall back to the ones that failed before."
         />
       </TableCell>
       <TableCell>
@@ -798,7 +826,7 @@ function RendezvousPointsSetting() {
                           onValidatedChange={setNewPoint}
                           placeholder="/dns4/discover.unstoppableswap.net/tcp/8888/p2p/12D3KooWA6cnqJpVnreBVnoro8midDL9Lpzmg8oJPoAGi7YYaamE"
                           fullWidth
-                          isValid={isValidMultiAddressWithPeerId}
+                          isValid={isValidRendezvousPoint}
                           variant="outlined"
                           noErrorWhenEmpty
                         />
@@ -808,7 +836,7 @@ function RendezvousPointsSetting() {
                           <IconButton
                             onClick={onAddNewPoint}
                             disabled={
-                              !isValidMultiAddressWithPeerId(newPoint) ||
+                              !isValidRendezvousPoint(newPoint) ||
                               newPoint.length === 0
                             }
                           >
@@ -12,7 +12,7 @@ import {
   isBobStateNamePossiblyRefundableSwap,
 } from "models/tauriModelExt";
 import PromiseInvokeButton from "renderer/components/PromiseInvokeButton";
-import { resumeSwap } from "renderer/rpc";
+import { cancelAndRefund, resumeSwap } from "renderer/rpc";
 
 export function SwapResumeButton({
   swap,
@@ -47,12 +47,9 @@ export function SwapCancelRefundButton({
 
   return (
     <PromiseInvokeButton
-      displayErrorSnackbar={false}
+      displayErrorSnackbar
       {...props}
-      onInvoke={async () => {
-        // TODO: Implement this using the Tauri RPC
-        throw new Error("Not implemented");
-      }}
+      onInvoke={() => cancelAndRefund(swap.swap_id)}
     >
       Attempt manual Cancel & Refund
     </PromiseInvokeButton>
@@ -4,18 +4,34 @@ import {
   BalanceResponse,
   BuyXmrArgs,
   BuyXmrResponse,
+  CancelAndRefundArgs,
   GetLogsArgs,
   GetLogsResponse,
   GetSwapInfoResponse,
   ListSellersArgs,
+  DiscoverMakersArgs,
+  DiscoverMakersResponse,
+  GetMakerStatsResponse,
+  CheckDbResponse,
+  ExportFormat,
+  GetHistoryExportArgs,
+  GetHistoryExportResponse,
+  GetSwapTimelineArgs,
+  GetSwapTimelineResponse,
+  GetSwapCostBreakdownArgs,
+  GetSwapCostBreakdownResponse,
   MoneroRecoveryArgs,
   ResumeSwapArgs,
   ResumeSwapResponse,
+  ResumeAllSwapsResponse,
   SuspendCurrentSwapResponse,
+  SuspendSwapArgs,
+  SuspendSwapResponse,
   WithdrawBtcArgs,
   WithdrawBtcResponse,
   GetSwapInfoArgs,
   ExportBitcoinWalletResponse,
+  RescanBitcoinWalletResponse,
   CheckMoneroNodeArgs,
   CheckMoneroNodeResponse,
   TauriSettings,
@@ -23,13 +39,24 @@ import {
   CheckElectrumNodeResponse,
   GetMoneroAddressesResponse,
   GetDataDirArgs,
+  IsSeedEncryptedArgs,
   ResolveApprovalArgs,
   ResolveApprovalResponse,
   RedactArgs,
   RedactResponse,
   LabeledMoneroAddress,
+  ListBitcoinUtxosResponse,
+  Utxo,
+  GetBitcoinAddressLabelsResponse,
+  AddressLabel,
+  GetElectrumServerStatsResponse,
+  ElectrumServerStats,
 } from "models/tauriModel";
-import { rpcSetBalance, rpcSetSwapInfo } from "store/features/rpcSlice";
+import {
+  rpcSetBalance,
+  rpcSetSwapInfo,
+  seedPasswordRequiredChanged,
+} from "store/features/rpcSlice";
 import { store } from "./store/storeRenderer";
 import { Maker } from "models/apiModel";
 import { providerToConcatenatedMultiAddr } from "utils/multiAddrUtils";
@@ -161,6 +188,9 @@ export async function withdrawBtc(address: string): Promise<string> {
     {
       address,
       amount: null,
+      utxo_include: [],
+      utxo_exclude: [],
+      fee_rate_sat_per_vb: null,
     },
   );
 
@@ -207,6 +237,7 @@ export async function buyXmr(
     seller: providerToConcatenatedMultiAddr(seller),
     monero_receive_pool: address_pool,
     bitcoin_change_address,
+    rendezvous_points: [],
   });
 }
 
@@ -216,10 +247,29 @@ export async function resumeSwap(swapId: string) {
   });
 }
 
+export async function resumeAllSwaps() {
+  await invokeNoArgs<ResumeAllSwapsResponse>("resume_all_swaps");
+}
+
 export async function suspendCurrentSwap() {
   await invokeNoArgs<SuspendCurrentSwapResponse>("suspend_current_swap");
 }
 
+export async function suspendSwap(swapId: string) {
+  await invoke<SuspendSwapArgs, SuspendSwapResponse>("suspend_swap", {
+    swap_id: swapId,
+  });
+}
+
+export async function cancelAndRefund(swapId: string) {
+  await invoke<CancelAndRefundArgs, unknown>("cancel_and_refund", {
+    swap_id: swapId,
+  });
+
+  // The swap is now cancelled or refunded, show the new state in the history
+  await getSwapInfo(swapId);
+}
+
 export async function getMoneroRecoveryKeys(
   swapId: string,
 ): Promise<MoneroRecoveryResponse> {
@@ -265,10 +315,67 @@ export async function listSellersAtRendezvousPoint(
   });
 }
 
-export async function initializeContext() {
+export async function discoverMakers(): Promise<DiscoverMakersResponse> {
+  return await invoke<DiscoverMakersArgs, DiscoverMakersResponse>(
+    "discover_makers",
+    {
+      rendezvous_points: store.getState().settings.rendezvousPoints,
+    },
+  );
+}
+
+export async function getMakerStats(): Promise<GetMakerStatsResponse> {
+  return await invokeNoArgs<GetMakerStatsResponse>("get_maker_stats");
+}
+
+export async function checkDb(): Promise<CheckDbResponse> {
+  return await invokeNoArgs<CheckDbResponse>("check_db");
+}
+
+export async function getSwapTimeline(
+  swapId: string,
+): Promise<GetSwapTimelineResponse> {
+  return await invoke<GetSwapTimelineArgs, GetSwapTimelineResponse>(
+    "get_swap_timeline",
+    { swap_id: swapId },
+  );
+}
+
+export async function getSwapCostBreakdown(
+  swapId: string,
+): Promise<GetSwapCostBreakdownResponse> {
+  return await invoke<GetSwapCostBreakdownArgs, GetSwapCostBreakdownResponse>(
+    "get_swap_cost_breakdown",
+    { swap_id: swapId },
+  );
+}
+
+export async function exportHistory(
+  format: ExportFormat,
+  path: string,
+): Promise<GetHistoryExportResponse> {
+  return await invoke<GetHistoryExportArgs, GetHistoryExportResponse>(
+    "export_history",
+    { format, path },
+  );
+}
+
+// Initializes the context, unless the seed is encrypted. Then we ask the user for the
+// password first and the dialog initializes the context with it.
+export async function startContext() {
+  if (await isSeedEncrypted()) {
+    store.dispatch(seedPasswordRequiredChanged(true));
+    return;
+  }
+
+  await initializeContext();
+}
+
+export async function initializeContext(password?: string) {
   const network = getNetwork();
   const testnet = isTestnet();
   const useTor = store.getState().settings.enableTor;
+  const autoResumeSwaps = store.getState().settings.autoResumeSwaps;
 
   // Get all Bitcoin nodes without checking availability
   // The backend ElectrumBalancer will handle load balancing and failover
@@ -300,6 +407,7 @@ export async function initializeContext() {
     electrum_rpc_urls: bitcoinNodes,
     monero_node_config: moneroNodeConfig,
     use_tor: useTor,
+    auto_resume_swaps: autoResumeSwaps,
   };
 
   logger.info("Initializing context with settings", tauriSettings);
@@ -307,6 +415,7 @@ export async function initializeContext() {
   await invokeUnsafe<void>("initialize_context", {
     settings: tauriSettings,
     testnet,
+    password: password ?? null,
   });
 }
 
@@ -316,6 +425,39 @@ export async function getWalletDescriptor() {
   );
 }
 
+export async function rescanBitcoinWallet() {
+  const response = await invokeNoArgs<RescanBitcoinWalletResponse>(
+    "rescan_bitcoin_wallet",
+  );
+
+  store.dispatch(rpcSetBalance(response.balance));
+}
+
+export async function listBitcoinUtxos(): Promise<Utxo[]> {
+  const response =
+    await invokeNoArgs<ListBitcoinUtxosResponse>("list_bitcoin_utxos");
+
+  return response.utxos;
+}
+
+export async function getBitcoinAddressLabels(): Promise<AddressLabel[]> {
+  const response = await invokeNoArgs<GetBitcoinAddressLabelsResponse>(
+    "get_bitcoin_address_labels",
+  );
+
+  return response.labels;
+}
+
+export async function getElectrumServerStats(): Promise<
+  ElectrumServerStats[]
+> {
+  const response = await invokeNoArgs<GetElectrumServerStatsResponse>(
+    "get_electrum_server_stats",
+  );
+
+  return response.servers;
+}
+
 export async function getMoneroNodeStatus(
   node: string,
   network: Network,
@@ -393,6 +535,12 @@ export async function getDataDir(): Promise<string> {
   });
 }
 
+export async function isSeedEncrypted(): Promise<boolean> {
+  return await invoke<IsSeedEncryptedArgs, boolean>("is_seed_encrypted", {
+    is_testnet: isTestnet(),
+  });
+}
+
 export async function resolveApproval(
   requestId: string,
   accept: boolean,
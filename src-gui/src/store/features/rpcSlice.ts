@@ -42,12 +42,15 @@ interface State {
 
 export interface RPCSlice {
   status: TauriContextStatusEvent | null;
+  // Whether the seed is encrypted and we are waiting for the user to enter its password
+  seedPasswordRequired: boolean;
   state: State;
   logs: (CliLog | string)[];
 }
 
 const initialState: RPCSlice = {
   status: null,
+  seedPasswordRequired: false,
   state: {
     balance: null,
     withdrawTxId: null,
@@ -79,6 +82,9 @@ export const rpcSlice = createSlice({
     ) {
       slice.status = action.payload;
     },
+    seedPasswordRequiredChanged(slice, action: PayloadAction<boolean>) {
+      slice.seedPasswordRequired = action.payload;
+    },
     timelockChangeEventReceived(
       slice: RPCSlice,
       action: PayloadAction<TauriTimelockChangeEvent>,
@@ -154,6 +160,7 @@ export const rpcSlice = createSlice({
 
 export const {
   contextStatusEventReceived,
+  seedPasswordRequiredChanged,
   receivedCliLog,
   rpcSetBalance,
   rpcSetWithdrawTxId,
@@ -21,6 +21,8 @@ export interface SettingsState {
   enableTor: boolean;
   /// Whether to use the Monero RPC pool for load balancing (true) or custom nodes (false)
   useMoneroRpcPool: boolean;
+  /// Whether to resume all unfinished swaps when the app starts
+  autoResumeSwaps: boolean;
   userHasSeenIntroduction: boolean;
   /// List of rendezvous points
   rendezvousPoints: string[];
@@ -127,6 +129,7 @@ const initialState: SettingsState = {
   fiatCurrency: FiatCurrency.Usd,
   enableTor: true,
   useMoneroRpcPool: true, // Default to using RPC pool
+  autoResumeSwaps: true,
   userHasSeenIntroduction: false,
   rendezvousPoints: DEFAULT_RENDEZVOUS_POINTS,
   donateToDevelopment: false, // Default to no donation
@@ -218,6 +221,9 @@ const alertsSlice = createSlice({
     setUseMoneroRpcPool(slice, action: PayloadAction<boolean>) {
       slice.useMoneroRpcPool = action.payload;
     },
+    setAutoResumeSwaps(slice, action: PayloadAction<boolean>) {
+      slice.autoResumeSwaps = action.payload;
+    },
     setDonateToDevelopment(
       slice,
       action: PayloadAction<DonateToDevelopmentTip>,
@@ -237,6 +243,7 @@ export const {
   setFiatCurrency,
   setTorEnabled,
   setUseMoneroRpcPool,
+  setAutoResumeSwaps,
   setUserHasSeenIntroduction,
   addRendezvousPoint,
   removeRendezvousPoint,
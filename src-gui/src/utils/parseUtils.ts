@@ -75,6 +75,22 @@ export function isValidMultiAddressWithPeerId(
   }
 }
 
+// This function checks if a given string can be used as a rendezvous point, which is
+// either a multi address with a peer ID or a DNS seed like `/dnsaddr/<domain>`.
+export function isValidRendezvousPoint(multiAddressStr: string): boolean {
+  if (isValidMultiAddressWithPeerId(multiAddressStr)) {
+    return true;
+  }
+
+  try {
+    const protoNames = new Multiaddr(multiAddressStr).protoNames();
+
+    return protoNames.length === 1 && protoNames[0] === "dnsaddr";
+  } catch {
+    return false;
+  }
+}
+
 // This function splits a multi address string into the multi address and peer ID components.
 // It throws an error if the multi address string is invalid or does not contain a peer ID component.
 export function splitPeerIdFromMultiAddress(
@@ -7,18 +7,22 @@ use swap::cli::{
     api::{
         data,
         request::{
-            BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, CheckElectrumNodeArgs,
+            BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, CheckDbArgs, CheckElectrumNodeArgs,
             CheckElectrumNodeResponse, CheckMoneroNodeArgs, CheckMoneroNodeResponse,
-            ExportBitcoinWalletArgs, GetDataDirArgs, GetHistoryArgs, GetLogsArgs,
-            GetMoneroAddressesArgs, GetSwapInfoArgs, GetSwapInfosAllArgs, ListSellersArgs,
-            MoneroRecoveryArgs, RedactArgs, ResolveApprovalArgs, ResumeSwapArgs,
-            SuspendCurrentSwapArgs, WithdrawBtcArgs,
+            DiscoverMakersArgs, ExportBitcoinWalletArgs, GetBitcoinAddressLabelsArgs,
+            GetDataDirArgs, GetElectrumServerStatsArgs, GetHistoryArgs, GetHistoryExportArgs,
+            GetLogsArgs, GetMakerStatsArgs, GetMoneroAddressesArgs, GetSwapCostBreakdownArgs,
+            GetSwapInfoArgs, GetSwapInfosAllArgs, GetSwapTimelineArgs, IsSeedEncryptedArgs,
+            ListBitcoinUtxosArgs, ListSellersArgs, MoneroRecoveryArgs, RedactArgs, Request,
+            RescanBitcoinWalletArgs, ResolveApprovalArgs, ResumeAllSwapsArgs, ResumeSwapArgs,
+            SuspendCurrentSwapArgs, SuspendSwapArgs, WithdrawBtcArgs,
         },
         tauri_bindings::{TauriContextStatusEvent, TauriEmitter, TauriHandle, TauriSettings},
         Context, ContextBuilder,
     },
     command::Bitcoin,
 };
+use swap::seed::Seed;
 use tauri::{async_runtime::RwLock, Manager, RunEvent};
 use tauri_plugin_dialog::DialogExt;
 use zip::{write::SimpleFileOptions, ZipWriter};
@@ -175,22 +179,35 @@ pub fn run() {
             get_balance,
             get_monero_addresses,
             get_swap_info,
+            get_swap_timeline,
+            get_swap_cost_breakdown,
             get_swap_infos_all,
             withdraw_btc,
             buy_xmr,
             resume_swap,
+            resume_all_swaps,
             get_history,
+            export_history,
             monero_recovery,
             get_logs,
             list_sellers,
+            discover_makers,
+            get_maker_stats,
+            check_db,
             suspend_current_swap,
+            suspend_swap,
             cancel_and_refund,
             is_context_available,
             initialize_context,
             check_monero_node,
             check_electrum_node,
             get_wallet_descriptor,
+            rescan_bitcoin_wallet,
+            list_bitcoin_utxos,
+            get_bitcoin_address_labels,
+            get_electrum_server_stats,
             get_data_dir,
+            is_seed_encrypted,
             resolve_approval_request,
             redact,
             save_txt_files,
@@ -229,21 +246,41 @@ pub fn run() {
 tauri_command!(get_balance, BalanceArgs);
 tauri_command!(buy_xmr, BuyXmrArgs);
 tauri_command!(resume_swap, ResumeSwapArgs);
+tauri_command!(resume_all_swaps, ResumeAllSwapsArgs, no_args);
 tauri_command!(withdraw_btc, WithdrawBtcArgs);
 tauri_command!(monero_recovery, MoneroRecoveryArgs);
 tauri_command!(get_logs, GetLogsArgs);
 tauri_command!(list_sellers, ListSellersArgs);
+tauri_command!(discover_makers, DiscoverMakersArgs);
+tauri_command!(get_maker_stats, GetMakerStatsArgs, no_args);
+tauri_command!(check_db, CheckDbArgs, no_args);
 tauri_command!(cancel_and_refund, CancelAndRefundArgs);
+tauri_command!(suspend_swap, SuspendSwapArgs);
 tauri_command!(resolve_approval_request, ResolveApprovalArgs);
 tauri_command!(redact, RedactArgs);
 
 // These commands require no arguments
 tauri_command!(get_wallet_descriptor, ExportBitcoinWalletArgs, no_args);
+tauri_command!(rescan_bitcoin_wallet, RescanBitcoinWalletArgs, no_args);
 tauri_command!(suspend_current_swap, SuspendCurrentSwapArgs, no_args);
 tauri_command!(get_swap_info, GetSwapInfoArgs);
+tauri_command!(get_swap_timeline, GetSwapTimelineArgs);
+tauri_command!(get_swap_cost_breakdown, GetSwapCostBreakdownArgs);
 tauri_command!(get_swap_infos_all, GetSwapInfosAllArgs, no_args);
 tauri_command!(get_history, GetHistoryArgs, no_args);
+tauri_command!(export_history, GetHistoryExportArgs);
 tauri_command!(get_monero_addresses, GetMoneroAddressesArgs, no_args);
+tauri_command!(list_bitcoin_utxos, ListBitcoinUtxosArgs, no_args);
+tauri_command!(
+    get_bitcoin_address_labels,
+    GetBitcoinAddressLabelsArgs,
+    no_args
+);
+tauri_command!(
+    get_electrum_server_stats,
+    GetElectrumServerStatsArgs,
+    no_args
+);
 
 /// Here we define Tauri commands whose implementation is not delegated to the Request trait
 #[tauri::command]
@@ -282,6 +319,18 @@ async fn get_data_dir(
         .to_string())
 }
 
+// Returns whether the seed is protected by a password, which then has to be passed to
+// `initialize_context`. Like the data directory, this is needed before the context exists
+#[tauri::command]
+async fn is_seed_encrypted(
+    args: IsSeedEncryptedArgs,
+    _: tauri::State<'_, RwLock<State>>,
+) -> Result<bool, String> {
+    let data_dir = data::data_dir_from(None, args.is_testnet).to_string_result()?;
+
+    Seed::is_encrypted(&data_dir).to_string_result()
+}
+
 #[tauri::command]
 async fn save_txt_files(
     app: tauri::AppHandle,
@@ -328,10 +377,13 @@ async fn save_txt_files(
 }
 
 /// Tauri command to initialize the Context
+///
+/// The password unlocks the seed. It is kept out of the settings, which are persisted and logged.
 #[tauri::command]
 async fn initialize_context(
     settings: TauriSettings,
     testnet: bool,
+    password: Option<String>,
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, RwLock<State>>,
 ) -> Result<(), String> {
@@ -377,23 +429,37 @@ async fn initialize_context(
         .with_bitcoin(Bitcoin {
             bitcoin_electrum_rpc_urls: settings.electrum_rpc_urls.clone(),
             bitcoin_target_block: None,
+            bitcoin_taproot: false,
+            // With `use_tor` the Electrum connections go through the Tor client of the context
+            bitcoin_electrum_socks5_proxy: None,
         })
         .with_monero(settings.monero_node_config)
         .with_json(false)
         .with_debug(true)
         .with_tor(settings.use_tor)
+        .with_seed_password(password)
         .with_tauri(tauri_handle.clone())
         .build()
         .await;
 
     match context_result {
         Ok(context_instance) => {
-            state_write_lock.set_context(Arc::new(context_instance));
+            let context = Arc::new(context_instance);
+            state_write_lock.set_context(context.clone());
 
             tracing::info!("Context initialized");
 
             // Emit event to frontend
             tauri_handle.emit_context_init_progress_event(TauriContextStatusEvent::Available);
+
+            if settings.auto_resume_swaps {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(error) = ResumeAllSwapsArgs.request(context).await {
+                        tracing::error!(%error, "Failed to resume unfinished swaps");
+                    }
+                });
+            }
+
             Ok(())
         }
         Err(e) => {
@@ -401,7 +467,8 @@ async fn initialize_context(
 
             // Emit event to frontend
             tauri_handle.emit_context_init_progress_event(TauriContextStatusEvent::Failed);
-            Err(e.to_string())
+            // Include the causes, e.g. that the password of the seed was wrong
+            Err(format!("{:#}", e))
         }
     }
 }
@@ -0,0 +1,8 @@
+-- The Bitcoin transactions we wait for, so we keep tracking them after a restart
+CREATE TABLE if NOT EXISTS bitcoin_subscriptions
+(
+    txid        TEXT    NOT NULL,
+    script      TEXT    NOT NULL,
+    entered_at  TEXT    NOT NULL,
+    PRIMARY KEY (txid, script)
+);
@@ -0,0 +1,8 @@
+-- The transactions we keep broadcasting until a server reports them, also after a restart
+CREATE TABLE if NOT EXISTS bitcoin_broadcasts
+(
+    txid        TEXT    PRIMARY KEY NOT NULL,
+    kind        TEXT    NOT NULL,
+    tx          TEXT    NOT NULL,
+    entered_at  INTEGER NOT NULL
+);
@@ -0,0 +1,8 @@
+-- How often a maker sent us a quote when we looked for makers, to rank them by uptime
+CREATE TABLE if NOT EXISTS maker_reachability
+(
+    peer_id             TEXT    PRIMARY KEY NOT NULL,
+    online_count        INTEGER NOT NULL DEFAULT 0,
+    unreachable_count   INTEGER NOT NULL DEFAULT 0,
+    last_online_at      TEXT
+);
@@ -0,0 +1,11 @@
+-- How the swaps with every maker went, so that we can avoid makers that let us down before
+CREATE TABLE if NOT EXISTS peer_stats
+(
+    peer_id             TEXT    PRIMARY KEY NOT NULL,
+    completed_count     INTEGER NOT NULL DEFAULT 0,
+    aborted_count       INTEGER NOT NULL DEFAULT 0,
+    punished_count      INTEGER NOT NULL DEFAULT 0,
+    avg_lock_time_secs  REAL,
+    lock_time_samples   INTEGER NOT NULL DEFAULT 0,
+    last_seen_at        TEXT
+);
@@ -0,0 +1,11 @@
+-- The Bitcoin every swap locked with the maker, to enforce its daily volume limits
+CREATE TABLE if NOT EXISTS swap_volumes
+(
+    swap_id         TEXT    PRIMARY KEY NOT NULL,
+    peer_id         TEXT    NOT NULL,
+    btc_amount_sat  INTEGER NOT NULL,
+    -- A unix timestamp, so we can sum up the volume of a time range
+    locked_at       INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS swap_volumes_locked_at ON swap_volumes (locked_at);
@@ -0,0 +1,9 @@
+-- How discovery at a rendezvous point went, to try the healthy ones first
+CREATE TABLE if NOT EXISTS rendezvous_point_health
+(
+    peer_id                 TEXT    PRIMARY KEY NOT NULL,
+    success_count           INTEGER NOT NULL DEFAULT 0,
+    failure_count           INTEGER NOT NULL DEFAULT 0,
+    consecutive_failures    INTEGER NOT NULL DEFAULT 0,
+    last_success_at         TEXT
+);
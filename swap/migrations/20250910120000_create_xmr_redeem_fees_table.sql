@@ -0,0 +1,6 @@
+-- The fee Bob paid to sweep the Monero of a swap to his receive addresses
+CREATE TABLE if NOT EXISTS xmr_redeem_fees
+(
+    swap_id         TEXT    PRIMARY KEY NOT NULL,
+    fee_piconero    INTEGER NOT NULL
+);
@@ -0,0 +1,8 @@
+-- What the Bitcoin addresses we use (ours or the ones we send to) are for
+CREATE TABLE if NOT EXISTS bitcoin_address_labels
+(
+    address     TEXT    PRIMARY KEY NOT NULL,
+    label       TEXT    NOT NULL,
+    swap_id     TEXT,
+    created_at  INTEGER NOT NULL
+);
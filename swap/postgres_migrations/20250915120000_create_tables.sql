@@ -0,0 +1,122 @@
+-- The same tables as in the sqlite database, in their current shape.
+-- Several asb instances can share one database, every row belongs to the instance (the peer id
+-- of the asb) that wrote it.
+CREATE TABLE IF NOT EXISTS swap_states
+(
+    id          BIGSERIAL   PRIMARY KEY,
+    instance    TEXT        NOT NULL,
+    swap_id     TEXT        NOT NULL,
+    entered_at  TEXT        NOT NULL,
+    state       TEXT        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS swap_states_instance_swap_id ON swap_states (instance, swap_id);
+
+CREATE TABLE IF NOT EXISTS monero_addresses
+(
+    instance    TEXT                NOT NULL,
+    swap_id     TEXT                NOT NULL,
+    address     TEXT                NOT NULL,
+    percentage  DOUBLE PRECISION    NOT NULL DEFAULT 1.0,
+    label       TEXT                NOT NULL DEFAULT 'user address'
+);
+
+CREATE INDEX IF NOT EXISTS monero_addresses_instance_swap_id ON monero_addresses (instance, swap_id);
+
+CREATE TABLE IF NOT EXISTS peers
+(
+    instance    TEXT    NOT NULL,
+    swap_id     TEXT    NOT NULL,
+    peer_id     TEXT    NOT NULL,
+    PRIMARY KEY (instance, swap_id)
+);
+
+CREATE TABLE IF NOT EXISTS peer_addresses
+(
+    instance    TEXT    NOT NULL,
+    peer_id     TEXT    NOT NULL,
+    address     TEXT    NOT NULL,
+    PRIMARY KEY (instance, peer_id, address)
+);
+
+CREATE TABLE IF NOT EXISTS buffered_transfer_proofs
+(
+    instance    TEXT    NOT NULL,
+    swap_id     TEXT    NOT NULL,
+    proof       TEXT    NOT NULL,
+    PRIMARY KEY (instance, swap_id)
+);
+
+CREATE TABLE IF NOT EXISTS bitcoin_subscriptions
+(
+    instance    TEXT    NOT NULL,
+    txid        TEXT    NOT NULL,
+    script      TEXT    NOT NULL,
+    entered_at  TEXT    NOT NULL,
+    PRIMARY KEY (instance, txid, script)
+);
+
+CREATE TABLE IF NOT EXISTS bitcoin_broadcasts
+(
+    instance    TEXT    NOT NULL,
+    txid        TEXT    NOT NULL,
+    kind        TEXT    NOT NULL,
+    tx          TEXT    NOT NULL,
+    entered_at  BIGINT  NOT NULL,
+    PRIMARY KEY (instance, txid)
+);
+
+CREATE TABLE IF NOT EXISTS maker_reachability
+(
+    instance            TEXT    NOT NULL,
+    peer_id             TEXT    NOT NULL,
+    online_count        BIGINT  NOT NULL DEFAULT 0,
+    unreachable_count   BIGINT  NOT NULL DEFAULT 0,
+    last_online_at      TEXT,
+    PRIMARY KEY (instance, peer_id)
+);
+
+CREATE TABLE IF NOT EXISTS peer_stats
+(
+    instance            TEXT                NOT NULL,
+    peer_id             TEXT                NOT NULL,
+    completed_count     BIGINT              NOT NULL DEFAULT 0,
+    aborted_count       BIGINT              NOT NULL DEFAULT 0,
+    punished_count      BIGINT              NOT NULL DEFAULT 0,
+    avg_lock_time_secs  DOUBLE PRECISION,
+    lock_time_samples   BIGINT              NOT NULL DEFAULT 0,
+    last_seen_at        TEXT,
+    PRIMARY KEY (instance, peer_id)
+);
+
+CREATE TABLE IF NOT EXISTS swap_volumes
+(
+    instance        TEXT    NOT NULL,
+    swap_id         TEXT    NOT NULL,
+    peer_id         TEXT    NOT NULL,
+    btc_amount_sat  BIGINT  NOT NULL,
+    -- A unix timestamp, so we can sum up the volume of a time range
+    locked_at       BIGINT  NOT NULL,
+    PRIMARY KEY (instance, swap_id)
+);
+
+CREATE INDEX IF NOT EXISTS swap_volumes_instance_locked_at ON swap_volumes (instance, locked_at);
+
+CREATE TABLE IF NOT EXISTS rendezvous_point_health
+(
+    instance                TEXT    NOT NULL,
+    peer_id                 TEXT    NOT NULL,
+    success_count           BIGINT  NOT NULL DEFAULT 0,
+    failure_count           BIGINT  NOT NULL DEFAULT 0,
+    consecutive_failures    BIGINT  NOT NULL DEFAULT 0,
+    last_success_at         TEXT,
+    PRIMARY KEY (instance, peer_id)
+);
+
+CREATE TABLE IF NOT EXISTS xmr_redeem_fees
+(
+    instance        TEXT    NOT NULL,
+    swap_id         TEXT    NOT NULL,
+    fee_piconero    BIGINT  NOT NULL,
+    PRIMARY KEY (instance, swap_id)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS bitcoin_address_labels
+(
+    instance    TEXT    NOT NULL,
+    address     TEXT    NOT NULL,
+    label       TEXT    NOT NULL,
+    swap_id     TEXT,
+    -- A unix timestamp, the labels are listed oldest first
+    created_at  BIGINT  NOT NULL,
+    PRIMARY KEY (instance, address)
+);
@@ -1,21 +1,29 @@
 pub mod command;
 pub mod config;
 mod event_loop;
+pub mod hooks;
+pub mod management;
 mod network;
 mod rate;
+pub mod rate_provider;
 mod recovery;
+pub mod volume_limits;
 
-pub use event_loop::{EventLoop, EventLoopHandle, FixedRate, KrakenRate, LatestRate};
+pub use event_loop::{EventLoop, EventLoopHandle, FixedRate, KrakenRate, LatestRate, MarketRate};
+pub use hooks::SwapHooks;
+pub use management::TradingSwitch;
 pub use network::behaviour::{Behaviour, OutEvent};
-pub use network::rendezvous::RendezvousNode;
+pub use network::rendezvous::{RegistrationReport, RendezvousNode, RendezvousStatus};
 pub use network::transport;
 pub use rate::Rate;
+pub use rate_provider::RateProvider;
 pub use recovery::cancel::cancel;
 pub use recovery::punish::punish;
 pub use recovery::redeem::{redeem, Finality};
 pub use recovery::refund::refund;
 pub use recovery::safely_abort::safely_abort;
 pub use recovery::{cancel, refund};
+pub use volume_limits::VolumeLimits;
 
 #[cfg(test)]
 pub use network::rendezvous;
@@ -1,4 +1,8 @@
-use crate::asb::{Behaviour, OutEvent, Rate};
+use crate::asb::volume_limits::VolumeReservation;
+use crate::asb::{
+    rate_provider, volume_limits, Behaviour, OutEvent, Rate, RateProvider, SwapHooks,
+    TradingSwitch, VolumeLimits,
+};
 use crate::network::cooperative_xmr_redeem_after_punish::CooperativeXmrRedeemRejectReason;
 use crate::network::cooperative_xmr_redeem_after_punish::Response::{Fullfilled, Rejected};
 use crate::network::quote::BidQuote;
@@ -30,6 +34,10 @@ use uuid::Uuid;
 /// The time-to-live for quotes in the cache
 const QUOTE_CACHE_TTL: Duration = Duration::from_secs(120);
 
+/// How long Bob can base a swap setup on a quote we handed out. Older swap setups are
+/// rejected and Bob has to request a fresh quote.
+const QUOTE_VALIDITY: Duration = Duration::from_secs(300);
+
 /// The key for the quote cache
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct QuoteCacheKey {
@@ -51,6 +59,9 @@ where
     min_buy: bitcoin::Amount,
     max_buy: bitcoin::Amount,
     external_redeem_address: Option<bitcoin::Address>,
+    hooks: SwapHooks,
+    trading: TradingSwitch,
+    volume_limits: VolumeLimits,
 
     /// Cache for quotes
     quote_cache: Cache<QuoteCacheKey, Result<Arc<BidQuote>, Arc<anyhow::Error>>>,
@@ -138,6 +149,9 @@ where
         min_buy: bitcoin::Amount,
         max_buy: bitcoin::Amount,
         external_redeem_address: Option<bitcoin::Address>,
+        hooks: SwapHooks,
+        trading: TradingSwitch,
+        volume_limits: VolumeLimits,
     ) -> Result<(Self, mpsc::Receiver<Swap>)> {
         let swap_channel = MpscChannels::default();
         let (outgoing_transfer_proofs_sender, outgoing_transfer_proofs_requests) =
@@ -156,6 +170,9 @@ where
             min_buy,
             max_buy,
             external_redeem_address,
+            hooks,
+            trading,
+            volume_limits,
             quote_cache,
             recv_encrypted_signature: Default::default(),
             inflight_encrypted_signatures: Default::default(),
@@ -207,6 +224,7 @@ where
                 monero_wallet: self.monero_wallet.clone(),
                 env_config: self.env_config,
                 db: self.db.clone(),
+                hooks: self.hooks.clone(),
                 state: state.try_into().expect("Alice state loaded from db"),
                 swap_id,
             };
@@ -223,7 +241,7 @@ where
             tokio::select! {
                 swarm_event = self.swarm.select_next_some() => {
                     match swarm_event {
-                        SwarmEvent::Behaviour(OutEvent::SwapSetupInitiated { mut send_wallet_snapshot }) => {
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupInitiated { peer_id, mut send_wallet_snapshot }) => {
                             let (btc, responder) = match send_wallet_snapshot.recv().await {
                                 Ok((btc, responder)) => (btc, responder),
                                 Err(error) => {
@@ -232,7 +250,21 @@ where
                                 }
                             };
 
-                            let wallet_snapshot = match WalletSnapshot::capture(&self.bitcoin_wallet, &self.monero_wallet, &self.external_redeem_address, btc).await {
+                            if self.trading.is_paused() {
+                                // Dropping the responder makes the swap setup fail for Bob
+                                tracing::info!("Swap request will be ignored because trading is paused");
+                                continue;
+                            }
+
+                            let (remaining_volume, volume_reservation) = match self.volume_limits.reserve(self.db.as_ref(), peer_id, btc).await {
+                                Ok(reserved) => reserved,
+                                Err(error) => {
+                                    tracing::error!(%peer_id, "Swap request will be ignored because we were unable to check the volume limits: {:#}", error);
+                                    continue;
+                                }
+                            };
+
+                            let wallet_snapshot = match WalletSnapshot::capture(&self.bitcoin_wallet, &self.monero_wallet, &self.external_redeem_address, btc, remaining_volume, volume_reservation).await {
                                 Ok(wallet_snapshot) => wallet_snapshot,
                                 Err(error) => {
                                     tracing::error!("Swap request will be ignored because we were unable to create wallet snapshot for swap: {:#}", error);
@@ -243,14 +275,33 @@ where
                             // Ignore result, we should never hit this because the receiver will alive as long as the connection is.
                             let _ = responder.respond(wallet_snapshot);
                         }
-                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted{peer_id, swap_id, state3}) => {
-                            self.handle_execution_setup_done(peer_id, swap_id, state3).await;
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted{peer_id, swap_id, state3, volume_reservation}) => {
+                            self.handle_execution_setup_done(peer_id, swap_id, state3, volume_reservation).await;
                         }
                         SwarmEvent::Behaviour(OutEvent::SwapDeclined { peer, error }) => {
                             tracing::warn!(%peer, "Ignoring spot price request: {}", error);
                         }
                         SwarmEvent::Behaviour(OutEvent::QuoteRequested { channel, peer }) => {
-                            match self.make_quote_or_use_cached(self.min_buy, self.max_buy).await {
+                            // While trading is paused we respond as if we failed to make a quote
+                            let quote = if self.trading.is_paused() {
+                                Err(Arc::new(anyhow!("Trading is paused")))
+                            } else {
+                                self.make_quote_or_use_cached(self.min_buy, self.max_buy).await
+                            };
+
+                            // The cached quote is the same for everyone, the volume limits are not
+                            let quote = match quote {
+                                Ok(quote_arc) => match self.volume_limits.remaining(self.db.as_ref(), peer).await {
+                                    Ok(remaining) => Ok(Arc::new(volume_limits::limit_quote(*quote_arc, remaining).valid_for(QUOTE_VALIDITY))),
+                                    Err(error) => {
+                                        tracing::error!(%peer, "Failed to check the volume limits: {:#}", error);
+                                        Err(Arc::new(error))
+                                    }
+                                },
+                                Err(error) => Err(error),
+                            };
+
+                            match quote {
                                 Ok(quote_arc) => {
                                     if self.swarm.behaviour_mut().quote.send_response(channel, *quote_arc).is_err() {
                                         tracing::debug!(%peer, "Failed to respond with quote");
@@ -561,9 +612,33 @@ where
         bob_peer_id: PeerId,
         swap_id: Uuid,
         state3: State3,
+        volume_reservation: VolumeReservation,
     ) {
         let handle = self.new_handle(bob_peer_id, swap_id);
 
+        // The swap counts towards the volume limits from now on, until it is aborted safely
+        if let Err(error) = self
+            .db
+            .record_swap_volume(swap_id, bob_peer_id, state3.btc)
+            .await
+        {
+            tracing::warn!(%swap_id, "Failed to record the volume of the swap: {:#}", error);
+        }
+        drop(volume_reservation);
+
+        for (address, label) in [
+            (&state3.redeem_address, "Swap redeem"),
+            (&state3.punish_address, "Swap punish"),
+        ] {
+            if let Err(error) = self
+                .bitcoin_wallet
+                .label_address(address, label, Some(swap_id))
+                .await
+            {
+                tracing::warn!(%swap_id, "Failed to label Bitcoin address: {:#}", error);
+            }
+        }
+
         let initial_state = AliceState::Started {
             state3: Box::new(state3),
         };
@@ -574,6 +649,7 @@ where
             monero_wallet: self.monero_wallet.clone(),
             env_config: self.env_config,
             db: self.db.clone(),
+            hooks: self.hooks.clone(),
             state: initial_state,
             swap_id,
         };
@@ -676,6 +752,33 @@ impl LatestRate for KrakenRate {
     }
 }
 
+/// Produces [`Rate`]s based on the asking price of the configured [`RateProvider`] and a
+/// configured spread.
+#[derive(Debug, Clone)]
+pub struct MarketRate {
+    ask_spread: Decimal,
+    provider: Arc<dyn RateProvider>,
+}
+
+impl MarketRate {
+    pub fn new(ask_spread: Decimal, provider: Arc<dyn RateProvider>) -> Self {
+        Self {
+            ask_spread,
+            provider,
+        }
+    }
+}
+
+impl LatestRate for MarketRate {
+    type Error = rate_provider::Error;
+
+    fn latest_rate(&mut self) -> Result<Rate, Self::Error> {
+        let ask = self.provider.latest_ask()?;
+
+        Ok(Rate::new(ask, self.ask_spread))
+    }
+}
+
 #[derive(Debug)]
 pub struct EventLoopHandle {
     swap_id: Uuid,
@@ -692,6 +795,11 @@ pub struct EventLoopHandle {
 }
 
 impl EventLoopHandle {
+    /// The peer we are swapping with.
+    pub fn peer_id(&self) -> PeerId {
+        self.peer
+    }
+
     fn build_transfer_proof_request(
         &self,
         transfer_proof: monero::TransferProof,
@@ -849,6 +957,7 @@ where
             price: ask_price,
             min_quantity: bitcoin::Amount::ZERO,
             max_quantity: bitcoin::Amount::ZERO,
+            expires_at: None,
         }));
     }
 
@@ -862,6 +971,7 @@ where
             price: ask_price,
             min_quantity: min_buy,
             max_quantity: max_bitcoin_for_monero,
+            expires_at: None,
         }));
     }
 
@@ -869,6 +979,7 @@ where
         price: ask_price,
         min_quantity: min_buy,
         max_quantity: max_buy,
+        expires_at: None,
     }))
 }
 
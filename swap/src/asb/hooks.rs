@@ -0,0 +1,378 @@
+use crate::asb::config;
+use crate::protocol::alice::{AliceState, State3};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use url::Url;
+use uuid::Uuid;
+
+/// We give up on a webhook or command that takes longer than this, to not pile up tasks.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sent to the webhooks and passed to the command whenever a swap changes its state.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateTransition {
+    pub swap_id: Uuid,
+    pub old_state: String,
+    pub new_state: String,
+    pub btc_amount_sat: Option<u64>,
+    pub xmr_amount_piconero: Option<u64>,
+    pub btc_lock_txid: Option<String>,
+    pub xmr_lock_txid: Option<String>,
+}
+
+impl StateTransition {
+    pub fn new(swap_id: Uuid, old_state: &AliceState, new_state: &AliceState) -> Self {
+        let state3 = state3(new_state).or_else(|| state3(old_state));
+        let xmr_lock_txid = match new_state {
+            AliceState::XmrLockTransactionSent { transfer_proof, .. }
+            | AliceState::XmrLocked { transfer_proof, .. }
+            | AliceState::XmrLockTransferProofSent { transfer_proof, .. }
+            | AliceState::EncSigLearned { transfer_proof, .. }
+            | AliceState::BtcRedeemTransactionPublished { transfer_proof, .. }
+            | AliceState::BtcCancelled { transfer_proof, .. }
+            | AliceState::BtcRefunded { transfer_proof, .. }
+            | AliceState::BtcPunishable { transfer_proof, .. }
+            | AliceState::CancelTimelockExpired { transfer_proof, .. }
+            | AliceState::BtcPunished { transfer_proof, .. } => Some(transfer_proof.tx_hash().0),
+            _ => None,
+        };
+
+        Self {
+            swap_id,
+            old_state: old_state.to_string(),
+            new_state: new_state.to_string(),
+            btc_amount_sat: state3.map(|state3| state3.btc.to_sat()),
+            xmr_amount_piconero: state3.map(|state3| state3.xmr.as_piconero()),
+            btc_lock_txid: state3.map(|state3| state3.tx_lock.txid().to_string()),
+            xmr_lock_txid,
+        }
+    }
+}
+
+pub(crate) fn state3(state: &AliceState) -> Option<&State3> {
+    match state {
+        AliceState::Started { state3 }
+        | AliceState::BtcLockTransactionSeen { state3 }
+        | AliceState::BtcLocked { state3 }
+        | AliceState::BtcEarlyRefundable { state3 }
+        | AliceState::XmrLockTransactionSent { state3, .. }
+        | AliceState::XmrLocked { state3, .. }
+        | AliceState::XmrLockTransferProofSent { state3, .. }
+        | AliceState::EncSigLearned { state3, .. }
+        | AliceState::BtcRedeemTransactionPublished { state3, .. }
+        | AliceState::BtcCancelled { state3, .. }
+        | AliceState::BtcEarlyRefunded(state3)
+        | AliceState::BtcRefunded { state3, .. }
+        | AliceState::BtcPunishable { state3, .. }
+        | AliceState::CancelTimelockExpired { state3, .. }
+        | AliceState::BtcPunished { state3, .. } => Some(state3),
+        AliceState::BtcRedeemed | AliceState::XmrRefunded | AliceState::SafelyAborted => None,
+    }
+}
+
+/// Tells the operator about state transitions of swaps by POSTing them to webhooks and/or
+/// running a command, as configured in the `[hooks]` section of the config.
+#[derive(Debug, Clone, Default)]
+pub struct SwapHooks {
+    client: reqwest::Client,
+    webhook_urls: Vec<Url>,
+    command: Vec<String>,
+}
+
+impl SwapHooks {
+    pub fn new(config: config::Hooks) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_urls: config.webhook_urls,
+            command: config.command,
+        }
+    }
+
+    /// Run the hooks in the background, a failing hook never holds up the swap.
+    pub fn notify(&self, transition: StateTransition) {
+        for url in self.webhook_urls.clone() {
+            let client = self.client.clone();
+            let transition = transition.clone();
+
+            tokio::spawn(async move {
+                if let Err(error) = post(&client, &url, &transition).await {
+                    tracing::warn!(swap_id = %transition.swap_id, %url, "Failed to call webhook: {:#}", error);
+                }
+            });
+        }
+
+        if !self.command.is_empty() {
+            let command = self.command.clone();
+
+            tokio::spawn(async move {
+                if let Err(error) = run_command(&command, &transition).await {
+                    tracing::warn!(swap_id = %transition.swap_id, "Failed to run hook command: {:#}", error);
+                }
+            });
+        }
+    }
+}
+
+async fn post(client: &reqwest::Client, url: &Url, transition: &StateTransition) -> Result<()> {
+    client
+        .post(url.clone())
+        .json(transition)
+        .timeout(HOOK_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Run the command with the transition as JSON on stdin. The swap id and the states are also
+/// passed as environment variables, which is all a simple script needs.
+async fn run_command(command: &[String], transition: &StateTransition) -> Result<()> {
+    let (program, args) = command.split_first().context("Hook command is empty")?;
+    let payload = serde_json::to_vec(transition)?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .env("SWAP_ID", transition.swap_id.to_string())
+        .env("SWAP_OLD_STATE", &transition.old_state)
+        .env("SWAP_NEW_STATE", &transition.new_state)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program))?;
+
+    // Commands that don't read the payload may exit before we wrote it, that's fine
+    let mut stdin = child.stdin.take().context("Failed to open stdin")?;
+    let _ = stdin.write_all(&payload).await;
+    drop(stdin);
+
+    let status = tokio::time::timeout(HOOK_TIMEOUT, child.wait())
+        .await
+        .context("Hook command timed out")??;
+    if !status.success() {
+        bail!("Hook command exited with {}", status);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin::{TestWalletBuilder, TxLock, TxPunish, TxRedeem};
+    use crate::env::{GetConfig, Regtest};
+    use crate::monero::{BlockHeight, TransferProof, TxHash};
+    use crate::protocol::{alice, bob};
+    use curve25519_dalek::scalar::Scalar;
+    use monero::PrivateKey;
+    use rand::rngs::OsRng;
+
+    /// Run the swap setup between two test wallets to get to Alice's [`State3`].
+    async fn alice_state3(
+        btc_amount: bitcoin::Amount,
+        xmr_amount: crate::monero::Amount,
+    ) -> State3 {
+        let alice_wallet = TestWalletBuilder::new(bitcoin::Amount::ONE_BTC.to_sat())
+            .build()
+            .await;
+        let bob_wallet = TestWalletBuilder::new(bitcoin::Amount::ONE_BTC.to_sat())
+            .build()
+            .await;
+        let spending_fee = bitcoin::Amount::from_sat(1_000);
+
+        let tx_redeem_fee = alice_wallet
+            .estimate_fee(TxRedeem::weight(), Some(btc_amount))
+            .await
+            .unwrap();
+        let tx_punish_fee = alice_wallet
+            .estimate_fee(TxPunish::weight(), Some(btc_amount))
+            .await
+            .unwrap();
+        let tx_lock_fee = alice_wallet
+            .estimate_fee(
+                TxLock::weight(crate::bitcoin::wallet::DescriptorType::Bip84),
+                Some(btc_amount),
+            )
+            .await
+            .unwrap();
+
+        let config = Regtest::get_config();
+        let alice_state0 = alice::State0::new(
+            btc_amount,
+            xmr_amount,
+            config,
+            alice_wallet.new_address().await.unwrap(),
+            alice_wallet.new_address().await.unwrap(),
+            tx_redeem_fee,
+            tx_punish_fee,
+            &mut OsRng,
+        );
+        let bob_state0 = bob::State0::new(
+            Uuid::new_v4(),
+            &mut OsRng,
+            btc_amount,
+            xmr_amount,
+            config.bitcoin_cancel_timelock,
+            config.bitcoin_punish_timelock,
+            bob_wallet.new_address().await.unwrap(),
+            config.monero_finality_confirmations,
+            spending_fee,
+            spending_fee,
+            tx_lock_fee,
+        );
+
+        let (_, alice_state1) = alice_state0.receive(bob_state0.next_message()).unwrap();
+        let bob_state1 = bob_state0
+            .receive(&bob_wallet, alice_state1.next_message())
+            .await
+            .unwrap();
+        let alice_state2 = alice_state1.receive(bob_state1.next_message()).unwrap();
+        let bob_state2 = bob_state1.receive(alice_state2.next_message()).unwrap();
+
+        alice_state2.receive(bob_state2.next_message()).unwrap()
+    }
+
+    fn transfer_proof(tx_hash: &str) -> TransferProof {
+        TransferProof::new(
+            TxHash(tx_hash.to_string()),
+            PrivateKey::from_scalar(Scalar::one()),
+        )
+    }
+
+    #[tokio::test]
+    async fn transition_has_the_amounts_and_transactions_of_the_swap() {
+        let state3 = Box::new(
+            alice_state3(
+                bitcoin::Amount::from_sat(500_000),
+                crate::monero::Amount::from_piconero(10_000),
+            )
+            .await,
+        );
+        let swap_id = Uuid::new_v4();
+
+        let transition = StateTransition::new(
+            swap_id,
+            &AliceState::BtcLocked {
+                state3: state3.clone(),
+            },
+            &AliceState::XmrLockTransactionSent {
+                monero_wallet_restore_blockheight: BlockHeight { height: 0 },
+                transfer_proof: transfer_proof("xmr-lock"),
+                state3: state3.clone(),
+            },
+        );
+
+        assert_eq!(transition.swap_id, swap_id);
+        assert_eq!(transition.old_state, "btc is locked");
+        assert_eq!(transition.new_state, "xmr lock transaction sent");
+        assert_eq!(transition.btc_amount_sat, Some(500_000));
+        assert_eq!(transition.xmr_amount_piconero, Some(10_000));
+        assert_eq!(
+            transition.btc_lock_txid,
+            Some(state3.tx_lock.txid().to_string())
+        );
+        assert_eq!(transition.xmr_lock_txid.as_deref(), Some("xmr-lock"));
+    }
+
+    #[tokio::test]
+    async fn transition_into_a_final_state_has_the_amounts_of_the_old_state() {
+        let state3 = Box::new(
+            alice_state3(
+                bitcoin::Amount::from_sat(500_000),
+                crate::monero::Amount::from_piconero(10_000),
+            )
+            .await,
+        );
+
+        let transition = StateTransition::new(
+            Uuid::new_v4(),
+            &AliceState::BtcRedeemTransactionPublished {
+                transfer_proof: transfer_proof("xmr-lock"),
+                state3,
+            },
+            &AliceState::BtcRedeemed,
+        );
+
+        assert_eq!(transition.new_state, "btc is redeemed");
+        assert_eq!(transition.btc_amount_sat, Some(500_000));
+        assert_eq!(transition.xmr_amount_piconero, Some(10_000));
+        assert_eq!(transition.xmr_lock_txid, None);
+    }
+
+    #[test]
+    fn transition_without_a_swap_has_no_amounts() {
+        let transition = StateTransition::new(
+            Uuid::new_v4(),
+            &AliceState::SafelyAborted,
+            &AliceState::XmrRefunded,
+        );
+
+        assert_eq!(transition.old_state, "safely aborted");
+        assert_eq!(transition.new_state, "xmr is refunded");
+        assert_eq!(transition.btc_amount_sat, None);
+        assert_eq!(transition.xmr_amount_piconero, None);
+        assert_eq!(transition.btc_lock_txid, None);
+        assert_eq!(transition.xmr_lock_txid, None);
+    }
+
+    #[tokio::test]
+    async fn command_gets_the_payload_on_stdin_and_the_states_in_the_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let payload_file = dir.path().join("payload.json");
+        let env_file = dir.path().join("env");
+
+        let transition = StateTransition {
+            swap_id: Uuid::new_v4(),
+            old_state: "btc is locked".to_string(),
+            new_state: "xmr lock transaction sent".to_string(),
+            btc_amount_sat: Some(500_000),
+            xmr_amount_piconero: Some(10_000),
+            btc_lock_txid: Some("btc-lock".to_string()),
+            xmr_lock_txid: Some("xmr-lock".to_string()),
+        };
+        let command = [
+            "sh",
+            "-c",
+            r#"cat > "$1" && printf '%s\n%s\n%s\n' "$SWAP_ID" "$SWAP_OLD_STATE" "$SWAP_NEW_STATE" > "$2""#,
+            "hook",
+            payload_file.to_str().unwrap(),
+            env_file.to_str().unwrap(),
+        ]
+        .map(String::from);
+
+        run_command(&command, &transition).await.unwrap();
+
+        let payload: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&payload_file).unwrap()).unwrap();
+        assert_eq!(payload, serde_json::to_value(&transition).unwrap());
+        assert_eq!(payload["btc_amount_sat"], 500_000);
+        assert_eq!(payload["xmr_lock_txid"], "xmr-lock");
+
+        let env = std::fs::read_to_string(&env_file).unwrap();
+        assert_eq!(
+            env,
+            format!(
+                "{}\nbtc is locked\nxmr lock transaction sent\n",
+                transition.swap_id
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_command_is_an_error() {
+        let transition = StateTransition::new(
+            Uuid::new_v4(),
+            &AliceState::SafelyAborted,
+            &AliceState::SafelyAborted,
+        );
+        let command = ["sh", "-c", "exit 3"].map(String::from);
+
+        assert!(run_command(&command, &transition).await.is_err());
+        assert!(run_command(&[], &transition).await.is_err());
+    }
+}
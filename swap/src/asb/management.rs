@@ -0,0 +1,517 @@
+use crate::asb::config::ManagementApi;
+use crate::asb::event_loop::unreserved_monero_balance;
+use crate::asb::hooks::state3;
+use crate::asb::{LatestRate, RendezvousStatus};
+use crate::bitcoin::bitcoin_address;
+use crate::monero::monero_address;
+use crate::protocol::alice::ReservesMonero;
+use crate::protocol::{Database, State};
+use crate::{bitcoin, env, monero};
+use ::bitcoin::address::NetworkUnchecked;
+use anyhow::{ensure, Context, Result};
+use axum::body::Body;
+use axum::extract::{FromRef, Request, State as AxumState};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Whether the asb accepts new swaps. Shared between the event loop, which turns away quote
+/// and swap requests while trading is paused, and the management API, which flips it.
+///
+/// Swaps that are already running are not affected by a pause.
+#[derive(Debug, Clone, Default)]
+pub struct TradingSwitch {
+    paused: Arc<AtomicBool>,
+}
+
+impl TradingSwitch {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+impl<LR> FromRef<ApiState<LR>> for TradingSwitch {
+    fn from_ref(state: &ApiState<LR>) -> Self {
+        state.trading.clone()
+    }
+}
+
+/// Everything the handlers of the management API need.
+#[derive(Clone)]
+pub struct ApiState<LR> {
+    pub env_config: env::Config,
+    pub bitcoin_wallet: Arc<bitcoin::Wallet>,
+    pub monero_wallet: Arc<monero::Wallets>,
+    pub db: Arc<dyn Database + Send + Sync>,
+    pub latest_rate: LR,
+    pub min_buy: bitcoin::Amount,
+    pub max_buy: bitcoin::Amount,
+    pub ask_spread: Decimal,
+    pub trading: TradingSwitch,
+    /// `None` if no rendezvous points are configured.
+    pub rendezvous: Option<RendezvousStatus>,
+}
+
+/// Start the management API in the background.
+pub async fn serve<LR>(config: ManagementApi, state: ApiState<LR>) -> Result<()>
+where
+    LR: LatestRate + Clone + Send + Sync + 'static,
+{
+    ensure!(
+        !config.api_token.is_empty(),
+        "The API token must not be empty"
+    );
+
+    let listener = TcpListener::bind(config.listen)
+        .await
+        .with_context(|| format!("Failed to bind management API to {}", config.listen))?;
+    let app = router(config.api_token).with_state(state);
+
+    tracing::info!(address = %config.listen, "Management API listening");
+
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, app).await {
+            tracing::error!("Management API stopped: {:#}", error);
+        }
+    });
+
+    Ok(())
+}
+
+/// Endpoints for operators to run the asb headless and to feed their dashboards.
+///
+/// Every request has to carry `Authorization: Bearer <api_token>`.
+fn router<LR>(api_token: String) -> Router<ApiState<LR>>
+where
+    LR: LatestRate + Clone + Send + Sync + 'static,
+{
+    let routes = Router::new()
+        .route("/api/balance", get(balance_handler::<LR>))
+        .route("/api/swaps", get(active_swaps_handler::<LR>))
+        .route("/api/quote", get(quote_handler::<LR>))
+        .route("/api/rendezvous", get(rendezvous_handler::<LR>))
+        .merge(trading_routes())
+        .route(
+            "/api/withdraw/bitcoin",
+            post(withdraw_bitcoin_handler::<LR>),
+        )
+        .route("/api/withdraw/monero", post(withdraw_monero_handler::<LR>));
+
+    with_api_token(routes, api_token)
+}
+
+/// Pausing and resuming only needs the [`TradingSwitch`].
+fn trading_routes<S>() -> Router<S>
+where
+    TradingSwitch: FromRef<S>,
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/api/trading/pause", post(pause_trading_handler))
+        .route("/api/trading/resume", post(resume_trading_handler))
+}
+
+fn with_api_token<S>(routes: Router<S>, api_token: String) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    routes.route_layer(middleware::from_fn_with_state(
+        Arc::<str>::from(api_token),
+        require_api_token,
+    ))
+}
+
+pub(crate) async fn require_api_token(
+    AxumState(api_token): AxumState<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), api_token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "Missing or invalid API token" }),
+        ),
+    }
+}
+
+/// Compare without returning early so the response time doesn't leak the token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+fn error_response(status: StatusCode, context: &str, error: anyhow::Error) -> Response {
+    tracing::warn!("{}: {:#}", context, error);
+    json_response(
+        status,
+        json!({ "error": format!("{}: {:#}", context, error) }),
+    )
+}
+
+async fn balance_handler<LR>(AxumState(state): AxumState<ApiState<LR>>) -> Response {
+    let bitcoin_balance = match state.bitcoin_wallet.balance().await {
+        Ok(balance) => balance,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get Bitcoin balance",
+                e,
+            )
+        }
+    };
+
+    let monero_wallet = state.monero_wallet.main_wallet().await;
+    let monero_total = monero_wallet.total_balance().await;
+    let monero_unlocked = monero_wallet.unlocked_balance().await;
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "bitcoin_sat": bitcoin_balance.to_sat(),
+            "monero_total_piconero": monero_total.as_pico(),
+            "monero_unlocked_piconero": monero_unlocked.as_pico(),
+        }),
+    )
+}
+
+async fn active_swaps_handler<LR>(AxumState(state): AxumState<ApiState<LR>>) -> Response {
+    match active_swaps(&state.db).await {
+        Ok(swaps) => json_response(StatusCode::OK, json!({ "swaps": swaps })),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to list swaps", e),
+    }
+}
+
+async fn active_swaps(db: &Arc<dyn Database + Send + Sync>) -> Result<Vec<serde_json::Value>> {
+    let mut swaps = Vec::new();
+
+    for (swap_id, state) in db.all().await? {
+        if state.swap_finished() {
+            continue;
+        }
+
+        let State::Alice(state) = state else {
+            continue;
+        };
+        let peer_id = db.get_peer_id(swap_id).await.ok();
+        let state3 = state3(&state);
+
+        swaps.push(json!({
+            "swap_id": swap_id,
+            "peer_id": peer_id.map(|peer_id| peer_id.to_string()),
+            "state": state.to_string(),
+            "btc_amount_sat": state3.map(|state3| state3.btc.to_sat()),
+            "xmr_amount_piconero": state3.map(|state3| state3.xmr.as_piconero()),
+            "reserved_xmr_piconero": state.reserved_monero().as_piconero(),
+        }));
+    }
+
+    Ok(swaps)
+}
+
+async fn quote_handler<LR>(AxumState(state): AxumState<ApiState<LR>>) -> Response
+where
+    LR: LatestRate,
+{
+    let mut latest_rate = state.latest_rate;
+    let ask_price = latest_rate
+        .latest_rate()
+        .map_err(|e| e.to_string())
+        .and_then(|rate| rate.ask().map_err(|e| format!("{:#}", e)));
+
+    let (ask_price_sat, rate_error) = match ask_price {
+        Ok(ask) => (Some(ask.to_sat()), None),
+        Err(error) => (None, Some(error)),
+    };
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "min_buy_sat": state.min_buy.to_sat(),
+            "max_buy_sat": state.max_buy.to_sat(),
+            "ask_spread": state.ask_spread,
+            "ask_price_sat": ask_price_sat,
+            "rate_error": rate_error,
+            "trading_paused": state.trading.is_paused(),
+        }),
+    )
+}
+
+async fn rendezvous_handler<LR>(AxumState(state): AxumState<ApiState<LR>>) -> Response {
+    let reports = state
+        .rendezvous
+        .map(|rendezvous| rendezvous.reports())
+        .unwrap_or_default();
+    let nodes: Vec<_> = reports
+        .iter()
+        .map(|report| {
+            json!({
+                "peer_id": report.peer_id,
+                "address": report.address,
+                "registered": report.is_registered(),
+                "registration_expires_at": report.registration_expires_at,
+                "consecutive_failures": report.consecutive_failures,
+                "last_error": report.last_error,
+            })
+        })
+        .collect();
+
+    json_response(StatusCode::OK, json!({ "rendezvous_nodes": nodes }))
+}
+
+async fn pause_trading_handler(AxumState(trading): AxumState<TradingSwitch>) -> Response {
+    trading.pause();
+    tracing::info!("Trading paused via the management API");
+
+    json_response(StatusCode::OK, json!({ "trading_paused": true }))
+}
+
+async fn resume_trading_handler(AxumState(trading): AxumState<TradingSwitch>) -> Response {
+    trading.resume();
+    tracing::info!("Trading resumed via the management API");
+
+    json_response(StatusCode::OK, json!({ "trading_paused": false }))
+}
+
+#[derive(Debug, Deserialize)]
+struct WithdrawBitcoinRequest {
+    address: bitcoin::Address<NetworkUnchecked>,
+    /// Sweep the whole balance if not set.
+    amount_sat: Option<u64>,
+}
+
+async fn withdraw_bitcoin_handler<LR>(
+    AxumState(state): AxumState<ApiState<LR>>,
+    Json(request): Json<WithdrawBitcoinRequest>,
+) -> Response {
+    let address = match bitcoin_address::validate_network(
+        request.address,
+        state.env_config.bitcoin_network,
+    ) {
+        Ok(address) => address,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, "Invalid address", e),
+    };
+
+    match withdraw_bitcoin(
+        &state.bitcoin_wallet,
+        address,
+        request.amount_sat.map(bitcoin::Amount::from_sat),
+    )
+    .await
+    {
+        Ok(txid) => json_response(StatusCode::OK, json!({ "txid": txid.to_string() })),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to withdraw Bitcoin",
+            e,
+        ),
+    }
+}
+
+async fn withdraw_bitcoin(
+    bitcoin_wallet: &bitcoin::Wallet,
+    address: bitcoin::Address,
+    amount: Option<bitcoin::Amount>,
+) -> Result<bitcoin::Txid> {
+    let withdraw_tx_unsigned = match amount {
+        Some(amount) => {
+            bitcoin_wallet
+                .send_to_address_dynamic_fee(
+                    address,
+                    amount,
+                    None,
+                    bitcoin::wallet::CoinControl::default(),
+                    None,
+                )
+                .await?
+        }
+        None => bitcoin_wallet.sweep_to(address).await?,
+    };
+
+    let signed_tx = bitcoin_wallet
+        .sign_and_finalize(withdraw_tx_unsigned)
+        .await?;
+    let (txid, _) = bitcoin_wallet.broadcast(signed_tx, "withdraw").await?;
+
+    Ok(txid)
+}
+
+#[derive(Debug, Deserialize)]
+struct WithdrawMoneroRequest {
+    address: String,
+    /// Sweep the whole balance if not set. Only possible while no swap reserves Monero.
+    amount_piconero: Option<u64>,
+}
+
+async fn withdraw_monero_handler<LR>(
+    AxumState(state): AxumState<ApiState<LR>>,
+    Json(request): Json<WithdrawMoneroRequest>,
+) -> Response {
+    let address = match monero_address::parse(&request.address)
+        .and_then(|address| monero_address::validate(address, state.env_config.monero_network))
+    {
+        Ok(address) => address,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, "Invalid address", e),
+    };
+
+    match withdraw_monero(
+        &state,
+        address,
+        request.amount_piconero.map(monero::Amount::from_piconero),
+    )
+    .await
+    {
+        Ok(txids) => json_response(StatusCode::OK, json!({ "txids": txids })),
+        Err(e) => error_response(StatusCode::CONFLICT, "Failed to withdraw Monero", e),
+    }
+}
+
+/// Withdraw Monero without touching the funds we reserved for the swaps that are running.
+async fn withdraw_monero<LR>(
+    state: &ApiState<LR>,
+    address: monero::Address,
+    amount: Option<monero::Amount>,
+) -> Result<Vec<String>> {
+    let reserved: Vec<monero::Amount> = state
+        .db
+        .all()
+        .await?
+        .into_iter()
+        .filter_map(|(_, state)| match state {
+            State::Alice(state) => Some(state.reserved_monero()),
+            State::Bob(_) => None,
+        })
+        .collect();
+    let swaps_reserve_monero = reserved.iter().any(|amount| *amount > monero::Amount::ZERO);
+
+    let wallet = state.monero_wallet.main_wallet().await;
+    let unreserved =
+        unreserved_monero_balance(wallet.unlocked_balance().await.into(), reserved.into_iter());
+
+    let receipts = match amount {
+        Some(amount) => {
+            ensure!(
+                amount <= unreserved,
+                "Only {} are not reserved for running swaps",
+                unreserved
+            );
+            vec![wallet.transfer(&address, amount.into()).await?]
+        }
+        None => {
+            ensure!(
+                !swaps_reserve_monero,
+                "Running swaps reserve Monero, withdraw an amount of at most {} instead",
+                unreserved
+            );
+            wallet.sweep(&address).await?
+        }
+    };
+
+    Ok(receipts.into_iter().map(|receipt| receipt.txid).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    const API_TOKEN: &str = "0123456789abcdef";
+
+    async fn post_with_token(
+        trading: &TradingSwitch,
+        path: &str,
+        authorization: Option<&str>,
+    ) -> StatusCode {
+        let app =
+            with_api_token(trading_routes(), API_TOKEN.to_string()).with_state(trading.clone());
+
+        let mut request = axum::http::Request::builder().method("POST").uri(path);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn request_without_token_is_rejected() {
+        let trading = TradingSwitch::default();
+
+        let status = post_with_token(&trading, "/api/trading/pause", None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(!trading.is_paused());
+    }
+
+    #[tokio::test]
+    async fn request_with_wrong_token_is_rejected() {
+        let trading = TradingSwitch::default();
+
+        for authorization in [
+            "Bearer wrong",
+            "Bearer 0123456789abcde",
+            "Bearer 0123456789abcdef0",
+            "Bearer ",
+            API_TOKEN,
+            "Basic 0123456789abcdef",
+        ] {
+            let status = post_with_token(&trading, "/api/trading/pause", Some(authorization)).await;
+
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", authorization);
+        }
+        assert!(!trading.is_paused());
+    }
+
+    #[tokio::test]
+    async fn request_with_correct_token_pauses_and_resumes_trading() {
+        let trading = TradingSwitch::default();
+        let authorization = format!("Bearer {}", API_TOKEN);
+
+        let status = post_with_token(&trading, "/api/trading/pause", Some(&authorization)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(trading.is_paused());
+
+        // Pausing twice keeps trading paused
+        let status = post_with_token(&trading, "/api/trading/pause", Some(&authorization)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(trading.is_paused());
+
+        let status = post_with_token(&trading, "/api/trading/resume", Some(&authorization)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!trading.is_paused());
+    }
+}
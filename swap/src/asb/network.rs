@@ -1,4 +1,5 @@
 use crate::asb::event_loop::LatestRate;
+use crate::asb::volume_limits::VolumeReservation;
 use crate::env;
 use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
@@ -9,7 +10,7 @@ use crate::network::{
     cooperative_xmr_redeem_after_punish, encrypted_signature, quote, transfer_proof,
 };
 use crate::protocol::alice::State3;
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use futures::FutureExt;
 use libp2p::core::muxing::StreamMuxerBox;
 use libp2p::core::transport::Boxed;
@@ -43,17 +44,24 @@ pub mod transport {
     /// If you pass in a `Some(tor_client)`, the ASB will listen on an onion service and return
     /// the onion address. If it fails to listen on the onion address, it will only use tor for
     /// dialing and not listening.
+    ///
+    /// With `onion_only`, the transport has no clearnet fallback and dials IP addresses through
+    /// Tor too, so neither takers nor rendezvous nodes learn the IP address of the ASB.
     pub fn new(
         identity: &identity::Keypair,
         maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
         register_hidden_service: bool,
         num_intro_points: u8,
+        onion_only: bool,
     ) -> Result<OnionTransportWithAddresses> {
         let (maybe_tor_transport, onion_addresses) = if let Some(tor_client) = maybe_tor_client {
-            let mut tor_transport = libp2p_community_tor::TorTransport::from_client(
-                tor_client,
-                AddressConversion::DnsOnly,
-            );
+            let address_conversion = if onion_only {
+                AddressConversion::IpAndDns
+            } else {
+                AddressConversion::DnsOnly
+            };
+            let mut tor_transport =
+                libp2p_community_tor::TorTransport::from_client(tor_client, address_conversion);
 
             let addresses = if register_hidden_service {
                 let onion_service_config = OnionServiceConfigBuilder::default()
@@ -84,11 +92,27 @@ pub mod transport {
                 vec![]
             };
 
-            (OptionalTransport::some(tor_transport), addresses)
+            (Some(tor_transport), addresses)
         } else {
-            (OptionalTransport::none(), vec![])
+            (None, vec![])
         };
 
+        if onion_only {
+            let Some(tor_transport) = maybe_tor_transport else {
+                bail!("Tor is required to only be reachable as an onion service");
+            };
+            if onion_addresses.is_empty() {
+                bail!("Only being reachable as an onion service requires the onion service to be set up");
+            }
+
+            return Ok((
+                authenticate_and_multiplex(tor_transport.boxed(), identity)?,
+                onion_addresses,
+            ));
+        }
+
+        let maybe_tor_transport =
+            maybe_tor_transport.map_or_else(OptionalTransport::none, OptionalTransport::some);
         let tcp = maybe_tor_transport
             .or_transport(tcp::tokio::Transport::new(tcp::Config::new().nodelay(true)));
         let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
@@ -113,12 +137,14 @@ pub mod behaviour {
     #[derive(Debug)]
     pub enum OutEvent {
         SwapSetupInitiated {
+            peer_id: PeerId,
             send_wallet_snapshot: bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>,
         },
         SwapSetupCompleted {
             peer_id: PeerId,
             swap_id: Uuid,
             state3: State3,
+            volume_reservation: VolumeReservation,
         },
         SwapDeclined {
             peer: PeerId,
@@ -269,6 +295,8 @@ pub mod behaviour {
 
 pub mod rendezvous {
     use super::*;
+    use backoff::backoff::Backoff;
+    use backoff::ExponentialBackoff;
     use libp2p::identity;
     use libp2p::rendezvous::client::RegisterError;
     use libp2p::swarm::dial_opts::DialOpts;
@@ -276,9 +304,17 @@ pub mod rendezvous {
         ConnectionDenied, ConnectionId, FromSwarm, THandler, THandlerInEvent, THandlerOutEvent,
         ToSwarm,
     };
+    use serde::Serialize;
     use std::collections::VecDeque;
     use std::pin::Pin;
     use std::task::Context;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio::sync::watch;
+
+    /// How long we wait before retrying after the first failure to register. Every consecutive
+    /// failure doubles this, up to [`MAX_RETRY_INTERVAL`].
+    const INITIAL_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+    const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
 
     #[derive(Clone, PartialEq)]
     enum ConnectionStatus {
@@ -293,12 +329,17 @@ pub mod rendezvous {
         Registered {
             re_register_in: Pin<Box<tokio::time::Sleep>>,
         },
+        /// Registering failed, we try again once the timer fires.
+        Backoff {
+            retry_in: Pin<Box<tokio::time::Sleep>>,
+        },
     }
 
     pub struct Behaviour {
         inner: libp2p::rendezvous::client::Behaviour,
         rendezvous_nodes: Vec<RendezvousNode>,
         to_dial: VecDeque<PeerId>,
+        status: watch::Sender<Vec<RegistrationReport>>,
     }
 
     /// A node running the rendezvous server protocol.
@@ -309,6 +350,44 @@ pub mod rendezvous {
         registration_status: RegistrationStatus,
         pub registration_ttl: Option<u64>,
         pub namespace: XmrBtcNamespace,
+        backoff: ExponentialBackoff,
+        registered_until: Option<SystemTime>,
+        consecutive_failures: u32,
+        last_error: Option<String>,
+    }
+
+    /// How the registration with a rendezvous node is doing, as reported by the management API.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+    pub struct RegistrationReport {
+        pub peer_id: String,
+        pub address: String,
+        /// Unix timestamp at which our latest registration expires.
+        pub registration_expires_at: Option<u64>,
+        pub consecutive_failures: u32,
+        pub last_error: Option<String>,
+    }
+
+    impl RegistrationReport {
+        /// Takers can discover us through the node unless our latest registration expired.
+        pub fn is_registered(&self) -> bool {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            self.registration_expires_at
+                .is_some_and(|expires_at| expires_at > now)
+        }
+    }
+
+    /// A handle to the latest [`RegistrationReport`]s of the rendezvous behaviour.
+    #[derive(Debug, Clone)]
+    pub struct RendezvousStatus(watch::Receiver<Vec<RegistrationReport>>);
+
+    impl RendezvousStatus {
+        pub fn reports(&self) -> Vec<RegistrationReport> {
+            self.0.borrow().clone()
+        }
     }
 
     impl RendezvousNode {
@@ -325,6 +404,16 @@ pub mod rendezvous {
                 peer_id,
                 registration_status: RegistrationStatus::RegisterOnNextConnection,
                 registration_ttl,
+                backoff: ExponentialBackoff {
+                    initial_interval: INITIAL_RETRY_INTERVAL,
+                    current_interval: INITIAL_RETRY_INTERVAL,
+                    max_interval: MAX_RETRY_INTERVAL,
+                    max_elapsed_time: None, // We never stop trying to register
+                    ..ExponentialBackoff::default()
+                },
+                registered_until: None,
+                consecutive_failures: 0,
+                last_error: None,
             }
         }
 
@@ -335,14 +424,65 @@ pub mod rendezvous {
         fn set_registration(&mut self, status: RegistrationStatus) {
             self.registration_status = status;
         }
+
+        fn registered(&mut self, ttl: u64) {
+            let ttl = Duration::from_secs(ttl);
+
+            self.backoff.reset();
+            self.consecutive_failures = 0;
+            self.last_error = None;
+            self.registered_until = Some(SystemTime::now() + ttl);
+
+            // Re-register well before the registration expires
+            let re_register_in = Box::pin(tokio::time::sleep(ttl / 2));
+            self.set_registration(RegistrationStatus::Registered { re_register_in });
+        }
+
+        fn registration_failed(&mut self, error: String) {
+            let retry_in = self.backoff.next_backoff().unwrap_or(MAX_RETRY_INTERVAL);
+
+            self.consecutive_failures += 1;
+            tracing::warn!(
+                rendezvous_node = %self.peer_id,
+                consecutive_failures = self.consecutive_failures,
+                retry_in_secs = retry_in.as_secs(),
+                %error,
+                "Failed to register with rendezvous node"
+            );
+            self.last_error = Some(error);
+
+            let retry_in = Box::pin(tokio::time::sleep(retry_in));
+            self.set_registration(RegistrationStatus::Backoff { retry_in });
+        }
+
+        fn report(&self) -> RegistrationReport {
+            RegistrationReport {
+                peer_id: self.peer_id.to_string(),
+                address: self.address.to_string(),
+                registration_expires_at: self.registered_until.map(|until| {
+                    until
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs()
+                }),
+                consecutive_failures: self.consecutive_failures,
+                last_error: self.last_error.clone(),
+            }
+        }
     }
 
     impl Behaviour {
         pub fn new(identity: identity::Keypair, rendezvous_nodes: Vec<RendezvousNode>) -> Self {
+            let reports = rendezvous_nodes
+                .iter()
+                .map(RendezvousNode::report)
+                .collect();
+
             Self {
                 inner: libp2p::rendezvous::client::Behaviour::new(identity),
                 rendezvous_nodes,
                 to_dial: VecDeque::new(),
+                status: watch::channel(reports).0,
             }
         }
 
@@ -355,6 +495,34 @@ pub mod rendezvous {
                 (node.namespace.into(), node.peer_id, node.registration_ttl);
             self.inner.register(namespace, peer_id, ttl)
         }
+
+        /// Registers the rendezvous node at the given index and backs off if that fails.
+        fn register_or_back_off(&mut self, node_index: usize) {
+            if let Err(error) = self.register(node_index) {
+                self.rendezvous_nodes[node_index].registration_failed(error.to_string());
+            }
+        }
+
+        /// Lets others follow how the registrations are doing, e.g. the management API.
+        pub fn status(&self) -> RendezvousStatus {
+            RendezvousStatus(self.status.subscribe())
+        }
+
+        fn publish_status(&self) {
+            let reports: Vec<_> = self
+                .rendezvous_nodes
+                .iter()
+                .map(RendezvousNode::report)
+                .collect();
+
+            self.status.send_if_modified(|current| {
+                if *current == reports {
+                    return false;
+                }
+                *current = reports;
+                true
+            });
+        }
     }
 
     impl NetworkBehaviour for Behaviour {
@@ -424,12 +592,7 @@ pub mod rendezvous {
                         if let RegistrationStatus::RegisterOnNextConnection =
                             rendezvous_node.registration_status
                         {
-                            let _ = self.register(index).inspect_err(|err| {
-                                tracing::error!(
-                                        error=%err,
-                                        rendezvous_node=%peer_id,
-                                        "Failed to register with rendezvous node");
-                            });
+                            self.register_or_back_off(index);
                         }
                     }
                 }
@@ -441,6 +604,13 @@ pub mod rendezvous {
                         .find(|node| node.peer_id == peer.peer_id)
                     {
                         node.set_connection(ConnectionStatus::Disconnected);
+
+                        // We won't hear back about a registration that was in flight
+                        if let RegistrationStatus::Pending = node.registration_status {
+                            node.registration_failed(
+                                "Connection closed before the registration completed".to_string(),
+                            );
+                        }
                     }
                 }
                 FromSwarm::DialFailure(peer) => {
@@ -452,12 +622,20 @@ pub mod rendezvous {
                             .find(|node| node.peer_id == peer_id)
                         {
                             node.set_connection(ConnectionStatus::Disconnected);
+
+                            // Without a backoff we would redial right away, over and over
+                            if let RegistrationStatus::RegisterOnNextConnection =
+                                node.registration_status
+                            {
+                                node.registration_failed(format!("Failed to dial: {}", peer.error));
+                            }
                         }
                     }
                 }
                 _ => {}
             }
             self.inner.on_swarm_event(event);
+            self.publish_status();
         }
 
         fn on_connection_handler_event(
@@ -501,57 +679,78 @@ pub mod rendezvous {
                         }
                         ConnectionStatus::Dialling => {}
                         ConnectionStatus::Connected => {
-                            let _ = self.register(i);
+                            self.register_or_back_off(i);
                         }
                     },
                     RegistrationStatus::Registered { re_register_in } => {
                         if let Poll::Ready(()) = re_register_in.poll_unpin(cx) {
                             match connection_status {
                                 ConnectionStatus::Connected => {
-                                    let _ = self.register(i).inspect_err(|err| {
-                                        tracing::error!(
-                                                error=%err,
-                                                rendezvous_node=%self.rendezvous_nodes[i].peer_id,
-                                                "Failed to register with rendezvous node");
-                                    });
+                                    self.register_or_back_off(i);
                                 }
                                 ConnectionStatus::Disconnected => {
                                     self.rendezvous_nodes[i].set_registration(
                                         RegistrationStatus::RegisterOnNextConnection,
                                     );
+                                    self.rendezvous_nodes[i]
+                                        .set_connection(ConnectionStatus::Dialling);
                                     self.to_dial.push_back(self.rendezvous_nodes[i].peer_id);
                                 }
                                 ConnectionStatus::Dialling => {}
                             }
                         }
                     }
+                    RegistrationStatus::Backoff { retry_in } => {
+                        if let Poll::Ready(()) = retry_in.poll_unpin(cx) {
+                            // Dials or registers on the next poll, depending on the connection
+                            self.rendezvous_nodes[i]
+                                .set_registration(RegistrationStatus::RegisterOnNextConnection);
+                            cx.waker().wake_by_ref();
+                        }
+                    }
                     RegistrationStatus::Pending => {}
                 }
             }
 
             let inner_poll = self.inner.poll(cx);
 
-            // reset the timer for the specific rendezvous node if we successfully registered
-            if let Poll::Ready(ToSwarm::GenerateEvent(
-                libp2p::rendezvous::client::Event::Registered {
-                    ttl,
-                    rendezvous_node,
-                    ..
-                },
-            )) = &inner_poll
-            {
-                if let Some(i) = self
-                    .rendezvous_nodes
-                    .iter()
-                    .position(|n| &n.peer_id == rendezvous_node)
-                {
-                    let half_of_ttl = Duration::from_secs(*ttl) / 2;
-                    let re_register_in = Box::pin(tokio::time::sleep(half_of_ttl));
-                    let status = RegistrationStatus::Registered { re_register_in };
-                    self.rendezvous_nodes[i].set_registration(status);
+            // Keep track of how the registration went for the specific rendezvous node
+            match &inner_poll {
+                Poll::Ready(ToSwarm::GenerateEvent(
+                    libp2p::rendezvous::client::Event::Registered {
+                        ttl,
+                        rendezvous_node,
+                        ..
+                    },
+                )) => {
+                    if let Some(node) = self
+                        .rendezvous_nodes
+                        .iter_mut()
+                        .find(|n| &n.peer_id == rendezvous_node)
+                    {
+                        node.registered(*ttl);
+                    }
                 }
+                Poll::Ready(ToSwarm::GenerateEvent(
+                    libp2p::rendezvous::client::Event::RegisterFailed {
+                        rendezvous_node,
+                        error,
+                        ..
+                    },
+                )) => {
+                    if let Some(node) = self
+                        .rendezvous_nodes
+                        .iter_mut()
+                        .find(|n| &n.peer_id == rendezvous_node)
+                    {
+                        node.registration_failed(format!("{:?}", error));
+                    }
+                }
+                _ => {}
             }
 
+            self.publish_status();
+
             inner_poll
         }
     }
@@ -737,5 +936,35 @@ pub mod rendezvous {
                 .unwrap()
                 .unwrap();
         }
+
+        #[tokio::test]
+        async fn failures_are_counted_until_the_next_registration() {
+            let mut node = RendezvousNode::new(
+                &"/memory/1".parse().unwrap(),
+                PeerId::random(),
+                XmrBtcNamespace::Testnet,
+                None,
+            );
+            assert!(!node.report().is_registered());
+
+            node.registration_failed("first".to_string());
+            node.registration_failed("second".to_string());
+
+            let report = node.report();
+            assert_eq!(report.consecutive_failures, 2);
+            assert_eq!(report.last_error.as_deref(), Some("second"));
+            assert!(!report.is_registered());
+            assert!(matches!(
+                node.registration_status,
+                RegistrationStatus::Backoff { .. }
+            ));
+
+            node.registered(7200);
+
+            let report = node.report();
+            assert_eq!(report.consecutive_failures, 0);
+            assert_eq!(report.last_error, None);
+            assert!(report.is_registered());
+        }
     }
 }
@@ -0,0 +1,253 @@
+use crate::asb::config::{Maker, RateProviderConfig};
+use crate::{bitcoin, kraken};
+use anyhow::{bail, ensure, Context, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use url::Url;
+
+/// How often we ask providers that don't push updates to us for the price.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// We stop quoting if a polled provider hasn't given us a price for this long. Quoting on an
+/// outdated price is worse than not quoting at all.
+const MAX_PRICE_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// A market the maker takes the price of XMR from. The spread of the maker is applied on top
+/// of the price, see [`crate::asb::MarketRate`].
+pub trait RateProvider: Debug + Send + Sync {
+    /// The latest asking price for 1 XMR.
+    fn latest_ask(&self) -> Result<bitcoin::Amount, Error>;
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Rate is not yet available")]
+    NotYetAvailable,
+    #[error("The latest rate is older than {} seconds", MAX_PRICE_AGE.as_secs())]
+    Outdated,
+    #[error(transparent)]
+    Kraken(#[from] kraken::Error),
+}
+
+/// Connect to the provider selected in the config. Providers that have to be polled are
+/// polled in the background from now on.
+pub fn connect(config: &Maker) -> Result<Arc<dyn RateProvider>> {
+    let provider: Arc<dyn RateProvider> = match &config.rate_provider {
+        RateProviderConfig::Kraken => {
+            Arc::new(Kraken(kraken::connect(config.price_ticker_ws_url.clone())?))
+        }
+        RateProviderConfig::Binance { url } => {
+            let client = reqwest::Client::new();
+            let url = url.clone();
+
+            Arc::new(Polled::spawn("Binance", move || {
+                fetch_binance(client.clone(), url.clone())
+            }))
+        }
+        RateProviderConfig::File { path } => {
+            let path = path.clone();
+
+            Arc::new(Polled::spawn("file", move || read_index_file(path.clone())))
+        }
+        RateProviderConfig::Http { url } => {
+            let client = reqwest::Client::new();
+            let url = url.clone();
+
+            Arc::new(Polled::spawn("HTTP endpoint", move || {
+                fetch_http(client.clone(), url.clone())
+            }))
+        }
+    };
+
+    Ok(provider)
+}
+
+/// Kraken pushes price updates to us over a websocket.
+#[derive(Debug)]
+struct Kraken(kraken::PriceUpdates);
+
+impl RateProvider for Kraken {
+    fn latest_ask(&self) -> Result<bitcoin::Amount, Error> {
+        let update = self.0.latest_update()?;
+
+        Ok(update.ask)
+    }
+}
+
+/// A provider we ask for the price every [`POLL_INTERVAL`].
+#[derive(Debug)]
+struct Polled {
+    latest: watch::Receiver<Option<(Instant, bitcoin::Amount)>>,
+}
+
+impl Polled {
+    fn spawn<F, Fut>(name: &'static str, fetch: F) -> Self
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<bitcoin::Amount>> + Send,
+    {
+        let (sender, latest) = watch::channel(None);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                match fetch().await {
+                    Ok(ask) => {
+                        if sender.send(Some((Instant::now(), ask))).is_err() {
+                            return;
+                        }
+                    }
+                    Err(error) => {
+                        tracing::warn!(provider = name, "Failed to fetch the rate: {:#}", error);
+                    }
+                }
+            }
+        });
+
+        Self { latest }
+    }
+}
+
+impl RateProvider for Polled {
+    fn latest_ask(&self) -> Result<bitcoin::Amount, Error> {
+        match *self.latest.borrow() {
+            None => Err(Error::NotYetAvailable),
+            Some((fetched_at, _)) if fetched_at.elapsed() > MAX_PRICE_AGE => Err(Error::Outdated),
+            Some((_, ask)) => Ok(ask),
+        }
+    }
+}
+
+async fn fetch_binance(client: reqwest::Client, url: Url) -> Result<bitcoin::Amount> {
+    let ticker: Value = client
+        .get(url)
+        .query(&[("symbol", "XMRBTC")])
+        .timeout(POLL_INTERVAL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    price_field(&ticker, "askPrice")
+}
+
+async fn fetch_http(client: reqwest::Client, url: Url) -> Result<bitcoin::Amount> {
+    let response: Value = client
+        .get(url)
+        .timeout(POLL_INTERVAL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    price_field(&response, "ask")
+}
+
+/// The file a maker can use to set the price themselves, e.g. from an index they follow.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IndexFile {
+    /// The price of 1 XMR in BTC.
+    index: Decimal,
+    /// Added on top of the index, `0.01` is 1%.
+    #[serde(default)]
+    spread: Decimal,
+}
+
+async fn read_index_file(path: PathBuf) -> Result<bitcoin::Amount> {
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    parse_index_file(&content, &path)
+}
+
+fn parse_index_file(content: &str, path: &Path) -> Result<bitcoin::Amount> {
+    let file: IndexFile =
+        toml::from_str(content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    to_amount(file.index * (Decimal::ONE + file.spread))
+}
+
+/// Prices are strings on most exchanges to not lose precision, but we accept numbers too.
+fn price_field(response: &Value, field: &str) -> Result<bitcoin::Amount> {
+    let price = match response.get(field) {
+        Some(Value::String(price)) => Decimal::from_str(price)?,
+        Some(Value::Number(price)) => Decimal::from_str(&price.to_string())?,
+        _ => bail!("Response has no {} field", field),
+    };
+
+    to_amount(price)
+}
+
+fn to_amount(price_in_btc: Decimal) -> Result<bitcoin::Amount> {
+    ensure!(
+        price_in_btc > Decimal::ZERO,
+        "Price must be positive, got {}",
+        price_in_btc
+    );
+
+    let sats = (price_in_btc * Decimal::from(bitcoin::Amount::ONE_BTC.to_sat()))
+        .round()
+        .to_u64()
+        .context("Price does not fit into u64")?;
+
+    Ok(bitcoin::Amount::from_sat(sats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_binance_book_ticker() {
+        let ticker = r#"{"symbol":"XMRBTC","bidPrice":"0.00270000","bidQty":"12.30000000","askPrice":"0.00270500","askQty":"4.56000000"}"#;
+        let ticker = serde_json::from_str(ticker).unwrap();
+
+        let ask = price_field(&ticker, "askPrice").unwrap();
+
+        assert_eq!(ask, bitcoin::Amount::from_sat(270_500));
+    }
+
+    #[test]
+    fn accepts_prices_as_strings_and_numbers() {
+        let as_string = serde_json::json!({ "ask": "0.0027" });
+        let as_number = serde_json::json!({ "ask": 0.0027 });
+
+        assert_eq!(
+            price_field(&as_string, "ask").unwrap(),
+            bitcoin::Amount::from_sat(270_000)
+        );
+        assert_eq!(
+            price_field(&as_number, "ask").unwrap(),
+            bitcoin::Amount::from_sat(270_000)
+        );
+        assert!(price_field(&serde_json::json!({ "ask": "0" }), "ask").is_err());
+        assert!(price_field(&serde_json::json!({}), "ask").is_err());
+    }
+
+    #[test]
+    fn applies_the_spread_of_the_index_file() {
+        let path = Path::new("index.toml");
+
+        let with_spread = parse_index_file("index = 0.0027\nspread = 0.01\n", path).unwrap();
+        let without_spread = parse_index_file("index = \"0.0027\"\n", path).unwrap();
+
+        assert_eq!(with_spread, bitcoin::Amount::from_sat(272_700));
+        assert_eq!(without_spread, bitcoin::Amount::from_sat(270_000));
+    }
+}
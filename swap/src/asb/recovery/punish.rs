@@ -47,7 +47,8 @@ pub async fn punish(
 
     tracing::info!(%swap_id, "Trying to manually punish swap");
 
-    let txid = state3.punish_btc(&bitcoin_wallet).await?;
+    // The operator is waiting for the command to return, they can bump the fee themselves
+    let txid = state3.punish_btc(&bitcoin_wallet, None).await?;
 
     let state = AliceState::BtcPunished {
         state3: state3.clone(),
@@ -0,0 +1,237 @@
+use crate::asb::config::Maker;
+use crate::bitcoin;
+use crate::network::quote::BidQuote;
+use crate::protocol::Database;
+use anyhow::Result;
+use libp2p::PeerId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+/// Caps on the Bitcoin the maker accepts within the last 24 hours, configured with
+/// `max_daily_buy_btc` and `max_daily_buy_btc_per_peer`.
+///
+/// A swap counts towards the limits from the moment its swap setup passed the check: a
+/// [`VolumeReservation`] holds the amount back until the setup completed and the swap was
+/// recorded in the database. A swap that was aborted safely doesn't count anymore.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeLimits {
+    pub global: Option<bitcoin::Amount>,
+    pub per_peer: Option<bitcoin::Amount>,
+    /// The Bitcoin held back for the swap setups that are running.
+    pending: Arc<Mutex<PendingSetups>>,
+}
+
+#[derive(Debug, Default)]
+struct PendingSetups {
+    next_id: u64,
+    amounts: HashMap<u64, (PeerId, bitcoin::Amount)>,
+}
+
+impl PendingSetups {
+    fn total(&self, peer_id: Option<PeerId>) -> bitcoin::Amount {
+        self.amounts
+            .values()
+            .filter(|(peer, _)| peer_id.is_none_or(|peer_id| *peer == peer_id))
+            .map(|(_, amount)| *amount)
+            .sum()
+    }
+}
+
+/// Holds back the Bitcoin of a swap setup from the volume limits until it is dropped.
+#[derive(Debug)]
+pub struct VolumeReservation {
+    pending: Arc<Mutex<PendingSetups>>,
+    id: u64,
+}
+
+impl Drop for VolumeReservation {
+    fn drop(&mut self) {
+        self.pending
+            .lock()
+            .expect("lock not to be poisoned")
+            .amounts
+            .remove(&self.id);
+    }
+}
+
+impl VolumeLimits {
+    pub fn new(config: &Maker) -> Self {
+        Self {
+            global: config.max_daily_buy_btc,
+            per_peer: config.max_daily_buy_btc_per_peer,
+            pending: Default::default(),
+        }
+    }
+
+    /// How much more Bitcoin we accept from the peer today, `None` if no limit is configured.
+    pub async fn remaining(
+        &self,
+        db: &(dyn Database + Send + Sync),
+        peer_id: PeerId,
+    ) -> Result<Option<bitcoin::Amount>> {
+        let since = OffsetDateTime::now_utc() - time::Duration::DAY;
+        let mut remaining = None;
+
+        if let Some(global) = self.global {
+            let volume = db.get_swap_volume_since(None, since).await? + self.pending_total(None);
+            remaining = Some(global.checked_sub(volume).unwrap_or(bitcoin::Amount::ZERO));
+        }
+
+        if let Some(per_peer) = self.per_peer {
+            let volume = db.get_swap_volume_since(Some(peer_id), since).await?
+                + self.pending_total(Some(peer_id));
+            let left = per_peer
+                .checked_sub(volume)
+                .unwrap_or(bitcoin::Amount::ZERO);
+            remaining = Some(remaining.map_or(left, |remaining| remaining.min(left)));
+        }
+
+        Ok(remaining)
+    }
+
+    /// Like [`VolumeLimits::remaining`], but also holds back what the swap setup can accept of
+    /// the `btc` the peer asks for, until the returned [`VolumeReservation`] is dropped.
+    ///
+    /// The event loop handles one swap setup request after the other, so swap setups running
+    /// at the same time can't exceed the limits together.
+    pub async fn reserve(
+        &self,
+        db: &(dyn Database + Send + Sync),
+        peer_id: PeerId,
+        btc: bitcoin::Amount,
+    ) -> Result<(Option<bitcoin::Amount>, VolumeReservation)> {
+        let remaining = self.remaining(db, peer_id).await?;
+        let amount = remaining.map_or(btc, |remaining| remaining.min(btc));
+
+        let mut pending = self.pending.lock().expect("lock not to be poisoned");
+        let id = pending.next_id;
+        pending.next_id += 1;
+        pending.amounts.insert(id, (peer_id, amount));
+
+        Ok((
+            remaining,
+            VolumeReservation {
+                pending: self.pending.clone(),
+                id,
+            },
+        ))
+    }
+
+    fn pending_total(&self, peer_id: Option<PeerId>) -> bitcoin::Amount {
+        self.pending
+            .lock()
+            .expect("lock not to be poisoned")
+            .total(peer_id)
+    }
+}
+
+/// Lower the maximum of the quote to what is left of the limits. If not even the minimum is
+/// left, we hand out a zero quote, which stops Bob from starting a swap with us.
+pub fn limit_quote(quote: BidQuote, remaining: Option<bitcoin::Amount>) -> BidQuote {
+    match remaining {
+        None => quote,
+        Some(remaining) if remaining < quote.min_quantity || remaining == bitcoin::Amount::ZERO => {
+            BidQuote {
+                min_quantity: bitcoin::Amount::ZERO,
+                max_quantity: bitcoin::Amount::ZERO,
+                ..quote
+            }
+        }
+        Some(remaining) => BidQuote {
+            max_quantity: quote.max_quantity.min(remaining),
+            ..quote
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{open_db, AccessMode};
+    use uuid::Uuid;
+
+    const QUOTE: BidQuote = BidQuote {
+        price: bitcoin::Amount::from_sat(270_000),
+        min_quantity: bitcoin::Amount::from_sat(100_000),
+        max_quantity: bitcoin::Amount::from_sat(1_000_000),
+        expires_at: None,
+    };
+
+    #[test]
+    fn quote_is_unchanged_without_limits() {
+        assert_eq!(limit_quote(QUOTE, None), QUOTE);
+    }
+
+    #[test]
+    fn max_quantity_is_capped_to_the_remaining_volume() {
+        let quote = limit_quote(QUOTE, Some(bitcoin::Amount::from_sat(400_000)));
+
+        assert_eq!(quote.min_quantity, QUOTE.min_quantity);
+        assert_eq!(quote.max_quantity, bitcoin::Amount::from_sat(400_000));
+
+        assert_eq!(
+            limit_quote(QUOTE, Some(bitcoin::Amount::from_sat(5_000_000))),
+            QUOTE
+        );
+    }
+
+    #[test]
+    fn zero_quote_if_less_than_the_minimum_is_left() {
+        let quote = limit_quote(QUOTE, Some(bitcoin::Amount::from_sat(99_999)));
+
+        assert_eq!(quote.price, QUOTE.price);
+        assert_eq!(quote.min_quantity, bitcoin::Amount::ZERO);
+        assert_eq!(quote.max_quantity, bitcoin::Amount::ZERO);
+    }
+
+    #[tokio::test]
+    async fn concurrent_swap_setups_cannot_exceed_the_limits_together() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open_db(dir.path().join("sqlite"), AccessMode::ReadWrite, None, None)
+            .await
+            .unwrap();
+        let limits = VolumeLimits {
+            global: Some(bitcoin::Amount::from_sat(1_000_000)),
+            per_peer: Some(bitcoin::Amount::from_sat(700_000)),
+            pending: Default::default(),
+        };
+        let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+        let (remaining, first) = limits
+            .reserve(db.as_ref(), peer_a, bitcoin::Amount::from_sat(600_000))
+            .await
+            .unwrap();
+        assert_eq!(remaining, Some(bitcoin::Amount::from_sat(700_000)));
+
+        // Only what is left is held back for the second setup of the same peer
+        let (remaining, second) = limits
+            .reserve(db.as_ref(), peer_a, bitcoin::Amount::from_sat(600_000))
+            .await
+            .unwrap();
+        assert_eq!(remaining, Some(bitcoin::Amount::from_sat(100_000)));
+
+        let (remaining, _third) = limits
+            .reserve(db.as_ref(), peer_b, bitcoin::Amount::from_sat(600_000))
+            .await
+            .unwrap();
+        assert_eq!(remaining, Some(bitcoin::Amount::from_sat(300_000)));
+
+        // A failed setup gives its amount back
+        drop(second);
+        assert_eq!(
+            limits.remaining(db.as_ref(), peer_a).await.unwrap(),
+            Some(bitcoin::Amount::from_sat(100_000))
+        );
+
+        // A completed setup keeps counting once the swap is in the database
+        db.record_swap_volume(Uuid::new_v4(), peer_a, bitcoin::Amount::from_sat(600_000))
+            .await
+            .unwrap();
+        drop(first);
+        assert_eq!(
+            limits.remaining(db.as_ref(), peer_a).await.unwrap(),
+            Some(bitcoin::Amount::from_sat(100_000))
+        );
+    }
+}
@@ -14,34 +14,44 @@
 
 use anyhow::{bail, Context, Result};
 use comfy_table::Table;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Password;
 use libp2p::Swarm;
 use monero_sys::Daemon;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use std::convert::TryInto;
 use std::env;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use structopt::clap;
 use structopt::clap::ErrorKind;
 use swap::asb::command::{parse_args, Arguments, Command};
 use swap::asb::config::{
     initial_setup, query_user_for_initial_config, read_config, Config, ConfigNotInitialized,
 };
-use swap::asb::{cancel, punish, redeem, refund, safely_abort, EventLoop, Finality, KrakenRate};
+use swap::asb::{
+    cancel, management, punish, rate_provider, redeem, refund, safely_abort, EventLoop, Finality,
+    MarketRate, SwapHooks, TradingSwitch, VolumeLimits,
+};
 use swap::common::tor::init_tor_client;
 use swap::common::tracing_util::Format;
 use swap::common::{self, get_logs, warn_if_outdated};
-use swap::database::{open_db, AccessMode};
+use swap::database::{open_db, open_postgres_db, AccessMode, StateEncryption};
 use swap::network::rendezvous::XmrBtcNamespace;
 use swap::network::swarm;
 use swap::protocol::alice::swap::is_complete;
 use swap::protocol::alice::{run, AliceState};
 use swap::protocol::{Database, State};
 use swap::seed::Seed;
-use swap::{bitcoin, kraken, monero};
+use swap::{bitcoin, monero};
 use tracing_subscriber::filter::LevelFilter;
 use uuid::Uuid;
 
+/// How often we check whether fees are low enough to consolidate the Bitcoin wallet.
+const CONSOLIDATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 const DEFAULT_WALLET_NAME: &str = "asb-wallet";
 
 trait IntoDaemon {
@@ -53,16 +63,11 @@ impl IntoDaemon for url::Url {
         let address = self.to_string();
         let ssl = self.scheme() == "https";
 
-        Ok(Daemon { address, ssl })
-    }
-}
-
-impl IntoDaemon for monero_rpc_pool::ServerInfo {
-    fn into_daemon(self) -> Result<Daemon> {
-        let address = format!("http://{}:{}", self.host, self.port);
-        let ssl = false; // Pool server always uses HTTP locally
-
-        Ok(Daemon { address, ssl })
+        Ok(Daemon {
+            address,
+            ssl,
+            ..Default::default()
+        })
     }
 }
 
@@ -132,14 +137,21 @@ pub async fn main() -> Result<()> {
         ));
     }
 
-    let seed =
-        Seed::from_file_or_generate(&config.data.dir).expect("Could not retrieve/initialize seed");
+    // Apply the confirmation targets and fee bumping of the config file
+    let env_config = swap::env::new(testnet, &config)
+        .context("Invalid confirmation targets in the config file")?;
 
-    let db_file = config.data.dir.join("sqlite");
+    let seed_password = seed_password(&config.data.dir)?;
+    let seed =
+        Seed::from_file_or_generate_with_password(&config.data.dir, seed_password.as_deref())
+            .context("Could not retrieve/initialize seed")?;
+    let state_encryption = seed_password
+        .is_some()
+        .then(|| StateEncryption::new(seed.derive_database_encryption_key()));
 
     match cmd {
         Command::Start { resume_only } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
             // check and warn for duplicate rendezvous points
             let mut rendezvous_addrs = config.network.rendezvous_point.clone();
@@ -163,8 +175,14 @@ pub async fn main() -> Result<()> {
             // Check Monero balance
             let wallet = monero_wallet.main_wallet().await;
 
-            let total = wallet.total_balance().await.as_pico();
-            let unlocked = wallet.unlocked_balance().await.as_pico();
+            let (total, unlocked) = wallet
+                .call(|wallet| {
+                    (
+                        wallet.total_balance().as_pico(),
+                        wallet.unlocked_balance().as_pico(),
+                    )
+                })
+                .await;
 
             match (total, unlocked) {
                 (0, _) => {
@@ -188,14 +206,27 @@ pub async fn main() -> Result<()> {
             }
 
             // Initialize Bitcoin wallet
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet =
+                init_bitcoin_wallet(&config, &seed, env_config, Some(db.clone())).await?;
             let bitcoin_balance = bitcoin_wallet.balance().await?;
             tracing::info!(%bitcoin_balance, "Bitcoin wallet balance");
 
-            // Connect to Kraken
-            let kraken_price_updates = kraken::connect(config.maker.price_ticker_ws_url.clone())?;
+            let bitcoin_wallet = Arc::new(bitcoin_wallet);
+
+            if let Some(max_fee_rate) = config.bitcoin.consolidation_max_fee_rate {
+                let max_fee_rate = ::bitcoin::FeeRate::from_sat_per_vb(max_fee_rate)
+                    .context("Consolidation fee rate is too high")?;
+
+                tokio::spawn(consolidate_utxos_periodically(
+                    bitcoin_wallet.clone(),
+                    max_fee_rate,
+                ));
+            }
+
+            // Connect to the market we take the price from
+            let rate_provider = rate_provider::connect(&config.maker)?;
 
-            let kraken_rate = KrakenRate::new(config.maker.ask_spread, kraken_price_updates);
+            let market_rate = MarketRate::new(config.maker.ask_spread, rate_provider);
             let namespace = XmrBtcNamespace::from_is_testnet(testnet);
 
             // Initialize Tor client
@@ -205,7 +236,7 @@ pub async fn main() -> Result<()> {
                 &seed,
                 config.maker.min_buy_btc,
                 config.maker.max_buy_btc,
-                kraken_rate.clone(),
+                market_rate.clone(),
                 resume_only,
                 env_config,
                 namespace,
@@ -213,11 +244,16 @@ pub async fn main() -> Result<()> {
                 tor_client,
                 config.tor.register_hidden_service,
                 config.tor.hidden_service_num_intro_points,
+                config.tor.onion_only,
             )?;
 
-            for listen in config.network.listen.clone() {
-                if let Err(e) = Swarm::listen_on(&mut swarm, listen.clone()) {
-                    tracing::warn!("Failed to listen on network interface {}: {}. Consider removing it from the config.", listen, e);
+            if config.tor.onion_only {
+                tracing::info!("Only listening on the onion service, ignoring the listen and external addresses of the config");
+            } else {
+                for listen in config.network.listen.clone() {
+                    if let Err(e) = Swarm::listen_on(&mut swarm, listen.clone()) {
+                        tracing::warn!("Failed to listen on network interface {}: {}. Consider removing it from the config.", listen, e);
+                    }
                 }
             }
 
@@ -238,26 +274,56 @@ pub async fn main() -> Result<()> {
 
             tracing::info!(peer_id = %swarm.local_peer_id(), "Network layer initialized");
 
-            for external_address in config.network.external_addresses {
-                swarm.add_external_address(external_address);
+            if !config.tor.onion_only {
+                for external_address in config.network.external_addresses {
+                    swarm.add_external_address(external_address);
+                }
             }
 
+            let trading = TradingSwitch::default();
+            if let Some(management_api) = config.management_api {
+                management::serve(
+                    management_api,
+                    management::ApiState {
+                        env_config,
+                        bitcoin_wallet: bitcoin_wallet.clone(),
+                        monero_wallet: monero_wallet.clone(),
+                        db: db.clone(),
+                        latest_rate: market_rate.clone(),
+                        min_buy: config.maker.min_buy_btc,
+                        max_buy: config.maker.max_buy_btc,
+                        ask_spread: config.maker.ask_spread,
+                        trading: trading.clone(),
+                        rendezvous: swarm
+                            .behaviour()
+                            .rendezvous
+                            .as_ref()
+                            .map(|rendezvous| rendezvous.status()),
+                    },
+                )
+                .await?;
+            }
+
+            let volume_limits = VolumeLimits::new(&config.maker);
             let (event_loop, mut swap_receiver) = EventLoop::new(
                 swarm,
                 env_config,
-                Arc::new(bitcoin_wallet),
+                bitcoin_wallet,
                 monero_wallet.clone(),
                 db,
-                kraken_rate.clone(),
+                market_rate.clone(),
                 config.maker.min_buy_btc,
                 config.maker.max_buy_btc,
                 config.maker.external_bitcoin_redeem_address,
+                SwapHooks::new(config.hooks),
+                trading,
+                volume_limits,
             )
             .unwrap();
 
             tokio::spawn(async move {
                 while let Some(swap) = swap_receiver.recv().await {
-                    let rate = kraken_rate.clone();
+                    let rate = market_rate.clone();
                     tokio::spawn(async move {
                         let swap_id = swap.swap_id;
                         match run(swap, rate).await {
@@ -275,7 +341,7 @@ pub async fn main() -> Result<()> {
             event_loop.run().await;
         }
         Command::History { only_unfinished } => {
-            let db = open_db(db_file, AccessMode::ReadOnly, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadOnly, state_encryption).await?;
             let mut table = Table::new();
 
             table.set_header(vec![
@@ -336,19 +402,21 @@ pub async fn main() -> Result<()> {
             }
         }
         Command::WithdrawBtc { amount, address } => {
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, None).await?;
 
             let withdraw_tx_unsigned = match amount {
                 Some(amount) => {
                     bitcoin_wallet
-                        .send_to_address_dynamic_fee(address, amount, None)
-                        .await?
-                }
-                None => {
-                    bitcoin_wallet
-                        .sweep_balance_to_address_dynamic_fee(address)
+                        .send_to_address_dynamic_fee(
+                            address,
+                            amount,
+                            None,
+                            bitcoin::wallet::CoinControl::default(),
+                            None,
+                        )
                         .await?
                 }
+                None => bitcoin_wallet.sweep_to(address).await?,
             };
 
             let signed_tx = bitcoin_wallet
@@ -362,24 +430,26 @@ pub async fn main() -> Result<()> {
             let monero_balance = monero_wallet.main_wallet().await.total_balance().await;
             tracing::info!(%monero_balance);
 
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, None).await?;
             let bitcoin_balance = bitcoin_wallet.balance().await?;
             tracing::info!(%bitcoin_balance);
             tracing::info!(%bitcoin_balance, %monero_balance, "Current balance");
         }
         Command::Cancel { swap_id } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet =
+                init_bitcoin_wallet(&config, &seed, env_config, Some(db.clone())).await?;
 
             let (txid, _) = cancel(swap_id, Arc::new(bitcoin_wallet), db).await?;
 
             tracing::info!("Cancel transaction successfully published with id {}", txid);
         }
         Command::Refund { swap_id } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet =
+                init_bitcoin_wallet(&config, &seed, env_config, Some(db.clone())).await?;
             let monero_wallet = init_monero_wallet(&config, env_config).await?;
 
             refund(swap_id, Arc::new(bitcoin_wallet), monero_wallet.clone(), db).await?;
@@ -387,16 +457,17 @@ pub async fn main() -> Result<()> {
             tracing::info!("Monero successfully refunded");
         }
         Command::Punish { swap_id } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet =
+                init_bitcoin_wallet(&config, &seed, env_config, Some(db.clone())).await?;
 
             let (txid, _) = punish(swap_id, Arc::new(bitcoin_wallet), db).await?;
 
             tracing::info!("Punish transaction successfully published with id {}", txid);
         }
         Command::SafelyAbort { swap_id } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
             safely_abort(swap_id, db).await?;
 
@@ -406,9 +477,10 @@ pub async fn main() -> Result<()> {
             swap_id,
             do_not_await_finality,
         } => {
-            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+            let db = open_asb_db(&config, &seed, AccessMode::ReadWrite, state_encryption).await?;
 
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet =
+                init_bitcoin_wallet(&config, &seed, env_config, Some(db.clone())).await?;
 
             let (txid, _) = redeem(
                 swap_id,
@@ -421,7 +493,7 @@ pub async fn main() -> Result<()> {
             tracing::info!("Redeem transaction successfully published with id {}", txid);
         }
         Command::ExportBitcoinWallet => {
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config).await?;
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, None).await?;
             let wallet_export = bitcoin_wallet.wallet_export("asb").await?;
             println!("{}", wallet_export)
         }
@@ -440,13 +512,58 @@ pub async fn main() -> Result<()> {
     Ok(())
 }
 
+/// The password of the seed is taken from `ASB_SEED_PASSWORD`. If the variable is set but the
+/// seed is not encrypted yet, it will be encrypted with the password. If the variable is not set
+/// but the seed is encrypted, we ask for the password.
+fn seed_password(data_dir: &Path) -> Result<Option<String>> {
+    if let Some(password) = env::var("ASB_SEED_PASSWORD")
+        .ok()
+        .filter(|password| !password.is_empty())
+    {
+        return Ok(Some(password));
+    }
+
+    if !Seed::is_encrypted(data_dir)? {
+        return Ok(None);
+    }
+
+    let password = Password::with_theme(&ColorfulTheme::default())
+        .with_prompt("Enter the password of the seed")
+        .interact()?;
+
+    Ok(Some(password))
+}
+
+/// Open the Postgres database if one is configured, the sqlite file in the data dir otherwise.
+async fn open_asb_db(
+    config: &Config,
+    seed: &Seed,
+    access_mode: AccessMode,
+    encryption: Option<StateEncryption>,
+) -> Result<Arc<dyn Database + Send + Sync>> {
+    match &config.data.database_url {
+        Some(url) => {
+            // Instances sharing the database are told apart by their peer id
+            let instance = seed.derive_libp2p_identity().public().to_peer_id();
+
+            open_postgres_db(url, instance.to_string(), access_mode, encryption).await
+        }
+        None => {
+            let db_file = config.data.dir.join("sqlite");
+
+            open_db(db_file, access_mode, None, encryption).await
+        }
+    }
+}
+
 async fn init_bitcoin_wallet(
     config: &Config,
     seed: &Seed,
     env_config: swap::env::Config,
+    db: Option<Arc<dyn Database + Send + Sync>>,
 ) -> Result<bitcoin::Wallet> {
     tracing::debug!("Opening Bitcoin wallet");
-    let wallet = bitcoin::wallet::WalletBuilder::default()
+    let mut builder = bitcoin::wallet::WalletBuilder::default()
         .seed(seed.clone())
         .network(env_config.bitcoin_network)
         .electrum_rpc_urls(
@@ -463,7 +580,18 @@ async fn init_bitcoin_wallet(
         .finality_confirmations(env_config.bitcoin_finality_confirmations)
         .target_block(config.bitcoin.target_block)
         .use_mempool_space_fee_estimation(config.bitcoin.use_mempool_space_fee_estimation)
-        .sync_interval(env_config.bitcoin_sync_interval())
+        .descriptor_type(config.bitcoin.descriptor_type)
+        .sync_interval(env_config.bitcoin_sync_interval());
+
+    if let Some(proxy) = &config.bitcoin.electrum_socks5_proxy {
+        builder = builder.electrum_socks5_proxy(proxy.clone());
+    }
+
+    if let Some(db) = db {
+        builder = builder.swap_db(db);
+    }
+
+    let wallet = builder
         .build()
         .await
         .context("Failed to initialize Bitcoin wallet")?;
@@ -473,6 +601,28 @@ async fn init_bitcoin_wallet(
     Ok(wallet)
 }
 
+/// Merge the outputs of the Bitcoin wallet whenever fees are at most `max_fee_rate`.
+async fn consolidate_utxos_periodically(
+    bitcoin_wallet: Arc<bitcoin::Wallet>,
+    max_fee_rate: ::bitcoin::FeeRate,
+) {
+    let mut interval = tokio::time::interval(CONSOLIDATION_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            bitcoin_wallet.sync().await?;
+            bitcoin_wallet.consolidate_utxos(max_fee_rate).await
+        }
+        .await;
+
+        if let Err(error) = result {
+            tracing::warn!("Failed to consolidate Bitcoin wallet outputs: {:#}", error);
+        }
+    }
+}
+
 async fn init_monero_wallet(
     config: &Config,
     env_config: swap::env::Config,
@@ -483,23 +633,13 @@ async fn init_monero_wallet(
         // Start the monero-rpc-pool and use it
         tracing::info!("Starting Monero RPC Pool for ASB");
 
-        let (server_info, _status_receiver, _pool_handle) =
-            monero_rpc_pool::start_server_with_random_port(
-                monero_rpc_pool::config::Config::new_random_port(
-                    "127.0.0.1".to_string(),
-                    config.data.dir.join("monero-rpc-pool"),
-                ),
-                env_config.monero_network,
-            )
-            .await
-            .context("Failed to start Monero RPC Pool for ASB")?;
-
-        let pool_url = format!("http://{}:{}", server_info.host, server_info.port);
-        tracing::info!("Monero RPC Pool started for ASB on {}", pool_url);
-
-        server_info
-            .into_daemon()
-            .context("Failed to convert ServerInfo to Daemon")?
+        monero_rpc_pool::embedded::spawn_for_wallet(
+            config.data.dir.join("monero-rpc-pool"),
+            env_config.monero_network,
+        )
+        .await
+        .context("Failed to start Monero RPC Pool for ASB")?
+        .daemon
     } else {
         tracing::info!(
             "Using direct Monero daemon connection: {}",
@@ -58,6 +58,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_max(0.01),
+            None,
             get_dummy_address(),
             || async { Ok(Amount::from_btc(0.001)?) },
             || async {
@@ -96,6 +97,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_max(0.01),
+            None,
             get_dummy_address(),
             || async { Ok(Amount::from_btc(0.1001)?) },
             || async {
@@ -134,6 +136,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_max(0.01),
+            None,
             async { panic!("should not request new address when initial balance  is > 0") },
             || async { Ok(Amount::from_btc(0.005)?) },
             || async {
@@ -168,6 +171,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_max(0.01),
+            None,
             async { panic!("should not request new address when initial balance is > 0") },
             || async { Ok(Amount::from_btc(0.1001)?) },
             || async {
@@ -202,6 +206,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_min(0.01),
+            None,
             get_dummy_address(),
             || async { Ok(Amount::from_btc(0.0101)?) },
             || async {
@@ -240,6 +245,7 @@ mod tests {
         let (amount, fees) = determine_btc_to_swap(
             true,
             quote_with_min(0.01),
+            None,
             get_dummy_address(),
             || async { Ok(Amount::from_btc(0.0101)?) },
             || async {
@@ -283,6 +289,7 @@ mod tests {
             determine_btc_to_swap(
                 true,
                 quote_with_min(0.1),
+                None,
                 get_dummy_address(),
                 || async { Ok(Amount::from_btc(0.0101)?) },
                 || async {
@@ -332,6 +339,7 @@ mod tests {
             determine_btc_to_swap(
                 true,
                 quote_with_min(0.1),
+                None,
                 get_dummy_address(),
                 || async { Ok(Amount::from_btc(0.21)?) },
                 || async {
@@ -368,6 +376,7 @@ mod tests {
         let determination_error = determine_btc_to_swap(
             true,
             quote_with_max(0.00),
+            None,
             get_dummy_address(),
             || async { Ok(Amount::from_btc(0.0101)?) },
             || async {
@@ -386,6 +395,88 @@ mod tests {
         assert_eq!("Received quote of 0", determination_error);
     }
 
+    #[tokio::test]
+    async fn given_exact_amount_and_larger_balance_swaps_exact_amount() {
+        let givable = Arc::new(Mutex::new(MaxGiveable::new(vec![(
+            Amount::from_btc(0.1).unwrap(),
+            Amount::from_sat(1000),
+        )])));
+
+        let (amount, fees) = determine_btc_to_swap(
+            true,
+            quote_with_max(0.05),
+            Some(Amount::from_btc(0.02).unwrap()),
+            async { panic!("should not request new address when balance covers the amount") },
+            || async { Ok(Amount::from_btc(0.1001)?) },
+            || async {
+                let mut result = givable.lock().unwrap();
+                result.give()
+            },
+            || async { Ok(()) },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let expected_amount = Amount::from_btc(0.02).unwrap();
+        let expected_fees = Amount::from_btc(0.0001).unwrap();
+
+        assert_eq!((amount, fees), (expected_amount, expected_fees));
+    }
+
+    #[tokio::test]
+    async fn given_exact_amount_wait_until_deposit_covers_it() {
+        let givable = Arc::new(Mutex::new(MaxGiveable::new(vec![
+            (Amount::ZERO, Amount::from_sat(1000)),
+            (Amount::from_btc(0.01).unwrap(), Amount::from_sat(1000)),
+            (Amount::from_btc(0.03).unwrap(), Amount::from_sat(1000)),
+        ])));
+
+        let (amount, _) = determine_btc_to_swap(
+            true,
+            quote_with_max(0.05),
+            Some(Amount::from_btc(0.02).unwrap()),
+            get_dummy_address(),
+            || async { Ok(Amount::from_btc(0.0301)?) },
+            || async {
+                let mut result = givable.lock().unwrap();
+                result.give()
+            },
+            || async { Ok(()) },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(amount, Amount::from_btc(0.02).unwrap());
+    }
+
+    #[tokio::test]
+    async fn given_exact_amount_above_max_quantity_return_error() {
+        let determination_error = determine_btc_to_swap(
+            true,
+            quote_with_max(0.01),
+            Some(Amount::from_btc(0.02).unwrap()),
+            get_dummy_address(),
+            || async { Ok(Amount::from_btc(0.1)?) },
+            || async { Ok((Amount::from_btc(0.1)?, Amount::from_sat(1000))) },
+            || async { Ok(()) },
+            None,
+            None,
+        )
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+
+        assert_eq!(
+            "The maker only accepts swapping between 0 BTC and 0.01000000 BTC, not 0.02000000 BTC",
+            determination_error
+        );
+    }
+
     struct MaxGiveable {
         amounts: Vec<(Amount, Amount)>,
         call_counter: usize,
@@ -413,6 +504,7 @@ mod tests {
             price: Amount::from_btc(0.001).unwrap(),
             max_quantity: Amount::from_btc(btc).unwrap(),
             min_quantity: Amount::ZERO,
+            expires_at: None,
         }
     }
 
@@ -421,6 +513,7 @@ mod tests {
             price: Amount::from_btc(0.001).unwrap(),
             max_quantity: Amount::MAX_MONEY,
             min_quantity: Amount::from_btc(btc).unwrap(),
+            expires_at: None,
         }
     }
 
@@ -1,6 +1,7 @@
 pub mod wallet;
 
 mod cancel;
+mod checkpoints;
 mod early_refund;
 mod lock;
 mod punish;
@@ -632,7 +633,10 @@ mod tests {
             .await
             .unwrap();
         let tx_lock_fee = alice_wallet
-            .estimate_fee(TxLock::weight(), Some(btc_amount))
+            .estimate_fee(
+                TxLock::weight(wallet::DescriptorType::Bip84),
+                Some(btc_amount),
+            )
             .await
             .unwrap();
 
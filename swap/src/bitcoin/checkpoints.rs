@@ -0,0 +1,453 @@
+use anyhow::{ensure, Context, Result};
+use bdk_electrum::electrum_client::HeaderNotification;
+use bitcoin::block::Header;
+use bitcoin::constants::genesis_block;
+use bitcoin::params::Params;
+use bitcoin::{BlockHash, Network};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Like Bitcoin Core, we reject blocks with a timestamp more than two hours in the future.
+const MAX_FUTURE_BLOCK_TIME_SECS: u64 = 2 * 60 * 60;
+
+/// Blocks this far below a verified tip become checkpoints. No reorg goes this deep.
+const LEARNED_CHECKPOINT_DEPTH: u32 = 100;
+
+/// Electrum servers return at most this many headers per request.
+const MAX_HEADERS_PER_REQUEST: u32 = 2016;
+
+/// Blocks of the Bitcoin main chain, one at every halving. The genesis block is always a
+/// checkpoint as well.
+const MAINNET_CHECKPOINTS: &[(u32, &str)] = &[
+    (
+        210_000,
+        "000000000000048b95347e83192f69cf0366076336c639f9b7228e9ba171342e",
+    ),
+    (
+        420_000,
+        "000000000000000002cce816c0ab2c5c269cb081896b7dcb34b8422d6b74ffa1",
+    ),
+    (
+        630_000,
+        "000000000000000000024bead8df69990852c202db0e0097c1a12ea637d7e96d",
+    ),
+    (
+        840_000,
+        "0000000000000000000320283a032748cef8227873ff4872689bf23f1cda83a5",
+    ),
+];
+
+/// Checks the latest block header an Electrum server reports by verifying the chain of
+/// headers from the latest checkpoint up to it: every header has to build on the one before,
+/// carry a valid proof of work and follow the difficulty adjustment rules.
+///
+/// This is no replacement for a full node, but a server can't report a higher block height
+/// (and with it more confirmations for our timelocks) without presenting a chain of headers
+/// on top of our checkpoint.
+///
+/// A block deep below a verified tip is remembered as a checkpoint (and written to the
+/// checkpoint file, if any), so later checks only fetch the headers since then.
+#[derive(Debug)]
+pub(crate) struct HeaderVerifier {
+    network: Network,
+    checkpoint_file: Option<PathBuf>,
+    state: Mutex<VerifierState>,
+}
+
+#[derive(Debug)]
+struct VerifierState {
+    /// The latest checkpoint we learned from a verified chain.
+    learned_checkpoint: Option<Checkpoint>,
+    /// The last tip we verified, which doesn't need to be verified again.
+    verified_tip: Option<BlockHash>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Checkpoint {
+    height: u32,
+    hash: BlockHash,
+}
+
+impl HeaderVerifier {
+    /// Create a verifier for the given network. The checkpoints it learns are persisted to
+    /// `checkpoint_file` if given.
+    pub(crate) fn new(network: Network, checkpoint_file: Option<PathBuf>) -> Self {
+        let learned_checkpoint = checkpoint_file.as_ref().and_then(|path| {
+            let checkpoint = std::fs::read(path).ok()?;
+
+            serde_json::from_slice(&checkpoint)
+                .inspect_err(|error| {
+                    tracing::warn!(%error, path = %path.display(), "Ignoring invalid block header checkpoint file");
+                })
+                .ok()
+        });
+
+        Self {
+            network,
+            checkpoint_file,
+            state: Mutex::new(VerifierState {
+                learned_checkpoint,
+                verified_tip: None,
+            }),
+        }
+    }
+
+    /// The latest checkpoint at or below the given height.
+    fn checkpoint_at_or_below(&self, height: u32) -> Checkpoint {
+        let checkpoints: &[(u32, &str)] = match self.network {
+            Network::Bitcoin => MAINNET_CHECKPOINTS,
+            _ => &[],
+        };
+
+        let checkpoint = checkpoints
+            .iter()
+            .rev()
+            .find(|(checkpoint_height, _)| *checkpoint_height <= height)
+            .map(|(checkpoint_height, hash)| Checkpoint {
+                height: *checkpoint_height,
+                hash: BlockHash::from_str(hash).expect("checkpoint hashes to be valid"),
+            })
+            .unwrap_or_else(|| Checkpoint {
+                height: 0,
+                hash: genesis_block(self.network).block_hash(),
+            });
+
+        let learned_checkpoint = self.lock_state().learned_checkpoint;
+
+        match learned_checkpoint {
+            Some(learned) if learned.height > checkpoint.height && learned.height <= height => {
+                learned
+            }
+            _ => checkpoint,
+        }
+    }
+
+    /// Verify the tip reported by a server. `fetch_headers` is called with a start height and
+    /// a count to get the headers between the latest checkpoint and the tip.
+    pub(crate) fn verify_tip(
+        &self,
+        tip: &HeaderNotification,
+        mut fetch_headers: impl FnMut(u32, u32) -> Result<Vec<Header>>,
+    ) -> Result<()> {
+        let params = self.network.params();
+        let tip_hash = tip.header.block_hash();
+        let height = u32::try_from(tip.height).context("Block height does not fit into u32")?;
+
+        if self.lock_state().verified_tip == Some(tip_hash) {
+            return Ok(());
+        }
+
+        let checkpoint = self.checkpoint_at_or_below(height);
+        if checkpoint.height == height {
+            ensure!(
+                tip_hash == checkpoint.hash,
+                "Block {} does not match our checkpoint",
+                height
+            );
+            return Ok(());
+        }
+
+        let mut previous = fetch_headers(checkpoint.height, 1)
+            .and_then(|headers| headers.into_iter().next().context("No header returned"))
+            .with_context(|| {
+                format!(
+                    "Failed to fetch the header of checkpoint {}",
+                    checkpoint.height
+                )
+            })?;
+        ensure!(
+            previous.block_hash() == checkpoint.hash,
+            "Block {} does not match our checkpoint",
+            checkpoint.height
+        );
+
+        let learn_height = height.saturating_sub(LEARNED_CHECKPOINT_DEPTH);
+        let mut learned_checkpoint = None;
+        let mut previous_height = checkpoint.height;
+
+        while previous_height < height {
+            let start = previous_height + 1;
+            let count = (height - previous_height).min(MAX_HEADERS_PER_REQUEST);
+            let headers = fetch_headers(start, count)
+                .with_context(|| format!("Failed to fetch the headers from block {}", start))?;
+            ensure!(
+                !headers.is_empty(),
+                "No headers from block {} returned",
+                start
+            );
+
+            for header in headers.into_iter().take(count as usize) {
+                let header_height = previous_height + 1;
+                verify_next_header(params, header_height, &previous, &header)?;
+
+                if header_height == learn_height {
+                    learned_checkpoint = Some(Checkpoint {
+                        height: header_height,
+                        hash: header.block_hash(),
+                    });
+                }
+
+                previous = header;
+                previous_height = header_height;
+            }
+        }
+
+        ensure!(
+            previous.block_hash() == tip_hash,
+            "The headers up to block {} don't lead to the reported tip",
+            height
+        );
+
+        let mut state = self.lock_state();
+        state.verified_tip = Some(tip_hash);
+
+        if let Some(learned) = learned_checkpoint {
+            let is_newer = state
+                .learned_checkpoint
+                .is_none_or(|checkpoint| checkpoint.height < learned.height);
+
+            if is_newer {
+                state.learned_checkpoint = Some(learned);
+                self.persist_checkpoint(learned);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn persist_checkpoint(&self, checkpoint: Checkpoint) {
+        let Some(path) = &self.checkpoint_file else {
+            return;
+        };
+
+        let result = serde_json::to_vec(&checkpoint)
+            .map_err(anyhow::Error::from)
+            .and_then(|checkpoint| Ok(std::fs::write(path, checkpoint)?));
+
+        if let Err(error) = result {
+            tracing::warn!(%error, path = %path.display(), "Failed to persist block header checkpoint");
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, VerifierState> {
+        self.state.lock().expect("lock not to be poisoned")
+    }
+}
+
+/// Check that `header` at `height` could have been mined on top of `previous`.
+fn verify_next_header(
+    params: &Params,
+    height: u32,
+    previous: &Header,
+    header: &Header,
+) -> Result<()> {
+    ensure!(
+        header.prev_blockhash == previous.block_hash(),
+        "Block {} does not build on block {}",
+        height,
+        height - 1
+    );
+
+    let target = header.target();
+    ensure!(
+        target <= params.max_attainable_target,
+        "Block {} has a target above the limit of the network",
+        height
+    );
+    header
+        .validate_pow(target)
+        .with_context(|| format!("Block {} has an invalid proof of work", height))?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System time is before the unix epoch")?
+        .as_secs();
+    ensure!(
+        u64::from(header.time) <= now + MAX_FUTURE_BLOCK_TIME_SECS,
+        "Block {} has a timestamp too far in the future",
+        height
+    );
+
+    verify_difficulty(params, height, previous, header)
+}
+
+/// Check that the difficulty only changes at a retarget, and by at most the factor of 4 the
+/// retargeting rules allow compared to the previous period.
+fn verify_difficulty(
+    params: &Params,
+    height: u32,
+    previous: &Header,
+    header: &Header,
+) -> Result<()> {
+    // Testnet allows blocks at the minimum difficulty and regtest never adjusts it
+    if params.allow_min_difficulty_blocks || params.no_pow_retargeting {
+        return Ok(());
+    }
+
+    if u64::from(height) % params.difficulty_adjustment_interval() != 0 {
+        ensure!(
+            header.bits == previous.bits,
+            "Block {} changes the difficulty outside of a retarget",
+            height
+        );
+        return Ok(());
+    }
+
+    let previous_target = previous.target();
+    let target = header.target();
+    ensure!(
+        target <= previous_target.max_transition_threshold(params)
+            && target >= previous_target.min_transition_threshold(),
+        "Block {} changes the difficulty more than the retargeting rules allow",
+        height
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::CompactTarget;
+
+    fn tip(height: usize, header: Header) -> HeaderNotification {
+        HeaderNotification { height, header }
+    }
+
+    /// A regtest chain of the given length on top of the genesis block. Regtest blocks need
+    /// almost no work, so they can be mined in tests.
+    fn regtest_chain(length: usize, time_offset: u32) -> Vec<Header> {
+        let mut chain = vec![genesis_block(Network::Regtest).header];
+
+        for _ in 0..length {
+            let previous = chain.last().unwrap();
+            let mut header = Header {
+                prev_blockhash: previous.block_hash(),
+                time: previous.time + 600 + time_offset,
+                nonce: 0,
+                ..*previous
+            };
+            while header.validate_pow(header.target()).is_err() {
+                header.nonce += 1;
+            }
+            chain.push(header);
+        }
+
+        chain
+    }
+
+    fn fetch_from(chain: &[Header]) -> impl FnMut(u32, u32) -> Result<Vec<Header>> + '_ {
+        move |start, count| {
+            Ok(chain
+                .iter()
+                .skip(start as usize)
+                .take(count as usize)
+                .copied()
+                .collect())
+        }
+    }
+
+    #[test]
+    fn accepts_the_genesis_block_and_rejects_a_tampered_one() {
+        let verifier = HeaderVerifier::new(Network::Bitcoin, None);
+        let genesis = genesis_block(Network::Bitcoin).header;
+
+        verifier
+            .verify_tip(&tip(0, genesis), |_, _| unreachable!())
+            .unwrap();
+
+        let mut tampered = genesis;
+        tampered.nonce += 1;
+        assert!(verifier
+            .verify_tip(&tip(0, tampered), |_, _| unreachable!())
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_checkpoint_headers_with_the_wrong_hash() {
+        let verifier = HeaderVerifier::new(Network::Bitcoin, None);
+        let genesis = genesis_block(Network::Bitcoin).header;
+
+        let result = verifier.verify_tip(&tip(850_000, genesis), |_, _| Ok(vec![genesis]));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn accepts_a_linked_chain_and_learns_a_checkpoint() {
+        let chain = regtest_chain(150, 0);
+        let verifier = HeaderVerifier::new(Network::Regtest, None);
+
+        verifier
+            .verify_tip(&tip(150, chain[150]), fetch_from(&chain))
+            .unwrap();
+
+        assert_eq!(
+            verifier.checkpoint_at_or_below(150),
+            Checkpoint {
+                height: 50,
+                hash: chain[50].block_hash(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_chains_which_do_not_link_to_the_tip() {
+        let chain = regtest_chain(150, 0);
+        let other_chain = regtest_chain(150, 1);
+        let verifier = HeaderVerifier::new(Network::Regtest, None);
+
+        // The headers lead to a different tip
+        assert!(verifier
+            .verify_tip(&tip(150, other_chain[150]), fetch_from(&chain))
+            .is_err());
+
+        // One of the headers doesn't build on the one before
+        let mut broken_chain = chain.clone();
+        broken_chain[70] = other_chain[70];
+        assert!(verifier
+            .verify_tip(&tip(150, chain[150]), fetch_from(&broken_chain))
+            .is_err());
+
+        assert_eq!(verifier.checkpoint_at_or_below(150).height, 0);
+    }
+
+    #[test]
+    fn learned_checkpoints_are_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        let chain = regtest_chain(150, 0);
+
+        HeaderVerifier::new(Network::Regtest, Some(path.clone()))
+            .verify_tip(&tip(150, chain[150]), fetch_from(&chain))
+            .unwrap();
+
+        let verifier = HeaderVerifier::new(Network::Regtest, Some(path));
+        assert_eq!(verifier.checkpoint_at_or_below(150).height, 50);
+        // Checkpoints above the tip are of no use
+        assert_eq!(verifier.checkpoint_at_or_below(40).height, 0);
+    }
+
+    #[test]
+    fn rejects_difficulty_changes_outside_the_retargeting_rules() {
+        let params = Network::Bitcoin.params();
+        let mut previous = genesis_block(Network::Bitcoin).header;
+        previous.bits = CompactTarget::from_consensus(0x1703_4219);
+
+        let mut header = previous;
+        assert!(verify_difficulty(params, 840_001, &previous, &header).is_ok());
+        assert!(verify_difficulty(params, 840_672, &previous, &header).is_ok());
+
+        // Only retargets may change the difficulty
+        header.bits = CompactTarget::from_consensus(0x1703_4218);
+        assert!(verify_difficulty(params, 840_001, &previous, &header).is_err());
+        assert!(verify_difficulty(params, 840_672, &previous, &header).is_ok());
+
+        // And not by more than a factor of 4
+        header.bits = CompactTarget::from_consensus(0x1d00_ffff);
+        assert!(verify_difficulty(params, 840_672, &previous, &header).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::bitcoin::wallet::Watchable;
+use crate::bitcoin::wallet::{CoinControl, DescriptorType, Watchable};
 use crate::bitcoin::{
     build_shared_output_descriptor, Address, Amount, PublicKey, Transaction, Wallet,
 };
@@ -13,7 +13,11 @@ use serde::{Deserialize, Serialize};
 use super::wallet::EstimateFeeRate;
 
 const SCRIPT_SIZE: usize = 34;
+/// Weight of a lock transaction spending a single BIP84 output, without change.
 const TX_LOCK_WEIGHT: usize = 485;
+/// Weight of a lock transaction spending a single BIP86 output, without change. The Schnorr
+/// signature in the witness is smaller than the ECDSA signature and public key.
+const TX_LOCK_WEIGHT_TAPROOT: usize = 444;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TxLock {
@@ -39,7 +43,13 @@ impl TxLock {
             .expect("can derive address from descriptor");
 
         let psbt = wallet
-            .send_to_address(address, amount, spending_fee, Some(change))
+            .send_to_address(
+                address,
+                amount,
+                spending_fee,
+                Some(change),
+                CoinControl::default(),
+            )
             .await?;
 
         Ok(Self {
@@ -178,8 +188,14 @@ impl TxLock {
         }
     }
 
-    pub fn weight() -> ::bitcoin::Weight {
-        ::bitcoin::Weight::from_wu(TX_LOCK_WEIGHT as u64)
+    /// The weight of the lock transaction when it is funded by a wallet of the given type.
+    pub fn weight(descriptor_type: DescriptorType) -> ::bitcoin::Weight {
+        let weight = match descriptor_type {
+            DescriptorType::Bip84 => TX_LOCK_WEIGHT,
+            DescriptorType::Bip86 => TX_LOCK_WEIGHT_TAPROOT,
+        };
+
+        ::bitcoin::Weight::from_wu(weight as u64)
     }
 }
 
@@ -1,35 +1,46 @@
 use crate::bitcoin::{parse_rpc_error_code, Address, Amount, RpcErrorCode, Transaction};
 use crate::cli::api::tauri_bindings::{
-    TauriBackgroundProgress, TauriBitcoinFullScanProgress, TauriBitcoinSyncProgress, TauriEmitter,
-    TauriHandle,
+    TauriBackgroundProgress, TauriBackgroundProgressHandle, TauriBitcoinFullScanProgress,
+    TauriBitcoinSyncProgress, TauriEmitter, TauriHandle,
 };
+use crate::protocol::Database;
 use crate::seed::Seed;
 use anyhow::{anyhow, bail, Context, Result};
-use bdk_chain::spk_client::{SyncRequest, SyncRequestBuilder};
-use bdk_chain::CheckPoint;
-use bdk_electrum::electrum_client::{ElectrumApi, GetHistoryRes};
+use bdk_chain::spk_client::{
+    FullScanRequest, FullScanRequestBuilder, FullScanResult, SyncRequest, SyncRequestBuilder,
+};
+use bdk_chain::{ChainPosition, CheckPoint};
+use bdk_electrum::electrum_client::{self, ElectrumApi, GetHistoryRes};
+use bdk_electrum::BdkElectrumClient;
 
 use bdk_wallet::bitcoin::FeeRate;
 use bdk_wallet::bitcoin::Network;
+use bdk_wallet::error::CreateTxError;
 use bdk_wallet::export::FullyNodedExport;
+use bdk_wallet::miniscript::descriptor::KeyMap;
+use bdk_wallet::miniscript::{Descriptor, DescriptorPublicKey};
 use bdk_wallet::rusqlite::Connection;
-use bdk_wallet::template::{Bip84, DescriptorTemplate};
+use bdk_wallet::template::{Bip84, Bip86, DescriptorTemplate, DescriptorTemplateOut};
 use bdk_wallet::KeychainKind;
 use bdk_wallet::SignOptions;
+use bdk_wallet::TxBuilder;
 use bdk_wallet::WalletPersister;
 use bdk_wallet::{Balance, PersistedWallet};
 use bitcoin::bip32::Xpriv;
-use bitcoin::{psbt::Psbt as PartiallySignedTransaction, Txid};
+use bitcoin::{psbt::Psbt as PartiallySignedTransaction, OutPoint, Txid};
 use bitcoin::{ScriptBuf, Weight};
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Debug;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex as SyncMutex;
 use std::time::Duration;
@@ -38,11 +49,14 @@ use sync_ext::{CumulativeProgressHandle, InnerSyncCallback, SyncCallbackExt};
 use tokio::sync::watch;
 use tokio::sync::Mutex as TokioMutex;
 use tracing::{debug_span, Instrument};
+use typeshare::typeshare;
+use uuid::Uuid;
 
 use super::bitcoin_address::revalidate_network;
+use super::checkpoints::HeaderVerifier;
 use super::BlockHeight;
 use derive_builder::Builder;
-use electrum_pool::ElectrumBalancer;
+use electrum_pool::{ElectrumBalancer, ElectrumBalancerConfig, ServerStats};
 use moka;
 
 /// We allow transaction fees of up to 20% of the transferred amount to ensure
@@ -50,8 +64,23 @@ use moka;
 const MAX_RELATIVE_TX_FEE: Decimal = dec!(0.20);
 const MAX_ABSOLUTE_TX_FEE: Amount = Amount::from_sat(100_000);
 const MIN_ABSOLUTE_TX_FEE: Amount = Amount::from_sat(1000);
+/// Fee rates above this (100k sat/vB) returned by an Electrum server are treated as garbage.
+const MAX_ELECTRUM_BTC_PER_KVB: f64 = 1.0;
 const DUST_AMOUNT: Amount = Amount::from_sat(546);
 
+/// How often [`Wallet::wait_until_final_bumping_fee`] checks whether a fee bump is due.
+const FEE_BUMP_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the subscription reader asks the Electrum server for pending notifications.
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Merging fewer outputs than this isn't worth a transaction.
+const CONSOLIDATION_MIN_UTXOS: usize = 5;
+/// The first retry of a queued broadcast, the interval doubles with every attempt.
+const BROADCAST_RETRY_INITIAL_INTERVAL: Duration = Duration::from_secs(30);
+const BROADCAST_RETRY_MAX_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// We stop broadcasting a queued transaction after this long. By then it has most likely been
+/// replaced by a conflicting transaction, e.g. a refund by the punish transaction.
+const BROADCAST_RETRY_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 /// This is our wrapper around a bdk wallet and a corresponding
 /// bdk electrum client.
 /// It unifies all the functionality we need when interacting
@@ -81,8 +110,13 @@ pub struct Wallet<Persister = Connection, C = Client> {
     /// We want our transactions to be confirmed after this many blocks
     /// (used for fee estimation).
     target_block: u32,
+    /// The descriptors our addresses are derived from.
+    descriptor_type: DescriptorType,
     /// The Tauri handle
     tauri_handle: Option<TauriHandle>,
+    /// The swap database. The subscriptions, the broadcast queue and the address labels are
+    /// kept there, so they survive a restart.
+    swap_db: Option<Arc<dyn Database + Send + Sync>>,
 }
 
 /// This is our wrapper around a bdk electrum client.
@@ -93,7 +127,15 @@ pub struct Client {
     /// The history of transactions for each script.
     script_history: BTreeMap<ScriptBuf, Vec<GetHistoryRes>>,
     /// The subscriptions to the status of transactions.
-    subscriptions: HashMap<(Txid, ScriptBuf), Subscription>,
+    subscriptions: HashMap<(Txid, ScriptBuf), SubscriptionSender>,
+    /// The connection we receive script hash and block header notifications on.
+    notifications: Option<NotificationConnection>,
+    /// Whether a task is reading the notifications for the subscriptions.
+    subscription_reader_running: bool,
+    /// The transactions a task keeps broadcasting until they are seen.
+    retrying_broadcasts: HashSet<Txid>,
+    /// Checks the block headers the servers report, if enabled.
+    header_verifier: Option<Arc<HeaderVerifier>>,
     /// The time of the last sync.
     last_sync: Instant,
     /// How often we sync with the server.
@@ -117,7 +159,9 @@ pub struct Client {
     derive(Clone)
 )]
 pub struct WalletConfig {
-    seed: Seed,
+    /// The seed the keys are derived from. Can only be omitted for a watch-only wallet.
+    #[builder(default)]
+    seed: Option<Seed>,
     network: Network,
     electrum_rpc_urls: Vec<String>,
     persister: PersisterConfig,
@@ -128,6 +172,19 @@ pub struct WalletConfig {
     tauri_handle: Option<TauriHandle>,
     #[builder(default = "true")]
     use_mempool_space_fee_estimation: bool,
+    #[builder(default)]
+    descriptor_type: DescriptorType,
+    /// SOCKS5 proxy to connect to the Electrum servers through.
+    #[builder(default)]
+    electrum_socks5_proxy: Option<String>,
+    /// Build a watch-only wallet from these descriptors instead of deriving them from the seed.
+    /// Such a wallet tracks the balance but cannot sign transactions.
+    #[builder(default)]
+    watch_only: Option<PublicDescriptors>,
+    /// The swap database. The subscriptions and the broadcast queue are persisted in it and
+    /// resumed from the last run, and the address labels are stored there.
+    #[builder(default)]
+    swap_db: Option<Arc<dyn Database + Send + Sync>>,
 }
 
 impl WalletBuilder {
@@ -139,20 +196,51 @@ impl WalletBuilder {
             .validate_config()
             .map_err(|e| anyhow!("Builder validation failed: {e}"))?;
 
-        let client = Client::new(&config.electrum_rpc_urls, config.sync_interval)
-            .await
-            .context("Failed to create Electrum client")?;
-
-        match &config.persister {
+        let (stats_db_path, header_checkpoint_path) = match &config.persister {
             PersisterConfig::SqliteFile { data_dir } => {
-                let xprivkey = config
-                    .seed
+                let wallet_parent_dir = data_dir.join(Wallet::<Connection>::WALLET_PARENT_DIR_NAME);
+
+                (
+                    Some(wallet_parent_dir.join(Wallet::<Connection>::ELECTRUM_STATS_FILE_NAME)),
+                    Some(wallet_parent_dir.join(Wallet::<Connection>::HEADER_CHECKPOINT_FILE_NAME)),
+                )
+            }
+            PersisterConfig::InMemorySqlite => (None, None),
+        };
+
+        let electrum_config = ElectrumBalancerConfig {
+            stats_db_path,
+            socks5_proxy: config.electrum_socks5_proxy.clone(),
+            ..Default::default()
+        };
+
+        let client = Client::with_config(
+            &config.electrum_rpc_urls,
+            config.sync_interval,
+            electrum_config,
+        )
+        .await
+        .context("Failed to create Electrum client")?
+        .verify_headers(config.network, header_checkpoint_path);
+
+        let descriptors = match (&config.seed, &config.watch_only) {
+            (Some(seed), None) => {
+                let xprivkey = seed
                     .derive_extended_private_key(config.network)
-                    .context("Failed to derive extended private key for file wallet")?;
+                    .context("Failed to derive extended private key")?;
 
+                WalletDescriptors::from_xpriv(xprivkey, config.descriptor_type, config.network)?
+            }
+            (None, Some(public_descriptors)) => WalletDescriptors::watch_only(public_descriptors)?,
+            (Some(_), Some(_)) => bail!("A watch-only wallet cannot be built from a seed"),
+            (None, None) => bail!("Either a seed or the descriptors to watch are required"),
+        };
+
+        let mut wallet = match &config.persister {
+            PersisterConfig::SqliteFile { data_dir } => {
                 let wallet_parent_dir = data_dir.join(Wallet::<Connection>::WALLET_PARENT_DIR_NAME);
                 let wallet_dir = wallet_parent_dir.join(Wallet::<Connection>::WALLET_DIR_NAME);
-                let wallet_path = wallet_dir.join(Wallet::<Connection>::WALLET_FILE_NAME);
+                let wallet_path = wallet_dir.join(descriptors.wallet_file_name());
                 let wallet_exists = wallet_path.exists();
 
                 tokio::fs::create_dir_all(&wallet_dir)
@@ -170,7 +258,7 @@ impl WalletBuilder {
                     let connection = open_connection()?;
 
                     Wallet::create_existing(
-                        xprivkey,
+                        descriptors,
                         config.network,
                         client,
                         connection,
@@ -182,16 +270,33 @@ impl WalletBuilder {
                     .await
                     .context("Failed to load existing wallet")
                 } else {
-                    let old_wallet_export = Wallet::<Connection>::get_pre_1_0_bdk_wallet_export(
-                        data_dir,
-                        config.network,
-                        &config.seed,
-                    )
-                    .await
-                    .context("Failed to get pre-1.0.0 BDK wallet export for migration")?;
+                    // The old wallet only ever used BIP84 descriptors
+                    let old_wallet_export = match (&config.seed, descriptors.descriptor_type) {
+                        (None, _) => None,
+                        (Some(seed), DescriptorType::Bip84) => {
+                            Wallet::<Connection>::get_pre_1_0_bdk_wallet_export(
+                                data_dir,
+                                config.network,
+                                seed,
+                            )
+                            .await
+                            .context("Failed to get pre-1.0.0 BDK wallet export for migration")?
+                        }
+                        (Some(_), DescriptorType::Bip86) => {
+                            let bip84_wallet_path =
+                                wallet_dir.join(DescriptorType::Bip84.wallet_file_name());
+                            if bip84_wallet_path.exists() {
+                                tracing::warn!(
+                                    "Creating a new Taproot Bitcoin wallet. Funds in the existing BIP84 wallet are not moved, switch back to it to spend them"
+                                );
+                            }
+
+                            None
+                        }
+                    };
 
                     Wallet::create_new(
-                        xprivkey,
+                        descriptors,
                         config.network,
                         client,
                         open_connection,
@@ -206,16 +311,11 @@ impl WalletBuilder {
                 }
             }
             PersisterConfig::InMemorySqlite => {
-                let xprivkey = config
-                    .seed
-                    .derive_extended_private_key(config.network)
-                    .context("Failed to derive extended private key for in-memory wallet")?;
-
                 let persister = Connection::open_in_memory()
                     .context("Failed to open in-memory SQLite database")?;
 
                 Wallet::create_new::<Connection>(
-                    xprivkey,
+                    descriptors,
                     config.network,
                     client,
                     move || Ok(persister),
@@ -228,6 +328,145 @@ impl WalletBuilder {
                 .await
                 .context("Failed to create new in-memory wallet")
             }
+        }?;
+
+        if let Some(db) = config.swap_db {
+            wallet.swap_db = Some(db);
+            wallet.resume_subscriptions().await;
+            wallet.resume_broadcasts().await;
+        }
+
+        Ok(wallet)
+    }
+}
+
+/// The public descriptors of a wallet, without any private keys.
+///
+/// Importing them into another wallet (e.g. Sparrow or Bitcoin Core) shows the balance and
+/// the transactions of our wallet, but doesn't allow spending from it.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicDescriptors {
+    /// The descriptor of the receiving addresses.
+    pub external: String,
+    /// The descriptor of the change addresses.
+    pub internal: String,
+}
+
+/// The descriptors a wallet is built from, including the private keys unless it is watch-only.
+struct WalletDescriptors {
+    external: (Descriptor<DescriptorPublicKey>, KeyMap),
+    internal: (Descriptor<DescriptorPublicKey>, KeyMap),
+    descriptor_type: DescriptorType,
+    watch_only: bool,
+}
+
+impl WalletDescriptors {
+    fn from_xpriv(
+        xprivkey: Xpriv,
+        descriptor_type: DescriptorType,
+        network: Network,
+    ) -> Result<Self> {
+        let (external, external_keys, _) = descriptor_type
+            .descriptor(xprivkey, KeychainKind::External, network)
+            .context("Failed to build external wallet descriptor")?;
+
+        let (internal, internal_keys, _) = descriptor_type
+            .descriptor(xprivkey, KeychainKind::Internal, network)
+            .context("Failed to build change wallet descriptor")?;
+
+        Ok(Self {
+            external: (external, external_keys),
+            internal: (internal, internal_keys),
+            descriptor_type,
+            watch_only: false,
+        })
+    }
+
+    fn watch_only(public_descriptors: &PublicDescriptors) -> Result<Self> {
+        // Parsing as public descriptors rejects any private key
+        let parse = |descriptor: &str| {
+            Descriptor::<DescriptorPublicKey>::from_str(descriptor)
+                .with_context(|| format!("Invalid public descriptor: {}", descriptor))
+        };
+        let external = parse(&public_descriptors.external)?;
+        let internal = parse(&public_descriptors.internal)?;
+
+        let descriptor_type = match (&external, &internal) {
+            (Descriptor::Wpkh(_), Descriptor::Wpkh(_)) => DescriptorType::Bip84,
+            (Descriptor::Tr(_), Descriptor::Tr(_)) => DescriptorType::Bip86,
+            _ => bail!("Only matching wpkh() or tr() descriptors can be watched"),
+        };
+
+        Ok(Self {
+            external: (external, KeyMap::new()),
+            internal: (internal, KeyMap::new()),
+            descriptor_type,
+            watch_only: true,
+        })
+    }
+
+    /// Watch-only wallets are stored in a file named after the checksum of their receiving
+    /// descriptor, so watching different descriptors doesn't mix up their transactions.
+    fn wallet_file_name(&self) -> String {
+        if self.watch_only {
+            let descriptor = self.external.0.to_string();
+            let checksum = descriptor
+                .rsplit_once('#')
+                .map(|(_, checksum)| checksum)
+                .expect("descriptor to be displayed with its checksum");
+
+            format!("wallet-watch-only-{}-db.sqlite", checksum)
+        } else {
+            self.descriptor_type.wallet_file_name().to_string()
+        }
+    }
+}
+
+/// The descriptors the wallet derives its addresses from.
+///
+/// Both kinds of wallets are derived from the same seed but are stored in different files, so
+/// switching between them doesn't lose track of the other one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DescriptorType {
+    /// Native SegWit `wpkh()` descriptors.
+    #[default]
+    Bip84,
+    /// Taproot `tr()` descriptors, spent with a single Schnorr signature.
+    Bip86,
+}
+
+impl DescriptorType {
+    fn descriptor(
+        self,
+        xprivkey: Xpriv,
+        keychain: KeychainKind,
+        network: Network,
+    ) -> Result<DescriptorTemplateOut> {
+        let descriptor = match self {
+            DescriptorType::Bip84 => Bip84(xprivkey, keychain).build(network)?,
+            DescriptorType::Bip86 => Bip86(xprivkey, keychain).build(network)?,
+        };
+
+        Ok(descriptor)
+    }
+
+    fn wallet_file_name(self) -> &'static str {
+        match self {
+            DescriptorType::Bip84 => "wallet-db.sqlite",
+            DescriptorType::Bip86 => "wallet-taproot-db.sqlite",
+        }
+    }
+
+    /// Worst-case weight of the witness needed to spend one of our outputs, with the same
+    /// safety margin for both kinds.
+    fn max_satisfaction_weight(self) -> Weight {
+        match self {
+            // Signature and public key
+            DescriptorType::Bip84 => Weight::from_wu(107 * 10),
+            // Schnorr signature
+            DescriptorType::Bip86 => Weight::from_wu(66 * 10),
         }
     }
 }
@@ -251,6 +490,24 @@ pub struct Subscription {
     txid: Txid,
 }
 
+/// The sending half of a [`Subscription`], updated by the subscription reader.
+#[derive(Debug, Clone)]
+struct SubscriptionSender {
+    sender: Arc<watch::Sender<ScriptStatus>>,
+    /// The last status we sent, `None` if we never got one.
+    last_status: Option<ScriptStatus>,
+    /// Whether the subscription still has to be resumed after a restart.
+    resumable: bool,
+}
+
+/// An Electrum connection with notifications enabled for the block headers and the scripts
+/// of our subscriptions.
+#[derive(Clone)]
+struct NotificationConnection {
+    connection: Arc<BdkElectrumClient<electrum_client::Client>>,
+    scripts: HashSet<ScriptBuf>,
+}
+
 /// The possible statuses of a script.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ScriptStatus {
@@ -269,6 +526,64 @@ pub struct Confirmed {
     depth: u32,
 }
 
+/// An output of this wallet that has not been spent yet.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Utxo {
+    #[typeshare(serialized_as = "string")]
+    pub outpoint: OutPoint,
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub value: Amount,
+    /// Zero while the output is unconfirmed.
+    pub confirmations: u32,
+    /// Whether the output was received (`External`) or is change (`Internal`).
+    #[typeshare(serialized_as = "string")]
+    pub keychain: KeychainKind,
+}
+
+/// What an address was used for, so the transactions of the wallet can be matched with swaps
+/// and withdrawals.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    /// The swap the address belongs to, if any.
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Option<Uuid>,
+}
+
+/// Which outputs of this wallet a transaction may spend.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoinControl {
+    /// Outputs the transaction has to spend.
+    pub utxo_include: Vec<OutPoint>,
+    /// Outputs the transaction must not spend.
+    pub utxo_exclude: Vec<OutPoint>,
+}
+
+impl CoinControl {
+    fn apply<Cs>(&self, tx_builder: &mut TxBuilder<'_, Cs>) -> Result<()> {
+        if let Some(outpoint) = self
+            .utxo_include
+            .iter()
+            .find(|outpoint| self.utxo_exclude.contains(outpoint))
+        {
+            bail!("Output {} cannot be both included and excluded", outpoint);
+        }
+
+        if !self.utxo_include.is_empty() {
+            tx_builder
+                .add_utxos(&self.utxo_include)
+                .context("Cannot spend the included outputs")?;
+        }
+        tx_builder.unspendable(self.utxo_exclude.clone());
+
+        Ok(())
+    }
+}
+
 /// Defines a watchable transaction.
 ///
 /// For a transaction to be watchable, we need to know two things: Its
@@ -383,13 +698,24 @@ impl Wallet {
     const SCAN_BATCH_SIZE: u32 = 32;
     /// The number of maximum chunks to use when syncing
     const SCAN_CHUNKS: u32 = 5;
+    /// How long syncing a single chunk should take. The slower the Electrum servers answer,
+    /// the fewer scripts fit into a chunk.
+    const SYNC_CHUNK_DURATION: Duration = Duration::from_secs(2);
+    /// The scripts in a sync chunk as long as we don't know how fast the servers answer
+    const SPKS_PER_SYNC_CHUNK: u32 = 500;
+    /// The chunks we may sync concurrently even with a single Electrum server
+    const MIN_SYNC_CHUNKS: u32 = 3;
+    /// The number of scripts in every chunk of the full scan.
+    /// A round of chunks covers exactly one stop gap of a keychain.
+    const FULL_SCAN_CHUNK_SIZE: u32 = Self::SCAN_STOP_GAP / Self::SCAN_CHUNKS;
 
     /// Maximum time we are willing to spend retrying a wallet sync
     const SYNC_MAX_ELAPSED_TIME: Duration = Duration::from_secs(15);
 
     const WALLET_PARENT_DIR_NAME: &str = "wallet";
     const WALLET_DIR_NAME: &str = "wallet-post-bdk-1.0";
-    const WALLET_FILE_NAME: &str = "wallet-db.sqlite";
+    const ELECTRUM_STATS_FILE_NAME: &str = "electrum-servers.sqlite";
+    const HEADER_CHECKPOINT_FILE_NAME: &str = "header-checkpoint.json";
 
     async fn get_pre_1_0_bdk_wallet_export(
         data_dir: impl AsRef<Path>,
@@ -444,17 +770,28 @@ impl Wallet {
         env_config: crate::env::Config,
         tauri_handle: Option<TauriHandle>,
     ) -> Result<Wallet<bdk_wallet::rusqlite::Connection, Client>> {
-        // Construct the private key, directory and wallet file for the new (>= 1.0.0) bdk wallet
+        // Construct the descriptors, directory and wallet file for the new (>= 1.0.0) bdk wallet
         let xprivkey = seed.derive_extended_private_key(env_config.bitcoin_network)?;
+        let descriptors = WalletDescriptors::from_xpriv(xprivkey, DescriptorType::Bip84, network)?;
         let wallet_dir = data_dir
             .as_ref()
             .join(Self::WALLET_PARENT_DIR_NAME)
             .join(Self::WALLET_DIR_NAME);
-        let wallet_path = wallet_dir.join(Self::WALLET_FILE_NAME);
+        let wallet_path = wallet_dir.join(DescriptorType::Bip84.wallet_file_name());
         let wallet_exists = wallet_path.exists();
 
         // Connect to the electrum server.
-        let client = Client::new(electrum_rpc_urls, sync_interval).await?;
+        let wallet_parent_dir = data_dir.as_ref().join(Self::WALLET_PARENT_DIR_NAME);
+        let electrum_config = ElectrumBalancerConfig {
+            stats_db_path: Some(wallet_parent_dir.join(Self::ELECTRUM_STATS_FILE_NAME)),
+            ..Default::default()
+        };
+        let client = Client::with_config(electrum_rpc_urls, sync_interval, electrum_config)
+            .await?
+            .verify_headers(
+                network,
+                Some(wallet_parent_dir.join(Self::HEADER_CHECKPOINT_FILE_NAME)),
+            );
 
         // Make sure the wallet directory exists.
         tokio::fs::create_dir_all(&wallet_dir).await?;
@@ -465,7 +802,7 @@ impl Wallet {
         // If the new Bitcoin wallet (> 1.0.0 bdk) already exists, we open it
         if wallet_exists {
             Self::create_existing(
-                xprivkey,
+                descriptors,
                 network,
                 client,
                 connection()?,
@@ -481,7 +818,7 @@ impl Wallet {
             let export = Self::get_pre_1_0_bdk_wallet_export(data_dir, network, seed).await?;
 
             Self::create_new(
-                xprivkey,
+                descriptors,
                 network,
                 client,
                 connection,
@@ -508,7 +845,11 @@ impl Wallet {
         tauri_handle: Option<TauriHandle>,
     ) -> Result<Wallet<bdk_wallet::rusqlite::Connection, Client>> {
         Self::create_new(
-            seed.derive_extended_private_key(network)?,
+            WalletDescriptors::from_xpriv(
+                seed.derive_extended_private_key(network)?,
+                DescriptorType::Bip84,
+                network,
+            )?,
             network,
             Client::new(electrum_rpc_urls, sync_interval)
                 .await
@@ -530,7 +871,7 @@ impl Wallet {
     /// This is a private API so we allow too many arguments.
     #[allow(clippy::too_many_arguments)]
     async fn create_new<Persister>(
-        xprivkey: Xpriv,
+        descriptors: WalletDescriptors,
         network: Network,
         client: Client,
         persister_constructor: impl FnOnce() -> Result<Persister>,
@@ -544,13 +885,12 @@ impl Wallet {
         Persister: WalletPersister + Sized,
         <Persister as WalletPersister>::Error: std::error::Error + Send + Sync + 'static,
     {
-        let external_descriptor = Bip84(xprivkey, KeychainKind::External)
-            .build(network)
-            .context("Failed to build external wallet descriptor")?;
-
-        let internal_descriptor = Bip84(xprivkey, KeychainKind::Internal)
-            .build(network)
-            .context("Failed to build change wallet descriptor")?;
+        let WalletDescriptors {
+            external: external_descriptor,
+            internal: internal_descriptor,
+            descriptor_type,
+            ..
+        } = descriptors;
 
         // Build the wallet without a persister
         // because we create the persistence AFTER the full scan
@@ -576,34 +916,9 @@ impl Wallet {
 
         tracing::info!("Starting initial Bitcoin wallet scan. This might take a while...");
 
-        let progress_handle = tauri_handle.new_background_process_with_initial_progress(
-            TauriBackgroundProgress::FullScanningBitcoinWallet,
-            TauriBitcoinFullScanProgress::Unknown,
-        );
-
-        let progress_handle_clone = progress_handle.clone();
-
-        let callback = sync_ext::InnerSyncCallback::new(move |consumed, total| {
-            progress_handle_clone.update(TauriBitcoinFullScanProgress::Known {
-                current_index: consumed,
-                assumed_total: total,
-            });
-        }).chain(sync_ext::InnerSyncCallback::new(move |consumed, total| {
-            tracing::debug!(
-                "Full scanning Bitcoin wallet, currently at index {}. We will scan around {} in total.",
-                consumed,
-                total
-            );
-        }).throttle_callback(10.0)).to_full_scan_callback(Self::SCAN_STOP_GAP, 100);
-
-        let full_scan = wallet.start_full_scan().inspect(callback);
+        let (progress_handle, callback) = Self::full_scan_progress(&tauri_handle);
 
-        let full_scan_response = client.inner.get_any_client().await?.full_scan(
-            full_scan,
-            Self::SCAN_STOP_GAP as usize,
-            Self::SCAN_BATCH_SIZE as usize,
-            true,
-        )?;
+        let full_scan_responses = Self::chunked_full_scan(&client.inner, &wallet, callback).await?;
 
         // Only create the persister once we have the full scan result
         let mut persister = persister_constructor()?;
@@ -614,8 +929,10 @@ impl Wallet {
             .create_wallet(&mut persister)
             .context("Failed to create wallet with persister")?;
 
-        // Apply the full scan result to the wallet
-        wallet.apply_update(full_scan_response)?;
+        // Apply the full scan results to the wallet
+        for full_scan_response in full_scan_responses {
+            wallet.apply_update(full_scan_response)?;
+        }
         wallet.persist(&mut persister)?;
 
         progress_handle.finish();
@@ -646,13 +963,15 @@ impl Wallet {
             network,
             finality_confirmations,
             target_block,
+            descriptor_type,
+            swap_db: None,
         })
     }
 
     /// Load existing wallet data from the database
     #[allow(clippy::too_many_arguments)]
     async fn create_existing<Persister>(
-        xprivkey: Xpriv,
+        descriptors: WalletDescriptors,
         network: Network,
         client: Client,
         mut persister: Persister,
@@ -665,13 +984,12 @@ impl Wallet {
         Persister: WalletPersister + Sized,
         <Persister as WalletPersister>::Error: std::error::Error + Send + Sync + 'static,
     {
-        let external_descriptor = Bip84(xprivkey, KeychainKind::External)
-            .build(network)
-            .context("Failed to build external wallet descriptor")?;
-
-        let internal_descriptor = Bip84(xprivkey, KeychainKind::Internal)
-            .build(network)
-            .context("Failed to build change wallet descriptor")?;
+        let WalletDescriptors {
+            external: external_descriptor,
+            internal: internal_descriptor,
+            descriptor_type,
+            ..
+        } = descriptors;
 
         tracing::debug!("Loading existing Bitcoin wallet from database");
 
@@ -705,11 +1023,44 @@ impl Wallet {
             network,
             finality_confirmations,
             target_block,
+            descriptor_type,
+            swap_db: None,
         };
 
         Ok(wallet)
     }
 
+    /// Finalize a PSBT created by [`Wallet::create_unsigned_psbt`] and signed elsewhere, and
+    /// publish the transaction.
+    pub async fn broadcast_signed_psbt(&self, psbt: PartiallySignedTransaction) -> Result<Txid> {
+        let transaction = self
+            .finalize_signed_psbt(psbt)
+            .await
+            .context("Failed to finalize signed PSBT")?;
+
+        let (txid, _) = self.broadcast(transaction, "withdraw").await?;
+
+        Ok(txid)
+    }
+
+    /// Merge our outputs into one if the fee rate is at most `max_fee_rate`, see
+    /// [`Wallet::build_consolidation`].
+    ///
+    /// Returns the id of the published transaction, if we published one.
+    pub async fn consolidate_utxos(&self, max_fee_rate: FeeRate) -> Result<Option<Txid>> {
+        let Some(psbt) = self.build_consolidation(max_fee_rate).await? else {
+            return Ok(None);
+        };
+
+        let num_inputs = psbt.unsigned_tx.input.len();
+        let transaction = self.sign_and_finalize(psbt).await?;
+        let (txid, _) = self.broadcast(transaction, "consolidation").await?;
+
+        tracing::info!(%txid, %num_inputs, "Consolidated Bitcoin outputs");
+
+        Ok(Some(txid))
+    }
+
     /// Broadcast the given transaction to the network and emit a tracing statement
     /// if done so successfully.
     ///
@@ -788,6 +1139,147 @@ impl Wallet {
         Ok((txid, subscription))
     }
 
+    /// Broadcast a transaction the protocol depends on, like cancel, refund or punish.
+    ///
+    /// Works like [`Wallet::broadcast`], but the transaction is also put into the broadcast
+    /// queue of the database. It is broadcast again with a backoff, also after a restart,
+    /// until a server reports it in the mempool or in a block, or a transaction spending the
+    /// same output is confirmed. The error of the first attempt is still returned.
+    pub async fn broadcast_with_retry(
+        &self,
+        transaction: Transaction,
+        kind: &str,
+    ) -> Result<(Txid, Subscription)> {
+        if let Some(db) = &self.swap_db {
+            if let Err(error) = db.insert_bitcoin_broadcast(&transaction, kind).await {
+                tracing::warn!(
+                    txid = %transaction.compute_txid(), %kind,
+                    "Failed to queue broadcast: {:#}", error
+                );
+            }
+        }
+
+        let result = self.broadcast(transaction.clone(), kind).await;
+        self.spawn_broadcast_retry(transaction, kind.to_string(), BROADCAST_RETRY_MAX_AGE)
+            .await;
+
+        result
+    }
+
+    /// Keep broadcasting the transaction until a server reports it, a transaction spending
+    /// the same output is confirmed or `max_age` passed, then remove it from the broadcast
+    /// queue.
+    ///
+    /// Does nothing if a task is already broadcasting the transaction.
+    async fn spawn_broadcast_retry(
+        &self,
+        transaction: Transaction,
+        kind: String,
+        max_age: Duration,
+    ) {
+        let client = self.electrum_client.clone();
+        let swap_db = self.swap_db.clone();
+        let txid = transaction.compute_txid();
+
+        // The state machine broadcasts the queued transactions again after a restart
+        if !client.lock().await.retrying_broadcasts.insert(txid) {
+            tracing::trace!(%txid, %kind, "Bitcoin transaction is already being broadcast again");
+            return;
+        }
+
+        tokio::spawn(
+            async move {
+                let backoff = backoff::ExponentialBackoffBuilder::new()
+                    .with_initial_interval(BROADCAST_RETRY_INITIAL_INTERVAL)
+                    .with_max_interval(BROADCAST_RETRY_MAX_INTERVAL)
+                    .with_max_elapsed_time(Some(max_age))
+                    .build();
+
+                let result = backoff::future::retry_notify(
+                    backoff,
+                    || async {
+                        // Only ask the servers once the first attempt had some time to propagate
+                        tokio::time::sleep(BROADCAST_RETRY_INITIAL_INTERVAL).await;
+                        let mut client = client.lock().await;
+
+                        let Err(error) = client.rebroadcast_unless_seen(&transaction).await else {
+                            return Ok(());
+                        };
+
+                        // Once a conflicting transaction is confirmed, ours can never be mined
+                        match client.confirmed_conflict(&transaction).await {
+                            Ok(Some(conflict)) => Err(backoff::Error::permanent(anyhow!(
+                                "Bitcoin transaction {} spending the same output was confirmed",
+                                conflict
+                            ))),
+                            Ok(None) => Err(backoff::Error::transient(error)),
+                            Err(conflict_error) => {
+                                tracing::debug!(
+                                    %txid,
+                                    "Failed to look for conflicting transactions: {:#}",
+                                    conflict_error
+                                );
+                                Err(backoff::Error::transient(error))
+                            }
+                        }
+                    },
+                    |error, wait: Duration| {
+                        tracing::debug!(
+                            %txid, %kind,
+                            "Bitcoin transaction not seen yet, broadcasting again in {}s: {:#}",
+                            wait.as_secs(), error
+                        );
+                    },
+                )
+                .await;
+
+                match result {
+                    Ok(()) => tracing::debug!(%txid, %kind, "Queued Bitcoin transaction was seen"),
+                    Err(error) => tracing::warn!(
+                        %txid, %kind,
+                        "Giving up on broadcasting Bitcoin transaction: {:#}",
+                        error
+                    ),
+                }
+
+                if let Some(db) = &swap_db {
+                    if let Err(error) = db.remove_bitcoin_broadcast(txid).await {
+                        tracing::warn!(%txid, "Failed to remove queued broadcast: {:#}", error);
+                    }
+                }
+
+                client.lock().await.retrying_broadcasts.remove(&txid);
+            }
+            .instrument(debug_span!("BitcoinBroadcastQueue")),
+        );
+    }
+
+    /// Pick up the broadcast queue of the last run.
+    async fn resume_broadcasts(&self) {
+        let Some(db) = &self.swap_db else {
+            return;
+        };
+
+        let broadcasts = match db.get_bitcoin_broadcasts().await {
+            Ok(broadcasts) => broadcasts,
+            Err(error) => {
+                tracing::warn!("Failed to load broadcast queue: {:#}", error);
+                return;
+            }
+        };
+
+        for (transaction, kind, entered_at) in broadcasts {
+            let age = (time::OffsetDateTime::now_utc() - entered_at).unsigned_abs();
+            let max_age = BROADCAST_RETRY_MAX_AGE.saturating_sub(age);
+
+            tracing::debug!(
+                txid = %transaction.compute_txid(), %kind,
+                "Resuming broadcast of Bitcoin transaction"
+            );
+            self.spawn_broadcast_retry(transaction, kind, max_age).await;
+        }
+    }
+
     pub async fn get_raw_transaction(&self, txid: Txid) -> Result<Option<Arc<Transaction>>> {
         self.get_tx(txid)
             .await
@@ -821,70 +1313,133 @@ impl Wallet {
     pub async fn subscribe_to(&self, tx: impl Watchable + Send + Sync + 'static) -> Subscription {
         let txid = tx.id();
         let script = tx.script();
+        let key = (txid, script.clone());
 
-        let initial_status = match self
-            .electrum_client
-            .lock()
-            .await
-            .status_of_script(&tx, false)
-            .await
-        {
-            Ok(status) => Some(status),
+        let mut client = self.electrum_client.lock().await;
+
+        if let Some(existing) = client.subscriptions.get(&key) {
+            return Subscription {
+                receiver: existing.sender.subscribe(),
+                finality_confirmations: self.finality_confirmations,
+                txid,
+            };
+        }
+
+        let initial_status = match client.status_of_script(&tx, false).await {
+            Ok(status) => Some(trace_status_change(txid, None, status)),
             Err(err) => {
                 tracing::debug!(%txid, %err, "Failed to get initial status for subscription. We won't notify the caller and will try again later.");
                 None
             }
         };
 
-        let sub = self
-            .electrum_client
-            .lock()
-            .await
-            .subscriptions
-            .entry((txid, script.clone()))
-            .or_insert_with(|| {
-                let (sender, receiver) = watch::channel(ScriptStatus::Unseen);
-                let client = self.electrum_client.clone();
+        let (sender, receiver) = watch::channel(initial_status.unwrap_or(ScriptStatus::Unseen));
+        client.subscriptions.insert(
+            key,
+            SubscriptionSender {
+                sender: Arc::new(sender),
+                last_status: initial_status,
+                resumable: true,
+            },
+        );
 
-                tokio::spawn(async move {
-                    let mut last_status = initial_status;
+        if !client.subscription_reader_running {
+            client.subscription_reader_running = true;
+            self.spawn_subscription_reader();
+        }
 
-                    loop {
-                        let new_status = client.lock()
-                            .await
-                            .status_of_script(&tx, false)
-                            .await
-                            .unwrap_or_else(|error| {
-                                tracing::warn!(%txid, "Failed to get status of script: {:#}", error);
-                                ScriptStatus::Retrying
-                            });
+        drop(client);
+
+        if let Some(db) = &self.swap_db {
+            if let Err(error) = db.insert_bitcoin_subscription(txid, script).await {
+                tracing::warn!(%txid, "Failed to persist subscription: {:#}", error);
+            }
+        }
+
+        Subscription {
+            receiver,
+            finality_confirmations: self.finality_confirmations,
+            txid,
+        }
+    }
 
-                        if new_status != ScriptStatus::Retrying
-                        {
-                            last_status = Some(trace_status_change(txid, last_status, new_status));
+    /// Start the task that reads the Electrum notifications for all subscriptions and sends
+    /// the new statuses to the subscribers. It stops once there are no subscriptions left.
+    fn spawn_subscription_reader(&self) {
+        let client = self.electrum_client.clone();
+        let swap_db = self.swap_db.clone();
+        let finality_confirmations = self.finality_confirmations;
 
-                            let all_receivers_gone = sender.send(new_status).is_err();
+        tokio::spawn(
+            async move {
+                loop {
+                    tokio::time::sleep(SUBSCRIPTION_POLL_INTERVAL).await;
 
-                            if all_receivers_gone {
-                                tracing::debug!(%txid, "All receivers gone, removing subscription");
-                                client.lock().await.subscriptions.remove(&(txid, script));
-                                return;
-                            }
+                    let (finished, stop) = {
+                        let mut client = client.lock().await;
+
+                        if let Err(error) = client.read_notifications().await {
+                            tracing::warn!("Failed to read Electrum notifications: {:#}", error);
+                        }
+
+                        let finished = client.notify_subscribers(finality_confirmations);
+                        let stop = client.subscriptions.is_empty();
+                        if stop {
+                            client.subscription_reader_running = false;
                         }
 
-                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        (finished, stop)
+                    };
+
+                    // Once a transaction is final or nobody waits for it anymore, there is
+                    // nothing left to resume after a restart
+                    if let Some(db) = &swap_db {
+                        for (txid, script) in finished {
+                            if let Err(error) = db.remove_bitcoin_subscription(txid, script).await
+                            {
+                                tracing::warn!(%txid, "Failed to forget persisted subscription: {:#}", error);
+                            }
+                        }
                     }
-                }.instrument(debug_span!("BitcoinWalletSubscription")));
 
-                Subscription {
-                    receiver,
-                    finality_confirmations: self.finality_confirmations,
-                    txid,
+                    if stop {
+                        return;
+                    }
                 }
-            })
-            .clone();
+            }
+            .instrument(debug_span!("BitcoinWalletSubscriptions")),
+        );
+    }
+
+    /// Subscribe again to the transactions we were waiting for when the wallet was last
+    /// running. Each of them is tracked until it is final, even if no state machine picks it
+    /// up again.
+    async fn resume_subscriptions(&self) {
+        let Some(db) = &self.swap_db else {
+            return;
+        };
+
+        let subscriptions = match db.get_bitcoin_subscriptions().await {
+            Ok(subscriptions) => subscriptions,
+            Err(error) => {
+                tracing::warn!("Failed to load persisted subscriptions: {:#}", error);
+                return;
+            }
+        };
+
+        for (txid, script) in subscriptions {
+            tracing::debug!(%txid, "Resuming subscription to Bitcoin transaction");
 
-        sub
+            let subscription = self.subscribe_to((txid, script)).await;
+            let finality_confirmations = self.finality_confirmations;
+
+            tokio::spawn(async move {
+                // Keeps the subscription alive until the transaction is final
+                let _ = subscription
+                    .wait_until(|status| status.is_confirmed_with(finality_confirmations))
+                    .await;
+            });
+        }
     }
 
     pub async fn wallet_export(&self, role: &str) -> Result<FullyNodedExport> {
@@ -899,6 +1454,12 @@ impl Wallet {
         }
     }
 
+    /// How the configured Electrum servers handled our requests, most reliable first as of
+    /// the last start.
+    pub async fn electrum_server_stats(&self) -> Vec<ServerStats> {
+        self.electrum_client.lock().await.inner.server_stats()
+    }
+
     /// Get a transaction from the Electrum server or the cache.
     pub async fn get_tx(&self, txid: Txid) -> Result<Option<Arc<Transaction>>> {
         let client = self.electrum_client.lock().await;
@@ -910,19 +1471,297 @@ impl Wallet {
         Ok(tx)
     }
 
-    /// Create a vector of sync requests
+    /// Perform the initial full scan of a wallet in parallel chunks
     ///
-    /// This splits up all the revealed spks and builds a sync request for each chunk.
-    /// Useful for syncing the whole wallet in chunks.
-    async fn chunked_sync_request(
-        &self,
-        max_num_chunks: u32,
-        batch_size: u32,
-    ) -> Vec<SyncRequestBuilderFactory> {
-        #[allow(clippy::type_complexity)]
-        let (spks, chain_tip): (Vec<((KeychainKind, u32), ScriptBuf)>, CheckPoint) = {
-            let wallet = self.wallet.lock().await;
-
+    /// Every round scans the next [`Self::SCAN_CHUNKS`] ranges of [`Self::FULL_SCAN_CHUNK_SIZE`]
+    /// scripts of every keychain concurrently. A keychain is done once the last
+    /// [`Self::SCAN_STOP_GAP`] scripts we scanned are unused and we've covered all the
+    /// addresses revealed in the wallet.
+    ///
+    /// The callback is called with the cumulative progress of all chunks. The total grows
+    /// with every round we start.
+    async fn chunked_full_scan(
+        balancer: &ElectrumBalancer,
+        wallet: &bdk_wallet::Wallet,
+        callback: sync_ext::SyncCallback,
+    ) -> Result<Vec<FullScanResult<KeychainKind>>> {
+        let chain_tip = wallet.latest_checkpoint();
+        let spk_iters = wallet.spk_index().all_unbounded_spk_iters();
+
+        // The next index to scan and the last used index of every keychain we're still scanning
+        let mut keychains: BTreeMap<KeychainKind, (u32, Option<u32>)> = spk_iters
+            .keys()
+            .map(|keychain| (*keychain, (0, None)))
+            .collect();
+
+        let cumulative_progress_handle = sync_ext::CumulativeProgress::new().into_arc_mutex_sync();
+        let mut num_chunks: u64 = 0;
+        let mut full_scan_responses = Vec::new();
+
+        let start_time = Instant::now();
+
+        while !keychains.is_empty() {
+            let mut futures = Vec::new();
+
+            for (keychain, (next_index, _)) in &keychains {
+                for chunk in 0..Self::SCAN_CHUNKS {
+                    let start = next_index + chunk * Self::FULL_SCAN_CHUNK_SIZE;
+                    let spks = spk_iters[keychain]
+                        .clone()
+                        .skip(start as usize)
+                        .take(Self::FULL_SCAN_CHUNK_SIZE as usize)
+                        .collect();
+
+                    let factory = FullScanRequestBuilderFactory {
+                        chain_tip: chain_tip.clone(),
+                        keychain: *keychain,
+                        spks,
+                    };
+                    let callback = cumulative_progress_handle
+                        .clone()
+                        .chunk_callback(callback.clone(), num_chunks);
+                    num_chunks += 1;
+
+                    futures
+                        .push(Self::full_scan_chunk(balancer, factory, callback).in_current_span());
+                }
+            }
+
+            // Execute all chunks of this round concurrently
+            let results = futures::future::join_all(futures).await;
+
+            for result in results {
+                let response = result?;
+
+                for (keychain, last_active_index) in &response.last_active_indices {
+                    if let Some((_, last_used)) = keychains.get_mut(keychain) {
+                        *last_used = (*last_used).max(Some(*last_active_index));
+                    }
+                }
+
+                full_scan_responses.push(response);
+            }
+
+            keychains.retain(|keychain, (next_index, last_used)| {
+                *next_index += Self::SCAN_CHUNKS * Self::FULL_SCAN_CHUNK_SIZE;
+
+                let unused_since = last_used.map_or(0, |index| index + 1);
+                let revealed_until = wallet
+                    .spk_index()
+                    .last_revealed_index(*keychain)
+                    .map_or(0, |index| index + 1);
+
+                *next_index < unused_since + Self::SCAN_STOP_GAP || *next_index < revealed_until
+            });
+        }
+
+        tracing::trace!(
+            "Full scanned Bitcoin wallet in {:?} with {} chunks of {} scripts",
+            start_time.elapsed(),
+            num_chunks,
+            Self::FULL_SCAN_CHUNK_SIZE
+        );
+
+        Ok(full_scan_responses)
+    }
+
+    /// Scan a single chunk of the full scan, calling the callback on progress updates.
+    async fn full_scan_chunk(
+        balancer: &ElectrumBalancer,
+        full_scan_factory: FullScanRequestBuilderFactory,
+        callback: InnerSyncCallback,
+    ) -> Result<FullScanResult<KeychainKind>> {
+        let total = full_scan_factory.spks.len() as u64;
+        let callback = Arc::new(SyncMutex::new(callback));
+
+        // Scan through the balancer so a failing Electrum server doesn't abort the scan
+        let full_scan_response = balancer
+            .call_async("full_scan", move |client| {
+                let callback = callback.clone();
+                let mut consumed = 0;
+                let full_scan = full_scan_factory
+                    .clone()
+                    .build()
+                    .inspect(move |_, _, _| {
+                        consumed += 1;
+                        if let Ok(mut callback) = callback.lock() {
+                            callback.call(consumed, total);
+                        }
+                    })
+                    .build();
+
+                // The stop gap is checked across all chunks of a keychain,
+                // so every chunk is scanned completely
+                client.full_scan(full_scan, usize::MAX, Self::SCAN_BATCH_SIZE as usize, true)
+            })
+            .await?;
+
+        Ok(full_scan_response)
+    }
+
+    /// Report the cumulative progress of a full scan to the UI and the logs.
+    fn full_scan_progress(
+        tauri_handle: &Option<TauriHandle>,
+    ) -> (
+        TauriBackgroundProgressHandle<TauriBitcoinFullScanProgress>,
+        sync_ext::SyncCallback,
+    ) {
+        let progress_handle = tauri_handle.new_background_process_with_initial_progress(
+            TauriBackgroundProgress::FullScanningBitcoinWallet,
+            TauriBitcoinFullScanProgress::Unknown,
+        );
+
+        let progress_handle_clone = progress_handle.clone();
+
+        let callback = sync_ext::InnerSyncCallback::new(move |consumed, total| {
+            progress_handle_clone.update(TauriBitcoinFullScanProgress::Known {
+                current_index: consumed,
+                assumed_total: total,
+            });
+        }).chain(sync_ext::InnerSyncCallback::new(move |consumed, total| {
+            tracing::debug!(
+                "Full scanning Bitcoin wallet, currently at index {}. We will scan around {} in total.",
+                consumed,
+                total
+            );
+        }).throttle_callback(10.0)).finalize();
+
+        (progress_handle, callback)
+    }
+
+    /// Wipe the chain data of the wallet and scan the blockchain for its transactions again,
+    /// just like we do when creating a wallet.
+    ///
+    /// Only the descriptors, the revealed addresses and our unconfirmed transactions are kept.
+    /// This repairs a wallet with a wrong balance, e.g. after restoring an outdated copy of the
+    /// wallet database.
+    ///
+    /// The rescanned wallet is persisted to a new database file which then replaces the old one,
+    /// so we never end up with a half wiped database.
+    pub async fn full_rescan(&self) -> Result<()> {
+        // Start from a wallet with the same descriptors but without any chain data
+        let (external_descriptor, internal_descriptor, mut fresh_wallet) = {
+            let wallet = self.wallet.lock().await;
+
+            let descriptor = |keychain: KeychainKind| {
+                (
+                    wallet.public_descriptor(keychain).clone(),
+                    wallet.get_signers(keychain).as_key_map(wallet.secp_ctx()),
+                )
+            };
+            let external_descriptor = descriptor(KeychainKind::External);
+            let internal_descriptor = descriptor(KeychainKind::Internal);
+
+            let mut fresh_wallet = bdk_wallet::Wallet::create(
+                external_descriptor.clone(),
+                internal_descriptor.clone(),
+            )
+            .network(self.network)
+            .create_wallet_no_persist()
+            .context("Failed to create persisterless wallet")?;
+
+            // Scan at least as far as we have handed out addresses
+            reveal_addresses_like(&mut fresh_wallet, &wallet);
+
+            (external_descriptor, internal_descriptor, fresh_wallet)
+        };
+
+        tracing::info!("Starting full rescan of the Bitcoin wallet. This might take a while...");
+
+        let (progress_handle, callback) = Self::full_scan_progress(&self.tauri_handle);
+
+        // Don't hold the lock while scanning, the wallet keeps working in the meantime
+        let balancer = self.electrum_client.lock().await.inner.clone();
+        let full_scan_responses = Self::chunked_full_scan(&balancer, &fresh_wallet, callback).await;
+
+        progress_handle.finish();
+
+        for full_scan_response in full_scan_responses? {
+            fresh_wallet.apply_update(full_scan_response)?;
+        }
+
+        // Hold the locks until the rescanned wallet replaced the old one, so nothing we do with
+        // the wallet in the meantime is lost
+        let mut wallet = self.wallet.lock().await;
+        let mut persister = self.persister.lock().await;
+
+        // Keep the addresses we handed out and the transactions we published during the scan
+        reveal_addresses_like(&mut fresh_wallet, &wallet);
+        let unconfirmed_txs: Vec<_> = wallet
+            .transactions()
+            .filter_map(|tx| match tx.chain_position {
+                ChainPosition::Unconfirmed(last_seen) => Some((tx.tx_node.tx.clone(), last_seen)),
+                ChainPosition::Confirmed(_) => None,
+            })
+            .collect();
+        fresh_wallet.apply_unconfirmed_txs(unconfirmed_txs);
+
+        let changeset = fresh_wallet
+            .take_staged()
+            .context("Rescanned wallet has no changes to persist")?;
+
+        let wallet_path = persister
+            .path()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+
+        match &wallet_path {
+            Some(wallet_path) => {
+                let rescan_path = wallet_path.with_extension("rescan");
+                // Left over by a rescan that didn't finish
+                if rescan_path.exists() {
+                    std::fs::remove_file(&rescan_path)?;
+                }
+
+                let mut rescan_connection = Connection::open(&rescan_path)?;
+                WalletPersister::initialize(&mut rescan_connection)?;
+                WalletPersister::persist(&mut rescan_connection, &changeset)?;
+                drop(rescan_connection);
+
+                // Close the old database before replacing it
+                *persister = Connection::open_in_memory()?;
+                let replaced = std::fs::rename(&rescan_path, wallet_path)
+                    .context("Failed to replace the wallet database with the rescanned one");
+
+                // Reopen the wallet database, the old one if replacing it failed
+                *persister = Connection::open(wallet_path)?;
+                replaced?;
+            }
+            None => {
+                let mut connection = Connection::open_in_memory()?;
+                WalletPersister::initialize(&mut connection)?;
+                WalletPersister::persist(&mut connection, &changeset)?;
+
+                *persister = connection;
+            }
+        }
+
+        *wallet = bdk_wallet::Wallet::load()
+            .descriptor(KeychainKind::External, Some(external_descriptor))
+            .descriptor(KeychainKind::Internal, Some(internal_descriptor))
+            .extract_keys()
+            .load_wallet(&mut *persister)
+            .context("Failed to load rescanned wallet")?
+            .context("No wallet found in database after rescan")?;
+
+        tracing::info!("Full rescan of the Bitcoin wallet completed");
+
+        Ok(())
+    }
+
+    /// Create a vector of sync requests
+    ///
+    /// This splits up all the revealed spks and builds a sync request for each chunk.
+    /// Useful for syncing the whole wallet in chunks.
+    async fn chunked_sync_request(
+        &self,
+        num_servers: usize,
+        latency: Option<Duration>,
+    ) -> Vec<SyncRequestBuilderFactory> {
+        #[allow(clippy::type_complexity)]
+        let (spks, chain_tip): (Vec<((KeychainKind, u32), ScriptBuf)>, CheckPoint) = {
+            let wallet = self.wallet.lock().await;
+
             let spks = wallet
                 .spk_index()
                 .revealed_spks(..)
@@ -942,16 +1781,7 @@ impl Wallet {
             return vec![];
         }
 
-        // We only use as many chunks as are useful to reduce the number of requests
-        // given the batch size
-        // This means: num_chunks * batch_size < total number of spks
-        //
-        // E.g we have 1000 spks and a batch size of 100, we only use 10 chunks at most
-        // If we used 20 chunks we would not maximize the batch size because
-        // each chunk would have 50 spks (which is less than the batch size)
-        //
-        // At least one chunk is always required. At most total_spks / batch_size or the provided num_chunks (whichever is smaller)
-        let num_chunks = max_num_chunks.min(total_spks / batch_size).max(1);
+        let num_chunks = Self::num_sync_chunks(total_spks, num_servers, latency);
         let chunk_size = (total_spks + num_chunks - 1) / num_chunks;
 
         let mut chunks = Vec::new();
@@ -967,18 +1797,62 @@ impl Wallet {
         chunks
     }
 
+    /// How many chunks to sync `total_spks` scripts in.
+    ///
+    /// A chunk requests its scripts one batch after the other, so it gets as many batches as
+    /// the servers answer within [`Self::SYNC_CHUNK_DURATION`], judging by their average
+    /// `latency`. Chunks are synced concurrently: one per server, but at least
+    /// [`Self::MIN_SYNC_CHUNKS`] and at most [`Self::SCAN_CHUNKS`].
+    ///
+    /// Every chunk holds at least one full batch and there is always at least one chunk.
+    fn num_sync_chunks(total_spks: u32, num_servers: usize, latency: Option<Duration>) -> u32 {
+        let spks_per_chunk = match latency {
+            Some(latency) => {
+                let batches =
+                    (Self::SYNC_CHUNK_DURATION.as_millis() / latency.as_millis().max(1)).max(1);
+
+                u32::try_from(batches)
+                    .unwrap_or(u32::MAX)
+                    .saturating_mul(Self::SCAN_BATCH_SIZE)
+            }
+            None => Self::SPKS_PER_SYNC_CHUNK,
+        };
+        let max_num_chunks = u32::try_from(num_servers)
+            .unwrap_or(u32::MAX)
+            .clamp(Self::MIN_SYNC_CHUNKS, Self::SCAN_CHUNKS);
+
+        max_num_chunks
+            .min(total_spks / Self::SCAN_BATCH_SIZE)
+            .min(total_spks.div_ceil(spks_per_chunk))
+            .max(1)
+    }
+
     /// Sync the wallet with the Blockchain
     /// Spawn `num_chunks` tasks to sync the wallet in parallel
     /// Call the callback with the cumulative progress of the sync
     pub async fn chunked_sync_with_callback(&self, callback: sync_ext::SyncCallback) -> Result<()> {
+        // The average latency of the servers that answered us before
+        let server_stats = self.electrum_client.lock().await.inner.server_stats();
+        let latencies: Vec<f64> = server_stats
+            .iter()
+            .filter_map(|stats| stats.avg_latency_ms)
+            .collect();
+        let latency = (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<f64>() / latencies.len() as f64)
+            .and_then(|latency_ms| Duration::try_from_secs_f64(latency_ms / 1000.0).ok());
+
         // Construct the chunks to process
-        let sync_request_factories = self
-            .chunked_sync_request(Self::SCAN_CHUNKS, Self::SCAN_BATCH_SIZE)
-            .await;
+        let sync_request_factories = self.chunked_sync_request(server_stats.len(), latency).await;
+        let num_chunks = sync_request_factories.len();
+        let num_spks: usize = sync_request_factories
+            .iter()
+            .map(|factory| factory.spks.len())
+            .sum();
 
         tracing::debug!(
+            ?latency,
             "Starting to sync Bitcoin wallet with {} concurrent chunks and batch size of {}",
-            sync_request_factories.len(),
+            num_chunks,
             Self::SCAN_BATCH_SIZE
         );
 
@@ -1021,10 +1895,11 @@ impl Wallet {
 
         // Calculate the time taken to sync the wallet
         let duration = start_time.elapsed();
-        tracing::trace!(
-            "Synced Bitcoin wallet in {:?} with {} concurrent chunks and batch size {}",
+        tracing::debug!(
+            "Synced {} scripts of the Bitcoin wallet in {:?} with {} concurrent chunks and batch size {}",
+            num_spks,
             duration,
-            Self::SCAN_CHUNKS,
+            num_chunks,
             Self::SCAN_BATCH_SIZE
         );
 
@@ -1042,11 +1917,10 @@ impl Wallet {
     ) -> Result<()> {
         let callback = Arc::new(SyncMutex::new(callback));
 
-        let sync_response = self
-            .electrum_client
-            .lock()
-            .await
-            .inner
+        // Don't hold the lock while syncing, other chunks are synced concurrently
+        let balancer = self.electrum_client.lock().await.inner.clone();
+
+        let sync_response = balancer
             .call_async("sync_wallet", move |client| {
                 let sync_request_factory = sync_request_factory.clone();
                 let callback = callback.clone();
@@ -1150,6 +2024,247 @@ impl Wallet {
 
         Ok(fee)
     }
+
+    /// Replace one of our unconfirmed transactions with one that pays a fee rate high
+    /// enough to be confirmed within `new_target_block` blocks (replace-by-fee).
+    ///
+    /// Only works for transactions whose inputs are all owned by this wallet. Transactions
+    /// spending the shared swap outputs are pre-signed and have to be bumped with
+    /// [`Wallet::bump_fee_with_child`] instead.
+    ///
+    /// Returns the transaction ID of the replacement.
+    pub async fn bump_fee(&self, txid: Txid, new_target_block: u32) -> Result<Txid> {
+        // The wallet has to know about the transaction to replace it
+        self.sync().await?;
+
+        let fee_rate = self.combined_fee_rate(new_target_block).await?;
+
+        let psbt = {
+            let mut wallet = self.wallet.lock().await;
+
+            let mut tx_builder = wallet
+                .build_fee_bump(txid)
+                .with_context(|| format!("Cannot replace Bitcoin transaction {}", txid))?;
+            tx_builder.fee_rate(fee_rate);
+
+            tx_builder
+                .finish()
+                .context("Failed to build replacement transaction")?
+        };
+
+        let transaction = self.sign_and_finalize(psbt).await?;
+        let (new_txid, _) = self.broadcast(transaction, "fee bump").await?;
+
+        tracing::info!(
+            %txid,
+            %new_txid,
+            fee_rate_sat_vb = fee_rate.to_sat_per_vb_ceil(),
+            "Replaced Bitcoin transaction with a higher fee"
+        );
+
+        Ok(new_txid)
+    }
+
+    /// Speed up an unconfirmed transaction which pays to this wallet by spending its output
+    /// back to ourselves with a fee high enough for both transactions to be confirmed within
+    /// `new_target_block` blocks (child pays for parent).
+    ///
+    /// This works for any transaction paying to us, including the pre-signed redeem and
+    /// punish transactions. If `previous_child` is an earlier child of the same transaction,
+    /// it is replaced instead of adding another one.
+    ///
+    /// Returns the transaction ID of the child.
+    pub async fn bump_fee_with_child(
+        &self,
+        parent_txid: Txid,
+        previous_child: Option<Txid>,
+        new_target_block: u32,
+    ) -> Result<Txid> {
+        let fee_rate = self.combined_fee_rate(new_target_block).await?;
+
+        self.publish_child(parent_txid, previous_child, fee_rate)
+            .await
+    }
+
+    /// Get an unconfirmed transaction paying to this wallet (e.g. a deposit or a redeem
+    /// transaction stuck below the mempool purge rate) confirmed by spending our output with a
+    /// child, so that both transactions together pay `fee_rate`.
+    ///
+    /// Returns the transaction ID of the child.
+    pub async fn cpfp(&self, parent_txid: Txid, fee_rate: FeeRate) -> Result<Txid> {
+        self.publish_child(parent_txid, None, fee_rate).await
+    }
+
+    /// Publish a child of `parent_txid` (or a replacement of `previous_child`) which brings
+    /// the fee rate of the package up to `fee_rate`.
+    ///
+    /// A child we published earlier, e.g. before a restart, is replaced as well, so the
+    /// parent never ends up with two children spending the same output.
+    async fn publish_child(
+        &self,
+        parent_txid: Txid,
+        previous_child: Option<Txid>,
+        fee_rate: FeeRate,
+    ) -> Result<Txid> {
+        // The wallet has to know about the output we spend and the child we replace
+        self.sync().await?;
+
+        let previous_child = match previous_child {
+            Some(child_txid) => Some(child_txid),
+            None => self.unconfirmed_child(parent_txid).await,
+        };
+
+        let parent = self
+            .get_raw_transaction(parent_txid)
+            .await?
+            .with_context(|| format!("Bitcoin transaction {} not found", parent_txid))?;
+        let parent_fee = self.absolute_fee(&parent).await?;
+
+        let min_relay_fee = self.combined_min_relay_fee().await?;
+
+        let previous_child = match previous_child {
+            Some(child_txid) => {
+                let child = self
+                    .get_raw_transaction(child_txid)
+                    .await?
+                    .with_context(|| format!("Bitcoin transaction {} not found", child_txid))?;
+                let child_fee = self.absolute_fee(&child).await?;
+
+                Some((child, child_fee))
+            }
+            None => None,
+        };
+
+        let (psbt, child_fee) = self
+            .build_cpfp_child(
+                &parent,
+                parent_fee,
+                previous_child
+                    .as_ref()
+                    .map(|(child, child_fee)| (&**child, *child_fee)),
+                fee_rate,
+                min_relay_fee,
+            )
+            .await?;
+        let transaction = self.sign_and_finalize(psbt).await?;
+        let (child_txid, _) = self.broadcast(transaction, "fee bump (CPFP)").await?;
+
+        tracing::info!(
+            %parent_txid,
+            %child_txid,
+            %child_fee,
+            fee_rate_sat_vb = fee_rate.to_sat_per_vb_ceil(),
+            "Bumped fee of Bitcoin transaction with a child transaction"
+        );
+
+        Ok(child_txid)
+    }
+
+    /// The unconfirmed transaction of this wallet spending an output of `parent_txid`, if any.
+    ///
+    /// Every transaction we broadcast is kept in the wallet database, so this also finds
+    /// children published before a restart.
+    async fn unconfirmed_child(&self, parent_txid: Txid) -> Option<Txid> {
+        let wallet = self.wallet.lock().await;
+
+        wallet
+            .transactions()
+            .filter(|tx| matches!(tx.chain_position, ChainPosition::Unconfirmed(_)))
+            .find(|tx| {
+                tx.tx_node
+                    .tx
+                    .input
+                    .iter()
+                    .any(|input| input.previous_output.txid == parent_txid)
+            })
+            .map(|tx| tx.tx_node.txid)
+    }
+
+    /// The fee paid by any transaction, looking up the outputs it spends.
+    async fn absolute_fee(&self, transaction: &Transaction) -> Result<Amount> {
+        let mut inputs = Amount::ZERO;
+        for input in &transaction.input {
+            let previous = input.previous_output;
+            let spent = self
+                .get_raw_transaction(previous.txid)
+                .await?
+                .with_context(|| format!("Bitcoin transaction {} not found", previous.txid))?;
+            inputs += spent
+                .output
+                .get(previous.vout as usize)
+                .context("Transaction spends an output that does not exist")?
+                .value;
+        }
+
+        let outputs = transaction.output.iter().map(|output| output.value).sum();
+        inputs
+            .checked_sub(outputs)
+            .context("Transaction spends more than its inputs")
+    }
+
+    /// Wait until the transaction is final, like [`Subscription::wait_until_final`].
+    ///
+    /// If the transaction pays to this wallet and is still unconfirmed after
+    /// `fee_bump_after_blocks` blocks, its fee is bumped with a child transaction, and again
+    /// every `fee_bump_after_blocks` blocks until it is confirmed.
+    pub async fn wait_until_final_bumping_fee(
+        &self,
+        txid: Txid,
+        subscription: &Subscription,
+        fee_bump_after_blocks: Option<u32>,
+    ) -> Result<()> {
+        let Some(fee_bump_after_blocks) = fee_bump_after_blocks else {
+            return subscription.wait_until_final().await;
+        };
+
+        let final_ = subscription.wait_until_final();
+        tokio::pin!(final_);
+
+        let mut waiting_since = self.latest_block_height().await;
+        let mut child = None;
+
+        loop {
+            tokio::select! {
+                result = &mut final_ => return result,
+                _ = tokio::time::sleep(FEE_BUMP_CHECK_INTERVAL) => {}
+            }
+
+            let block_height = self.latest_block_height().await;
+            // We might not have known the chain tip when we started waiting
+            if u32::from(waiting_since) == 0 {
+                waiting_since = block_height;
+            }
+
+            let confirmed = subscription.receiver.borrow().is_confirmed();
+            if confirmed
+                || u32::from(block_height) < u32::from(waiting_since) + fee_bump_after_blocks
+            {
+                continue;
+            }
+
+            tracing::info!(
+                %txid,
+                blocks = fee_bump_after_blocks,
+                "Bitcoin transaction is still unconfirmed, bumping its fee"
+            );
+
+            match self
+                .bump_fee_with_child(txid, child, self.target_block)
+                .await
+            {
+                Ok(child_txid) => child = Some(child_txid),
+                Err(error) => {
+                    tracing::warn!(%txid, "Failed to bump fee of Bitcoin transaction: {:#}", error)
+                }
+            }
+            waiting_since = block_height;
+        }
+    }
+
+    /// The height of the latest block we know about.
+    pub async fn latest_block_height(&self) -> BlockHeight {
+        self.electrum_client.lock().await.latest_block_height
+    }
 }
 
 // These are the methods that are always available, regardless of the persister.
@@ -1171,6 +2286,16 @@ impl<T, C> Wallet<T, C> {
     pub fn target_block(&self) -> u32 {
         self.target_block
     }
+
+    /// The public descriptors of the wallet, see [`PublicDescriptors`].
+    pub async fn export_descriptors(&self) -> PublicDescriptors {
+        let wallet = self.wallet.lock().await;
+
+        PublicDescriptors {
+            external: wallet.public_descriptor(KeychainKind::External).to_string(),
+            internal: wallet.public_descriptor(KeychainKind::Internal).to_string(),
+        }
+    }
 }
 
 impl<Persister, C> Wallet<Persister, C>
@@ -1185,14 +2310,14 @@ where
     /// If the mempool client is available, we use the higher of the two.
     /// If either of the clients fail but the other is successful, we use the successful one.
     /// If both clients fail, we return an error
-    async fn combined_fee_rate(&self) -> Result<FeeRate> {
+    async fn combined_fee_rate(&self, target_block: u32) -> Result<FeeRate> {
         let electrum_future = self
             .cached_electrum_fee_estimator
-            .estimate_feerate(self.target_block);
+            .estimate_feerate(target_block);
         let mempool_future = async {
             match self.cached_mempool_fee_estimator.as_ref() {
                 Some(mempool_client) => mempool_client
-                    .estimate_feerate(self.target_block)
+                    .estimate_feerate(target_block)
                     .await
                     .map(Some),
                 None => Ok(None),
@@ -1334,6 +2459,21 @@ where
         Ok(tx?)
     }
 
+    /// Finalizes a PSBT that was signed outside of this wallet, e.g. on a hardware wallet.
+    pub async fn finalize_signed_psbt(&self, mut psbt: bitcoin::psbt::Psbt) -> Result<Transaction> {
+        let finalized = self
+            .wallet
+            .lock()
+            .await
+            .finalize_psbt(&mut psbt, SignOptions::default())?;
+
+        if !finalized {
+            bail!("PSBT is missing signatures")
+        }
+
+        Ok(psbt.extract_tx()?)
+    }
+
     /// Returns the total Bitcoin balance, which includes pending funds
     pub async fn balance(&self) -> Result<Amount> {
         Ok(self.wallet.lock().await.balance().total())
@@ -1344,6 +2484,166 @@ where
         Ok(self.wallet.lock().await.balance())
     }
 
+    /// Returns the unspent outputs of the wallet, including unconfirmed ones.
+    pub async fn list_unspent(&self) -> Vec<Utxo> {
+        let wallet = self.wallet.lock().await;
+        let latest_block = wallet.latest_checkpoint().height();
+
+        wallet
+            .list_unspent()
+            .map(|output| Utxo {
+                outpoint: output.outpoint,
+                value: output.txout.value,
+                confirmations: match output.chain_position {
+                    ChainPosition::Confirmed(anchor) => {
+                        (latest_block + 1).saturating_sub(anchor.block_id.height)
+                    }
+                    ChainPosition::Unconfirmed(_) => 0,
+                },
+                keychain: output.keychain,
+            })
+            .collect()
+    }
+
+    /// Build a child of `parent` (or a replacement of `previous_child`, given with its fee)
+    /// which brings the fee rate of the package up to `fee_rate`.
+    ///
+    /// Returns the unsigned child and its fee.
+    async fn build_cpfp_child(
+        &self,
+        parent: &Transaction,
+        parent_fee: Amount,
+        previous_child: Option<(&Transaction, Amount)>,
+        fee_rate: FeeRate,
+        min_relay_fee: FeeRate,
+    ) -> Result<(PartiallySignedTransaction, Amount)> {
+        let child_weight = match previous_child {
+            // Replacing the child doesn't change its inputs and outputs
+            Some((child, _)) => child.weight(),
+            // Sign a draft to learn the weight of the child including the witness
+            None => {
+                let draft = self.build_child(parent, None, Amount::ZERO).await?;
+                self.sign_and_finalize(draft).await?.weight()
+            }
+        };
+
+        let child_fee = cpfp_child_fee(
+            parent.weight(),
+            parent_fee,
+            child_weight,
+            fee_rate,
+            min_relay_fee,
+        )?
+        .with_context(|| {
+            format!(
+                "Bitcoin transaction {} already pays a fee rate of {} sat/vB",
+                parent.compute_txid(),
+                fee_rate.to_sat_per_vb_ceil()
+            )
+        })?;
+
+        // Nodes only accept the replacement if it pays more than the child it replaces, even
+        // if the fee estimates went down since
+        let child_fee = match previous_child {
+            Some((_, previous_child_fee)) => child_fee.max(min_replacement_fee(
+                previous_child_fee,
+                child_weight,
+                min_relay_fee,
+            )?),
+            None => child_fee,
+        };
+
+        let previous_child_txid = previous_child.map(|(child, _)| child.compute_txid());
+        let psbt = self
+            .build_child(parent, previous_child_txid, child_fee)
+            .await?;
+
+        Ok((psbt, child_fee))
+    }
+
+    /// Build a transaction spending the first output of `parent` that belongs to this wallet
+    /// back to the wallet, or a replacement of `previous_child` if given.
+    async fn build_child(
+        &self,
+        parent: &Transaction,
+        previous_child: Option<Txid>,
+        fee: Amount,
+    ) -> Result<PartiallySignedTransaction> {
+        let mut wallet = self.wallet.lock().await;
+
+        if let Some(child_txid) = previous_child {
+            let mut tx_builder = wallet
+                .build_fee_bump(child_txid)
+                .with_context(|| format!("Cannot replace child transaction {}", child_txid))?;
+            tx_builder.fee_absolute(fee);
+
+            return tx_builder
+                .finish()
+                .context("Failed to build replacement child transaction");
+        }
+
+        let vout = parent
+            .output
+            .iter()
+            .position(|output| wallet.is_mine(output.script_pubkey.clone()))
+            .context("Transaction does not pay to this wallet")?;
+        let outpoint = bitcoin::OutPoint::new(parent.compute_txid(), vout as u32);
+        let change = wallet
+            .next_unused_address(KeychainKind::Internal)
+            .script_pubkey();
+
+        let mut tx_builder = wallet.build_tx();
+        tx_builder
+            .add_utxo(outpoint)
+            .context("Output of the transaction is not spendable by this wallet")?;
+        tx_builder.manually_selected_only();
+        tx_builder.drain_to(change);
+        tx_builder.fee_absolute(fee);
+
+        tx_builder
+            .finish()
+            .context("Failed to build child transaction")
+    }
+
+    /// Reveal a new address, remembering what it is used for.
+    pub async fn new_address_with_label(
+        &self,
+        label: &str,
+        swap_id: Option<Uuid>,
+    ) -> Result<Address> {
+        let address = self.new_address().await?;
+        self.label_address(&address, label, swap_id).await?;
+
+        Ok(address)
+    }
+
+    /// Remember what an address (ours or someone else's) is used for.
+    ///
+    /// Labeling an address again replaces its label. The labels are kept in the swap database
+    /// rather than the wallet file, so they don't depend on the descriptors we use.
+    pub async fn label_address(
+        &self,
+        address: &Address,
+        label: &str,
+        swap_id: Option<Uuid>,
+    ) -> Result<()> {
+        self.label_db()?
+            .insert_bitcoin_address_label(address, label, swap_id)
+            .await
+            .context("Failed to store address label")
+    }
+
+    /// All address labels, oldest first.
+    pub async fn address_labels(&self) -> Result<Vec<AddressLabel>> {
+        self.label_db()?.get_bitcoin_address_labels().await
+    }
+
+    fn label_db(&self) -> Result<&Arc<dyn Database + Send + Sync>> {
+        self.swap_db
+            .as_ref()
+            .context("Address labels need the swap database")
+    }
+
     /// Reveals the next address from the wallet.
     pub async fn new_address(&self) -> Result<Address> {
         let mut wallet = self.wallet.lock().await;
@@ -1363,12 +2663,14 @@ where
     /// Builds a partially signed transaction that sends
     /// the given amount to the given address.
     /// The fee is calculated based on the weight of the transaction
-    /// and the state of the current mempool.
+    /// and the state of the current mempool, unless a `fee_rate` is given.
     pub async fn send_to_address_dynamic_fee(
         &self,
         address: Address,
         amount: Amount,
         change_override: Option<Address>,
+        coin_control: CoinControl,
+        fee_rate: Option<FeeRate>,
     ) -> Result<PartiallySignedTransaction> {
         // Check address and change address for network equality.
         let address = revalidate_network(address, self.network)?;
@@ -1389,31 +2691,146 @@ where
             // send_to_address(...) takes an absolute fee
             let mut tx_builder = wallet.build_tx();
 
-            tx_builder.add_recipient(script.clone(), amount);
-            tx_builder.fee_absolute(Amount::ZERO);
+            tx_builder.add_recipient(script.clone(), amount);
+            tx_builder.fee_absolute(Amount::ZERO);
+            coin_control.apply(&mut tx_builder)?;
+
+            tx_builder.finish()?
+        };
+
+        let fee = self.fee_with_rate(&psbt, amount, fee_rate).await?;
+
+        self.send_to_address(address, amount, fee, change_override, coin_control)
+            .await
+    }
+
+    /// Builds an unsigned transaction that sends the given amount to the given address, to be
+    /// signed outside of this wallet and published with [`Wallet::broadcast_signed_psbt`].
+    ///
+    /// The inputs carry their key origins, so this also works for a watch-only wallet whose
+    /// keys live on a hardware wallet.
+    pub async fn create_unsigned_psbt(
+        &self,
+        address: Address,
+        amount: Amount,
+    ) -> Result<PartiallySignedTransaction> {
+        self.send_to_address_dynamic_fee(address, amount, None, CoinControl::default(), None)
+            .await
+    }
+
+    /// Builds a partially signed transaction that sends all spendable
+    /// funds of the wallet to the given address in a single output.
+    ///
+    /// The fee is paid out of the swept funds.
+    pub async fn sweep_to(&self, address: Address) -> Result<PartiallySignedTransaction> {
+        self.sweep_balance_to_address_dynamic_fee(address, CoinControl::default(), None)
+            .await
+    }
+
+    /// Builds a partially signed transaction that sweeps our entire balance
+    /// to a single address.
+    ///
+    /// If `coin_control` includes outputs, only those are swept.
+    ///
+    /// The fee is calculated based on the weight of the transaction
+    /// and the state of the current mempool, unless a `fee_rate` is given.
+    pub async fn sweep_balance_to_address_dynamic_fee(
+        &self,
+        address: Address,
+        coin_control: CoinControl,
+        fee_rate: Option<FeeRate>,
+    ) -> Result<PartiallySignedTransaction> {
+        let address = revalidate_network(address, self.network)?;
+        let script = address.script_pubkey();
+
+        let build_sweep = |wallet: &mut PersistedWallet<Persister>, fee: Amount| {
+            let mut tx_builder = wallet.build_tx();
+            tx_builder.drain_to(script.clone());
+            tx_builder.fee_absolute(fee);
+            coin_control.apply(&mut tx_builder)?;
+
+            if coin_control.utxo_include.is_empty() {
+                tx_builder.drain_wallet();
+            } else {
+                tx_builder.manually_selected_only();
+            }
 
-            tx_builder.finish()?
+            match tx_builder.finish() {
+                Ok(psbt) => Ok(psbt),
+                Err(
+                    CreateTxError::NoUtxosSelected
+                    | CreateTxError::CoinSelection(_)
+                    | CreateTxError::OutputBelowDustLimit(_),
+                ) => bail!("Not enough spendable funds to sweep"),
+                Err(error) => Err(error).context("Failed to build sweep transaction"),
+            }
         };
 
-        let weight = psbt.unsigned_tx.weight();
-        let fee = self.estimate_fee(weight, Some(amount)).await?;
+        // Draining means the inputs and outputs don't depend on the fee
+        let dummy_psbt = build_sweep(&mut *self.wallet.lock().await, Amount::ZERO)?;
+        let swept_amount = dummy_psbt
+            .unsigned_tx
+            .output
+            .first()
+            .context("Sweep transaction has no output")?
+            .value;
+        let fee = self
+            .fee_with_rate(&dummy_psbt, swept_amount, fee_rate)
+            .await?;
 
-        self.send_to_address(address, amount, fee, change_override)
-            .await
+        build_sweep(&mut *self.wallet.lock().await, fee)
     }
 
-    /// Builds a partially signed transaction that sweeps our entire balance
-    /// to a single address.
+    /// Builds a partially signed transaction that merges all our confirmed outputs into a
+    /// single output to a new change address.
     ///
-    /// The fee is calculated based on the weight of the transaction
-    /// and the state of the current mempool.
-    pub async fn sweep_balance_to_address_dynamic_fee(
+    /// Many small outputs make every transaction spending them large. Paying for the extra
+    /// inputs now, while fees are low, keeps the transactions we need confirmed quickly later
+    /// (like redeem transactions) small.
+    ///
+    /// Returns `None` if the estimated fee rate is above `max_fee_rate` or we have fewer than
+    /// [`CONSOLIDATION_MIN_UTXOS`] outputs.
+    pub async fn build_consolidation(
         &self,
-        address: Address,
-    ) -> Result<PartiallySignedTransaction> {
-        let (max_giveable, fee) = self.max_giveable(address.script_pubkey().len()).await?;
+        max_fee_rate: FeeRate,
+    ) -> Result<Option<PartiallySignedTransaction>> {
+        let fee_rate = self.combined_fee_rate(self.target_block).await?;
+        if fee_rate > max_fee_rate {
+            tracing::debug!(%fee_rate, %max_fee_rate, "Not consolidating Bitcoin outputs, fee rate is too high");
+            return Ok(None);
+        }
+
+        let utxos: Vec<OutPoint> = self
+            .list_unspent()
+            .await
+            .into_iter()
+            .filter(|utxo| utxo.confirmations > 0)
+            .map(|utxo| utxo.outpoint)
+            .collect();
+        if utxos.len() < CONSOLIDATION_MIN_UTXOS {
+            return Ok(None);
+        }
+
+        let address = {
+            let mut wallet = self.wallet.lock().await;
+            let address = wallet.next_unused_address(KeychainKind::Internal).address;
+
+            // Persist the revealed address so it isn't handed out again
+            let mut persister = self.persister.lock().await;
+            wallet.persist(&mut persister)?;
+
+            address
+        };
+
+        let coin_control = CoinControl {
+            utxo_include: utxos,
+            utxo_exclude: vec![],
+        };
+        let psbt = self
+            .sweep_balance_to_address_dynamic_fee(address, coin_control, Some(fee_rate))
+            .await?;
 
-        self.send_to_address(address, max_giveable, fee, None).await
+        Ok(Some(psbt))
     }
 
     /// Builds a partially signed transaction that sends
@@ -1428,6 +2845,7 @@ where
         amount: Amount,
         spending_fee: Amount,
         change_override: Option<Address>,
+        coin_control: CoinControl,
     ) -> Result<PartiallySignedTransaction> {
         // Check address and change address for network equality.
         let address = revalidate_network(address, self.network)?;
@@ -1445,6 +2863,7 @@ where
         let mut tx_builder = wallet.build_tx();
         tx_builder.add_recipient(script.clone(), amount);
         tx_builder.fee_absolute(spending_fee);
+        coin_control.apply(&mut tx_builder)?;
 
         let mut psbt = tx_builder.finish()?;
 
@@ -1563,7 +2982,7 @@ where
                 };
 
                 // Worst-case witness weight for our script type.
-                const DUMMY_SATISFACTION_WEIGHT: Weight = Weight::from_wu(107 * 10);
+                let dummy_satisfaction_weight = self.descriptor_type.max_satisfaction_weight();
 
                 let mut tx_builder = wallet.build_tx();
 
@@ -1575,7 +2994,7 @@ where
                     .add_foreign_utxo(
                         fake_deposit_outpoint,
                         fake_deposit_input,
-                        DUMMY_SATISFACTION_WEIGHT,
+                        dummy_satisfaction_weight,
                     ).context("Failed to add dummy foreign utxo to calculate fee for max_giveable if we had one more utxo")?;
 
                 // Try building the dummy drain transaction with the new fake UTXO
@@ -1640,17 +3059,76 @@ where
         weight: Weight,
         transfer_amount: Option<bitcoin::Amount>,
     ) -> Result<bitcoin::Amount> {
-        let fee_rate = self.combined_fee_rate().await?;
+        let fee_rate = self.combined_fee_rate(self.target_block).await?;
         let min_relay_fee = self.combined_min_relay_fee().await?;
 
         estimate_fee(weight, transfer_amount, fee_rate, min_relay_fee)
     }
+
+    /// The fee for sending `transfer_amount` in the transaction of `psbt` once it is signed,
+    /// paying `fee_rate` if given and the estimated fee rate otherwise.
+    async fn fee_with_rate(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        transfer_amount: Amount,
+        fee_rate: Option<FeeRate>,
+    ) -> Result<Amount> {
+        let weight = self.signed_weight(psbt).await?;
+
+        match fee_rate {
+            Some(fee_rate) => {
+                let min_relay_fee = self.combined_min_relay_fee().await?;
+
+                fee_for_fee_rate(weight, transfer_amount, fee_rate, min_relay_fee)
+            }
+            None => self.estimate_fee(weight, Some(transfer_amount)).await,
+        }
+    }
+
+    /// The weight of the transaction of `psbt` once we signed all its inputs.
+    ///
+    /// The unsigned transaction carries no witness, so we add the segwit marker and the
+    /// largest witness needed to spend one of our outputs for every input.
+    async fn signed_weight(&self, psbt: &PartiallySignedTransaction) -> Result<Weight> {
+        let satisfaction_weight = self
+            .wallet
+            .lock()
+            .await
+            .public_descriptor(KeychainKind::External)
+            .max_weight_to_satisfy()
+            .context("Failed to compute the witness weight of our outputs")?;
+
+        let inputs = psbt.unsigned_tx.input.len() as u64;
+        let witness_weight = satisfaction_weight
+            .checked_mul(inputs)
+            .context("Witness weight overflows")?;
+        // Segwit marker and flag
+        let segwit_header = Weight::from_wu(2);
+
+        Ok(psbt.unsigned_tx.weight() + segwit_header + witness_weight)
+    }
 }
 
 impl Client {
     /// Create a new client with multiple electrum servers for load balancing.
     pub async fn new(electrum_rpc_urls: &[String], sync_interval: Duration) -> Result<Self> {
-        let balancer = ElectrumBalancer::new(electrum_rpc_urls.to_vec()).await?;
+        Self::with_config(
+            electrum_rpc_urls,
+            sync_interval,
+            ElectrumBalancerConfig::default(),
+        )
+        .await
+    }
+
+    /// Like [`Client::new`], but with a custom configuration of the Electrum balancer, e.g. to
+    /// persist the server stats or to connect through a SOCKS5 proxy.
+    pub async fn with_config(
+        electrum_rpc_urls: &[String],
+        sync_interval: Duration,
+        config: ElectrumBalancerConfig,
+    ) -> Result<Self> {
+        let balancer =
+            ElectrumBalancer::new_with_config(electrum_rpc_urls.to_vec(), config).await?;
 
         Ok(Self {
             inner: Arc::new(balancer),
@@ -1661,9 +3139,22 @@ impl Client {
             sync_interval,
             latest_block_height: BlockHeight::from(0),
             subscriptions: Default::default(),
+            notifications: None,
+            subscription_reader_running: false,
+            retrying_broadcasts: HashSet::new(),
+            header_verifier: None,
         })
     }
 
+    /// Verify the chain of block headers from the checkpoints of the given network up to the
+    /// tip reported by the Electrum servers, and ignore the servers that fail the check.
+    ///
+    /// The checkpoints learned along the way are persisted to `checkpoint_file`, if given.
+    pub fn verify_headers(mut self, network: Network, checkpoint_file: Option<PathBuf>) -> Self {
+        self.header_verifier = Some(Arc::new(HeaderVerifier::new(network, checkpoint_file)));
+        self
+    }
+
     /// Update the client state, if the refresh duration has passed.
     ///
     /// Optionally force an update even if the sync interval has not passed.
@@ -1695,22 +3186,21 @@ impl Client {
 
     /// Update the block height.
     async fn update_block_height(&mut self) -> Result<()> {
+        let header_verifier = self.header_verifier.clone();
         let latest_block = self
             .inner
-            .call_async("block_headers_subscribe", |client| {
-                client.inner.block_headers_subscribe()
+            .call_async("block_headers_subscribe", move |client| {
+                let tip = client.inner.block_headers_subscribe()?;
+
+                if let Some(verifier) = &header_verifier {
+                    verify_tip(verifier, &client.inner, &tip)?;
+                }
+
+                Ok(tip)
             })
             .await
             .context("Failed to subscribe to header notifications")?;
-        let latest_block_height = BlockHeight::try_from(latest_block)?;
-
-        if latest_block_height > self.latest_block_height {
-            tracing::trace!(
-                block_height = u32::from(latest_block_height),
-                "Got notification for new block"
-            );
-            self.latest_block_height = latest_block_height;
-        }
+        self.update_latest_block_height(BlockHeight::try_from(latest_block)?);
 
         Ok(())
     }
@@ -1854,6 +3344,275 @@ impl Client {
         Ok(results)
     }
 
+    /// Succeeds once a server reports the transaction in the mempool or in a block, or accepts
+    /// it into its mempool when we broadcast it again.
+    async fn rebroadcast_unless_seen(&mut self, transaction: &Transaction) -> Result<()> {
+        let txid = transaction.compute_txid();
+        let script = transaction
+            .output
+            .first()
+            .context("Transaction has no outputs")?
+            .script_pubkey
+            .clone();
+
+        match self.status_of_script(&(txid, script), true).await? {
+            ScriptStatus::InMempool | ScriptStatus::Confirmed(_) => return Ok(()),
+            ScriptStatus::Unseen | ScriptStatus::Retrying => {}
+        }
+
+        let results = self.transaction_broadcast_all(transaction).await?;
+
+        if results.iter().any(|result| result.is_ok()) {
+            return Ok(());
+        }
+
+        let errors: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+        let error = anyhow::Error::from(electrum_pool::MultiError::new(
+            errors,
+            format!("Failed to broadcast transaction {}", txid),
+        ));
+
+        if let Ok(code) = parse_rpc_error_code(&error) {
+            if code == i64::from(RpcErrorCode::RpcVerifyAlreadyInChain) {
+                return Ok(());
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Find a confirmed transaction other than `transaction` that spends one of its inputs.
+    async fn confirmed_conflict(&self, transaction: &Transaction) -> Result<Option<Txid>> {
+        let txid = transaction.compute_txid();
+
+        for input in &transaction.input {
+            let outpoint = input.previous_output;
+            let Some(previous) = self.get_tx(outpoint.txid).await? else {
+                continue;
+            };
+            let script = previous
+                .output
+                .get(outpoint.vout as usize)
+                .context("Transaction spends an output that does not exist")?
+                .script_pubkey
+                .clone();
+
+            let history = self
+                .inner
+                .call_async("script_get_history", move |client| {
+                    client.inner.script_get_history(script.as_script())
+                })
+                .await?;
+
+            for entry in history {
+                // The history also lists the transaction creating the output
+                if entry.height <= 0 || entry.tx_hash == txid || entry.tx_hash == outpoint.txid {
+                    continue;
+                }
+
+                let Some(spender) = self.get_tx(entry.tx_hash).await? else {
+                    continue;
+                };
+
+                if spender
+                    .input
+                    .iter()
+                    .any(|input| input.previous_output == outpoint)
+                {
+                    return Ok(Some(entry.tx_hash));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Fetch the notifications the Electrum server sent since the last call, and refetch the
+    /// history of every script whose status changed.
+    ///
+    /// Notifications are only sent over the connection we subscribed on. If that connection
+    /// fails, we subscribe again on the next call, possibly on another server.
+    async fn read_notifications(&mut self) -> Result<()> {
+        let result = self.read_notifications_inner().await;
+
+        if result.is_err() {
+            self.notifications = None;
+        }
+
+        result
+    }
+
+    async fn read_notifications_inner(&mut self) -> Result<()> {
+        let mut notifications = match self.notifications.take() {
+            Some(notifications) => notifications,
+            None => {
+                let connection = self
+                    .inner
+                    .get_any_client()
+                    .await
+                    .context("Failed to connect to an Electrum server for notifications")?;
+
+                let header = {
+                    let connection = connection.clone();
+                    let header_verifier = self.header_verifier.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let tip = connection.inner.block_headers_subscribe()?;
+
+                        if let Some(verifier) = &header_verifier {
+                            verify_tip(verifier, &connection.inner, &tip)?;
+                        }
+
+                        Ok::<_, electrum_client::Error>(tip)
+                    })
+                    .await??
+                };
+                self.update_latest_block_height(BlockHeight::try_from(header)?);
+
+                NotificationConnection {
+                    connection,
+                    scripts: HashSet::new(),
+                }
+            }
+        };
+
+        // Subscribe to the scripts of new subscriptions and unsubscribe from the scripts
+        // nobody waits for anymore
+        let wanted_scripts: HashSet<ScriptBuf> = self
+            .subscriptions
+            .keys()
+            .map(|(_, script)| script.clone())
+            .collect();
+        let new_scripts: Vec<ScriptBuf> = wanted_scripts
+            .difference(&notifications.scripts)
+            .cloned()
+            .collect();
+        let stale_scripts: Vec<ScriptBuf> = notifications
+            .scripts
+            .difference(&wanted_scripts)
+            .cloned()
+            .collect();
+
+        let connection = notifications.connection.clone();
+        let subscribed_scripts = wanted_scripts.clone();
+        let header_verifier = self.header_verifier.clone();
+        let (notified_scripts, latest_header) =
+            tokio::task::spawn_blocking(move || -> Result<_, electrum_client::Error> {
+                for script in &new_scripts {
+                    match connection.inner.script_subscribe(script) {
+                        Ok(_) | Err(electrum_client::Error::AlreadySubscribed(_)) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+
+                for script in &stale_scripts {
+                    let _ = connection.inner.script_unsubscribe(script);
+                }
+
+                // Any request makes the client read the notifications that arrived in the
+                // meantime
+                connection.inner.ping()?;
+
+                // The status may have changed between the initial fetch and the subscription
+                let mut notified_scripts: HashSet<ScriptBuf> = new_scripts.into_iter().collect();
+                for script in &subscribed_scripts {
+                    while connection.inner.script_pop(script)?.is_some() {
+                        notified_scripts.insert(script.clone());
+                    }
+                }
+
+                let mut latest_header = None;
+                while let Some(header) = connection.inner.block_headers_pop()? {
+                    latest_header = Some(header);
+                }
+
+                // A bad header only costs us the block height update, the script
+                // notifications are still useful
+                if let (Some(tip), Some(verifier)) = (&latest_header, &header_verifier) {
+                    if let Err(error) = verify_tip(verifier, &connection.inner, tip) {
+                        tracing::warn!(%error, "Ignoring block header notification");
+                        latest_header = None;
+                    }
+                }
+
+                Ok((notified_scripts, latest_header))
+            })
+            .await?
+            .context("Failed to read notifications from Electrum server")?;
+
+        notifications.scripts = wanted_scripts;
+        self.notifications = Some(notifications);
+
+        if let Some(header) = latest_header {
+            self.update_latest_block_height(BlockHeight::try_from(header)?);
+        }
+
+        for script in notified_scripts {
+            let Some((txid, _)) = self.subscriptions.keys().find(|(_, s)| *s == script) else {
+                continue;
+            };
+            let watchable = (*txid, script);
+
+            self.update_script_history(&watchable).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the current status to the subscribers whose transaction changed, and drop the
+    /// subscriptions nobody listens to anymore.
+    ///
+    /// Returns the subscriptions that no longer have to be resumed after a restart, because
+    /// their transaction is final or because they were dropped.
+    fn notify_subscribers(&mut self, finality_confirmations: u32) -> Vec<(Txid, ScriptBuf)> {
+        let mut statuses = HashMap::new();
+        for (txid, script) in self.subscriptions.keys() {
+            if let Ok(status) = self.cached_status_of_script(script, *txid) {
+                statuses.insert((*txid, script.clone()), status);
+            }
+        }
+
+        let mut finished = Vec::new();
+        self.subscriptions.retain(|key, subscription| {
+            let (txid, script) = key;
+
+            if let Some(&status) = statuses.get(key) {
+                if subscription.last_status != Some(status) {
+                    subscription.last_status =
+                        Some(trace_status_change(*txid, subscription.last_status, status));
+                    subscription.sender.send_replace(status);
+                }
+            }
+
+            let is_final = subscription
+                .last_status
+                .is_some_and(|status| status.is_confirmed_with(finality_confirmations));
+            let all_receivers_gone = subscription.sender.is_closed();
+
+            if subscription.resumable && (is_final || all_receivers_gone) {
+                subscription.resumable = false;
+                finished.push((*txid, script.clone()));
+            }
+
+            if all_receivers_gone {
+                tracing::debug!(%txid, "All receivers gone, removing subscription");
+            }
+
+            !all_receivers_gone
+        });
+
+        finished
+    }
+
+    fn update_latest_block_height(&mut self, block_height: BlockHeight) {
+        if block_height > self.latest_block_height {
+            tracing::trace!(
+                block_height = u32::from(block_height),
+                "Got notification for new block"
+            );
+            self.latest_block_height = block_height;
+        }
+    }
+
     /// Get the status of a script.
     pub async fn status_of_script(
         &mut self,
@@ -1877,7 +3636,14 @@ impl Client {
             self.update_state(false).await?;
         }
 
-        let history = self.script_history.entry(script_buf).or_default();
+        self.cached_status_of_script(&script_buf, txid)
+    }
+
+    /// Get the status of a script from the last fetched history, without asking the server.
+    fn cached_status_of_script(&self, script_buf: &ScriptBuf, txid: Txid) -> Result<ScriptStatus> {
+        let Some(history) = self.script_history.get(script_buf) else {
+            return Ok(ScriptStatus::Unseen);
+        };
 
         let history_of_tx: Vec<&GetHistoryRes> = history
             .iter()
@@ -1996,6 +3762,14 @@ impl Client {
             ));
         }
 
+        // A misbehaving server might return garbage, which we don't want to pay
+        if !btc_per_kvb.is_finite() || btc_per_kvb > MAX_ELECTRUM_BTC_PER_KVB {
+            return Err(anyhow!(
+                "Fee rate returned by Electrum server is implausible: {} BTC/kvB",
+                btc_per_kvb
+            ));
+        }
+
         // Convert to sat / kB without ever constructing an Amount from the float
         // Simply by multiplying the float with the satoshi value of 1 BTC.
         // Truncation is allowed here because we are converting to sats and rounding down sats will
@@ -2020,10 +3794,6 @@ impl Client {
     /// We calculate how many vMB we are away from the tip of the mempool.
     /// This method adapts faster to sudden spikes in the mempool.
     async fn estimate_fee_rate_from_histogram(&self, target_block: u32) -> Result<FeeRate> {
-        // Assume we want to get into the next block:
-        // We want to be 80% of the block size away from the tip of the mempool.
-        const HISTOGRAM_SAFETY_MARGIN: f32 = 0.8;
-
         // First we fetch the fee histogram from the Electrum server
         let fee_histogram = self
             .inner
@@ -2035,46 +3805,7 @@ impl Client {
         // Parse the histogram as array of [fee, vsize] pairs
         let histogram: Vec<(f64, u64)> = serde_json::from_value(fee_histogram)?;
 
-        // If the histogram is empty, we return an error
-        if histogram.is_empty() {
-            return Err(anyhow!(
-                "The mempool seems to be empty therefore we cannot estimate the fee rate from the histogram"
-            ));
-        }
-
-        // Sort the histogram by fee rate
-        let mut histogram = histogram;
-        histogram.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Estimate block size (typically ~1MB = 1,000,000 vbytes)
-        let estimated_block_size = 1_000_000u64;
-        #[allow(clippy::cast_precision_loss)]
-        let target_distance_from_tip =
-            (estimated_block_size * target_block as u64) as f32 * HISTOGRAM_SAFETY_MARGIN;
-
-        // Find cumulative vsize and corresponding fee rate
-        let mut cumulative_vsize = 0u64;
-        for (fee_rate, vsize) in histogram.clone() {
-            cumulative_vsize += vsize;
-            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            if cumulative_vsize >= target_distance_from_tip as u64 {
-                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-                let sat_per_vb = fee_rate.ceil() as u64;
-                return FeeRate::from_sat_per_vb(sat_per_vb)
-                    .context("Failed to create fee rate from histogram");
-            }
-        }
-
-        // If we get here, the entire mempool is less than the target distance from the tip.
-        // We return the lowest fee rate in the histogram.
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        let sat_per_vb = histogram
-            .first()
-            .expect("The histogram should not be empty")
-            .0
-            .ceil() as u64;
-        FeeRate::from_sat_per_vb(sat_per_vb)
-            .context("Failed to create fee rate from histogram (all mempool is less than the target distance from the tip)")
+        fee_rate_from_histogram(histogram, target_block)
     }
 
     /// Get the minimum relay fee rate from the Electrum server.
@@ -2116,11 +3847,17 @@ impl EstimateFeeRate for Client {
         match (electrum_conservative_fee_rate, electrum_histogram_fee_rate) {
             // If both the histogram and conservative fee rate are successful, we use the higher one
             (Ok(electrum_conservative_fee_rate), Ok(electrum_histogram_fee_rate)) => {
+                let source = if electrum_conservative_fee_rate >= electrum_histogram_fee_rate {
+                    "estimatefee"
+                } else {
+                    "fee histogram"
+                };
                 tracing::debug!(
                     electrum_conservative_fee_rate_sat_vb =
                         electrum_conservative_fee_rate.to_sat_per_vb_ceil(),
                     electrum_histogram_fee_rate_sat_vb =
                         electrum_histogram_fee_rate.to_sat_per_vb_ceil(),
+                    %source,
                     "Successfully fetched fee rates from both sources. We will use the higher one"
                 );
 
@@ -2164,8 +3901,6 @@ mod sync_ext {
     use std::sync::Arc;
     use std::sync::Mutex as SyncMutex;
 
-    use bdk_wallet::KeychainKind;
-
     use super::IntoArcMutex;
 
     /// Type alias for an optional callback
@@ -2184,14 +3919,6 @@ mod sync_ext {
         fn chain(self, callback: InnerSyncCallback) -> InnerSyncCallback;
         fn finalize(self) -> SyncCallback;
         fn call(&mut self, consumed: u64, total: u64);
-        #[allow(clippy::type_complexity)]
-        fn to_full_scan_callback(
-            self,
-            stop_gap: u32,
-            assumed_buffer: u32,
-        ) -> Box<dyn FnMut(KeychainKind, u32, &bitcoin::Script) + Send + 'static>
-        where
-            Self: Sized;
     }
 
     impl SyncCallbackExt for InnerSyncCallback {
@@ -2256,21 +3983,6 @@ mod sync_ext {
         fn finalize(self) -> SyncCallback {
             self.into_arc_mutex_sync()
         }
-
-        fn to_full_scan_callback(
-            mut self,
-            stop_gap: u32,
-            assumed_buffer: u32,
-        ) -> Box<dyn FnMut(KeychainKind, u32, &bitcoin::Script) + Send + 'static>
-        where
-            Self: Sized,
-        {
-            Box::new(move |_, current_index, _| {
-                let total = stop_gap.max(current_index + assumed_buffer);
-
-                self.call(current_index as u64, total as u64);
-            })
-        }
     }
 
     // This struct combines progress updates from different chunks
@@ -2330,6 +4042,32 @@ mod sync_ext {
     }
 }
 
+/// Reveal the addresses of `wallet` in `fresh_wallet` as well, so they are scanned and not
+/// handed out a second time.
+fn reveal_addresses_like(fresh_wallet: &mut bdk_wallet::Wallet, wallet: &bdk_wallet::Wallet) {
+    for keychain in [KeychainKind::External, KeychainKind::Internal] {
+        if let Some(index) = wallet.spk_index().last_revealed_index(keychain) {
+            let _ = fresh_wallet.reveal_addresses_to(keychain, index);
+        }
+    }
+}
+
+/// Verify the tip reported by an Electrum server, fetching the headers leading up to it from
+/// the same server.
+fn verify_tip(
+    verifier: &HeaderVerifier,
+    client: &electrum_client::Client,
+    tip: &electrum_client::HeaderNotification,
+) -> Result<(), electrum_client::Error> {
+    verifier
+        .verify_tip(tip, |start, count| {
+            Ok(client
+                .block_headers(start as usize, count as usize)?
+                .headers)
+        })
+        .map_err(|error| electrum_client::Error::Protocol(format!("{:#}", error).into()))
+}
+
 fn trace_status_change(txid: Txid, old: Option<ScriptStatus>, new: ScriptStatus) -> ScriptStatus {
     match (old, new) {
         (None, new_status) => {
@@ -2399,6 +4137,154 @@ impl Subscription {
     }
 }
 
+/// The fee a child transaction has to pay so that it and its parent together pay the fee
+/// [`estimate_fee`] recommends for both of them.
+///
+/// Returns `None` if the parent already pays that much on its own.
+fn cpfp_child_fee(
+    parent_weight: Weight,
+    parent_fee: Amount,
+    child_weight: Weight,
+    fee_rate_estimation: FeeRate,
+    min_relay_fee_rate: FeeRate,
+) -> Result<Option<Amount>> {
+    let package_fee = estimate_fee(
+        parent_weight + child_weight,
+        None,
+        fee_rate_estimation,
+        min_relay_fee_rate,
+    )?;
+
+    Ok(package_fee
+        .checked_sub(parent_fee)
+        .filter(|child_fee| *child_fee > Amount::ZERO))
+}
+
+/// The lowest fee a replacement of a transaction paying `replaced_fee` may pay: nodes want it
+/// to pay for its own size at the incremental relay fee rate on top (BIP 125 rule 4).
+fn min_replacement_fee(
+    replaced_fee: Amount,
+    replacement_weight: Weight,
+    incremental_relay_fee_rate: FeeRate,
+) -> Result<Amount> {
+    let incremental_fee = incremental_relay_fee_rate
+        .max(FeeRate::BROADCAST_MIN)
+        .checked_mul_by_weight(replacement_weight)
+        .context("Fee rate is too high")?;
+
+    replaced_fee
+        .checked_add(incremental_fee)
+        .context("Replacement fee overflows")
+}
+
+/// Calculates the fee rate needed to be included in a block at the given offset from the
+/// fee histogram of the mempool (`[fee rate in sat/vB, vsize]` pairs).
+///
+/// We walk down from the highest paying transactions until we are `target_block` blocks
+/// (minus a safety margin) away from the tip of the mempool.
+fn fee_rate_from_histogram(mut histogram: Vec<(f64, u64)>, target_block: u32) -> Result<FeeRate> {
+    // Assume we want to get into the next block:
+    // We want to be 80% of the block size away from the tip of the mempool.
+    const HISTOGRAM_SAFETY_MARGIN: f32 = 0.8;
+
+    // Garbage entries would make the result meaningless
+    histogram.retain(|(fee_rate, _)| fee_rate.is_finite() && *fee_rate >= 0.0);
+
+    // If the histogram is empty, we return an error
+    if histogram.is_empty() {
+        return Err(anyhow!(
+            "The mempool seems to be empty therefore we cannot estimate the fee rate from the histogram"
+        ));
+    }
+
+    // Sort the histogram by fee rate, highest first. The tip of the mempool is what gets mined next.
+    histogram.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Estimate block size (typically ~1MB = 1,000,000 vbytes)
+    let estimated_block_size = 1_000_000u64;
+    #[allow(clippy::cast_precision_loss)]
+    let target_distance_from_tip =
+        (estimated_block_size * target_block as u64) as f32 * HISTOGRAM_SAFETY_MARGIN;
+
+    // Find cumulative vsize and corresponding fee rate
+    let mut cumulative_vsize = 0u64;
+    for (fee_rate, vsize) in histogram.iter() {
+        cumulative_vsize += vsize;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        if cumulative_vsize >= target_distance_from_tip as u64 {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let sat_per_vb = fee_rate.ceil() as u64;
+            return FeeRate::from_sat_per_vb(sat_per_vb)
+                .context("Failed to create fee rate from histogram");
+        }
+    }
+
+    // If we get here, the entire mempool is less than the target distance from the tip.
+    // We return the lowest fee rate in the histogram.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let sat_per_vb = histogram
+        .last()
+        .expect("The histogram should not be empty")
+        .0
+        .ceil() as u64;
+    FeeRate::from_sat_per_vb(sat_per_vb)
+        .context("Failed to create fee rate from histogram (all mempool is less than the target distance from the tip)")
+}
+
+/// The absolute fee for a transaction paying a fee rate chosen by the user.
+///
+/// Unlike [`estimate_fee`] this doesn't fall back to a different fee if the fee rate is too
+/// high but fails, because the user asked for exactly this fee rate. The fee is only raised to
+/// [`MIN_ABSOLUTE_TX_FEE`] because nodes wouldn't relay the transaction otherwise.
+///
+/// This function will fail if:
+/// - The fee rate is below the minimum relay fee rate
+/// - The fee is higher than MAX_RELATIVE_TX_FEE of the transfer amount or MAX_ABSOLUTE_TX_FEE
+fn fee_for_fee_rate(
+    weight: Weight,
+    transfer_amount: Amount,
+    fee_rate: FeeRate,
+    min_relay_fee_rate: FeeRate,
+) -> Result<Amount> {
+    let min_relay_fee_rate = min_relay_fee_rate.max(FeeRate::BROADCAST_MIN);
+    if fee_rate < min_relay_fee_rate {
+        bail!(
+            "Fee rate of {} sat/vB is below the minimum relay fee rate of {} sat/vB",
+            fee_rate.to_sat_per_vb_floor(),
+            min_relay_fee_rate.to_sat_per_vb_ceil()
+        );
+    }
+
+    let fee = fee_rate
+        .checked_mul_by_weight(weight)
+        .context("Fee rate is too high")?
+        .max(MIN_ABSOLUTE_TX_FEE);
+
+    let max_relative_fee = Amount::from_sat(
+        MAX_RELATIVE_TX_FEE
+            .saturating_mul(Decimal::from(transfer_amount.to_sat()))
+            .ceil()
+            .to_u64()
+            .expect("Max relative tx fee to fit into u64"),
+    );
+    if fee > max_relative_fee {
+        bail!(
+            "Fee of {} sats is more than {}% of the transferred amount",
+            fee.to_sat(),
+            MAX_RELATIVE_TX_FEE * dec!(100)
+        );
+    }
+    if fee > MAX_ABSOLUTE_TX_FEE {
+        bail!(
+            "Fee of {} sats is above the maximum of {} sats",
+            fee.to_sat(),
+            MAX_ABSOLUTE_TX_FEE.to_sat()
+        );
+    }
+
+    Ok(fee)
+}
+
 /// Estimate the absolute fee for a transaction.
 ///
 /// This function takes the following parameters:
@@ -2878,6 +4764,7 @@ pub struct TestWalletBuilder {
     min_relay_sats_per_vb: u64,
     key: bitcoin::bip32::Xpriv,
     num_utxos: u8,
+    descriptor_type: DescriptorType,
 }
 
 #[cfg(test)]
@@ -2893,6 +4780,7 @@ impl TestWalletBuilder {
             min_relay_sats_per_vb: 1,
             key: "tprv8ZgxMBicQKsPeZRHk4rTG6orPS2CRNFX3njhUXx5vj9qGog5ZMH4uGReDWN5kCkY3jmWEtWause41CDvBRXD1shKknAMKxT99o9qUTRVC6m".parse().unwrap(),
             num_utxos: 1,
+            descriptor_type: DescriptorType::Bip84,
         }
     }
 
@@ -2916,9 +4804,16 @@ impl TestWalletBuilder {
         Self { key, ..self }
     }
 
-    pub fn with_num_utxos(self, number: u8) -> Self {
+    pub fn with_num_utxos(self, number: u8) -> Self {
+        Self {
+            num_utxos: number,
+            ..self
+        }
+    }
+
+    pub fn with_descriptor_type(self, descriptor_type: DescriptorType) -> Self {
         Self {
-            num_utxos: number,
+            descriptor_type,
             ..self
         }
     }
@@ -2929,11 +4824,13 @@ impl TestWalletBuilder {
 
         let bdk_network = bitcoin::Network::Regtest;
 
-        let external_descriptor = Bip84(self.key, KeychainKind::External)
-            .build(bdk_network)
+        let external_descriptor = self
+            .descriptor_type
+            .descriptor(self.key, KeychainKind::External, bdk_network)
             .expect("Failed to build external descriptor for test wallet");
-        let internal_descriptor = Bip84(self.key, KeychainKind::Internal)
-            .build(bdk_network)
+        let internal_descriptor = self
+            .descriptor_type
+            .descriptor(self.key, KeychainKind::Internal, bdk_network)
             .expect("Failed to build internal descriptor for test wallet");
 
         let mut persister = bdk_wallet::rusqlite::Connection::open_in_memory()
@@ -2961,6 +4858,8 @@ impl TestWalletBuilder {
             network: Network::Regtest,
             finality_confirmations: 1,
             target_block: 1,
+            descriptor_type: self.descriptor_type,
+            swap_db: None,
         };
 
         let mut locked_wallet = wallet.wallet.try_lock().unwrap();
@@ -3004,6 +4903,30 @@ mod tests {
     use proptest::prelude::*;
     use tracing::level_filters::LevelFilter;
 
+    #[test]
+    fn sync_chunks_get_smaller_the_slower_the_servers_answer() {
+        let num_chunks = Wallet::<Connection>::num_sync_chunks;
+
+        // 2s per chunk at 100ms per batch of 32 are 640 scripts per chunk
+        assert_eq!(num_chunks(2_000, 5, Some(Duration::from_millis(100))), 4);
+        assert_eq!(num_chunks(2_000, 5, Some(Duration::from_millis(20))), 1);
+        assert_eq!(num_chunks(2_000, 5, Some(Duration::from_secs(1))), 5);
+        assert_eq!(num_chunks(2_000, 5, None), 4);
+    }
+
+    #[test]
+    fn sync_chunks_are_bounded_by_the_servers_and_the_wallet() {
+        let num_chunks = Wallet::<Connection>::num_sync_chunks;
+        let slow = Some(Duration::from_secs(2));
+
+        // A single server still gets a few chunks at once
+        assert_eq!(num_chunks(10_000, 1, slow), 3);
+        assert_eq!(num_chunks(10_000, 20, slow), 5);
+        // Every chunk fills at least one batch of 32 scripts
+        assert_eq!(num_chunks(64, 5, slow), 2);
+        assert_eq!(num_chunks(10, 5, slow), 1);
+    }
+
     #[test]
     fn given_depth_0_should_meet_confirmation_target_one() {
         let script = ScriptStatus::Confirmed(Confirmed { depth: 0 });
@@ -3133,6 +5056,204 @@ mod tests {
         assert_eq!(is_fee, relative_max);
     }
 
+    #[test]
+    fn given_full_mempool_histogram_fee_rate_should_be_near_the_tip() {
+        // 500k vbytes at 50 sat/vB, 500k at 20 sat/vB, then a long tail at 2 sat/vB
+        let histogram = vec![(2.0, 10_000_000), (50.0, 500_000), (20.0, 500_000)];
+
+        let fee_rate = fee_rate_from_histogram(histogram, 1).unwrap();
+
+        assert_eq!(fee_rate, FeeRate::from_sat_per_vb(20).unwrap());
+    }
+
+    #[test]
+    fn given_small_mempool_histogram_fee_rate_should_be_the_lowest() {
+        let histogram = vec![(5.0, 1_000), (3.0, 1_000), (f64::NAN, 1_000)];
+
+        let fee_rate = fee_rate_from_histogram(histogram, 1).unwrap();
+
+        assert_eq!(fee_rate, FeeRate::from_sat_per_vb(3).unwrap());
+    }
+
+    #[tokio::test]
+    async fn given_1BTC_and_explicit_2_sats_per_vb_should_pay_exactly_that() {
+        // 4000 weight = 1000 vbyte
+        let weight = Weight::from_wu(4000);
+        let amount = bitcoin::Amount::from_sat(100_000_000);
+
+        let fee_rate = FeeRate::from_sat_per_vb(2).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+        let fee = fee_for_fee_rate(weight, amount, fee_rate, relay_fee).unwrap();
+
+        assert_eq!(fee, bitcoin::Amount::from_sat(2_000));
+
+        // The fee has to cover the signed transaction, witness included. We pay more than
+        // 2 sat/vB here so the fee isn't raised to the minimum absolute fee.
+        let fee_rate = FeeRate::from_sat_per_vb(20).unwrap();
+        let wallet = TestWalletBuilder::new(100_000_000).build().await;
+        let psbt = wallet
+            .send_to_address_dynamic_fee(
+                wallet.new_address().await.unwrap(),
+                Amount::from_sat(10_000_000),
+                None,
+                CoinControl::default(),
+                Some(fee_rate),
+            )
+            .await
+            .unwrap();
+        let fee = psbt.fee().unwrap();
+        let vsize = wallet.sign_and_finalize(psbt).await.unwrap().vsize() as u64;
+
+        // Signatures may be a byte shorter than the worst case we pay for
+        assert!(
+            fee >= Amount::from_sat(vsize * 20),
+            "{} for {} vbyte",
+            fee,
+            vsize
+        );
+        assert!(
+            fee <= Amount::from_sat((vsize + 1) * 20),
+            "{} for {} vbyte",
+            fee,
+            vsize
+        );
+    }
+
+    #[test]
+    fn given_explicit_fee_rate_below_min_relay_fee_should_fail() {
+        let weight = Weight::from_wu(4000);
+        let amount = bitcoin::Amount::from_sat(100_000_000);
+
+        let fee_rate = FeeRate::from_sat_per_vb(1).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(5).unwrap();
+
+        assert!(fee_for_fee_rate(weight, amount, fee_rate, relay_fee).is_err());
+    }
+
+    #[test]
+    fn given_explicit_fee_rate_above_relative_max_should_fail() {
+        // 400 weight = 100 vbyte, 1000 sat/vb = 100k sats > 20% of 100k sats
+        let weight = Weight::from_wu(400);
+        let amount = bitcoin::Amount::from_sat(100_000);
+
+        let fee_rate = FeeRate::from_sat_per_vb(1_000).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+
+        assert!(fee_for_fee_rate(weight, amount, fee_rate, relay_fee).is_err());
+    }
+
+    #[test]
+    fn given_parent_paying_1_sat_per_vb_child_should_pay_for_both_at_10_sats_per_vb() {
+        // 400 weight = 100 vbyte each
+        let parent_weight = Weight::from_wu(400);
+        let child_weight = Weight::from_wu(400);
+        let parent_fee = bitcoin::Amount::from_sat(100);
+
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+        let child_fee =
+            cpfp_child_fee(parent_weight, parent_fee, child_weight, fee_rate, relay_fee).unwrap();
+
+        // 10 sat/vb * 200 vbytes = 2_000 sats for both, minus what the parent pays
+        assert_eq!(child_fee, Some(bitcoin::Amount::from_sat(1_900)));
+    }
+
+    #[test]
+    fn given_parent_already_paying_enough_should_not_create_child() {
+        let parent_weight = Weight::from_wu(400);
+        let child_weight = Weight::from_wu(400);
+        let parent_fee = bitcoin::Amount::from_sat(5_000);
+
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+        let child_fee =
+            cpfp_child_fee(parent_weight, parent_fee, child_weight, fee_rate, relay_fee).unwrap();
+
+        assert_eq!(child_fee, None);
+    }
+
+    #[test]
+    fn replacement_child_pays_the_incremental_relay_fee_on_top() {
+        // 400 weight = 100 vbyte
+        let child_weight = Weight::from_wu(400);
+        let previous_child_fee = bitcoin::Amount::from_sat(1_900);
+
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+        let fee = min_replacement_fee(previous_child_fee, child_weight, relay_fee).unwrap();
+
+        assert_eq!(fee, bitcoin::Amount::from_sat(2_000));
+    }
+
+    #[tokio::test]
+    async fn cpfp_child_spends_the_parent_output_back_to_the_wallet_paying_for_both() {
+        let wallet = TestWalletBuilder::new(100_000).build().await;
+        let recipient = "bcrt1q08pfqpsyrt7acllzyjm8q5qsz5capvyahm49rw"
+            .parse::<Address<NetworkUnchecked>>()
+            .unwrap()
+            .assume_checked();
+
+        // A parent paying 1 sat/vB, whose change comes back to the wallet
+        let parent_fee = Amount::from_sat(200);
+        let psbt = wallet
+            .send_to_address(
+                recipient,
+                Amount::from_sat(50_000),
+                parent_fee,
+                None,
+                CoinControl::default(),
+            )
+            .await
+            .unwrap();
+        let parent = wallet.sign_and_finalize(psbt).await.unwrap();
+        wallet
+            .wallet
+            .lock()
+            .await
+            .apply_unconfirmed_txs(vec![(parent.clone(), 0)]);
+
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        let relay_fee = FeeRate::from_sat_per_vb(1).unwrap();
+        let (psbt, child_fee) = wallet
+            .build_cpfp_child(&parent, parent_fee, None, fee_rate, relay_fee)
+            .await
+            .unwrap();
+        assert_eq!(psbt.fee().unwrap(), child_fee);
+        let child = wallet.sign_and_finalize(psbt).await.unwrap();
+
+        let change_vout = parent
+            .output
+            .iter()
+            .position(|output| output.value == Amount::from_sat(100_000 - 50_000 - 200))
+            .unwrap();
+        assert_eq!(child.input.len(), 1);
+        assert_eq!(
+            child.input[0].previous_output,
+            bitcoin::OutPoint::new(parent.compute_txid(), change_vout as u32)
+        );
+
+        assert_eq!(child.output.len(), 1);
+        assert_eq!(
+            child.output[0].value,
+            parent.output[change_vout].value - child_fee
+        );
+        assert!(wallet
+            .wallet
+            .lock()
+            .await
+            .is_mine(child.output[0].script_pubkey.clone()));
+
+        // Together they pay the target fee rate, rounded up to the next vbyte
+        let package_weight = parent.weight() + child.weight();
+        let package_fee = parent_fee + child_fee;
+        assert!(package_fee >= fee_rate.checked_mul_by_weight(package_weight).unwrap());
+        assert!(
+            package_fee
+                <= fee_rate
+                    .checked_mul_by_weight(package_weight + Weight::from_vb_unchecked(1))
+                    .unwrap()
+        );
+    }
+
     proptest! {
         #[test]
         fn given_randon_amount_random_fee_and_random_relay_rate_but_fix_weight_does_not_error(
@@ -3323,6 +5444,7 @@ mod tests {
                 Amount::from_sat(10_000),
                 spending_fee,
                 Some(custom_change.clone()),
+                CoinControl::default(),
             )
             .await
             .unwrap();
@@ -3337,6 +5459,365 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn taproot_wallet_can_fund_tx_lock() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_descriptor_type(DescriptorType::Bip86)
+            .build()
+            .await;
+        let address = wallet.new_address().await.unwrap();
+        assert_eq!(address.address_type(), Some(bitcoin::AddressType::P2tr));
+
+        let (amount, spending_fee) = wallet.max_giveable(TxLock::script_size()).await.unwrap();
+        let alice = PublicKey::random();
+        let bob = PublicKey::random();
+        let psbt: PartiallySignedTransaction =
+            TxLock::new(&wallet, amount, spending_fee, alice, bob, address)
+                .await
+                .unwrap()
+                .into();
+        let transaction = wallet.sign_and_finalize(psbt).await.unwrap();
+
+        assert!(transaction.weight() <= TxLock::weight(DescriptorType::Bip86));
+    }
+
+    #[tokio::test]
+    async fn exported_descriptors_can_be_watched() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_descriptor_type(DescriptorType::Bip86)
+            .build()
+            .await;
+
+        let public_descriptors = wallet.export_descriptors().await;
+        let descriptors = WalletDescriptors::watch_only(&public_descriptors).unwrap();
+
+        assert!(!public_descriptors.external.contains("prv"));
+        assert!(!public_descriptors.internal.contains("prv"));
+        assert_eq!(descriptors.descriptor_type, DescriptorType::Bip86);
+        assert!(descriptors.external.1.is_empty());
+        assert!(descriptors.internal.1.is_empty());
+    }
+
+    #[test]
+    fn watched_descriptors_are_stored_in_separate_files() {
+        let watch_only = |seed: u8| {
+            let key = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[seed; 32]).unwrap();
+            let descriptors =
+                WalletDescriptors::from_xpriv(key, DescriptorType::Bip84, Network::Regtest)
+                    .unwrap();
+
+            WalletDescriptors::watch_only(&PublicDescriptors {
+                external: descriptors.external.0.to_string(),
+                internal: descriptors.internal.0.to_string(),
+            })
+            .unwrap()
+        };
+
+        let file_name = watch_only(0).wallet_file_name();
+
+        assert_eq!(file_name, watch_only(0).wallet_file_name());
+        assert_ne!(file_name, watch_only(1).wallet_file_name());
+        assert_ne!(file_name, DescriptorType::Bip84.wallet_file_name());
+    }
+
+    #[test]
+    fn private_descriptors_cannot_be_watched() {
+        let key = bitcoin::bip32::Xpriv::new_master(Network::Regtest, &[0u8; 32]).unwrap();
+        let (descriptor, keys, _) = DescriptorType::Bip84
+            .descriptor(key, KeychainKind::External, Network::Regtest)
+            .unwrap();
+        let private_descriptor = descriptor.to_string_with_secret(&keys);
+
+        let result = WalletDescriptors::watch_only(&PublicDescriptors {
+            external: private_descriptor.clone(),
+            internal: private_descriptor,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn unsigned_psbt_can_be_signed_externally() {
+        let wallet = TestWalletBuilder::new(50_000).build().await;
+        let address = wallet.new_address().await.unwrap();
+
+        let mut psbt = wallet
+            .create_unsigned_psbt(address, Amount::from_sat(10_000))
+            .await
+            .unwrap();
+
+        assert!(psbt
+            .inputs
+            .iter()
+            .all(|input| input.partial_sigs.is_empty() && !input.bip32_derivation.is_empty()));
+        assert!(wallet.finalize_signed_psbt(psbt.clone()).await.is_err());
+
+        // Sign without finalizing, like an external signer
+        let sign_options = SignOptions {
+            try_finalize: false,
+            ..SignOptions::default()
+        };
+        wallet
+            .wallet
+            .lock()
+            .await
+            .sign(&mut psbt, sign_options)
+            .unwrap();
+
+        let transaction = wallet.finalize_signed_psbt(psbt).await.unwrap();
+        assert!(transaction
+            .input
+            .iter()
+            .all(|input| !input.witness.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn lists_unspent_outputs() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(2)
+            .build()
+            .await;
+
+        let utxos = wallet.list_unspent().await;
+
+        assert_eq!(utxos.len(), 2);
+        for utxo in utxos {
+            assert_eq!(utxo.value, Amount::from_sat(50_000));
+            // Received in block 42, the latest block is 43
+            assert_eq!(utxo.confirmations, 2);
+            assert_eq!(utxo.keychain, KeychainKind::External);
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_spend_excluded_outputs() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(2)
+            .build()
+            .await;
+        let utxos = wallet.list_unspent().await;
+        let coin_control = CoinControl {
+            utxo_include: vec![],
+            utxo_exclude: vec![utxos[0].outpoint],
+        };
+
+        let psbt = wallet
+            .send_to_address(
+                wallet.new_address().await.unwrap(),
+                Amount::from_sat(10_000),
+                Amount::from_sat(1000),
+                None,
+                coin_control,
+            )
+            .await
+            .unwrap();
+
+        let inputs: Vec<_> = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect();
+        assert_eq!(inputs, vec![utxos[1].outpoint]);
+    }
+
+    #[tokio::test]
+    async fn sweeps_all_outputs_into_one() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(3)
+            .build()
+            .await;
+
+        let psbt = wallet
+            .sweep_to(wallet.new_address().await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(psbt.unsigned_tx.input.len(), 3);
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        let fee = Amount::from_sat(150_000) - psbt.unsigned_tx.output[0].value;
+        assert!(fee >= MIN_ABSOLUTE_TX_FEE);
+    }
+
+    #[tokio::test]
+    async fn cannot_sweep_empty_wallet() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(0)
+            .build()
+            .await;
+
+        let result = wallet.sweep_to(wallet.new_address().await.unwrap()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn sweeps_only_included_outputs() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(2)
+            .build()
+            .await;
+        let utxos = wallet.list_unspent().await;
+        let coin_control = CoinControl {
+            utxo_include: vec![utxos[0].outpoint],
+            utxo_exclude: vec![],
+        };
+
+        let psbt = wallet
+            .sweep_balance_to_address_dynamic_fee(
+                wallet.new_address().await.unwrap(),
+                coin_control,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(psbt.unsigned_tx.input.len(), 1);
+        assert_eq!(psbt.unsigned_tx.input[0].previous_output, utxos[0].outpoint);
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert!(psbt.unsigned_tx.output[0].value < Amount::from_sat(50_000));
+    }
+
+    #[tokio::test]
+    async fn remembers_address_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wallet = TestWalletBuilder::new(50_000).build().await;
+        wallet.swap_db = Some(
+            crate::database::open_db(
+                dir.path().join("sqlite"),
+                crate::database::AccessMode::ReadWrite,
+                None,
+                None,
+            )
+            .await
+            .unwrap(),
+        );
+        let swap_id = Uuid::new_v4();
+
+        let deposit = wallet
+            .new_address_with_label("Swap deposit", Some(swap_id))
+            .await
+            .unwrap();
+        let withdrawal = wallet.new_address().await.unwrap();
+        wallet
+            .label_address(&withdrawal, "Withdrawal", None)
+            .await
+            .unwrap();
+        wallet
+            .label_address(&deposit, "Swap refund", Some(swap_id))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            wallet.address_labels().await.unwrap(),
+            vec![
+                AddressLabel {
+                    address: deposit.to_string(),
+                    label: "Swap refund".to_string(),
+                    swap_id: Some(swap_id),
+                },
+                AddressLabel {
+                    address: withdrawal.to_string(),
+                    label: "Withdrawal".to_string(),
+                    swap_id: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn consolidates_confirmed_outputs_when_fees_are_low() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(6)
+            .with_fees(2, 1)
+            .build()
+            .await;
+
+        let too_low = FeeRate::from_sat_per_vb(1).unwrap();
+        assert!(wallet.build_consolidation(too_low).await.unwrap().is_none());
+
+        let psbt = wallet
+            .build_consolidation(FeeRate::from_sat_per_vb(5).unwrap())
+            .await
+            .unwrap()
+            .expect("outputs to be consolidated");
+
+        assert_eq!(psbt.unsigned_tx.input.len(), 6);
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert!(psbt.unsigned_tx.output[0].value < Amount::from_sat(300_000));
+    }
+
+    #[tokio::test]
+    async fn does_not_consolidate_few_outputs() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_num_utxos(2)
+            .build()
+            .await;
+
+        let psbt = wallet
+            .build_consolidation(FeeRate::from_sat_per_vb(5).unwrap())
+            .await
+            .unwrap();
+
+        assert!(psbt.is_none());
+    }
+
+    #[tokio::test]
+    async fn notifies_subscribers_of_status_changes_and_drops_abandoned_subscriptions() {
+        // The balancer only connects on the first request
+        let mut client = Client::new(&["tcp://127.0.0.1:1".to_string()], Duration::from_secs(60))
+            .await
+            .unwrap();
+        client.latest_block_height = BlockHeight::from(100);
+
+        let txid = Txid::from_raw_hash(bitcoin::hashes::sha256d::Hash::all_zeros());
+        let script = ScriptBuf::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let abandoned_txid =
+            Txid::from_raw_hash(bitcoin::hashes::sha256d::Hash::hash(b"abandoned"));
+
+        let (sender, receiver) = watch::channel(ScriptStatus::Unseen);
+        let (abandoned_sender, _) = watch::channel(ScriptStatus::Unseen);
+        for (txid, sender) in [(txid, sender), (abandoned_txid, abandoned_sender)] {
+            client.subscriptions.insert(
+                (txid, script.clone()),
+                SubscriptionSender {
+                    sender: Arc::new(sender),
+                    last_status: Some(ScriptStatus::Unseen),
+                    resumable: true,
+                },
+            );
+        }
+
+        client.script_history.insert(
+            script.clone(),
+            vec![GetHistoryRes {
+                height: 0,
+                tx_hash: txid,
+                fee: None,
+            }],
+        );
+        let finished = client.notify_subscribers(1);
+
+        assert_eq!(*receiver.borrow(), ScriptStatus::InMempool);
+        assert_eq!(finished, vec![(abandoned_txid, script.clone())]);
+        assert_eq!(client.subscriptions.len(), 1);
+
+        client.script_history.insert(
+            script.clone(),
+            vec![GetHistoryRes {
+                height: 100,
+                tx_hash: txid,
+                fee: None,
+            }],
+        );
+        let finished = client.notify_subscribers(1);
+
+        assert!(receiver.borrow().is_confirmed_with(1_u32));
+        assert_eq!(finished, vec![(txid, script)]);
+        // Final, but still being waited for
+        assert_eq!(client.subscriptions.len(), 1);
+    }
+
     #[test]
     fn printing_status_change_doesnt_spam_on_same_status() {
         let writer = capture_logs(LevelFilter::TRACE);
@@ -3678,3 +6159,22 @@ impl SyncRequestBuilderFactory {
             .spks_with_indexes(self.spks)
     }
 }
+
+/// A full scan request can only be used once, this lets us build a new one
+/// for every Electrum server we try.
+///
+/// Every factory covers one chunk of the scripts of a single keychain.
+#[derive(Clone)]
+struct FullScanRequestBuilderFactory {
+    chain_tip: CheckPoint,
+    keychain: KeychainKind,
+    spks: Vec<(u32, ScriptBuf)>,
+}
+
+impl FullScanRequestBuilderFactory {
+    fn build(self) -> FullScanRequestBuilder<KeychainKind> {
+        FullScanRequest::builder()
+            .chain_tip(self.chain_tip)
+            .spks_for_keychain(self.keychain, self.spks)
+    }
+}
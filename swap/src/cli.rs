@@ -2,15 +2,18 @@ pub mod api;
 mod behaviour;
 pub mod cancel_and_refund;
 pub mod command;
+pub mod cost_breakdown;
 mod event_loop;
+pub mod history_export;
 mod list_sellers;
+pub mod rendezvous_bootstrap;
 pub mod transport;
 pub mod watcher;
 
 pub use behaviour::{Behaviour, OutEvent};
 pub use cancel_and_refund::{cancel, cancel_and_refund, refund};
-pub use event_loop::{EventLoop, EventLoopHandle};
-pub use list_sellers::{list_sellers, SellerStatus};
+pub use event_loop::{BufferedTransferProofs, EventLoop, EventLoopHandle};
+pub use list_sellers::{discover, list_sellers, Discovery, SellerStatus};
 
 #[cfg(test)]
 mod tests {
@@ -112,6 +115,7 @@ mod tests {
             price: bitcoin::Amount::from_sat(1337),
             min_quantity: bitcoin::Amount::from_sat(42),
             max_quantity: bitcoin::Amount::from_sat(9001),
+            expires_at: None,
         };
 
         let mut asb = new_swarm(|identity| {
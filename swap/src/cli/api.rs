@@ -1,11 +1,12 @@
 pub mod request;
+pub mod rpc;
 pub mod tauri_bindings;
 
 use crate::cli::command::{Bitcoin, Monero};
-use crate::common::tor::init_tor_client;
+use crate::common::tor::{init_tor_client, spawn_socks5_proxy};
 use crate::common::tracing_util::Format;
-use crate::database::{open_db, AccessMode};
-use crate::env::{Config as EnvConfig, GetConfig, Mainnet, Testnet};
+use crate::database::{open_db, AccessMode, StateEncryption};
+use crate::env::{Config as EnvConfig, Confirmations, GetConfig, Mainnet, Testnet};
 use crate::fs::system_data_dir;
 use crate::monero::Wallets;
 use crate::network::rendezvous::XmrBtcNamespace;
@@ -15,6 +16,9 @@ use crate::{bitcoin, common, monero};
 use anyhow::{bail, Context as AnyContext, Error, Result};
 use arti_client::TorClient;
 use futures::future::try_join_all;
+use futures::FutureExt;
+use monero_rpc_pool::embedded::EmbeddedPool;
+use std::collections::HashSet;
 use std::fmt;
 use std::future::Future;
 use std::path::{Path, PathBuf};
@@ -30,6 +34,7 @@ use tracing::Level;
 use uuid::Uuid;
 
 use super::watcher::Watcher;
+use super::BufferedTransferProofs;
 
 static START: Once = Once::new();
 
@@ -73,77 +78,105 @@ impl PendingTaskList {
     }
 }
 
-/// The `SwapLock` manages the state of the current swap, ensuring that only one swap can be active at a time.
+/// The `SwapLock` keeps track of the swaps that are currently running. Different swaps can run
+/// concurrently, but each swap can only be run once at a time.
 /// It includes:
-/// - A lock for the current swap (`current_swap`)
+/// - The ids of the running swaps (`current_swaps`)
 /// - A broadcast channel for suspension signals (`suspension_trigger`)
 ///
-/// The `SwapLock` provides methods to acquire and release the swap lock, and to listen for suspension signals.
-/// This ensures that swap operations do not overlap and can be safely suspended if needed.
+/// The `SwapLock` provides methods to acquire and release the lock of a swap, and to listen for suspension signals.
+/// This ensures that operations on the same swap do not overlap and can be safely suspended if needed.
 pub struct SwapLock {
-    current_swap: RwLock<Option<Uuid>>,
-    suspension_trigger: Sender<()>,
+    current_swaps: RwLock<HashSet<Uuid>>,
+    /// Carries the swap to suspend, or `None` to suspend all of them.
+    suspension_trigger: Sender<Option<Uuid>>,
 }
 
 impl SwapLock {
     pub fn new() -> Self {
         let (suspension_trigger, _) = broadcast::channel(10);
         SwapLock {
-            current_swap: RwLock::new(None),
+            current_swaps: RwLock::new(HashSet::new()),
             suspension_trigger,
         }
     }
 
-    pub async fn listen_for_swap_force_suspension(&self) -> Result<(), Error> {
+    /// Resolves once the given swap, or all swaps, are to be suspended.
+    pub async fn listen_for_swap_force_suspension(&self, swap_id: Uuid) -> Result<(), Error> {
         let mut listener = self.suspension_trigger.subscribe();
-        let event = listener.recv().await;
-        match event {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                tracing::error!("Error receiving swap suspension signal: {}", e);
-                bail!(e)
+        loop {
+            match listener.recv().await {
+                Ok(None) => return Ok(()),
+                Ok(Some(suspended_swap_id)) if suspended_swap_id == swap_id => return Ok(()),
+                Ok(Some(_)) => continue,
+                Err(e) => {
+                    tracing::error!("Error receiving swap suspension signal: {}", e);
+                    bail!(e)
+                }
             }
         }
     }
 
     pub async fn acquire_swap_lock(&self, swap_id: Uuid) -> Result<(), Error> {
-        let mut current_swap = self.current_swap.write().await;
-        if current_swap.is_some() {
-            bail!("There already exists an active swap lock");
+        let mut current_swaps = self.current_swaps.write().await;
+        if current_swaps.contains(&swap_id) {
+            bail!("Swap {} is already running", swap_id);
         }
 
         tracing::debug!(swap_id = %swap_id, "Acquiring swap lock");
-        *current_swap = Some(swap_id);
+        current_swaps.insert(swap_id);
         Ok(())
     }
 
-    pub async fn get_current_swap_id(&self) -> Option<Uuid> {
-        *self.current_swap.read().await
+    pub async fn is_swap_running(&self, swap_id: Uuid) -> bool {
+        self.current_swaps.read().await.contains(&swap_id)
+    }
+
+    pub async fn get_current_swap_ids(&self) -> Vec<Uuid> {
+        self.current_swaps.read().await.iter().copied().collect()
     }
 
     /// Sends a signal to suspend all ongoing swap processes.
     ///
     /// This function performs the following steps:
-    /// 1. Triggers the suspension by sending a unit `()` signal to all listeners via `self.suspension_trigger`.
-    /// 2. Polls the `current_swap` state every 50 milliseconds to check if it has been set to `None`, indicating that the swap processes have been suspended and the lock released.
-    /// 3. If the lock is not released within 10 seconds, the function returns an error.
+    /// 1. Triggers the suspension by sending a `None` signal to all listeners via `self.suspension_trigger`.
+    /// 2. Polls `current_swaps` every 50 milliseconds to check if it is empty, indicating that the swap processes have been suspended and their locks released.
+    /// 3. If the locks are not released within 10 seconds, the function returns an error.
     ///
     /// If we send a suspend signal while no swap is in progress, the function will not fail, but will return immediately.
     ///
     /// # Returns
-    /// - `Ok(())` if the swap lock is successfully released.
-    /// - `Err(Error)` if the function times out waiting for the swap lock to be released.
+    /// - `Ok(())` if the swap locks are successfully released.
+    /// - `Err(Error)` if the function times out waiting for the swap locks to be released.
     ///
     /// # Notes
     /// The 50ms polling interval is considered negligible overhead compared to the typical time required to suspend ongoing swap processes.
     pub async fn send_suspend_signal(&self) -> Result<(), Error> {
+        self.suspend(None).await
+    }
+
+    /// Like [`SwapLock::send_suspend_signal`], but only suspends the given swap. The other swaps
+    /// keep running.
+    pub async fn send_suspend_signal_for(&self, swap_id: Uuid) -> Result<(), Error> {
+        if !self.is_swap_running(swap_id).await {
+            bail!("Swap {} is not running", swap_id);
+        }
+
+        self.suspend(Some(swap_id)).await
+    }
+
+    async fn suspend(&self, swap_id: Option<Uuid>) -> Result<(), Error> {
         const TIMEOUT: u64 = 10_000;
         const INTERVAL: u64 = 50;
 
-        let _ = self.suspension_trigger.send(())?;
+        let _ = self.suspension_trigger.send(swap_id)?;
 
         for _ in 0..(TIMEOUT / INTERVAL) {
-            if self.get_current_swap_id().await.is_none() {
+            let released = match swap_id {
+                Some(swap_id) => !self.is_swap_running(swap_id).await,
+                None => self.current_swaps.read().await.is_empty(),
+            };
+            if released {
                 return Ok(());
             }
             tokio::time::sleep(tokio::time::Duration::from_millis(INTERVAL)).await;
@@ -152,17 +185,13 @@ impl SwapLock {
         bail!("Timed out waiting for swap lock to be released");
     }
 
-    pub async fn release_swap_lock(&self) -> Result<Uuid, Error> {
-        let mut current_swap = self.current_swap.write().await;
-        if let Some(swap_id) = current_swap.as_ref() {
+    pub async fn release_swap_lock(&self, swap_id: Uuid) -> Result<(), Error> {
+        let mut current_swaps = self.current_swaps.write().await;
+        if current_swaps.remove(&swap_id) {
             tracing::debug!(swap_id = %swap_id, "Releasing swap lock");
-
-            let prev_swap_id = *swap_id;
-            *current_swap = None;
-            drop(current_swap);
-            Ok(prev_swap_id)
+            Ok(())
         } else {
-            bail!("There is no current swap lock to release");
+            bail!("There is no swap lock for swap {} to release", swap_id);
         }
     }
 }
@@ -184,6 +213,8 @@ pub struct Context {
     pub db: Arc<dyn Database + Send + Sync>,
     pub swap_lock: Arc<SwapLock>,
     pub config: Config,
+    /// Shared by the event loops of the running swaps, see [`BufferedTransferProofs`].
+    buffered_transfer_proofs: BufferedTransferProofs,
     pub tasks: Arc<PendingTaskList>,
     tauri_handle: Option<TauriHandle>,
     bitcoin_wallet: Option<Arc<bitcoin::Wallet>>,
@@ -203,6 +234,8 @@ pub struct ContextBuilder {
     debug: bool,
     json: bool,
     tor: bool,
+    seed_password: Option<String>,
+    confirmations: Confirmations,
     tauri_handle: Option<TauriHandle>,
 }
 
@@ -226,6 +259,8 @@ impl ContextBuilder {
             debug: false,
             json: false,
             tor: false,
+            seed_password: None,
+            confirmations: Confirmations::default(),
             tauri_handle: None,
         }
     }
@@ -279,13 +314,34 @@ impl ContextBuilder {
         self
     }
 
+    /// The password that unlocks the seed (default none).
+    ///
+    /// If the seed is not encrypted yet, it is encrypted with the password from now on and so
+    /// are the swap states in the database. An empty password counts as none.
+    pub fn with_seed_password(mut self, seed_password: impl Into<Option<String>>) -> Self {
+        self.seed_password = seed_password.into().filter(|password| !password.is_empty());
+        self
+    }
+
+    /// Override the confirmation targets of the network (default none). They are validated
+    /// when the Context is built.
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
     /// Takes the builder, initializes the context by initializing the wallets and other components and returns the Context.
     pub async fn build(self) -> Result<Context> {
         // These are needed for everything else, and are blocking calls
         let data_dir = &data::data_dir_from(self.data, self.is_testnet)?;
-        let env_config = env_config_from(self.is_testnet);
-        let seed = &Seed::from_file_or_generate(data_dir.as_path())
-            .context("Failed to read seed in file")?;
+        let env_config = env_config_from(self.is_testnet)
+            .with_confirmations(self.confirmations)
+            .context("Invalid confirmation targets")?;
+        let seed = &Seed::from_file_or_generate_with_password(
+            data_dir.as_path(),
+            self.seed_password.as_deref(),
+        )
+        .context("Failed to read seed in file")?;
 
         // Initialize logging
         let format = if self.json { Format::Json } else { Format::Raw };
@@ -324,10 +380,17 @@ impl ContextBuilder {
                 (),
             );
 
+        // The key is derived from the seed, so this only helps if the seed is encrypted as well
+        let state_encryption = self
+            .seed_password
+            .is_some()
+            .then(|| StateEncryption::new(seed.derive_database_encryption_key()));
+
         let db = open_db(
             data_dir.join("sqlite"),
             AccessMode::ReadWrite,
             self.tauri_handle.clone(),
+            state_encryption,
         )
         .await?;
 
@@ -335,9 +398,42 @@ impl ContextBuilder {
 
         let tauri_handle = &self.tauri_handle.clone();
 
+        let initialize_tor_client = async {
+            // Don't init a tor client unless we should use it.
+            if !self.tor {
+                tracing::warn!("Internal Tor client not enabled, skipping initialization");
+                return None;
+            }
+
+            init_tor_client(data_dir, tauri_handle.clone())
+                .await
+                .inspect_err(|err| {
+                    tracing::warn!(%err, "Failed to create Tor client. We will continue without Tor");
+                })
+                .ok()
+        }
+        // The Bitcoin wallet waits for the Tor client as well
+        .shared();
+
         let initialize_bitcoin_wallet = async {
             match self.bitcoin {
                 Some(bitcoin) => {
+                    let descriptor_type = if bitcoin.bitcoin_taproot {
+                        bitcoin::wallet::DescriptorType::Bip86
+                    } else {
+                        bitcoin::wallet::DescriptorType::Bip84
+                    };
+                    // Unless a proxy is given, the Electrum connections go through our Tor
+                    // client if we use one
+                    let socks5_proxy = match bitcoin.bitcoin_electrum_socks5_proxy.clone() {
+                        Some(proxy) => Some(proxy),
+                        None => match initialize_tor_client.clone().await {
+                            Some(tor_client) => {
+                                Some(spawn_socks5_proxy(tor_client).await?.to_string())
+                            }
+                            None => None,
+                        },
+                    };
                     let (urls, target_block) = bitcoin.apply_defaults(self.is_testnet)?;
 
                     let bitcoin_progress_handle = tauri_handle
@@ -352,6 +448,9 @@ impl ContextBuilder {
                         data_dir,
                         env_config,
                         target_block,
+                        descriptor_type,
+                        socks5_proxy,
+                        db.clone(),
                         self.tauri_handle.clone(),
                     )
                     .await?;
@@ -377,25 +476,22 @@ impl ContextBuilder {
 
                     // If we are instructed to use a pool, we start it and use it
                     // Otherwise we use the single node address provided by the user
-                    let (monero_node_address, rpc_pool_handle) = match monero_config {
+                    let (monero_daemon, rpc_pool_handle) = match monero_config {
                         MoneroNodeConfig::Pool => {
                             // Start RPC pool and use it
-                            let (server_info, mut status_receiver, pool_handle) =
-                                monero_rpc_pool::start_server_with_random_port(
-                                    monero_rpc_pool::config::Config::new_random_port(
-                                        "127.0.0.1".to_string(),
-                                        data_dir.join("monero-rpc-pool"),
-                                    ),
-                                    match self.is_testnet {
-                                        true => crate::monero::Network::Stagenet,
-                                        false => crate::monero::Network::Mainnet,
-                                    },
-                                )
-                                .await?;
-
-                            let rpc_url =
-                                format!("http://{}:{}", server_info.host, server_info.port);
-                            tracing::info!("Monero RPC Pool started on {}", rpc_url);
+                            let EmbeddedPool {
+                                daemon,
+                                mut status_receiver,
+                                handle: pool_handle,
+                                ..
+                            } = monero_rpc_pool::embedded::spawn_for_wallet(
+                                data_dir.join("monero-rpc-pool"),
+                                match self.is_testnet {
+                                    true => crate::monero::Network::Stagenet,
+                                    false => crate::monero::Network::Mainnet,
+                                },
+                            )
+                            .await?;
 
                             // Start listening for pool status updates and forward them to frontend
                             if let Some(ref handle) = self.tauri_handle {
@@ -407,14 +503,21 @@ impl ContextBuilder {
                                 });
                             }
 
-                            (rpc_url, Some(Arc::new(pool_handle)))
+                            (daemon, Some(Arc::new(pool_handle)))
                         }
-                        MoneroNodeConfig::SingleNode { url } => (url, None),
+                        MoneroNodeConfig::SingleNode { url } => (
+                            monero_sys::Daemon {
+                                address: url,
+                                ssl: false,
+                                ..Default::default()
+                            },
+                            None,
+                        ),
                     };
 
                     let wallets = init_monero_wallet(
                         data_dir.as_path(),
-                        monero_node_address,
+                        monero_daemon,
                         env_config,
                         tauri_handle.clone(),
                     )
@@ -428,40 +531,23 @@ impl ContextBuilder {
             }
         };
 
-        let initialize_tor_client = async {
-            // Don't init a tor client unless we should use it.
-            if !self.tor {
-                tracing::warn!("Internal Tor client not enabled, skipping initialization");
-                return Ok(None);
-            }
-
-            let maybe_tor_client = init_tor_client(data_dir, tauri_handle.clone())
-                .await
-                .inspect_err(|err| {
-                    tracing::warn!(%err, "Failed to create Tor client. We will continue without Tor");
-                })
-                .ok();
-
-            Ok(maybe_tor_client)
-        };
-
         let (bitcoin_wallet, (monero_manager, monero_rpc_pool_handle), tor) = tokio::try_join!(
             initialize_bitcoin_wallet,
             initialize_monero_wallet,
-            initialize_tor_client,
+            initialize_tor_client.clone().map(Ok::<_, Error>),
         )?;
 
-        // If we have a bitcoin wallet and a tauri handle, we start a background task
+        // If we have a bitcoin wallet, we start a background task which refunds unfinished
+        // swaps once their cancel timelock expires. This is not limited to the GUI, a CLI
+        // left running should not miss a timelock either.
         if let Some(wallet) = bitcoin_wallet.clone() {
-            if self.tauri_handle.is_some() {
-                let watcher = Watcher::new(
-                    wallet,
-                    db.clone(),
-                    self.tauri_handle.clone(),
-                    swap_lock.clone(),
-                );
-                tokio::spawn(watcher.run());
-            }
+            let watcher = Watcher::new(
+                wallet,
+                db.clone(),
+                self.tauri_handle.clone(),
+                swap_lock.clone(),
+            );
+            tokio::spawn(watcher.run());
         }
 
         tauri_handle.emit_context_init_progress_event(TauriContextStatusEvent::Available);
@@ -480,6 +566,7 @@ impl ContextBuilder {
                 data_dir: data_dir.clone(),
             },
             swap_lock,
+            buffered_transfer_proofs: Default::default(),
             tasks,
             tauri_handle: self.tauri_handle,
             tor_client: tor,
@@ -510,10 +597,11 @@ impl Context {
             bitcoin_wallet: Some(bob_bitcoin_wallet),
             monero_manager: Some(bob_monero_wallet),
             config,
-            db: open_db(db_path, AccessMode::ReadWrite, None)
+            db: open_db(db_path, AccessMode::ReadWrite, None, None)
                 .await
                 .expect("Could not open sqlite database"),
             swap_lock: SwapLock::new().into(),
+            buffered_transfer_proofs: Default::default(),
             tasks: PendingTaskList::default().into(),
             tauri_handle: None,
             tor_client: None,
@@ -548,6 +636,9 @@ async fn init_bitcoin_wallet(
     data_dir: &Path,
     env_config: EnvConfig,
     bitcoin_target_block: u16,
+    descriptor_type: bitcoin::wallet::DescriptorType,
+    electrum_socks5_proxy: Option<String>,
+    db: Arc<dyn Database + Send + Sync>,
     tauri_handle_option: Option<TauriHandle>,
 ) -> Result<bitcoin::Wallet<bdk_wallet::rusqlite::Connection, bitcoin::wallet::Client>> {
     let mut builder = bitcoin::wallet::WalletBuilder::default()
@@ -559,12 +650,18 @@ async fn init_bitcoin_wallet(
         })
         .finality_confirmations(env_config.bitcoin_finality_confirmations)
         .target_block(bitcoin_target_block)
-        .sync_interval(env_config.bitcoin_sync_interval());
+        .descriptor_type(descriptor_type)
+        .sync_interval(env_config.bitcoin_sync_interval())
+        .swap_db(db);
 
     if let Some(handle) = tauri_handle_option {
         builder = builder.tauri_handle(handle.clone());
     }
 
+    if let Some(proxy) = electrum_socks5_proxy {
+        builder = builder.electrum_socks5_proxy(proxy);
+    }
+
     let wallet = builder
         .build()
         .await
@@ -575,18 +672,13 @@ async fn init_bitcoin_wallet(
 
 async fn init_monero_wallet(
     data_dir: &Path,
-    monero_daemon_address: String,
+    daemon: monero_sys::Daemon,
     env_config: EnvConfig,
     tauri_handle: Option<TauriHandle>,
 ) -> Result<Arc<Wallets>> {
     let network = env_config.monero_network;
     let wallet_dir = data_dir.join("monero").join("monero-data");
 
-    let daemon = monero_sys::Daemon {
-        address: monero_daemon_address,
-        ssl: false,
-    };
-
     // This is the name of a wallet we only use for blockchain monitoring
     const DEFAULT_WALLET: &str = "swap-tool-blockchain-monitoring-wallet";
 
@@ -1,9 +1,14 @@
 use super::tauri_bindings::TauriHandle;
+use crate::bitcoin::wallet::{ScriptStatus, Watchable};
 use crate::bitcoin::{wallet, CancelTimelock, ExpiredTimelocks, PunishTimelock, TxLock};
 use crate::cli::api::tauri_bindings::{TauriEmitter, TauriSwapProgressEvent};
 use crate::cli::api::Context;
-use crate::cli::list_sellers::{QuoteWithAddress, UnreachableSeller};
-use crate::cli::{list_sellers as list_sellers_impl, EventLoop, SellerStatus};
+use crate::cli::cost_breakdown::{self, SwapCostBreakdown};
+use crate::cli::history_export::{self, ExportFormat, ExportedSwap};
+use crate::cli::list_sellers::{
+    rank_makers, select_maker, DiscoveredMaker, QuoteWithAddress, UnreachableSeller,
+};
+use crate::cli::{discover as discover_sellers, rendezvous_bootstrap, EventLoop, SellerStatus};
 use crate::common::{get_logs, redact};
 use crate::libp2p_ext::MultiAddrExt;
 use crate::monero::wallet_rpc::MoneroDaemon;
@@ -11,23 +16,30 @@ use crate::monero::MoneroAddressPool;
 use crate::network::quote::{BidQuote, ZeroQuoteReceived};
 use crate::network::swarm;
 use crate::protocol::bob::{BobState, Swap};
-use crate::protocol::{bob, State};
-use crate::{bitcoin, cli, monero};
+use crate::protocol::{bob, DatabaseCheck, PeerStats, State};
+use crate::{bitcoin, cli, kraken, monero};
 use ::bitcoin::address::NetworkUnchecked;
-use ::bitcoin::Txid;
+use ::bitcoin::{FeeRate, OutPoint, Txid};
 use ::monero::Network;
 use anyhow::{bail, Context as AnyContext, Result};
 use libp2p::core::Multiaddr;
+use libp2p::multiaddr::Protocol;
 use libp2p::PeerId;
 use once_cell::sync::Lazy;
 use qrcode::render::unicode;
 use qrcode::QrCode;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use serde_with::{serde_as, DisplayFromStr};
 use std::cmp::min;
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::fmt;
 use std::future::Future;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
@@ -52,15 +64,63 @@ fn get_swap_tracing_span(swap_id: Uuid) -> Span {
     debug_span!("swap", swap_id = %swap_id)
 }
 
+/// With `auto`, we refuse makers asking more than this above the median quote by default.
+const DEFAULT_MAX_SPREAD: Decimal = dec!(0.05);
+
 // BuyXmr
+#[serde_as]
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct BuyXmrArgs {
+    /// The address of the seller, including its peer id, or `auto`
+    #[serde_as(as = "DisplayFromStr")]
     #[typeshare(serialized_as = "string")]
-    pub seller: Multiaddr,
+    pub seller: SellerChoice,
     #[typeshare(serialized_as = "Option<string>")]
     pub bitcoin_change_address: Option<bitcoin::Address<NetworkUnchecked>>,
     pub monero_receive_pool: MoneroAddressPool,
+    /// Where to look for makers if the seller is `auto`
+    #[serde(default)]
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub rendezvous_points: Vec<Multiaddr>,
+    /// If the seller is `auto`, refuse makers asking more than this fraction above the median
+    /// quote (defaults to 5%)
+    #[serde(default)]
+    #[typeshare(serialized_as = "Option<number>")]
+    pub max_spread: Option<Decimal>,
+    /// Swap exactly this amount instead of everything we deposit, up to the maker's maximum.
+    /// It has to be within the limits of the maker's quote.
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub amount: Option<bitcoin::Amount>,
+}
+
+/// The maker to swap with: a specific one, or `auto` to pick the best maker we discover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SellerChoice {
+    Auto,
+    Address(Multiaddr),
+}
+
+impl FromStr for SellerChoice {
+    type Err = libp2p::multiaddr::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            return Ok(SellerChoice::Auto);
+        }
+
+        Ok(SellerChoice::Address(s.parse()?))
+    }
+}
+
+impl fmt::Display for SellerChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SellerChoice::Auto => f.write_str("auto"),
+            SellerChoice::Address(address) => write!(f, "{}", address),
+        }
+    }
 }
 
 #[typeshare]
@@ -106,6 +166,27 @@ impl Request for ResumeSwapArgs {
     }
 }
 
+// ResumeAllSwaps
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResumeAllSwapsArgs;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResumeAllSwapsResponse {
+    /// The swaps we resumed
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub swap_ids: Vec<Uuid>,
+}
+
+impl Request for ResumeAllSwapsArgs {
+    type Response = ResumeAllSwapsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        resume_all_swaps(ctx).await
+    }
+}
+
 // CancelAndRefund
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -144,12 +225,27 @@ impl Request for MoneroRecoveryArgs {
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct WithdrawBtcArgs {
+    /// The amount to withdraw. If not given, all spendable funds are swept to the address
+    /// and the fee is paid out of them.
     #[typeshare(serialized_as = "number")]
     #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
     pub amount: Option<bitcoin::Amount>,
     #[typeshare(serialized_as = "string")]
     #[serde(with = "crate::bitcoin::address_serde")]
     pub address: bitcoin::Address,
+    /// Outputs of the wallet the withdrawal has to spend. If no amount is given, only these
+    /// are swept.
+    #[typeshare(serialized_as = "Vec<string>")]
+    #[serde(default)]
+    pub utxo_include: Vec<OutPoint>,
+    /// Outputs of the wallet the withdrawal must not spend.
+    #[typeshare(serialized_as = "Vec<string>")]
+    #[serde(default)]
+    pub utxo_exclude: Vec<OutPoint>,
+    /// The fee rate to pay in sat/vB. If not given, the fee rate is estimated.
+    #[typeshare(serialized_as = "number")]
+    #[serde(default)]
+    pub fee_rate_sat_per_vb: Option<u64>,
 }
 
 #[typeshare]
@@ -174,7 +270,7 @@ impl Request for WithdrawBtcArgs {
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ListSellersArgs {
     /// The rendezvous points to search for sellers
-    /// The address must contain a peer ID
+    /// The address must contain a peer ID, or be a DNS seed like `/dnsaddr/<domain>`
     #[typeshare(serialized_as = "Vec<string>")]
     pub rendezvous_points: Vec<Multiaddr>,
 }
@@ -193,6 +289,53 @@ impl Request for ListSellersArgs {
     }
 }
 
+// DiscoverMakers
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DiscoverMakersArgs {
+    /// The rendezvous points to search for makers
+    /// The address must contain a peer ID, or be a DNS seed like `/dnsaddr/<domain>`
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub rendezvous_points: Vec<Multiaddr>,
+}
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct DiscoverMakersResponse {
+    /// The makers that sent us a quote, the best offer first
+    pub makers: Vec<DiscoveredMaker>,
+}
+
+impl Request for DiscoverMakersArgs {
+    type Response = DiscoverMakersResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        discover_makers(self, ctx).await
+    }
+}
+
+// GetMakerStats
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetMakerStatsArgs;
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct GetMakerStatsResponse {
+    /// The stats of every maker we swapped with or found online
+    pub makers: Vec<PeerStats>,
+}
+
+impl Request for GetMakerStatsArgs {
+    type Response = GetMakerStatsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let makers = ctx.db.get_all_peer_stats().await?;
+
+        Ok(GetMakerStatsResponse { makers })
+    }
+}
+
 // GetSwapInfo
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -292,6 +435,139 @@ impl Request for GetHistoryArgs {
     }
 }
 
+// GetHistoryExport
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetHistoryExportArgs {
+    pub format: ExportFormat,
+    /// The file to write the history to, it is overwritten if it exists
+    #[typeshare(serialized_as = "string")]
+    pub path: PathBuf,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetHistoryExportResponse {
+    #[typeshare(serialized_as = "string")]
+    pub path: PathBuf,
+    #[typeshare(serialized_as = "number")]
+    pub swap_count: usize,
+}
+
+impl Request for GetHistoryExportArgs {
+    type Response = GetHistoryExportResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        export_history(self, ctx).await
+    }
+}
+
+// GetSwapTimeline
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetSwapTimelineArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetSwapTimelineResponse {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    /// Every state the swap went through, the oldest first
+    pub entries: Vec<SwapTimelineEntry>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SwapTimelineEntry {
+    pub state_name: String,
+    pub entered_at: String,
+    /// The transactions the swap had to do with in this state
+    pub transactions: Vec<SwapTimelineTransaction>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwapTimelineTransaction {
+    /// One of `btc_lock`, `btc_cancel`, `btc_refund`, `btc_early_refund` and `xmr_lock`
+    pub kind: String,
+    pub txid: String,
+    /// Zero if the transaction is in the mempool, none if it wasn't seen (yet) or if we can't
+    /// tell, which is always the case for Monero transactions
+    #[typeshare(serialized_as = "Option<number>")]
+    pub confirmations: Option<u32>,
+}
+
+impl Request for GetSwapTimelineArgs {
+    type Response = GetSwapTimelineResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_swap_timeline(self, ctx).await
+    }
+}
+
+// GetSwapCostBreakdown
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetSwapCostBreakdownArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct GetSwapCostBreakdownResponse {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    pub costs: SwapCostBreakdown,
+}
+
+impl Request for GetSwapCostBreakdownArgs {
+    type Response = GetSwapCostBreakdownResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_swap_cost_breakdown(self, ctx).await
+    }
+}
+
+// CheckDb
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckDbArgs;
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct CheckDbResponse {
+    pub healthy: bool,
+    pub check: DatabaseCheck,
+}
+
+impl Request for CheckDbArgs {
+    type Response = CheckDbResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let check = ctx.db.check_integrity().await?;
+
+        if check.is_healthy() {
+            tracing::info!("The database check found no problems");
+        } else {
+            tracing::warn!(
+                integrity_errors = ?check.integrity_errors,
+                orphaned_swaps = ?check.orphaned_swaps,
+                unreadable_swaps = ?check.unreadable_swaps,
+                "The database check found problems, a backup can be restored from the backups directory"
+            );
+        }
+
+        Ok(CheckDbResponse {
+            healthy: check.is_healthy(),
+            check,
+        })
+    }
+}
+
 // Additional structs
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -301,15 +577,15 @@ pub struct AliceAddress {
     pub addresses: Vec<String>,
 }
 
-// Suspend current swap
+// Suspend all running swaps
 #[derive(Debug, Deserialize)]
 pub struct SuspendCurrentSwapArgs;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SuspendCurrentSwapResponse {
-    #[typeshare(serialized_as = "string")]
-    pub swap_id: Uuid,
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub swap_ids: Vec<Uuid>,
 }
 
 impl Request for SuspendCurrentSwapArgs {
@@ -320,6 +596,33 @@ impl Request for SuspendCurrentSwapArgs {
     }
 }
 
+// Suspend a single running swap
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuspendSwapArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SuspendSwapResponse {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+impl Request for SuspendSwapArgs {
+    type Response = SuspendSwapResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        ctx.swap_lock.send_suspend_signal_for(self.swap_id).await?;
+
+        Ok(SuspendSwapResponse {
+            swap_id: self.swap_id,
+        })
+    }
+}
+
 pub struct GetCurrentSwapArgs;
 
 impl Request for GetCurrentSwapArgs {
@@ -360,6 +663,36 @@ impl Request for ExportBitcoinWalletArgs {
     }
 }
 
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RescanBitcoinWalletArgs;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RescanBitcoinWalletResponse {
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub balance: bitcoin::Amount,
+}
+
+impl Request for RescanBitcoinWalletArgs {
+    type Response = RescanBitcoinWalletResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let bitcoin_wallet = ctx
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?;
+
+        bitcoin_wallet.full_rescan().await?;
+        let balance = bitcoin_wallet.balance().await?;
+
+        tracing::info!(%balance, "Rescanned Bitcoin wallet");
+
+        Ok(RescanBitcoinWalletResponse { balance })
+    }
+}
+
 pub struct GetConfigArgs;
 
 impl Request for GetConfigArgs {
@@ -434,6 +767,105 @@ impl Request for RedactArgs {
     }
 }
 
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListBitcoinUtxosArgs;
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct ListBitcoinUtxosResponse {
+    pub utxos: Vec<wallet::Utxo>,
+}
+
+impl Request for ListBitcoinUtxosArgs {
+    type Response = ListBitcoinUtxosResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let bitcoin_wallet = ctx
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?;
+
+        Ok(ListBitcoinUtxosResponse {
+            utxos: bitcoin_wallet.list_unspent().await,
+        })
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GetBitcoinAddressLabelsArgs;
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct GetBitcoinAddressLabelsResponse {
+    pub labels: Vec<wallet::AddressLabel>,
+}
+
+impl Request for GetBitcoinAddressLabelsArgs {
+    type Response = GetBitcoinAddressLabelsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let bitcoin_wallet = ctx
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?;
+
+        Ok(GetBitcoinAddressLabelsResponse {
+            labels: bitcoin_wallet.address_labels().await?,
+        })
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GetElectrumServerStatsArgs;
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct ElectrumServerStats {
+    pub url: String,
+    #[typeshare(serialized_as = "number")]
+    pub success_count: u64,
+    #[typeshare(serialized_as = "number")]
+    pub failure_count: u64,
+    pub avg_latency_ms: Option<f64>,
+    /// Between 0 and 1, servers with a higher score are tried first.
+    pub reliability_score: f64,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct GetElectrumServerStatsResponse {
+    pub servers: Vec<ElectrumServerStats>,
+}
+
+impl Request for GetElectrumServerStatsArgs {
+    type Response = GetElectrumServerStatsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let bitcoin_wallet = ctx
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?;
+
+        let servers = bitcoin_wallet
+            .electrum_server_stats()
+            .await
+            .into_iter()
+            .map(|stats| ElectrumServerStats {
+                reliability_score: stats.reliability_score(),
+                url: stats.url,
+                success_count: stats.success_count,
+                failure_count: stats.failure_count,
+                avg_latency_ms: stats.avg_latency_ms,
+            })
+            .collect();
+
+        Ok(GetElectrumServerStatsResponse { servers })
+    }
+}
+
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct GetMoneroAddressesArgs;
@@ -456,15 +888,15 @@ impl Request for GetMoneroAddressesArgs {
 
 #[tracing::instrument(fields(method = "suspend_current_swap"), skip(context))]
 pub async fn suspend_current_swap(context: Arc<Context>) -> Result<SuspendCurrentSwapResponse> {
-    let swap_id = context.swap_lock.get_current_swap_id().await;
-
-    if let Some(id_value) = swap_id {
-        context.swap_lock.send_suspend_signal().await?;
+    let swap_ids = context.swap_lock.get_current_swap_ids().await;
 
-        Ok(SuspendCurrentSwapResponse { swap_id: id_value })
-    } else {
+    if swap_ids.is_empty() {
         bail!("No swap is currently running")
     }
+
+    context.swap_lock.send_suspend_signal().await?;
+
+    Ok(SuspendCurrentSwapResponse { swap_ids })
 }
 
 #[tracing::instrument(fields(method = "get_swap_infos_all"), skip(context))]
@@ -595,11 +1027,26 @@ pub async fn buy_xmr(
         seller,
         bitcoin_change_address,
         monero_receive_pool,
+        rendezvous_points,
+        max_spread,
+        amount,
     } = buy_xmr;
 
     monero_receive_pool.assert_network(context.config.env_config.monero_network)?;
     monero_receive_pool.assert_sum_to_one()?;
 
+    let seller = match seller {
+        SellerChoice::Address(seller) => seller,
+        SellerChoice::Auto => {
+            select_best_maker(
+                rendezvous_points,
+                max_spread.unwrap_or(DEFAULT_MAX_SPREAD),
+                context.clone(),
+            )
+            .await?
+        }
+    };
+
     let bitcoin_wallet = Arc::clone(
         context
             .bitcoin_wallet
@@ -612,7 +1059,9 @@ pub async fn buy_xmr(
             .require_network(bitcoin_wallet.network())
             .context("Address is not on the correct network")?,
         None => {
-            let internal_wallet_address = bitcoin_wallet.new_address().await?;
+            let internal_wallet_address = bitcoin_wallet
+                .new_address_with_label("Swap change", Some(swap_id))
+                .await?;
 
             tracing::info!(
                 internal_wallet_address=%internal_wallet_address,
@@ -673,9 +1122,9 @@ pub async fn buy_xmr(
 
     let initialize_swap = tokio::select! {
         biased;
-        _ = context.swap_lock.listen_for_swap_force_suspension() => {
+        _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
             tracing::debug!("Shutdown signal received, exiting");
-            context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+            context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
 
             context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
 
@@ -683,7 +1132,13 @@ pub async fn buy_xmr(
         },
         result = async {
             let (event_loop, mut event_loop_handle) =
-                EventLoop::new(swap_id, swarm, seller_peer_id, context.db.clone())?;
+                EventLoop::new(
+                    swap_id,
+                    swarm,
+                    seller_peer_id,
+                    context.db.clone(),
+                    context.buffered_transfer_proofs.clone(),
+                )?;
             let event_loop = tokio::spawn(event_loop.run().in_current_span());
 
             let bid_quote = event_loop_handle.request_quote().await?;
@@ -701,7 +1156,7 @@ pub async fn buy_xmr(
 
             context
                 .swap_lock
-                .release_swap_lock()
+                .release_swap_lock(swap_id)
                 .await
                 .expect("Could not release swap lock");
 
@@ -720,9 +1175,9 @@ pub async fn buy_xmr(
     context.tasks.clone().spawn(async move {
         tokio::select! {
             biased;
-            _ = context.swap_lock.listen_for_swap_force_suspension() => {
+            _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
                 tracing::debug!("Shutdown signal received, exiting");
-                context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+                context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
 
                 context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
 
@@ -747,7 +1202,8 @@ pub async fn buy_xmr(
                 let determine_amount = determine_btc_to_swap(
                     context.config.json,
                     bid_quote,
-                    bitcoin_wallet.new_address(),
+                    amount,
+                    bitcoin_wallet.new_address_with_label("Swap deposit", Some(swap_id)),
                     || bitcoin_wallet.balance(),
                     max_givable,
                     || bitcoin_wallet.sync(),
@@ -779,7 +1235,12 @@ pub async fn buy_xmr(
                     monero_receive_pool.clone(),
                     bitcoin_change_address,
                     tx_lock_amount,
-                    tx_lock_fee
+                    tx_lock_fee,
+                    bid_quote.expires_at,
+                    // Without an exact amount we would have swapped less anyway if the maker
+                    // had quoted a lower maximum
+                    amount.is_none().then_some(bid_quote.min_quantity),
+                    Some(bid_quote.price)
                 ).with_event_emitter(context.tauri_handle.clone());
 
                 bob::run(swap).await
@@ -798,7 +1259,7 @@ pub async fn buy_xmr(
 
         context
             .swap_lock
-            .release_swap_lock()
+            .release_swap_lock(swap_id)
             .await
             .expect("Could not release swap lock");
 
@@ -813,6 +1274,45 @@ pub async fn buy_xmr(
     })
 }
 
+/// Discover the makers at the rendezvous points and pick the one with the best price among
+/// those we can swap with. Returns its address including the peer id.
+async fn select_best_maker(
+    rendezvous_points: Vec<Multiaddr>,
+    max_spread: Decimal,
+    context: Arc<Context>,
+) -> Result<Multiaddr> {
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+    let (max_giveable, _) = bitcoin_wallet.max_giveable(TxLock::script_size()).await?;
+
+    let DiscoverMakersResponse { makers } =
+        discover_makers(DiscoverMakersArgs { rendezvous_points }, context.clone()).await?;
+
+    let mut reliable_makers = Vec::new();
+    for maker in makers {
+        let stats = context.db.get_peer_stats(maker.maker.peer_id).await?;
+        if stats.is_flaky() {
+            tracing::debug!(peer_id = %stats.peer_id, "Not selecting maker because too many swaps with it were refunded");
+            continue;
+        }
+        reliable_makers.push(maker);
+    }
+
+    let maker = &select_maker(&reliable_makers, max_giveable, max_spread)?.maker;
+
+    tracing::info!(
+        peer_id = %maker.peer_id,
+        price = %maker.quote.price,
+        min_quantity = %maker.quote.min_quantity,
+        max_quantity = %maker.quote.max_quantity,
+        "Selected the maker with the best offer"
+    );
+
+    Ok(maker.multiaddr.clone().with(Protocol::P2p(maker.peer_id)))
+}
+
 #[tracing::instrument(fields(method = "resume_swap"), skip(context))]
 pub async fn resume_swap(
     resume: ResumeSwapArgs,
@@ -851,8 +1351,13 @@ pub async fn resume_swap(
         swarm.add_peer_address(seller_peer_id, seller_address);
     }
 
-    let (event_loop, event_loop_handle) =
-        EventLoop::new(swap_id, swarm, seller_peer_id, context.db.clone())?;
+    let (event_loop, event_loop_handle) = EventLoop::new(
+        swap_id,
+        swarm,
+        seller_peer_id,
+        context.db.clone(),
+        context.buffered_transfer_proofs.clone(),
+    )?;
 
     let monero_receive_pool = context.db.get_monero_address_pool(swap_id).await?;
 
@@ -888,9 +1393,9 @@ pub async fn resume_swap(
             let handle = tokio::spawn(event_loop.run().in_current_span());
             tokio::select! {
                 biased;
-                _ = context.swap_lock.listen_for_swap_force_suspension() => {
+                _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
                      tracing::debug!("Shutdown signal received, exiting");
-                    context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+                    context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
 
                     context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
 
@@ -921,7 +1426,7 @@ pub async fn resume_swap(
             }
             context
                 .swap_lock
-                .release_swap_lock()
+                .release_swap_lock(swap_id)
                 .await
                 .expect("Could not release swap lock");
 
@@ -955,7 +1460,7 @@ pub async fn cancel_and_refund(
 
     context
         .swap_lock
-        .release_swap_lock()
+        .release_swap_lock(swap_id)
         .await
         .expect("Could not release swap lock");
 
@@ -985,6 +1490,210 @@ pub async fn get_history(context: Arc<Context>) -> Result<GetHistoryResponse> {
     Ok(GetHistoryResponse { swaps: vec })
 }
 
+#[tracing::instrument(fields(method = "export_history"), skip(context))]
+pub async fn export_history(
+    args: GetHistoryExportArgs,
+    context: Arc<Context>,
+) -> Result<GetHistoryExportResponse> {
+    let GetHistoryExportArgs { format, path } = args;
+
+    let mut swaps = Vec::new();
+    for (swap_id, _) in context.db.all().await? {
+        let Ok(peer_id) = context.db.get_peer_id(swap_id).await else {
+            tracing::debug!(%swap_id, "Not exporting swap without a maker");
+            continue;
+        };
+        let states = context.db.get_states_with_timestamps(swap_id).await?;
+
+        if let Some(swap) = ExportedSwap::from_states(swap_id, peer_id, &states)? {
+            swaps.push(swap);
+        }
+    }
+    swaps.sort_by(|a, b| a.start_date.cmp(&b.start_date));
+
+    history_export::write(&path, format, &swaps)?;
+    tracing::info!(path = %path.display(), swaps = swaps.len(), "Exported swap history");
+
+    Ok(GetHistoryExportResponse {
+        path,
+        swap_count: swaps.len(),
+    })
+}
+
+/// Resume every unfinished swap that isn't running yet.
+///
+/// Swaps that didn't lock the Bitcoin yet are left alone. Resuming them would lock it at a
+/// price the maker agreed to a long time ago, that's up to the user to decide.
+#[tracing::instrument(fields(method = "resume_all_swaps"), skip(context))]
+pub async fn resume_all_swaps(context: Arc<Context>) -> Result<ResumeAllSwapsResponse> {
+    let mut swap_ids = Vec::new();
+
+    for (swap_id, state) in context.db.all().await? {
+        if state.swap_finished() || context.swap_lock.is_swap_running(swap_id).await {
+            continue;
+        }
+
+        let state: BobState = state.try_into()?;
+        if matches!(
+            state,
+            BobState::Started { .. } | BobState::SwapSetupCompleted(..)
+        ) {
+            tracing::debug!(%swap_id, "Not resuming swap that did not lock the Bitcoin yet");
+            continue;
+        }
+
+        // One swap we can't resume (e.g. because the maker is offline) must not keep us from
+        // resuming the others
+        let resumed = ResumeSwapArgs { swap_id }.request(context.clone()).await;
+        match resumed {
+            Ok(_) => swap_ids.push(swap_id),
+            Err(error) => tracing::error!(%swap_id, "Failed to resume swap: {:#}", error),
+        }
+    }
+
+    tracing::info!(swaps = swap_ids.len(), "Resumed unfinished swaps");
+
+    Ok(ResumeAllSwapsResponse { swap_ids })
+}
+
+#[tracing::instrument(fields(method = "get_swap_timeline"), skip(context))]
+pub async fn get_swap_timeline(
+    args: GetSwapTimelineArgs,
+    context: Arc<Context>,
+) -> Result<GetSwapTimelineResponse> {
+    let states = context.db.get_states_with_timestamps(args.swap_id).await?;
+
+    // Every transaction shows up in a lot of states, only look up its status once
+    let mut confirmations = HashMap::new();
+    let mut entries = Vec::new();
+
+    for (state, entered_at) in states {
+        let state: BobState = state.try_into()?;
+
+        let mut transactions = Vec::new();
+        for (kind, txid, script) in bitcoin_transactions_of(&state)? {
+            if let (Some(script), Some(bitcoin_wallet)) = (script, context.bitcoin_wallet.as_ref())
+            {
+                if !confirmations.contains_key(&txid) {
+                    let status = bitcoin_wallet.status_of_script(&(txid, script)).await?;
+                    confirmations.insert(txid, status);
+                }
+            }
+
+            transactions.push(SwapTimelineTransaction {
+                kind: kind.to_string(),
+                txid: txid.to_string(),
+                confirmations: match confirmations.get(&txid) {
+                    Some(ScriptStatus::Confirmed(confirmed)) => Some(confirmed.confirmations()),
+                    Some(ScriptStatus::InMempool) => Some(0),
+                    _ => None,
+                },
+            });
+        }
+
+        if let BobState::XmrLockProofReceived {
+            lock_transfer_proof,
+            ..
+        } = &state
+        {
+            transactions.push(SwapTimelineTransaction {
+                kind: "xmr_lock".to_string(),
+                txid: lock_transfer_proof.tx_hash().0,
+                confirmations: None,
+            });
+        }
+
+        entries.push(SwapTimelineEntry {
+            state_name: state.to_string(),
+            entered_at,
+            transactions,
+        });
+    }
+
+    Ok(GetSwapTimelineResponse {
+        swap_id: args.swap_id,
+        entries,
+    })
+}
+
+#[tracing::instrument(fields(method = "get_swap_cost_breakdown"), skip(context))]
+pub async fn get_swap_cost_breakdown(
+    args: GetSwapCostBreakdownArgs,
+    context: Arc<Context>,
+) -> Result<GetSwapCostBreakdownResponse> {
+    let GetSwapCostBreakdownArgs { swap_id } = args;
+
+    let states = context.db.get_states(swap_id).await?;
+    let xmr_network_fee = context.db.get_xmr_redeem_fee(swap_id).await?;
+
+    // Asking Kraken for the rate outside of Tor would tell it when we swapped
+    let reference_price = match context.tor_client {
+        Some(_) => None,
+        None => {
+            let start_date = context.db.get_swap_start_date(swap_id).await?;
+
+            match cost_breakdown::day_of(&start_date) {
+                Some(day) => match kraken::daily_average_rate(day).await {
+                    Ok(rate) => Some(rate),
+                    Err(error) => {
+                        tracing::warn!(%swap_id, "Failed to get the reference price: {:#}", error);
+                        None
+                    }
+                },
+                None => None,
+            }
+        }
+    };
+
+    let costs = SwapCostBreakdown::from_states(&states, xmr_network_fee, reference_price)?
+        .context("The swap was aborted before it cost anything")?;
+
+    Ok(GetSwapCostBreakdownResponse { swap_id, costs })
+}
+
+/// The Bitcoin transactions a state refers to, with the script to look up their status by.
+/// Later states only know the id of the lock transaction.
+fn bitcoin_transactions_of(
+    state: &BobState,
+) -> Result<Vec<(&'static str, Txid, Option<::bitcoin::ScriptBuf>)>> {
+    fn watched(
+        kind: &'static str,
+        tx: &impl Watchable,
+    ) -> (&'static str, Txid, Option<::bitcoin::ScriptBuf>) {
+        (kind, tx.id(), Some(tx.script()))
+    }
+
+    Ok(match state {
+        BobState::Started { .. } | BobState::SafelyAborted => vec![],
+        BobState::SwapSetupCompleted(state2) => vec![watched("btc_lock", &state2.tx_lock)],
+        BobState::BtcLocked { state3, .. }
+        | BobState::XmrLockProofReceived { state: state3, .. } => {
+            vec![watched("btc_lock", &state3.tx_lock)]
+        }
+        BobState::XmrLocked(state4) | BobState::EncSigSent(state4) => {
+            vec![watched("btc_lock", &state4.tx_lock)]
+        }
+        BobState::BtcRedeemed(state5) => vec![("btc_lock", state5.tx_lock_id(), None)],
+        BobState::CancelTimelockExpired(state6) | BobState::BtcPunished { state: state6, .. } => {
+            vec![watched("btc_lock", &state6.tx_lock)]
+        }
+        BobState::BtcCancelled(state6) => vec![
+            watched("btc_lock", &state6.tx_lock),
+            watched("btc_cancel", &state6.construct_tx_cancel()?),
+        ],
+        BobState::BtcRefundPublished(state6) | BobState::BtcRefunded(state6) => vec![
+            watched("btc_lock", &state6.tx_lock),
+            watched("btc_cancel", &state6.construct_tx_cancel()?),
+            watched("btc_refund", &state6.construct_tx_refund()?),
+        ],
+        BobState::BtcEarlyRefundPublished(state6) | BobState::BtcEarlyRefunded(state6) => vec![
+            watched("btc_lock", &state6.tx_lock),
+            watched("btc_early_refund", &state6.construct_tx_early_refund()),
+        ],
+        BobState::XmrRedeemed { tx_lock_id } => vec![("btc_lock", *tx_lock_id, None)],
+    })
+}
+
 #[tracing::instrument(fields(method = "get_config"), skip(context))]
 pub async fn get_config(context: Arc<Context>) -> Result<serde_json::Value> {
     let data_dir_display = context.config.data_dir.display();
@@ -1009,30 +1718,48 @@ pub async fn withdraw_btc(
     withdraw_btc: WithdrawBtcArgs,
     context: Arc<Context>,
 ) -> Result<WithdrawBtcResponse> {
-    let WithdrawBtcArgs { address, amount } = withdraw_btc;
+    let WithdrawBtcArgs {
+        address,
+        amount,
+        utxo_include,
+        utxo_exclude,
+        fee_rate_sat_per_vb,
+    } = withdraw_btc;
     let bitcoin_wallet = context
         .bitcoin_wallet
         .as_ref()
         .context("Could not get Bitcoin wallet")?;
+    let coin_control = wallet::CoinControl {
+        utxo_include,
+        utxo_exclude,
+    };
+    let fee_rate = fee_rate_sat_per_vb
+        .map(|fee_rate| {
+            FeeRate::from_sat_per_vb(fee_rate)
+                .with_context(|| format!("Fee rate of {} sat/vB is too high", fee_rate))
+        })
+        .transpose()?;
 
     let (withdraw_tx_unsigned, amount) = match amount {
         Some(amount) => {
             let withdraw_tx_unsigned = bitcoin_wallet
-                .send_to_address_dynamic_fee(address, amount, None)
+                .send_to_address_dynamic_fee(address.clone(), amount, None, coin_control, fee_rate)
                 .await?;
 
             (withdraw_tx_unsigned, amount)
         }
         None => {
-            let (max_giveable, spending_fee) = bitcoin_wallet
-                .max_giveable(address.script_pubkey().len())
-                .await?;
-
             let withdraw_tx_unsigned = bitcoin_wallet
-                .send_to_address(address, max_giveable, spending_fee, None)
+                .sweep_balance_to_address_dynamic_fee(address.clone(), coin_control, fee_rate)
                 .await?;
-
-            (withdraw_tx_unsigned, max_giveable)
+            let swept_amount = withdraw_tx_unsigned
+                .unsigned_tx
+                .output
+                .first()
+                .context("Sweep transaction has no output")?
+                .value;
+
+            (withdraw_tx_unsigned, swept_amount)
         }
     };
 
@@ -1046,6 +1773,14 @@ pub async fn withdraw_btc(
 
     let txid = withdraw_tx.compute_txid();
 
+    // The withdrawal already went out, a missing label is no reason to fail
+    if let Err(error) = bitcoin_wallet
+        .label_address(&address, "Withdrawal", None)
+        .await
+    {
+        tracing::warn!(%txid, "Failed to label withdrawal address: {:#}", error);
+    }
+
     Ok(WithdrawBtcResponse {
         txid: txid.to_string(),
         amount,
@@ -1089,10 +1824,41 @@ pub async fn list_sellers(
     context: Arc<Context>,
 ) -> Result<ListSellersResponse> {
     let ListSellersArgs { rendezvous_points } = list_sellers;
-    let rendezvous_nodes: Vec<_> = rendezvous_points
-        .iter()
-        .filter_map(|rendezvous_point| rendezvous_point.split_peer_id())
-        .collect();
+
+    let mut rendezvous_nodes = Vec::new();
+    let mut seeded_rendezvous_nodes = Vec::new();
+    for rendezvous_point in &rendezvous_points {
+        let Some(domain) = rendezvous_bootstrap::dns_seed(rendezvous_point) else {
+            rendezvous_nodes.extend(rendezvous_point.split_peer_id());
+            continue;
+        };
+
+        // Resolving the seed outside of Tor would tell the DNS servers that we look for makers
+        if context.tor_client.is_some() {
+            tracing::warn!(%domain, "Skipping DNS seed because Tor is enabled");
+            continue;
+        }
+
+        match rendezvous_bootstrap::resolve_dns_seed(&domain).await {
+            Ok(nodes) => seeded_rendezvous_nodes.extend(nodes),
+            Err(error) => tracing::warn!(%domain, "Failed to resolve DNS seed: {:#}", error),
+        }
+    }
+
+    let mut healthy = HashMap::new();
+    for (peer_id, _) in rendezvous_nodes.iter().chain(&seeded_rendezvous_nodes) {
+        let health = context.db.get_rendezvous_point_health(*peer_id).await?;
+        healthy.insert(*peer_id, health.is_healthy());
+    }
+
+    let mut rounds =
+        rendezvous_bootstrap::rotation(rendezvous_nodes, seeded_rendezvous_nodes, |peer_id| {
+            healthy.get(peer_id).copied().unwrap_or(true)
+        });
+    // Without any rendezvous point we still ask the makers we already know for a quote
+    if rounds.is_empty() {
+        rounds.push(Vec::new());
+    }
 
     let identity = context
         .config
@@ -1101,15 +1867,42 @@ pub async fn list_sellers(
         .context("Cannot extract seed")?
         .derive_libp2p_identity();
 
-    let sellers = list_sellers_impl(
-        rendezvous_nodes,
-        context.config.namespace,
-        context.tor_client.clone(),
-        identity,
-        Some(context.db.clone()),
-        context.tauri_handle(),
-    )
-    .await?;
+    let number_of_rounds = rounds.len();
+    let mut sellers = Vec::new();
+    for (round, rendezvous_nodes) in rounds.into_iter().enumerate() {
+        // The makers we already know only need to be asked once
+        let db = (round == 0).then(|| context.db.clone());
+
+        let discovery = discover_sellers(
+            rendezvous_nodes,
+            context.config.namespace,
+            context.tor_client.clone(),
+            identity.clone(),
+            db,
+            context.tauri_handle(),
+        )
+        .await?;
+
+        for (peer_id, success) in &discovery.rendezvous_points {
+            context
+                .db
+                .record_rendezvous_discovery(*peer_id, *success)
+                .await?;
+        }
+
+        let any_rendezvous_point_succeeded = discovery.any_rendezvous_point_succeeded();
+        rendezvous_bootstrap::merge_sellers(&mut sellers, discovery.sellers);
+
+        if any_rendezvous_point_succeeded {
+            break;
+        }
+
+        if round + 1 < number_of_rounds {
+            tracing::warn!(
+                "Failed to discover makers at any of the rendezvous points, trying the next ones"
+            );
+        }
+    }
 
     for seller in &sellers {
         match seller {
@@ -1137,6 +1930,7 @@ pub async fn list_sellers(
                     .db
                     .insert_address(*peer_id, multiaddr.clone())
                     .await?;
+                context.db.record_maker_reachability(*peer_id, true).await?;
             }
             SellerStatus::Unreachable(UnreachableSeller { peer_id }) => {
                 tracing::debug!(
@@ -1144,6 +1938,10 @@ pub async fn list_sellers(
                     peer_id = %peer_id.to_string(),
                     "Fetched peer status"
                 );
+                context
+                    .db
+                    .record_maker_reachability(*peer_id, false)
+                    .await?;
             }
         }
     }
@@ -1151,6 +1949,29 @@ pub async fn list_sellers(
     Ok(ListSellersResponse { sellers })
 }
 
+#[tracing::instrument(fields(method = "discover_makers"), skip(context))]
+pub async fn discover_makers(
+    discover_makers: DiscoverMakersArgs,
+    context: Arc<Context>,
+) -> Result<DiscoverMakersResponse> {
+    let DiscoverMakersArgs { rendezvous_points } = discover_makers;
+
+    let ListSellersResponse { sellers } =
+        list_sellers(ListSellersArgs { rendezvous_points }, context.clone()).await?;
+
+    let mut makers = Vec::new();
+    for seller in sellers {
+        if let SellerStatus::Online(maker) = seller {
+            let reachability = context.db.get_maker_reachability(maker.peer_id).await?;
+            makers.push(DiscoveredMaker::new(maker, reachability));
+        }
+    }
+
+    rank_makers(&mut makers);
+
+    Ok(DiscoverMakersResponse { makers })
+}
+
 #[tracing::instrument(fields(method = "export_bitcoin_wallet"), skip(context))]
 pub async fn export_bitcoin_wallet(context: Arc<Context>) -> Result<serde_json::Value> {
     let bitcoin_wallet = context
@@ -1202,7 +2023,7 @@ pub async fn monero_recovery(
 #[tracing::instrument(fields(method = "get_current_swap"), skip(context))]
 pub async fn get_current_swap(context: Arc<Context>) -> Result<serde_json::Value> {
     Ok(json!({
-        "swap_id": context.swap_lock.get_current_swap_id().await,
+        "swap_ids": context.swap_lock.get_current_swap_ids().await,
     }))
 }
 
@@ -1237,6 +2058,7 @@ fn qr_code(value: &impl ToString) -> Result<String> {
 pub async fn determine_btc_to_swap<FB, TB, FMG, TMG, FS, TS>(
     json: bool,
     bid_quote: BidQuote,
+    amount: Option<bitcoin::Amount>,
     get_new_address: impl Future<Output = Result<bitcoin::Address>>,
     balance: FB,
     max_giveable_fn: FMG,
@@ -1263,13 +2085,30 @@ where
         "Received quote",
     );
 
+    // With an exact amount we wait until we can swap just that, any excess stays in the wallet
+    let (minimum_amount, maximum_amount) = match amount {
+        Some(amount) => {
+            if amount < bid_quote.min_quantity || amount > bid_quote.max_quantity {
+                bail!(
+                    "The maker only accepts swapping between {} and {}, not {}",
+                    bid_quote.min_quantity,
+                    bid_quote.max_quantity,
+                    amount
+                );
+            }
+
+            tracing::info!(%amount, "Swapping an exact amount");
+
+            (amount, amount)
+        }
+        None => (bid_quote.min_quantity, bid_quote.max_quantity),
+    };
+
     sync().await.context("Failed to sync of Bitcoin wallet")?;
     let (mut max_giveable, mut spending_fee) = max_giveable_fn().await?;
 
-    if max_giveable == bitcoin::Amount::ZERO || max_giveable < bid_quote.min_quantity {
+    if max_giveable == bitcoin::Amount::ZERO || max_giveable < minimum_amount {
         let deposit_address = get_new_address.await?;
-        let minimum_amount = bid_quote.min_quantity;
-        let maximum_amount = bid_quote.max_quantity;
 
         // To avoid any issus, we clip maximum_amount to never go above the
         // total maximim Bitcoin supply
@@ -1280,7 +2119,7 @@ where
         }
 
         loop {
-            let min_outstanding = bid_quote.min_quantity - max_giveable;
+            let min_outstanding = minimum_amount - max_giveable;
             let min_bitcoin_lock_tx_fee = spending_fee;
             let min_deposit_until_swap_will_start = min_outstanding + min_bitcoin_lock_tx_fee;
             let max_deposit_until_maximum_amount_is_reached = maximum_amount
@@ -1335,7 +2174,7 @@ where
             let new_balance = balance().await?;
             tracing::info!(%new_balance, %max_giveable, "Received Bitcoin");
 
-            if max_giveable < bid_quote.min_quantity {
+            if max_giveable < minimum_amount {
                 tracing::info!("Deposited amount is not enough to cover `min_quantity` when accounting for network fees");
                 continue;
             }
@@ -1346,8 +2185,7 @@ where
 
     let balance = balance().await?;
     let fees = balance - max_giveable;
-    let max_accepted = bid_quote.max_quantity;
-    let btc_swap_amount = min(max_giveable, max_accepted);
+    let btc_swap_amount = min(max_giveable, maximum_amount);
 
     Ok((btc_swap_amount, fees))
 }
@@ -1371,6 +2209,12 @@ pub struct GetDataDirArgs {
     pub is_testnet: bool,
 }
 
+#[typeshare]
+#[derive(Deserialize, Serialize)]
+pub struct IsSeedEncryptedArgs {
+    pub is_testnet: bool,
+}
+
 #[derive(Error, Debug)]
 #[error("this is not one of the known monero networks")]
 struct UnknownMoneroNetwork(String);
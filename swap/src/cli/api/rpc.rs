@@ -0,0 +1,251 @@
+use crate::asb::management::require_api_token;
+use crate::cli::api::request::{
+    BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, CheckDbArgs, DiscoverMakersArgs,
+    ExportBitcoinWalletArgs, GetBitcoinAddressLabelsArgs, GetConfigArgs,
+    GetElectrumServerStatsArgs, GetHistoryArgs, GetHistoryExportArgs, GetLogsArgs,
+    GetMakerStatsArgs, GetMoneroAddressesArgs, GetSwapCostBreakdownArgs, GetSwapInfoArgs,
+    GetSwapInfosAllArgs, GetSwapTimelineArgs, ListBitcoinUtxosArgs, ListSellersArgs,
+    MoneroRecoveryArgs, RedactArgs, Request, RescanBitcoinWalletArgs, ResolveApprovalArgs,
+    ResumeAllSwapsArgs, ResumeSwapArgs, SuspendCurrentSwapArgs, SuspendSwapArgs, WithdrawBtcArgs,
+};
+use crate::cli::api::Context;
+use anyhow::{ensure, Context as _, Result};
+use axum::extract::State;
+use axum::routing::post;
+use axum::{middleware, Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// The request was valid but failed, e.g. because the swap does not exist.
+const REQUEST_FAILED: i64 = -32000;
+
+/// Serve the requests of the GUI over JSON-RPC 2.0, for bots and alternative interfaces.
+///
+/// Every call is a `POST /` with `Authorization: Bearer <api_token>`. The methods are named
+/// like the Tauri commands (`buy_xmr`, `get_history`, `withdraw_btc`, ...) and take the same
+/// arguments as `params`.
+pub async fn serve(listen: SocketAddr, api_token: String, context: Arc<Context>) -> Result<()> {
+    ensure!(!api_token.is_empty(), "The API token must not be empty");
+
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind the JSON-RPC server to {}", listen))?;
+
+    if !listen.ip().is_loopback() {
+        tracing::warn!(%listen, "The JSON-RPC server is reachable from other machines, anyone with the API token controls your funds");
+    }
+
+    let app = Router::new()
+        .route("/", post(rpc_handler))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::<str>::from(api_token),
+            require_api_token,
+        ))
+        .with_state(context.clone());
+
+    tracing::info!(%listen, "JSON-RPC server listening");
+
+    context
+        .tasks
+        .spawn(async move {
+            if let Err(error) = axum::serve(listener, app).await {
+                tracing::error!("JSON-RPC server stopped: {:#}", error);
+            }
+        })
+        .await;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl RpcResponse {
+    fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        Self {
+            jsonrpc: "2.0",
+            result,
+            error,
+            id,
+        }
+    }
+}
+
+/// We take the body as a plain string to answer malformed requests with a JSON-RPC error
+/// instead of the rejection of the `Json` extractor.
+async fn rpc_handler(State(context): State<Arc<Context>>, body: String) -> Json<RpcResponse> {
+    let request: RpcRequest = match serde_json::from_str::<Value>(&body) {
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(error) => {
+                return Json(RpcResponse::new(
+                    Value::Null,
+                    Err(RpcError::new(INVALID_REQUEST, error)),
+                ))
+            }
+        },
+        Err(error) => {
+            return Json(RpcResponse::new(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, error)),
+            ))
+        }
+    };
+
+    if request.jsonrpc != "2.0" {
+        return Json(RpcResponse::new(
+            request.id,
+            Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"")),
+        ));
+    }
+
+    let result = call(context, &request.method, request.params).await;
+
+    Json(RpcResponse::new(request.id, result))
+}
+
+/// Deserialize the params into the arguments of the request and run it.
+macro_rules! call {
+    ($args:ident, $context:expr, no_args) => {{
+        to_result(<$args as Request>::request($args {}, $context).await)
+    }};
+    ($args:ident, $context:expr, $params:expr) => {{
+        let args: $args = serde_json::from_value(params_or_empty_object($params))
+            .map_err(|error| RpcError::new(INVALID_PARAMS, error))?;
+        to_result(<$args as Request>::request(args, $context).await)
+    }};
+}
+
+async fn call(context: Arc<Context>, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "get_balance" => call!(BalanceArgs, context, params),
+        "buy_xmr" => call!(BuyXmrArgs, context, params),
+        "resume_swap" => call!(ResumeSwapArgs, context, params),
+        "resume_all_swaps" => call!(ResumeAllSwapsArgs, context, no_args),
+        "withdraw_btc" => call!(WithdrawBtcArgs, context, params),
+        "monero_recovery" => call!(MoneroRecoveryArgs, context, params),
+        "get_logs" => call!(GetLogsArgs, context, params),
+        "list_sellers" => call!(ListSellersArgs, context, params),
+        "discover_makers" => call!(DiscoverMakersArgs, context, params),
+        "get_maker_stats" => call!(GetMakerStatsArgs, context, no_args),
+        "cancel_and_refund" => call!(CancelAndRefundArgs, context, params),
+        "suspend_swap" => call!(SuspendSwapArgs, context, params),
+        "resolve_approval_request" => call!(ResolveApprovalArgs, context, params),
+        "redact" => call!(RedactArgs, context, params),
+        "get_wallet_descriptor" => call!(ExportBitcoinWalletArgs, context, no_args),
+        "rescan_bitcoin_wallet" => call!(RescanBitcoinWalletArgs, context, no_args),
+        "suspend_current_swap" => call!(SuspendCurrentSwapArgs, context, no_args),
+        "get_swap_info" => call!(GetSwapInfoArgs, context, params),
+        "get_swap_timeline" => call!(GetSwapTimelineArgs, context, params),
+        "get_swap_cost_breakdown" => call!(GetSwapCostBreakdownArgs, context, params),
+        "get_swap_infos_all" => call!(GetSwapInfosAllArgs, context, no_args),
+        "get_history" => call!(GetHistoryArgs, context, no_args),
+        "export_history" => call!(GetHistoryExportArgs, context, params),
+        "get_monero_addresses" => call!(GetMoneroAddressesArgs, context, no_args),
+        "list_bitcoin_utxos" => call!(ListBitcoinUtxosArgs, context, no_args),
+        "get_bitcoin_address_labels" => call!(GetBitcoinAddressLabelsArgs, context, no_args),
+        "get_electrum_server_stats" => call!(GetElectrumServerStatsArgs, context, no_args),
+        "get_config" => call!(GetConfigArgs, context, no_args),
+        "check_db" => call!(CheckDbArgs, context, no_args),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method {}", method),
+        )),
+    }
+}
+
+/// Arguments whose fields are all optional may leave out `params` entirely.
+fn params_or_empty_object(params: Value) -> Value {
+    match params {
+        Value::Null => Value::Object(Default::default()),
+        params => params,
+    }
+}
+
+fn to_result<T: Serialize>(result: Result<T>) -> Result<Value, RpcError> {
+    let response = result.map_err(|error| RpcError::new(REQUEST_FAILED, format!("{:#}", error)))?;
+
+    serde_json::to_value(response).map_err(|error| RpcError::new(INTERNAL_ERROR, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn responses_have_either_a_result_or_an_error() {
+        let ok = RpcResponse::new(json!(1), Ok(json!({ "balance": 1000 })));
+        let err = RpcResponse::new(
+            json!("a"),
+            Err(RpcError::new(METHOD_NOT_FOUND, "Unknown method foo")),
+        );
+
+        assert_eq!(
+            serde_json::to_value(ok).unwrap(),
+            json!({ "jsonrpc": "2.0", "result": { "balance": 1000 }, "id": 1 })
+        );
+        assert_eq!(
+            serde_json::to_value(err).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "error": { "code": -32601, "message": "Unknown method foo" },
+                "id": "a"
+            })
+        );
+    }
+
+    #[test]
+    fn missing_params_deserialize_as_empty_arguments() {
+        let request: RpcRequest =
+            serde_json::from_value(json!({ "jsonrpc": "2.0", "method": "get_history" })).unwrap();
+
+        assert_eq!(request.params, Value::Null);
+        assert_eq!(params_or_empty_object(request.params), json!({}));
+    }
+}
@@ -731,6 +731,8 @@ pub struct TauriSettings {
     pub electrum_rpc_urls: Vec<String>,
     /// Whether to initialize and use a tor client.
     pub use_tor: bool,
+    /// Whether to resume all unfinished swaps once the context is initialized.
+    pub auto_resume_swaps: bool,
 }
 
 #[typeshare]
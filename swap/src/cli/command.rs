@@ -1,15 +1,20 @@
 use crate::bitcoin::{bitcoin_address, Amount};
 use crate::cli::api::request::{
-    BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, ExportBitcoinWalletArgs, GetConfigArgs,
-    GetHistoryArgs, ListSellersArgs, MoneroRecoveryArgs, Request, ResumeSwapArgs, WithdrawBtcArgs,
+    BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, CheckDbArgs, ExportBitcoinWalletArgs,
+    GetConfigArgs, GetHistoryArgs, GetHistoryExportArgs, ListSellersArgs, MoneroRecoveryArgs,
+    Request, RescanBitcoinWalletArgs, ResumeAllSwapsArgs, ResumeSwapArgs, SellerChoice,
+    WithdrawBtcArgs,
 };
-use crate::cli::api::Context;
+use crate::cli::api::{rpc, Context};
+use crate::cli::history_export::ExportFormat;
+use crate::env::Confirmations;
 use crate::monero::monero_address;
 use crate::monero::{self, MoneroAddressPool};
 use anyhow::Result;
 use bitcoin::address::NetworkUnchecked;
 use libp2p::core::Multiaddr;
 use std::ffi::OsString;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use structopt::{clap, StructOpt};
@@ -59,13 +64,24 @@ where
     let json = args.json;
     let is_testnet = args.testnet;
     let data = args.data;
+    let seed_password = args.seed_password;
+    let confirmations = Confirmations {
+        bitcoin_finality: args.bitcoin_finality_confirmations,
+        monero_finality: args.monero_finality_confirmations,
+        monero_double_spend_safe: args.monero_double_spend_safe_confirmations,
+    };
     let result: Result<Arc<Context>> = match args.cmd {
         CliCommand::BuyXmr {
-            seller: Seller { seller },
+            seller:
+                Seller {
+                    seller,
+                    rendezvous_points,
+                },
             bitcoin,
             bitcoin_change_address,
             monero,
             monero_receive_address,
+            amount,
             tor,
         } => {
             let monero_receive_pool: MoneroAddressPool =
@@ -82,6 +98,8 @@ where
                     .with_bitcoin(bitcoin)
                     .with_monero(monero)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -92,6 +110,9 @@ where
                 seller,
                 bitcoin_change_address,
                 monero_receive_pool,
+                rendezvous_points,
+                max_spread: None,
+                amount,
             }
             .request(context.clone())
             .await?;
@@ -102,6 +123,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -112,6 +135,40 @@ where
 
             Ok(context)
         }
+        CliCommand::ExportHistory { format, path } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            GetHistoryExportArgs { format, path }
+                .request(context.clone())
+                .await?;
+
+            Ok(context)
+        }
+        CliCommand::CheckDb => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            CheckDbArgs.request(context.clone()).await?;
+
+            Ok(context)
+        }
         CliCommand::Logs {
             logs_dir,
             redact,
@@ -120,6 +177,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -140,6 +199,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -155,6 +216,8 @@ where
                 ContextBuilder::new(is_testnet)
                     .with_bitcoin(bitcoin)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -173,6 +236,7 @@ where
             bitcoin,
             amount,
             address,
+            fee_rate,
         } => {
             let address = bitcoin_address::validate(address, is_testnet)?;
 
@@ -180,15 +244,23 @@ where
                 ContextBuilder::new(is_testnet)
                     .with_bitcoin(bitcoin)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
                     .await?,
             );
 
-            WithdrawBtcArgs { amount, address }
-                .request(context.clone())
-                .await?;
+            WithdrawBtcArgs {
+                amount,
+                address,
+                utxo_include: vec![],
+                utxo_exclude: vec![],
+                fee_rate_sat_per_vb: fee_rate,
+            }
+            .request(context.clone())
+            .await?;
 
             Ok(context)
         }
@@ -204,6 +276,8 @@ where
                     .with_bitcoin(bitcoin)
                     .with_monero(monero)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -214,6 +288,29 @@ where
 
             Ok(context)
         }
+        CliCommand::ResumeAll {
+            bitcoin,
+            monero,
+            tor,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_tor(tor.enable_tor)
+                    .with_bitcoin(bitcoin)
+                    .with_monero(monero)
+                    .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            ResumeAllSwapsArgs {}.request(context.clone()).await?;
+
+            Ok(context)
+        }
         CliCommand::CancelAndRefund {
             swap_id: SwapId { swap_id },
             bitcoin,
@@ -222,6 +319,8 @@ where
                 ContextBuilder::new(is_testnet)
                     .with_bitcoin(bitcoin)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -235,24 +334,24 @@ where
             Ok(context)
         }
         CliCommand::ListSellers {
-            rendezvous_point,
+            rendezvous_points,
             tor,
         } => {
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_tor(tor.enable_tor)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
                     .await?,
             );
 
-            ListSellersArgs {
-                rendezvous_points: vec![rendezvous_point],
-            }
-            .request(context.clone())
-            .await?;
+            ListSellersArgs { rendezvous_points }
+                .request(context.clone())
+                .await?;
 
             Ok(context)
         }
@@ -261,6 +360,8 @@ where
                 ContextBuilder::new(is_testnet)
                     .with_bitcoin(bitcoin)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -271,12 +372,56 @@ where
 
             Ok(context)
         }
+        CliCommand::RescanBitcoinWallet { bitcoin } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            RescanBitcoinWalletArgs {}.request(context.clone()).await?;
+
+            Ok(context)
+        }
+        CliCommand::Daemon {
+            listen,
+            api_token,
+            bitcoin,
+            monero,
+            tor,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_tor(tor.enable_tor)
+                    .with_bitcoin(bitcoin)
+                    .with_monero(monero)
+                    .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            rpc::serve(listen, api_token, context.clone()).await?;
+
+            Ok(context)
+        }
         CliCommand::MoneroRecovery {
             swap_id: SwapId { swap_id },
         } => {
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_data_dir(data)
+                    .with_seed_password(seed_password)
+                    .with_confirmations(confirmations)
                     .with_debug(debug)
                     .with_json(json)
                     .build()
@@ -317,6 +462,35 @@ struct Arguments {
     )]
     data: Option<PathBuf>,
 
+    #[structopt(
+        long = "seed-password",
+        env = "SWAP_SEED_PASSWORD",
+        hide_env_values = true,
+        help = "The password that unlocks the seed. If the seed is not encrypted yet, it will be encrypted with this password. Prefer setting the environment variable over passing it as an argument"
+    )]
+    seed_password: Option<String>,
+
+    #[structopt(
+        long = "bitcoin-finality-confirmations",
+        help = "Number of confirmations after which a Bitcoin transaction is considered final. Defaults to the value of the network",
+        global = true
+    )]
+    bitcoin_finality_confirmations: Option<u32>,
+
+    #[structopt(
+        long = "monero-finality-confirmations",
+        help = "Number of confirmations the Monero lock transaction of the maker needs before it is considered final. Defaults to the value of the network",
+        global = true
+    )]
+    monero_finality_confirmations: Option<u64>,
+
+    #[structopt(
+        long = "monero-double-spend-safe-confirmations",
+        help = "Number of confirmations after which the Monero lock transaction of the maker is assumed to be safe from double spending. Defaults to the value of the network",
+        global = true
+    )]
+    monero_double_spend_safe_confirmations: Option<u64>,
+
     #[structopt(long, help = "Activate debug logging")]
     debug: bool,
 
@@ -357,11 +531,32 @@ enum CliCommand {
         )]
         monero_receive_address: monero::Address,
 
+        #[structopt(
+            long = "amount",
+            help = "Swap exactly this amount of Bitcoin, e.g. \"0.01 BTC\". If omitted, everything that is deposited is swapped, up to the maximum of the seller."
+        )]
+        amount: Option<Amount>,
+
         #[structopt(flatten)]
         tor: Tor,
     },
     /// Show a list of past, ongoing and completed swaps
     History,
+    /// Write all swaps with their amounts, fees and transaction ids to a file, e.g. for
+    /// accounting
+    ExportHistory {
+        #[structopt(
+            long,
+            default_value = "csv",
+            help = "The format of the file, either csv or json"
+        )]
+        format: ExportFormat,
+
+        #[structopt(long, help = "The file to write the history to")]
+        path: PathBuf,
+    },
+    /// Check the database for corruption and for swaps that can't be resumed
+    CheckDb,
     /// Output all logging messages that have been issued.
     Logs {
         #[structopt(
@@ -399,6 +594,12 @@ enum CliCommand {
             parse(try_from_str = bitcoin_address::parse)
         )]
         address: bitcoin::Address<NetworkUnchecked>,
+
+        #[structopt(
+            long = "fee-rate",
+            help = "Optionally specify the fee rate in sat/vB. If not specified the fee rate is estimated."
+        )]
+        fee_rate: Option<u64>,
     },
     #[structopt(about = "Prints the Bitcoin balance.")]
     Balance {
@@ -419,6 +620,17 @@ enum CliCommand {
         #[structopt(flatten)]
         tor: Tor,
     },
+    /// Resume all unfinished swaps that already locked the Bitcoin
+    ResumeAll {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(flatten)]
+        monero: Monero,
+
+        #[structopt(flatten)]
+        tor: Tor,
+    },
     /// Force the submission of the cancel and refund transactions of a swap
     #[structopt(aliases = &["cancel", "refund"])]
     CancelAndRefund {
@@ -431,10 +643,11 @@ enum CliCommand {
     /// Discover and list sellers (i.e. ASB providers)
     ListSellers {
         #[structopt(
-            long,
-            help = "Address of the rendezvous point you want to use to discover ASBs"
+            long = "rendezvous-point",
+            required = true,
+            help = "Address of a rendezvous point you want to use to discover ASBs. Can be given multiple times, the next ones are tried if discovery fails. Pass `/dnsaddr/<domain>` to use the rendezvous points listed in the DNS TXT records of the domain"
         )]
-        rendezvous_point: Multiaddr,
+        rendezvous_points: Vec<Multiaddr>,
 
         #[structopt(flatten)]
         tor: Tor,
@@ -444,6 +657,12 @@ enum CliCommand {
         #[structopt(flatten)]
         bitcoin: Bitcoin,
     },
+    /// Forget the transactions of the internal bitcoin wallet and scan the
+    /// blockchain for them again. Use this if the balance is wrong.
+    RescanBitcoinWallet {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+    },
     /// Prints Monero information related to the swap in case the generated
     /// wallet fails to detect the funds. This can only be used for swaps
     /// that are in a `btc is redeemed` state.
@@ -451,6 +670,31 @@ enum CliCommand {
         #[structopt(flatten)]
         swap_id: SwapId,
     },
+    /// Keep running and serve the requests of the GUI over JSON-RPC, e.g. for
+    /// bots or alternative interfaces
+    Daemon {
+        #[structopt(
+            long,
+            default_value = "127.0.0.1:1234",
+            help = "The address the JSON-RPC server listens on"
+        )]
+        listen: SocketAddr,
+
+        #[structopt(
+            long = "api-token",
+            help = "Every request has to carry this token as `Authorization: Bearer <api-token>`"
+        )]
+        api_token: String,
+
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(flatten)]
+        monero: Monero,
+
+        #[structopt(flatten)]
+        tor: Tor,
+    },
 }
 
 #[derive(structopt::StructOpt, Debug)]
@@ -472,6 +716,18 @@ pub struct Bitcoin {
         help = "Estimate Bitcoin fees such that transactions are confirmed within the specified number of blocks"
     )]
     pub bitcoin_target_block: Option<u16>,
+
+    #[structopt(
+        long = "bitcoin-taproot",
+        help = "Use a Taproot (BIP86) Bitcoin wallet. It is kept apart from the default BIP84 wallet, funds are not moved between them"
+    )]
+    pub bitcoin_taproot: bool,
+
+    #[structopt(
+        long = "electrum-socks5-proxy",
+        help = "Connect to the Electrum servers through this SOCKS5 proxy, e.g. Tor at 127.0.0.1:9050. Required for .onion servers"
+    )]
+    pub bitcoin_electrum_socks5_proxy: Option<String>,
 }
 
 impl Bitcoin {
@@ -518,9 +774,15 @@ struct SwapId {
 struct Seller {
     #[structopt(
         long,
-        help = "The seller's address. Must include a peer ID part, i.e. `/p2p/`"
+        help = "The seller's address. Must include a peer ID part, i.e. `/p2p/`. Pass `auto` to swap with the best seller found at the rendezvous points"
+    )]
+    seller: SellerChoice,
+
+    #[structopt(
+        long = "rendezvous-point",
+        help = "Address of a rendezvous point to discover sellers at if the seller is `auto`"
     )]
-    seller: Multiaddr,
+    rendezvous_points: Vec<Multiaddr>,
 }
 
 #[cfg(test)]
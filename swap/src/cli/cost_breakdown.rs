@@ -0,0 +1,233 @@
+use crate::protocol::bob::BobState;
+use crate::protocol::State;
+use crate::{bitcoin, monero};
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use time::{Date, Month};
+use typeshare::typeshare;
+
+/// What a swap cost us. Fees are only set for the transactions that were published.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SwapCostBreakdown {
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub btc_amount: bitcoin::Amount,
+    #[typeshare(serialized_as = "number")]
+    pub xmr_amount: monero::Amount,
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub btc_lock_fee: Option<bitcoin::Amount>,
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub btc_cancel_fee: Option<bitcoin::Amount>,
+    /// Also set if the maker refunded us early, we pay the fee of that transaction as well
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub btc_refund_fee: Option<bitcoin::Amount>,
+    /// If we were punished the maker took all of the locked Bitcoin, this fee came out of it
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub btc_punish_fee: Option<bitcoin::Amount>,
+    /// The fee of sweeping the Monero to our receive addresses. Also `None` for swaps that
+    /// redeemed the Monero before we kept track of it.
+    #[typeshare(serialized_as = "Option<number>")]
+    pub xmr_network_fee: Option<monero::Amount>,
+    /// The price the maker charged, in satoshi per XMR
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub price: Option<bitcoin::Amount>,
+    /// The average rate on Kraken on the day the swap started, in satoshi per XMR
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub reference_price: Option<bitcoin::Amount>,
+    /// How much more than the reference price the maker charged, e.g. 0.02 for 2%
+    pub spread: Option<f64>,
+    /// What we paid per XMR we received, including the fees of the Bitcoin lock and the
+    /// Monero sweep. Only known once we redeemed the Monero.
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub effective_price: Option<bitcoin::Amount>,
+}
+
+impl SwapCostBreakdown {
+    /// Build the breakdown from all states of the swap, oldest first. Returns `None` if the
+    /// swap never got past the negotiation with the maker, it didn't cost anything then.
+    pub fn from_states(
+        states: &[State],
+        xmr_network_fee: Option<monero::Amount>,
+        reference_price: Option<bitcoin::Amount>,
+    ) -> Result<Option<Self>> {
+        let states = states
+            .iter()
+            .map(|state| state.clone().try_into())
+            .collect::<Result<Vec<BobState>>>()?;
+
+        let Some(state2) = states.iter().find_map(|state| match state {
+            BobState::SwapSetupCompleted(state2) => Some(state2),
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+
+        let reached = |matches: fn(&BobState) -> bool| states.iter().any(matches);
+        let btc_locked = reached(|state| {
+            !matches!(
+                state,
+                BobState::Started { .. }
+                    | BobState::SwapSetupCompleted(_)
+                    | BobState::SafelyAborted
+            )
+        });
+        let cancelled = reached(|state| {
+            matches!(
+                state,
+                BobState::BtcCancelled(_)
+                    | BobState::BtcRefundPublished(_)
+                    | BobState::BtcRefunded(_)
+                    | BobState::BtcPunished { .. }
+            )
+        });
+        let refunded = reached(|state| {
+            matches!(
+                state,
+                BobState::BtcRefundPublished(_)
+                    | BobState::BtcRefunded(_)
+                    | BobState::BtcEarlyRefundPublished(_)
+                    | BobState::BtcEarlyRefunded(_)
+            )
+        });
+        let punished = reached(|state| matches!(state, BobState::BtcPunished { .. }));
+        let xmr_redeemed = reached(|state| matches!(state, BobState::XmrRedeemed { .. }));
+
+        let btc_amount = state2.tx_lock.lock_amount();
+        let xmr_amount = state2.xmr;
+        let btc_lock_fee = if btc_locked {
+            state2.tx_lock.fee().ok()
+        } else {
+            None
+        };
+        let xmr_network_fee = xmr_network_fee.filter(|_| xmr_redeemed);
+
+        let price = price_per_xmr(btc_amount, xmr_amount);
+        let spread = match (price, reference_price) {
+            (Some(price), Some(reference_price)) => spread(price, reference_price),
+            _ => None,
+        };
+        let effective_price = if xmr_redeemed {
+            let btc_spent = btc_amount + btc_lock_fee.unwrap_or(bitcoin::Amount::ZERO);
+            let xmr_received = monero::Amount::from_piconero(
+                xmr_amount.as_piconero().saturating_sub(
+                    xmr_network_fee
+                        .unwrap_or(monero::Amount::ZERO)
+                        .as_piconero(),
+                ),
+            );
+            price_per_xmr(btc_spent, xmr_received)
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            btc_amount,
+            xmr_amount,
+            btc_lock_fee,
+            btc_cancel_fee: cancelled.then_some(state2.tx_cancel_fee),
+            btc_refund_fee: refunded.then_some(state2.tx_refund_fee),
+            btc_punish_fee: punished.then_some(state2.tx_punish_fee),
+            xmr_network_fee,
+            price,
+            reference_price,
+            spread,
+            effective_price,
+        }))
+    }
+}
+
+/// The day (UTC) of a date as stored in the database, e.g. `2025-08-01 12:00:00.0 +00:00:00`.
+pub fn day_of(date: &str) -> Option<Date> {
+    let mut parts = date.get(..10)?.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+fn price_per_xmr(btc: bitcoin::Amount, xmr: monero::Amount) -> Option<bitcoin::Amount> {
+    if xmr == monero::Amount::ZERO {
+        return None;
+    }
+
+    let xmr = xmr.as_piconero_decimal() / monero::Amount::ONE_XMR.as_piconero_decimal();
+    let sats = (Decimal::from(btc.to_sat()) / xmr).round().to_u64()?;
+
+    Some(bitcoin::Amount::from_sat(sats))
+}
+
+fn spread(price: bitcoin::Amount, reference_price: bitcoin::Amount) -> Option<f64> {
+    if reference_price == bitcoin::Amount::ZERO {
+        return None;
+    }
+
+    let spread =
+        Decimal::from(price.to_sat()) / Decimal::from(reference_price.to_sat()) - Decimal::ONE;
+
+    spread.to_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_is_in_satoshi_per_xmr() {
+        assert_eq!(
+            price_per_xmr(
+                bitcoin::Amount::from_sat(540_000),
+                monero::Amount::from_piconero(2_000_000_000_000)
+            ),
+            Some(bitcoin::Amount::from_sat(270_000))
+        );
+        assert_eq!(
+            price_per_xmr(bitcoin::Amount::from_sat(540_000), monero::Amount::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn spread_is_relative_to_the_reference_price() {
+        let reference_price = bitcoin::Amount::from_sat(200_000);
+
+        let above = spread(bitcoin::Amount::from_sat(204_000), reference_price).unwrap();
+        let below = spread(bitcoin::Amount::from_sat(196_000), reference_price).unwrap();
+
+        assert!((above - 0.02).abs() < 1e-9);
+        assert!((below + 0.02).abs() < 1e-9);
+        assert_eq!(
+            spread(bitcoin::Amount::from_sat(196_000), bitcoin::Amount::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn day_of_stored_date() {
+        assert_eq!(
+            day_of("2025-08-01 12:00:00.0 +00:00:00"),
+            Some(Date::from_calendar_date(2025, Month::August, 1).unwrap())
+        );
+        assert_eq!(day_of("not a date"), None);
+    }
+
+    #[test]
+    fn swaps_without_setup_cost_nothing() {
+        let states = [State::Bob(BobState::SafelyAborted)];
+
+        assert_eq!(
+            SwapCostBreakdown::from_states(&states, None, None).unwrap(),
+            None
+        );
+    }
+}
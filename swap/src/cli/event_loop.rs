@@ -1,10 +1,11 @@
 use crate::bitcoin::EncryptedSignature;
 use crate::cli::behaviour::{Behaviour, OutEvent};
+use crate::database::SwapNotFound;
 use crate::monero;
 use crate::network::cooperative_xmr_redeem_after_punish::{self, Request, Response};
 use crate::network::encrypted_signature;
 use crate::network::quote::BidQuote;
-use crate::network::swap_setup::bob::NewSwap;
+use crate::network::swap_setup::bob::{Error as SwapSetupError, NewSwap};
 use crate::protocol::bob::swap::has_already_processed_transfer_proof;
 use crate::protocol::bob::{BobState, State2};
 use crate::protocol::Database;
@@ -16,8 +17,10 @@ use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::SwarmEvent;
 use libp2p::{PeerId, Swarm};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::Notify;
 use uuid::Uuid;
 
 static REQUEST_RESPONSE_PROTOCOL_TIMEOUT: Duration = Duration::from_secs(60);
@@ -67,6 +70,41 @@ pub struct EventLoop {
     /// resolves, we use the `ResponseChannel` returned from it to send an ACK
     /// to Alice that we have successfully processed the transfer proof.
     pending_transfer_proof: OptionFuture<BoxFuture<'static, ResponseChannel<()>>>,
+
+    /// Wakes up the swaps whose transfer proofs we buffer.
+    buffered_transfer_proofs: BufferedTransferProofs,
+}
+
+/// Wakes up a swap once the event loop of another swap buffered its transfer proof.
+///
+/// Alice sends the transfer proof over any connection she has to us, so if we run several
+/// swaps with the same seller it can arrive at the event loop of another swap. The event
+/// loops of all swaps have to share one instance.
+#[derive(Debug, Clone, Default)]
+pub struct BufferedTransferProofs {
+    notifiers: Arc<Mutex<HashMap<Uuid, Arc<Notify>>>>,
+}
+
+impl BufferedTransferProofs {
+    /// Tell the swap that its transfer proof was buffered in the database.
+    fn notify(&self, swap_id: Uuid) {
+        self.notifier(swap_id).notify_one();
+    }
+
+    /// Resolves once the transfer proof of the swap was buffered, also if that happened
+    /// before we started waiting.
+    async fn buffered(&self, swap_id: Uuid) {
+        self.notifier(swap_id).notified().await;
+    }
+
+    fn notifier(&self, swap_id: Uuid) -> Arc<Notify> {
+        self.notifiers
+            .lock()
+            .expect("lock not to be poisoned")
+            .entry(swap_id)
+            .or_default()
+            .clone()
+    }
 }
 
 impl EventLoop {
@@ -75,6 +113,7 @@ impl EventLoop {
         swarm: Swarm<Behaviour>,
         alice_peer_id: PeerId,
         db: Arc<dyn Database + Send + Sync>,
+        buffered_transfer_proofs: BufferedTransferProofs,
     ) -> Result<(Self, EventLoopHandle)> {
         // We still use a timeout here, because this protocol does not dial Alice itself
         // and we want to fail if we cannot reach Alice
@@ -102,6 +141,7 @@ impl EventLoop {
             inflight_cooperative_xmr_redeem_requests: HashMap::default(),
             pending_transfer_proof: OptionFuture::from(None),
             db,
+            buffered_transfer_proofs: buffered_transfer_proofs.clone(),
         };
 
         let handle = EventLoopHandle {
@@ -110,6 +150,7 @@ impl EventLoop {
             encrypted_signature_sender,
             cooperative_xmr_redeem_sender,
             quote_sender,
+            buffered_transfer_proofs,
         };
 
         Ok((event_loop, handle))
@@ -195,6 +236,7 @@ impl EventLoop {
                                             match self.db.insert_buffered_transfer_proof(swap_id, msg.tx_lock_proof).await {
                                                 Ok(_) => {
                                                     tracing::info!("Received transfer proof for swap {} while running swap {}. Buffering this transfer proof in the database for later retrieval", swap_id, self.swap_id);
+                                                    self.buffered_transfer_proofs.notify(swap_id);
                                                     let _ = self.swarm.behaviour_mut().transfer_proof.send_response(channel, ());
                                                 }
                                                 Err(e) => {
@@ -211,7 +253,7 @@ impl EventLoop {
                                     },
                                     // We do not have a record of the swap or an error occurred while retrieving the peer id of Alice
                                     Err(e) => {
-                                        if let Some(sqlx::Error::RowNotFound) = e.downcast_ref::<sqlx::Error>() {
+                                        if e.is::<SwapNotFound>() {
                                             tracing::warn!("Ignoring transfer proof for swap {} while running swap {}. We do not have a record of this swap", swap_id, self.swap_id);
                                         } else {
                                             tracing::error!("Ignoring transfer proof for swap {} while running swap {}. Failed to retrieve the peer id of Alice for the corresponding swap: {:#}", swap_id, self.swap_id, e);
@@ -391,6 +433,9 @@ pub struct EventLoopHandle {
         (),
         Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>,
     >,
+
+    /// Tells us when the event loop of another swap buffered a transfer proof for us.
+    buffered_transfer_proofs: BufferedTransferProofs,
 }
 
 impl EventLoopHandle {
@@ -409,6 +454,10 @@ impl EventLoopHandle {
         backoff::future::retry_notify(backoff, || async {
             match self.execution_setup_sender.send_receive(swap.clone()).await {
                 Ok(Ok(state2)) => Ok(state2),
+                // Retrying with the same quote is pointless, the caller has to request a new one
+                Ok(Err(err)) if matches!(err.downcast_ref::<SwapSetupError>(), Some(SwapSetupError::QuoteExpired)) => {
+                    Err(backoff::Error::permanent(err))
+                }
                 // These are errors thrown by the swap_setup/bob behaviour
                 Ok(Err(err)) => {
                     Err(backoff::Error::transient(err.context("A network error occurred while setting up the swap")))
@@ -434,6 +483,15 @@ impl EventLoopHandle {
         .context("Failed to setup swap after retries")
     }
 
+    /// Resolves once the event loop of another swap buffered the transfer proof of the swap
+    /// in the database. The future doesn't borrow the handle, so we can wait for the transfer
+    /// proof to arrive here at the same time.
+    pub fn transfer_proof_buffered(&self, swap_id: Uuid) -> impl Future<Output = ()> + 'static {
+        let buffered_transfer_proofs = self.buffered_transfer_proofs.clone();
+
+        async move { buffered_transfer_proofs.buffered(swap_id).await }
+    }
+
     pub async fn recv_transfer_proof(&mut self) -> Result<monero::TransferProof> {
         let (transfer_proof, responder) = self
             .transfer_proof_receiver
@@ -0,0 +1,218 @@
+use crate::bitcoin;
+use crate::protocol::bob::swap::is_complete;
+use crate::protocol::bob::BobState;
+use crate::protocol::State;
+use anyhow::{bail, Context, Result};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use typeshare::typeshare;
+use uuid::Uuid;
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => bail!("Unknown export format {}, expected csv or json", s),
+        }
+    }
+}
+
+/// A swap as it shows up in the exported history. Amounts and fees are in satoshi and
+/// piconero to not lose precision.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedSwap {
+    pub swap_id: Uuid,
+    pub peer_id: String,
+    pub start_date: String,
+    pub last_update: String,
+    pub state: String,
+    pub completed: bool,
+    pub btc_amount_sat: u64,
+    pub xmr_amount_piconero: u64,
+    pub tx_lock_fee_sat: u64,
+    pub tx_cancel_fee_sat: u64,
+    pub tx_refund_fee_sat: u64,
+    pub tx_lock_id: String,
+    pub xmr_lock_tx_hash: Option<String>,
+    pub tx_refund_id: Option<String>,
+}
+
+impl ExportedSwap {
+    /// Build the entry from all states of the swap, oldest first. Returns `None` if the swap
+    /// never got past the negotiation with the maker, no funds were involved then.
+    pub fn from_states(
+        swap_id: Uuid,
+        peer_id: PeerId,
+        states: &[(State, String)],
+    ) -> Result<Option<Self>> {
+        let states = states
+            .iter()
+            .map(|(state, entered_at)| {
+                let state: BobState = state.clone().try_into()?;
+                Ok((state, entered_at))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let Some(state2) = states.iter().find_map(|(state, _)| match state {
+            BobState::SwapSetupCompleted(state2) => Some(state2),
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+
+        let (start_date, (latest_state, last_update)) = match (states.first(), states.last()) {
+            (Some((_, start_date)), Some(latest)) => (start_date.to_string(), latest),
+            _ => return Ok(None),
+        };
+
+        let xmr_lock_tx_hash = states.iter().find_map(|(state, _)| match state {
+            BobState::XmrLockProofReceived {
+                lock_transfer_proof,
+                ..
+            } => Some(lock_transfer_proof.tx_hash().0),
+            _ => None,
+        });
+        let tx_refund_id = match latest_state {
+            BobState::BtcRefunded(state6) => Some(state6.construct_tx_refund()?.txid()),
+            BobState::BtcEarlyRefunded(state6) => Some(state6.construct_tx_early_refund().txid()),
+            _ => None,
+        };
+
+        Ok(Some(Self {
+            swap_id,
+            peer_id: peer_id.to_string(),
+            start_date,
+            last_update: last_update.to_string(),
+            state: latest_state.to_string(),
+            completed: is_complete(latest_state),
+            btc_amount_sat: state2.tx_lock.lock_amount().to_sat(),
+            xmr_amount_piconero: state2.xmr.as_piconero(),
+            tx_lock_fee_sat: state2
+                .tx_lock
+                .fee()
+                .unwrap_or(bitcoin::Amount::ZERO)
+                .to_sat(),
+            tx_cancel_fee_sat: state2.tx_cancel_fee.to_sat(),
+            tx_refund_fee_sat: state2.tx_refund_fee.to_sat(),
+            tx_lock_id: state2.tx_lock.txid().to_string(),
+            xmr_lock_tx_hash,
+            tx_refund_id: tx_refund_id.map(|txid| txid.to_string()),
+        }))
+    }
+}
+
+/// Write the swaps to `path`, overwriting the file if it exists.
+pub fn write(path: &Path, format: ExportFormat, swaps: &[ExportedSwap]) -> Result<()> {
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(swaps)?,
+        ExportFormat::Csv => to_csv(swaps),
+    };
+
+    std::fs::write(path, content)
+        .with_context(|| format!("Failed to write history to {}", path.display()))
+}
+
+const CSV_HEADER: &[&str] = &[
+    "swap_id",
+    "peer_id",
+    "start_date",
+    "last_update",
+    "state",
+    "completed",
+    "btc_amount_sat",
+    "xmr_amount_piconero",
+    "tx_lock_fee_sat",
+    "tx_cancel_fee_sat",
+    "tx_refund_fee_sat",
+    "tx_lock_id",
+    "xmr_lock_tx_hash",
+    "tx_refund_id",
+];
+
+fn to_csv(swaps: &[ExportedSwap]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push('\n');
+
+    for swap in swaps {
+        let fields = [
+            swap.swap_id.to_string(),
+            swap.peer_id.clone(),
+            swap.start_date.clone(),
+            swap.last_update.clone(),
+            swap.state.clone(),
+            swap.completed.to_string(),
+            swap.btc_amount_sat.to_string(),
+            swap.xmr_amount_piconero.to_string(),
+            swap.tx_lock_fee_sat.to_string(),
+            swap.tx_cancel_fee_sat.to_string(),
+            swap.tx_refund_fee_sat.to_string(),
+            swap.tx_lock_id.clone(),
+            swap.xmr_lock_tx_hash.clone().unwrap_or_default(),
+            swap.tx_refund_id.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<_> = fields.iter().map(|field| escape(field)).collect();
+        let _ = writeln!(csv, "{}", fields.join(","));
+    }
+
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_has_a_header_and_quotes_fields() {
+        let swap = ExportedSwap {
+            swap_id: Uuid::nil(),
+            peer_id: "12D3KooW".to_string(),
+            start_date: "2025-08-01 12:00:00.0 +00:00:00".to_string(),
+            last_update: "2025-08-01 13:00:00.0 +00:00:00".to_string(),
+            state: "btc is \"refunded\", finally".to_string(),
+            completed: true,
+            btc_amount_sat: 100_000,
+            xmr_amount_piconero: 1_000_000_000_000,
+            tx_lock_fee_sat: 500,
+            tx_cancel_fee_sat: 400,
+            tx_refund_fee_sat: 300,
+            tx_lock_id: "lock".to_string(),
+            xmr_lock_tx_hash: None,
+            tx_refund_id: Some("refund".to_string()),
+        };
+
+        let csv = to_csv(&[swap]);
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), CSV_HEADER.len());
+        assert_eq!(
+            lines[1],
+            "00000000-0000-0000-0000-000000000000,12D3KooW,2025-08-01 12:00:00.0 +00:00:00,\
+             2025-08-01 13:00:00.0 +00:00:00,\"btc is \"\"refunded\"\", finally\",true,100000,\
+             1000000000000,500,400,300,lock,,refund"
+        );
+    }
+}
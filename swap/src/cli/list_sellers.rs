@@ -6,7 +6,7 @@ use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
 use crate::network::{quote, swarm};
 use crate::protocol::Database;
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
 use arti_client::TorClient;
 use futures::StreamExt;
 use libp2p::identify;
@@ -15,6 +15,7 @@ use libp2p::request_response;
 use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
 use libp2p::{identity, ping, rendezvous, Multiaddr, PeerId, Swarm};
+use rust_decimal::Decimal;
 use semver::Version;
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
@@ -49,6 +50,44 @@ pub async fn list_sellers(
     db: Option<Arc<dyn Database + Send + Sync>>,
     tauri_handle: Option<TauriHandle>,
 ) -> Result<Vec<SellerStatus>> {
+    let discovery = discover(
+        rendezvous_points,
+        namespace,
+        maybe_tor_client,
+        identity,
+        db,
+        tauri_handle,
+    )
+    .await?;
+
+    Ok(discovery.sellers)
+}
+
+/// The outcome of looking for sellers at a set of rendezvous points.
+#[derive(Debug)]
+pub struct Discovery {
+    /// Sorted list of sellers, as returned by [`list_sellers`].
+    pub sellers: Vec<SellerStatus>,
+    /// Whether we could connect to each rendezvous point and discover peers at it.
+    pub rendezvous_points: Vec<(PeerId, bool)>,
+}
+
+impl Discovery {
+    pub fn any_rendezvous_point_succeeded(&self) -> bool {
+        self.rendezvous_points.iter().any(|(_, success)| *success)
+    }
+}
+
+/// Like [`list_sellers`] but also reports which of the rendezvous points answered, such that
+/// the caller can keep track of their health.
+pub async fn discover(
+    rendezvous_points: Vec<(PeerId, Multiaddr)>,
+    namespace: XmrBtcNamespace,
+    maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
+    identity: identity::Keypair,
+    db: Option<Arc<dyn Database + Send + Sync>>,
+    tauri_handle: Option<TauriHandle>,
+) -> Result<Discovery> {
     let behaviour = Behaviour {
         rendezvous: rendezvous::client::Behaviour::new(identity.clone()),
         quote: quote::cli(),
@@ -73,9 +112,8 @@ pub async fn list_sellers(
         external_dial_queue,
         tauri_handle,
     );
-    let sellers = event_loop.run().await;
 
-    Ok(sellers)
+    Ok(event_loop.run().await)
 }
 
 #[serde_as]
@@ -116,6 +154,91 @@ pub enum SellerStatus {
     Unreachable(UnreachableSeller),
 }
 
+/// A maker that sent us a quote, together with how reliably it answered in the past.
+#[typeshare]
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct DiscoveredMaker {
+    pub maker: QuoteWithAddress,
+    /// The share of our searches for makers in which this one sent us a quote, between 0 and 1.
+    /// `None` if we never looked for it before.
+    pub uptime: Option<f64>,
+}
+
+impl DiscoveredMaker {
+    pub fn new(maker: QuoteWithAddress, (online_count, unreachable_count): (u64, u64)) -> Self {
+        let total = online_count + unreachable_count;
+        let uptime = (total > 0).then(|| online_count as f64 / total as f64);
+
+        Self { maker, uptime }
+    }
+}
+
+/// Makers we found with a lower uptime than this are never picked automatically.
+const MIN_AUTO_SELECT_UPTIME: f64 = 0.5;
+
+/// Sorts the makers with the best offer first: the lowest price, then the highest uptime.
+/// Makers that currently don't buy any Bitcoin come last.
+pub fn rank_makers(makers: &mut [DiscoveredMaker]) {
+    makers.sort_by(|a, b| {
+        let a_quote = &a.maker.quote;
+        let b_quote = &b.maker.quote;
+
+        (a_quote.max_quantity == bitcoin::Amount::ZERO)
+            .cmp(&(b_quote.max_quantity == bitcoin::Amount::ZERO))
+            .then(a_quote.price.cmp(&b_quote.price))
+            .then(
+                b.uptime
+                    .unwrap_or_default()
+                    .total_cmp(&a.uptime.unwrap_or_default()),
+            )
+    });
+}
+
+/// Pick the maker to swap with out of makers ranked by [`rank_makers`].
+///
+/// The maker has to buy Bitcoin at all, accept `max_giveable` as an amount (unless we have no
+/// funds yet) and must not have been unreachable most of the time. To not fall for an outlier,
+/// its price may exceed the median of all quotes by at most `max_spread`.
+pub fn select_maker(
+    makers: &[DiscoveredMaker],
+    max_giveable: bitcoin::Amount,
+    max_spread: Decimal,
+) -> Result<&DiscoveredMaker> {
+    let selected = makers
+        .iter()
+        .find(|maker| {
+            let quote = &maker.maker.quote;
+
+            quote.max_quantity > bitcoin::Amount::ZERO
+                && (max_giveable == bitcoin::Amount::ZERO || quote.min_quantity <= max_giveable)
+                && maker
+                    .uptime
+                    .is_none_or(|uptime| uptime >= MIN_AUTO_SELECT_UPTIME)
+        })
+        .context("None of the discovered makers can take our swap")?;
+
+    let mut prices: Vec<_> = makers
+        .iter()
+        .map(|maker| maker.maker.quote)
+        .filter(|quote| quote.max_quantity > bitcoin::Amount::ZERO)
+        .map(|quote| quote.price)
+        .collect();
+    prices.sort();
+    let median = prices[prices.len() / 2];
+
+    let price = selected.maker.quote.price;
+    let max_price = Decimal::from(median.to_sat()) * (Decimal::ONE + max_spread);
+    ensure!(
+        Decimal::from(price.to_sat()) <= max_price,
+        "The best maker we can swap with asks {} per XMR, more than {}% above the median quote of {}",
+        price,
+        max_spread * Decimal::ONE_HUNDRED,
+        median
+    );
+
+    Ok(selected)
+}
+
 #[allow(unused)]
 #[derive(Debug)]
 enum OutEvent {
@@ -578,7 +701,7 @@ impl EventLoop {
         }
     }
 
-    async fn run(mut self) -> Vec<SellerStatus> {
+    async fn run(mut self) -> Discovery {
         // Dial all rendezvous points initially
         for (peer_id, multiaddr) in &self.rendezvous_points {
             let dial_opts = DialOpts::peer_id(*peer_id)
@@ -827,7 +950,23 @@ impl EventLoop {
                     if let Some(ref progress_handle) = self.progress_handle {
                         progress_handle.finish();
                     }
-                    break sellers;
+
+                    let rendezvous_points = self
+                        .rendezvous_points
+                        .iter()
+                        .map(|(peer_id, _)| {
+                            let success = matches!(
+                                self.rendezvous_points_status.get(peer_id),
+                                Some(RendezvousPointStatus::Success)
+                            );
+                            (*peer_id, success)
+                        })
+                        .collect();
+
+                    break Discovery {
+                        sellers,
+                        rendezvous_points,
+                    };
                 }
                 Err(StillPending {}) => continue,
             }
@@ -883,6 +1022,7 @@ mod tests {
             price: bitcoin::Amount::from_sat(50000),
             min_quantity: bitcoin::Amount::from_sat(1000),
             max_quantity: bitcoin::Amount::from_sat(100000),
+            expires_at: None,
         }
     }
 
@@ -1398,6 +1538,7 @@ mod tests {
                 price: bitcoin::Amount::from_sat(99999),
                 min_quantity: bitcoin::Amount::from_sat(1),
                 max_quantity: bitcoin::Amount::from_sat(1000),
+                expires_at: None,
             };
             let new_state = state.apply_quote(Ok(new_quote));
 
@@ -1465,6 +1606,94 @@ mod tests {
         }
     }
 
+    #[test]
+    fn makers_rank_by_price_then_uptime() {
+        let maker = |price: u64, max_quantity: u64, reachability: (u64, u64)| {
+            DiscoveredMaker::new(
+                QuoteWithAddress {
+                    multiaddr: test_multiaddr(),
+                    peer_id: test_peer_id(),
+                    quote: BidQuote {
+                        price: bitcoin::Amount::from_sat(price),
+                        min_quantity: bitcoin::Amount::from_sat(1000),
+                        max_quantity: bitcoin::Amount::from_sat(max_quantity),
+                        expires_at: None,
+                    },
+                    version: test_version(),
+                },
+                reachability,
+            )
+        };
+
+        let without_liquidity = maker(40_000, 0, (10, 0));
+        let expensive = maker(60_000, 100_000, (10, 0));
+        let flaky = maker(50_000, 100_000, (1, 9));
+        let reliable = maker(50_000, 100_000, (9, 1));
+        let mut makers = vec![
+            without_liquidity.clone(),
+            expensive.clone(),
+            flaky.clone(),
+            reliable.clone(),
+        ];
+
+        rank_makers(&mut makers);
+
+        assert_eq!(makers, vec![reliable, flaky, expensive, without_liquidity]);
+        assert_eq!(maker(50_000, 100_000, (0, 0)).uptime, None);
+    }
+
+    #[test]
+    fn selects_the_cheapest_reliable_maker_within_the_spread() {
+        let maker = |price: u64, min_quantity: u64, reachability: (u64, u64)| {
+            DiscoveredMaker::new(
+                QuoteWithAddress {
+                    multiaddr: test_multiaddr(),
+                    peer_id: test_peer_id(),
+                    quote: BidQuote {
+                        price: bitcoin::Amount::from_sat(price),
+                        min_quantity: bitcoin::Amount::from_sat(min_quantity),
+                        max_quantity: bitcoin::Amount::from_sat(1_000_000),
+                        expires_at: None,
+                    },
+                    version: test_version(),
+                },
+                reachability,
+            )
+        };
+        let max_giveable = bitcoin::Amount::from_sat(100_000);
+        let max_spread = Decimal::new(5, 2);
+
+        let flaky = maker(48_000, 1000, (1, 9));
+        let too_big = maker(49_000, 500_000, (10, 0));
+        let unknown = maker(50_000, 1000, (0, 0));
+        let reliable = maker(51_000, 1000, (10, 0));
+        let makers = vec![flaky, too_big, unknown.clone(), reliable];
+
+        assert_eq!(
+            select_maker(&makers, max_giveable, max_spread).unwrap(),
+            &unknown
+        );
+
+        // Without funds yet, the minimum amount doesn't matter
+        assert_eq!(
+            select_maker(&makers, bitcoin::Amount::ZERO, max_spread)
+                .unwrap()
+                .maker
+                .quote
+                .price,
+            bitcoin::Amount::from_sat(49_000)
+        );
+
+        // Only an outlier is left to swap with
+        let outlier = maker(70_000, 1000, (10, 0));
+        let makers = vec![
+            maker(48_000, 1000, (0, 10)),
+            maker(50_000, 1000, (0, 10)),
+            outlier,
+        ];
+        assert!(select_maker(&makers, max_giveable, max_spread).is_err());
+    }
+
     #[test]
     fn sellers_sort_with_unreachable_coming_last() {
         let mut list = vec![
@@ -1481,6 +1710,7 @@ mod tests {
                     price: Default::default(),
                     min_quantity: Default::default(),
                     max_quantity: Default::default(),
+                    expires_at: None,
                 },
                 version: Version::parse("1.0.0").unwrap(), // Fixed: Use valid semver
             }),
@@ -0,0 +1,240 @@
+use crate::cli::SellerStatus;
+use crate::libp2p_ext::MultiAddrExt;
+use anyhow::{Context, Result};
+use hickory_resolver::TokioAsyncResolver;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashSet;
+
+/// The prefix of the TXT records listing the rendezvous points of a DNS seed.
+const DNSADDR_PREFIX: &str = "dnsaddr=";
+
+/// If the address is a DNS seed like `/dnsaddr/rendezvous.example.org` we return its domain.
+///
+/// A DNS seed doesn't point to a single rendezvous point, it is resolved to the list of
+/// rendezvous points in the TXT records of `_dnsaddr.<domain>`.
+pub fn dns_seed(address: &Multiaddr) -> Option<String> {
+    let mut protocols = address.iter();
+
+    match (protocols.next(), protocols.next()) {
+        (Some(Protocol::Dnsaddr(domain)), None) => Some(domain.to_string()),
+        _ => None,
+    }
+}
+
+/// Resolve a DNS seed to the rendezvous points it lists.
+///
+/// The TXT records follow the `dnsaddr` convention of libp2p, each record holds one address
+/// which must end with the peer id of the rendezvous point:
+/// `dnsaddr=/dns4/rendezvous.example.org/tcp/8888/p2p/12D3KooW...`
+pub async fn resolve_dns_seed(domain: &str) -> Result<Vec<(PeerId, Multiaddr)>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .context("Failed to read the system DNS configuration")?;
+
+    let lookup = resolver
+        .txt_lookup(format!("_dnsaddr.{}", domain))
+        .await
+        .with_context(|| format!("Failed to look up the DNS seed {}", domain))?;
+
+    let rendezvous_points = lookup
+        .iter()
+        .filter_map(|txt| {
+            let record = txt
+                .iter()
+                .map(|data| String::from_utf8_lossy(data))
+                .collect::<String>();
+
+            let rendezvous_point = parse_dnsaddr_record(&record);
+            if rendezvous_point.is_none() {
+                tracing::debug!(%domain, %record, "Ignoring invalid record of DNS seed");
+            }
+
+            rendezvous_point
+        })
+        .collect();
+
+    Ok(rendezvous_points)
+}
+
+fn parse_dnsaddr_record(record: &str) -> Option<(PeerId, Multiaddr)> {
+    record
+        .strip_prefix(DNSADDR_PREFIX)?
+        .parse::<Multiaddr>()
+        .ok()?
+        .split_peer_id()
+}
+
+/// Order in which we try the rendezvous points, as a list of rounds.
+///
+/// We start with the healthy rendezvous points that were configured, then try the healthy
+/// ones we learned from DNS seeds. Only if none of those answered do we fall back to the
+/// rendezvous points that failed repeatedly before. Rounds without rendezvous points are
+/// left out.
+pub fn rotation(
+    configured: Vec<(PeerId, Multiaddr)>,
+    seeded: Vec<(PeerId, Multiaddr)>,
+    is_healthy: impl Fn(&PeerId) -> bool,
+) -> Vec<Vec<(PeerId, Multiaddr)>> {
+    let mut seen = HashSet::new();
+    let mut configured_healthy = Vec::new();
+    let mut seeded_healthy = Vec::new();
+    let mut unhealthy = Vec::new();
+
+    for (rendezvous_point, is_seeded) in configured
+        .into_iter()
+        .map(|point| (point, false))
+        .chain(seeded.into_iter().map(|point| (point, true)))
+    {
+        if !seen.insert(rendezvous_point.0) {
+            continue;
+        }
+
+        match (is_healthy(&rendezvous_point.0), is_seeded) {
+            (true, false) => configured_healthy.push(rendezvous_point),
+            (true, true) => seeded_healthy.push(rendezvous_point),
+            (false, _) => unhealthy.push(rendezvous_point),
+        }
+    }
+
+    [configured_healthy, seeded_healthy, unhealthy]
+        .into_iter()
+        .filter(|round| !round.is_empty())
+        .collect()
+}
+
+/// Add the sellers found in a later round. A seller we already know is only replaced if it
+/// was unreachable before and sent us a quote now.
+pub fn merge_sellers(sellers: &mut Vec<SellerStatus>, found: Vec<SellerStatus>) {
+    for seller in found {
+        match sellers
+            .iter_mut()
+            .find(|known| peer_id(known) == peer_id(&seller))
+        {
+            Some(known) => {
+                if matches!(known, SellerStatus::Unreachable(_)) {
+                    *known = seller;
+                }
+            }
+            None => sellers.push(seller),
+        }
+    }
+
+    sellers.sort();
+}
+
+fn peer_id(seller: &SellerStatus) -> PeerId {
+    match seller {
+        SellerStatus::Online(quote) => quote.peer_id,
+        SellerStatus::Unreachable(unreachable) => unreachable.peer_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin;
+    use crate::cli::list_sellers::{QuoteWithAddress, UnreachableSeller};
+    use crate::network::quote::BidQuote;
+    use semver::Version;
+
+    fn rendezvous_point(port: u16) -> (PeerId, Multiaddr) {
+        let peer_id = PeerId::random();
+        let address = format!("/ip4/127.0.0.1/tcp/{}/p2p/{}", port, peer_id)
+            .parse()
+            .unwrap();
+
+        (peer_id, address)
+    }
+
+    #[test]
+    fn only_plain_dnsaddr_addresses_are_dns_seeds() {
+        let seed = "/dnsaddr/rendezvous.example.org".parse().unwrap();
+        let with_peer_id = format!("/dnsaddr/rendezvous.example.org/p2p/{}", PeerId::random())
+            .parse()
+            .unwrap();
+        let (_, regular) = rendezvous_point(8888);
+
+        assert_eq!(dns_seed(&seed), Some("rendezvous.example.org".to_string()));
+        assert_eq!(dns_seed(&with_peer_id), None);
+        assert_eq!(dns_seed(&regular), None);
+    }
+
+    #[test]
+    fn dnsaddr_records_must_contain_a_peer_id() {
+        let (peer_id, address) = rendezvous_point(8888);
+
+        assert_eq!(
+            parse_dnsaddr_record(&format!("dnsaddr={}", address)),
+            Some((peer_id, address.clone()))
+        );
+        assert_eq!(parse_dnsaddr_record(&address.to_string()), None);
+        assert_eq!(
+            parse_dnsaddr_record("dnsaddr=/ip4/127.0.0.1/tcp/8888"),
+            None
+        );
+        assert_eq!(parse_dnsaddr_record("dnsaddr=not an address"), None);
+    }
+
+    #[test]
+    fn unhealthy_rendezvous_points_are_tried_last() {
+        let healthy = rendezvous_point(1);
+        let failing = rendezvous_point(2);
+        let seeded = rendezvous_point(3);
+        let failing_seeded = rendezvous_point(4);
+
+        let rounds = rotation(
+            vec![healthy.clone(), failing.clone()],
+            vec![seeded.clone(), failing_seeded.clone(), healthy.clone()],
+            |peer_id| *peer_id != failing.0 && *peer_id != failing_seeded.0,
+        );
+
+        assert_eq!(
+            rounds,
+            vec![vec![healthy], vec![seeded], vec![failing, failing_seeded]]
+        );
+    }
+
+    #[test]
+    fn rounds_without_rendezvous_points_are_skipped() {
+        let configured = rendezvous_point(1);
+
+        assert_eq!(
+            rotation(vec![configured.clone()], vec![], |_| true),
+            vec![vec![configured]]
+        );
+        assert!(rotation(vec![], vec![], |_| true).is_empty());
+    }
+
+    #[test]
+    fn unreachable_sellers_are_replaced_by_later_rounds() {
+        let (peer_id, multiaddr) = rendezvous_point(1);
+        let other = PeerId::random();
+        let online = SellerStatus::Online(QuoteWithAddress {
+            multiaddr,
+            peer_id,
+            quote: BidQuote {
+                price: bitcoin::Amount::from_sat(270_000),
+                min_quantity: bitcoin::Amount::from_sat(100_000),
+                max_quantity: bitcoin::Amount::from_sat(1_000_000),
+                expires_at: None,
+            },
+            version: Version::new(1, 0, 0),
+        });
+        let mut sellers = vec![SellerStatus::Unreachable(UnreachableSeller { peer_id })];
+
+        merge_sellers(
+            &mut sellers,
+            vec![
+                online.clone(),
+                SellerStatus::Unreachable(UnreachableSeller { peer_id: other }),
+            ],
+        );
+        merge_sellers(
+            &mut sellers,
+            vec![SellerStatus::Unreachable(UnreachableSeller { peer_id })],
+        );
+
+        assert_eq!(sellers.len(), 2);
+        assert!(sellers.contains(&online));
+    }
+}
@@ -4,7 +4,7 @@ use crate::network::transport::authenticate_and_multiplex;
 use anyhow::Result;
 use arti_client::TorClient;
 use libp2p::core::muxing::StreamMuxerBox;
-use libp2p::core::transport::{Boxed, OptionalTransport};
+use libp2p::core::transport::Boxed;
 use libp2p::dns;
 use libp2p::tcp;
 use libp2p::{identity, PeerId, Transport};
@@ -16,25 +16,24 @@ use tor_rtcompat::tokio::TokioRustlsRuntime;
 /// The CLI's transport needs the following capabilities:
 /// - Establish TCP connections
 /// - Resolve DNS entries
-/// - Dial onion-addresses through a running Tor daemon by connecting to the
-///   socks5 port. If the port is not given, we will fall back to the regular
-///   TCP transport.
+/// - Dial onion-addresses through the Tor client, if one is given. All other
+///   connections go through Tor as well then, we never fall back to a direct
+///   connection because that would reveal our IP address to the maker.
 pub fn new(
     identity: &identity::Keypair,
     maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
 ) -> Result<Boxed<(PeerId, StreamMuxerBox)>> {
-    let tcp = tcp::tokio::Transport::new(tcp::Config::new().nodelay(true));
-    let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
+    match maybe_tor_client {
+        Some(client) => {
+            let tor = TorTransport::from_client(client, AddressConversion::IpAndDns);
 
-    let maybe_tor_transport: OptionalTransport<TorTransport> = match maybe_tor_client {
-        Some(client) => OptionalTransport::some(libp2p_community_tor::TorTransport::from_client(
-            client,
-            AddressConversion::IpAndDns,
-        )),
-        None => OptionalTransport::none(),
-    };
+            authenticate_and_multiplex(tor.boxed(), identity)
+        }
+        None => {
+            let tcp = tcp::tokio::Transport::new(tcp::Config::new().nodelay(true));
+            let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
 
-    let transport = maybe_tor_transport.or_transport(tcp_with_dns).boxed();
-
-    authenticate_and_multiplex(transport, identity)
+            authenticate_and_multiplex(tcp_with_dns.boxed(), identity)
+        }
+    }
 }
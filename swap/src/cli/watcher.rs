@@ -11,7 +11,10 @@ use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
-/// A long running task which watches for changes to timelocks and balance
+/// A long running task which watches for changes to timelocks and balance.
+///
+/// Every swap whose cancel timelock expired is cancelled and refunded in the background,
+/// unless the swap is currently running (then its state machine takes care of it).
 #[derive(Clone)]
 pub struct Watcher {
     wallet: Arc<Wallet>,
@@ -114,14 +117,12 @@ impl Watcher {
 
                 // If the swap is already running, we can skip the refund
                 // The refund will be handled by the state machine
-                if let Some(current_swap_id) = self.swap_lock.get_current_swap_id().await {
-                    if current_swap_id == swap_id {
-                        continue;
-                    }
+                if self.swap_lock.is_swap_running(swap_id).await {
+                    continue;
                 }
 
                 if let Err(e) = self.swap_lock.acquire_swap_lock(swap_id).await {
-                    tracing::error!(%e, %swap_id, "Watcher failed to refund a swap in the background because another swap is already running");
+                    tracing::error!(%e, %swap_id, "Watcher failed to refund a swap in the background because it was started in the meantime");
                     continue;
                 }
 
@@ -145,7 +146,7 @@ impl Watcher {
                 background_process_handle.finish();
 
                 // We have to release the swap lock when we are done
-                self.swap_lock.release_swap_lock().await?;
+                self.swap_lock.release_swap_lock(swap_id).await?;
             }
         }
 
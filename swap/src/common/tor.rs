@@ -1,11 +1,15 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::Path;
 use std::sync::Arc;
 
 use crate::cli::api::tauri_bindings::{
     TauriBackgroundProgress, TauriEmitter, TauriHandle, TorBootstrapStatus,
 };
+use anyhow::{bail, Context};
 use arti_client::{config::TorClientConfigBuilder, status::BootstrapStatus, Error, TorClient};
 use futures::StreamExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 use tor_rtcompat::tokio::TokioRustlsRuntime;
 
 pub async fn init_tor_client(
@@ -70,6 +74,108 @@ pub async fn init_tor_client(
     Ok(Arc::new(tor_client))
 }
 
+/// Serve a SOCKS5 proxy on a random local port which opens its connections through the given
+/// Tor client, for libraries which can only connect through a proxy (like the Electrum client).
+///
+/// Only the `CONNECT` command without authentication is supported.
+pub async fn spawn_socks5_proxy(
+    tor_client: Arc<TorClient<TokioRustlsRuntime>>,
+) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .await
+        .context("Failed to bind the SOCKS5 proxy for Tor")?;
+    let address = listener.local_addr()?;
+
+    tracing::debug!(%address, "Serving SOCKS5 proxy for the Tor client");
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    tracing::warn!(%err, "Failed to accept SOCKS5 connection");
+                    continue;
+                }
+            };
+
+            let tor_client = tor_client.clone();
+            tokio::spawn(async move {
+                if let Err(err) = proxy_socks5_connection(stream, &tor_client).await {
+                    tracing::debug!(%err, "SOCKS5 connection through Tor failed");
+                }
+            });
+        }
+    });
+
+    Ok(address)
+}
+
+async fn proxy_socks5_connection(
+    mut stream: TcpStream,
+    tor_client: &TorClient<TokioRustlsRuntime>,
+) -> anyhow::Result<()> {
+    const VERSION: u8 = 5;
+    const NO_AUTHENTICATION: u8 = 0;
+    const CONNECT: u8 = 1;
+    const SUCCEEDED: u8 = 0;
+    const GENERAL_FAILURE: u8 = 1;
+    const COMMAND_NOT_SUPPORTED: u8 = 7;
+
+    // Greeting: version, number of methods and the methods
+    let [version, methods] = read_array(&mut stream).await?;
+    if version != VERSION {
+        bail!("Unsupported SOCKS version {}", version);
+    }
+    let mut offered_methods = vec![0; methods as usize];
+    stream.read_exact(&mut offered_methods).await?;
+    if !offered_methods.contains(&NO_AUTHENTICATION) {
+        stream.write_all(&[VERSION, 0xff]).await?;
+        bail!("SOCKS5 client requires authentication");
+    }
+    stream.write_all(&[VERSION, NO_AUTHENTICATION]).await?;
+
+    // Request: version, command, reserved and the address to connect to
+    let [_, command, _, address_type] = read_array(&mut stream).await?;
+    let host = match address_type {
+        1 => Ipv4Addr::from(read_array::<4>(&mut stream).await?).to_string(),
+        3 => {
+            let [length] = read_array(&mut stream).await?;
+            let mut domain = vec![0; length as usize];
+            stream.read_exact(&mut domain).await?;
+            String::from_utf8(domain).context("Domain name is not UTF-8")?
+        }
+        4 => Ipv6Addr::from(read_array::<16>(&mut stream).await?).to_string(),
+        other => bail!("Unsupported SOCKS5 address type {}", other),
+    };
+    let port = u16::from_be_bytes(read_array(&mut stream).await?);
+
+    let reply = |status: u8| [VERSION, status, 0, 1, 0, 0, 0, 0, 0, 0];
+
+    if command != CONNECT {
+        stream.write_all(&reply(COMMAND_NOT_SUPPORTED)).await?;
+        bail!("Unsupported SOCKS5 command {}", command);
+    }
+
+    let mut tor_stream = match tor_client.connect((host.as_str(), port)).await {
+        Ok(tor_stream) => tor_stream,
+        Err(err) => {
+            stream.write_all(&reply(GENERAL_FAILURE)).await?;
+            return Err(err).with_context(|| format!("Failed to connect to {}:{}", host, port));
+        }
+    };
+    stream.write_all(&reply(SUCCEEDED)).await?;
+
+    tokio::io::copy_bidirectional(&mut stream, &mut tor_stream).await?;
+
+    Ok(())
+}
+
+async fn read_array<const N: usize>(stream: &mut TcpStream) -> std::io::Result<[u8; N]> {
+    let mut buffer = [0; N];
+    stream.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
 // A trait to convert the Tor bootstrap event into a TauriBootstrapStatus
 trait ToTauriBootstrapStatus {
     fn to_tauri_bootstrap_status(&self) -> TorBootstrapStatus;
@@ -1,5 +1,7 @@
 pub use alice::Alice;
 pub use bob::Bob;
+pub use encryption::StateEncryption;
+pub use postgres::PostgresDatabase;
 pub use sqlite::SqliteDatabase;
 
 use crate::cli::api::tauri_bindings::TauriHandle;
@@ -12,7 +14,10 @@ use std::path::Path;
 use std::sync::Arc;
 
 mod alice;
+mod backup;
 mod bob;
+mod encryption;
+mod postgres;
 mod sqlite;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -60,6 +65,11 @@ impl Display for Swap {
     }
 }
 
+/// We have no record of the swap, independent of the storage backend.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
+#[error("We do not have a record of this swap")]
+pub struct SwapNotFound;
+
 #[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
 #[error("Not in the role of Alice")]
 struct NotAlice;
@@ -94,24 +104,57 @@ pub async fn open_db(
     sqlite_path: impl AsRef<Path>,
     access_mode: AccessMode,
     tauri_handle: impl Into<Option<TauriHandle>>,
+    encryption: impl Into<Option<StateEncryption>>,
 ) -> Result<Arc<dyn Database + Send + Sync>> {
     if sqlite_path.as_ref().exists() {
         tracing::debug!("Using existing sqlite database.");
-
-        let sqlite = SqliteDatabase::open(sqlite_path, access_mode)
-            .await?
-            .with_tauri_handle(tauri_handle.into());
-
-        Ok(Arc::new(sqlite))
     } else {
         tracing::debug!("Creating and using new sqlite database.");
 
         ensure_directory_exists(sqlite_path.as_ref())?;
         tokio::fs::File::create(&sqlite_path).await?;
-        let sqlite = SqliteDatabase::open(sqlite_path, access_mode)
-            .await?
-            .with_tauri_handle(tauri_handle.into());
+    }
+
+    let backup_dir = sqlite_path
+        .as_ref()
+        .parent()
+        .map(|data_dir| data_dir.join("backups"));
+
+    let sqlite = SqliteDatabase::open(sqlite_path, access_mode)
+        .await?
+        .with_tauri_handle(tauri_handle.into())
+        .with_state_encryption(encryption);
+
+    if access_mode == AccessMode::ReadWrite {
+        sqlite.encrypt_plaintext_states().await?;
+    }
+
+    let sqlite = Arc::new(sqlite);
 
-        Ok(Arc::new(sqlite))
+    if let (AccessMode::ReadWrite, Some(backup_dir)) = (access_mode, backup_dir) {
+        tokio::spawn(backup::run(Arc::downgrade(&sqlite), backup_dir));
     }
+
+    Ok(sqlite)
+}
+
+/// Open a Postgres database, which several asb instances can share. Each instance only sees
+/// the rows it wrote itself, `instance` tells them apart.
+pub async fn open_postgres_db(
+    url: &str,
+    instance: impl Into<String>,
+    access_mode: AccessMode,
+    encryption: impl Into<Option<StateEncryption>>,
+) -> Result<Arc<dyn Database + Send + Sync>> {
+    tracing::debug!("Using postgres database.");
+
+    let postgres = PostgresDatabase::open(url, instance, access_mode)
+        .await?
+        .with_state_encryption(encryption);
+
+    if access_mode == AccessMode::ReadWrite {
+        postgres.encrypt_plaintext_states().await?;
+    }
+
+    Ok(Arc::new(postgres))
 }
@@ -0,0 +1,160 @@
+use crate::database::SqliteDatabase;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
+use std::time::{Duration, SystemTime};
+use time::OffsetDateTime;
+
+/// How often we back up the database.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How many backups we keep, the oldest ones are deleted.
+const BACKUPS_TO_KEEP: usize = 7;
+
+const BACKUP_PREFIX: &str = "sqlite-";
+const BACKUP_EXTENSION: &str = ".db";
+
+/// A backup is written to a file with this extension first and only renamed once it is
+/// complete, so a backup that was interrupted is never mistaken for a good one.
+const PARTIAL_EXTENSION: &str = ".partial";
+
+/// Back up the database into `backup_dir` once a day for as long as the database is open.
+///
+/// The first backup is only made if the latest one is older than a day, so that starting the
+/// CLI for a short command doesn't back up the database every time.
+pub(super) async fn run(db: Weak<SqliteDatabase>, backup_dir: PathBuf) {
+    let mut wait = match latest_backup_age(&backup_dir).await {
+        Some(age) => BACKUP_INTERVAL.saturating_sub(age),
+        None => Duration::ZERO,
+    };
+
+    loop {
+        tokio::time::sleep(wait).await;
+        wait = BACKUP_INTERVAL;
+
+        let Some(db) = db.upgrade() else {
+            return;
+        };
+
+        match backup(&db, &backup_dir).await {
+            Ok(path) => tracing::debug!(path = %path.display(), "Backed up the database"),
+            Err(error) => tracing::warn!("Failed to back up the database: {:#}", error),
+        }
+    }
+}
+
+async fn backup(db: &SqliteDatabase, backup_dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(backup_dir)
+        .await
+        .context("Failed to create the backup directory")?;
+
+    let mut names = backup_names(backup_dir).await?;
+
+    // Clean up after backups that were interrupted
+    for name in names
+        .iter()
+        .filter(|name| name.ends_with(PARTIAL_EXTENSION))
+    {
+        tokio::fs::remove_file(backup_dir.join(name)).await?;
+    }
+    names.retain(|name| name.ends_with(BACKUP_EXTENSION));
+
+    let name = backup_name(OffsetDateTime::now_utc());
+    let partial = backup_dir.join(format!("{}{}", name, PARTIAL_EXTENSION));
+    let path = backup_dir.join(&name);
+
+    db.backup_into(&partial).await?;
+    tokio::fs::rename(&partial, &path).await?;
+    names.push(name);
+
+    for name in backups_to_remove(names, BACKUPS_TO_KEEP) {
+        tokio::fs::remove_file(backup_dir.join(name)).await?;
+    }
+
+    Ok(path)
+}
+
+/// The names of the files in the backup directory that are (partial) backups.
+async fn backup_names(backup_dir: &Path) -> Result<Vec<String>> {
+    let mut entries = tokio::fs::read_dir(backup_dir).await?;
+    let mut names = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(name) = entry.file_name().to_str() {
+            if name.starts_with(BACKUP_PREFIX) {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+async fn latest_backup_age(backup_dir: &Path) -> Option<Duration> {
+    let names = backup_names(backup_dir).await.ok()?;
+    let mut latest = None;
+
+    for name in names.iter().filter(|name| name.ends_with(BACKUP_EXTENSION)) {
+        let modified = tokio::fs::metadata(backup_dir.join(name))
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        latest = latest.max(modified);
+    }
+
+    SystemTime::now().duration_since(latest?).ok()
+}
+
+/// Backups are named after the time they were made at, so they sort by age.
+fn backup_name(made_at: OffsetDateTime) -> String {
+    format!(
+        "{}{:04}{:02}{:02}T{:02}{:02}{:02}{}",
+        BACKUP_PREFIX,
+        made_at.year(),
+        u8::from(made_at.month()),
+        made_at.day(),
+        made_at.hour(),
+        made_at.minute(),
+        made_at.second(),
+        BACKUP_EXTENSION
+    )
+}
+
+fn backups_to_remove(mut names: Vec<String>, keep: usize) -> Vec<String> {
+    names.sort();
+    names.dedup();
+
+    let remove = names.len().saturating_sub(keep);
+    names.truncate(remove);
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_names_sort_by_age() {
+        // 2025-09-09 23:59:59 and 2025-09-10 08:05:00 UTC
+        let older = backup_name(OffsetDateTime::from_unix_timestamp(1_757_462_399).unwrap());
+        let newer = backup_name(OffsetDateTime::from_unix_timestamp(1_757_491_500).unwrap());
+
+        assert_eq!(newer, "sqlite-20250910T080500.db");
+        assert!(older < newer);
+    }
+
+    #[test]
+    fn only_the_oldest_backups_are_removed() {
+        let names = ["sqlite-3.db", "sqlite-1.db", "sqlite-4.db", "sqlite-2.db"]
+            .map(String::from)
+            .to_vec();
+
+        assert_eq!(
+            backups_to_remove(names.clone(), 2),
+            vec!["sqlite-1.db", "sqlite-2.db"]
+        );
+        assert!(backups_to_remove(names, 7).is_empty());
+    }
+}
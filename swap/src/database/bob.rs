@@ -13,6 +13,12 @@ pub enum Bob {
         #[serde(with = "crate::bitcoin::address_serde")]
         change_address: bitcoin::Address,
         tx_lock_fee: bitcoin::Amount,
+        #[serde(default)]
+        quote_expires_at: Option<u64>,
+        #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+        min_btc_amount: Option<bitcoin::Amount>,
+        #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+        quote_price: Option<bitcoin::Amount>,
     },
     ExecutionSetupDone {
         state2: bob::State2,
@@ -59,10 +65,16 @@ impl From<BobState> for Bob {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                quote_expires_at,
+                min_btc_amount,
+                quote_price,
             } => Bob::Started {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                quote_expires_at,
+                min_btc_amount,
+                quote_price,
             },
             BobState::SwapSetupCompleted(state2) => Bob::ExecutionSetupDone { state2 },
             BobState::BtcLocked {
@@ -108,10 +120,16 @@ impl From<Bob> for BobState {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                quote_expires_at,
+                min_btc_amount,
+                quote_price,
             } => BobState::Started {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                quote_expires_at,
+                min_btc_amount,
+                quote_price,
             },
             Bob::ExecutionSetupDone { state2 } => BobState::SwapSetupCompleted(state2),
             Bob::BtcLocked {
@@ -0,0 +1,137 @@
+use crate::database::Swap;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+const NONCE_LENGTH: usize = 12;
+
+/// Encrypts the states of swaps, which contain the secret keys of the swap, before they are
+/// written to the database.
+///
+/// The key is derived from the seed. We only encrypt if the seed itself is protected by a
+/// password, otherwise the key would lie in plaintext right next to the database.
+#[derive(Clone)]
+pub struct StateEncryption(ChaCha20Poly1305);
+
+/// An encrypted state is stored as `{"encrypted":"<base64 of nonce and ciphertext>"}`. Being
+/// valid JSON, the `json_extract` calls of our data migrations skip it instead of failing.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EncryptedState {
+    encrypted: String,
+}
+
+impl StateEncryption {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(ChaCha20Poly1305::new(&key.into()))
+    }
+
+    pub(super) fn encrypt(&self, swap: &Swap) -> Result<String> {
+        let plaintext = serde_json::to_vec(swap)?;
+
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .0
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt swap state"))?;
+
+        let encrypted = EncryptedState {
+            encrypted: general_purpose::STANDARD.encode([&nonce[..], &ciphertext[..]].concat()),
+        };
+
+        Ok(serde_json::to_string(&encrypted)?)
+    }
+
+    fn decrypt(&self, encrypted: EncryptedState) -> Result<Swap> {
+        let bytes = general_purpose::STANDARD
+            .decode(encrypted.encrypted)
+            .context("Encrypted swap state is not valid base64")?;
+
+        if bytes.len() < NONCE_LENGTH {
+            bail!("Encrypted swap state is too short");
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                anyhow!("Failed to decrypt swap state, it was encrypted with another seed")
+            })?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+/// Turn a state into what we store in the database, encrypted if we have a key.
+pub(super) fn encode(swap: &Swap, encryption: Option<&StateEncryption>) -> Result<String> {
+    match encryption {
+        Some(encryption) => encryption.encrypt(swap),
+        None => Ok(serde_json::to_string(swap)?),
+    }
+}
+
+/// Whether the state as stored in the database is encrypted.
+pub(super) fn is_encrypted(stored: &str) -> bool {
+    serde_json::from_str::<EncryptedState>(stored).is_ok()
+}
+
+/// Read a state as stored in the database, which may or may not be encrypted. States written
+/// before the encryption was enabled stay readable.
+pub(super) fn decode(stored: &str, encryption: Option<&StateEncryption>) -> Result<Swap> {
+    match serde_json::from_str::<EncryptedState>(stored) {
+        Ok(encrypted) => match encryption {
+            Some(encryption) => encryption.decrypt(encrypted),
+            None => bail!("Swap state is encrypted, the password of the seed is needed to read it"),
+        },
+        Err(_) => Ok(serde_json::from_str(stored)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::bob::BobState;
+    use crate::protocol::State;
+
+    fn swap() -> Swap {
+        Swap::from(State::Bob(BobState::SafelyAborted))
+    }
+
+    #[test]
+    fn encrypted_state_round_trip() {
+        let encryption = StateEncryption::new([1u8; 32]);
+
+        let stored = encryption.encrypt(&swap()).unwrap();
+
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains("SafelyAborted"));
+        assert_eq!(decode(&stored, Some(&encryption)).unwrap(), swap());
+    }
+
+    #[test]
+    fn plaintext_states_stay_readable() {
+        let stored = serde_json::to_string(&swap()).unwrap();
+
+        assert!(!is_encrypted(&stored));
+        assert_eq!(decode(&stored, None).unwrap(), swap());
+        assert_eq!(
+            decode(&stored, Some(&StateEncryption::new([1u8; 32]))).unwrap(),
+            swap()
+        );
+    }
+
+    #[test]
+    fn encrypted_state_needs_the_right_key() {
+        let stored = StateEncryption::new([1u8; 32]).encrypt(&swap()).unwrap();
+
+        assert!(decode(&stored, None).is_err());
+        assert!(decode(&stored, Some(&StateEncryption::new([2u8; 32]))).is_err());
+    }
+}
@@ -1,12 +1,19 @@
+use crate::bitcoin::wallet::AddressLabel;
 use crate::cli::api::tauri_bindings::TauriEmitter;
 use crate::cli::api::tauri_bindings::TauriHandle;
-use crate::database::Swap;
+use crate::database::encryption::{self, StateEncryption};
+use crate::database::{Swap, SwapNotFound};
+use crate::monero;
 use crate::monero::LabeledMoneroAddress;
 use crate::monero::MoneroAddressPool;
 use crate::monero::TransferProof;
-use crate::protocol::{Database, State};
+use crate::protocol::{
+    Database, DatabaseCheck, PeerStats, RendezvousPointHealth, State, SwapOutcome,
+};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use bitcoin::{ScriptBuf, Transaction, Txid};
 use libp2p::{Multiaddr, PeerId};
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
@@ -14,6 +21,7 @@ use sqlx::sqlite::{Sqlite, SqliteConnectOptions};
 use sqlx::{ConnectOptions, Pool, SqlitePool};
 use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -22,6 +30,7 @@ use super::AccessMode;
 pub struct SqliteDatabase {
     pool: Pool<Sqlite>,
     tauri_handle: Option<TauriHandle>,
+    encryption: Option<StateEncryption>,
 }
 
 impl SqliteDatabase {
@@ -40,6 +49,7 @@ impl SqliteDatabase {
         let mut sqlite = Self {
             pool,
             tauri_handle: None,
+            encryption: None,
         };
 
         if !read_only {
@@ -54,6 +64,104 @@ impl SqliteDatabase {
         self
     }
 
+    /// Encrypt the states of swaps we write from now on.
+    pub fn with_state_encryption(mut self, encryption: impl Into<Option<StateEncryption>>) -> Self {
+        self.encryption = encryption.into();
+        self
+    }
+
+    /// Encrypt the states that were written before the encryption was enabled.
+    ///
+    /// SQLite keeps the content of overwritten rows in free pages of the file, which is why we
+    /// vacuum afterwards.
+    pub async fn encrypt_plaintext_states(&self) -> Result<()> {
+        let Some(encryption) = &self.encryption else {
+            return Ok(());
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, state
+            FROM swap_states
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transaction = self.pool.begin().await?;
+        let mut encrypted_states = 0;
+
+        for row in rows {
+            if encryption::is_encrypted(&row.state) {
+                continue;
+            }
+
+            let swap: Swap = serde_json::from_str(&row.state)?;
+            let state = encryption.encrypt(&swap)?;
+
+            sqlx::query!(
+                r#"
+                UPDATE swap_states
+                SET state = ?
+                WHERE id = ?
+                "#,
+                state,
+                row.id
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            encrypted_states += 1;
+        }
+
+        transaction.commit().await?;
+
+        if encrypted_states > 0 {
+            sqlx::query!(
+                r#"
+                VACUUM
+                "#
+            )
+            .execute(&self.pool)
+            .await?;
+
+            tracing::info!(
+                encrypted_states,
+                "Encrypted the swap states in the database"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Write a consistent copy of the database to `path`, which must not exist yet. The copy
+    /// is made with `VACUUM INTO`, so it doesn't contain the free pages of the database.
+    pub async fn backup_into(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path
+            .as_ref()
+            .to_str()
+            .context("Path of the backup is not valid UTF-8")?;
+
+        sqlx::query!(
+            r#"
+            VACUUM INTO ?
+            "#,
+            path
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn encode_state(&self, swap: &Swap) -> Result<String> {
+        encryption::encode(swap, self.encryption.as_ref())
+    }
+
+    fn decode_state(&self, stored: &str) -> Result<Swap> {
+        encryption::decode(stored, self.encryption.as_ref())
+    }
+
     async fn run_migrations(&mut self) -> anyhow::Result<()> {
         sqlx::migrate!("./migrations").run(&self.pool).await?;
         Ok(())
@@ -93,8 +201,9 @@ impl Database for SqliteDatabase {
         "#,
             swap_id
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(SwapNotFound)?;
 
         let peer_id = PeerId::from_str(&row.peer_id)?;
         Ok(peer_id)
@@ -287,7 +396,7 @@ impl Database for SqliteDatabase {
     async fn insert_latest_state(&self, swap_id: Uuid, state: State) -> Result<()> {
         let entered_at = OffsetDateTime::now_utc();
 
-        let swap = serde_json::to_string(&Swap::from(state))?;
+        let swap = self.encode_state(&Swap::from(state))?;
         let entered_at = entered_at.to_string();
         let swap_id_str = swap_id.to_string();
 
@@ -332,7 +441,7 @@ impl Database for SqliteDatabase {
         let row = row
             .first()
             .context(format!("No state in database for swap: {}", swap_id))?;
-        let swap: Swap = serde_json::from_str(&row.state)?;
+        let swap = self.decode_state(&row.state)?;
 
         Ok(swap.into())
     }
@@ -366,7 +475,7 @@ impl Database for SqliteDatabase {
                         return None;
                     }
                 };
-                let state = match serde_json::from_str::<Swap>(state) {
+                let state = match self.decode_state(state) {
                     Ok(a) => State::from(a),
                     Err(e) => {
                         tracing::error!(%swap_id, error = ?e, "Failed to deserialize state");
@@ -402,7 +511,7 @@ impl Database for SqliteDatabase {
             .map(|row| {
                 let state_str: &str = &row.state;
 
-                let state = match serde_json::from_str::<Swap>(state_str) {
+                let state = match self.decode_state(state_str) {
                     Ok(a) => Ok(State::from(a)),
                     Err(e) => Err(e),
                 }?;
@@ -413,6 +522,29 @@ impl Database for SqliteDatabase {
         result
     }
 
+    async fn get_states_with_timestamps(&self, swap_id: Uuid) -> Result<Vec<(State, String)>> {
+        let swap_id = swap_id.to_string();
+
+        let rows = sqlx::query!(
+            r#"
+           SELECT state, entered_at
+           FROM swap_states
+           WHERE swap_id = ?
+           ORDER BY id ASC
+        "#,
+            swap_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let state = State::from(self.decode_state(&row.state)?);
+                Ok((state, row.entered_at))
+            })
+            .collect()
+    }
+
     async fn insert_buffered_transfer_proof(
         &self,
         swap_id: Uuid,
@@ -460,141 +592,731 @@ impl Database for SqliteDatabase {
 
         Ok(Some(proof))
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::protocol::alice::AliceState;
-    use crate::protocol::bob::BobState;
-    use std::fs::File;
-    use tempfile::{tempdir, TempDir};
-
-    #[tokio::test]
-    async fn test_insert_and_load_state() {
-        let db = setup_test_db().await.unwrap();
-
-        let state_1 = State::Alice(AliceState::BtcRedeemed);
-        let swap_id_1 = Uuid::new_v4();
+    async fn insert_bitcoin_subscription(&self, txid: Txid, script: ScriptBuf) -> Result<()> {
+        let txid = txid.to_string();
+        let script = script.to_hex_string();
+        let entered_at = OffsetDateTime::now_utc().to_string();
 
-        db.insert_latest_state(swap_id_1, state_1).await.unwrap();
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO bitcoin_subscriptions (
+                txid,
+                script,
+                entered_at
+                ) VALUES (?, ?, ?);
+        "#,
+            txid,
+            script,
+            entered_at
+        )
+        .execute(&self.pool)
+        .await?;
 
-        let state_1 = State::Alice(AliceState::BtcRedeemed);
+        Ok(())
+    }
 
-        db.insert_latest_state(swap_id_1, state_1.clone())
-            .await
-            .unwrap();
+    async fn remove_bitcoin_subscription(&self, txid: Txid, script: ScriptBuf) -> Result<()> {
+        let txid = txid.to_string();
+        let script = script.to_hex_string();
 
-        let state_1_loaded = db.get_state(swap_id_1).await.unwrap();
+        sqlx::query!(
+            r#"
+            DELETE FROM bitcoin_subscriptions
+            WHERE txid = ? AND script = ?
+        "#,
+            txid,
+            script
+        )
+        .execute(&self.pool)
+        .await?;
 
-        assert_eq!(state_1, state_1_loaded);
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_retrieve_all_latest_states() {
-        let db = setup_test_db().await.unwrap();
-
-        let state_1 = State::Alice(AliceState::BtcRedeemed);
-        let state_2 = State::Alice(AliceState::SafelyAborted);
-        let state_3 = State::Bob(BobState::SafelyAborted);
-        let swap_id_1 = Uuid::new_v4();
-        let swap_id_2 = Uuid::new_v4();
+    async fn get_bitcoin_subscriptions(&self) -> Result<Vec<(Txid, ScriptBuf)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT txid, script
+            FROM bitcoin_subscriptions
+            ORDER BY entered_at ASC
+        "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-        db.insert_latest_state(swap_id_1, state_1.clone())
-            .await
-            .unwrap();
-        db.insert_latest_state(swap_id_1, state_2.clone())
-            .await
-            .unwrap();
-        db.insert_latest_state(swap_id_2, state_3.clone())
-            .await
-            .unwrap();
+        rows.into_iter()
+            .map(|row| {
+                let txid = Txid::from_str(&row.txid)?;
+                let script = ScriptBuf::from_hex(&row.script)?;
 
-        let latest_loaded = db.all().await.unwrap();
+                Ok((txid, script))
+            })
+            .collect()
+    }
 
-        assert_eq!(latest_loaded.len(), 2);
+    async fn insert_bitcoin_broadcast(&self, transaction: &Transaction, kind: &str) -> Result<()> {
+        let txid = transaction.compute_txid().to_string();
+        let tx = serialize_hex(transaction);
+        let entered_at = OffsetDateTime::now_utc().unix_timestamp();
 
-        assert!(latest_loaded.contains(&(swap_id_1, state_2)));
-        assert!(latest_loaded.contains(&(swap_id_2, state_3)));
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO bitcoin_broadcasts (
+                txid,
+                kind,
+                tx,
+                entered_at
+                ) VALUES (?, ?, ?, ?);
+        "#,
+            txid,
+            kind,
+            tx,
+            entered_at
+        )
+        .execute(&self.pool)
+        .await?;
 
-        assert!(!latest_loaded.contains(&(swap_id_1, state_1)));
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_insert_and_load_monero_address_pool() -> Result<()> {
-        use crate::monero::{LabeledMoneroAddress, MoneroAddressPool};
-        use rust_decimal::Decimal;
-
-        let db = setup_test_db().await?;
+    async fn remove_bitcoin_broadcast(&self, txid: Txid) -> Result<()> {
+        let txid = txid.to_string();
 
-        let swap_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            DELETE FROM bitcoin_broadcasts
+            WHERE txid = ?
+        "#,
+            txid
+        )
+        .execute(&self.pool)
+        .await?;
 
-        // Create multiple labeled addresses with valid percentages that sum to 1
-        let address1 = "53gEuGZUhP9JMEBZoGaFNzhwEgiG7hwQdMCqFxiyiTeFPmkbt1mAoNybEUvYBKHcnrSgxnVWgZsTvRBaHBNXPa8tHiCU51a".parse()?; // Stagenet address
-        let address2 = "44Ato7HveWidJYUAVw5QffEcEtSH1DwzSP3FPPkHxNAS4LX9CqgucphTisH978FLHE34YNEx7FcbBfQLQUU8m3NUC4VqsRa".parse()?; // Mainnet address
-        let address3 = "53gEuGZUhP9JMEBZoGaFNzhwEgiG7hwQdMCqFxiyiTeFPmkbt1mAoNybEUvYBKHcnrSgxnVWgZsTvRBaHBNXPa8tHiCU51a".parse()?; // Same as address1 for simplicity
+        Ok(())
+    }
 
-        let labeled_addresses = vec![
-            LabeledMoneroAddress::new(address1, Decimal::new(5, 1), "Primary".to_string())
-                .map_err(|e| anyhow!(e))?, // 0.5
-            LabeledMoneroAddress::new(address2, Decimal::new(3, 1), "Secondary".to_string())
-                .map_err(|e| anyhow!(e))?, // 0.3
-            LabeledMoneroAddress::new(address3, Decimal::new(2, 1), "Tertiary".to_string())
-                .map_err(|e| anyhow!(e))?, // 0.2
-        ];
+    async fn record_maker_reachability(&self, peer_id: PeerId, online: bool) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let online_count = i64::from(online);
+        let unreachable_count = i64::from(!online);
+        let last_online_at = online.then(|| OffsetDateTime::now_utc().to_string());
 
-        let address_pool = MoneroAddressPool::new(labeled_addresses);
+        sqlx::query!(
+            r#"
+            INSERT INTO maker_reachability (
+                peer_id,
+                online_count,
+                unreachable_count,
+                last_online_at
+                ) VALUES (?, ?, ?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET
+                online_count = online_count + excluded.online_count,
+                unreachable_count = unreachable_count + excluded.unreachable_count,
+                last_online_at = COALESCE(excluded.last_online_at, last_online_at);
+        "#,
+            peer_id,
+            online_count,
+            unreachable_count,
+            last_online_at
+        )
+        .execute(&self.pool)
+        .await?;
 
-        // Insert the address pool
-        db.insert_monero_address_pool(swap_id, address_pool.clone())
+        if let Some(last_seen_at) = last_online_at {
+            sqlx::query!(
+                r#"
+                INSERT INTO peer_stats (peer_id, last_seen_at) VALUES (?, ?)
+                ON CONFLICT(peer_id) DO UPDATE SET last_seen_at = excluded.last_seen_at;
+            "#,
+                peer_id,
+                last_seen_at
+            )
+            .execute(&self.pool)
             .await?;
+        }
 
-        // Load the address pool back
-        let loaded_address_pool = db.get_monero_address_pool(swap_id).await?;
+        Ok(())
+    }
 
-        // Verify they are equal
-        assert_eq!(address_pool.addresses(), loaded_address_pool.addresses());
-        assert_eq!(
-            address_pool.percentages(),
-            loaded_address_pool.percentages()
-        );
+    async fn get_maker_reachability(&self, peer_id: PeerId) -> Result<(u64, u64)> {
+        let peer_id = peer_id.to_string();
 
-        // Verify each labeled address individually
-        let original_addresses: Vec<_> = address_pool.iter().collect();
-        let loaded_addresses: Vec<_> = loaded_address_pool.iter().collect();
+        let row = sqlx::query!(
+            r#"
+            SELECT online_count, unreachable_count
+            FROM maker_reachability
+            WHERE peer_id = ?
+        "#,
+            peer_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
-        assert_eq!(original_addresses.len(), loaded_addresses.len());
+        Ok(row
+            .map(|row| {
+                (
+                    row.online_count.try_into().unwrap_or_default(),
+                    row.unreachable_count.try_into().unwrap_or_default(),
+                )
+            })
+            .unwrap_or_default())
+    }
 
-        for (orig, loaded) in original_addresses.iter().zip(loaded_addresses.iter()) {
-            assert_eq!(orig.address(), loaded.address());
-            assert_eq!(orig.percentage(), loaded.percentage());
-            assert_eq!(orig.label(), loaded.label());
-        }
+    async fn record_swap_outcome(&self, peer_id: PeerId, outcome: SwapOutcome) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let completed_count = i64::from(outcome == SwapOutcome::Completed);
+        let aborted_count = i64::from(outcome == SwapOutcome::Aborted);
+        let punished_count = i64::from(outcome == SwapOutcome::Punished);
+        let last_seen_at = OffsetDateTime::now_utc().to_string();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_stats (
+                peer_id,
+                completed_count,
+                aborted_count,
+                punished_count,
+                last_seen_at
+                ) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET
+                completed_count = completed_count + excluded.completed_count,
+                aborted_count = aborted_count + excluded.aborted_count,
+                punished_count = punished_count + excluded.punished_count,
+                last_seen_at = excluded.last_seen_at;
+        "#,
+            peer_id,
+            completed_count,
+            aborted_count,
+            punished_count,
+            last_seen_at
+        )
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_insert_and_load_multiaddr() -> Result<()> {
-        let db = setup_test_db().await?;
+    async fn record_lock_time(&self, peer_id: PeerId, lock_time: Duration) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let lock_time_secs = lock_time.as_secs_f64();
+        let last_seen_at = OffsetDateTime::now_utc().to_string();
 
-        let peer_id = PeerId::random();
-        let multiaddr1 = "/ip4/127.0.0.1".parse::<Multiaddr>()?;
-        let multiaddr2 = "/ip4/127.0.0.2".parse::<Multiaddr>()?;
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_stats (
+                peer_id,
+                avg_lock_time_secs,
+                lock_time_samples,
+                last_seen_at
+                ) VALUES (?, ?, 1, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET
+                avg_lock_time_secs = (COALESCE(avg_lock_time_secs, 0) * lock_time_samples + excluded.avg_lock_time_secs) / (lock_time_samples + 1),
+                lock_time_samples = lock_time_samples + 1,
+                last_seen_at = excluded.last_seen_at;
+        "#,
+            peer_id,
+            lock_time_secs,
+            last_seen_at
+        )
+        .execute(&self.pool)
+        .await?;
 
-        db.insert_address(peer_id, multiaddr1.clone()).await?;
-        db.insert_address(peer_id, multiaddr2.clone()).await?;
+        Ok(())
+    }
 
-        let loaded_multiaddr = db.get_addresses(peer_id).await?;
+    async fn get_peer_stats(&self, peer_id: PeerId) -> Result<PeerStats> {
+        let peer_id_str = peer_id.to_string();
 
-        assert!(loaded_multiaddr.contains(&multiaddr1));
-        assert!(loaded_multiaddr.contains(&multiaddr2));
-        assert_eq!(loaded_multiaddr.len(), 2);
+        let row = sqlx::query!(
+            r#"
+            SELECT completed_count, aborted_count, punished_count, avg_lock_time_secs, last_seen_at
+            FROM peer_stats
+            WHERE peer_id = ?
+        "#,
+            peer_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
-        Ok(())
+        Ok(match row {
+            Some(row) => PeerStats {
+                peer_id,
+                completed_count: row.completed_count.try_into().unwrap_or_default(),
+                aborted_count: row.aborted_count.try_into().unwrap_or_default(),
+                punished_count: row.punished_count.try_into().unwrap_or_default(),
+                avg_lock_time_secs: row.avg_lock_time_secs,
+                last_seen_at: row.last_seen_at,
+            },
+            None => PeerStats::new(peer_id),
+        })
     }
 
-    #[tokio::test]
+    async fn get_all_peer_stats(&self) -> Result<Vec<PeerStats>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT peer_id, completed_count, aborted_count, punished_count, avg_lock_time_secs, last_seen_at
+            FROM peer_stats
+        "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PeerStats {
+                    peer_id: PeerId::from_str(&row.peer_id)?,
+                    completed_count: row.completed_count.try_into().unwrap_or_default(),
+                    aborted_count: row.aborted_count.try_into().unwrap_or_default(),
+                    punished_count: row.punished_count.try_into().unwrap_or_default(),
+                    avg_lock_time_secs: row.avg_lock_time_secs,
+                    last_seen_at: row.last_seen_at,
+                })
+            })
+            .collect()
+    }
+
+    async fn record_rendezvous_discovery(&self, peer_id: PeerId, success: bool) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let success_count = i64::from(success);
+        let failure_count = i64::from(!success);
+        let last_success_at = success.then(|| OffsetDateTime::now_utc().to_string());
+
+        sqlx::query!(
+            r#"
+            INSERT INTO rendezvous_point_health (
+                peer_id,
+                success_count,
+                failure_count,
+                consecutive_failures,
+                last_success_at
+                ) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET
+                success_count = success_count + excluded.success_count,
+                failure_count = failure_count + excluded.failure_count,
+                consecutive_failures = CASE
+                    WHEN excluded.success_count > 0 THEN 0
+                    ELSE consecutive_failures + 1
+                END,
+                last_success_at = COALESCE(excluded.last_success_at, last_success_at);
+        "#,
+            peer_id,
+            success_count,
+            failure_count,
+            failure_count,
+            last_success_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_rendezvous_point_health(&self, peer_id: PeerId) -> Result<RendezvousPointHealth> {
+        let peer_id_str = peer_id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+            SELECT success_count, failure_count, consecutive_failures, last_success_at
+            FROM rendezvous_point_health
+            WHERE peer_id = ?
+        "#,
+            peer_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            Some(row) => RendezvousPointHealth {
+                peer_id,
+                success_count: row.success_count.try_into().unwrap_or_default(),
+                failure_count: row.failure_count.try_into().unwrap_or_default(),
+                consecutive_failures: row.consecutive_failures.try_into().unwrap_or_default(),
+                last_success_at: row.last_success_at,
+            },
+            None => RendezvousPointHealth::new(peer_id),
+        })
+    }
+
+    async fn record_swap_volume(
+        &self,
+        swap_id: Uuid,
+        peer_id: PeerId,
+        btc_amount: bitcoin::Amount,
+    ) -> Result<()> {
+        let swap_id = swap_id.to_string();
+        let peer_id = peer_id.to_string();
+        let btc_amount_sat =
+            i64::try_from(btc_amount.to_sat()).context("Bitcoin amount does not fit into i64")?;
+        let locked_at = OffsetDateTime::now_utc().unix_timestamp();
+
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO swap_volumes (
+                swap_id,
+                peer_id,
+                btc_amount_sat,
+                locked_at
+                ) VALUES (?, ?, ?, ?);
+        "#,
+            swap_id,
+            peer_id,
+            btc_amount_sat,
+            locked_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_swap_volume(&self, swap_id: Uuid) -> Result<()> {
+        let swap_id = swap_id.to_string();
+
+        sqlx::query!(
+            r#"
+            DELETE FROM swap_volumes WHERE swap_id = ?
+        "#,
+            swap_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_swap_volume_since(
+        &self,
+        peer_id: Option<PeerId>,
+        since: OffsetDateTime,
+    ) -> Result<bitcoin::Amount> {
+        let peer_id = peer_id.map(|peer_id| peer_id.to_string());
+        let since = since.unix_timestamp();
+
+        let row = sqlx::query!(
+            r#"
+            SELECT COALESCE(SUM(btc_amount_sat), 0) AS "volume_sat!: i64"
+            FROM swap_volumes
+            WHERE locked_at >= ? AND (? IS NULL OR peer_id = ?)
+        "#,
+            since,
+            peer_id,
+            peer_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(bitcoin::Amount::from_sat(
+            row.volume_sat.try_into().unwrap_or_default(),
+        ))
+    }
+
+    async fn insert_xmr_redeem_fee(&self, swap_id: Uuid, fee: monero::Amount) -> Result<()> {
+        let swap_id = swap_id.to_string();
+        let fee_piconero =
+            i64::try_from(fee.as_piconero()).context("Monero amount does not fit into i64")?;
+
+        sqlx::query!(
+            r#"
+            INSERT OR REPLACE INTO xmr_redeem_fees (
+                swap_id,
+                fee_piconero
+                ) VALUES (?, ?);
+        "#,
+            swap_id,
+            fee_piconero
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_xmr_redeem_fee(&self, swap_id: Uuid) -> Result<Option<monero::Amount>> {
+        let swap_id = swap_id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+            SELECT fee_piconero
+            FROM xmr_redeem_fees
+            WHERE swap_id = ?
+        "#,
+            swap_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            let fee_piconero =
+                u64::try_from(row.fee_piconero).context("Stored Monero fee is negative")?;
+            Ok(monero::Amount::from_piconero(fee_piconero))
+        })
+        .transpose()
+    }
+
+    async fn get_bitcoin_broadcasts(&self) -> Result<Vec<(Transaction, String, OffsetDateTime)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT kind, tx, entered_at
+            FROM bitcoin_broadcasts
+            ORDER BY entered_at ASC
+        "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let transaction = deserialize_hex(&row.tx)?;
+                let entered_at = OffsetDateTime::from_unix_timestamp(row.entered_at)?;
+
+                Ok((transaction, row.kind, entered_at))
+            })
+            .collect()
+    }
+
+    async fn insert_bitcoin_address_label(
+        &self,
+        address: &bitcoin::Address,
+        label: &str,
+        swap_id: Option<Uuid>,
+    ) -> Result<()> {
+        let address = address.to_string();
+        let swap_id = swap_id.map(|swap_id| swap_id.to_string());
+        let created_at = OffsetDateTime::now_utc().unix_timestamp();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO bitcoin_address_labels (
+                address,
+                label,
+                swap_id,
+                created_at
+                ) VALUES (?, ?, ?, ?)
+            ON CONFLICT(address) DO UPDATE SET
+                label = excluded.label,
+                swap_id = excluded.swap_id;
+        "#,
+            address,
+            label,
+            swap_id,
+            created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_bitcoin_address_labels(&self) -> Result<Vec<AddressLabel>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT address, label, swap_id
+            FROM bitcoin_address_labels
+            ORDER BY created_at ASC, rowid ASC
+        "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let swap_id = row
+                    .swap_id
+                    .map(|swap_id| Uuid::from_str(&swap_id))
+                    .transpose()
+                    .context("Invalid swap id in address labels")?;
+
+                Ok(AddressLabel {
+                    address: row.address,
+                    label: row.label,
+                    swap_id,
+                })
+            })
+            .collect()
+    }
+
+    async fn check_integrity(&self) -> Result<DatabaseCheck> {
+        let integrity_errors = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .filter(|result| result != "ok")
+            .collect();
+
+        let orphaned_swaps = sqlx::query!(
+            r#"
+            SELECT DISTINCT swap_id
+            FROM swap_states
+            WHERE swap_id NOT IN (SELECT swap_id FROM peers)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| Uuid::from_str(&row.swap_id))
+        .collect::<Result<_, _>>()?;
+
+        let latest_states = sqlx::query!(
+            r#"
+            SELECT swap_id, state
+            FROM swap_states
+            WHERE id IN (
+                SELECT max(id)
+                FROM swap_states
+                GROUP BY swap_id
+            )
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let unreadable_swaps = latest_states
+            .iter()
+            .filter(|row| self.decode_state(&row.state).is_err())
+            .map(|row| Uuid::from_str(&row.swap_id))
+            .collect::<Result<_, _>>()?;
+
+        Ok(DatabaseCheck {
+            integrity_errors,
+            orphaned_swaps,
+            unreadable_swaps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::alice::AliceState;
+    use crate::protocol::bob::BobState;
+    use std::fs::File;
+    use tempfile::{tempdir, TempDir};
+
+    #[tokio::test]
+    async fn test_insert_and_load_state() {
+        let db = setup_test_db().await.unwrap();
+
+        let state_1 = State::Alice(AliceState::BtcRedeemed);
+        let swap_id_1 = Uuid::new_v4();
+
+        db.insert_latest_state(swap_id_1, state_1).await.unwrap();
+
+        let state_1 = State::Alice(AliceState::BtcRedeemed);
+
+        db.insert_latest_state(swap_id_1, state_1.clone())
+            .await
+            .unwrap();
+
+        let state_1_loaded = db.get_state(swap_id_1).await.unwrap();
+
+        assert_eq!(state_1, state_1_loaded);
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_all_latest_states() {
+        let db = setup_test_db().await.unwrap();
+
+        let state_1 = State::Alice(AliceState::BtcRedeemed);
+        let state_2 = State::Alice(AliceState::SafelyAborted);
+        let state_3 = State::Bob(BobState::SafelyAborted);
+        let swap_id_1 = Uuid::new_v4();
+        let swap_id_2 = Uuid::new_v4();
+
+        db.insert_latest_state(swap_id_1, state_1.clone())
+            .await
+            .unwrap();
+        db.insert_latest_state(swap_id_1, state_2.clone())
+            .await
+            .unwrap();
+        db.insert_latest_state(swap_id_2, state_3.clone())
+            .await
+            .unwrap();
+
+        let latest_loaded = db.all().await.unwrap();
+
+        assert_eq!(latest_loaded.len(), 2);
+
+        assert!(latest_loaded.contains(&(swap_id_1, state_2)));
+        assert!(latest_loaded.contains(&(swap_id_2, state_3)));
+
+        assert!(!latest_loaded.contains(&(swap_id_1, state_1)));
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_load_monero_address_pool() -> Result<()> {
+        use crate::monero::{LabeledMoneroAddress, MoneroAddressPool};
+        use rust_decimal::Decimal;
+
+        let db = setup_test_db().await?;
+
+        let swap_id = Uuid::new_v4();
+
+        // Create multiple labeled addresses with valid percentages that sum to 1
+        let address1 = "53gEuGZUhP9JMEBZoGaFNzhwEgiG7hwQdMCqFxiyiTeFPmkbt1mAoNybEUvYBKHcnrSgxnVWgZsTvRBaHBNXPa8tHiCU51a".parse()?; // Stagenet address
+        let address2 = "44Ato7HveWidJYUAVw5QffEcEtSH1DwzSP3FPPkHxNAS4LX9CqgucphTisH978FLHE34YNEx7FcbBfQLQUU8m3NUC4VqsRa".parse()?; // Mainnet address
+        let address3 = "53gEuGZUhP9JMEBZoGaFNzhwEgiG7hwQdMCqFxiyiTeFPmkbt1mAoNybEUvYBKHcnrSgxnVWgZsTvRBaHBNXPa8tHiCU51a".parse()?; // Same as address1 for simplicity
+
+        let labeled_addresses = vec![
+            LabeledMoneroAddress::new(address1, Decimal::new(5, 1), "Primary".to_string())
+                .map_err(|e| anyhow!(e))?, // 0.5
+            LabeledMoneroAddress::new(address2, Decimal::new(3, 1), "Secondary".to_string())
+                .map_err(|e| anyhow!(e))?, // 0.3
+            LabeledMoneroAddress::new(address3, Decimal::new(2, 1), "Tertiary".to_string())
+                .map_err(|e| anyhow!(e))?, // 0.2
+        ];
+
+        let address_pool = MoneroAddressPool::new(labeled_addresses);
+
+        // Insert the address pool
+        db.insert_monero_address_pool(swap_id, address_pool.clone())
+            .await?;
+
+        // Load the address pool back
+        let loaded_address_pool = db.get_monero_address_pool(swap_id).await?;
+
+        // Verify they are equal
+        assert_eq!(address_pool.addresses(), loaded_address_pool.addresses());
+        assert_eq!(
+            address_pool.percentages(),
+            loaded_address_pool.percentages()
+        );
+
+        // Verify each labeled address individually
+        let original_addresses: Vec<_> = address_pool.iter().collect();
+        let loaded_addresses: Vec<_> = loaded_address_pool.iter().collect();
+
+        assert_eq!(original_addresses.len(), loaded_addresses.len());
+
+        for (orig, loaded) in original_addresses.iter().zip(loaded_addresses.iter()) {
+            assert_eq!(orig.address(), loaded.address());
+            assert_eq!(orig.percentage(), loaded.percentage());
+            assert_eq!(orig.label(), loaded.label());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_load_multiaddr() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let peer_id = PeerId::random();
+        let multiaddr1 = "/ip4/127.0.0.1".parse::<Multiaddr>()?;
+        let multiaddr2 = "/ip4/127.0.0.2".parse::<Multiaddr>()?;
+
+        db.insert_address(peer_id, multiaddr1.clone()).await?;
+        db.insert_address(peer_id, multiaddr2.clone()).await?;
+
+        let loaded_multiaddr = db.get_addresses(peer_id).await?;
+
+        assert!(loaded_multiaddr.contains(&multiaddr1));
+        assert!(loaded_multiaddr.contains(&multiaddr2));
+        assert_eq!(loaded_multiaddr.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
     async fn test_insert_and_load_peer_id() -> Result<()> {
         let db = setup_test_db().await?;
 
@@ -614,6 +1336,354 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_unknown_swap_has_no_peer_id() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let error = db.get_peer_id(Uuid::new_v4()).await.unwrap_err();
+
+        assert!(error.is::<SwapNotFound>());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_remove_bitcoin_subscriptions() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let txid =
+            Txid::from_str("0e3e2357e806b6cdb1f70b54c3a3a17b6714ee1f0e68bebb44a74b1efd512098")?;
+        let script = ScriptBuf::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6")?;
+        let other_script = ScriptBuf::from_hex("00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1")?;
+
+        db.insert_bitcoin_subscription(txid, script.clone()).await?;
+        db.insert_bitcoin_subscription(txid, other_script.clone())
+            .await?;
+        // Subscribing twice to the same output is a no-op
+        db.insert_bitcoin_subscription(txid, script.clone()).await?;
+
+        let subscriptions = db.get_bitcoin_subscriptions().await?;
+        assert_eq!(subscriptions.len(), 2);
+        assert!(subscriptions.contains(&(txid, script.clone())));
+
+        db.remove_bitcoin_subscription(txid, script).await?;
+
+        assert_eq!(
+            db.get_bitcoin_subscriptions().await?,
+            vec![(txid, other_script)]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_remove_bitcoin_broadcasts() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let transaction = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6")?,
+            }],
+        };
+
+        db.insert_bitcoin_broadcast(&transaction, "cancel").await?;
+        // Queueing the same transaction twice is a no-op
+        db.insert_bitcoin_broadcast(&transaction, "cancel").await?;
+
+        let broadcasts = db.get_bitcoin_broadcasts().await?;
+        assert_eq!(broadcasts.len(), 1);
+        assert_eq!(broadcasts[0].0, transaction);
+        assert_eq!(broadcasts[0].1, "cancel");
+
+        db.remove_bitcoin_broadcast(transaction.compute_txid())
+            .await?;
+
+        assert!(db.get_bitcoin_broadcasts().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_maker_reachability() -> Result<()> {
+        let db = setup_test_db().await?;
+        let peer_id = PeerId::random();
+
+        assert_eq!(db.get_maker_reachability(peer_id).await?, (0, 0));
+
+        db.record_maker_reachability(peer_id, true).await?;
+        db.record_maker_reachability(peer_id, true).await?;
+        db.record_maker_reachability(peer_id, false).await?;
+
+        assert_eq!(db.get_maker_reachability(peer_id).await?, (2, 1));
+        assert_eq!(db.get_maker_reachability(PeerId::random()).await?, (0, 0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_states_with_timestamps() -> Result<()> {
+        let db = setup_test_db().await?;
+        let swap_id = Uuid::new_v4();
+
+        let started = State::Alice(AliceState::SafelyAborted);
+        let redeemed = State::Alice(AliceState::BtcRedeemed);
+        db.insert_latest_state(swap_id, started.clone()).await?;
+        db.insert_latest_state(swap_id, redeemed.clone()).await?;
+
+        let states = db.get_states_with_timestamps(swap_id).await?;
+        let (states, timestamps): (Vec<_>, Vec<_>) = states.into_iter().unzip();
+        assert_eq!(states, vec![started, redeemed]);
+        assert!(timestamps[0] <= timestamps[1]);
+        assert!(db
+            .get_states_with_timestamps(Uuid::new_v4())
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_peer_stats() -> Result<()> {
+        let db = setup_test_db().await?;
+        let peer_id = PeerId::random();
+
+        assert_eq!(db.get_peer_stats(peer_id).await?, PeerStats::new(peer_id));
+
+        db.record_swap_outcome(peer_id, SwapOutcome::Completed)
+            .await?;
+        db.record_swap_outcome(peer_id, SwapOutcome::Completed)
+            .await?;
+        db.record_swap_outcome(peer_id, SwapOutcome::Aborted)
+            .await?;
+        db.record_lock_time(peer_id, Duration::from_secs(600))
+            .await?;
+        db.record_lock_time(peer_id, Duration::from_secs(1200))
+            .await?;
+        db.record_maker_reachability(PeerId::random(), true).await?;
+
+        let stats = db.get_peer_stats(peer_id).await?;
+        assert_eq!(stats.completed_count, 2);
+        assert_eq!(stats.aborted_count, 1);
+        assert_eq!(stats.punished_count, 0);
+        assert_eq!(stats.avg_lock_time_secs, Some(900.0));
+        assert!(stats.last_seen_at.is_some());
+        assert!(!stats.is_flaky());
+
+        assert_eq!(db.get_all_peer_stats().await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_swap_volume_per_peer_and_in_total() -> Result<()> {
+        let db = setup_test_db().await?;
+        let peer_a = PeerId::random();
+        let peer_b = PeerId::random();
+        let swap_a = Uuid::new_v4();
+        let an_hour_ago = OffsetDateTime::now_utc() - time::Duration::HOUR;
+
+        db.record_swap_volume(swap_a, peer_a, bitcoin::Amount::from_sat(100_000))
+            .await?;
+        // Recording a swap twice must not count its volume twice
+        db.record_swap_volume(swap_a, peer_a, bitcoin::Amount::from_sat(100_000))
+            .await?;
+        db.record_swap_volume(Uuid::new_v4(), peer_b, bitcoin::Amount::from_sat(50_000))
+            .await?;
+
+        assert_eq!(
+            db.get_swap_volume_since(Some(peer_a), an_hour_ago).await?,
+            bitcoin::Amount::from_sat(100_000)
+        );
+        assert_eq!(
+            db.get_swap_volume_since(None, an_hour_ago).await?,
+            bitcoin::Amount::from_sat(150_000)
+        );
+        assert_eq!(
+            db.get_swap_volume_since(Some(PeerId::random()), an_hour_ago)
+                .await?,
+            bitcoin::Amount::ZERO
+        );
+        assert_eq!(
+            db.get_swap_volume_since(None, OffsetDateTime::now_utc() + time::Duration::HOUR)
+                .await?,
+            bitcoin::Amount::ZERO
+        );
+
+        db.remove_swap_volume(swap_a).await?;
+        assert_eq!(
+            db.get_swap_volume_since(Some(peer_a), an_hour_ago).await?,
+            bitcoin::Amount::ZERO
+        );
+        assert_eq!(
+            db.get_swap_volume_since(None, an_hour_ago).await?,
+            bitcoin::Amount::from_sat(50_000)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_xmr_redeem_fee() -> Result<()> {
+        let db = setup_test_db().await?;
+        let swap_id = Uuid::new_v4();
+
+        assert_eq!(db.get_xmr_redeem_fee(swap_id).await?, None);
+
+        db.insert_xmr_redeem_fee(swap_id, monero::Amount::from_piconero(30_000_000))
+            .await?;
+
+        assert_eq!(
+            db.get_xmr_redeem_fee(swap_id).await?,
+            Some(monero::Amount::from_piconero(30_000_000))
+        );
+        assert_eq!(db.get_xmr_redeem_fee(Uuid::new_v4()).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bitcoin_address_labels() -> Result<()> {
+        let db = setup_test_db().await?;
+        let swap_id = Uuid::new_v4();
+        let address = bitcoin::Address::from_str("bcrt1q08pfqpsyrt7acllzyjm8q5qsz5capvyahm49rw")?
+            .assume_checked();
+
+        assert!(db.get_bitcoin_address_labels().await?.is_empty());
+
+        db.insert_bitcoin_address_label(&address, "Swap deposit", Some(swap_id))
+            .await?;
+        db.insert_bitcoin_address_label(&address, "Swap refund", None)
+            .await?;
+
+        assert_eq!(
+            db.get_bitcoin_address_labels().await?,
+            vec![AddressLabel {
+                address: address.to_string(),
+                label: "Swap refund".to_string(),
+                swap_id: None,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rendezvous_point_health() -> Result<()> {
+        let db = setup_test_db().await?;
+        let peer_id = PeerId::random();
+
+        assert_eq!(
+            db.get_rendezvous_point_health(peer_id).await?,
+            RendezvousPointHealth::new(peer_id)
+        );
+
+        db.record_rendezvous_discovery(peer_id, false).await?;
+        db.record_rendezvous_discovery(peer_id, false).await?;
+        db.record_rendezvous_discovery(peer_id, false).await?;
+
+        let health = db.get_rendezvous_point_health(peer_id).await?;
+        assert_eq!(health.failure_count, 3);
+        assert_eq!(health.consecutive_failures, 3);
+        assert!(!health.is_healthy());
+
+        db.record_rendezvous_discovery(peer_id, true).await?;
+
+        let health = db.get_rendezvous_point_health(peer_id).await?;
+        assert_eq!(health.success_count, 1);
+        assert_eq!(health.failure_count, 3);
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_success_at.is_some());
+        assert!(health.is_healthy());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_plaintext_states() -> Result<()> {
+        let db = setup_test_db().await?;
+        let swap_id = Uuid::new_v4();
+        let started = State::Bob(BobState::SafelyAborted);
+        let redeemed = State::Alice(AliceState::BtcRedeemed);
+
+        db.insert_latest_state(swap_id, started.clone()).await?;
+
+        let db = db.with_state_encryption(StateEncryption::new([7u8; 32]));
+        db.encrypt_plaintext_states().await?;
+        db.insert_latest_state(swap_id, redeemed.clone()).await?;
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, state
+            FROM swap_states
+            "#
+        )
+        .fetch_all(&db.pool)
+        .await?;
+        assert!(rows.iter().all(|row| encryption::is_encrypted(&row.state)));
+
+        assert_eq!(
+            db.get_states(swap_id).await?,
+            vec![started, redeemed.clone()]
+        );
+        assert_eq!(db.get_state(swap_id).await?, redeemed);
+
+        let db = db.with_state_encryption(None);
+        assert!(db.get_state(swap_id).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backup_into() -> Result<()> {
+        let db = setup_test_db().await?;
+        let swap_id = Uuid::new_v4();
+        let state = State::Alice(AliceState::BtcRedeemed);
+
+        db.insert_latest_state(swap_id, state.clone()).await?;
+
+        let backup = tempdir()?.keep().join("backup");
+        db.backup_into(&backup).await?;
+
+        let restored = SqliteDatabase::open(&backup, AccessMode::ReadOnly).await?;
+        assert_eq!(restored.get_state(swap_id).await?, state);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_integrity() -> Result<()> {
+        let db = setup_test_db().await?;
+        let complete = Uuid::new_v4();
+        let orphaned = Uuid::new_v4();
+
+        assert!(db.check_integrity().await?.is_healthy());
+
+        db.insert_peer_id(complete, PeerId::random()).await?;
+        db.insert_latest_state(complete, State::Bob(BobState::SafelyAborted))
+            .await?;
+        db.insert_latest_state(orphaned, State::Bob(BobState::SafelyAborted))
+            .await?;
+
+        let check = db.check_integrity().await?;
+        assert!(check.integrity_errors.is_empty());
+        assert_eq!(check.orphaned_swaps, vec![orphaned]);
+        assert!(check.unreadable_swaps.is_empty());
+
+        // The latest state was encrypted with a key we don't have
+        let db = db.with_state_encryption(StateEncryption::new([7u8; 32]));
+        db.insert_latest_state(complete, State::Bob(BobState::SafelyAborted))
+            .await?;
+        let db = db.with_state_encryption(None);
+
+        assert_eq!(db.check_integrity().await?.unreadable_swaps, vec![complete]);
+
+        Ok(())
+    }
+
     async fn setup_test_db() -> Result<SqliteDatabase> {
         let dir: TempDir = tempdir().unwrap();
         let temp_db = dir.path().join("tempdb");
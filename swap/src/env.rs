@@ -1,10 +1,14 @@
 use crate::asb;
 use crate::bitcoin::{CancelTimelock, PunishTimelock};
+use anyhow::{bail, Result};
 use serde::Serialize;
 use std::cmp::max;
 use std::time::Duration;
 use time::ext::NumericalStdDuration;
 
+/// Monero only lets an output be spent once it has this many confirmations.
+pub const MONERO_SPENDABLE_CONFIRMATIONS: u64 = 10;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 pub struct Config {
     pub bitcoin_lock_mempool_timeout: Duration,
@@ -14,6 +18,8 @@ pub struct Config {
     pub bitcoin_cancel_timelock: CancelTimelock,
     pub bitcoin_punish_timelock: PunishTimelock,
     pub bitcoin_network: bitcoin::Network,
+    // If a redeem or punish transaction is still unconfirmed after this many blocks, Alice bumps its fee
+    pub bitcoin_fee_bump_after_blocks: Option<u32>,
     pub monero_avg_block_time: Duration,
     pub monero_finality_confirmations: u64,
     // If Alice does manage to lock her Monero within this timeout, she will initiate an early refund of the Bitcoin.
@@ -32,6 +38,64 @@ impl Config {
     pub fn monero_sync_interval(&self) -> Duration {
         sync_interval(self.monero_avg_block_time)
     }
+
+    /// Override the confirmation targets of the network, keeping the defaults for the ones
+    /// that are not set. Fails if the resulting targets could not be used to swap safely.
+    pub fn with_confirmations(self, confirmations: Confirmations) -> Result<Self> {
+        let config = Config {
+            bitcoin_finality_confirmations: confirmations
+                .bitcoin_finality
+                .unwrap_or(self.bitcoin_finality_confirmations),
+            monero_finality_confirmations: confirmations
+                .monero_finality
+                .unwrap_or(self.monero_finality_confirmations),
+            monero_double_spend_safe_confirmations: confirmations
+                .monero_double_spend_safe
+                .unwrap_or(self.monero_double_spend_safe_confirmations),
+            ..self
+        };
+
+        config.validate_confirmations()?;
+
+        Ok(config)
+    }
+
+    fn validate_confirmations(&self) -> Result<()> {
+        if self.bitcoin_finality_confirmations == 0 {
+            bail!("Bitcoin finality confirmations must be at least 1");
+        }
+        if self.bitcoin_finality_confirmations >= u32::from(self.bitcoin_cancel_timelock) {
+            bail!(
+                "Bitcoin finality confirmations ({}) must be below the cancel timelock ({})",
+                self.bitcoin_finality_confirmations,
+                self.bitcoin_cancel_timelock
+            );
+        }
+        if self.monero_double_spend_safe_confirmations == 0 {
+            bail!("Monero double spend safe confirmations must be at least 1");
+        }
+        if self.monero_double_spend_safe_confirmations > self.monero_finality_confirmations {
+            bail!(
+                "Monero double spend safe confirmations ({}) must not exceed the Monero finality confirmations ({})",
+                self.monero_double_spend_safe_confirmations,
+                self.monero_finality_confirmations
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Confirmation targets that replace the defaults of the network, `None` keeps the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Confirmations {
+    /// Confirmations after which we consider a Bitcoin transaction final
+    pub bitcoin_finality: Option<u32>,
+    /// Confirmations the Monero lock transaction needs before we consider it final
+    pub monero_finality: Option<u64>,
+    /// Confirmations after which we assume the Monero lock transaction can no longer be
+    /// double spent
+    pub monero_double_spend_safe: Option<u64>,
 }
 
 pub trait GetConfig {
@@ -57,6 +121,7 @@ impl GetConfig for Mainnet {
             bitcoin_cancel_timelock: CancelTimelock::new(72),
             bitcoin_punish_timelock: PunishTimelock::new(144),
             bitcoin_network: bitcoin::Network::Bitcoin,
+            bitcoin_fee_bump_after_blocks: Some(6),
             monero_avg_block_time: 2.std_minutes(),
             // If Alice cannot lock her Monero within this timeout,
             // she will initiate an early refund of Bobs Bitcoin
@@ -78,6 +143,7 @@ impl GetConfig for Testnet {
             bitcoin_cancel_timelock: CancelTimelock::new(12),
             bitcoin_punish_timelock: PunishTimelock::new(24),
             bitcoin_network: bitcoin::Network::Testnet,
+            bitcoin_fee_bump_after_blocks: Some(6),
             monero_avg_block_time: 2.std_minutes(),
             monero_lock_retry_timeout: 10.std_minutes(),
             monero_finality_confirmations: 10,
@@ -97,6 +163,7 @@ impl GetConfig for Regtest {
             bitcoin_cancel_timelock: CancelTimelock::new(100),
             bitcoin_punish_timelock: PunishTimelock::new(50),
             bitcoin_network: bitcoin::Network::Regtest,
+            bitcoin_fee_bump_after_blocks: None,
             monero_avg_block_time: 1.std_seconds(),
             monero_lock_retry_timeout: 1.std_minutes(),
             monero_finality_confirmations: 10,
@@ -110,31 +177,30 @@ fn sync_interval(avg_block_time: Duration) -> Duration {
     max(avg_block_time / 10, Duration::from_secs(1))
 }
 
-pub fn new(is_testnet: bool, asb_config: &asb::config::Config) -> Config {
+pub fn new(is_testnet: bool, asb_config: &asb::config::Config) -> Result<Config> {
     let env_config = if is_testnet {
         Testnet::get_config()
     } else {
         Mainnet::get_config()
     };
 
-    let env_config =
-        if let Some(bitcoin_finality_confirmations) = asb_config.bitcoin.finality_confirmations {
-            Config {
-                bitcoin_finality_confirmations,
-                ..env_config
-            }
-        } else {
-            env_config
-        };
-
-    if let Some(monero_finality_confirmations) = asb_config.monero.finality_confirmations {
-        Config {
-            monero_finality_confirmations,
+    let env_config = match asb_config.bitcoin.fee_bump_after_blocks {
+        Some(0) => Config {
+            bitcoin_fee_bump_after_blocks: None,
             ..env_config
-        }
-    } else {
-        env_config
-    }
+        },
+        Some(blocks) => Config {
+            bitcoin_fee_bump_after_blocks: Some(blocks),
+            ..env_config
+        },
+        None => env_config,
+    };
+
+    env_config.with_confirmations(Confirmations {
+        bitcoin_finality: asb_config.bitcoin.finality_confirmations,
+        monero_finality: asb_config.monero.finality_confirmations,
+        monero_double_spend_safe: None,
+    })
 }
 
 mod monero_network {
@@ -171,4 +237,54 @@ mod tests {
 
         assert_eq!(interval, Duration::from_secs(10))
     }
+
+    #[test]
+    fn unset_confirmations_keep_the_network_defaults() {
+        let config = Mainnet::get_config()
+            .with_confirmations(Confirmations::default())
+            .unwrap();
+
+        assert_eq!(config, Mainnet::get_config());
+    }
+
+    #[test]
+    fn confirmations_override_the_network_defaults() {
+        let config = Regtest::get_config()
+            .with_confirmations(Confirmations {
+                bitcoin_finality: Some(3),
+                monero_finality: Some(15),
+                monero_double_spend_safe: Some(5),
+            })
+            .unwrap();
+
+        assert_eq!(config.bitcoin_finality_confirmations, 3);
+        assert_eq!(config.monero_finality_confirmations, 15);
+        assert_eq!(config.monero_double_spend_safe_confirmations, 5);
+    }
+
+    #[test]
+    fn unsafe_confirmations_are_rejected() {
+        let config = Testnet::get_config();
+
+        for confirmations in [
+            Confirmations {
+                bitcoin_finality: Some(0),
+                ..Default::default()
+            },
+            Confirmations {
+                bitcoin_finality: Some(12),
+                ..Default::default()
+            },
+            Confirmations {
+                monero_double_spend_safe: Some(0),
+                ..Default::default()
+            },
+            Confirmations {
+                monero_finality: Some(1),
+                ..Default::default()
+            },
+        ] {
+            assert!(config.with_confirmations(confirmations).is_err());
+        }
+    }
 }
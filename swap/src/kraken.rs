@@ -86,7 +86,7 @@ impl PriceUpdates {
         Ok(self.inner.borrow().clone())
     }
 
-    pub fn latest_update(&mut self) -> PriceUpdate {
+    pub fn latest_update(&self) -> PriceUpdate {
         self.inner.borrow().clone()
     }
 }
@@ -101,6 +101,114 @@ pub enum Error {
 
 type PriceUpdate = Result<wire::PriceUpdate, Error>;
 
+/// The XMR/BTC rate on Kraken on the given day (UTC), averaged over all trades of that day.
+///
+/// Kraken only hands out the last 720 daily candles, so this fails for days more than about
+/// two years back.
+/// See: https://docs.kraken.com/api/docs/rest-api/get-ohlc-data
+pub async fn daily_average_rate(day: time::Date) -> Result<bitcoin::Amount> {
+    let response = reqwest::Client::new()
+        .get(OHLC_URL)
+        .query(&[("pair", "XMRXBT"), ("interval", "1440")])
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .context("Failed to request the daily rates from Kraken")?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    ohlc::average_rate(&response, day)
+}
+
+const OHLC_URL: &str = "https://api.kraken.com/0/public/OHLC";
+
+/// Kraken REST API module for the OHLC (candlestick) data.
+mod ohlc {
+    use super::*;
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[derive(Debug, Deserialize)]
+    struct Response {
+        error: Vec<String>,
+        result: Option<HashMap<String, Value>>,
+    }
+
+    /// Time, open, high, low, close, volume weighted average, volume and number of trades
+    type Candle = (i64, String, String, String, String, String, String, u64);
+
+    pub fn average_rate(response: &str, day: time::Date) -> Result<bitcoin::Amount> {
+        let response: Response = serde_json::from_str(response)?;
+
+        if let Some(error) = response.error.first() {
+            anyhow::bail!("Kraken returned an error: {}", error);
+        }
+
+        // Besides the candles of the pair, the result contains the timestamp `last`
+        let candles = response
+            .result
+            .unwrap_or_default()
+            .into_values()
+            .find(Value::is_array)
+            .context("Kraken returned no candles")?;
+        let candles: Vec<Candle> = serde_json::from_value(candles)?;
+
+        let start_of_day = day.midnight().assume_utc().unix_timestamp();
+        let (_, _, _, _, _, vwap, _, _) = candles
+            .into_iter()
+            .find(|(time, ..)| *time == start_of_day)
+            .with_context(|| format!("Kraken has no rate for {}", day))?;
+
+        // The rate may have more decimals than a Bitcoin amount
+        let sats = (Decimal::from_str(&vwap)? * Decimal::from(100_000_000))
+            .round()
+            .to_u64()
+            .context("Rate is out of range")?;
+
+        Ok(bitcoin::Amount::from_sat(sats))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use time::Month;
+
+        fn date(month: Month, day: u8) -> time::Date {
+            time::Date::from_calendar_date(2024, month, day).unwrap()
+        }
+
+        const RESPONSE: &str = r#"{"error":[],"result":{"XXMRXXBT":[[1719705600,"0.0027000","0.0028000","0.0026000","0.0027500","0.002743215","1520.13",812],[1719792000,"0.0027500","0.0029000","0.0027100","0.0028900","0.0028","980.40",605]],"last":1719705600}}"#;
+
+        #[test]
+        fn finds_the_average_rate_of_the_day() {
+            assert_eq!(
+                average_rate(RESPONSE, date(Month::June, 30)).unwrap(),
+                bitcoin::Amount::from_sat(274_322)
+            );
+            assert_eq!(
+                average_rate(RESPONSE, date(Month::July, 1)).unwrap(),
+                bitcoin::Amount::from_sat(280_000)
+            );
+        }
+
+        #[test]
+        fn fails_for_days_without_a_candle() {
+            assert!(average_rate(RESPONSE, date(Month::July, 2)).is_err());
+        }
+
+        #[test]
+        fn fails_on_kraken_errors() {
+            let response = r#"{"error":["EQuery:Unknown asset pair"]}"#;
+
+            assert!(average_rate(response, date(Month::June, 30)).is_err());
+        }
+    }
+}
+
 /// Maps a [`connection::Error`] to a backoff error, effectively defining our
 /// retry strategy.
 fn to_backoff(e: connection::Error) -> backoff::Error<anyhow::Error> {
@@ -9,7 +9,7 @@ use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Context, Result};
 use monero::{Address, Network};
-pub use monero_sys::{Daemon, WalletHandle as Wallet};
+pub use monero_sys::{Daemon, SeedLanguage, WalletHandle as Wallet};
 use uuid::Uuid;
 
 use crate::cli::api::tauri_bindings::TauriHandle;
@@ -72,9 +72,11 @@ impl Wallets {
     ) -> Result<Self> {
         let main_wallet = Wallet::open_or_create(
             wallet_dir.join(&main_wallet_name).display().to_string(),
-            daemon.clone(),
+            vec![daemon.clone()],
             network,
+            SeedLanguage::default(),
             true,
+            None,
         )
         .await
         .context("Failed to open main wallet")?;
@@ -133,7 +135,8 @@ impl Wallets {
             spend_key,
             blockheight,
             false, // We don't sync the swap wallet, just import the transaction
-            self.daemon.clone(),
+            vec![self.daemon.clone()],
+            None,
         )
         .await
         .context(format!(
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{asb, bitcoin, cli};
 use libp2p::request_response::{self, ProtocolSupport};
@@ -38,6 +38,11 @@ pub struct BidQuote {
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     #[typeshare(serialized_as = "number")]
     pub max_quantity: bitcoin::Amount,
+    /// Unix timestamp (in seconds) until which the maker accepts swap setups based on this
+    /// quote. `None` if the maker does not announce a validity window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[typeshare(serialized_as = "Option<number>")]
+    pub expires_at: Option<u64>,
 }
 
 impl BidQuote {
@@ -46,7 +51,29 @@ impl BidQuote {
         price: bitcoin::Amount::ZERO,
         min_quantity: bitcoin::Amount::ZERO,
         max_quantity: bitcoin::Amount::ZERO,
+        expires_at: None,
     };
+
+    /// Set the validity window of the quote, starting now.
+    pub fn valid_for(self, validity: Duration) -> Self {
+        Self {
+            expires_at: Some(unix_timestamp() + validity.as_secs()),
+            ..self
+        }
+    }
+}
+
+/// Whether a quote with the given expiry can no longer be used for a swap setup. Quotes
+/// without a validity window never expire.
+pub fn is_expired(expires_at: Option<u64>) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at <= unix_timestamp())
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
 }
 
 #[derive(Clone, Copy, Debug, thiserror::Error)]
@@ -100,3 +127,32 @@ impl From<(PeerId, Message)> for cli::OutEvent {
     }
 }
 crate::impl_from_rr_event!(OutEvent, cli::OutEvent, PROTOCOL);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_of_older_makers_have_no_validity_window() {
+        let quote: BidQuote =
+            serde_json::from_str(r#"{"price":270000,"min_quantity":1000,"max_quantity":90000}"#)
+                .unwrap();
+
+        assert_eq!(quote.expires_at, None);
+        assert!(!is_expired(quote.expires_at));
+        assert!(!serde_json::to_string(&quote)
+            .unwrap()
+            .contains("expires_at"));
+    }
+
+    #[test]
+    fn quotes_expire_after_their_validity_window() {
+        let quote = BidQuote::ZERO.valid_for(Duration::from_secs(300));
+
+        assert!(!is_expired(quote.expires_at));
+        assert!(is_expired(
+            BidQuote::ZERO.valid_for(Duration::ZERO).expires_at
+        ));
+        assert!(is_expired(Some(0)));
+    }
+}
@@ -47,11 +47,32 @@ pub struct SpotPriceRequest {
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     pub btc: bitcoin::Amount,
     pub blockchain_network: BlockchainNetwork,
+    /// Expiry of the quote Bob based the swap on, see [`BidQuote::expires_at`]. Older versions
+    /// of Bob don't send it.
+    ///
+    /// [`BidQuote::expires_at`]: crate::network::quote::BidQuote::expires_at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_expires_at: Option<u64>,
+    /// The least Bob is willing to swap if Alice can't take all of `btc`. If not set Bob swaps
+    /// exactly `btc` or nothing.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::bitcoin::amount::serde::as_sat::opt"
+    )]
+    pub min_btc: Option<bitcoin::Amount>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SpotPriceResponse {
     Xmr(monero::Amount),
+    /// Alice only takes part of the Bitcoin Bob offered. Only sent if Bob told us the least he
+    /// is willing to swap.
+    PartialXmr {
+        #[serde(with = "::bitcoin::amount::serde::as_sat")]
+        btc: bitcoin::Amount,
+        xmr: monero::Amount,
+    },
     Error(SpotPriceError),
 }
 
@@ -78,6 +99,9 @@ pub enum SpotPriceError {
         cli: BlockchainNetwork,
         asb: BlockchainNetwork,
     },
+    /// The quote the swap setup is based on is no longer valid, Bob has to request a new one.
+    /// Only sent to Bobs that told us when their quote expires.
+    QuoteExpired,
     /// To be used for errors that cannot be explained on the CLI side (e.g.
     /// rate update problems on the seller side)
     Other,
@@ -1,8 +1,9 @@
+use crate::asb::volume_limits::VolumeReservation;
 use crate::asb::LatestRate;
-use crate::network::swap_setup;
 use crate::network::swap_setup::{
     protocol, BlockchainNetwork, SpotPriceError, SpotPriceRequest, SpotPriceResponse,
 };
+use crate::network::{quote, swap_setup};
 use crate::protocol::alice::{State0, State3};
 use crate::protocol::{Message0, Message2, Message4};
 use crate::{asb, bitcoin, env, monero};
@@ -25,12 +26,14 @@ use uuid::Uuid;
 #[allow(clippy::large_enum_variant)]
 pub enum OutEvent {
     Initiated {
+        peer_id: PeerId,
         send_wallet_snapshot: bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>,
     },
     Completed {
         peer_id: PeerId,
         swap_id: Uuid,
         state3: State3,
+        volume_reservation: VolumeReservation,
     },
     Error {
         peer_id: PeerId,
@@ -50,6 +53,11 @@ pub struct WalletSnapshot {
 
     redeem_fee: bitcoin::Amount,
     punish_fee: bitcoin::Amount,
+
+    /// What is left of the daily volume limits for the peer, see [`asb::VolumeLimits`].
+    remaining_volume: Option<bitcoin::Amount>,
+    /// Holds back the Bitcoin of this swap setup from the volume limits while it runs.
+    volume_reservation: VolumeReservation,
 }
 
 impl WalletSnapshot {
@@ -58,9 +66,14 @@ impl WalletSnapshot {
         monero_wallet: &monero::Wallets,
         external_redeem_address: &Option<bitcoin::Address>,
         transfer_amount: bitcoin::Amount,
+        remaining_volume: Option<bitcoin::Amount>,
+        volume_reservation: VolumeReservation,
     ) -> Result<Self> {
-        let unlocked_balance = monero_wallet.main_wallet().await.unlocked_balance().await;
-        let total_balance = monero_wallet.main_wallet().await.total_balance().await;
+        let (unlocked_balance, total_balance) = monero_wallet
+            .main_wallet()
+            .await
+            .call(|wallet| (wallet.unlocked_balance(), wallet.total_balance()))
+            .await;
 
         tracing::info!(%unlocked_balance, %total_balance, "Capturing monero wallet snapshot");
 
@@ -85,6 +98,8 @@ impl WalletSnapshot {
             punish_address,
             redeem_fee,
             punish_fee,
+            remaining_volume,
+            volume_reservation,
         })
     }
 }
@@ -93,18 +108,22 @@ impl From<OutEvent> for asb::OutEvent {
     fn from(event: OutEvent) -> Self {
         match event {
             OutEvent::Initiated {
+                peer_id,
                 send_wallet_snapshot,
             } => asb::OutEvent::SwapSetupInitiated {
+                peer_id,
                 send_wallet_snapshot,
             },
             OutEvent::Completed {
                 peer_id: bob_peer_id,
                 swap_id,
                 state3,
+                volume_reservation,
             } => asb::OutEvent::SwapSetupCompleted {
                 peer_id: bob_peer_id,
                 swap_id,
                 state3,
+                volume_reservation,
             },
             OutEvent::Error { peer_id, error } => asb::OutEvent::Failure {
                 peer: peer_id,
@@ -204,14 +223,16 @@ where
         match event {
             HandlerOutEvent::Initiated(send_wallet_snapshot) => {
                 self.events.push_back(OutEvent::Initiated {
+                    peer_id,
                     send_wallet_snapshot,
                 })
             }
-            HandlerOutEvent::Completed(Ok((swap_id, state3))) => {
+            HandlerOutEvent::Completed(Ok((swap_id, state3, volume_reservation))) => {
                 self.events.push_back(OutEvent::Completed {
                     peer_id,
                     swap_id,
                     state3,
+                    volume_reservation,
                 })
             }
             HandlerOutEvent::Completed(Err(error)) => {
@@ -234,7 +255,7 @@ where
     }
 }
 
-type InboundStream = BoxFuture<'static, Result<(Uuid, State3)>>;
+type InboundStream = BoxFuture<'static, Result<(Uuid, State3, VolumeReservation)>>;
 
 pub struct Handler<LR> {
     inbound_stream: OptionFuture<InboundStream>,
@@ -281,7 +302,7 @@ impl<LR> Handler<LR> {
 #[derive(Debug)]
 pub enum HandlerOutEvent {
     Initiated(bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>),
-    Completed(Result<(Uuid, State3)>),
+    Completed(Result<(Uuid, State3, VolumeReservation)>),
 }
 
 impl<LR> ConnectionHandler for Handler<LR>
@@ -356,7 +377,37 @@ where
                             });
                         }
 
-                        let btc = request.btc;
+                        if quote::is_expired(request.quote_expires_at) {
+                            return Err(Error::QuoteExpired);
+                        }
+
+                        let rate =
+                            latest_rate.map_err(|e| Error::LatestRateFetchFailed(Box::new(e)))?;
+
+                        let btc = match request.min_btc {
+                            Some(min_btc) => {
+                                let ask = rate.ask().map_err(Error::SellQuoteCalculationFailed)?;
+                                let affordable = wallet_snapshot
+                                    .unlocked_balance
+                                    .max_bitcoin_for_price(ask)
+                                    .unwrap_or(bitcoin::Amount::ZERO);
+
+                                // If we can't take all of it, the checks below tell Bob why
+                                partial_amount(
+                                    request.btc,
+                                    min_btc.max(min_buy),
+                                    [
+                                        Some(max_buy),
+                                        wallet_snapshot.remaining_volume,
+                                        Some(affordable),
+                                    ]
+                                    .into_iter()
+                                    .flatten(),
+                                )
+                                .unwrap_or(request.btc)
+                            }
+                            None => request.btc,
+                        };
 
                         if btc < min_buy {
                             return Err(Error::AmountBelowMinimum {
@@ -372,8 +423,15 @@ where
                             });
                         }
 
-                        let rate =
-                            latest_rate.map_err(|e| Error::LatestRateFetchFailed(Box::new(e)))?;
+                        if let Some(remaining) = wallet_snapshot.remaining_volume {
+                            if btc > remaining {
+                                return Err(Error::VolumeLimitReached {
+                                    remaining,
+                                    buy: btc,
+                                });
+                            }
+                        }
+
                         let xmr = rate
                             .sell_quote(btc)
                             .map_err(Error::SellQuoteCalculationFailed)?;
@@ -393,22 +451,22 @@ where
                             });
                         }
 
-                        Ok(xmr)
+                        Ok((btc, xmr))
                     };
 
                     let result = validate.await;
 
                     swap_setup::write_cbor_message(
                         &mut substream,
-                        SpotPriceResponse::from_result_ref(&result),
+                        SpotPriceResponse::from_result_ref(&result, request.btc),
                     )
                     .await
                     .context("Failed to write spot price response")?;
 
-                    let xmr = result?;
+                    let (btc, xmr) = result?;
 
                     let state0 = State0::new(
-                        request.btc,
+                        btc,
                         xmr,
                         env_config,
                         wallet_snapshot.redeem_address,
@@ -456,7 +514,8 @@ where
                         .await
                         .context("Failed to close substream after all messages were sent")?;
 
-                    Ok((swap_id, state3))
+                    // The reservation is only released once the swap is in the database
+                    Ok((swap_id, state3, wallet_snapshot.volume_reservation))
                 });
 
                 let max_seconds = self.negotiation_timeout.as_secs();
@@ -520,10 +579,29 @@ where
     }
 }
 
+/// How much of the Bitcoin Bob offered we take if he is fine with swapping less: as much as
+/// our limits allow. `None` if that is less than he is willing to swap.
+fn partial_amount(
+    requested: bitcoin::Amount,
+    min_acceptable: bitcoin::Amount,
+    limits: impl IntoIterator<Item = bitcoin::Amount>,
+) -> Option<bitcoin::Amount> {
+    let amount = limits.into_iter().fold(requested, bitcoin::Amount::min);
+
+    (amount >= min_acceptable).then_some(amount)
+}
+
 impl SpotPriceResponse {
-    pub fn from_result_ref(result: &Result<monero::Amount, Error>) -> Self {
+    pub fn from_result_ref(
+        result: &Result<(bitcoin::Amount, monero::Amount), Error>,
+        requested_btc: bitcoin::Amount,
+    ) -> Self {
         match result {
-            Ok(amount) => SpotPriceResponse::Xmr(*amount),
+            Ok((btc, xmr)) if *btc == requested_btc => SpotPriceResponse::Xmr(*xmr),
+            Ok((btc, xmr)) => SpotPriceResponse::PartialXmr {
+                btc: *btc,
+                xmr: *xmr,
+            },
             Err(error) => SpotPriceResponse::Error(error.to_error_response()),
         }
     }
@@ -543,6 +621,11 @@ pub enum Error {
         max: bitcoin::Amount,
         buy: bitcoin::Amount,
     },
+    #[error("Amount {buy} above the {remaining} left of the daily volume limits")]
+    VolumeLimitReached {
+        remaining: bitcoin::Amount,
+        buy: bitcoin::Amount,
+    },
     #[error("Unlocked balance ({balance}) too low to fulfill swapping {buy}")]
     BalanceTooLow {
         balance: monero::Amount,
@@ -557,6 +640,8 @@ pub enum Error {
         cli: BlockchainNetwork,
         asb: BlockchainNetwork,
     },
+    #[error("Swap setup is based on an expired quote")]
+    QuoteExpired,
 }
 
 impl Error {
@@ -571,6 +656,11 @@ impl Error {
                 max: *max,
                 buy: *buy,
             },
+            // Bob only knows about a maximum, the limits are not part of the protocol
+            Error::VolumeLimitReached { remaining, buy } => SpotPriceError::AmountAboveMaximum {
+                max: *remaining,
+                buy: *buy,
+            },
             Error::BalanceTooLow { buy, .. } => SpotPriceError::BalanceTooLow { buy: *buy },
             Error::BlockchainNetworkMismatch { cli, asb } => {
                 SpotPriceError::BlockchainNetworkMismatch {
@@ -578,9 +668,53 @@ impl Error {
                     asb: *asb,
                 }
             }
+            Error::QuoteExpired => SpotPriceError::QuoteExpired,
             Error::LatestRateFetchFailed(_) | Error::SellQuoteCalculationFailed(_) => {
                 SpotPriceError::Other
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc(sats: u64) -> bitcoin::Amount {
+        bitcoin::Amount::from_sat(sats)
+    }
+
+    #[test]
+    fn partial_amount_is_capped_by_the_lowest_limit() {
+        assert_eq!(
+            partial_amount(btc(100_000), btc(10_000), [btc(80_000), btc(50_000)]),
+            Some(btc(50_000))
+        );
+        assert_eq!(
+            partial_amount(btc(100_000), btc(10_000), [btc(200_000)]),
+            Some(btc(100_000))
+        );
+    }
+
+    #[test]
+    fn partial_amount_must_be_acceptable_to_bob() {
+        assert_eq!(
+            partial_amount(btc(100_000), btc(60_000), [btc(50_000)]),
+            None
+        );
+    }
+
+    #[test]
+    fn partial_amounts_are_announced_to_bob() {
+        let xmr = monero::Amount::from_piconero(1_000_000);
+
+        assert!(matches!(
+            SpotPriceResponse::from_result_ref(&Ok((btc(100_000), xmr)), btc(100_000)),
+            SpotPriceResponse::Xmr(_)
+        ));
+        assert!(matches!(
+            SpotPriceResponse::from_result_ref(&Ok((btc(50_000), xmr)), btc(100_000)),
+            SpotPriceResponse::PartialXmr { btc: partial, .. } if partial == btc(50_000)
+        ));
+    }
+}
@@ -140,6 +140,8 @@ pub struct NewSwap {
     pub tx_refund_fee: bitcoin::Amount,
     pub tx_cancel_fee: bitcoin::Amount,
     pub bitcoin_refund_address: bitcoin::Address,
+    pub quote_expires_at: Option<u64>,
+    pub min_btc: Option<bitcoin::Amount>,
 }
 
 #[derive(Debug)]
@@ -190,6 +192,8 @@ impl ConnectionHandler for Handler {
                                     bitcoin: env_config.bitcoin_network,
                                     monero: env_config.monero_network,
                                 },
+                                quote_expires_at: new_swap_request.quote_expires_at,
+                                min_btc: new_swap_request.min_btc,
                             },
                         )
                         .await
@@ -197,18 +201,21 @@ impl ConnectionHandler for Handler {
 
                         // Here we read the spot price response from Alice
                         // The outer ? checks if Alice responded with an error (SpotPriceError)
-                        let xmr = Result::from(
+                        // or offered an amount we are not willing to swap
+                        let (btc, xmr) = agreed_amounts(
                             // The inner ? is for the read_cbor_message function
                             // It will return an error if the deserialization fails
                             read_cbor_message::<SpotPriceResponse>(&mut substream)
                                 .await
                                 .context("Failed to read spot price response from Alice")?,
+                            new_swap_request.btc,
+                            new_swap_request.min_btc,
                         )?;
 
                         let state0 = State0::new(
                             new_swap_request.swap_id,
                             &mut rand::thread_rng(),
-                            new_swap_request.btc,
+                            btc,
                             xmr,
                             env_config.bitcoin_cancel_timelock,
                             env_config.bitcoin_punish_timelock,
@@ -258,7 +265,9 @@ impl ConnectionHandler for Handler {
 
                     result.map_err(|e: anyhow::Error| {
                         tracing::error!("Error occurred during swap setup protocol: {:#}", e);
-                        Error::Other
+
+                        // Keep the reason if Alice told us why she rejected the swap
+                        e.downcast::<Error>().unwrap_or(Error::Other)
                     })
                 });
 
@@ -319,12 +328,23 @@ impl ConnectionHandler for Handler {
     }
 }
 
-impl From<SpotPriceResponse> for Result<monero::Amount, Error> {
-    fn from(response: SpotPriceResponse) -> Self {
-        match response {
-            SpotPriceResponse::Xmr(amount) => Ok(amount),
-            SpotPriceResponse::Error(e) => Err(e.into()),
-        }
+/// The amounts of the swap as agreed with Alice. She can only lower the Bitcoin amount if we
+/// told her the least we are willing to swap.
+fn agreed_amounts(
+    response: SpotPriceResponse,
+    btc: bitcoin::Amount,
+    min_btc: Option<bitcoin::Amount>,
+) -> Result<(bitcoin::Amount, monero::Amount), Error> {
+    match response {
+        SpotPriceResponse::Xmr(xmr) => Ok((btc, xmr)),
+        SpotPriceResponse::PartialXmr {
+            btc: partial_btc,
+            xmr,
+        } => match min_btc {
+            Some(min_btc) if partial_btc >= min_btc && partial_btc <= btc => Ok((partial_btc, xmr)),
+            _ => Err(Error::UnacceptableAmount { buy: partial_btc }),
+        },
+        SpotPriceResponse::Error(e) => Err(e.into()),
     }
 }
 
@@ -351,6 +371,12 @@ pub enum Error {
         asb: BlockchainNetwork,
     },
 
+    #[error("Seller rejected the swap because the quote it was based on expired")]
+    QuoteExpired,
+
+    #[error("Seller offered to buy {buy} which is not within the amount we are willing to swap")]
+    UnacceptableAmount { buy: bitcoin::Amount },
+
     #[error("Failed to complete swap setup within {seconds}s")]
     Timeout { seconds: u64 },
 
@@ -374,7 +400,53 @@ impl From<SpotPriceError> for Error {
             SpotPriceError::BlockchainNetworkMismatch { cli, asb } => {
                 Error::BlockchainNetworkMismatch { cli, asb }
             }
+            SpotPriceError::QuoteExpired => Error::QuoteExpired,
             SpotPriceError::Other => Error::Other,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc(sats: u64) -> bitcoin::Amount {
+        bitcoin::Amount::from_sat(sats)
+    }
+
+    #[test]
+    fn partial_amount_is_only_accepted_within_our_range() {
+        let xmr = monero::Amount::from_piconero(1_000_000);
+        let partial = |sats| SpotPriceResponse::PartialXmr {
+            btc: btc(sats),
+            xmr,
+        };
+
+        assert_eq!(
+            agreed_amounts(partial(60_000), btc(100_000), Some(btc(50_000))),
+            Ok((btc(60_000), xmr))
+        );
+        assert_eq!(
+            agreed_amounts(partial(40_000), btc(100_000), Some(btc(50_000))),
+            Err(Error::UnacceptableAmount { buy: btc(40_000) })
+        );
+        assert_eq!(
+            agreed_amounts(partial(120_000), btc(100_000), Some(btc(50_000))),
+            Err(Error::UnacceptableAmount { buy: btc(120_000) })
+        );
+        assert_eq!(
+            agreed_amounts(partial(60_000), btc(100_000), None),
+            Err(Error::UnacceptableAmount { buy: btc(60_000) })
+        );
+    }
+
+    #[test]
+    fn requested_amount_is_kept_if_alice_takes_all_of_it() {
+        let xmr = monero::Amount::from_piconero(1_000_000);
+
+        assert_eq!(
+            agreed_amounts(SpotPriceResponse::Xmr(xmr), btc(100_000), None),
+            Ok((btc(100_000), xmr))
+        );
+    }
+}
@@ -26,6 +26,7 @@ pub fn asb<LR>(
     maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
     register_hidden_service: bool,
     num_intro_points: u8,
+    onion_only: bool,
 ) -> Result<(Swarm<asb::Behaviour<LR>>, Vec<Multiaddr>)>
 where
     LR: LatestRate + Send + 'static + Debug + Clone,
@@ -58,6 +59,7 @@ where
         maybe_tor_client,
         register_hidden_service,
         num_intro_points,
+        onion_only,
     )?;
 
     let swarm = SwarmBuilder::with_existing_identity(identity)
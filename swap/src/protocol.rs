@@ -13,6 +13,8 @@ use sha2::Sha256;
 use sigma_fun::ext::dl_secp256k1_ed25519_eq::{CrossCurveDLEQ, CrossCurveDLEQProof};
 use sigma_fun::HashTranscript;
 use std::convert::TryInto;
+use std::time::Duration;
+use typeshare::typeshare;
 use uuid::Uuid;
 
 pub mod alice;
@@ -136,6 +138,111 @@ impl TryInto<AliceState> for State {
     }
 }
 
+/// How a swap with a maker ended, from our point of view as the buyer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapOutcome {
+    /// We redeemed the Monero.
+    Completed,
+    /// We got our Bitcoin back.
+    Aborted,
+    /// The maker punished us.
+    Punished,
+}
+
+/// What we know about a maker from the swaps we did with it.
+#[typeshare]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerStats {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+    #[typeshare(serialized_as = "number")]
+    pub completed_count: u64,
+    #[typeshare(serialized_as = "number")]
+    pub aborted_count: u64,
+    #[typeshare(serialized_as = "number")]
+    pub punished_count: u64,
+    /// How long the maker took on average to lock the Monero after we locked the Bitcoin.
+    pub avg_lock_time_secs: Option<f64>,
+    /// When we last got a quote from the maker or swapped with it.
+    pub last_seen_at: Option<String>,
+}
+
+impl PeerStats {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            completed_count: 0,
+            aborted_count: 0,
+            punished_count: 0,
+            avg_lock_time_secs: None,
+            last_seen_at: None,
+        }
+    }
+
+    /// A maker is flaky if more of our swaps with it were refunded than completed.
+    pub fn is_flaky(&self) -> bool {
+        self.aborted_count > self.completed_count
+    }
+}
+
+/// How discovering makers at a rendezvous point went in the past.
+#[typeshare]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RendezvousPointHealth {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+    #[typeshare(serialized_as = "number")]
+    pub success_count: u64,
+    #[typeshare(serialized_as = "number")]
+    pub failure_count: u64,
+    /// Failures since the last time we discovered makers at the rendezvous point.
+    #[typeshare(serialized_as = "number")]
+    pub consecutive_failures: u64,
+    pub last_success_at: Option<String>,
+}
+
+impl RendezvousPointHealth {
+    /// After this many failures in a row, we only fall back to a rendezvous point once the
+    /// healthy ones failed as well.
+    const MAX_CONSECUTIVE_FAILURES: u64 = 3;
+
+    pub fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            success_count: 0,
+            failure_count: 0,
+            consecutive_failures: 0,
+            last_success_at: None,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures < Self::MAX_CONSECUTIVE_FAILURES
+    }
+}
+
+/// What checking the database for corruption found.
+#[typeshare]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseCheck {
+    /// The problems the storage engine found in its files, empty if they are intact
+    pub integrity_errors: Vec<String>,
+    /// Swaps with states but without the peer id of the counterparty, they can't be resumed
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub orphaned_swaps: Vec<Uuid>,
+    /// Swaps whose latest state can't be read
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub unreadable_swaps: Vec<Uuid>,
+}
+
+impl DatabaseCheck {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.orphaned_swaps.is_empty()
+            && self.unreadable_swaps.is_empty()
+    }
+}
+
 #[async_trait]
 pub trait Database {
     async fn insert_peer_id(&self, swap_id: Uuid, peer_id: PeerId) -> Result<()>;
@@ -154,6 +261,8 @@ pub trait Database {
     async fn insert_latest_state(&self, swap_id: Uuid, state: State) -> Result<()>;
     async fn get_state(&self, swap_id: Uuid) -> Result<State>;
     async fn get_states(&self, swap_id: Uuid) -> Result<Vec<State>>;
+    /// All states of the swap in the order we entered them, with the time we entered them at.
+    async fn get_states_with_timestamps(&self, swap_id: Uuid) -> Result<Vec<(State, String)>>;
     async fn all(&self) -> Result<Vec<(Uuid, State)>>;
     async fn insert_buffered_transfer_proof(
         &self,
@@ -164,4 +273,74 @@ pub trait Database {
         &self,
         swap_id: Uuid,
     ) -> Result<Option<monero::TransferProof>>;
+    async fn insert_bitcoin_subscription(
+        &self,
+        txid: bitcoin::Txid,
+        script: ::bitcoin::ScriptBuf,
+    ) -> Result<()>;
+    async fn remove_bitcoin_subscription(
+        &self,
+        txid: bitcoin::Txid,
+        script: ::bitcoin::ScriptBuf,
+    ) -> Result<()>;
+    async fn get_bitcoin_subscriptions(&self)
+        -> Result<Vec<(bitcoin::Txid, ::bitcoin::ScriptBuf)>>;
+    async fn insert_bitcoin_broadcast(
+        &self,
+        transaction: &bitcoin::Transaction,
+        kind: &str,
+    ) -> Result<()>;
+    async fn remove_bitcoin_broadcast(&self, txid: bitcoin::Txid) -> Result<()>;
+    /// Also marks the maker as seen in its [`PeerStats`] if it was online.
+    async fn record_maker_reachability(&self, peer_id: PeerId, online: bool) -> Result<()>;
+    /// How often the maker was online and unreachable when we looked for it.
+    async fn get_maker_reachability(&self, peer_id: PeerId) -> Result<(u64, u64)>;
+    async fn record_swap_outcome(&self, peer_id: PeerId, outcome: SwapOutcome) -> Result<()>;
+    /// Record how long the maker took to lock the Monero after we locked the Bitcoin.
+    async fn record_lock_time(&self, peer_id: PeerId, lock_time: Duration) -> Result<()>;
+    /// The stats of the maker, empty if we never swapped with it.
+    async fn get_peer_stats(&self, peer_id: PeerId) -> Result<PeerStats>;
+    async fn get_all_peer_stats(&self) -> Result<Vec<PeerStats>>;
+    /// Record the Bitcoin a swap locks with us, towards the volume limits of the maker.
+    /// Recording the same swap again has no effect.
+    async fn record_swap_volume(
+        &self,
+        swap_id: Uuid,
+        peer_id: PeerId,
+        btc_amount: bitcoin::Amount,
+    ) -> Result<()>;
+    /// Stop counting a swap that never locked its Bitcoin towards the volume limits.
+    async fn remove_swap_volume(&self, swap_id: Uuid) -> Result<()>;
+    /// The Bitcoin locked with us since `since`, by all peers or only by the given one.
+    ///
+    /// If several instances share the database, this includes the swaps of all of them.
+    async fn get_swap_volume_since(
+        &self,
+        peer_id: Option<PeerId>,
+        since: time::OffsetDateTime,
+    ) -> Result<bitcoin::Amount>;
+    /// Record whether we could discover makers at the rendezvous point.
+    async fn record_rendezvous_discovery(&self, peer_id: PeerId, success: bool) -> Result<()>;
+    /// The health of the rendezvous point, healthy if we never used it before.
+    async fn get_rendezvous_point_health(&self, peer_id: PeerId) -> Result<RendezvousPointHealth>;
+    /// Record the fee we paid to sweep the Monero of the swap to our receive addresses.
+    async fn insert_xmr_redeem_fee(&self, swap_id: Uuid, fee: monero::Amount) -> Result<()>;
+    /// The fee we paid to redeem the Monero, `None` if we didn't redeem it (yet).
+    async fn get_xmr_redeem_fee(&self, swap_id: Uuid) -> Result<Option<monero::Amount>>;
+    /// The queued transactions with their kind and the time they were queued at.
+    async fn get_bitcoin_broadcasts(
+        &self,
+    ) -> Result<Vec<(bitcoin::Transaction, String, time::OffsetDateTime)>>;
+    /// Remember what a Bitcoin address (ours or someone else's) is used for. Labeling an
+    /// address again replaces its label.
+    async fn insert_bitcoin_address_label(
+        &self,
+        address: &bitcoin::Address,
+        label: &str,
+        swap_id: Option<Uuid>,
+    ) -> Result<()>;
+    /// All Bitcoin address labels, oldest first.
+    async fn get_bitcoin_address_labels(&self) -> Result<Vec<bitcoin::wallet::AddressLabel>>;
+    /// Check the database for corruption and for swaps we could not recover.
+    async fn check_integrity(&self) -> Result<DatabaseCheck>;
 }
@@ -20,4 +20,5 @@ pub struct Swap {
     pub env_config: Config,
     pub swap_id: Uuid,
     pub db: Arc<dyn Database + Send + Sync>,
+    pub hooks: asb::SwapHooks,
 }
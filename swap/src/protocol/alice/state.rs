@@ -2,7 +2,7 @@ use crate::bitcoin::{
     current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, Transaction, TxCancel,
     TxEarlyRefund, TxPunish, TxRedeem, TxRefund, Txid,
 };
-use crate::env::Config;
+use crate::env::{Config, MONERO_SPENDABLE_CONFIRMATIONS};
 use crate::monero::wallet::{TransferRequest, WatchRequest};
 use crate::monero::BlockHeight;
 use crate::monero::TransferProof;
@@ -416,9 +416,9 @@ pub struct State3 {
     #[serde(with = "crate::bitcoin::address_serde")]
     refund_address: bitcoin::Address,
     #[serde(with = "crate::bitcoin::address_serde")]
-    redeem_address: bitcoin::Address,
+    pub redeem_address: bitcoin::Address,
     #[serde(with = "crate::bitcoin::address_serde")]
-    punish_address: bitcoin::Address,
+    pub punish_address: bitcoin::Address,
     pub tx_lock: bitcoin::TxLock,
     tx_punish_sig_bob: bitcoin::Signature,
     tx_cancel_sig_bob: bitcoin::Signature,
@@ -556,7 +556,9 @@ impl State3 {
 
     pub async fn submit_tx_cancel(&self, bitcoin_wallet: &bitcoin::Wallet) -> Result<Txid> {
         let transaction = self.signed_cancel_transaction()?;
-        let (tx_id, _) = bitcoin_wallet.broadcast(transaction, "cancel").await?;
+        let (tx_id, _) = bitcoin_wallet
+            .broadcast_with_retry(transaction, "cancel")
+            .await?;
         Ok(tx_id)
     }
 
@@ -569,13 +571,13 @@ impl State3 {
     ) -> Result<()> {
         let view_key = self.v;
 
-        // Ensure that the XMR to be refunded are spendable by awaiting 10 confirmations
+        // Ensure that the XMR to be refunded are spendable by awaiting enough confirmations
         // on the lock transaction.
         tracing::info!("Waiting for Monero lock transaction to be confirmed");
         let transfer_proof_2 = transfer_proof.clone();
         monero_wallet
             .wait_until_confirmed(
-                self.lock_xmr_watch_request(transfer_proof_2, 10),
+                self.lock_xmr_watch_request(transfer_proof_2, MONERO_SPENDABLE_CONFIRMATIONS),
                 Some(move |(confirmations, target_confirmations)| {
                     tracing::debug!(
                         %confirmations,
@@ -613,11 +615,21 @@ impl State3 {
         Ok(())
     }
 
-    pub async fn punish_btc(&self, bitcoin_wallet: &bitcoin::Wallet) -> Result<Txid> {
+    /// Publish the punish transaction and wait until it is final, bumping its fee if it is
+    /// still unconfirmed after `fee_bump_after_blocks` blocks.
+    pub async fn punish_btc(
+        &self,
+        bitcoin_wallet: &bitcoin::Wallet,
+        fee_bump_after_blocks: Option<u32>,
+    ) -> Result<Txid> {
         let signed_tx_punish = self.signed_punish_transaction()?;
 
-        let (txid, subscription) = bitcoin_wallet.broadcast(signed_tx_punish, "punish").await?;
-        subscription.wait_until_final().await?;
+        let (txid, subscription) = bitcoin_wallet
+            .broadcast_with_retry(signed_tx_punish, "punish")
+            .await?;
+        bitcoin_wallet
+            .wait_until_final_bumping_fee(txid, &subscription, fee_bump_after_blocks)
+            .await?;
 
         Ok(txid)
     }
@@ -4,6 +4,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::asb::hooks::StateTransition;
 use crate::asb::{EventLoopHandle, LatestRate};
 use crate::bitcoin::ExpiredTimelocks;
 use crate::common::retry;
@@ -36,9 +37,9 @@ where
     let mut current_state = swap.state;
 
     while !is_complete(&current_state) && !exit_early(&current_state) {
-        current_state = next_state(
+        let next_state = next_state(
             swap.swap_id,
-            current_state,
+            current_state.clone(),
             &mut swap.event_loop_handle,
             swap.bitcoin_wallet.as_ref(),
             swap.monero_wallet.clone(),
@@ -48,8 +49,36 @@ where
         .await?;
 
         swap.db
-            .insert_latest_state(swap.swap_id, current_state.clone().into())
+            .insert_latest_state(swap.swap_id, next_state.clone().into())
             .await?;
+
+        // The event loop records the volume when the swap setup completes. Recording it again
+        // once the Bitcoin is locked covers swaps where that failed. A swap that was aborted
+        // before the Bitcoin was locked gives its volume back.
+        match &next_state {
+            AliceState::BtcLockTransactionSeen { state3 } | AliceState::BtcLocked { state3 } => {
+                if let Err(error) = swap
+                    .db
+                    .record_swap_volume(swap.swap_id, swap.event_loop_handle.peer_id(), state3.btc)
+                    .await
+                {
+                    tracing::warn!("Failed to record the volume of the swap: {:#}", error);
+                }
+            }
+            AliceState::SafelyAborted => {
+                if let Err(error) = swap.db.remove_swap_volume(swap.swap_id).await {
+                    tracing::warn!("Failed to release the volume of the swap: {:#}", error);
+                }
+            }
+            _ => {}
+        }
+
+        swap.hooks.notify(StateTransition::new(
+            swap.swap_id,
+            &current_state,
+            &next_state,
+        ));
+        current_state = next_state;
     }
 
     Ok(current_state)
@@ -160,9 +189,8 @@ where
                         .address_and_amount(env_config.monero_network);
 
                     // Lock the Monero
-                    let receipt = monero_wallet
-                        .main_wallet()
-                        .await
+                    let main_wallet = monero_wallet.main_wallet().await;
+                    let receipt = main_wallet
                         .transfer(&address, amount)
                         .await
                         .map_err(|e| tracing::error!(err=%e, "Failed to lock Monero"))
@@ -174,6 +202,22 @@ where
                         )));
                     };
 
+                    tracing::info!(
+                        %swap_id,
+                        txid = %receipt.txid,
+                        fee = %receipt.details.fee,
+                        weight = receipt.details.weight,
+                        "Locked Monero"
+                    );
+
+                    // Attach the swap id to the transaction so it can be matched to the swap later
+                    if let Err(error) = main_wallet
+                        .set_tx_note(receipt.txid.clone(), format!("swap {}", swap_id))
+                        .await
+                    {
+                        tracing::warn!(%swap_id, ?error, "Failed to attach swap id to Monero lock transaction");
+                    }
+
                     Ok(Some((
                         monero_wallet_restore_blockheight,
                         TransferProof::new(
@@ -498,7 +542,14 @@ where
         AliceState::BtcRedeemTransactionPublished { state3, .. } => {
             let subscription = bitcoin_wallet.subscribe_to(state3.tx_redeem()).await;
 
-            match subscription.wait_until_final().await {
+            match bitcoin_wallet
+                .wait_until_final_bumping_fee(
+                    state3.tx_redeem().txid(),
+                    &subscription,
+                    env_config.bitcoin_fee_bump_after_blocks,
+                )
+                .await
+            {
                 Ok(_) => AliceState::BtcRedeemed,
                 Err(e) => {
                     bail!("The Bitcoin redeem transaction was seen in mempool, but waiting for finality timed out with {}. Manual investigation might be needed to ensure that the transaction was included.", e)
@@ -597,7 +648,9 @@ where
         } => {
             // TODO: We should retry indefinitely here until we find the refund transaction
             // TODO: If we crash while we are waiting for the punish_tx to be confirmed (punish_btc waits until confirmation), we will remain in this state forever because we will attempt to re-publish the punish transaction
-            let punish = state3.punish_btc(bitcoin_wallet).await;
+            let punish = state3
+                .punish_btc(bitcoin_wallet, env_config.bitcoin_fee_bump_after_blocks)
+                .await;
 
             match punish {
                 Ok(_) => AliceState::BtcPunished {
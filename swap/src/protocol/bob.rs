@@ -40,12 +40,18 @@ impl Swap {
         bitcoin_change_address: bitcoin::Address,
         btc_amount: bitcoin::Amount,
         tx_lock_fee: bitcoin::Amount,
+        quote_expires_at: Option<u64>,
+        min_btc_amount: Option<bitcoin::Amount>,
+        quote_price: Option<bitcoin::Amount>,
     ) -> Self {
         Self {
             state: BobState::Started {
                 btc_amount,
                 tx_lock_fee,
                 change_address: bitcoin_change_address,
+                quote_expires_at,
+                min_btc_amount,
+                quote_price,
             },
             event_loop_handle,
             db,
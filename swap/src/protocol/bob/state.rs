@@ -4,6 +4,7 @@ use crate::bitcoin::{
     self, current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, Transaction, TxCancel,
     TxLock, Txid, Wallet,
 };
+use crate::env::MONERO_SPENDABLE_CONFIRMATIONS;
 use crate::monero::wallet::WatchRequest;
 use crate::monero::{self, MoneroAddressPool, TxHash};
 use crate::monero::{monero_private_key, TransferProof};
@@ -30,6 +31,17 @@ pub enum BobState {
         tx_lock_fee: bitcoin::Amount,
         #[serde(with = "address_serde")]
         change_address: bitcoin::Address,
+        /// Expiry of the maker's quote the amount was determined with
+        #[serde(default)]
+        quote_expires_at: Option<u64>,
+        /// The least we swap if the maker can't take all of `btc_amount` anymore. `None` if
+        /// we swap exactly `btc_amount` or not at all.
+        #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+        min_btc_amount: Option<bitcoin::Amount>,
+        /// The price of the maker's quote the amount was determined with. We don't continue
+        /// at a higher price if the quote has to be renewed.
+        #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+        quote_price: Option<bitcoin::Amount>,
     },
     SwapSetupCompleted(State2),
     BtcLocked {
@@ -351,7 +363,7 @@ pub struct State2 {
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     tx_redeem_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
-    tx_punish_fee: bitcoin::Amount,
+    pub tx_punish_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     pub tx_refund_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
@@ -717,19 +729,20 @@ impl State5 {
             public_spend_key: S,
             public_view_key: self.v.public(),
             transfer_proof: self.lock_transfer_proof.clone(),
-            // To sweep the funds we need 10 full confirmations because
-            // Monero requires 10 on an UTXO before it can be spent.
-            confirmation_target: 10,
+            // To sweep the funds the lock output has to be spendable
+            confirmation_target: MONERO_SPENDABLE_CONFIRMATIONS,
             expected_amount: self.xmr.into(),
         }
     }
 
+    /// Sweep the Monero to the receive addresses. Returns the hashes of the sweep transactions
+    /// and the fee we paid for them.
     pub async fn redeem_xmr(
         &self,
         monero_wallet: &monero::Wallets,
         swap_id: Uuid,
         monero_receive_pool: MoneroAddressPool,
-    ) -> Result<Vec<TxHash>> {
+    ) -> Result<(Vec<TxHash>, monero::Amount)> {
         let (spend_key, view_key) = self.xmr_keys();
 
         tracing::info!(%swap_id, "Redeeming Monero from extracted keys");
@@ -755,20 +768,27 @@ impl State5 {
 
         tracing::debug!(%swap_id, receive_address=?monero_receive_pool, "Sweeping Monero to receive address");
 
-        let tx_hashes = wallet
+        let tx_receipts = wallet
             .sweep_multi(
                 &monero_receive_pool.addresses(),
                 &monero_receive_pool.percentages(),
             )
             .await
-            .context("Failed to redeem Monero")?
+            .context("Failed to redeem Monero")?;
+
+        // Every receipt carries the details of the whole sweep
+        let fee = tx_receipts
+            .first()
+            .map(|tx_receipt| monero::Amount::from(tx_receipt.details.fee))
+            .unwrap_or(monero::Amount::ZERO);
+        let tx_hashes: Vec<_> = tx_receipts
             .into_iter()
             .map(|tx_receipt| TxHash(tx_receipt.txid))
             .collect();
 
-        tracing::info!(%swap_id, txids=?tx_hashes, "Monero sweep completed");
+        tracing::info!(%swap_id, txids=?tx_hashes, %fee, "Monero sweep completed");
 
-        Ok(tx_hashes)
+        Ok((tx_hashes, fee))
     }
 }
 
@@ -850,7 +870,9 @@ impl State6 {
             .complete_as_bob(self.A, self.b.clone(), self.tx_cancel_sig_a.clone())
             .context("Failed to complete Bitcoin cancel transaction")?;
 
-        let (tx_id, subscription) = bitcoin_wallet.broadcast(transaction, "cancel").await?;
+        let (tx_id, subscription) = bitcoin_wallet
+            .broadcast_with_retry(transaction, "cancel")
+            .await?;
 
         Ok((tx_id, subscription))
     }
@@ -861,7 +883,9 @@ impl State6 {
     ) -> Result<bitcoin::Txid> {
         let signed_tx_refund = self.signed_refund_transaction()?;
         let signed_tx_refund_txid = signed_tx_refund.compute_txid();
-        bitcoin_wallet.broadcast(signed_tx_refund, "refund").await?;
+        bitcoin_wallet
+            .broadcast_with_retry(signed_tx_refund, "refund")
+            .await?;
 
         Ok(signed_tx_refund_txid)
     }
@@ -8,13 +8,14 @@ use crate::cli::EventLoopHandle;
 use crate::common::retry;
 use crate::monero::MoneroAddressPool;
 use crate::network::cooperative_xmr_redeem_after_punish::Response::{Fullfilled, Rejected};
-use crate::network::swap_setup::bob::NewSwap;
+use crate::network::quote;
+use crate::network::swap_setup::bob::{Error as SwapSetupError, NewSwap};
 use crate::protocol::bob::state::*;
-use crate::protocol::{bob, Database};
+use crate::protocol::{bob, Database, SwapOutcome};
 use crate::{bitcoin, env, monero};
 use anyhow::{bail, Context as AnyContext, Result};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::select;
 use uuid::Uuid;
 
@@ -57,6 +58,16 @@ pub fn is_run_at_most_once(state: &BobState) -> bool {
     matches!(state, BobState::BtcPunished { .. })
 }
 
+/// How a swap that reached the given state ended, if it has.
+fn swap_outcome(state: &BobState) -> Option<SwapOutcome> {
+    match state {
+        BobState::XmrRedeemed { .. } => Some(SwapOutcome::Completed),
+        BobState::BtcRefunded(..) | BobState::BtcEarlyRefunded(..) => Some(SwapOutcome::Aborted),
+        BobState::BtcPunished { .. } => Some(SwapOutcome::Punished),
+        _ => None,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn run(swap: bob::Swap) -> Result<BobState> {
     run_until(swap, is_complete).await
@@ -67,6 +78,9 @@ pub async fn run_until(
     is_target_state: fn(&BobState) -> bool,
 ) -> Result<BobState> {
     let mut current_state = swap.state.clone();
+    // We can only tell how long the maker took to lock the Monero if we saw the Bitcoin being
+    // locked in this run
+    let mut btc_locked_at = None;
 
     while !is_target_state(&current_state) {
         let next_state = next_state(
@@ -86,6 +100,21 @@ pub async fn run_until(
             .insert_latest_state(swap.id, next_state.clone().into())
             .await?;
 
+        if matches!(next_state, BobState::BtcLocked { .. }) {
+            btc_locked_at = Some(Instant::now());
+        }
+        if let Err(error) = record_peer_stats(
+            swap.db.as_ref(),
+            swap.id,
+            &current_state,
+            &next_state,
+            btc_locked_at,
+        )
+        .await
+        {
+            tracing::warn!(%error, "Failed to record the stats of the maker");
+        }
+
         if is_run_at_most_once(&current_state) && next_state == current_state {
             break;
         }
@@ -96,6 +125,87 @@ pub async fn run_until(
     Ok(current_state)
 }
 
+async fn record_peer_stats(
+    db: &(dyn Database + Send + Sync),
+    swap_id: Uuid,
+    current_state: &BobState,
+    next_state: &BobState,
+    btc_locked_at: Option<Instant>,
+) -> Result<()> {
+    if let (BobState::BtcLocked { .. }, BobState::XmrLockProofReceived { .. }, Some(locked_at)) =
+        (current_state, next_state, btc_locked_at)
+    {
+        let peer_id = db.get_peer_id(swap_id).await?;
+        db.record_lock_time(peer_id, locked_at.elapsed()).await?;
+    }
+
+    match swap_outcome(next_state) {
+        Some(outcome) if swap_outcome(current_state) != Some(outcome) => {
+            let peer_id = db.get_peer_id(swap_id).await?;
+            db.record_swap_outcome(peer_id, outcome).await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The Monero is ours at this point, failing to record what we paid for it must not fail the
+/// swap.
+async fn record_xmr_redeem_fee(
+    db: &(dyn Database + Send + Sync),
+    swap_id: Uuid,
+    fee: monero::Amount,
+) {
+    if let Err(error) = db.insert_xmr_redeem_fee(swap_id, fee).await {
+        tracing::warn!(%error, "Failed to record the fee of redeeming the Monero");
+    }
+}
+
+/// Ask the maker for a new quote because the one we determined the swap amount with expired.
+///
+/// The user only agreed to the price of the original quote, so we return `None` if the maker
+/// asks for a higher one now (or we don't know the original price) and the swap has to be
+/// aborted. The amount has to be within the limits of the new quote, or at least the part
+/// of it we are willing to swap.
+async fn request_fresh_quote(
+    swap_id: Uuid,
+    event_loop_handle: &mut EventLoopHandle,
+    event_emitter: &Option<TauriHandle>,
+    btc_amount: bitcoin::Amount,
+    min_btc_amount: Option<bitcoin::Amount>,
+    quote_price: Option<bitcoin::Amount>,
+) -> Result<Option<quote::BidQuote>> {
+    let quote = event_loop_handle
+        .request_quote()
+        .await
+        .context("Failed to request a fresh quote")?;
+
+    if quote_price.is_none_or(|quote_price| quote.price > quote_price) {
+        tracing::warn!(
+            new_price = %quote.price,
+            original_price = ?quote_price,
+            "The fresh quote has a higher price than the swap was started with (or we don't know that one), aborting the swap"
+        );
+        return Ok(None);
+    }
+
+    if btc_amount < quote.min_quantity || min_btc_amount.unwrap_or(btc_amount) > quote.max_quantity
+    {
+        bail!(
+            "The maker no longer accepts swapping {} (minimum {}, maximum {})",
+            btc_amount,
+            quote.min_quantity,
+            quote.max_quantity
+        );
+    }
+
+    tracing::info!(price = %quote.price, "Received a fresh quote");
+    event_emitter.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::ReceivedQuote(quote));
+
+    Ok(Some(quote))
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn next_state(
     swap_id: Uuid,
@@ -115,6 +225,9 @@ async fn next_state(
             btc_amount,
             change_address,
             tx_lock_fee,
+            quote_expires_at,
+            min_btc_amount,
+            quote_price,
         } => {
             let tx_refund_fee = bitcoin_wallet
                 .estimate_fee(TxRefund::weight(), Some(btc_amount))
@@ -123,6 +236,28 @@ async fn next_state(
                 .estimate_fee(TxCancel::weight(), Some(btc_amount))
                 .await?;
 
+            // Waiting for the deposit or resuming the swap later can take longer than the
+            // maker honours its quote for
+            let quote_expires_at = if quote::is_expired(quote_expires_at) {
+                tracing::info!("The quote we determined the swap amount with expired");
+                let Some(quote) = request_fresh_quote(
+                    swap_id,
+                    event_loop_handle,
+                    &event_emitter,
+                    btc_amount,
+                    min_btc_amount,
+                    quote_price,
+                )
+                .await?
+                else {
+                    return Ok(BobState::SafelyAborted);
+                };
+
+                quote.expires_at
+            } else {
+                quote_expires_at
+            };
+
             // Emit an event to tauri that we are negotiating with the maker to lock the Bitcoin
             event_emitter.emit_swap_progress_event(
                 swap_id,
@@ -133,16 +268,49 @@ async fn next_state(
                 },
             );
 
-            let state2 = event_loop_handle
-                .setup_swap(NewSwap {
-                    swap_id,
-                    btc: btc_amount,
-                    tx_lock_fee,
-                    tx_refund_fee,
-                    tx_cancel_fee,
-                    bitcoin_refund_address: change_address,
-                })
-                .await?;
+            let new_swap = |quote_expires_at| NewSwap {
+                swap_id,
+                btc: btc_amount,
+                tx_lock_fee,
+                tx_refund_fee,
+                tx_cancel_fee,
+                bitcoin_refund_address: change_address.clone(),
+                quote_expires_at,
+                min_btc: min_btc_amount,
+            };
+
+            let state2 = match event_loop_handle
+                .setup_swap(new_swap(quote_expires_at))
+                .await
+            {
+                // The clock of the maker might be ahead of ours, we negotiate once more
+                Err(error)
+                    if matches!(
+                        error.downcast_ref::<SwapSetupError>(),
+                        Some(SwapSetupError::QuoteExpired)
+                    ) =>
+                {
+                    tracing::info!("Maker rejected the swap setup because our quote expired");
+
+                    let Some(quote) = request_fresh_quote(
+                        swap_id,
+                        event_loop_handle,
+                        &event_emitter,
+                        btc_amount,
+                        min_btc_amount,
+                        quote_price,
+                    )
+                    .await?
+                    else {
+                        return Ok(BobState::SafelyAborted);
+                    };
+
+                    event_loop_handle
+                        .setup_swap(new_swap(quote.expires_at))
+                        .await?
+                }
+                result => result?,
+            };
 
             tracing::info!(%swap_id, "Starting new swap");
 
@@ -285,6 +453,10 @@ async fn next_state(
                 });
             }
 
+            // When we run another swap with the same seller concurrently, Alice may send our
+            // transfer proof over its connection. Its event loop then buffers it for us.
+            let transfer_proof_buffered = event_loop_handle.transfer_proof_buffered(swap_id);
+
             // Wait for either Alice to send the XMR transfer proof or until we can cancel the swap
             let transfer_proof_watcher = event_loop_handle.recv_transfer_proof();
             let cancel_timelock_expires = tx_lock_status.wait_until(|status| {
@@ -338,6 +510,20 @@ async fn next_state(
                         monero_wallet_restore_blockheight
                     }
                 },
+                _ = transfer_proof_buffered => {
+                    let transfer_proof = db
+                        .get_buffered_transfer_proof(swap_id)
+                        .await
+                        .context("Failed to get buffered transfer proof")?
+                        .context("Transfer proof was buffered but is missing from the database")?;
+                    tracing::debug!(txid = %transfer_proof.tx_hash(), "Found buffered transfer proof");
+
+                    BobState::XmrLockProofReceived {
+                        state: state3,
+                        lock_transfer_proof: transfer_proof,
+                        monero_wallet_restore_blockheight
+                    }
+                },
                 // Wait for the cancel timelock to expire
                 result = cancel_timelock_expires => {
                     result?;
@@ -575,7 +761,7 @@ async fn next_state(
             event_emitter
                 .emit_swap_progress_event(swap_id, TauriSwapProgressEvent::RedeemingMonero);
 
-            let xmr_redeem_txids = retry(
+            let (xmr_redeem_txids, xmr_redeem_fee) = retry(
                 "Redeeming Monero",
                 || async {
                     state
@@ -589,6 +775,8 @@ async fn next_state(
             .await
             .context("Failed to redeem Monero")?;
 
+            record_xmr_redeem_fee(db.as_ref(), swap_id, xmr_redeem_fee).await;
+
             event_emitter.emit_swap_progress_event(
                 swap_id,
                 TauriSwapProgressEvent::XmrRedeemInMempool {
@@ -828,7 +1016,9 @@ async fn next_state(
                     .await
                     .context("Failed to redeem Monero")
                     {
-                        Ok(xmr_redeem_txids) => {
+                        Ok((xmr_redeem_txids, xmr_redeem_fee)) => {
+                            record_xmr_redeem_fee(db.as_ref(), swap_id, xmr_redeem_fee).await;
+
                             event_emitter.emit_swap_progress_event(
                                 swap_id,
                                 TauriSwapProgressEvent::XmrRedeemInMempool {
@@ -4,6 +4,8 @@ use anyhow::{Context, Result};
 use bitcoin::hashes::{sha256, Hash, HashEngine};
 use bitcoin::secp256k1::constants::SECRET_KEY_SIZE;
 use bitcoin::secp256k1::{self, SecretKey};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use libp2p::identity;
 use pem::{encode, Pem};
 use rand::prelude::*;
@@ -15,6 +17,17 @@ use std::path::{Path, PathBuf};
 
 pub const SEED_LENGTH: usize = 32;
 
+const PEM_TAG: &str = "SEED";
+const ENCRYPTED_PEM_TAG: &str = "ENCRYPTED SEED";
+
+/// Rounds of PBKDF2-HMAC-SHA256 to derive the key that encrypts the seed from the password.
+/// The rounds are stored alongside the encrypted seed, so this can be raised later on.
+const PBKDF2_ROUNDS: u32 = 600_000;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const ENCRYPTED_SEED_LENGTH: usize = 4 + SALT_LENGTH + NONCE_LENGTH + SEED_LENGTH + TAG_LENGTH;
+
 #[derive(Clone, Eq, PartialEq)]
 pub struct Seed([u8; SEED_LENGTH]);
 
@@ -60,22 +73,70 @@ impl Seed {
         identity::Keypair::ed25519_from_bytes(bytes).expect("we always pass 32 bytes")
     }
 
+    /// Key for the encryption of the swap states in the database, see
+    /// [`crate::database::StateEncryption`].
+    pub fn derive_database_encryption_key(&self) -> [u8; 32] {
+        self.derive(b"DATABASE")
+            .derive(b"STATE_ENCRYPTION_KEY")
+            .bytes()
+    }
+
     pub fn from_file_or_generate(data_dir: &Path) -> Result<Self, Error> {
-        let file_path_buf = data_dir.join("seed.pem");
-        let file_path = Path::new(&file_path_buf);
+        Self::from_file_or_generate_with_password(data_dir, None)
+    }
+
+    /// Like [`Seed::from_file_or_generate`], but the seed file may be encrypted with a password.
+    ///
+    /// If a password is given, a newly generated seed is written encrypted and an existing
+    /// unencrypted seed file is encrypted in place. Without a password, an encrypted seed file
+    /// fails to load with [`Error::PasswordRequired`].
+    pub fn from_file_or_generate_with_password(
+        data_dir: &Path,
+        password: Option<&str>,
+    ) -> Result<Self, Error> {
+        let file_path = data_dir.join("seed.pem");
 
         if file_path.exists() {
-            return Self::from_file(file_path);
+            let Some(password) = password else {
+                return Self::from_file(file_path);
+            };
+
+            let pem = Self::read_pem(&file_path)?;
+            if pem.tag() == ENCRYPTED_PEM_TAG {
+                return Self::from_encrypted_pem(pem, password);
+            }
+
+            let seed = Self::from_pem(pem)?;
+
+            tracing::info!("Encrypting seed file at {}", file_path.display());
+            seed.write_encrypted_to(file_path, password)?;
+
+            return Ok(seed);
         }
 
         tracing::debug!("No seed file found, creating at {}", file_path.display());
 
         let random_seed = Seed::random()?;
-        random_seed.write_to(file_path.to_path_buf())?;
+        match password {
+            Some(password) => random_seed.write_encrypted_to(file_path, password)?,
+            None => random_seed.write_to(file_path)?,
+        }
 
         Ok(random_seed)
     }
 
+    /// Whether the seed in the data directory is protected by a password, which has to be
+    /// given to unlock it. `false` if there is no seed yet.
+    pub fn is_encrypted(data_dir: &Path) -> Result<bool, Error> {
+        let file_path = data_dir.join("seed.pem");
+
+        if !file_path.exists() {
+            return Ok(false);
+        }
+
+        Ok(Self::read_pem(&file_path)?.tag() == ENCRYPTED_PEM_TAG)
+    }
+
     /// Derive a new seed using the given scope.
     ///
     /// This function is purposely kept private because it is only a helper
@@ -100,13 +161,21 @@ impl Seed {
     where
         D: AsRef<OsStr>,
     {
-        let file = Path::new(&seed_file);
+        let pem = Self::read_pem(Path::new(&seed_file))?;
+        if pem.tag() == ENCRYPTED_PEM_TAG {
+            return Err(Error::PasswordRequired);
+        }
+
+        Self::from_pem(pem)
+    }
+
+    fn read_pem(file: &Path) -> Result<Pem, Error> {
         let contents = fs::read_to_string(file)?;
         let pem = pem::parse(contents)?;
 
         tracing::debug!("Reading in seed from {}", file.display());
 
-        Self::from_pem(pem)
+        Ok(pem)
     }
 
     fn from_pem(pem: pem::Pem) -> Result<Self, Error> {
@@ -123,19 +192,75 @@ impl Seed {
         }
     }
 
-    fn write_to(&self, seed_file: PathBuf) -> Result<(), Error> {
-        ensure_directory_exists(&seed_file)?;
+    fn from_encrypted_pem(pem: pem::Pem, password: &str) -> Result<Self, Error> {
+        let contents = pem.contents();
+        if contents.len() != ENCRYPTED_SEED_LENGTH {
+            return Err(Error::IncorrectEncryptedLength(contents.len()));
+        }
 
-        let data = self.bytes();
-        let pem = Pem::new("SEED", data);
+        let (rounds, rest) = contents.split_at(4);
+        let (salt, rest) = rest.split_at(SALT_LENGTH);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+        let rounds = u32::from_be_bytes(rounds.try_into().expect("we split off 4 bytes"));
 
-        let pem_string = encode(&pem);
+        let plaintext = password_cipher(password, salt, rounds)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::WrongPassword)?;
 
-        let mut file = File::create(seed_file)?;
-        file.write_all(pem_string.as_bytes())?;
+        Self::from_pem(Pem::new(PEM_TAG, plaintext))
+    }
 
-        Ok(())
+    fn encrypt(&self, password: &str, rounds: u32) -> Pem {
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = password_cipher(password, &salt, rounds)
+            .encrypt(Nonce::from_slice(&nonce), self.bytes().as_slice())
+            .expect("encrypting 32 bytes never fails");
+
+        let contents = [
+            &rounds.to_be_bytes()[..],
+            &salt[..],
+            &nonce[..],
+            &ciphertext[..],
+        ]
+        .concat();
+
+        Pem::new(ENCRYPTED_PEM_TAG, contents)
     }
+
+    fn write_to(&self, seed_file: PathBuf) -> Result<(), Error> {
+        write_pem(seed_file, Pem::new(PEM_TAG, self.bytes()))
+    }
+
+    fn write_encrypted_to(&self, seed_file: PathBuf, password: &str) -> Result<(), Error> {
+        write_pem(seed_file, self.encrypt(password, PBKDF2_ROUNDS))
+    }
+}
+
+/// Write through a temporary file, so we never end up with a half-written seed file when
+/// encrypting an existing one.
+fn write_pem(seed_file: PathBuf, pem: Pem) -> Result<(), Error> {
+    ensure_directory_exists(&seed_file)?;
+
+    let pem_string = encode(&pem);
+    let temp_file = seed_file.with_extension("pem.tmp");
+
+    let mut file = File::create(&temp_file)?;
+    file.write_all(pem_string.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(temp_file, seed_file)?;
+
+    Ok(())
+}
+
+fn password_cipher(password: &str, salt: &[u8], rounds: u32) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), salt, rounds, &mut key);
+
+    ChaCha20Poly1305::new(&key.into())
 }
 
 impl fmt::Debug for Seed {
@@ -170,6 +295,12 @@ pub enum Error {
     Rand(#[from] rand::Error),
     #[error("no default path")]
     NoDefaultPath,
+    #[error("expected {ENCRYPTED_SEED_LENGTH} bytes of encrypted seed, got {0} bytes")]
+    IncorrectEncryptedLength(usize),
+    #[error("the seed is encrypted, a password is required to unlock it")]
+    PasswordRequired,
+    #[error("wrong password for the seed")]
+    WrongPassword,
 }
 
 #[cfg(test)]
@@ -257,4 +388,48 @@ dWWSQ0nRGt2hOPDO+35NKhQEjBQxPh/v7n0CAwEAAQJBAOGaBAyuw0ICyENy5NsO
         let rinsed = Seed::from_file(tmpfile).expect("Read from temp file");
         assert_eq!(seed.0, rinsed.0);
     }
+
+    #[test]
+    fn encrypted_seed_round_trip() {
+        let seed = Seed::random().unwrap();
+
+        let pem = seed.encrypt("correct horse battery staple", 1_000);
+        assert_eq!(pem.tag(), ENCRYPTED_PEM_TAG);
+        assert_eq!(pem.contents().len(), ENCRYPTED_SEED_LENGTH);
+
+        let decrypted = Seed::from_encrypted_pem(pem, "correct horse battery staple").unwrap();
+        assert_eq!(seed, decrypted);
+    }
+
+    #[test]
+    fn encrypted_seed_fails_with_wrong_password() {
+        let seed = Seed::random().unwrap();
+        let pem = seed.encrypt("correct horse battery staple", 1_000);
+
+        match Seed::from_encrypted_pem(pem, "wrong password") {
+            Err(Error::WrongPassword) => {} // pass
+            other => panic!("should fail with WrongPassword, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn encrypted_seed_file_requires_password() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let seed = Seed::random().unwrap();
+        write_pem(
+            data_dir.path().join("seed.pem"),
+            seed.encrypt("password", 1_000),
+        )
+        .unwrap();
+
+        assert!(Seed::is_encrypted(data_dir.path()).unwrap());
+        assert!(matches!(
+            Seed::from_file_or_generate(data_dir.path()),
+            Err(Error::PasswordRequired)
+        ));
+        assert_eq!(
+            Seed::from_file_or_generate_with_password(data_dir.path(), Some("password")).unwrap(),
+            seed
+        );
+    }
 }
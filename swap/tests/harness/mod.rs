@@ -263,6 +263,7 @@ async fn start_alice(
         None,
         false,
         1,
+        false,
     )
     .unwrap();
     swarm.listen_on(listen_address).unwrap();
@@ -277,6 +278,9 @@ async fn start_alice(
         min_buy,
         max_buy,
         None,
+        asb::SwapHooks::default(),
+        asb::TradingSwitch::default(),
+        asb::VolumeLimits::default(),
     )
     .unwrap();
 
@@ -305,6 +309,7 @@ async fn init_test_wallets(
     let monero_daemon = Daemon {
         address: format!("http://127.0.0.1:{}", monerod_port),
         ssl: false,
+        ..Default::default()
     };
 
     let wallets = Wallets::new(
@@ -538,6 +543,9 @@ impl BobParams {
             self.bitcoin_wallet.new_address().await?,
             btc_amount,
             bitcoin::Amount::from_sat(1000), // Fixed fee of 1000 satoshis for now
+            None,
+            None,
+            None,
         );
 
         Ok((swap, event_loop))
@@ -559,7 +567,13 @@ impl BobParams {
         let mut swarm = swarm::cli(identity.clone(), None, behaviour).await?;
         swarm.add_peer_address(self.alice_peer_id, self.alice_address.clone());
 
-        cli::EventLoop::new(swap_id, swarm, self.alice_peer_id, db.clone())
+        cli::EventLoop::new(
+            swap_id,
+            swarm,
+            self.alice_peer_id,
+            db.clone(),
+            Default::default(),
+        )
     }
 }
 